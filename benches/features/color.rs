@@ -1,10 +1,33 @@
-use criterion::{criterion_group, Criterion};
+use criterion::{criterion_group, BatchSize, Criterion};
 use csslsrs::service::LanguageService;
 use lsp_types::{TextDocumentItem, Uri};
 use std::{hint::black_box, str::FromStr};
 
+// A component of a typical framework stylesheet, repeated to get a large document
+static COMPONENT: &str = r#"
+.btn-primary {
+	color: #fff;
+	background-color: rgb(13, 110, 253);
+	border: 1px solid #0d6efd;
+	box-shadow: 0 0 0 0.25rem rgba(49, 132, 253, 0.5);
+}
+
+.btn-primary:hover {
+	background: linear-gradient(to bottom, hsl(216, 98%, 52%), #0b5ed7);
+	border-color: darkblue;
+}
+
+@media (prefers-color-scheme: dark) {
+	.card {
+		color: white;
+		background-color: hwb(210 10% 85%);
+		outline: 2px dashed orange;
+	}
+}
+"#;
+
 fn get_colors_benchmark(c: &mut Criterion) {
-    let mut ls = LanguageService::default();
+    let ls = LanguageService::default();
 
     let document = TextDocumentItem {
         uri: Uri::from_str("file:///test.css").unwrap(),
@@ -18,4 +41,26 @@ fn get_colors_benchmark(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, get_colors_benchmark);
+fn get_colors_large_document_benchmark(c: &mut Criterion) {
+    let document = TextDocumentItem {
+        uri: Uri::from_str("file:///large.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 0,
+        text: COMPONENT.repeat(1000),
+    };
+
+    // A new LanguageService per iteration, so that colors are not cached
+    c.bench_function("get_document_colors_large_document", |b| {
+        b.iter_batched(
+            LanguageService::default,
+            |ls| ls.get_document_colors(black_box(document.clone())),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(
+    benches,
+    get_colors_benchmark,
+    get_colors_large_document_benchmark
+);
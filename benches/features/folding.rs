@@ -39,7 +39,7 @@ h4 {
 "#;
 
 fn get_folding_ranges_benchmark(c: &mut Criterion) {
-    let mut ls = LanguageService::default();
+    let ls = LanguageService::default();
 
     let document = TextDocumentItem {
         uri: Uri::from_str("file:///test.css").unwrap(),
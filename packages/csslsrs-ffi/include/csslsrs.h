@@ -0,0 +1,62 @@
+/*
+ * The C API of csslsrs, a CSS language service. See `src/lib.rs` for the conventions of the API.
+ *
+ * Strings are NUL-terminated UTF-8, and LSP objects are passed as their JSON. Every string returned by the API is
+ * owned by the caller and must be freed with `csslsrs_string_free`. Panics don't unwind into the caller: a function
+ * that panics fails with the message of the panic, after which the service should be freed.
+ */
+
+#ifndef CSSLSRS_H
+#define CSSLSRS_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A LanguageService keeping the documents opened in it. */
+typedef struct CsslsrsService CsslsrsService;
+
+/*
+ * Create a LanguageService. `position_encoding` is "utf-8", "utf-16" or "utf-32", or NULL for "utf-16" as in the LSP.
+ * Returns NULL if the encoding is unknown.
+ */
+CsslsrsService *csslsrs_service_new(const char *position_encoding);
+
+/* Free a LanguageService and every document opened in it. */
+void csslsrs_service_free(CsslsrsService *service);
+
+/*
+ * The following functions return NULL on success, or an error message.
+ */
+
+/* Open a document, replacing it if it was already open. */
+char *csslsrs_open_document(const CsslsrsService *service, const char *uri, const char *language_id, int32_t version,
+                            const char *text);
+
+/* Apply changes to an open document, given the JSON of an array of `TextDocumentContentChangeEvent`s. */
+char *csslsrs_update_document(const CsslsrsService *service, const char *uri, int32_t version,
+                              const char *content_changes);
+
+/* Close a document, removing it from the service. */
+char *csslsrs_close_document(const CsslsrsService *service, const char *uri);
+
+/* Replace every setting of the service at once, given the JSON of its settings. */
+char *csslsrs_set_configuration(const CsslsrsService *service, const char *settings);
+
+/*
+ * Request a feature by its LSP method, e.g. "textDocument/documentColor", given the JSON of its params.
+ * Returns the JSON of either `{ "result": ... }` or `{ "error": { "code": ..., "message": ... } }`, the code being a
+ * JSON-RPC error code, e.g. -32601 for methods that aren't handled, or -32603 for requests that panic.
+ */
+char *csslsrs_request(const CsslsrsService *service, const char *method, const char *params);
+
+/* Free a string returned by the API. Does nothing if `string` is NULL. */
+void csslsrs_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CSSLSRS_H */
@@ -0,0 +1,317 @@
+//! A C API of csslsrs, for editors that are neither written in Rust nor in JS, e.g. a C++ or Swift editor core. Its
+//! declarations are in `include/csslsrs.h`.
+//!
+//! Strings are passed as NUL-terminated UTF-8. LSP objects, e.g. the params of a request and its result, are passed as
+//! their JSON, and features are requested by their LSP method, so that new features don't change the API. Requests are
+//! routed with the `JsonRpcAdapter` of csslsrs, and fail with the same JSON-RPC error codes.
+//!
+//! Strings returned by the API are owned by the caller, and must be freed with `csslsrs_string_free`.
+//!
+//! Panics don't unwind into the caller, which would abort it: a function that panics fails as if it got invalid
+//! arguments, with the message of the panic as its error. The service may be left in an inconsistent state though, so
+//! it is better to free it and create a new one.
+
+use std::{
+    any::Any,
+    ffi::{c_char, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use csslsrs::{
+    converters::PositionEncoding,
+    json_rpc::{error_codes, JsonRpcAdapter, ResponseError},
+    service::LanguageService,
+};
+use serde_json::{json, Value};
+
+/// A LanguageService keeping the documents opened in it.
+pub struct CsslsrsService {
+    adapter: JsonRpcAdapter,
+}
+
+fn to_c_string(string: String) -> *mut c_char {
+    // Strings built from JSON or error messages have no interior NUL
+    CString::new(string).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+fn error_string(error: ResponseError) -> *mut c_char {
+    to_c_string(error.message)
+}
+
+/// The message of a panic, from the payload given to `panic!`.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown error");
+    format!("panicked: {message}")
+}
+
+/// Call `function`, or `on_panic` with the message of its panic if it panics, so that the panic doesn't unwind across
+/// the C API.
+fn catch_panic<T>(function: impl FnOnce() -> T, on_panic: impl FnOnce(String) -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(function))
+        .unwrap_or_else(|payload| on_panic(panic_message(payload.as_ref())))
+}
+
+/// Read a string argument, naming the argument in the error if it is null or not UTF-8.
+///
+/// # Safety
+///
+/// `string` must be null or a valid NUL-terminated string.
+unsafe fn read_str<'a>(string: *const c_char, argument: &str) -> Result<&'a str, ResponseError> {
+    if string.is_null() {
+        return Err(ResponseError::new(
+            error_codes::INVALID_PARAMS,
+            format!("`{argument}` is null"),
+        ));
+    }
+
+    CStr::from_ptr(string).to_str().map_err(|error| {
+        ResponseError::new(
+            error_codes::INVALID_PARAMS,
+            format!("`{argument}`: {error}"),
+        )
+    })
+}
+
+/// Read a JSON argument, `null` if the pointer is null.
+///
+/// # Safety
+///
+/// `json` must be null or a valid NUL-terminated string.
+unsafe fn read_json(json: *const c_char, argument: &str) -> Result<Value, ResponseError> {
+    if json.is_null() {
+        return Ok(Value::Null);
+    }
+
+    serde_json::from_str(read_str(json, argument)?).map_err(|error| {
+        ResponseError::new(error_codes::PARSE_ERROR, format!("`{argument}`: {error}"))
+    })
+}
+
+/// Create a LanguageService.
+///
+/// # Safety
+///
+/// `position_encoding` must be null or a valid NUL-terminated string: `"utf-8"`, `"utf-16"` or `"utf-32"`, the
+/// encoding of every position passed to and returned by the service. It is `"utf-16"` by default, as in the LSP.
+///
+/// Returns null if the encoding is unknown, or if creating the service panicked.
+#[no_mangle]
+pub unsafe extern "C" fn csslsrs_service_new(
+    position_encoding: *const c_char,
+) -> *mut CsslsrsService {
+    catch_panic(
+        || {
+            let encoding = if position_encoding.is_null() {
+                Some(PositionEncoding::default())
+            } else {
+                read_str(position_encoding, "position_encoding")
+                    .ok()
+                    .and_then(|encoding| PositionEncoding::from_kind(&encoding.to_string().into()))
+            };
+            let Some(encoding) = encoding else {
+                return ptr::null_mut();
+            };
+
+            Box::into_raw(Box::new(CsslsrsService {
+                adapter: JsonRpcAdapter::new(LanguageService::new(encoding)),
+            }))
+        },
+        |_| ptr::null_mut(),
+    )
+}
+
+/// Free a LanguageService and every document opened in it.
+///
+/// # Safety
+///
+/// `service` must be null or a pointer returned by `csslsrs_service_new` that wasn't freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn csslsrs_service_free(service: *mut CsslsrsService) {
+    if !service.is_null() {
+        // The service is freed even if dropping a document panics, nothing else can be done with it
+        catch_panic(|| drop(Box::from_raw(service)), |_| ());
+    }
+}
+
+/// Handle a notification with the JSON of its params, returning its error message if any, or the message of its panic.
+///
+/// # Safety
+///
+/// `service` must be a valid pointer returned by `csslsrs_service_new`.
+unsafe fn notify(
+    service: *const CsslsrsService,
+    method: &str,
+    params: impl FnOnce() -> Result<Value, ResponseError>,
+) -> *mut c_char {
+    let Some(service) = service.as_ref() else {
+        return to_c_string("`service` is null".to_string());
+    };
+
+    catch_panic(
+        || match params().and_then(|params| service.adapter.handle_notification(method, params)) {
+            Ok(()) => ptr::null_mut(),
+            Err(error) => error_string(error),
+        },
+        to_c_string,
+    )
+}
+
+/// Open a document, replacing it if it was already open.
+///
+/// Returns null on success, or an error message to free with `csslsrs_string_free`.
+///
+/// # Safety
+///
+/// `service` must be a valid pointer returned by `csslsrs_service_new`, and the strings valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn csslsrs_open_document(
+    service: *const CsslsrsService,
+    uri: *const c_char,
+    language_id: *const c_char,
+    version: i32,
+    text: *const c_char,
+) -> *mut c_char {
+    notify(service, "textDocument/didOpen", || {
+        Ok(json!({
+            "textDocument": {
+                "uri": read_str(uri, "uri")?,
+                "languageId": read_str(language_id, "language_id")?,
+                "version": version,
+                "text": read_str(text, "text")?,
+            }
+        }))
+    })
+}
+
+/// Apply changes to an open document, as notified by `textDocument/didChange`.
+///
+/// `content_changes` is the JSON of an array of `TextDocumentContentChangeEvent`s, applied in order.
+///
+/// Returns null on success, or an error message to free with `csslsrs_string_free`.
+///
+/// # Safety
+///
+/// `service` must be a valid pointer returned by `csslsrs_service_new`, and the strings valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn csslsrs_update_document(
+    service: *const CsslsrsService,
+    uri: *const c_char,
+    version: i32,
+    content_changes: *const c_char,
+) -> *mut c_char {
+    notify(service, "textDocument/didChange", || {
+        Ok(json!({
+            "textDocument": { "uri": read_str(uri, "uri")?, "version": version },
+            "contentChanges": read_json(content_changes, "content_changes")?,
+        }))
+    })
+}
+
+/// Close a document, removing it from the service.
+///
+/// Returns null on success, or an error message to free with `csslsrs_string_free`.
+///
+/// # Safety
+///
+/// `service` must be a valid pointer returned by `csslsrs_service_new`, and `uri` a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn csslsrs_close_document(
+    service: *const CsslsrsService,
+    uri: *const c_char,
+) -> *mut c_char {
+    notify(service, "textDocument/didClose", || {
+        Ok(json!({ "textDocument": { "uri": read_str(uri, "uri")? } }))
+    })
+}
+
+/// Replace every setting of the service at once. `settings` is the JSON of the `Settings` of csslsrs, missing
+/// settings get their default value.
+///
+/// Returns null on success, or an error message to free with `csslsrs_string_free`.
+///
+/// # Safety
+///
+/// `service` must be a valid pointer returned by `csslsrs_service_new`, and `settings` a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn csslsrs_set_configuration(
+    service: *const CsslsrsService,
+    settings: *const c_char,
+) -> *mut c_char {
+    notify(service, "workspace/didChangeConfiguration", || {
+        Ok(json!({ "settings": read_json(settings, "settings")? }))
+    })
+}
+
+/// Request a feature by its LSP method, e.g. `textDocument/documentColor`, with the JSON of its params.
+///
+/// Returns the JSON of an object with either the `result` of the request, or its `error` with a JSON-RPC `code` and a
+/// `message`, to free with `csslsrs_string_free`. Methods of features that aren't handled fail with `-32601`, and
+/// requests that panic with `-32603`.
+///
+/// # Safety
+///
+/// `service` must be a valid pointer returned by `csslsrs_service_new`, and the strings valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn csslsrs_request(
+    service: *const CsslsrsService,
+    method: *const c_char,
+    params: *const c_char,
+) -> *mut c_char {
+    let response = catch_panic(
+        || {
+            service
+                .as_ref()
+                .ok_or_else(|| ResponseError::new(error_codes::INVALID_PARAMS, "`service` is null"))
+                .and_then(|service| {
+                    let method = read_str(method, "method")?;
+                    service
+                        .adapter
+                        .handle_request(method, read_json(params, "params")?)
+                })
+        },
+        |message| Err(ResponseError::new(error_codes::INTERNAL_ERROR, message)),
+    );
+
+    to_c_string(
+        match response {
+            Ok(result) => json!({ "result": result }),
+            Err(error) => json!({ "error": error }),
+        }
+        .to_string(),
+    )
+}
+
+/// Free a string returned by the API.
+///
+/// # Safety
+///
+/// `string` must be null or a string returned by the API that wasn't freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn csslsrs_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        catch_panic(|| drop(CString::from_raw(string)), |_| ());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catch_panic() {
+        assert_eq!(catch_panic(|| 1, |_| 0), 1);
+        assert_eq!(
+            catch_panic(|| panic!("invalid state"), |message| message),
+            "panicked: invalid state"
+        );
+        assert_eq!(
+            catch_panic(|| panic!("invalid {}", "state"), |message| message),
+            "panicked: invalid state"
+        );
+    }
+}
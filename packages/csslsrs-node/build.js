@@ -0,0 +1,12 @@
+import { execFileSync } from "node:child_process";
+import { copyFile } from "node:fs/promises";
+
+// Build the addon with cargo and give it the name Node expects, `--release` builds an optimized addon
+const release = process.argv.includes("--release");
+execFileSync("cargo", ["build", ...(release ? ["--release"] : [])], { stdio: "inherit" });
+
+const library = {
+  darwin: "libcsslsrs_node.dylib",
+  win32: "csslsrs_node.dll",
+}[process.platform] ?? "libcsslsrs_node.so";
+await copyFile(`./target/${release ? "release" : "debug"}/${library}`, "./csslsrs-node.node");
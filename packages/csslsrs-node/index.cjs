@@ -0,0 +1 @@
+module.exports = require("./csslsrs-node.node");
@@ -0,0 +1,59 @@
+import type { TextDocumentContentChangeEvent } from "vscode-languageserver-textdocument";
+import type {
+	Color,
+	ColorInformation,
+	ColorPresentation,
+	CompletionItem,
+	Diagnostic,
+	FoldingRange,
+	InlayHint,
+	Position,
+	Range,
+	SemanticTokens,
+	SemanticTokensLegend,
+	WorkspaceSymbol,
+} from "vscode-languageserver-types";
+
+/** The settings of a `LanguageService`, every setting being optional. */
+export interface Settings {
+	colors?: {
+		/** The maximum number of colors returned for a document, no limit by default. */
+		maxDocumentColors?: number | null;
+	};
+}
+
+/** Get the legend of the semantic tokens returned by `LanguageService.getSemanticTokens`. */
+export function getSemanticTokensLegend(): SemanticTokensLegend;
+
+/**
+ * A language service keeping the documents opened in it, so that the text of a document is only
+ * passed once and then updated with the changes made to it. Its other methods, one per feature,
+ * take the URI of an open document.
+ */
+export class LanguageService {
+	constructor();
+	/**
+	 * Open a document, replacing it if it was already open.
+	 *
+	 * @param encoding The encoding of the positions of every request on the document and of its
+	 * changes, `"utf-16"` by default as in the LSP.
+	 */
+	openDocument(uri: string, languageId: string, version: number, text: string, encoding?: "utf-8" | "utf-16" | "utf-32"): void;
+	/** Apply changes to an open document, as notified by `textDocument/didChange`. */
+	updateDocument(uri: string, version: number, contentChanges: TextDocumentContentChangeEvent[]): void;
+	/** Close a document, removing it from the service. */
+	closeDocument(uri: string): void;
+	/** Replace every setting of the service at once. Missing settings get their default value. */
+	setConfiguration(settings: Settings): void;
+	getCompletions(uri: string, position: Position): CompletionItem[];
+	getDiagnostics(uri: string): Diagnostic[];
+	getDocumentColors(uri: string): ColorInformation[];
+	getColorPresentations(uri: string, color: Color, range: Range): ColorPresentation[];
+	getFoldingRanges(uri: string): FoldingRange[];
+	getInlayHints(uri: string, range: Range): InlayHint[];
+	getSemanticTokens(uri: string): SemanticTokens;
+	/** Get the symbols declared across every open document whose name contains `query`. */
+	getWorkspaceSymbols(query: string): WorkspaceSymbol[];
+	/** Get the syntax tree of an open document, formatted for debugging. */
+	getSyntaxTree(uri: string): string;
+}
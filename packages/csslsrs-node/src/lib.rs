@@ -0,0 +1,227 @@
+//! Native Node.js bindings of csslsrs, built with napi-rs.
+//!
+//! They expose the same `LanguageService` class as the WASM package, for servers where native addons are acceptable.
+//! The text of documents is passed as a string, instead of being serialized and copied into the memory of a WASM
+//! instance, which matters for multi-megabyte stylesheets. Other arguments and results are LSP objects, converted
+//! with serde_json.
+
+use std::str::FromStr;
+
+use csslsrs::{
+    converters::PositionEncoding, features::semantic_tokens::semantic_tokens_legend,
+    service::LanguageService, settings::Settings,
+};
+use lsp_types::{TextDocumentContentChangeEvent, TextDocumentItem, Uri};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+fn service_error(error: csslsrs::Error) -> Error {
+    let status = match error {
+        csslsrs::Error::Cancelled => Status::Cancelled,
+        _ => Status::InvalidArg,
+    };
+    Error::new(status, error.to_string())
+}
+
+fn parse_uri(uri: &str) -> Result<Uri> {
+    Uri::from_str(uri)
+        .map_err(|error| Error::new(Status::InvalidArg, format!("invalid URI `{uri}`: {error}")))
+}
+
+/// Convert an argument from JS, naming the argument in the error if it is malformed.
+fn from_json<T: DeserializeOwned>(value: Value, argument: &str) -> Result<T> {
+    serde_json::from_value(value)
+        .map_err(|error| Error::new(Status::InvalidArg, format!("`{argument}`: {error}")))
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<Value> {
+    serde_json::to_value(value).map_err(|error| Error::from_reason(error.to_string()))
+}
+
+/// Get the legend of the semantic tokens returned by `LanguageService.getSemanticTokens`.
+#[napi]
+pub fn get_semantic_tokens_legend() -> Result<Value> {
+    to_json(&semantic_tokens_legend())
+}
+
+/// A language service keeping the documents opened in it, so that the text of a document is only passed once and
+/// then updated with the changes made to it.
+#[napi(js_name = "LanguageService")]
+pub struct NodeLanguageService {
+    language_service: LanguageService,
+}
+
+#[napi]
+impl NodeLanguageService {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self {
+            language_service: LanguageService::default(),
+        }
+    }
+
+    /// Open a document, replacing it if it was already open. `encoding` is the encoding of the positions of every
+    /// request on the document and of its changes, `utf-16` by default as in the LSP.
+    #[napi]
+    pub fn open_document(
+        &self,
+        uri: String,
+        language_id: String,
+        version: i32,
+        text: String,
+        encoding: Option<String>,
+    ) -> Result<()> {
+        let encoding = match encoding {
+            Some(encoding) => Some(
+                PositionEncoding::from_kind(&encoding.clone().into()).ok_or_else(|| {
+                    Error::new(
+                        Status::InvalidArg,
+                        format!("unknown position encoding `{encoding}`"),
+                    )
+                })?,
+            ),
+            None => None,
+        };
+
+        self.language_service.open_document_with_encoding(
+            TextDocumentItem {
+                uri: parse_uri(&uri)?,
+                language_id,
+                version,
+                text,
+            },
+            encoding,
+        );
+        Ok(())
+    }
+
+    /// Apply changes to an open document, as notified by `textDocument/didChange`.
+    #[napi]
+    pub fn update_document(&self, uri: String, version: i32, content_changes: Value) -> Result<()> {
+        let content_changes: Vec<TextDocumentContentChangeEvent> =
+            from_json(content_changes, "contentChanges")?;
+
+        self.language_service
+            .change_document(&parse_uri(&uri)?, version, content_changes)
+            .map_err(service_error)?;
+        Ok(())
+    }
+
+    /// Close a document, removing it from the service.
+    #[napi]
+    pub fn close_document(&self, uri: String) -> Result<()> {
+        self.language_service.close_document(&parse_uri(&uri)?);
+        Ok(())
+    }
+
+    /// Replace every setting of the service at once, with the same shape as the `Settings` of the native language
+    /// service. Missing settings get their default value.
+    #[napi]
+    pub fn set_configuration(&self, settings: Value) -> Result<()> {
+        let settings: Settings = from_json(settings, "settings")?;
+
+        self.language_service.update_settings(settings);
+        Ok(())
+    }
+
+    #[napi]
+    pub fn get_completions(&self, uri: String, position: Value) -> Result<Value> {
+        let completions = self
+            .language_service
+            .get_completions_for_uri(&parse_uri(&uri)?, from_json(position, "position")?)
+            .map_err(service_error)?;
+
+        to_json(&completions)
+    }
+
+    #[napi]
+    pub fn get_diagnostics(&self, uri: String) -> Result<Value> {
+        let diagnostics = self
+            .language_service
+            .get_diagnostics_for_uri(&parse_uri(&uri)?)
+            .map_err(service_error)?;
+
+        to_json(&diagnostics)
+    }
+
+    #[napi]
+    pub fn get_document_colors(&self, uri: String) -> Result<Value> {
+        let colors = self
+            .language_service
+            .get_document_colors_for_uri(&parse_uri(&uri)?)
+            .map_err(service_error)?;
+
+        to_json(&colors)
+    }
+
+    #[napi]
+    pub fn get_color_presentations(
+        &self,
+        uri: String,
+        color: Value,
+        range: Value,
+    ) -> Result<Value> {
+        let presentations = self
+            .language_service
+            .get_color_presentations_for_uri(
+                &parse_uri(&uri)?,
+                from_json(color, "color")?,
+                from_json(range, "range")?,
+            )
+            .map_err(service_error)?;
+
+        to_json(&presentations)
+    }
+
+    #[napi]
+    pub fn get_folding_ranges(&self, uri: String) -> Result<Value> {
+        let folding_ranges = self
+            .language_service
+            .get_folding_ranges_for_uri(&parse_uri(&uri)?)
+            .map_err(service_error)?;
+
+        to_json(&folding_ranges)
+    }
+
+    #[napi]
+    pub fn get_inlay_hints(&self, uri: String, range: Value) -> Result<Value> {
+        let inlay_hints = self
+            .language_service
+            .get_inlay_hints_for_uri(&parse_uri(&uri)?, from_json(range, "range")?)
+            .map_err(service_error)?;
+
+        to_json(&inlay_hints)
+    }
+
+    #[napi]
+    pub fn get_semantic_tokens(&self, uri: String) -> Result<Value> {
+        let semantic_tokens = self
+            .language_service
+            .get_semantic_tokens_for_uri(&parse_uri(&uri)?)
+            .map_err(service_error)?;
+
+        to_json(&semantic_tokens)
+    }
+
+    /// Get the symbols declared across every open document whose name contains `query`.
+    #[napi]
+    pub fn get_workspace_symbols(&self, query: String) -> Result<Value> {
+        to_json(&self.language_service.get_workspace_symbols(&query))
+    }
+
+    /// Get the syntax tree of an open document, formatted for debugging.
+    #[napi]
+    pub fn get_syntax_tree(&self, uri: String) -> Result<String> {
+        self.language_service
+            .debug_syntax_tree_for_uri(&parse_uri(&uri)?)
+            .map_err(service_error)
+    }
+}
+
+impl Default for NodeLanguageService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,4 +1,6 @@
 import { cp, mkdir, rm } from "node:fs/promises";
-await rm("./src/generated/package.json");
-await mkdir("./dist/generated", { recursive: true });
-await cp("./src/generated", "./dist/generated", { recursive: true });
+for (const generated of ["generated", "generated-web"]) {
+	await rm(`./src/${generated}/package.json`, { force: true });
+	await mkdir(`./dist/${generated}`, { recursive: true });
+	await cp(`./src/${generated}`, `./dist/${generated}`, { recursive: true });
+}
@@ -1,5 +1,19 @@
 export {
+	LanguageService,
+	analyze_document,
+	analyze_document_json,
 	get_folding_ranges,
 	get_document_colors,
+	get_document_colors_in_range,
 	get_color_presentations,
+	get_inlay_hints,
+	resolve_inlay_hint,
+	get_semantic_tokens,
+	get_semantic_tokens_legend,
+	get_workspace_symbols,
+	index_workspace_folder,
+	get_completions,
+	get_diagnostics,
+	get_syntax_tree,
+	get_resolved_value,
 } from "./generated/csslsrs.js";
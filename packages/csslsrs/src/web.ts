@@ -0,0 +1,6 @@
+// The web flavor of the package, for browsers and Web Workers. The module has to be initialized before use, either
+// with `await init()`, which fetches `csslsrs_bg.wasm` next to it, or with `initSync({ module })` when a bundler
+// inlines the wasm. It relies on no main-thread-only global, so the whole language service can run in a worker and
+// transfer its results with `analyze_document_json`.
+export { default as init } from "./generated-web/csslsrs.js";
+export * from "./generated-web/csslsrs.js";
@@ -16,4 +16,23 @@ describe("Folding", () => {
       },
     ] satisfies FoldingRange[]);
   });
+
+  it("Accepts plain objects and [uri, text] pairs as documents", async () => {
+    const text = "body {\n    margin: 0;\n    padding: 0;\n}\n";
+    const expected = [
+      {
+        endLine: 3,
+        startLine: 0,
+      },
+    ] satisfies FoldingRange[];
+
+    expect(await get_folding_ranges({ uri: "file:///test.css", languageId: "css", version: 0, text })).to.deep.equal(
+      expected
+    );
+    expect(await get_folding_ranges(["file:///test.css", text])).to.deep.equal(expected);
+  });
+
+  it("Rejects documents without a text", async () => {
+    expect(() => get_folding_ranges({ uri: "file:///test.css" } as any)).to.throw("missing field `text`");
+  });
 });
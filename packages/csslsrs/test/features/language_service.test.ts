@@ -0,0 +1,72 @@
+import { describe, it } from "mocha";
+import { expect } from "chai";
+import { LanguageService, analyze_document } from "../../dist/index.js";
+import { TextDocument } from "vscode-languageserver-textdocument";
+import type { FoldingRange } from "vscode-languageserver-types";
+
+describe("LanguageService", () => {
+  it("Can update an open document incrementally", () => {
+    const languageService = new LanguageService();
+    const myDocument = TextDocument.create("file:///test.css", "css", 0, "body {\n    margin: 0;\n}\n");
+    languageService.openDocument(myDocument);
+
+    languageService.updateDocument("file:///test.css", 1, [
+      {
+        range: { start: { line: 1, character: 14 }, end: { line: 1, character: 14 } },
+        text: "\n    padding: 0;",
+      },
+    ]);
+
+    expect(languageService.getFoldingRanges("file:///test.css")).to.deep.equal([
+      {
+        endLine: 3,
+        startLine: 0,
+      },
+    ] satisfies FoldingRange[]);
+
+    languageService.closeDocument("file:///test.css");
+    languageService.free();
+  });
+
+  it("Can compute several features at once", async () => {
+    const myDocument = TextDocument.create("file:///test.css", "css", 0, ".button {\n    color: red;\n}\n");
+    const analysis = await analyze_document(myDocument, { folding: true, symbols: true });
+
+    expect(analysis.colors).to.be.undefined;
+    expect(analysis.diagnostics).to.be.undefined;
+    expect(analysis.folding).to.deep.equal([
+      {
+        endLine: 2,
+        startLine: 0,
+      },
+    ] satisfies FoldingRange[]);
+    expect(analysis.symbols?.map((symbol) => symbol.name)).to.deep.equal([".button"]);
+  });
+
+  it("Can be configured", () => {
+    const languageService = new LanguageService();
+    const myDocument = TextDocument.create("file:///test.css", "css", 0, "a { color: red; background: blue; }");
+    languageService.openDocument(myDocument);
+
+    languageService.setConfiguration({ colors: { maxDocumentColors: 1 } });
+    expect(languageService.getDocumentColors("file:///test.css")).to.have.length(1);
+
+    expect(() => languageService.setConfiguration({ colors: { maxDocumentColors: "1" } } as any)).to.throw("`settings`");
+    languageService.free();
+  });
+
+  it("Returns results as the JSON of the LSP", () => {
+    const languageService = new LanguageService();
+    const myDocument = TextDocument.create("file:///test.css", "css", 0, ":root { --a: red; }\na { color: var(--a); }");
+    languageService.openDocument(myDocument);
+
+    const hints = languageService.getInlayHints("file:///test.css", {
+      start: { line: 0, character: 0 },
+      end: { line: 2, character: 0 },
+    });
+    expect(hints).to.have.length(1);
+    expect(hints[0]?.data).to.not.be.instanceOf(Map);
+    expect(JSON.parse(JSON.stringify(hints))).to.deep.equal(hints);
+    languageService.free();
+  });
+});
@@ -0,0 +1,139 @@
+//! A language server for CSS.
+//!
+//! The transport is chosen with the arguments of the server:
+//!
+//! - `--stdio`, the default: stdin and stdout.
+//! - `--socket=<port>`: connect to a client listening on a local TCP port, as done by VS Code's `TransportKind.socket`.
+//! - `--port=<port>`: listen on a TCP port, e.g. in a container, serving each client on its own thread.
+//! - `--websocket=<port>`: listen for WebSocket connections on a port, for browser-based editors. Only available with
+//!   the `websocket` feature.
+//!
+//! `--host=<address>` sets the address listened on with `--port` and `--websocket`, `127.0.0.1` by default.
+
+use std::{
+    env,
+    io::{self, BufReader},
+    net::{TcpListener, TcpStream},
+    process, thread,
+};
+
+use csslsrs::server::{run, serve, StreamTransport};
+
+const USAGE: &str = "usage: csslsrs-server [--stdio | --socket=<port> | --port=<port> | --websocket=<port>] [--host=<address>]";
+
+enum Transport {
+    Stdio,
+    Socket(u16),
+    Port(u16),
+    // Parsed without the `websocket` feature too, to tell that it is missing
+    #[cfg_attr(not(feature = "websocket"), allow(dead_code))]
+    WebSocket(u16),
+}
+
+struct Options {
+    transport: Transport,
+    host: String,
+}
+
+fn parse_options(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        transport: Transport::Stdio,
+        host: "127.0.0.1".to_string(),
+    };
+    let mut args = args.peekable();
+
+    while let Some(arg) = args.next() {
+        let (name, value) = match arg.split_once('=') {
+            Some((name, value)) => (name.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+        // Values can also be passed as the next argument, e.g. `--port 8080`
+        let value = || {
+            value
+                .or_else(|| args.next_if(|next| !next.starts_with("--")))
+                .ok_or_else(|| format!("`{name}` needs a value"))
+        };
+        let port = |value: String| {
+            value
+                .parse::<u16>()
+                .map_err(|_| format!("invalid port `{value}`"))
+        };
+
+        options.transport = match name.as_str() {
+            "--stdio" => Transport::Stdio,
+            "--socket" => Transport::Socket(port(value()?)?),
+            "--port" => Transport::Port(port(value()?)?),
+            "--websocket" => Transport::WebSocket(port(value()?)?),
+            "--host" => {
+                options.host = value()?;
+                continue;
+            }
+            // The process id of the client, passed by some clients, is not needed over these transports
+            "--clientProcessId" => {
+                value()?;
+                continue;
+            }
+            _ => return Err(format!("unknown argument `{name}`")),
+        };
+    }
+
+    Ok(options)
+}
+
+fn serve_tcp(stream: TcpStream) -> io::Result<i32> {
+    run(&mut BufReader::new(stream.try_clone()?), &mut &stream)
+}
+
+/// Accept connections on a port until the process is killed, serving each of them on its own thread with its own
+/// LanguageService.
+fn listen(address: (&str, u16), handle: fn(TcpStream) -> io::Result<i32>) -> io::Result<i32> {
+    let listener = TcpListener::bind(address)?;
+    eprintln!("csslsrs-server: listening on {}", listener.local_addr()?);
+
+    for stream in listener.incoming() {
+        let stream = stream?;
+        thread::spawn(move || {
+            if let Err(error) = handle(stream) {
+                eprintln!("csslsrs-server: {error}");
+            }
+        });
+    }
+
+    Ok(0)
+}
+
+#[cfg(feature = "websocket")]
+fn serve_websocket(stream: TcpStream) -> io::Result<i32> {
+    let mut websocket =
+        tungstenite::accept(stream).map_err(|error| io::Error::other(error.to_string()))?;
+    serve(&mut websocket)
+}
+
+fn main() {
+    let options = parse_options(env::args().skip(1)).unwrap_or_else(|error| {
+        eprintln!("csslsrs-server: {error}\n{USAGE}");
+        process::exit(2);
+    });
+
+    let exit_code = match options.transport {
+        Transport::Stdio => serve(&mut StreamTransport::new(
+            io::stdin().lock(),
+            io::stdout().lock(),
+        )),
+        Transport::Socket(port) => TcpStream::connect(("127.0.0.1", port)).and_then(serve_tcp),
+        Transport::Port(port) => listen((&options.host, port), serve_tcp),
+        #[cfg(feature = "websocket")]
+        Transport::WebSocket(port) => listen((&options.host, port), serve_websocket),
+        #[cfg(not(feature = "websocket"))]
+        Transport::WebSocket(_) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "csslsrs-server was built without the `websocket` feature",
+        )),
+    }
+    .unwrap_or_else(|error| {
+        eprintln!("csslsrs-server: {error}");
+        1
+    });
+
+    process::exit(exit_code);
+}
@@ -0,0 +1,43 @@
+//! The `csslsrs` command line interface, see `csslsrs::cli` for its commands.
+
+use std::{env, io, process};
+
+use csslsrs::cli::{
+    check, exit_code, find_stylesheets, format_diagnostics, json_lines, language_service, Command,
+    Options, USAGE,
+};
+
+fn run(command: Command, options: &Options) -> io::Result<i32> {
+    let folder = env::current_dir()?.canonicalize()?;
+    let language_service = language_service(options.settings.as_deref())?;
+    let stylesheets = find_stylesheets(&language_service, &folder, &options.globs)?;
+
+    if command != Command::Check {
+        print!("{}", json_lines(&language_service, command, &stylesheets)?);
+        return Ok(0);
+    }
+    let results = check(&language_service, &stylesheets)?;
+    print!("{}", format_diagnostics(&results, options.format, &folder));
+    Ok(exit_code(&results))
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let Some(command) = args.next() else {
+        eprintln!("{USAGE}");
+        process::exit(2);
+    };
+    let (command, options) = command
+        .parse::<Command>()
+        .and_then(|command| Ok((command, Options::parse(args)?)))
+        .unwrap_or_else(|error| {
+            eprintln!("csslsrs: {error}\n{USAGE}");
+            process::exit(2);
+        });
+
+    let exit_code = run(command, &options).unwrap_or_else(|error| {
+        eprintln!("csslsrs: {error}");
+        2
+    });
+    process::exit(exit_code);
+}
@@ -0,0 +1,137 @@
+//! Cancellation of long-running requests, so that servers can honor `$/cancelRequest` instead of computing results
+//! that will be thrown away.
+//!
+//! Features supporting cancellation take a `CancellationToken` and check it at safe points, returning `Err(Cancelled)`
+//! as soon as it is cancelled. Servers can keep track of the tokens of their pending requests with a
+//! `CancellationRegistry`.
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+};
+
+use lsp_types::NumberOrString;
+use rustc_hash::FxHashMap;
+
+/// The error returned by a request that was cancelled before it completed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the request was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// A flag shared between a request and whoever may cancel it. Clones of a token are cancelled together.
+///
+/// A token created with `CancellationToken::default()` that is never cancelled can be used for requests that can't be
+/// cancelled.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the request using this token. It will stop at its next check.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Return `Err(Cancelled)` if the token was cancelled, to be used with `?` at safe points of a request.
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The tokens of the pending requests of a server, keyed by their LSP request id.
+#[derive(Default)]
+pub struct CancellationRegistry {
+    tokens: Mutex<FxHashMap<NumberOrString, CancellationToken>>,
+}
+
+impl CancellationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create the token of a new request. It should be removed with `finish` once the request completes.
+    pub fn register(&self, id: NumberOrString) -> CancellationToken {
+        let token = CancellationToken::new();
+        self.tokens
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(id, token.clone());
+        token
+    }
+
+    /// Cancel a pending request, e.g. when receiving `$/cancelRequest`. Unknown ids are ignored, as the request may
+    /// already have completed.
+    pub fn cancel(&self, id: &NumberOrString) {
+        if let Some(token) = self
+            .tokens
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(id)
+        {
+            token.cancel();
+        }
+    }
+
+    /// Forget the token of a request that completed.
+    pub fn finish(&self, id: &NumberOrString) {
+        self.tokens
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancellation_token() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert_eq!(clone.check(), Ok(()));
+
+        token.cancel();
+        assert!(clone.is_cancelled());
+        assert_eq!(clone.check(), Err(Cancelled));
+    }
+
+    #[test]
+    fn test_cancellation_registry() {
+        let registry = CancellationRegistry::new();
+        let first = registry.register(NumberOrString::Number(1));
+        let second = registry.register(NumberOrString::String("2".to_string()));
+
+        registry.cancel(&NumberOrString::Number(1));
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+
+        // Requests that completed can't be cancelled anymore
+        registry.finish(&NumberOrString::String("2".to_string()));
+        registry.cancel(&NumberOrString::String("2".to_string()));
+        assert!(!second.is_cancelled());
+    }
+}
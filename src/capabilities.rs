@@ -0,0 +1,677 @@
+//! The capabilities of a language server embedding csslsrs, negotiated with the capabilities of its client, so that
+//! servers don't have to keep track of which requests the LanguageService answers and how.
+
+use lsp_types::{
+    ClientCapabilities, CodeActionProviderCapability, ColorProviderCapability, CompletionOptions,
+    DiagnosticOptions, DiagnosticServerCapabilities, DocumentLinkOptions,
+    FoldingRangeProviderCapability, HoverProviderCapability, InlayHintOptions,
+    InlayHintServerCapabilities, OneOf, Registration, RenameOptions, ServerCapabilities,
+    SignatureHelpOptions, TextDocumentSyncCapability, TextDocumentSyncKind,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{converters::PositionEncoding, parser::CssDialect};
+
+/// The characters that trigger completion when typed, e.g. `-` for custom properties, `:` for pseudo-classes and `$` for
+/// SCSS variables.
+const COMPLETION_TRIGGER_CHARACTERS: [&str; 5] = ["/", "-", ":", "(", "$"];
+
+/// The characters that trigger signature help when typed, opening and separating the arguments of mixins and functions.
+const SIGNATURE_HELP_TRIGGER_CHARACTERS: [&str; 2] = ["(", ","];
+
+/// The features a server provides, by default every feature compiled in. Features left out of the build with their
+/// cargo feature are never advertised.
+///
+/// Features deserialize from the settings sent by clients, with camelCase keys, e.g. `{ "inlayHints": false }`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EnabledFeatures {
+    /// The conversions of lengths between units.
+    pub code_actions: bool,
+    pub colors: bool,
+    pub completion: bool,
+    /// Go to definition and find references.
+    pub definition: bool,
+    pub diagnostics: bool,
+    pub document_links: bool,
+    /// The outline of documents.
+    pub document_symbols: bool,
+    pub folding: bool,
+    pub hover: bool,
+    pub inlay_hints: bool,
+    /// The renaming of custom properties, `@keyframes` rules and classes.
+    pub rename: bool,
+    pub semantic_tokens: bool,
+    pub signature_help: bool,
+    pub workspace_symbols: bool,
+}
+
+// Derivable when no feature is compiled in
+#[allow(clippy::derivable_impls)]
+impl Default for EnabledFeatures {
+    fn default() -> Self {
+        Self {
+            code_actions: cfg!(feature = "code-actions"),
+            colors: cfg!(feature = "colors"),
+            completion: cfg!(feature = "completion"),
+            definition: cfg!(feature = "definition"),
+            diagnostics: cfg!(feature = "diagnostics"),
+            document_links: cfg!(feature = "document-links"),
+            document_symbols: cfg!(feature = "document-symbols"),
+            folding: cfg!(feature = "folding"),
+            hover: cfg!(feature = "hover"),
+            inlay_hints: cfg!(feature = "inlay-hints"),
+            rename: cfg!(feature = "rename"),
+            semantic_tokens: cfg!(feature = "semantic-tokens"),
+            signature_help: cfg!(feature = "signature-help"),
+            workspace_symbols: cfg!(feature = "workspace-symbols"),
+        }
+    }
+}
+
+impl EnabledFeatures {
+    /// Only keep the features that are compiled in.
+    fn compiled(&self) -> Self {
+        let compiled = Self::default();
+        Self {
+            code_actions: self.code_actions && compiled.code_actions,
+            colors: self.colors && compiled.colors,
+            completion: self.completion && compiled.completion,
+            definition: self.definition && compiled.definition,
+            diagnostics: self.diagnostics && compiled.diagnostics,
+            document_links: self.document_links && compiled.document_links,
+            document_symbols: self.document_symbols && compiled.document_symbols,
+            folding: self.folding && compiled.folding,
+            hover: self.hover && compiled.hover,
+            inlay_hints: self.inlay_hints && compiled.inlay_hints,
+            rename: self.rename && compiled.rename,
+            semantic_tokens: self.semantic_tokens && compiled.semantic_tokens,
+            signature_help: self.signature_help && compiled.signature_help,
+            workspace_symbols: self.workspace_symbols && compiled.workspace_symbols,
+        }
+    }
+
+    /// Each feature, with the method it is registered with and the key of its `ServerCapabilities`.
+    fn registrable(&self) -> [(bool, &'static str, &'static str); 15] {
+        [
+            (
+                self.code_actions,
+                "textDocument/codeAction",
+                "codeActionProvider",
+            ),
+            (self.colors, "textDocument/documentColor", "colorProvider"),
+            (
+                self.completion,
+                "textDocument/completion",
+                "completionProvider",
+            ),
+            (
+                self.definition,
+                "textDocument/definition",
+                "definitionProvider",
+            ),
+            (
+                self.definition,
+                "textDocument/references",
+                "referencesProvider",
+            ),
+            (
+                self.diagnostics,
+                "textDocument/diagnostic",
+                "diagnosticProvider",
+            ),
+            (
+                self.document_links,
+                "textDocument/documentLink",
+                "documentLinkProvider",
+            ),
+            (
+                self.document_symbols,
+                "textDocument/documentSymbol",
+                "documentSymbolProvider",
+            ),
+            (
+                self.folding,
+                "textDocument/foldingRange",
+                "foldingRangeProvider",
+            ),
+            (self.hover, "textDocument/hover", "hoverProvider"),
+            (
+                self.inlay_hints,
+                "textDocument/inlayHint",
+                "inlayHintProvider",
+            ),
+            (self.rename, "textDocument/rename", "renameProvider"),
+            (
+                self.semantic_tokens,
+                "textDocument/semanticTokens",
+                "semanticTokensProvider",
+            ),
+            (
+                self.signature_help,
+                "textDocument/signatureHelp",
+                "signatureHelpProvider",
+            ),
+            (
+                self.workspace_symbols,
+                "workspace/symbol",
+                "workspaceSymbolProvider",
+            ),
+        ]
+    }
+}
+
+/// Builds the `ServerCapabilities` of a server from the capabilities of its client, and tells the server what was
+/// negotiated, e.g. the PositionEncoding to create its LanguageService with.
+#[derive(Clone, Debug)]
+pub struct ServerCapabilitiesBuilder {
+    features: EnabledFeatures,
+    dynamic_registration: EnabledFeatures,
+    position_encoding: PositionEncoding,
+    folding_range_limit: Option<u32>,
+    pull_diagnostics: bool,
+    refresh_diagnostics: bool,
+    resolve_inlay_hints: bool,
+    prepare_rename: bool,
+    completion_trigger_characters: Vec<String>,
+}
+
+impl ServerCapabilitiesBuilder {
+    /// Negotiate the capabilities of a server with those of its client.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The capabilities sent by the client in its `initialize` request.
+    /// * `features` - The features the server wants to provide.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use csslsrs::capabilities::{EnabledFeatures, ServerCapabilitiesBuilder};
+    /// use csslsrs::service::LanguageService;
+    /// use lsp_types::ClientCapabilities;
+    ///
+    /// let negotiated = ServerCapabilitiesBuilder::from_client_capabilities(
+    ///     &ClientCapabilities::default(),
+    ///     &EnabledFeatures::default(),
+    /// );
+    /// let language_service = LanguageService::new(negotiated.position_encoding());
+    /// let capabilities = negotiated.build();
+    /// ```
+    pub fn from_client_capabilities(
+        client: &ClientCapabilities,
+        features: &EnabledFeatures,
+    ) -> Self {
+        let text_document = client.text_document.as_ref();
+        let dynamic_registration = |dynamic_registration: Option<Option<bool>>| {
+            dynamic_registration.flatten().unwrap_or_default()
+        };
+
+        Self {
+            features: features.compiled(),
+            dynamic_registration: EnabledFeatures {
+                code_actions: dynamic_registration(text_document.and_then(|text_document| {
+                    Some(text_document.code_action.as_ref()?.dynamic_registration)
+                })),
+                colors: dynamic_registration(text_document.and_then(|text_document| {
+                    Some(text_document.color_provider.as_ref()?.dynamic_registration)
+                })),
+                completion: dynamic_registration(text_document.and_then(|text_document| {
+                    Some(text_document.completion.as_ref()?.dynamic_registration)
+                })),
+                // References are registered along with definitions, both need dynamic registration
+                definition: dynamic_registration(text_document.and_then(|text_document| {
+                    Some(text_document.definition.as_ref()?.dynamic_registration)
+                })) && dynamic_registration(text_document.and_then(|text_document| {
+                    Some(text_document.references.as_ref()?.dynamic_registration)
+                })),
+                diagnostics: dynamic_registration(text_document.and_then(|text_document| {
+                    Some(text_document.diagnostic.as_ref()?.dynamic_registration)
+                })),
+                document_links: dynamic_registration(text_document.and_then(|text_document| {
+                    Some(text_document.document_link.as_ref()?.dynamic_registration)
+                })),
+                document_symbols: dynamic_registration(text_document.and_then(|text_document| {
+                    Some(text_document.document_symbol.as_ref()?.dynamic_registration)
+                })),
+                folding: dynamic_registration(text_document.and_then(|text_document| {
+                    Some(text_document.folding_range.as_ref()?.dynamic_registration)
+                })),
+                hover: dynamic_registration(text_document.and_then(|text_document| {
+                    Some(text_document.hover.as_ref()?.dynamic_registration)
+                })),
+                inlay_hints: dynamic_registration(text_document.and_then(|text_document| {
+                    Some(text_document.inlay_hint.as_ref()?.dynamic_registration)
+                })),
+                rename: dynamic_registration(text_document.and_then(|text_document| {
+                    Some(text_document.rename.as_ref()?.dynamic_registration)
+                })),
+                semantic_tokens: dynamic_registration(text_document.and_then(|text_document| {
+                    Some(text_document.semantic_tokens.as_ref()?.dynamic_registration)
+                })),
+                signature_help: dynamic_registration(text_document.and_then(|text_document| {
+                    Some(text_document.signature_help.as_ref()?.dynamic_registration)
+                })),
+                workspace_symbols: dynamic_registration(
+                    client.workspace.as_ref().and_then(|workspace| {
+                        Some(workspace.symbol.as_ref()?.dynamic_registration)
+                    }),
+                ),
+            },
+            position_encoding: PositionEncoding::negotiate(
+                client
+                    .general
+                    .as_ref()
+                    .and_then(|general| general.position_encodings.as_deref())
+                    .unwrap_or_default(),
+            ),
+            folding_range_limit: text_document
+                .and_then(|text_document| text_document.folding_range.as_ref())
+                .and_then(|folding_range| folding_range.range_limit),
+            pull_diagnostics: text_document
+                .is_some_and(|text_document| text_document.diagnostic.is_some()),
+            refresh_diagnostics: client
+                .workspace
+                .as_ref()
+                .and_then(|workspace| workspace.diagnostic.as_ref()?.refresh_support)
+                .unwrap_or_default(),
+            resolve_inlay_hints: text_document
+                .and_then(|text_document| text_document.inlay_hint.as_ref())
+                .and_then(|inlay_hint| inlay_hint.resolve_support.as_ref())
+                .is_some_and(|resolve_support| {
+                    resolve_support
+                        .properties
+                        .iter()
+                        .any(|property| property == "tooltip")
+                }),
+            prepare_rename: text_document
+                .and_then(|text_document| text_document.rename.as_ref())
+                .and_then(|rename| rename.prepare_support)
+                .unwrap_or_default(),
+            completion_trigger_characters: COMPLETION_TRIGGER_CHARACTERS
+                .iter()
+                .map(|character| character.to_string())
+                .collect(),
+        }
+    }
+
+    /// Replace the characters that trigger completion when typed.
+    pub fn completion_trigger_characters(
+        mut self,
+        characters: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.completion_trigger_characters = characters.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// The PositionEncoding negotiated with the client, to create the LanguageService with, see
+    /// `PositionEncoding::negotiate`.
+    pub fn position_encoding(&self) -> PositionEncoding {
+        self.position_encoding
+    }
+
+    /// The maximum number of folding ranges the client wants per document, if any. Servers should only send it the
+    /// first ranges returned by `get_folding_ranges`.
+    pub fn folding_range_limit(&self) -> Option<u32> {
+        self.folding_range_limit
+    }
+
+    /// Whether the client pulls diagnostics with `textDocument/diagnostic`. Otherwise, servers should push them with
+    /// `textDocument/publishDiagnostics`.
+    pub fn pull_diagnostics(&self) -> bool {
+        self.pull_diagnostics
+    }
+
+    /// Whether the client pulls diagnostics again when asked with `workspace/diagnostic/refresh`, e.g. after settings
+    /// changed the diagnostics of every document.
+    pub fn refresh_diagnostics(&self) -> bool {
+        self.refresh_diagnostics
+    }
+
+    /// Get the capabilities to send in the response to the `initialize` request. Features the client can register
+    /// dynamically are left out, to be registered with `registrations` once the client is initialized.
+    pub fn build(&self) -> ServerCapabilities {
+        let features = &EnabledFeatures {
+            code_actions: self.features.code_actions && !self.dynamic_registration.code_actions,
+            colors: self.features.colors && !self.dynamic_registration.colors,
+            completion: self.features.completion && !self.dynamic_registration.completion,
+            definition: self.features.definition && !self.dynamic_registration.definition,
+            diagnostics: self.features.diagnostics && !self.dynamic_registration.diagnostics,
+            document_links: self.features.document_links
+                && !self.dynamic_registration.document_links,
+            document_symbols: self.features.document_symbols
+                && !self.dynamic_registration.document_symbols,
+            folding: self.features.folding && !self.dynamic_registration.folding,
+            hover: self.features.hover && !self.dynamic_registration.hover,
+            inlay_hints: self.features.inlay_hints && !self.dynamic_registration.inlay_hints,
+            rename: self.features.rename && !self.dynamic_registration.rename,
+            semantic_tokens: self.features.semantic_tokens
+                && !self.dynamic_registration.semantic_tokens,
+            signature_help: self.features.signature_help
+                && !self.dynamic_registration.signature_help,
+            workspace_symbols: self.features.workspace_symbols
+                && !self.dynamic_registration.workspace_symbols,
+        };
+        self.capabilities(features)
+    }
+
+    /// Get the registrations of the features the client can register dynamically, for the `client/registerCapability`
+    /// request. Servers can enable and disable these features at runtime, e.g. when their settings change, by
+    /// registering and unregistering them. The id of each registration is its method.
+    ///
+    /// # Arguments
+    ///
+    /// * `features` - The features to register, only those compiled in and registrable dynamically are.
+    pub fn registrations(&self, features: &EnabledFeatures) -> Vec<Registration> {
+        let features = features.compiled();
+        let capabilities = serde_json::to_value(self.capabilities(&features)).unwrap_or_default();
+        let document_selector = [CssDialect::Css, CssDialect::Scss, CssDialect::Less]
+            .map(|dialect| json!({ "language": dialect.language_id() }));
+
+        features
+            .registrable()
+            .into_iter()
+            .zip(self.dynamic_registration.registrable())
+            .filter(|((enabled, ..), (dynamic, ..))| *enabled && *dynamic)
+            .filter_map(|((_, method, key), _)| {
+                let mut register_options = match &capabilities[key] {
+                    Value::Object(options) => options.clone(),
+                    Value::Bool(true) => Default::default(),
+                    // Features without capabilities, e.g. pulled diagnostics for clients pushed to
+                    _ => return None,
+                };
+                if method != "workspace/symbol" {
+                    register_options
+                        .insert("documentSelector".to_string(), json!(document_selector));
+                }
+
+                Some(Registration {
+                    id: method.to_string(),
+                    method: method.to_string(),
+                    register_options: Some(register_options.into()),
+                })
+            })
+            .collect()
+    }
+
+    fn capabilities(&self, features: &EnabledFeatures) -> ServerCapabilities {
+        ServerCapabilities {
+            position_encoding: Some(self.position_encoding.kind()),
+            text_document_sync: Some(TextDocumentSyncCapability::Kind(
+                TextDocumentSyncKind::INCREMENTAL,
+            )),
+            code_action_provider: features
+                .code_actions
+                .then_some(CodeActionProviderCapability::Simple(true)),
+            color_provider: features
+                .colors
+                .then_some(ColorProviderCapability::Simple(true)),
+            completion_provider: features.completion.then(|| CompletionOptions {
+                trigger_characters: Some(self.completion_trigger_characters.clone()),
+                ..Default::default()
+            }),
+            definition_provider: features.definition.then_some(OneOf::Left(true)),
+            references_provider: features.definition.then_some(OneOf::Left(true)),
+            diagnostic_provider: (features.diagnostics && self.pull_diagnostics).then(|| {
+                DiagnosticServerCapabilities::Options(DiagnosticOptions {
+                    identifier: Some("csslsrs".to_string()),
+                    inter_file_dependencies: false,
+                    workspace_diagnostics: true,
+                    ..Default::default()
+                })
+            }),
+            document_link_provider: features.document_links.then(|| DocumentLinkOptions {
+                resolve_provider: Some(false),
+                work_done_progress_options: Default::default(),
+            }),
+            document_symbol_provider: features.document_symbols.then_some(OneOf::Left(true)),
+            folding_range_provider: features
+                .folding
+                .then_some(FoldingRangeProviderCapability::Simple(true)),
+            hover_provider: features
+                .hover
+                .then_some(HoverProviderCapability::Simple(true)),
+            inlay_hint_provider: features.inlay_hints.then(|| {
+                OneOf::Right(InlayHintServerCapabilities::Options(InlayHintOptions {
+                    resolve_provider: Some(self.resolve_inlay_hints),
+                    ..Default::default()
+                }))
+            }),
+            // Only the clients supporting `textDocument/prepareRename` can be told that it is provided
+            rename_provider: features.rename.then(|| {
+                if self.prepare_rename {
+                    OneOf::Right(RenameOptions {
+                        prepare_provider: Some(true),
+                        work_done_progress_options: Default::default(),
+                    })
+                } else {
+                    OneOf::Left(true)
+                }
+            }),
+            semantic_tokens_provider: self.semantic_tokens_provider(features),
+            signature_help_provider: features.signature_help.then(|| SignatureHelpOptions {
+                trigger_characters: Some(
+                    SIGNATURE_HELP_TRIGGER_CHARACTERS
+                        .iter()
+                        .map(|character| character.to_string())
+                        .collect(),
+                ),
+                ..Default::default()
+            }),
+            workspace_symbol_provider: features.workspace_symbols.then_some(OneOf::Left(true)),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(feature = "semantic-tokens")]
+    fn semantic_tokens_provider(
+        &self,
+        features: &EnabledFeatures,
+    ) -> Option<lsp_types::SemanticTokensServerCapabilities> {
+        use lsp_types::{SemanticTokensFullOptions, SemanticTokensOptions};
+
+        features.semantic_tokens.then(|| {
+            SemanticTokensOptions {
+                legend: crate::features::semantic_tokens::semantic_tokens_legend(),
+                full: Some(SemanticTokensFullOptions::Bool(true)),
+                ..Default::default()
+            }
+            .into()
+        })
+    }
+
+    #[cfg(not(feature = "semantic-tokens"))]
+    fn semantic_tokens_provider(
+        &self,
+        _features: &EnabledFeatures,
+    ) -> Option<lsp_types::SemanticTokensServerCapabilities> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{
+        FoldingRangeClientCapabilities, GeneralClientCapabilities, InlayHintClientCapabilities,
+        InlayHintResolveClientCapabilities, PositionEncodingKind, RenameClientCapabilities,
+        TextDocumentClientCapabilities,
+    };
+
+    use crate::converters::WideEncoding;
+
+    use super::*;
+
+    fn client_capabilities(
+        position_encodings: Vec<PositionEncodingKind>,
+        text_document: TextDocumentClientCapabilities,
+    ) -> ClientCapabilities {
+        ClientCapabilities {
+            general: Some(GeneralClientCapabilities {
+                position_encodings: Some(position_encodings),
+                ..Default::default()
+            }),
+            text_document: Some(text_document),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_position_encoding_negotiation() {
+        let negotiate = |encodings| {
+            ServerCapabilitiesBuilder::from_client_capabilities(
+                &client_capabilities(encodings, Default::default()),
+                &EnabledFeatures::default(),
+            )
+        };
+
+        let negotiated = negotiate(vec![
+            PositionEncodingKind::UTF16,
+            PositionEncodingKind::UTF8,
+        ]);
+        assert!(matches!(
+            negotiated.position_encoding(),
+            PositionEncoding::Utf8
+        ));
+        assert_eq!(
+            negotiated.build().position_encoding,
+            Some(PositionEncodingKind::UTF8)
+        );
+
+        assert!(matches!(
+            negotiate(vec![PositionEncodingKind::UTF32]).position_encoding(),
+            PositionEncoding::Wide(WideEncoding::Utf32)
+        ));
+        assert!(matches!(
+            ServerCapabilitiesBuilder::from_client_capabilities(
+                &ClientCapabilities::default(),
+                &EnabledFeatures::default()
+            )
+            .position_encoding(),
+            PositionEncoding::Wide(WideEncoding::Utf16)
+        ));
+    }
+
+    #[test]
+    fn test_server_capabilities() {
+        let client = client_capabilities(
+            vec![PositionEncodingKind::UTF16],
+            TextDocumentClientCapabilities {
+                folding_range: Some(FoldingRangeClientCapabilities {
+                    range_limit: Some(5000),
+                    ..Default::default()
+                }),
+                inlay_hint: Some(InlayHintClientCapabilities {
+                    resolve_support: Some(InlayHintResolveClientCapabilities {
+                        properties: vec!["tooltip".to_string()],
+                    }),
+                    ..Default::default()
+                }),
+                rename: Some(RenameClientCapabilities {
+                    prepare_support: Some(true),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        );
+        let negotiated = ServerCapabilitiesBuilder::from_client_capabilities(
+            &client,
+            &EnabledFeatures {
+                folding: false,
+                ..Default::default()
+            },
+        )
+        .completion_trigger_characters(["-"]);
+
+        assert_eq!(negotiated.folding_range_limit(), Some(5000));
+        assert!(!negotiated.pull_diagnostics());
+
+        let capabilities = negotiated.build();
+        assert_eq!(capabilities.folding_range_provider, None);
+        // Diagnostics are pushed to clients that can't pull them
+        assert_eq!(capabilities.diagnostic_provider, None);
+        assert_eq!(
+            capabilities.text_document_sync,
+            Some(TextDocumentSyncCapability::Kind(
+                TextDocumentSyncKind::INCREMENTAL
+            ))
+        );
+
+        #[cfg(feature = "completion")]
+        assert_eq!(
+            capabilities
+                .completion_provider
+                .and_then(|completion| completion.trigger_characters),
+            Some(vec!["-".to_string()])
+        );
+        #[cfg(feature = "inlay-hints")]
+        assert_eq!(
+            capabilities.inlay_hint_provider,
+            Some(OneOf::Right(InlayHintServerCapabilities::Options(
+                InlayHintOptions {
+                    resolve_provider: Some(true),
+                    ..Default::default()
+                }
+            )))
+        );
+        #[cfg(feature = "colors")]
+        assert_eq!(
+            capabilities.color_provider,
+            Some(ColorProviderCapability::Simple(true))
+        );
+        #[cfg(feature = "code-actions")]
+        assert_eq!(
+            capabilities.code_action_provider,
+            Some(CodeActionProviderCapability::Simple(true))
+        );
+        #[cfg(feature = "rename")]
+        assert_eq!(
+            capabilities.rename_provider,
+            Some(OneOf::Right(RenameOptions {
+                prepare_provider: Some(true),
+                work_done_progress_options: Default::default(),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_dynamic_registration() {
+        let client = client_capabilities(
+            vec![PositionEncodingKind::UTF16],
+            TextDocumentClientCapabilities {
+                folding_range: Some(FoldingRangeClientCapabilities {
+                    dynamic_registration: Some(true),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        );
+        let negotiated = ServerCapabilitiesBuilder::from_client_capabilities(
+            &client,
+            &EnabledFeatures::default(),
+        );
+
+        // Features registered dynamically aren't advertised in the response to `initialize`
+        assert_eq!(negotiated.build().folding_range_provider, None);
+
+        #[cfg(feature = "folding")]
+        assert_eq!(
+            negotiated.registrations(&EnabledFeatures::default()),
+            vec![Registration {
+                id: "textDocument/foldingRange".to_string(),
+                method: "textDocument/foldingRange".to_string(),
+                register_options: Some(json!({
+                    "documentSelector": [{ "language": "css" }, { "language": "scss" }, { "language": "less" }]
+                })),
+            }]
+        );
+
+        assert_eq!(
+            negotiated.registrations(&EnabledFeatures {
+                folding: false,
+                ..Default::default()
+            }),
+            Vec::new()
+        );
+    }
+}
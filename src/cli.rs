@@ -0,0 +1,436 @@
+//! The `csslsrs` command line interface, running the features of a LanguageService over the stylesheets on disk without
+//! an editor, e.g. to check the stylesheets of a project in CI:
+//!
+//! - `csslsrs check [<globs>...]`: report the diagnostics of the stylesheets matching the globs, every stylesheet of
+//!   the current folder by default. Exits with `1` if any diagnostic is an error.
+//! - `csslsrs colors [<globs>...]`, `csslsrs symbols [<globs>...]` and `csslsrs links [<globs>...]`: print the colors,
+//!   the document symbols or the document links of the stylesheets as JSON lines, one object per result with the
+//!   `uri` of its stylesheet, for build tools and scripts, e.g. to extract the palette of a stylesheet.
+//!
+//! Options:
+//!
+//! - `--format=<format>`: the output of `check`, `human`, the default, `json` or `sarif`, for the code scanning of CI
+//!   services.
+//! - `--settings=<file>`: a JSON file with the settings of the server, as sent in its `initializationOptions`.
+//!
+//! Globs are relative to the current folder, e.g. `src/**/*.scss`, and paths to stylesheets or folders are accepted as
+//! is. Like the workspace index, hidden folders and `node_modules` are skipped.
+
+use std::{
+    fmt::Write,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, TextDocumentItem, Uri};
+use serde_json::{json, Value};
+
+use crate::{
+    cancellation::CancellationToken,
+    file_system::FileSystemProvider,
+    parser::CssDialect,
+    server::{LocalFileSystem, ServerSettings},
+    service::LanguageService,
+    settings::SettingsScope,
+};
+
+pub const USAGE: &str = "usage: csslsrs <check | colors | symbols | links> [--format=human|json|sarif] [--settings=<file>] [<globs>...]";
+
+/// A command of the CLI, the first argument of `csslsrs`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Report the diagnostics of stylesheets.
+    Check,
+    Colors,
+    Symbols,
+    Links,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(command: &str) -> Result<Self, Self::Err> {
+        match command {
+            "check" => Ok(Command::Check),
+            "colors" => Ok(Command::Colors),
+            "symbols" => Ok(Command::Symbols),
+            "links" => Ok(Command::Links),
+            _ => Err(format!("unknown command `{command}`")),
+        }
+    }
+}
+
+/// How the results of a command are printed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// One line per result, for people.
+    #[default]
+    Human,
+    Json,
+    /// The Static Analysis Results Interchange Format 2.1.0, read by the code scanning of CI services.
+    Sarif,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "human" => Ok(OutputFormat::Human),
+            "json" => Ok(OutputFormat::Json),
+            "sarif" => Ok(OutputFormat::Sarif),
+            _ => Err(format!("unknown format `{format}`")),
+        }
+    }
+}
+
+/// The diagnostics of a stylesheet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocumentDiagnostics {
+    pub uri: Uri,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Get the `file://` URI of an absolute path, percent-encoding the characters URIs don't allow.
+pub fn file_uri(path: &Path) -> io::Result<Uri> {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+    // The paths of Windows URIs start with a slash before their drive, e.g. `/C:/Users`
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/:".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            let _ = write!(uri, "%{byte:02X}");
+        }
+    }
+
+    Uri::from_str(&uri).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("`{}` can't be a URI", path),
+        )
+    })
+}
+
+/// Create a LanguageService reading the stylesheets of a folder from disk, with the settings of a JSON file if any.
+pub fn language_service(settings: Option<&Path>) -> io::Result<LanguageService> {
+    let mut language_service = LanguageService::default();
+    language_service.set_file_system_provider(LocalFileSystem);
+
+    if let Some(path) = settings {
+        let settings: ServerSettings =
+            serde_json::from_str(&fs::read_to_string(path)?).map_err(|error| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid settings in `{}`: {error}", path.display()),
+                )
+            })?;
+        language_service.update_settings(settings.settings);
+    }
+
+    Ok(language_service)
+}
+
+/// Find the stylesheets matching globs or paths relative to a folder, after indexing the stylesheets of the folder, in
+/// the order of their URIs. Without globs, every stylesheet of the folder is returned.
+pub fn find_stylesheets(
+    language_service: &LanguageService,
+    folder: &Path,
+    globs: &[String],
+) -> io::Result<Vec<Uri>> {
+    let folder_uri = file_uri(folder)?;
+    language_service
+        .index_workspace_folder(&folder_uri, &CancellationToken::default())
+        .map_err(|_| io::Error::new(io::ErrorKind::Interrupted, "indexing was cancelled"))?;
+
+    let mut files = Vec::new();
+    let mut scopes = Vec::new();
+    for glob in globs {
+        let path = folder.join(glob);
+        if path.is_file() {
+            files.push(file_uri(&path.canonicalize()?)?);
+            continue;
+        }
+        let pattern = if path.is_dir() {
+            format!("{}/**", glob.trim_end_matches('/'))
+        } else {
+            glob.clone()
+        };
+        scopes.push(SettingsScope::Glob(format!(
+            "{}/{}",
+            folder_uri.path().as_str().trim_end_matches('/'),
+            pattern.trim_start_matches("./")
+        )));
+    }
+
+    let mut stylesheets = language_service
+        .workspace_index()
+        .documents()
+        .iter()
+        .map(|document| document.uri.clone())
+        .filter(|uri| uri.as_str().starts_with(folder_uri.as_str()))
+        .filter(|uri| globs.is_empty() || scopes.iter().any(|scope| scope.matches(uri)))
+        .chain(files)
+        .collect::<Vec<_>>();
+    stylesheets.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    stylesheets.dedup();
+    Ok(stylesheets)
+}
+
+/// Read a stylesheet from disk, with the language of its extension.
+fn read_document(uri: &Uri) -> io::Result<TextDocumentItem> {
+    let text = FileSystemProvider::read_file(&LocalFileSystem, uri)
+        .map_err(|error| io::Error::new(error.kind(), format!("{}: {error}", uri.as_str())))?;
+    Ok(TextDocumentItem {
+        uri: uri.clone(),
+        language_id: CssDialect::from_uri(uri).language_id().to_string(),
+        version: 0,
+        text,
+    })
+}
+
+/// Get the diagnostics of stylesheets on disk. Stylesheets that can't be read are reported as an error.
+pub fn check(
+    language_service: &LanguageService,
+    stylesheets: &[Uri],
+) -> io::Result<Vec<DocumentDiagnostics>> {
+    stylesheets
+        .iter()
+        .map(|uri| {
+            Ok(DocumentDiagnostics {
+                uri: uri.clone(),
+                diagnostics: language_service.get_diagnostics(read_document(uri)?),
+            })
+        })
+        .collect()
+}
+
+/// Get the results of a command for stylesheets on disk as JSON lines: one object per result, e.g. a
+/// `ColorInformation`, with the `uri` of its stylesheet. The diagnostics of `check` are printed the same way.
+pub fn json_lines(
+    language_service: &LanguageService,
+    command: Command,
+    stylesheets: &[Uri],
+) -> io::Result<String> {
+    let mut output = String::new();
+    for uri in stylesheets {
+        let document = read_document(uri)?;
+        let results = match command {
+            Command::Check => json!(language_service.get_diagnostics(document)),
+            Command::Colors => json!(language_service.get_document_colors(document)),
+            Command::Symbols => json!(language_service.get_document_symbols(document)),
+            Command::Links => json!(language_service.get_document_links(document)),
+        };
+        let Value::Array(results) = results else {
+            continue;
+        };
+
+        for mut result in results {
+            if let Value::Object(result) = &mut result {
+                result.insert("uri".to_string(), json!(uri));
+            }
+            let _ = writeln!(output, "{result}");
+        }
+    }
+    Ok(output)
+}
+
+/// The exit code of `check`: `1` if any diagnostic is an error, `0` otherwise.
+pub fn exit_code(results: &[DocumentDiagnostics]) -> i32 {
+    let has_errors = results
+        .iter()
+        .flat_map(|result| &result.diagnostics)
+        .any(|diagnostic| diagnostic.severity == Some(DiagnosticSeverity::ERROR));
+    i32::from(has_errors)
+}
+
+/// Get the path of a stylesheet to print, relative to a folder if it is in it.
+fn display_path(uri: &Uri, folder: &Path) -> String {
+    let path = uri
+        .path()
+        .as_estr()
+        .decode()
+        .into_string_lossy()
+        .into_owned();
+    let prefix = format!("{}/", folder.to_string_lossy().replace('\\', "/"));
+    path.strip_prefix(&prefix).unwrap_or(&path).to_string()
+}
+
+fn severity_name(severity: Option<DiagnosticSeverity>) -> &'static str {
+    match severity {
+        Some(DiagnosticSeverity::ERROR) => "error",
+        Some(DiagnosticSeverity::INFORMATION) => "info",
+        Some(DiagnosticSeverity::HINT) => "hint",
+        _ => "warning",
+    }
+}
+
+fn code(diagnostic: &Diagnostic) -> Option<String> {
+    match diagnostic.code.as_ref()? {
+        NumberOrString::Number(code) => Some(code.to_string()),
+        NumberOrString::String(code) => Some(code.clone()),
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {noun}"),
+        _ => format!("{count} {noun}s"),
+    }
+}
+
+/// Format the diagnostics of stylesheets, with their paths relative to a folder in the human format.
+pub fn format_diagnostics(
+    results: &[DocumentDiagnostics],
+    format: OutputFormat,
+    folder: &Path,
+) -> String {
+    match format {
+        OutputFormat::Human => {
+            let mut output = String::new();
+            let (mut errors, mut warnings) = (0, 0);
+            for result in results {
+                let path = display_path(&result.uri, folder);
+                for diagnostic in &result.diagnostics {
+                    match diagnostic.severity {
+                        Some(DiagnosticSeverity::ERROR) => errors += 1,
+                        Some(DiagnosticSeverity::WARNING) | None => warnings += 1,
+                        _ => {}
+                    }
+                    let start = diagnostic.range.start;
+                    let _ = write!(
+                        output,
+                        "{path}:{}:{}: {}",
+                        start.line + 1,
+                        start.character + 1,
+                        severity_name(diagnostic.severity)
+                    );
+                    if let Some(code) = code(diagnostic) {
+                        let _ = write!(output, " [{code}]");
+                    }
+                    let _ = writeln!(output, " {}", diagnostic.message);
+                }
+            }
+            let _ = writeln!(
+                output,
+                "{} and {} in {}",
+                plural(errors, "error"),
+                plural(warnings, "warning"),
+                plural(results.len(), "file")
+            );
+            output
+        }
+        OutputFormat::Json => {
+            let results = results
+                .iter()
+                .map(|result| json!({ "uri": result.uri, "diagnostics": result.diagnostics }))
+                .collect::<Vec<_>>();
+            format!("{}\n", Value::Array(results))
+        }
+        OutputFormat::Sarif => format!("{}\n", sarif(results)),
+    }
+}
+
+/// Get the SARIF log of the diagnostics of stylesheets, with a rule per code of their diagnostics.
+fn sarif(results: &[DocumentDiagnostics]) -> Value {
+    let mut rules = results
+        .iter()
+        .flat_map(|result| &result.diagnostics)
+        .filter_map(code)
+        .collect::<Vec<_>>();
+    rules.sort();
+    rules.dedup();
+
+    let sarif_results = results
+        .iter()
+        .flat_map(|result| {
+            result.diagnostics.iter().map(|diagnostic| {
+                let level = match diagnostic.severity {
+                    Some(DiagnosticSeverity::ERROR) => "error",
+                    Some(DiagnosticSeverity::WARNING) | None => "warning",
+                    _ => "note",
+                };
+                let range = diagnostic.range;
+                let mut sarif_result = json!({
+                    "level": level,
+                    "message": { "text": diagnostic.message },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": result.uri },
+                            "region": {
+                                "startLine": range.start.line + 1,
+                                "startColumn": range.start.character + 1,
+                                "endLine": range.end.line + 1,
+                                "endColumn": range.end.character + 1,
+                            },
+                        },
+                    }],
+                });
+                if let Some(code) = code(diagnostic) {
+                    sarif_result["ruleId"] = json!(code);
+                }
+                sarif_result
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "version": "2.1.0",
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "csslsrs",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules.iter().map(|rule| json!({ "id": rule })).collect::<Vec<_>>(),
+                },
+            },
+            "results": sarif_results,
+        }],
+    })
+}
+
+/// The arguments following the command of the CLI.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Options {
+    pub format: OutputFormat,
+    pub settings: Option<PathBuf>,
+    pub globs: Vec<String>,
+}
+
+impl Options {
+    /// Parse the arguments following the command, e.g. `--format=sarif src/**/*.css`.
+    pub fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = args.peekable();
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                options.globs.push(arg);
+                continue;
+            }
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            // Values can also be passed as the next argument, e.g. `--format json`
+            let value = || {
+                value
+                    .or_else(|| args.next_if(|next| !next.starts_with("--")))
+                    .ok_or_else(|| format!("`{name}` needs a value"))
+            };
+
+            match name.as_str() {
+                "--format" => options.format = value()?.parse()?,
+                "--settings" => options.settings = Some(PathBuf::from(value()?)),
+                _ => return Err(format!("unknown argument `{name}`")),
+            }
+        }
+
+        Ok(options)
+    }
+}
@@ -0,0 +1,320 @@
+//! Commands executed with `workspace/executeCommand`, e.g. those of the code lenses and code actions of a client.
+//!
+//! A LanguageService starts with the built-in commands below, and hosts add their own with
+//! `LanguageService::add_command`. As in the LSP, the arguments of a command are JSON values, and so is its result.
+
+use lsp_types::{Location, Position, Uri};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{
+    cancellation::{CancellationToken, Cancelled},
+    converters::{from_proto, to_proto},
+    error::Error,
+    progress::{ProgressReporter, StepProgress},
+    service::LanguageService,
+    workspace_index::IndexedSymbol,
+};
+
+/// Apply the fixes of every diagnostic of a document. Its argument is the URI of the document, its result the
+/// WorkspaceEdit to apply, see `LanguageService::get_fix_all_edit`. Without arguments, every document of the workspace
+/// index is fixed, see `LanguageService::get_workspace_fix_all_edit`. Only available with the `diagnostics` feature.
+pub const APPLY_FIX_ALL: &str = "csslsrs.applyFixAll";
+
+/// Find the occurrences of the symbol at a position in the workspace. Its arguments are the URI of the document and
+/// the position, its result the `Location`s of the occurrences, see `LanguageService::get_references`.
+pub const SHOW_REFERENCES: &str = "csslsrs.showReferences";
+
+/// Index the stylesheets of workspace folders again, e.g. after files changed outside of the editor. Its arguments are
+/// the URIs of the folders, its result whether anything was indexed, `false` if the service has no FileSystemProvider.
+pub const REINDEX_WORKSPACE: &str = "csslsrs.reindexWorkspace";
+
+/// A command that can be executed by a LanguageService.
+pub trait Command: Send + Sync {
+    /// The name of the command, e.g. `csslsrs.applyFixAll`. Commands added later replace those with the same name.
+    fn name(&self) -> &str;
+
+    /// Execute the command, returning its result if it has one.
+    fn execute(
+        &self,
+        language_service: &LanguageService,
+        arguments: Vec<Value>,
+    ) -> Result<Option<Value>, Error>;
+
+    /// Same as `execute`, reporting the progress of the command to `progress`. Commands that take a while, e.g. on a
+    /// whole workspace, should implement it, the default implementation only calls `execute`.
+    fn execute_with_progress(
+        &self,
+        language_service: &LanguageService,
+        arguments: Vec<Value>,
+        _progress: &mut dyn ProgressReporter,
+    ) -> Result<Option<Value>, Error> {
+        self.execute(language_service, arguments)
+    }
+}
+
+/// Deserialize the argument at `index`, naming the argument in the error if it is missing or invalid.
+fn argument<T: DeserializeOwned>(
+    arguments: &[Value],
+    index: usize,
+    name: &str,
+) -> Result<T, Error> {
+    let value = arguments
+        .get(index)
+        .ok_or_else(|| Error::InvalidArguments(format!("missing `{name}`")))?;
+
+    serde_json::from_value(value.clone())
+        .map_err(|error| Error::InvalidArguments(format!("`{name}`: {error}")))
+}
+
+fn to_value(value: impl serde::Serialize) -> Result<Option<Value>, Error> {
+    serde_json::to_value(value)
+        .map(Some)
+        .map_err(|error| Error::Serialization(error.to_string()))
+}
+
+#[cfg(feature = "diagnostics")]
+struct ApplyFixAll;
+
+#[cfg(feature = "diagnostics")]
+impl Command for ApplyFixAll {
+    fn name(&self) -> &str {
+        APPLY_FIX_ALL
+    }
+
+    fn execute(
+        &self,
+        language_service: &LanguageService,
+        arguments: Vec<Value>,
+    ) -> Result<Option<Value>, Error> {
+        self.execute_with_progress(language_service, arguments, &mut ())
+    }
+
+    fn execute_with_progress(
+        &self,
+        language_service: &LanguageService,
+        arguments: Vec<Value>,
+        progress: &mut dyn ProgressReporter,
+    ) -> Result<Option<Value>, Error> {
+        if arguments.is_empty() {
+            let cancellation = CancellationToken::default();
+            return to_value(language_service.get_workspace_fix_all_edit(&cancellation, progress)?);
+        }
+
+        let uri: Uri = argument(&arguments, 0, "uri")?;
+        to_value(language_service.get_fix_all_edit(&uri)?)
+    }
+}
+
+struct ShowReferences;
+
+impl Command for ShowReferences {
+    fn name(&self) -> &str {
+        SHOW_REFERENCES
+    }
+
+    fn execute(
+        &self,
+        language_service: &LanguageService,
+        arguments: Vec<Value>,
+    ) -> Result<Option<Value>, Error> {
+        let uri: Uri = argument(&arguments, 0, "uri")?;
+        let position: Position = argument(&arguments, 1, "position")?;
+        to_value(language_service.get_references(&uri, position)?)
+    }
+}
+
+struct ReindexWorkspace;
+
+impl Command for ReindexWorkspace {
+    fn name(&self) -> &str {
+        REINDEX_WORKSPACE
+    }
+
+    fn execute(
+        &self,
+        language_service: &LanguageService,
+        arguments: Vec<Value>,
+    ) -> Result<Option<Value>, Error> {
+        self.execute_with_progress(language_service, arguments, &mut ())
+    }
+
+    fn execute_with_progress(
+        &self,
+        language_service: &LanguageService,
+        arguments: Vec<Value>,
+        progress: &mut dyn ProgressReporter,
+    ) -> Result<Option<Value>, Error> {
+        let folders = (0..arguments.len())
+            .map(|index| argument::<Uri>(&arguments, index, "folder"))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The folders are reported as a single operation
+        progress.begin("Indexing the workspace");
+        let cancellation = CancellationToken::default();
+        let indexed = folders.iter().try_fold(false, |indexed, folder| {
+            let folder_indexed = language_service.index_workspace_folder_with_progress(
+                folder,
+                &cancellation,
+                &mut StepProgress(progress),
+            )?;
+            Ok::<_, Cancelled>(indexed | folder_indexed)
+        });
+        progress.end();
+
+        to_value(indexed?)
+    }
+}
+
+/// The commands every LanguageService starts with.
+pub(crate) fn builtin_commands() -> Vec<Box<dyn Command>> {
+    vec![
+        #[cfg(feature = "diagnostics")]
+        Box::new(ApplyFixAll),
+        Box::new(ShowReferences),
+        Box::new(ReindexWorkspace),
+    ]
+}
+
+impl LanguageService {
+    /// Get the names of the commands of the service, e.g. for the `executeCommandProvider` capability of a server.
+    pub fn command_names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        for command in &self.commands {
+            if !names.iter().any(|name| name == command.name()) {
+                names.push(command.name().to_string());
+            }
+        }
+        names
+    }
+
+    /// Execute a command, as requested by `workspace/executeCommand`.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The name of the command, e.g. `csslsrs.applyFixAll`.
+    /// * `arguments` - The arguments of the command.
+    ///
+    /// # Returns
+    ///
+    /// * The result of the command, or `Error::UnknownCommand` if the service has no command with this name.
+    pub fn execute_command(
+        &self,
+        command: &str,
+        arguments: Vec<Value>,
+    ) -> Result<Option<Value>, Error> {
+        self.execute_command_with_progress(command, arguments, &mut ())
+    }
+
+    /// Same as `execute_command`, reporting the progress of the command to `progress`, see
+    /// `Command::execute_with_progress`.
+    pub fn execute_command_with_progress(
+        &self,
+        command: &str,
+        arguments: Vec<Value>,
+        progress: &mut dyn ProgressReporter,
+    ) -> Result<Option<Value>, Error> {
+        self.commands
+            .iter()
+            .rev()
+            .find(|registered| registered.name() == command)
+            .ok_or_else(|| Error::UnknownCommand(command.to_string()))?
+            .execute_with_progress(self, arguments, progress)
+    }
+
+    /// Find every occurrence of the symbol at a position, e.g. of a custom property or a class, in the documents of the
+    /// workspace index.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    /// * `position` - A position in the name of the symbol.
+    ///
+    /// # Returns
+    ///
+    /// * The locations of the declarations and references of the symbol, sorted by document and position, and empty
+    ///   if there is no symbol at the position.
+    pub fn get_references(&self, uri: &Uri, position: Position) -> Result<Vec<Location>, Error> {
+        self.symbol_occurrences(uri, position, |_| true)
+    }
+
+    /// Same as `get_references`, only keeping the occurrences matching `filter`, e.g. the declarations of the symbol.
+    pub(crate) fn symbol_occurrences(
+        &self,
+        uri: &Uri,
+        position: Position,
+        filter: impl Fn(&IndexedSymbol) -> bool,
+    ) -> Result<Vec<Location>, Error> {
+        let store_entry = self.open_entry(uri)?;
+        let offset = from_proto::offset(
+            store_entry.line_index(),
+            position,
+            self.encoding_for(&store_entry),
+        )?;
+
+        let index = self.workspace_index();
+        let Some(symbol) = index.document(uri).and_then(|document| {
+            document
+                .symbols
+                .iter()
+                .find(|symbol| symbol.range.contains_inclusive(offset))
+                .cloned()
+        }) else {
+            return Ok(Vec::new());
+        };
+
+        let mut locations = index
+            .find(symbol.kind, &symbol.name)
+            .into_iter()
+            .filter(|(_, symbol)| filter(symbol))
+            .filter_map(|(document, symbol)| {
+                let encoding = document
+                    .entry()
+                    .map_or(self.encoding, |entry| self.encoding_for(entry));
+                let range = to_proto::range(document.line_index(), symbol.range, encoding).ok()?;
+                Some(Location::new(document.uri.clone(), range))
+            })
+            .collect::<Vec<_>>();
+        locations
+            .sort_by(|a, b| (a.uri.as_str(), a.range.start).cmp(&(b.uri.as_str(), b.range.start)));
+
+        Ok(locations)
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod wasm_bindings {
+    use crate::service::wasm_bindings::WasmLanguageService;
+    use crate::{error::Error, wasm_text_document};
+    use serde_json::Value;
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_APPEND_CONTENT: &'static str = r#"
+export interface LanguageService {
+	/**
+	 * Execute a command, e.g. `csslsrs.applyFixAll` with the URI of an open document.
+	 *
+	 * @param command The name of the command.
+	 * @param args The arguments of the command.
+	 * @returns The result of the command, `null` if it has none.
+	 */
+	executeCommand(command: string, args?: any[]): any;
+}
+"#;
+
+    #[wasm_bindgen(js_class = LanguageService)]
+    impl WasmLanguageService {
+        #[wasm_bindgen(js_name = executeCommand, skip_typescript)]
+        pub fn execute_command(&self, command: String, args: JsValue) -> Result<JsValue, Error> {
+            let arguments: Vec<Value> = if args.is_undefined() {
+                Vec::new()
+            } else {
+                wasm_text_document::from_value(args, "args")?
+            };
+            let result = self.language_service.execute_command(&command, arguments)?;
+
+            wasm_text_document::to_value(&result)
+        }
+    }
+}
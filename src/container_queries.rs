@@ -0,0 +1,137 @@
+//! Helpers for the queries of `@container` rules and the containers they query, declared by the `container-name` and
+//! `container` properties. Shared by the workspace index and the features completing and checking queries.
+
+use biome_css_syntax::{
+    CssContainerAtRule, CssCustomIdentifier, CssGenericProperty, CssSyntaxKind,
+};
+use biome_rowan::{AstNode, AstNodeList, TextRange};
+
+/// A feature of the size queries of `@container`, e.g. `inline-size` in `@container (inline-size > 400px)`.
+#[cfg(any(feature = "completion", feature = "diagnostics"))]
+pub(crate) struct SizeFeature {
+    pub(crate) name: &'static str,
+    #[cfg_attr(not(feature = "completion"), allow(dead_code))]
+    pub(crate) description: &'static str,
+    /// Whether the feature can be compared, e.g. `(width > 400px)`, and prefixed with `min-` and `max-`.
+    pub(crate) is_range: bool,
+}
+
+/// The size features of CSS Containment Module Level 3.
+#[cfg(any(feature = "completion", feature = "diagnostics"))]
+pub(crate) const SIZE_FEATURES: &[SizeFeature] = &[
+    SizeFeature {
+        name: "width",
+        description: "The width of the query container's content box.",
+        is_range: true,
+    },
+    SizeFeature {
+        name: "height",
+        description: "The height of the query container's content box.",
+        is_range: true,
+    },
+    SizeFeature {
+        name: "inline-size",
+        description: "The size of the query container's content box in its inline axis.",
+        is_range: true,
+    },
+    SizeFeature {
+        name: "block-size",
+        description: "The size of the query container's content box in its block axis.",
+        is_range: true,
+    },
+    SizeFeature {
+        name: "aspect-ratio",
+        description: "The ratio of the width to the height of the query container's content box.",
+        is_range: true,
+    },
+    SizeFeature {
+        name: "orientation",
+        description: "The orientation of the query container, `portrait` or `landscape`.",
+        is_range: false,
+    },
+];
+
+/// Get the size feature with the given name, ignoring the `min-` and `max-` prefixes of range features.
+///
+/// # Returns
+///
+/// * The feature and whether the name is prefixed, or `None` if the name isn't a size feature.
+#[cfg(feature = "diagnostics")]
+pub(crate) fn size_feature(name: &str) -> Option<(&'static SizeFeature, bool)> {
+    let name = name.to_ascii_lowercase();
+    let (name, is_prefixed) = match name
+        .strip_prefix("min-")
+        .or_else(|| name.strip_prefix("max-"))
+    {
+        Some(unprefixed) => (unprefixed, true),
+        None => (name.as_str(), false),
+    };
+
+    SIZE_FEATURES
+        .iter()
+        .find(|feature| feature.name == name && (feature.is_range || !is_prefixed))
+        .map(|feature| (feature, is_prefixed))
+}
+
+/// Get the name of the container queried by a `@container` rule, e.g. `sidebar` in `@container sidebar (width > 400px)`.
+///
+/// The parser reads the keyword or function starting a query without a name as the name, e.g. `not` in
+/// `@container not (width > 400px)` or `style` in `@container style(--theme: dark)`, so they aren't returned.
+pub(crate) fn queried_container(rule: &CssContainerAtRule) -> Option<CssCustomIdentifier> {
+    let name = rule.name()?;
+    let token = name.syntax().first_token()?;
+    let is_keyword = matches!(
+        token.text_trimmed().to_ascii_lowercase().as_str(),
+        "not" | "and" | "or"
+    );
+    let is_function = token.trailing_trivia().pieces().next().is_none()
+        && token
+            .next_token()
+            .is_some_and(|next| next.kind() == CssSyntaxKind::L_PAREN);
+
+    (!is_keyword && !is_function).then_some(name)
+}
+
+/// Get the container names declared by a `container-name` or `container` declaration, e.g. `sidebar` and `main` in
+/// `container-name: sidebar main` or `sidebar` in `container: sidebar / inline-size`.
+pub(crate) fn declared_containers(property: &CssGenericProperty) -> Vec<(String, TextRange)> {
+    let Ok(name) = property.name() else {
+        return Vec::new();
+    };
+    let is_shorthand = match name
+        .syntax()
+        .text_trimmed()
+        .to_string()
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "container-name" => false,
+        "container" => true,
+        _ => return Vec::new(),
+    };
+
+    property
+        .value()
+        .syntax_list()
+        .node()
+        .children()
+        // The names of the shorthand are before its container type, e.g. `container: sidebar / inline-size`
+        .take_while(|value| !(is_shorthand && value.kind() == CssSyntaxKind::CSS_GENERIC_DELIMITER))
+        .filter(|value| value.kind() == CssSyntaxKind::CSS_IDENTIFIER)
+        .map(|value| (value.text_trimmed().to_string(), value.text_trimmed_range()))
+        .filter(|(name, _)| {
+            !matches!(
+                name.to_ascii_lowercase().as_str(),
+                "none"
+                    | "initial"
+                    | "inherit"
+                    | "unset"
+                    | "revert"
+                    | "revert-layer"
+                    | "and"
+                    | "or"
+                    | "not"
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,77 @@
+//! Helpers computing the contrast between colors as defined by WCAG 2, shared by the features checking the colors of
+//! text against their background.
+
+use csscolorparser::Color;
+
+use crate::settings::ContrastLevel;
+
+/// Get the relative luminance of an opaque color, from `0` for black to `1` for white.
+fn relative_luminance(color: &Color) -> f32 {
+    let linear = |component: f32| {
+        if component <= 0.04045 {
+            component / 12.92
+        } else {
+            ((component + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(color.r) + 0.7152 * linear(color.g) + 0.0722 * linear(color.b)
+}
+
+/// Get the contrast ratio of a foreground color on an opaque background, from `1` for the same colors to `21` for
+/// black on white. A translucent foreground is blended with the background first.
+pub(crate) fn contrast_ratio(foreground: &Color, background: &Color) -> f32 {
+    let blend = |foreground_component: f32, background_component: f32| {
+        foreground_component * foreground.a + background_component * (1.0 - foreground.a)
+    };
+    let foreground = Color::new(
+        blend(foreground.r, background.r),
+        blend(foreground.g, background.g),
+        blend(foreground.b, background.b),
+        1.0,
+    );
+
+    let foreground = relative_luminance(&foreground);
+    let background = relative_luminance(background);
+    (foreground.max(background) + 0.05) / (foreground.min(background) + 0.05)
+}
+
+/// Get the minimum contrast ratio of text at a level of conformance of WCAG 2, lower for large text.
+pub(crate) fn minimum_ratio(level: ContrastLevel, large_text: bool) -> f32 {
+    match (level, large_text) {
+        (ContrastLevel::Aa, false) => 4.5,
+        (ContrastLevel::Aa, true) => 3.0,
+        (ContrastLevel::Aaa, false) => 7.0,
+        (ContrastLevel::Aaa, true) => 4.5,
+    }
+}
+
+/// Whether text is large for WCAG 2: at least 18pt, i.e. 24px, or 14pt, i.e. about 18.66px, when bold.
+pub(crate) fn is_large_text(font_size_px: f32, bold: bool) -> bool {
+    font_size_px >= 24.0 || (bold && font_size_px >= 18.66)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contrast_ratio() {
+        let ratio = |foreground: &str, background: &str| {
+            let ratio = contrast_ratio(
+                &csscolorparser::parse(foreground).unwrap(),
+                &csscolorparser::parse(background).unwrap(),
+            );
+            (ratio * 100.0).round() / 100.0
+        };
+
+        assert_eq!(ratio("black", "white"), 21.0);
+        assert_eq!(ratio("white", "black"), 21.0);
+        assert_eq!(ratio("#777", "#fff"), 4.48);
+        assert_eq!(ratio("red", "red"), 1.0);
+        // Half transparent black on white is blended into a gray
+        assert_eq!(
+            ratio("rgb(0 0 0 / 50%)", "white"),
+            ratio("rgb(50% 50% 50%)", "white")
+        );
+    }
+}
@@ -1,13 +1,18 @@
+//! Conversions from the positions of the LSP to offsets.
+
 use crate::converters::line_index::LineIndex;
 use crate::converters::{LineCol, PositionEncoding, WideLineCol};
+use crate::error::Error;
+use crate::instrumentation::warning;
 use biome_rowan::{TextRange, TextSize};
 
-/// The function is used to convert a LSP position to TextSize.
-pub(crate) fn offset(
+/// Convert a LSP position to the offset of the same position, `Error::InvalidPosition` if its line is past the end of
+/// the document. As required by the LSP, a column past the end of its line stands for the end of the line.
+pub fn offset(
     line_index: &LineIndex,
     position: lsp_types::Position,
     position_encoding: PositionEncoding,
-) -> Result<TextSize, ()> {
+) -> Result<TextSize, Error> {
     let line_col = match position_encoding {
         PositionEncoding::Utf8 => LineCol {
             line: position.line,
@@ -22,18 +27,78 @@ pub(crate) fn offset(
         }
     };
 
-    line_index.offset(line_col).ok_or(())
+    let (Some(offset), Some(line_end)) = (
+        line_index.offset(line_col),
+        line_index.line_end(line_col.line),
+    ) else {
+        return Err(Error::InvalidPosition(position));
+    };
+
+    if offset > line_end {
+        warning!(
+            "position clamped to the end of its line",
+            line = position.line,
+            character = position.character
+        );
+        return Ok(line_end);
+    }
+
+    Ok(offset)
+}
+
+/// Same as `offset`, but a position past the end of the document stands for the end of the document, e.g. for the
+/// ranges sent by clients whose view of the document is slightly out of date.
+pub fn clamped_offset(
+    line_index: &LineIndex,
+    position: lsp_types::Position,
+    position_encoding: PositionEncoding,
+) -> TextSize {
+    offset(line_index, position, position_encoding).unwrap_or_else(|_| {
+        warning!(
+            "position clamped to the end of the document",
+            line = position.line,
+            character = position.character
+        );
+        line_index.text_len()
+    })
 }
 
-/// The function is used to convert a LSP range to TextRange.
-#[allow(dead_code)]
-pub(crate) fn text_range(
+/// Convert a LSP range to the range of offsets of the same range, `Error::InvalidRange` if it is past the end of the
+/// document or ends before it starts.
+pub fn text_range(
     line_index: &LineIndex,
     range: lsp_types::Range,
     position_encoding: PositionEncoding,
-) -> Result<TextRange, ()> {
-    let start = offset(line_index, range.start, position_encoding)?;
-    let end = offset(line_index, range.end, position_encoding)?;
+) -> Result<TextRange, Error> {
+    let invalid_range = |_| Error::InvalidRange(range);
+    let start = offset(line_index, range.start, position_encoding).map_err(invalid_range)?;
+    let end = offset(line_index, range.end, position_encoding).map_err(invalid_range)?;
+
+    if start > end {
+        return Err(Error::InvalidRange(range));
+    }
 
     Ok(TextRange::new(start, end))
 }
+
+/// Same as `text_range`, but positions past the end of the document stand for the end of the document, and a range
+/// ending before it starts is reversed, as done by `vscode-languageserver-textdocument`.
+pub fn clamped_text_range(
+    line_index: &LineIndex,
+    range: lsp_types::Range,
+    position_encoding: PositionEncoding,
+) -> TextRange {
+    let start = clamped_offset(line_index, range.start, position_encoding);
+    let end = clamped_offset(line_index, range.end, position_encoding);
+
+    if start > end {
+        warning!(
+            "range ending before it starts reversed",
+            start_line = range.start.line,
+            end_line = range.end.line
+        );
+        return TextRange::new(end, start);
+    }
+
+    TextRange::new(start, end)
+}
@@ -1,24 +1,60 @@
 //! `LineIndex` maps flat `TextSize` offsets into `(Line, Column)`
 //! representation.
+//!
+//! As specified by the LSP, lines end with `\n`, `\r\n` or `\r`. Other Unicode line separators, such as U+2028, are
+//! part of their line.
 
 use std::mem;
 
 use crate::converters::{LineCol, WideChar, WideEncoding, WideLineCol};
-use biome_rowan::TextSize;
+use biome_rowan::{TextRange, TextSize};
 use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
 
+/// The line break ending a line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+    Cr,
+}
+
+impl LineEnding {
+    /// Get the text of the line break, e.g. to insert new lines matching the style of a document.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+
+    fn size(self) -> TextSize {
+        TextSize::of(self.as_str())
+    }
+}
+
+/// The start of each line of a document and its non-ASCII characters, to convert offsets to positions in any
+/// encoding without scanning the text again.
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub(crate) struct LineIndex {
+pub struct LineIndex {
     /// Offset the beginning of each line, zero-based.
     pub(crate) newlines: Vec<TextSize>,
     /// List of non-ASCII characters on each line.
     pub(crate) line_wide_chars: FxHashMap<u32, Vec<WideChar>>,
+    /// The line break of each line not ending with `\n`.
+    pub(crate) line_endings: FxHashMap<u32, LineEnding>,
+    /// Length of the text.
+    pub(crate) text_len: TextSize,
 }
 
 impl LineIndex {
+    /// Index the lines of `text`.
     pub fn new(text: &str) -> LineIndex {
         let mut line_wide_chars = FxHashMap::default();
         let mut wide_chars = Vec::new();
+        let mut line_endings = FxHashMap::default();
 
         let mut newlines = vec![TextSize::from(0)];
 
@@ -28,7 +64,16 @@ impl LineIndex {
         for (offset, char) in text.char_indices() {
             let char_size = TextSize::of(char);
 
-            if char == '\n' {
+            if char == '\r' {
+                if text.as_bytes().get(offset + 1) == Some(&b'\n') {
+                    // The line ends with the following `\n`
+                    line_endings.insert(line, LineEnding::CrLf);
+                    continue;
+                }
+                line_endings.insert(line, LineEnding::Cr);
+            }
+
+            if char == '\n' || char == '\r' {
                 // SAFETY: the conversion from `usize` to `TextSize` can fail if `offset`
                 // is larger than 2^32. We don't support such large files.
                 let char_offset = TextSize::try_from(offset).expect("TextSize overflow");
@@ -63,7 +108,154 @@ impl LineIndex {
         LineIndex {
             newlines,
             line_wide_chars,
+            line_endings,
+            text_len: TextSize::of(text),
+        }
+    }
+
+    /// Update the index after the text in `range` was replaced by `new_text`, without rescanning
+    /// the rest of the text: the start of the lines after the edit are shifted, and only the
+    /// non-ASCII characters of the lines touched by the edit are recomputed.
+    ///
+    /// Edits involving a `\r` can create or split a `\r\n`, so neither `new_text` nor the replaced
+    /// text may contain one, and `range` may not start right after one. Callers rebuild the index
+    /// for such edits, see `DocumentStore::update_document`.
+    pub(crate) fn apply_edit(&mut self, range: TextRange, new_text: &str) {
+        let Some(start) = self.line_col(range.start()) else {
+            return;
+        };
+        let Some(end) = self.line_col(range.end()) else {
+            return;
+        };
+
+        // The starts of the lines created by `new_text`, and the wide characters on each of them
+        let mut new_lines = Vec::new();
+        let mut new_wide_chars = vec![Vec::new()];
+        let mut current_col = TextSize::from(start.col);
+        for (offset, char) in new_text.char_indices() {
+            let char_size = TextSize::of(char);
+
+            if char == '\n' {
+                let char_offset = TextSize::try_from(offset).expect("TextSize overflow");
+                new_lines.push(range.start() + char_offset + char_size);
+                new_wide_chars.push(Vec::new());
+                current_col = TextSize::from(0);
+                continue;
+            }
+
+            if !char.is_ascii() {
+                new_wide_chars
+                    .last_mut()
+                    .expect("there is always a current line")
+                    .push(WideChar {
+                        start: current_col,
+                        end: current_col + char_size,
+                    });
+            }
+
+            current_col += char_size;
+        }
+
+        // Shift the lines after the edit, replacing the ones that started inside of it
+        let new_length = TextSize::of(new_text);
+        let first_line = start.line as usize + 1;
+        let last_line = end.line as usize + 1;
+        let shifted = self.newlines[last_line..]
+            .iter()
+            .map(|&offset| offset - range.len() + new_length)
+            .collect::<Vec<_>>();
+        self.newlines.truncate(first_line);
+        self.newlines.extend(new_lines);
+        self.newlines.extend(shifted);
+        self.text_len = self.text_len - range.len() + new_length;
+
+        // The first line touched by the edit keeps its characters before the edit, and the last
+        // one its characters after the edit, at a new column
+        let end_col = TextSize::from(end.col);
+        let mut line_wide_chars = FxHashMap::default();
+        for (line, wide_chars) in mem::take(&mut self.line_wide_chars) {
+            if line < start.line {
+                line_wide_chars.insert(line, wide_chars);
+                continue;
+            }
+            if line == start.line {
+                new_wide_chars[0].splice(
+                    0..0,
+                    wide_chars
+                        .iter()
+                        .filter(|c| c.end <= TextSize::from(start.col))
+                        .cloned(),
+                );
+            }
+            if line == end.line {
+                new_wide_chars
+                    .last_mut()
+                    .expect("there is always a current line")
+                    .extend(
+                        wide_chars
+                            .iter()
+                            .filter(|c| c.start >= end_col)
+                            .map(|c| WideChar {
+                                start: c.start - end_col + current_col,
+                                end: c.end - end_col + current_col,
+                            }),
+                    );
+            } else if line > end.line {
+                let new_line = line - end.line + start.line + new_wide_chars.len() as u32 - 1;
+                line_wide_chars.insert(new_line, wide_chars);
+            }
+        }
+        let new_wide_chars_len = new_wide_chars.len();
+        for (line, wide_chars) in (start.line..).zip(new_wide_chars) {
+            if !wide_chars.is_empty() {
+                line_wide_chars.insert(line, wide_chars);
+            }
+        }
+        self.line_wide_chars = line_wide_chars;
+
+        // Only the lines from the end of the edit can end with a `\r`, and they are shifted
+        let added_lines = new_wide_chars_len as u32 - 1;
+        self.line_endings = mem::take(&mut self.line_endings)
+            .into_iter()
+            .map(|(line, line_ending)| {
+                if line < start.line {
+                    (line, line_ending)
+                } else {
+                    (line - end.line + start.line + added_lines, line_ending)
+                }
+            })
+            .collect();
+    }
+
+    /// Get the line ending style of the text, the line break used by most lines, `LineEnding::Lf` if there are none.
+    pub fn line_ending_style(&self) -> LineEnding {
+        let mut crlf = 0;
+        let mut cr = 0;
+        for line_ending in self.line_endings.values() {
+            match line_ending {
+                LineEnding::CrLf => crlf += 1,
+                LineEnding::Cr => cr += 1,
+                LineEnding::Lf => {}
+            }
         }
+        let lf = self.newlines.len() - 1 - crlf - cr;
+
+        if crlf > lf && crlf >= cr {
+            LineEnding::CrLf
+        } else if cr > lf && cr > crlf {
+            LineEnding::Cr
+        } else {
+            LineEnding::Lf
+        }
+    }
+
+    /// Get the line break ending a line, `None` for the last line.
+    pub fn line_break(&self, line: u32) -> Option<LineEnding> {
+        if line as usize + 1 >= self.newlines.len() {
+            return None;
+        }
+
+        Some(self.line_endings.get(&line).copied().unwrap_or_default())
     }
 
     /// Return the number of lines in the index, clamped to [u32::MAX]
@@ -72,6 +264,7 @@ impl LineIndex {
         self.newlines.len().try_into().unwrap_or(u32::MAX)
     }
 
+    /// Get the line and the column in bytes of an offset.
     pub fn line_col(&self, offset: TextSize) -> Option<LineCol> {
         let line = self.newlines.partition_point(|&it| it <= offset) - 1;
         let line_start_offset = self.newlines.get(line)?;
@@ -83,13 +276,32 @@ impl LineIndex {
         })
     }
 
-    #[allow(dead_code)]
+    /// Get the offset of a line and a column in bytes, `None` if the line is past the end of the text.
     pub fn offset(&self, line_col: LineCol) -> Option<TextSize> {
         self.newlines
             .get(line_col.line as usize)
             .map(|offset| offset + TextSize::from(line_col.col))
     }
 
+    /// Get the offset of the end of a line, before its line break, `None` if the line is past the end of the text.
+    pub fn line_end(&self, line: u32) -> Option<TextSize> {
+        let line = line as usize;
+        if line >= self.newlines.len() {
+            return None;
+        }
+
+        Some(match self.line_break(line as u32) {
+            Some(line_ending) => self.newlines[line + 1] - line_ending.size(),
+            None => self.text_len,
+        })
+    }
+
+    /// Get the length of the text.
+    pub fn text_len(&self) -> TextSize {
+        self.text_len
+    }
+
+    /// Convert the column of a position from bytes to UTF-16 or UTF-32 code units.
     pub fn to_wide(&self, enc: WideEncoding, line_col: LineCol) -> Option<WideLineCol> {
         let col = self.utf8_to_wide_col(enc, line_col.line, line_col.col.into());
         Some(WideLineCol {
@@ -98,7 +310,7 @@ impl LineIndex {
         })
     }
 
-    #[allow(dead_code)]
+    /// Convert the column of a position from UTF-16 or UTF-32 code units to bytes.
     pub fn to_utf8(&self, enc: WideEncoding, line_col: WideLineCol) -> LineCol {
         let col = self.wide_to_utf8_col(enc, line_col.line, line_col.col);
         LineCol {
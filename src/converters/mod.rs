@@ -1,27 +1,93 @@
+//! Conversions between the offsets of biome, `TextSize` and `TextRange` in bytes, and the positions of the LSP, lines
+//! and columns in a `PositionEncoding`.
+//!
+//! Embedders mixing the results of csslsrs with their own analyses can use them to convert offsets the same way the
+//! LanguageService does, with the `LineIndex` of a document (see `StoreEntry::line_index`) or a new one:
+//!
+//! ```rust
+//! use csslsrs::biome_rowan::TextSize;
+//! use csslsrs::converters::{from_proto, line_index::LineIndex, to_proto, PositionEncoding};
+//! use lsp_types::Position;
+//!
+//! let line_index = LineIndex::new("a {}\n/* 🎨 */ b {}");
+//! let encoding = PositionEncoding::default();
+//!
+//! let position = to_proto::position(&line_index, TextSize::from(16), encoding).unwrap();
+//! assert_eq!(position, Position::new(1, 9));
+//! assert_eq!(
+//!     from_proto::offset(&line_index, position, encoding),
+//!     Ok(TextSize::from(16))
+//! );
+//! ```
+
 use biome_rowan::TextSize;
+use lsp_types::PositionEncodingKind;
 
-pub(crate) mod from_proto;
-pub(crate) mod line_index;
-pub(crate) mod to_proto;
+pub mod from_proto;
+pub mod line_index;
+pub(crate) mod rope;
+pub mod to_proto;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PositionEncoding {
     Utf8,
     Wide(WideEncoding),
 }
 
+/// UTF-16, the encoding of the LSP unless the client and the server agree on another one.
+impl Default for PositionEncoding {
+    fn default() -> Self {
+        PositionEncoding::Wide(WideEncoding::Utf16)
+    }
+}
+
+impl PositionEncoding {
+    /// Get the encoding identified by a `PositionEncodingKind` of the LSP, e.g. `utf-8`.
+    pub fn from_kind(kind: &PositionEncodingKind) -> Option<Self> {
+        match kind.as_str() {
+            "utf-8" => Some(PositionEncoding::Utf8),
+            "utf-16" => Some(PositionEncoding::Wide(WideEncoding::Utf16)),
+            "utf-32" => Some(PositionEncoding::Wide(WideEncoding::Utf32)),
+            _ => None,
+        }
+    }
+
+    /// Get the `PositionEncodingKind` of the LSP identifying the encoding.
+    pub fn kind(self) -> PositionEncodingKind {
+        match self {
+            PositionEncoding::Utf8 => PositionEncodingKind::UTF8,
+            PositionEncoding::Wide(WideEncoding::Utf16) => PositionEncodingKind::UTF16,
+            PositionEncoding::Wide(WideEncoding::Utf32) => PositionEncodingKind::UTF32,
+        }
+    }
+
+    /// Choose an encoding among those supported by a client, as sent in its `general.positionEncodings` capability.
+    /// UTF-8 is preferred, as it doesn't need any conversion, then UTF-32, and UTF-16 otherwise, as required by the
+    /// LSP.
+    pub fn negotiate(supported: &[PositionEncodingKind]) -> Self {
+        if supported.contains(&PositionEncodingKind::UTF8) {
+            PositionEncoding::Utf8
+        } else if supported.contains(&PositionEncodingKind::UTF32) {
+            PositionEncoding::Wide(WideEncoding::Utf32)
+        } else {
+            PositionEncoding::default()
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum WideEncoding {
     Utf16,
     Utf32,
 }
 
+/// A position in a document, with its column in bytes.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub(crate) struct LineCol {
+pub struct LineCol {
     /// Zero-based
-    pub(crate) line: u32,
+    pub line: u32,
     /// Zero-based utf8 offset
-    pub(crate) col: u32,
+    pub col: u32,
 }
 
 /// Deliberately not a generic type and different from `LineCol`.
@@ -65,13 +131,14 @@ impl WideChar {
 
 #[cfg(test)]
 mod tests {
-    use crate::converters::from_proto::offset;
-    use crate::converters::line_index::LineIndex;
+    use crate::converters::from_proto::{clamped_text_range, offset, text_range};
+    use crate::converters::line_index::{LineEnding, LineIndex};
     use crate::converters::to_proto::position;
     use crate::converters::WideEncoding::{Utf16, Utf32};
     use crate::converters::{LineCol, PositionEncoding, WideEncoding};
-    use biome_rowan::TextSize;
-    use lsp_types::Position;
+    use crate::error::Error;
+    use biome_rowan::{TextRange, TextSize};
+    use lsp_types::{Position, PositionEncodingKind, Range};
 
     macro_rules! check_conversion {
         ($line_index:ident : $position:expr => $text_size:expr ) => {
@@ -112,6 +179,96 @@ mod tests {
         assert!(offset.is_none());
     }
 
+    #[test]
+    fn invalid_positions() {
+        let line_index = LineIndex::new("abc\ndef");
+        let encoding = PositionEncoding::default();
+
+        let position = Position::new(2, 0);
+        assert_eq!(
+            offset(&line_index, position, encoding),
+            Err(Error::InvalidPosition(position))
+        );
+
+        let past_end = Range::new(Position::new(0, 0), position);
+        assert_eq!(
+            text_range(&line_index, past_end, encoding),
+            Err(Error::InvalidRange(past_end))
+        );
+
+        let reversed = Range::new(Position::new(1, 0), Position::new(0, 2));
+        assert_eq!(
+            text_range(&line_index, reversed, encoding),
+            Err(Error::InvalidRange(reversed))
+        );
+    }
+
+    #[test]
+    fn clamped_positions() {
+        let line_index = LineIndex::new("abc\ndé");
+        let encoding = PositionEncoding::default();
+
+        // Columns past the end of their line are clamped, as required by the LSP
+        assert_eq!(
+            offset(&line_index, Position::new(0, 10), encoding),
+            Ok(TextSize::from(3))
+        );
+        assert_eq!(
+            offset(&line_index, Position::new(1, 10), encoding),
+            Ok(TextSize::from(7))
+        );
+
+        assert_eq!(
+            clamped_text_range(
+                &line_index,
+                Range::new(Position::new(1, 1), Position::new(5, 0)),
+                encoding
+            ),
+            TextRange::new(TextSize::from(5), TextSize::from(7))
+        );
+        assert_eq!(
+            clamped_text_range(
+                &line_index,
+                Range::new(Position::new(1, 0), Position::new(0, 1)),
+                encoding
+            ),
+            TextRange::new(TextSize::from(1), TextSize::from(4))
+        );
+    }
+
+    #[test]
+    fn line_breaks() {
+        let line_index = LineIndex::new("a\r\nb\rc\nd\u{2028}e");
+
+        check_conversion!(line_index: Position { line: 1, character: 0 } => TextSize::from(3));
+        check_conversion!(line_index: Position { line: 2, character: 0 } => TextSize::from(5));
+        check_conversion!(line_index: Position { line: 2, character: 1 } => TextSize::from(6));
+        // U+2028 doesn't break lines in the LSP
+        check_conversion!(line_index: Position { line: 3, character: 2 } => TextSize::from(11));
+
+        assert_eq!(line_index.line_break(0), Some(LineEnding::CrLf));
+        assert_eq!(line_index.line_break(1), Some(LineEnding::Cr));
+        assert_eq!(line_index.line_break(2), Some(LineEnding::Lf));
+        assert_eq!(line_index.line_break(3), None);
+
+        // Columns are clamped before the line break
+        assert_eq!(
+            offset(
+                &line_index,
+                Position::new(0, 5),
+                PositionEncoding::default()
+            ),
+            Ok(TextSize::from(1))
+        );
+
+        assert_eq!(line_index.line_ending_style(), LineEnding::Lf);
+        assert_eq!(
+            LineIndex::new("a\r\nb\r\nc\n").line_ending_style(),
+            LineEnding::CrLf
+        );
+        assert_eq!(LineIndex::new("a\rb").line_ending_style(), LineEnding::Cr);
+    }
+
     #[test]
     fn unicode() {
         let line_index = LineIndex::new("'Jan 1, 2018 – Jan 1, 2019'");
@@ -171,4 +328,63 @@ mod tests {
             }
         }
     }
+
+    fn check_apply_edit(text: &str, start: u32, end: u32, new_text: &str) {
+        let mut line_index = LineIndex::new(text);
+        line_index.apply_edit(
+            TextRange::new(TextSize::from(start), TextSize::from(end)),
+            new_text,
+        );
+
+        let mut edited_text = text.to_string();
+        edited_text.replace_range(start as usize..end as usize, new_text);
+
+        assert_eq!(line_index, LineIndex::new(&edited_text));
+    }
+
+    #[test]
+    fn apply_edit() {
+        // Edits on a single line
+        check_apply_edit("a { }\nb { }", 3, 3, "color: red;");
+        check_apply_edit("a { }\nb { }", 0, 1, "é");
+        // Edits adding or removing lines
+        check_apply_edit("a { }\nb { }\nc { }", 3, 3, "\n  color: red;\n");
+        check_apply_edit("a { }\nb { }\nc { }", 2, 9, "");
+        check_apply_edit("a\nb\nc\nd\n", 2, 6, "x\ny");
+        // Edits around non-ASCII characters, on the edited lines and after them
+        check_apply_edit("é { } ü\nà { }\n– ü", 3, 3, "\n💡\n");
+        check_apply_edit("é { } ü\nà { }\n– ü", 5, 12, "");
+        check_apply_edit("é ü\nà\n– ü", 0, TextSize::of("é ü\nà\n– ü").into(), "ü\n");
+    }
+
+    #[test]
+    fn position_encoding_kind() {
+        for encoding in [
+            PositionEncoding::Utf8,
+            PositionEncoding::Wide(Utf16),
+            PositionEncoding::Wide(Utf32),
+        ] {
+            assert_eq!(
+                PositionEncoding::from_kind(&encoding.kind()),
+                Some(encoding)
+            );
+        }
+        assert_eq!(
+            PositionEncoding::from_kind(&PositionEncodingKind::new("utf-7")),
+            None
+        );
+
+        assert_eq!(
+            PositionEncoding::negotiate(&[PositionEncodingKind::UTF16, PositionEncodingKind::UTF8]),
+            PositionEncoding::Utf8
+        );
+        assert_eq!(
+            PositionEncoding::negotiate(&[PositionEncodingKind::UTF32]),
+            PositionEncoding::Wide(Utf32)
+        );
+        assert_eq!(
+            PositionEncoding::negotiate(&[]),
+            PositionEncoding::Wide(Utf16)
+        );
+    }
 }
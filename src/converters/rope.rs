@@ -0,0 +1,137 @@
+//! Conversions from LSP positions to offsets in a `Rope`, used to apply incremental changes to a
+//! document without rebuilding its `LineIndex` after every change.
+
+use crate::converters::{PositionEncoding, WideEncoding};
+use crate::instrumentation::warning;
+use biome_rowan::{TextRange, TextSize};
+use ropey::Rope;
+
+/// The function is used to convert a LSP position to the TextSize of the same position in `rope`. As in
+/// `from_proto::clamped_offset`, a column past the end of its line stands for the end of the line, and a line past the
+/// end of the document for the end of the document. Only positions in the middle of a character are invalid.
+pub(crate) fn offset(
+    rope: &Rope,
+    position: lsp_types::Position,
+    position_encoding: PositionEncoding,
+) -> Result<TextSize, ()> {
+    let line = position.line as usize;
+    if line >= rope.len_lines() {
+        warning!(
+            "position clamped to the end of the document",
+            line = position.line,
+            character = position.character
+        );
+        return TextSize::try_from(rope.len_bytes()).map_err(|_| ());
+    }
+
+    let column = position.character as usize;
+    let line_start = rope.line_to_char(line);
+    let line_end = line_end(rope, line);
+
+    let char_index = match position_encoding {
+        PositionEncoding::Utf8 => {
+            let byte = rope.char_to_byte(line_start) + column;
+            let line_end_byte = rope.char_to_byte(line_end);
+            if byte > line_end_byte {
+                clamped(line_end, position)
+            } else {
+                // Positions in the middle of a character are invalid
+                let char_index = rope.byte_to_char(byte);
+                if rope.char_to_byte(char_index) != byte {
+                    return Err(());
+                }
+                char_index
+            }
+        }
+        PositionEncoding::Wide(WideEncoding::Utf16) => {
+            let code_unit = rope.char_to_utf16_cu(line_start) + column;
+            if code_unit > rope.char_to_utf16_cu(line_end) {
+                clamped(line_end, position)
+            } else {
+                // Positions in the middle of a surrogate pair are invalid
+                let char_index = rope.utf16_cu_to_char(code_unit);
+                if rope.char_to_utf16_cu(char_index) != code_unit {
+                    return Err(());
+                }
+                char_index
+            }
+        }
+        PositionEncoding::Wide(WideEncoding::Utf32) => {
+            let char_index = line_start + column;
+            if char_index > line_end {
+                clamped(line_end, position)
+            } else {
+                char_index
+            }
+        }
+    };
+
+    TextSize::try_from(rope.char_to_byte(char_index)).map_err(|_| ())
+}
+
+/// The char index of the end of a line of `rope`, before its line break. Ropes break lines on `\n`, `\r\n` and `\r`,
+/// as `LineIndex` does.
+fn line_end(rope: &Rope, line: usize) -> usize {
+    let slice = rope.line(line);
+    let mut len = slice.len_chars();
+
+    if len > 0 && slice.char(len - 1) == '\n' {
+        len -= 1;
+    }
+    if len > 0 && slice.char(len - 1) == '\r' {
+        len -= 1;
+    }
+
+    rope.line_to_char(line) + len
+}
+
+fn clamped(line_end: usize, position: lsp_types::Position) -> usize {
+    warning!(
+        "position clamped to the end of its line",
+        line = position.line,
+        character = position.character
+    );
+    line_end
+}
+
+/// The function is used to convert a LSP range to the TextRange of the same range in `rope`, reversing ranges that end
+/// before they start as in `from_proto::clamped_text_range`.
+pub(crate) fn text_range(
+    rope: &Rope,
+    range: lsp_types::Range,
+    position_encoding: PositionEncoding,
+) -> Result<TextRange, ()> {
+    let start = offset(rope, range.start, position_encoding)?;
+    let end = offset(rope, range.end, position_encoding)?;
+
+    if start > end {
+        warning!(
+            "range ending before it starts reversed",
+            start_line = range.start.line,
+            end_line = range.end.line
+        );
+        return Ok(TextRange::new(end, start));
+    }
+
+    Ok(TextRange::new(start, end))
+}
+
+/// Whether replacing the text in `range` with `text` involves a `\r`, which can create or split a `\r\n`, in which
+/// case a `LineIndex` can't be updated by `LineIndex::apply_edit`.
+pub(crate) fn edits_carriage_return(rope: &Rope, range: TextRange, text: &str) -> bool {
+    let start = rope.byte_to_char(range.start().into());
+    let end = rope.byte_to_char(range.end().into());
+
+    text.contains('\r')
+        || (start > 0 && rope.char(start - 1) == '\r')
+        || rope.slice(start..end).chars().any(|char| char == '\r')
+}
+
+/// Replace the text in `range` with `text`.
+pub(crate) fn replace(rope: &mut Rope, range: TextRange, text: &str) {
+    let start = rope.byte_to_char(range.start().into());
+    let end = rope.byte_to_char(range.end().into());
+
+    rope.remove(start..end);
+    rope.insert(start, text);
+}
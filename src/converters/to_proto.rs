@@ -1,19 +1,27 @@
+//! Conversions from offsets to the positions of the LSP.
+
 use crate::converters::line_index::LineIndex;
 use crate::converters::PositionEncoding;
+use crate::error::Error;
 use biome_rowan::{TextRange, TextSize};
 
-/// The function is used to convert TextSize to a LSP position.
-pub(crate) fn position(
+/// Convert an offset to the LSP position of the same position, `Error::InvalidOffset` if it can't be represented in
+/// the encoding.
+pub fn position(
     line_index: &LineIndex,
     offset: TextSize,
     position_encoding: PositionEncoding,
-) -> Result<lsp_types::Position, ()> {
-    let line_col = line_index.line_col(offset).ok_or(())?;
+) -> Result<lsp_types::Position, Error> {
+    let line_col = line_index
+        .line_col(offset)
+        .ok_or(Error::InvalidOffset(offset))?;
 
     let position = match position_encoding {
         PositionEncoding::Utf8 => lsp_types::Position::new(line_col.line, line_col.col),
         PositionEncoding::Wide(enc) => {
-            let line_col = line_index.to_wide(enc, line_col).ok_or(())?;
+            let line_col = line_index
+                .to_wide(enc, line_col)
+                .ok_or(Error::InvalidOffset(offset))?;
             lsp_types::Position::new(line_col.line, line_col.col)
         }
     };
@@ -21,12 +29,13 @@ pub(crate) fn position(
     Ok(position)
 }
 
-/// The function is used to convert TextRange to a LSP range.
-pub(crate) fn range(
+/// Convert a range of offsets to the LSP range of the same range, `Error::InvalidOffset` if one of its ends can't be
+/// represented in the encoding.
+pub fn range(
     line_index: &LineIndex,
     range: TextRange,
     position_encoding: PositionEncoding,
-) -> Result<lsp_types::Range, ()> {
+) -> Result<lsp_types::Range, Error> {
     let start = position(line_index, range.start(), position_encoding)?;
     let end = position(line_index, range.end(), position_encoding)?;
     Ok(lsp_types::Range::new(start, end))
@@ -0,0 +1,96 @@
+//! Helpers for the counter styles defined by `@counter-style` rules and used by the `list-style-type` and
+//! `list-style` properties, shared by the workspace index and the features completing and checking them.
+
+use biome_css_syntax::{CssGenericProperty, CssLanguage, CssSyntaxKind};
+use biome_rowan::{AstNode, AstNodeList, SyntaxNode, TextRange};
+
+use crate::descriptors::descriptor_at_rule;
+
+/// The predefined counter styles that `@counter-style` rules can't redefine.
+#[cfg(feature = "diagnostics")]
+pub(crate) const RESERVED_COUNTER_STYLES: &[&str] = &[
+    "decimal",
+    "disc",
+    "square",
+    "circle",
+    "disclosure-open",
+    "disclosure-closed",
+];
+
+/// The algorithms of the `system` descriptor.
+#[cfg(feature = "diagnostics")]
+pub(crate) const SYSTEMS: &[&str] = &[
+    "cyclic",
+    "numeric",
+    "alphabetic",
+    "symbolic",
+    "additive",
+    "fixed",
+    "extends",
+];
+
+/// Whether an identifier can't name a counter style, as it is a keyword of the values referencing counter styles.
+pub(crate) fn is_reserved_name(name: &str) -> bool {
+    matches!(
+        name.to_ascii_lowercase().as_str(),
+        "none" | "initial" | "inherit" | "unset" | "revert" | "revert-layer" | "default"
+    )
+}
+
+/// Get the counter styles referenced by a declaration, e.g. `thumbs` in `list-style: thumbs inside`, or in the
+/// `fallback` and `system: extends thumbs` descriptors of a `@counter-style` rule.
+pub(crate) fn referenced_counter_styles(property: &CssGenericProperty) -> Vec<(String, TextRange)> {
+    let Ok(name) = property.name() else {
+        return Vec::new();
+    };
+    let name = name
+        .syntax()
+        .text_trimmed()
+        .to_string()
+        .to_ascii_lowercase();
+    let values = property
+        .value()
+        .syntax_list()
+        .node()
+        .children()
+        .collect::<Vec<_>>();
+    let in_counter_style =
+        || descriptor_at_rule(property.syntax()).is_some_and(|at_rule| at_rule == "@counter-style");
+
+    let references: Vec<&SyntaxNode<CssLanguage>> = match name.as_str() {
+        "list-style-type" => values.iter().collect(),
+        "list-style" => values
+            .iter()
+            .filter(|value| {
+                !matches!(
+                    value
+                        .text_trimmed()
+                        .to_string()
+                        .to_ascii_lowercase()
+                        .as_str(),
+                    "inside" | "outside"
+                )
+            })
+            .collect(),
+        "fallback" if in_counter_style() => values.iter().collect(),
+        "system" if in_counter_style() => match values.as_slice() {
+            [system, extended]
+                if system
+                    .text_trimmed()
+                    .to_string()
+                    .eq_ignore_ascii_case("extends") =>
+            {
+                vec![extended]
+            }
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+
+    references
+        .into_iter()
+        .filter(|value| value.kind() == CssSyntaxKind::CSS_IDENTIFIER)
+        .map(|value| (value.text_trimmed().to_string(), value.text_trimmed_range()))
+        .filter(|(name, _)| !is_reserved_name(name))
+        .collect()
+}
@@ -0,0 +1,198 @@
+//! Helpers to find custom property declarations (`--name: value`) and resolve `var()` references
+//! to them, shared by the features that need to know what a custom property evaluates to.
+
+use biome_css_syntax::{CssLanguage, CssSyntaxKind};
+use biome_rowan::{SyntaxNode, TextRange};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// A `var(--name, fallback)` reference found in a value.
+pub(crate) struct VarReference {
+    /// The referenced custom property name, including the leading `--`.
+    pub(crate) name: String,
+    /// The range of the referenced name, without trivia.
+    pub(crate) name_range: TextRange,
+    /// The raw text of the fallback, if any.
+    pub(crate) fallback: Option<String>,
+    /// The range of the whole `var()` function, without trivia.
+    pub(crate) range: TextRange,
+}
+
+/// One step of a resolution chain, e.g. `--primary: var(--blue)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ResolutionStep {
+    pub(crate) name: String,
+    pub(crate) value: String,
+}
+
+/// Collect the custom properties declared in the given tree, mapped to their value node.
+///
+/// The cascade is approximated by letting the last declaration in source order win.
+pub(crate) fn collect_custom_properties(
+    root: &SyntaxNode<CssLanguage>,
+) -> FxHashMap<String, SyntaxNode<CssLanguage>> {
+    let mut declarations = FxHashMap::default();
+
+    for node in root.descendants() {
+        if node.kind() != CssSyntaxKind::CSS_GENERIC_PROPERTY {
+            continue;
+        }
+
+        let Some(name) = node
+            .first_child()
+            .filter(|name| name.kind() == CssSyntaxKind::CSS_IDENTIFIER)
+            .map(|name| name.text_trimmed().to_string())
+        else {
+            continue;
+        };
+
+        if !name.starts_with("--") {
+            continue;
+        }
+
+        if let Some(value) = node
+            .children()
+            .find(|child| child.kind() == CssSyntaxKind::CSS_GENERIC_COMPONENT_VALUE_LIST)
+        {
+            declarations.insert(name, value);
+        }
+    }
+
+    declarations
+}
+
+/// If `node` is a `var()` function, return the custom property it references.
+pub(crate) fn var_reference(node: &SyntaxNode<CssLanguage>) -> Option<VarReference> {
+    if node.kind() != CssSyntaxKind::CSS_FUNCTION {
+        return None;
+    }
+
+    let function_name = node.first_child()?;
+    if !function_name
+        .text_trimmed()
+        .to_string()
+        .eq_ignore_ascii_case("var")
+    {
+        return None;
+    }
+
+    let parameters = node
+        .children()
+        .find(|child| child.kind() == CssSyntaxKind::CSS_PARAMETER_LIST)?;
+    let mut parameters = parameters.children();
+
+    let name = parameters.next()?;
+    let name_range = name.text_trimmed_range();
+    let name = name.text_trimmed().to_string();
+    if !name.starts_with("--") {
+        return None;
+    }
+
+    let fallback = parameters
+        .map(|parameter| parameter.text_trimmed().to_string())
+        .collect::<Vec<_>>();
+
+    Some(VarReference {
+        name,
+        name_range,
+        fallback: (!fallback.is_empty()).then(|| fallback.join(", ")),
+        range: node.text_trimmed_range(),
+    })
+}
+
+/// Resolve the value of the custom property `name`, substituting every `var()` it contains.
+///
+/// Every custom property visited along the way is pushed to `chain`, in resolution order. Returns
+/// `None` if the property is not declared, or if it (transitively) depends on itself.
+#[cfg(feature = "inlay-hints")]
+pub(crate) fn resolve_custom_property(
+    name: &str,
+    declarations: &FxHashMap<String, SyntaxNode<CssLanguage>>,
+    chain: &mut Vec<ResolutionStep>,
+) -> Option<String> {
+    resolve_custom_property_inner(name, declarations, chain, &mut FxHashSet::default())
+}
+
+/// Resolve a value, e.g. the value of a declaration, substituting every `var()` it contains.
+///
+/// Every custom property visited along the way is pushed to `chain`, in resolution order. Returns
+/// `None` if a `var()` can't be resolved and has no fallback.
+pub(crate) fn resolve_value(
+    value: &SyntaxNode<CssLanguage>,
+    declarations: &FxHashMap<String, SyntaxNode<CssLanguage>>,
+    chain: &mut Vec<ResolutionStep>,
+) -> Option<String> {
+    let raw_value = value.text_trimmed().to_string();
+    substitute_var_references(
+        value,
+        &raw_value,
+        declarations,
+        chain,
+        &mut FxHashSet::default(),
+    )
+}
+
+fn resolve_custom_property_inner(
+    name: &str,
+    declarations: &FxHashMap<String, SyntaxNode<CssLanguage>>,
+    chain: &mut Vec<ResolutionStep>,
+    visited: &mut FxHashSet<String>,
+) -> Option<String> {
+    let value = declarations.get(name)?;
+
+    // A custom property referencing itself, directly or not, is invalid at computed-value time.
+    if !visited.insert(name.to_string()) {
+        return None;
+    }
+
+    let raw_value = value.text_trimmed().to_string();
+    chain.push(ResolutionStep {
+        name: name.to_string(),
+        value: raw_value.clone(),
+    });
+
+    let resolved = substitute_var_references(value, &raw_value, declarations, chain, visited);
+
+    visited.remove(name);
+
+    resolved
+}
+
+fn substitute_var_references(
+    value: &SyntaxNode<CssLanguage>,
+    raw_value: &str,
+    declarations: &FxHashMap<String, SyntaxNode<CssLanguage>>,
+    chain: &mut Vec<ResolutionStep>,
+    visited: &mut FxHashSet<String>,
+) -> Option<String> {
+    let value_start = value.text_trimmed_range().start();
+    let mut resolved = String::with_capacity(raw_value.len());
+    let mut last_end = value_start;
+
+    for node in value.descendants() {
+        // Nested `var()` (e.g. in a fallback) are handled when the outer one is resolved.
+        if node.text_trimmed_range().start() < last_end {
+            continue;
+        }
+
+        let Some(reference) = var_reference(&node) else {
+            continue;
+        };
+
+        let substitution =
+            match resolve_custom_property_inner(&reference.name, declarations, chain, visited) {
+                Some(substitution) => substitution,
+                None => reference.fallback?,
+            };
+
+        resolved.push_str(
+            &raw_value[usize::from(last_end - value_start)
+                ..usize::from(reference.range.start() - value_start)],
+        );
+        resolved.push_str(&substitution);
+        last_end = reference.range.end();
+    }
+
+    resolved.push_str(&raw_value[usize::from(last_end - value_start)..]);
+
+    Some(resolved)
+}
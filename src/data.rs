@@ -0,0 +1,641 @@
+//! Data about CSS properties, at-rules, pseudo-classes, pseudo-elements and functions, used by the features needing to
+//! know about the CSS language itself, e.g. completion, hover or validation.
+//!
+//! Data uses the format of VS Code's CSS custom data (the `vscode-css-customdata` schema), so that frameworks already
+//! describing their custom syntax for VS Code (e.g. Tailwind's `@apply`) can load the same files into csslsrs with
+//! `LanguageService::add_custom_data`. The schema is extended with a `functions` list, and with the `initial` value of
+//! properties.
+//!
+//! csslsrs bundles data about the standard CSS syntax, available with `bundled()` and loaded into every
+//! LanguageService before its custom data. It can also be queried directly, e.g. by tools that only need to know
+//! the syntax of a property or which browsers support it:
+//!
+//! ```rust
+//! use csslsrs::data::{self, Browser, DataEntry};
+//!
+//! let gap = data::bundled().property("gap").unwrap();
+//! assert_eq!(gap.syntax.as_deref(), Some("<'row-gap'> <'column-gap'>?"));
+//! assert!(gap.supports(Browser::Firefox).is_some());
+//! ```
+
+use std::sync::OnceLock;
+
+use lsp_types::MarkupContent;
+use rustc_hash::FxHashMap;
+use serde::Deserialize;
+
+/// The description of an entry, either plain text or markup.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Description {
+    PlainText(String),
+    Markup(MarkupContent),
+}
+
+/// The standardization status of an entry.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryStatus {
+    #[default]
+    Standard,
+    Experimental,
+    Nonstandard,
+    Obsolete,
+}
+
+/// A browser, as abbreviated in the `browsers` list of the entries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Browser {
+    /// `E`
+    Edge,
+    /// `FF`
+    Firefox,
+    /// `S`
+    Safari,
+    /// `C`
+    Chrome,
+    /// `IE`
+    InternetExplorer,
+    /// `O`
+    Opera,
+}
+
+impl Browser {
+    /// The name of the browser, e.g. `Firefox`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Browser::Edge => "Edge",
+            Browser::Firefox => "Firefox",
+            Browser::Safari => "Safari",
+            Browser::Chrome => "Chrome",
+            Browser::InternetExplorer => "IE",
+            Browser::Opera => "Opera",
+        }
+    }
+
+    fn from_abbreviation(abbreviation: &str) -> Option<Self> {
+        match abbreviation {
+            "E" => Some(Browser::Edge),
+            "FF" => Some(Browser::Firefox),
+            "S" => Some(Browser::Safari),
+            "C" => Some(Browser::Chrome),
+            "IE" => Some(Browser::InternetExplorer),
+            "O" => Some(Browser::Opera),
+            _ => None,
+        }
+    }
+}
+
+/// A browser supporting an entry, and the first version supporting it if known.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BrowserSupport {
+    pub browser: Browser,
+    pub version: Option<String>,
+}
+
+impl BrowserSupport {
+    /// Parse an item of the `browsers` list of an entry, e.g. `FF69` for Firefox 69. Returns None for unknown browsers.
+    pub fn parse(support: &str) -> Option<Self> {
+        let split = support
+            .find(|char: char| !char.is_ascii_alphabetic())
+            .unwrap_or(support.len());
+        let (abbreviation, version) = support.split_at(split);
+
+        Some(Self {
+            browser: Browser::from_abbreviation(abbreviation)?,
+            version: (!version.is_empty()).then(|| version.to_string()),
+        })
+    }
+}
+
+/// The fields shared by every kind of entry, to query entries without knowing their kind.
+pub trait DataEntry {
+    /// The name of the entry, as written in CSS, e.g. `@media`, `:hover` or `calc()`.
+    fn name(&self) -> &str;
+    fn description(&self) -> Option<&Description>;
+    fn status(&self) -> EntryStatus;
+    /// The browsers supporting the entry, as written in the data, e.g. `FF69` for Firefox 69.
+    fn browsers(&self) -> &[String];
+    fn references(&self) -> &[Reference];
+
+    /// The formal syntax of the entry, e.g. `<color>` for `color`. Only properties and functions have one.
+    fn syntax(&self) -> Option<&str> {
+        None
+    }
+
+    /// The initial value of the entry, e.g. `normal` for `line-height`. Only properties have one.
+    fn initial(&self) -> Option<&str> {
+        None
+    }
+
+    /// The browsers supporting the entry, skipping those this version of csslsrs doesn't know.
+    fn browser_support(&self) -> Vec<BrowserSupport> {
+        self.browsers()
+            .iter()
+            .filter_map(|support| BrowserSupport::parse(support))
+            .collect()
+    }
+
+    /// The documentation of the entry as markdown, e.g. for hovers and completion items: its description, its syntax,
+    /// its initial value, its status if it isn't standard, the browsers supporting it and its references.
+    fn documentation(&self) -> String {
+        let mut sections = Vec::new();
+        match self.description() {
+            Some(Description::PlainText(text)) => sections.push(text.clone()),
+            Some(Description::Markup(markup)) => sections.push(markup.value.clone()),
+            None => {}
+        }
+        if let Some(syntax) = self.syntax() {
+            sections.push(format!("Syntax: `{syntax}`"));
+        }
+        if let Some(initial) = self.initial() {
+            sections.push(format!("Initial value: `{initial}`"));
+        }
+        match self.status() {
+            EntryStatus::Standard => {}
+            EntryStatus::Experimental => sections.push("**Experimental**".to_string()),
+            EntryStatus::Nonstandard => sections.push("**Non-standard**".to_string()),
+            EntryStatus::Obsolete => sections.push("**Obsolete**".to_string()),
+        }
+        let browsers = self
+            .browser_support()
+            .into_iter()
+            .map(|support| match support.version {
+                Some(version) => format!("{} {version}", support.browser.name()),
+                None => support.browser.name().to_string(),
+            })
+            .collect::<Vec<_>>();
+        if !browsers.is_empty() {
+            sections.push(format!("({})", browsers.join(", ")));
+        }
+        let references = self
+            .references()
+            .iter()
+            .map(|reference| format!("[{}]({})", reference.name, reference.url))
+            .collect::<Vec<_>>();
+        if !references.is_empty() {
+            sections.push(references.join(" | "));
+        }
+
+        sections.join("\n\n")
+    }
+
+    /// Whether a browser supports the entry.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<BrowserSupport>` - The support of the browser, or None if it doesn't support the entry or the data
+    ///   doesn't say.
+    fn supports(&self, browser: Browser) -> Option<BrowserSupport> {
+        self.browser_support()
+            .into_iter()
+            .find(|support| support.browser == browser)
+    }
+}
+
+macro_rules! impl_data_entry {
+    ($data:ident) => {
+        impl_data_entry!($data, |_data| None, |_data| None);
+    };
+    ($data:ident, syntax) => {
+        impl_data_entry!($data, |data| data.syntax.as_deref(), |_data| None);
+    };
+    ($data:ident, syntax, initial) => {
+        impl_data_entry!($data, |data| data.syntax.as_deref(), |data| data
+            .initial
+            .as_deref());
+    };
+    ($data:ident, $syntax:expr, $initial:expr) => {
+        impl DataEntry for $data {
+            fn name(&self) -> &str {
+                &self.name
+            }
+
+            fn description(&self) -> Option<&Description> {
+                self.description.as_ref()
+            }
+
+            fn status(&self) -> EntryStatus {
+                self.status
+            }
+
+            fn browsers(&self) -> &[String] {
+                &self.browsers
+            }
+
+            fn references(&self) -> &[Reference] {
+                &self.references
+            }
+
+            fn syntax(&self) -> Option<&str> {
+                let syntax: fn(&Self) -> Option<&str> = $syntax;
+                syntax(self)
+            }
+
+            fn initial(&self) -> Option<&str> {
+                let initial: fn(&Self) -> Option<&str> = $initial;
+                initial(self)
+            }
+        }
+    };
+}
+
+/// A link to the documentation of an entry, e.g. on MDN.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct Reference {
+    pub name: String,
+    pub url: String,
+}
+
+/// A keyword value of a property.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct ValueData {
+    pub name: String,
+    pub description: Option<Description>,
+    /// The browsers supporting the value, e.g. `FF69` for Firefox 69.
+    #[serde(default)]
+    pub browsers: Vec<String>,
+    #[serde(default)]
+    pub status: EntryStatus,
+    #[serde(default)]
+    pub references: Vec<Reference>,
+}
+
+/// A property, e.g. `color`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct PropertyData {
+    pub name: String,
+    pub description: Option<Description>,
+    /// The browsers supporting the property, e.g. `FF69` for Firefox 69.
+    #[serde(default)]
+    pub browsers: Vec<String>,
+    #[serde(default)]
+    pub status: EntryStatus,
+    #[serde(default)]
+    pub references: Vec<Reference>,
+    /// The formal syntax of the property's values, e.g. `<color>`.
+    pub syntax: Option<String>,
+    /// The initial value of the property, e.g. `normal`. Shorthands whose initial value is the one of each of their
+    /// longhands don't have one, e.g. `font`.
+    pub initial: Option<String>,
+    /// The kinds of values accepted by the property, e.g. `color` or `length`.
+    #[serde(default)]
+    pub restrictions: Vec<String>,
+    #[serde(default)]
+    pub values: Vec<ValueData>,
+    /// How often the property is used, from 0 to 100.
+    pub relevance: Option<u32>,
+}
+
+/// An at-rule, e.g. `@media`. Its name includes the `@`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct AtDirectiveData {
+    pub name: String,
+    pub description: Option<Description>,
+    #[serde(default)]
+    pub browsers: Vec<String>,
+    #[serde(default)]
+    pub status: EntryStatus,
+    #[serde(default)]
+    pub references: Vec<Reference>,
+    /// The descriptors of the at-rule's block, described as properties, e.g. `system` in `@counter-style`. Empty for
+    /// the at-rules whose block contains rules or regular properties.
+    #[serde(default)]
+    pub descriptors: Vec<PropertyData>,
+}
+
+/// A pseudo-class or pseudo-element, e.g. `:hover` or `::after`. Its name includes the colons.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct PseudoData {
+    pub name: String,
+    pub description: Option<Description>,
+    #[serde(default)]
+    pub browsers: Vec<String>,
+    #[serde(default)]
+    pub status: EntryStatus,
+    #[serde(default)]
+    pub references: Vec<Reference>,
+}
+
+/// A function, e.g. `calc()`. Its name includes the parentheses.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct FunctionData {
+    pub name: String,
+    pub description: Option<Description>,
+    #[serde(default)]
+    pub browsers: Vec<String>,
+    #[serde(default)]
+    pub status: EntryStatus,
+    #[serde(default)]
+    pub references: Vec<Reference>,
+    /// The formal syntax of the function, e.g. `calc( <calc-sum> )`.
+    pub syntax: Option<String>,
+}
+
+impl_data_entry!(ValueData);
+impl_data_entry!(PropertyData, syntax, initial);
+impl_data_entry!(AtDirectiveData);
+impl_data_entry!(PseudoData);
+impl_data_entry!(FunctionData, syntax);
+
+/// A source of CSS data, e.g. the content of a custom data file.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CssData {
+    /// The version of the schema, `1.1` for current files.
+    pub version: Option<f64>,
+    #[serde(default)]
+    pub properties: Vec<PropertyData>,
+    #[serde(default)]
+    pub at_directives: Vec<AtDirectiveData>,
+    #[serde(default)]
+    pub pseudo_classes: Vec<PseudoData>,
+    #[serde(default)]
+    pub pseudo_elements: Vec<PseudoData>,
+    #[serde(default)]
+    pub functions: Vec<FunctionData>,
+}
+
+impl CssData {
+    /// Parse a custom data file, in the format of VS Code's `css.customData` files.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// The data of every source loaded into a LanguageService, indexed by name.
+///
+/// Names are looked up case-insensitively. When multiple sources describe the same entry, the last one added wins, so
+/// that custom data can override the data of the previous sources.
+#[derive(Clone, Debug, Default)]
+pub struct CssDataSet {
+    properties: FxHashMap<String, PropertyData>,
+    at_directives: FxHashMap<String, AtDirectiveData>,
+    pseudo_classes: FxHashMap<String, PseudoData>,
+    pseudo_elements: FxHashMap<String, PseudoData>,
+    functions: FxHashMap<String, FunctionData>,
+}
+
+impl CssDataSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the entries of a source, replacing the entries with the same names.
+    pub fn add(&mut self, data: CssData) {
+        for property in data.properties {
+            self.properties
+                .insert(property.name.to_ascii_lowercase(), property);
+        }
+        for at_directive in data.at_directives {
+            self.at_directives
+                .insert(at_directive.name.to_ascii_lowercase(), at_directive);
+        }
+        for pseudo_class in data.pseudo_classes {
+            self.pseudo_classes
+                .insert(pseudo_class.name.to_ascii_lowercase(), pseudo_class);
+        }
+        for pseudo_element in data.pseudo_elements {
+            self.pseudo_elements
+                .insert(pseudo_element.name.to_ascii_lowercase(), pseudo_element);
+        }
+        for function in data.functions {
+            self.functions
+                .insert(function.name.to_ascii_lowercase(), function);
+        }
+    }
+
+    pub fn property(&self, name: &str) -> Option<&PropertyData> {
+        self.properties.get(&name.to_ascii_lowercase())
+    }
+
+    pub fn at_directive(&self, name: &str) -> Option<&AtDirectiveData> {
+        self.at_directives.get(&name.to_ascii_lowercase())
+    }
+
+    /// Get a descriptor of an at-rule, e.g. `system` of `@counter-style`.
+    pub fn descriptor(&self, at_directive: &str, name: &str) -> Option<&PropertyData> {
+        self.at_directive(at_directive)?
+            .descriptors
+            .iter()
+            .find(|descriptor| descriptor.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn pseudo_class(&self, name: &str) -> Option<&PseudoData> {
+        self.pseudo_classes.get(&name.to_ascii_lowercase())
+    }
+
+    pub fn pseudo_element(&self, name: &str) -> Option<&PseudoData> {
+        self.pseudo_elements.get(&name.to_ascii_lowercase())
+    }
+
+    /// Get a function by name, with or without its parentheses, e.g. `calc` or `calc()`.
+    pub fn function(&self, name: &str) -> Option<&FunctionData> {
+        let name = name.trim_end_matches("()").to_ascii_lowercase();
+        self.functions.get(&format!("{name}()"))
+    }
+
+    /// Iterate over the properties, in no particular order. The same goes for the other kinds of entries.
+    pub fn properties(&self) -> impl Iterator<Item = &PropertyData> {
+        self.properties.values()
+    }
+
+    pub fn at_directives(&self) -> impl Iterator<Item = &AtDirectiveData> {
+        self.at_directives.values()
+    }
+
+    pub fn pseudo_classes(&self) -> impl Iterator<Item = &PseudoData> {
+        self.pseudo_classes.values()
+    }
+
+    pub fn pseudo_elements(&self) -> impl Iterator<Item = &PseudoData> {
+        self.pseudo_elements.values()
+    }
+
+    pub fn functions(&self) -> impl Iterator<Item = &FunctionData> {
+        self.functions.values()
+    }
+
+    /// Iterate over the entries of every kind, e.g. to find the experimental or obsolete syntax.
+    pub fn entries(&self) -> impl Iterator<Item = &dyn DataEntry> {
+        (self
+            .properties
+            .values()
+            .map(|entry| entry as &dyn DataEntry))
+        .chain(
+            self.at_directives
+                .values()
+                .map(|entry| entry as &dyn DataEntry),
+        )
+        .chain(
+            self.pseudo_classes
+                .values()
+                .map(|entry| entry as &dyn DataEntry),
+        )
+        .chain(
+            self.pseudo_elements
+                .values()
+                .map(|entry| entry as &dyn DataEntry),
+        )
+        .chain(self.functions.values().map(|entry| entry as &dyn DataEntry))
+    }
+
+    /// Iterate over the entries of every kind with a given status.
+    pub fn entries_with_status(&self, status: EntryStatus) -> impl Iterator<Item = &dyn DataEntry> {
+        self.entries().filter(move |entry| entry.status() == status)
+    }
+}
+
+/// The data about the standard CSS syntax bundled with csslsrs, a curated subset of MDN's data.
+pub fn bundled() -> &'static CssDataSet {
+    static BUNDLED: OnceLock<CssDataSet> = OnceLock::new();
+
+    BUNDLED.get_or_init(|| {
+        let mut data_set = CssDataSet::new();
+        data_set.add(
+            CssData::from_json(include_str!("data/css-data.json"))
+                .expect("the bundled CSS data should be valid"),
+        );
+        data_set
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::MarkupKind;
+
+    use super::*;
+
+    const CUSTOM_DATA: &str = r#"{
+        "version": 1.1,
+        "properties": [
+            {
+                "name": "-tw-ring-color",
+                "description": "The color of Tailwind rings.",
+                "status": "nonstandard",
+                "values": [{ "name": "currentColor" }],
+                "references": [{ "name": "Tailwind", "url": "https://tailwindcss.com" }]
+            }
+        ],
+        "atDirectives": [
+            {
+                "name": "@apply",
+                "description": { "kind": "markdown", "value": "Inline **utility classes**." }
+            }
+        ],
+        "pseudoClasses": [{ "name": ":focus-ring", "status": "experimental" }]
+    }"#;
+
+    #[test]
+    fn test_custom_data() {
+        let data = CssData::from_json(CUSTOM_DATA).unwrap();
+        assert_eq!(data.properties.len(), 1);
+        assert!(data.pseudo_elements.is_empty());
+
+        let mut data_set = CssDataSet::new();
+        data_set.add(data);
+
+        let property = data_set.property("-TW-RING-COLOR").unwrap();
+        assert_eq!(property.status, EntryStatus::Nonstandard);
+        assert_eq!(property.values[0].name, "currentColor");
+        assert_eq!(property.references[0].url, "https://tailwindcss.com");
+        assert_eq!(
+            data_set.at_directive("@apply").unwrap().description,
+            Some(Description::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: "Inline **utility classes**.".to_string(),
+            }))
+        );
+        assert_eq!(
+            data_set.pseudo_class(":focus-ring").unwrap().status,
+            EntryStatus::Experimental
+        );
+
+        // Later sources override the entries of previous ones
+        data_set.add(
+            CssData::from_json(r#"{ "properties": [{ "name": "-tw-ring-color" }] }"#).unwrap(),
+        );
+        assert_eq!(
+            data_set.property("-tw-ring-color").unwrap().status,
+            EntryStatus::Standard
+        );
+    }
+
+    #[test]
+    fn test_bundled_data() {
+        let data_set = bundled();
+
+        let color = data_set.property("color").unwrap();
+        assert_eq!(color.syntax(), Some("<color>"));
+        assert_eq!(color.initial(), Some("canvastext"));
+        assert_eq!(data_set.property("font").unwrap().initial, None);
+        assert!(color.references[0]
+            .url
+            .starts_with("https://developer.mozilla.org"));
+
+        assert!(data_set.at_directive("@media").is_some());
+        assert_eq!(
+            data_set
+                .descriptor("@counter-style", "SYSTEM")
+                .unwrap()
+                .values[0]
+                .name,
+            "cyclic"
+        );
+        assert!(data_set.descriptor("@counter-style", "color").is_none());
+        assert!(data_set.pseudo_class(":hover").is_some());
+        assert!(data_set.pseudo_element("::after").is_some());
+        assert_eq!(
+            data_set.function("CALC").unwrap().syntax(),
+            Some("calc( <calc-sum> )")
+        );
+        assert_eq!(data_set.function("calc()"), data_set.function("calc"));
+
+        let obsolete = data_set
+            .entries_with_status(EntryStatus::Obsolete)
+            .map(|entry| entry.name())
+            .collect::<Vec<_>>();
+        assert!(obsolete.contains(&"@viewport"));
+        assert!(!obsolete.contains(&"color"));
+    }
+
+    #[test]
+    fn test_browser_support() {
+        assert_eq!(
+            BrowserSupport::parse("FF69"),
+            Some(BrowserSupport {
+                browser: Browser::Firefox,
+                version: Some("69".to_string()),
+            })
+        );
+        assert_eq!(
+            BrowserSupport::parse("O3.5").unwrap().version.as_deref(),
+            Some("3.5")
+        );
+        assert_eq!(BrowserSupport::parse("IE").unwrap().version, None);
+        assert_eq!(BrowserSupport::parse("NN4"), None);
+
+        let has = bundled().pseudo_class(":has()").unwrap();
+        assert_eq!(
+            has.supports(Browser::Safari).unwrap().version.as_deref(),
+            Some("15.4")
+        );
+        assert_eq!(has.supports(Browser::InternetExplorer), None);
+    }
+
+    #[test]
+    fn test_documentation() {
+        assert_eq!(
+            bundled().at_directive("@scope").unwrap().documentation(),
+            "Applies styles to the elements of a DOM subtree, optionally stopping at a lower bound.\n\n\
+             **Experimental**\n\n\
+             (Edge 118, Safari 17.4, Chrome 118, Opera 104)\n\n\
+             [MDN Reference](https://developer.mozilla.org/docs/Web/CSS/@scope)"
+        );
+        assert!(bundled()
+            .function("calc()")
+            .unwrap()
+            .documentation()
+            .contains("Syntax: `calc( <calc-sum> )`"));
+    }
+}
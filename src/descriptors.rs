@@ -0,0 +1,93 @@
+//! Helpers for the descriptors of at-rules, e.g. `system` in `@counter-style { system: cyclic; }`, which are written as
+//! declarations but are described by the `descriptors` of their at-rule in the CSS data rather than by its properties.
+
+use biome_css_syntax::{CssLanguage, CssSyntaxKind};
+use biome_rowan::SyntaxNode;
+
+/// Get the name of the at-rule whose declaration block directly contains a node, including its `@`, e.g.
+/// `@counter-style` for the declarations of `@counter-style thumbs { … }`.
+///
+/// # Returns
+///
+/// * The name of the at-rule, or `None` if the node is in a style rule, or in a block nested in the at-rule's block,
+///   e.g. a keyframe of `@keyframes`. The declarations of `@starting-style` nested in a style rule are properties of
+///   the rule's elements, so they aren't in an at-rule either.
+pub(crate) fn descriptor_at_rule(node: &SyntaxNode<CssLanguage>) -> Option<String> {
+    let block = enclosing_block(node)?;
+    if block.kind() != CssSyntaxKind::CSS_DECLARATION_LIST_BLOCK {
+        return None;
+    }
+
+    let rule = block.parent().filter(|rule| {
+        rule.kind() != CssSyntaxKind::CSS_STARTING_STYLE_AT_RULE
+            && rule
+                .parent()
+                .is_some_and(|parent| parent.kind() == CssSyntaxKind::CSS_AT_RULE)
+    })?;
+    let keyword = rule.first_token()?;
+    Some(format!("@{}", keyword.text_trimmed().to_ascii_lowercase()))
+}
+
+/// Get the nearest block containing a node, of a rule or of an at-rule.
+pub(crate) fn enclosing_block(node: &SyntaxNode<CssLanguage>) -> Option<SyntaxNode<CssLanguage>> {
+    node.ancestors().find(|ancestor| {
+        matches!(
+            ancestor.kind(),
+            CssSyntaxKind::CSS_DECLARATION_LIST_BLOCK
+                | CssSyntaxKind::CSS_DECLARATION_OR_AT_RULE_BLOCK
+                | CssSyntaxKind::CSS_DECLARATION_OR_RULE_BLOCK
+                | CssSyntaxKind::CSS_FONT_FEATURE_VALUES_BLOCK
+                | CssSyntaxKind::CSS_KEYFRAMES_BLOCK
+                | CssSyntaxKind::CSS_PAGE_AT_RULE_BLOCK
+                | CssSyntaxKind::CSS_RULE_LIST_BLOCK
+                | CssSyntaxKind::CSS_BOGUS_BLOCK
+        )
+    })
+}
+
+/// Get the prelude of the block a position is in from the source before it, e.g. `@page :first` in
+/// `@page :first { margin: 0; @`. The source is used as is since the rules being typed are often bogus, e.g. `@` alone,
+/// and the at-rules the parser doesn't know are masked, e.g. `@view-transition`.
+#[cfg(any(feature = "completion", feature = "diagnostics", feature = "hover"))]
+pub(crate) fn enclosing_prelude(before: &str) -> Option<&str> {
+    let mut depth = 0;
+    let start = before.char_indices().rev().find_map(|(index, char)| {
+        match char {
+            '}' => depth += 1,
+            '{' if depth == 0 => return Some(index),
+            '{' => depth -= 1,
+            _ => {}
+        }
+        None
+    })?;
+    let prelude = &before[..start];
+    let prelude = prelude
+        .rfind(['{', '}', ';'])
+        .map_or(prelude, |end| &prelude[end + 1..]);
+    Some(prelude.trim())
+}
+
+#[cfg(all(
+    test,
+    any(feature = "completion", feature = "diagnostics", feature = "hover")
+))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enclosing_prelude() {
+        assert_eq!(
+            enclosing_prelude("@page :first { margin: 0; @"),
+            Some("@page :first")
+        );
+        assert_eq!(
+            enclosing_prelude("@page { @top-center { content: none; } @bottom-center { "),
+            Some("@bottom-center")
+        );
+        assert_eq!(
+            enclosing_prelude(".a { color: red; }\n@page {\n  @top-left { }\n  "),
+            Some("@page")
+        );
+        assert_eq!(enclosing_prelude(".a { } "), None);
+    }
+}
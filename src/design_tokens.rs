@@ -0,0 +1,313 @@
+//! The design tokens of a project, e.g. its colors and spacings, so that the features of the LanguageService can
+//! suggest them, show their colors and point out literals duplicating them.
+//!
+//! Tokens are read from JSON, either in the W3C Design Tokens format, where tokens are objects with a `$value` and
+//! optionally a `$type` and `$description`, nested in groups, or in a simpler format where every string or number is a
+//! token. CSS uses a token through the custom property named after its path, e.g. `--color-brand-primary` for
+//! `{ "color": { "brand": { "primary": { "$value": "#0055ff" } } } }`.
+
+use lsp_types::Color;
+use rustc_hash::FxHashMap;
+use serde_json::{Map, Value};
+
+/// A design token, with its aliases resolved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DesignToken {
+    /// The path of the token in its file, e.g. `color.brand.primary`.
+    pub path: String,
+    /// The custom property CSS uses the token through, e.g. `--color-brand-primary`.
+    pub property: String,
+    /// The value of the token, e.g. `#0055ff`. Aliases of other tokens (e.g. `{color.blue}`) are replaced by the value
+    /// of the token they refer to.
+    pub value: String,
+    /// The type of the token, e.g. `color` or `dimension`, from its `$type` or the one of its closest group.
+    pub token_type: Option<String>,
+    pub description: Option<String>,
+}
+
+impl DesignToken {
+    /// The color of the token, if its value is one.
+    pub fn color(&self) -> Option<Color> {
+        let color = csscolorparser::parse(&self.value).ok()?;
+        Some(Color {
+            red: color.r,
+            green: color.g,
+            blue: color.b,
+            alpha: color.a,
+        })
+    }
+}
+
+/// The key of a value in `DesignTokens::by_value`, so that colors match regardless of how they are written, e.g.
+/// `#F00` and `red`.
+fn value_key(value: &str) -> String {
+    let value = value.trim();
+    // Numbers and dimensions aren't colors, even if csscolorparser accepts some of them
+    if value.starts_with(|char: char| char.is_ascii_digit() || char == '.' || char == '-') {
+        return value.to_ascii_lowercase();
+    }
+
+    match csscolorparser::parse(value) {
+        Ok(color) => format!("color:{}", color.to_hex_string()),
+        Err(_) => value.to_ascii_lowercase(),
+    }
+}
+
+// Aliases referring to each other in a cycle are left unresolved after this many steps.
+const MAX_ALIAS_DEPTH: usize = 16;
+
+/// The design tokens loaded into a LanguageService.
+#[derive(Clone, Debug, Default)]
+pub struct DesignTokens {
+    tokens: Vec<DesignToken>,
+    by_property: FxHashMap<String, usize>,
+    by_value: FxHashMap<String, usize>,
+}
+
+impl DesignTokens {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a design tokens file, in the W3C Design Tokens format or as nested objects of plain values.
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - The content of the file.
+    ///
+    /// # Returns
+    ///
+    /// * The tokens of the file. Values that can't be used in CSS, like the composite values of the W3C format, are
+    ///   skipped.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let root = serde_json::from_str::<Value>(json)?;
+
+        let mut raw_tokens = Vec::new();
+        if let Value::Object(root) = &root {
+            collect_tokens(root, &mut Vec::new(), None, &mut raw_tokens);
+        }
+
+        let values = raw_tokens
+            .iter()
+            .map(|token| (token.path.clone(), token.value.clone()))
+            .collect::<FxHashMap<_, _>>();
+
+        let mut tokens = Self::new();
+        for token in raw_tokens {
+            let mut value = token.value;
+            for _ in 0..MAX_ALIAS_DEPTH {
+                let Some(alias) = value
+                    .strip_prefix('{')
+                    .and_then(|value| value.strip_suffix('}'))
+                else {
+                    break;
+                };
+                match values.get(alias) {
+                    Some(aliased) => value = aliased.clone(),
+                    None => break,
+                }
+            }
+
+            tokens.push(DesignToken {
+                property: format!("--{}", token.path.replace('.', "-")),
+                path: token.path,
+                value,
+                token_type: token.token_type,
+                description: token.description,
+            });
+        }
+
+        Ok(tokens)
+    }
+
+    fn push(&mut self, token: DesignToken) {
+        let index = self.tokens.len();
+        self.by_property.insert(token.property.clone(), index);
+        self.by_value
+            .entry(value_key(&token.value))
+            .or_insert(index);
+        self.tokens.push(token);
+    }
+
+    pub fn tokens(&self) -> &[DesignToken] {
+        &self.tokens
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Get a token by the custom property CSS uses it through, e.g. `--color-brand-primary`.
+    pub fn get(&self, property: &str) -> Option<&DesignToken> {
+        self.by_property
+            .get(property)
+            .map(|&index| &self.tokens[index])
+    }
+
+    /// Find a token whose value is `value`, e.g. to suggest it instead of a literal. Colors match regardless of how
+    /// they are written, other values ignore their case.
+    pub fn find_by_value(&self, value: &str) -> Option<&DesignToken> {
+        self.by_value
+            .get(&value_key(value))
+            .map(|&index| &self.tokens[index])
+    }
+}
+
+struct RawToken {
+    path: String,
+    value: String,
+    token_type: Option<String>,
+    description: Option<String>,
+}
+
+fn collect_tokens(
+    group: &Map<String, Value>,
+    path: &mut Vec<String>,
+    group_type: Option<&str>,
+    tokens: &mut Vec<RawToken>,
+) {
+    let group_type = group.get("$type").and_then(Value::as_str).or(group_type);
+
+    for (name, value) in group {
+        if name.starts_with('$') {
+            continue;
+        }
+        path.push(name.clone());
+
+        match value {
+            Value::Object(object) if object.contains_key("$value") => {
+                if let Some(css_value) = object.get("$value").and_then(css_value) {
+                    tokens.push(RawToken {
+                        path: path.join("."),
+                        value: css_value,
+                        token_type: object
+                            .get("$type")
+                            .and_then(Value::as_str)
+                            .or(group_type)
+                            .map(str::to_string),
+                        description: object
+                            .get("$description")
+                            .and_then(Value::as_str)
+                            .map(str::to_string),
+                    });
+                }
+            }
+            Value::Object(object) => collect_tokens(object, path, group_type, tokens),
+            value => {
+                if let Some(css_value) = css_value(value) {
+                    tokens.push(RawToken {
+                        path: path.join("."),
+                        value: css_value,
+                        token_type: group_type.map(str::to_string),
+                        description: None,
+                    });
+                }
+            }
+        }
+
+        path.pop();
+    }
+}
+
+/// The CSS text of a value: strings and numbers as is, dimensions like `{ "value": 4, "unit": "px" }` joined, and lists
+/// (e.g. of font families) separated by commas.
+fn css_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(string) => Some(string.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Object(object) => {
+            let number = object.get("value")?.as_number()?;
+            let unit = object.get("unit")?.as_str()?;
+            Some(format!("{number}{unit}"))
+        }
+        Value::Array(values) => values
+            .iter()
+            .map(|value| match value {
+                Value::Array(_) | Value::Object(_) => None,
+                value => css_value(value),
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|values| values.join(", ")),
+        Value::Bool(_) | Value::Null => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_w3c_design_tokens() {
+        let tokens = DesignTokens::from_json(
+            r##"{
+                "color": {
+                    "$type": "color",
+                    "blue": { "$value": "#0055FF", "$description": "The blue of the logo." },
+                    "brand": { "primary": { "$value": "{color.blue}" } }
+                },
+                "space": {
+                    "sm": { "$value": { "value": 4, "unit": "px" }, "$type": "dimension" }
+                },
+                "font": {
+                    "body": { "$value": ["Inter", "sans-serif"], "$type": "fontFamily" }
+                },
+                "shadow": {
+                    "card": { "$value": { "color": "#000", "offsetX": "0px" }, "$type": "shadow" }
+                }
+            }"##,
+        )
+        .unwrap();
+
+        assert_eq!(tokens.tokens().len(), 4);
+        assert_eq!(
+            tokens.get("--color-brand-primary"),
+            Some(&DesignToken {
+                path: "color.brand.primary".to_string(),
+                property: "--color-brand-primary".to_string(),
+                value: "#0055FF".to_string(),
+                token_type: Some("color".to_string()),
+                description: None,
+            })
+        );
+        assert_eq!(
+            tokens.get("--color-blue").unwrap().description.as_deref(),
+            Some("The blue of the logo.")
+        );
+        assert_eq!(tokens.get("--space-sm").unwrap().value, "4px");
+        assert_eq!(
+            tokens.get("--font-body").unwrap().value,
+            "Inter, sans-serif"
+        );
+        assert!(tokens.get("--shadow-card").is_none());
+
+        assert_eq!(
+            tokens.get("--color-blue").unwrap().color(),
+            Some(Color {
+                red: 0.0,
+                green: 85.0 / 255.0,
+                blue: 1.0,
+                alpha: 1.0,
+            })
+        );
+        assert_eq!(tokens.get("--space-sm").unwrap().color(), None);
+    }
+
+    #[test]
+    fn test_simple_design_tokens() {
+        let tokens =
+            DesignTokens::from_json(r##"{ "red": "#f00", "radius": { "md": 8, "lg": "1rem" } }"##)
+                .unwrap();
+
+        assert_eq!(tokens.get("--red").unwrap().value, "#f00");
+        assert_eq!(tokens.get("--radius-md").unwrap().value, "8");
+        assert_eq!(tokens.get("--radius-lg").unwrap().value, "1rem");
+
+        assert_eq!(tokens.find_by_value("#FF0000").unwrap().property, "--red");
+        assert_eq!(tokens.find_by_value("red").unwrap().property, "--red");
+        assert_eq!(
+            tokens.find_by_value("1REM").unwrap().property,
+            "--radius-lg"
+        );
+        assert!(tokens.find_by_value("2rem").is_none());
+    }
+}
@@ -0,0 +1,292 @@
+//! Support for the custom at-rules and functions of PostCSS plugins and frameworks, e.g. Tailwind's `@tailwind base;`,
+//! `@apply font-bold;`, `@screen md { … }` or `theme(colors.blue.500)`.
+//!
+//! The parser rejects at-rules it doesn't know, and the arguments of these functions often aren't valid CSS values. The
+//! custom directives listed in the `css.customDirectives` setting are thus masked before parsing like SCSS constructs,
+//! see `scss.rs`, with the same scanner: at-rules without a block are blanked, those with a block become a nested rule
+//! as SCSS directives do, and the arguments of functions are blanked. They are recorded as directives.
+
+use crate::{
+    parser::CssDialect,
+    scss::{is_ident_char, is_ident_start, Scanner, ScssConstructKind, ScssScan},
+};
+
+/// The at-rules the parser knows, which are never masked even when listed as custom directives, e.g. Tailwind's
+/// `@layer`.
+const PARSER_AT_RULES: &[&str] = &[
+    "charset",
+    "color-profile",
+    "container",
+    "counter-style",
+    "document",
+    "font-face",
+    "font-feature-values",
+    "font-palette-values",
+    "import",
+    "keyframes",
+    "layer",
+    "media",
+    "namespace",
+    "page",
+    "property",
+    "supports",
+    "viewport",
+    "scope",
+    "starting-style",
+];
+
+/// The standard at-rules the parser doesn't know yet, always masked as custom directives with a block are, so that
+/// their declarations are still parsed, e.g. `@view-transition { navigation: auto; }`.
+const UNPARSED_AT_RULES: &[&str] = &["view-transition"];
+
+/// Whether code contains one of the standard at-rules the parser doesn't know yet, which must be masked before parsing
+/// even without custom directives.
+pub(crate) fn has_unparsed_at_rules(code: &str) -> bool {
+    UNPARSED_AT_RULES.iter().any(|at_rule| {
+        code.as_bytes()
+            .windows(at_rule.len() + 1)
+            .any(|window| window[0] == b'@' && window[1..].eq_ignore_ascii_case(at_rule.as_bytes()))
+    })
+}
+
+/// A custom directive of the `css.customDirectives` setting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CustomDirective<'a> {
+    /// An at-rule, listed by its name, e.g. `apply` for `@apply`.
+    AtRule(&'a str),
+    /// A function, listed by its name followed by parentheses, e.g. `theme()`.
+    Function(&'a str),
+}
+
+impl<'a> CustomDirective<'a> {
+    /// Get the directive described by an entry of the `css.customDirectives` setting. A leading `@` is accepted for
+    /// at-rules, e.g. `@apply`.
+    pub fn parse(directive: &'a str) -> Self {
+        match directive.strip_suffix("()") {
+            Some(function) => CustomDirective::Function(function),
+            None => CustomDirective::AtRule(directive.trim_start_matches('@')),
+        }
+    }
+}
+
+/// Scans code for the given custom directives, and masks them so that the code can be parsed, along with the standard
+/// at-rules the parser doesn't know yet, e.g. `@view-transition`. The code is usually already masked for its dialect,
+/// e.g. by `scan_scss`, custom directives being left as is by the other scans.
+///
+/// # Arguments
+///
+/// * `code` - A string slice that holds the code to scan.
+/// * `dialect` - The dialect the code is written in, for its comments.
+/// * `directives` - The entries of the `css.customDirectives` setting, see `CustomDirective`.
+///
+/// # Returns
+///
+/// * The masked code, and the directives found in it.
+pub fn scan_custom_directives(code: &str, dialect: CssDialect, directives: &[String]) -> ScssScan {
+    let directives = directives
+        .iter()
+        .map(|directive| CustomDirective::parse(directive))
+        .collect::<Vec<_>>();
+    let mut scanner = Scanner::new(code, dialect);
+    scanner.scan_custom_directives(&directives);
+    scanner.finish()
+}
+
+impl Scanner<'_> {
+    fn scan_custom_directives(&mut self, directives: &[CustomDirective]) {
+        let is_at_rule = |name: &str| {
+            let is_custom = directives.iter().any(
+                |directive| matches!(directive, CustomDirective::AtRule(at_rule) if *at_rule == name),
+            );
+            UNPARSED_AT_RULES.contains(&name) || (is_custom && !PARSER_AT_RULES.contains(&name))
+        };
+        let has_functions = directives
+            .iter()
+            .any(|directive| matches!(directive, CustomDirective::Function(_)));
+
+        let mut i = 0;
+        loop {
+            i = self.skip_trivia(i);
+            let Some(&byte) = self.bytes.get(i) else {
+                break;
+            };
+
+            let end = self.statement_end(i);
+            if byte == b'{' || byte == b'}' || byte == b';' {
+                i += 1;
+                continue;
+            }
+            if byte == b'@' {
+                let name_end = self.ident_end(i + 1);
+                if is_at_rule(&self.code[i + 1..name_end].to_ascii_lowercase()) {
+                    self.push(
+                        ScssConstructKind::Directive,
+                        (i + 1, name_end),
+                        (i, self.trim_end(i, end)),
+                        None,
+                    );
+                    // At-rules with a block (e.g. `@screen md {`) become a nested rule, so that the declarations and
+                    // rules of their block are still parsed
+                    if self.bytes.get(end) == Some(&b'{') {
+                        self.masked[i] = b'&';
+                        self.blank(i + 1, end);
+                    } else {
+                        self.blank(i, self.terminated_end(end));
+                    }
+                    i = end;
+                    continue;
+                }
+            }
+            // Functions are only masked in values and in the preludes of at-rules, not in selectors
+            if has_functions && (byte == b'@' || self.bytes.get(end) != Some(&b'{')) {
+                self.mask_custom_functions(i, end, directives);
+            }
+            i = end;
+        }
+    }
+
+    /// Blank the arguments of the custom functions called in the given range, keeping their parentheses.
+    fn mask_custom_functions(&mut self, start: usize, end: usize, directives: &[CustomDirective]) {
+        let mut i = start;
+        while i < end {
+            match self.bytes[i] {
+                b'"' | b'\'' => {
+                    i = self.string_end(i);
+                    continue;
+                }
+                b'/' if self.bytes[i..].starts_with(b"/*") => {
+                    i = self.comment_end(i);
+                    continue;
+                }
+                byte if is_ident_start(byte)
+                    && !i
+                        .checked_sub(1)
+                        .is_some_and(|previous| is_ident_char(self.bytes[previous])) =>
+                {
+                    let name_end = self.ident_end(i);
+                    let name = self.code[i..name_end].to_ascii_lowercase();
+                    let is_function = self.bytes.get(name_end) == Some(&b'(')
+                        && directives.iter().any(|directive| {
+                            matches!(directive, CustomDirective::Function(function) if *function == name)
+                        });
+                    if !is_function {
+                        i = name_end;
+                        continue;
+                    }
+
+                    let close = self.closing_parenthesis(name_end, end);
+                    self.push(
+                        ScssConstructKind::Directive,
+                        (i, name_end),
+                        (i, (close + 1).min(end)),
+                        None,
+                    );
+                    self.blank(name_end + 1, close);
+                    i = close;
+                    continue;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    /// Find the parenthesis closing the one opening at `start`, or `end` if it isn't closed before.
+    fn closing_parenthesis(&self, start: usize, end: usize) -> usize {
+        let mut depth = 0usize;
+        let mut i = start;
+        while i < end {
+            match self.bytes[i] {
+                b'"' | b'\'' => {
+                    i = self.string_end(i);
+                    continue;
+                }
+                b'(' => depth += 1,
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return i;
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_css_with_directives;
+
+    const TAILWIND: &str = "@tailwind base;\n.btn { @apply font-bold py-2; color: theme(colors.blue.500); }\n@screen md { .b { margin: 0; } }\n@layer components { .c { color: red; } }";
+
+    fn directives() -> Vec<String> {
+        ["tailwind", "apply", "screen", "layer", "theme()"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    #[test]
+    fn test_scan_custom_directives() {
+        let scan = scan_custom_directives(TAILWIND, CssDialect::Css, &directives());
+        assert_eq!(scan.masked.len(), TAILWIND.len());
+        assert_eq!(
+            scan.constructs
+                .iter()
+                .map(|construct| (construct.kind, construct.name.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (ScssConstructKind::Directive, "tailwind"),
+                (ScssConstructKind::Directive, "apply"),
+                (ScssConstructKind::Directive, "theme"),
+                (ScssConstructKind::Directive, "screen"),
+            ],
+            "`@layer` is known to the parser and left as is"
+        );
+        assert_eq!(
+            &TAILWIND[scan.constructs[2].range],
+            "theme(colors.blue.500)"
+        );
+        assert!(scan.masked.contains("theme(               )"));
+        assert!(scan.masked.contains("@layer components"));
+    }
+
+    #[test]
+    fn test_parse_custom_directives() {
+        assert!(parse_css_with_directives(TAILWIND, CssDialect::Css, &[]).has_errors());
+        assert!(!parse_css_with_directives(TAILWIND, CssDialect::Css, &directives()).has_errors());
+        assert!(
+            !parse_css_with_directives(
+                &format!("$gap: 4px;\n{TAILWIND}"),
+                CssDialect::Scss,
+                &directives()
+            )
+            .has_errors(),
+            "custom directives are masked in every dialect"
+        );
+    }
+
+    #[test]
+    fn test_parse_unparsed_at_rules() {
+        let code = "@view-transition { navigation: auto; }\n.a { @starting-style { opacity: 0; } }";
+        assert!(has_unparsed_at_rules(code));
+        assert!(!parse_css_with_directives(code, CssDialect::Css, &[]).has_errors());
+        assert!(
+            !parse_css_with_directives(code, CssDialect::Css, &["starting-style".to_string()])
+                .has_errors(),
+            "`@starting-style` is known to the parser and left as is"
+        );
+        assert!(!parse_css_with_directives(code, CssDialect::Scss, &[]).has_errors());
+    }
+
+    #[test]
+    fn test_scan_custom_directives_keeps_others() {
+        let code = ".a { color: red; }\n@media (min-width: 100px) { .apply { margin: apply(0); } }";
+        let scan = scan_custom_directives(code, CssDialect::Css, &["apply".to_string()]);
+        assert_eq!(scan.masked, code, "`apply()` isn't listed as a function");
+        assert!(scan.constructs.is_empty());
+    }
+}
@@ -0,0 +1,239 @@
+//! Expansion of the Emmet abbreviations of CSS declarations, e.g. `m10` into `margin: 10px;` or `dib` into
+//! `display: inline-block;`, offered by completion when the `completion.emmetAbbreviations` setting is enabled.
+//!
+//! Only the common abbreviations are known: a property followed by numbers with an optional unit, e.g. `w100p`, or by
+//! a color, e.g. `c#fff`, and the declarations with a keyword value, e.g. `posa`.
+
+/// The abbreviations of properties, followed by numbers or colors in abbreviations, e.g. `m` in `m10-20`.
+const PROPERTIES: &[(&str, &str)] = &[
+    ("b", "bottom"),
+    ("bd", "border"),
+    ("bdrs", "border-radius"),
+    ("bg", "background"),
+    ("bgc", "background-color"),
+    ("bdc", "border-color"),
+    ("c", "color"),
+    ("fw", "font-weight"),
+    ("fx", "flex"),
+    ("fz", "font-size"),
+    ("g", "gap"),
+    ("h", "height"),
+    ("l", "left"),
+    ("lh", "line-height"),
+    ("m", "margin"),
+    ("mah", "max-height"),
+    ("maw", "max-width"),
+    ("mb", "margin-bottom"),
+    ("mih", "min-height"),
+    ("miw", "min-width"),
+    ("ml", "margin-left"),
+    ("mr", "margin-right"),
+    ("mt", "margin-top"),
+    ("op", "opacity"),
+    ("p", "padding"),
+    ("pb", "padding-bottom"),
+    ("pl", "padding-left"),
+    ("pr", "padding-right"),
+    ("pt", "padding-top"),
+    ("r", "right"),
+    ("t", "top"),
+    ("w", "width"),
+    ("z", "z-index"),
+];
+
+/// The properties whose numbers have no unit, e.g. `lh1.5` into `line-height: 1.5;`.
+const UNITLESS_PROPERTIES: &[&str] = &["flex", "font-weight", "line-height", "opacity", "z-index"];
+
+/// The abbreviations of declarations with a keyword value.
+const DECLARATIONS: &[(&str, &str, &str)] = &[
+    ("aic", "align-items", "center"),
+    ("aifs", "align-items", "flex-start"),
+    ("aife", "align-items", "flex-end"),
+    ("curp", "cursor", "pointer"),
+    ("db", "display", "block"),
+    ("df", "display", "flex"),
+    ("dg", "display", "grid"),
+    ("di", "display", "inline"),
+    ("dib", "display", "inline-block"),
+    ("dif", "display", "inline-flex"),
+    ("dn", "display", "none"),
+    ("fdc", "flex-direction", "column"),
+    ("fdr", "flex-direction", "row"),
+    ("fll", "float", "left"),
+    ("fln", "float", "none"),
+    ("flr", "float", "right"),
+    ("fsi", "font-style", "italic"),
+    ("fwb", "font-weight", "bold"),
+    ("fwn", "font-weight", "normal"),
+    ("fxww", "flex-wrap", "wrap"),
+    ("jcc", "justify-content", "center"),
+    ("jcfe", "justify-content", "flex-end"),
+    ("jcfs", "justify-content", "flex-start"),
+    ("jcsb", "justify-content", "space-between"),
+    ("ova", "overflow", "auto"),
+    ("ovh", "overflow", "hidden"),
+    ("posa", "position", "absolute"),
+    ("posf", "position", "fixed"),
+    ("posr", "position", "relative"),
+    ("poss", "position", "sticky"),
+    ("tac", "text-align", "center"),
+    ("tal", "text-align", "left"),
+    ("tar", "text-align", "right"),
+    ("tdn", "text-decoration", "none"),
+    ("tdu", "text-decoration", "underline"),
+    ("ttu", "text-transform", "uppercase"),
+    ("vh", "visibility", "hidden"),
+];
+
+/// The units of the numbers of abbreviations, by their alias, e.g. `p` for `%` in `w100p`.
+const UNITS: &[(&str, &str)] = &[("p", "%"), ("e", "em"), ("r", "rem"), ("x", "ex")];
+
+/// Expand an Emmet abbreviation into the declaration it stands for, as a snippet, e.g. `m10-20` into
+/// `margin: 10px 20px;`. Numbers are separated by `-`, and a `-` starting a number makes it negative, e.g. `m-10`.
+/// A property alone expands with a placeholder for its value, e.g. `m` into `margin: $1;`.
+///
+/// # Returns
+///
+/// * The snippet, or `None` if the abbreviation isn't known.
+pub(crate) fn expand_abbreviation(abbreviation: &str) -> Option<String> {
+    if let Some((_, property, value)) = DECLARATIONS
+        .iter()
+        .find(|(known, _, _)| *known == abbreviation)
+    {
+        return Some(format!("{property}: {value};"));
+    }
+
+    // The property is all the letters before the values, e.g. `mt` rather than `m` in `mt10`
+    let name_end = abbreviation
+        .find(|char: char| !char.is_ascii_lowercase())
+        .unwrap_or(abbreviation.len());
+    let (_, property) = PROPERTIES
+        .iter()
+        .find(|(known, _)| *known == &abbreviation[..name_end])?;
+    let values = &abbreviation[name_end..];
+    if values.is_empty() {
+        return Some(format!("{property}: $1;"));
+    }
+    if let Some(color) = values.strip_prefix('#') {
+        return Some(format!("{property}: #{};", expand_color(color)?));
+    }
+
+    let unitless = UNITLESS_PROPERTIES.contains(property);
+    let mut numbers = Vec::new();
+    let mut rest = values;
+    while !rest.is_empty() {
+        let (number, after) = number(rest, unitless)?;
+        numbers.push(number);
+        rest = match after.strip_prefix('-') {
+            // A `-` ending the abbreviation doesn't separate numbers
+            Some("") => return None,
+            Some(next) => next,
+            None if after.is_empty() => after,
+            None => return None,
+        };
+    }
+    Some(format!("{property}: {};", numbers.join(" ")))
+}
+
+/// Split a number with its unit from the start of the values of an abbreviation, e.g. `10px` from `10-20`.
+fn number(values: &str, unitless: bool) -> Option<(String, &str)> {
+    let digits_start = usize::from(values.starts_with('-'));
+    let digits_end = values[digits_start..]
+        .find(|char: char| !(char.is_ascii_digit() || char == '.'))
+        .map_or(values.len(), |end| digits_start + end);
+    let digits = &values[digits_start..digits_end];
+    if digits.is_empty() || digits.parse::<f64>().is_err() {
+        return None;
+    }
+    let number = &values[..digits_end];
+
+    let rest = &values[digits_end..];
+    let alias_end = rest
+        .find(|char: char| !char.is_ascii_lowercase())
+        .unwrap_or(rest.len());
+    let unit = match &rest[..alias_end] {
+        "" if unitless || digits.parse::<f64>() == Ok(0.0) => "",
+        "" => "px",
+        alias => {
+            UNITS
+                .iter()
+                .find(|(known, _)| *known == alias)
+                .map(|(_, unit)| *unit)
+                // Units can also be written as is, e.g. `m1vh`
+                .unwrap_or(alias)
+        }
+    };
+    Some((format!("{number}{unit}"), &rest[alias_end..]))
+}
+
+/// Expand the short forms of a hexadecimal color, e.g. `f` into `fff` or `e0` into `e0e0e0`.
+fn expand_color(color: &str) -> Option<String> {
+    if !color.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    match color.len() {
+        1 | 2 => Some(color.repeat(3)),
+        3 | 4 | 6 | 8 => Some(color.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_abbreviation() {
+        assert_eq!(
+            expand_abbreviation("m10"),
+            Some("margin: 10px;".to_string())
+        );
+        assert_eq!(
+            expand_abbreviation("m10-20"),
+            Some("margin: 10px 20px;".to_string())
+        );
+        assert_eq!(
+            expand_abbreviation("m-10--5"),
+            Some("margin: -10px -5px;".to_string())
+        );
+        assert_eq!(
+            expand_abbreviation("mt0"),
+            Some("margin-top: 0;".to_string())
+        );
+        assert_eq!(
+            expand_abbreviation("w100p"),
+            Some("width: 100%;".to_string())
+        );
+        assert_eq!(
+            expand_abbreviation("fz1.5r"),
+            Some("font-size: 1.5rem;".to_string())
+        );
+        assert_eq!(
+            expand_abbreviation("h50vh"),
+            Some("height: 50vh;".to_string())
+        );
+        assert_eq!(
+            expand_abbreviation("lh1.5"),
+            Some("line-height: 1.5;".to_string())
+        );
+        assert_eq!(expand_abbreviation("c#f"), Some("color: #fff;".to_string()));
+        assert_eq!(
+            expand_abbreviation("bgc#e0"),
+            Some("background-color: #e0e0e0;".to_string())
+        );
+        assert_eq!(
+            expand_abbreviation("dib"),
+            Some("display: inline-block;".to_string())
+        );
+        assert_eq!(
+            expand_abbreviation("posa"),
+            Some("position: absolute;".to_string())
+        );
+        assert_eq!(expand_abbreviation("p"), Some("padding: $1;".to_string()));
+
+        assert_eq!(expand_abbreviation("m10-"), None);
+        assert_eq!(expand_abbreviation("c#ggg"), None);
+        assert_eq!(expand_abbreviation("xyz10"), None);
+        assert_eq!(expand_abbreviation("div"), None);
+    }
+}
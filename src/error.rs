@@ -0,0 +1,85 @@
+//! The errors returned by the public API of csslsrs.
+//!
+//! Requests fail instead of panicking on invalid input, e.g. positions beyond the end of a document. The WASM bindings
+//! turn these errors into JavaScript exceptions.
+
+use std::fmt;
+
+use biome_rowan::TextSize;
+use lsp_types::{Position, Range, Uri};
+
+use crate::cancellation::Cancelled;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// A request was made for a document that isn't in the store.
+    DocumentNotOpen(Uri),
+    /// A position is outside of its document.
+    InvalidPosition(Position),
+    /// A range is outside of its document, or ends before it starts.
+    InvalidRange(Range),
+    /// An offset is outside of its document.
+    InvalidOffset(TextSize),
+    /// The request was cancelled before it completed.
+    Cancelled,
+    /// A value couldn't be converted from or to JavaScript by the WASM bindings.
+    Serialization(String),
+    /// A command was executed that isn't registered in the service.
+    UnknownCommand(String),
+    /// The arguments of a command are missing or invalid.
+    InvalidArguments(String),
+    /// A rename was requested where there is nothing to rename, or with an invalid new name.
+    InvalidRename(String),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::DocumentNotOpen(uri) => write!(f, "the document {} is not open", uri.as_str()),
+            Error::InvalidPosition(position) => write!(
+                f,
+                "the position {}:{} is outside of the document",
+                position.line, position.character
+            ),
+            Error::InvalidRange(range) => write!(
+                f,
+                "the range {}:{}-{}:{} is not a valid range of the document",
+                range.start.line, range.start.character, range.end.line, range.end.character
+            ),
+            Error::InvalidOffset(offset) => write!(
+                f,
+                "the offset {} is outside of the document",
+                u32::from(*offset)
+            ),
+            Error::Cancelled => Cancelled.fmt(f),
+            Error::Serialization(message) => write!(f, "invalid value: {message}"),
+            Error::UnknownCommand(command) => write!(f, "unknown command `{command}`"),
+            Error::InvalidArguments(message) => write!(f, "invalid arguments: {message}"),
+            Error::InvalidRename(message) => write!(f, "cannot rename: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Cancelled> for Error {
+    fn from(_: Cancelled) -> Self {
+        Error::Cancelled
+    }
+}
+
+#[cfg(feature = "wasm")]
+impl From<serde_wasm_bindgen::Error> for Error {
+    fn from(error: serde_wasm_bindgen::Error) -> Self {
+        Error::Serialization(error.to_string())
+    }
+}
+
+#[cfg(feature = "wasm")]
+impl From<Error> for wasm_bindgen::JsValue {
+    fn from(error: Error) -> Self {
+        wasm_bindgen::JsError::new(&error.to_string()).into()
+    }
+}
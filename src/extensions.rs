@@ -0,0 +1,213 @@
+//! Custom LSP requests for debugging, e.g. from a dev panel of an editor extension, when reporting parser or resolution
+//! bugs. Servers route them to the LanguageService like the standard requests:
+//!
+//! - `cssls/syntaxTree` returns the syntax tree of a document, see `LanguageService::debug_syntax_tree_for_uri`.
+//! - `cssls/resolvedValue` returns the value of the declaration at a position, with its `var()` resolved, see
+//!   `LanguageService::get_resolved_value`.
+
+use biome_css_syntax::CssGenericProperty;
+use biome_rowan::AstNode;
+use lsp_types::{
+    request::Request, Position, Range, TextDocumentIdentifier, TextDocumentPositionParams, Uri,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    converters::{from_proto, to_proto::range},
+    custom_properties::{collect_custom_properties, resolve_value},
+    error::Error,
+    service::LanguageService,
+};
+
+/// The `cssls/syntaxTree` request.
+pub enum SyntaxTree {}
+
+impl Request for SyntaxTree {
+    type Params = SyntaxTreeParams;
+    type Result = String;
+    const METHOD: &'static str = "cssls/syntaxTree";
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyntaxTreeParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+/// The `cssls/resolvedValue` request.
+pub enum ResolvedValue {}
+
+impl Request for ResolvedValue {
+    type Params = TextDocumentPositionParams;
+    type Result = Option<ResolvedValueResult>;
+    const METHOD: &'static str = "cssls/resolvedValue";
+}
+
+/// The value of a declaration, with the custom properties it was resolved through.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "wasm", derive(tsify_next::Tsify))]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedValueResult {
+    /// The name of the property of the declaration, e.g. `color`.
+    pub property: String,
+    /// The value of the declaration as written, e.g. `var(--primary)`.
+    pub value: String,
+    /// The value with every `var()` substituted, `None` if one of them can't be resolved and has no fallback.
+    pub resolved: Option<String>,
+    /// The custom properties used to resolve the value, in resolution order.
+    pub chain: Vec<ResolutionStep>,
+    /// The range of the declaration.
+    #[cfg_attr(
+        feature = "wasm",
+        tsify(type = "import(\"vscode-languageserver-types\").Range")
+    )]
+    pub range: Range,
+}
+
+/// A custom property used to resolve a value, and its value as written.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "wasm", derive(tsify_next::Tsify))]
+#[serde(rename_all = "camelCase")]
+pub struct ResolutionStep {
+    pub name: String,
+    pub value: String,
+}
+
+impl LanguageService {
+    /// Handle a `cssls/syntaxTree` request, see `debug_syntax_tree_for_uri`.
+    pub fn handle_syntax_tree(&self, params: SyntaxTreeParams) -> Result<String, Error> {
+        self.debug_syntax_tree_for_uri(&params.text_document.uri)
+    }
+
+    /// Handle a `cssls/resolvedValue` request, see `get_resolved_value`.
+    pub fn handle_resolved_value(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<ResolvedValueResult>, Error> {
+        self.get_resolved_value(&params.text_document.uri, params.position)
+    }
+
+    /// Get the value of the declaration at a position, with every `var()` substituted by the value of its custom
+    /// property. As for the other features, the cascade is approximated by the last declaration of each custom property
+    /// in the document.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    /// * `position` - A position in the declaration.
+    ///
+    /// # Returns
+    ///
+    /// * The value of the declaration, `None` if the position isn't in a declaration, or `Error::InvalidPosition` if
+    ///   the position isn't in the document.
+    pub fn get_resolved_value(
+        &self,
+        uri: &Uri,
+        position: Position,
+    ) -> Result<Option<ResolvedValueResult>, Error> {
+        let store_entry = self.open_entry(uri)?;
+        let offset = from_proto::offset(
+            store_entry.line_index(),
+            position,
+            self.encoding_for(&store_entry),
+        )?;
+
+        let root = store_entry.css_tree().syntax();
+        let Some(declaration) = root
+            .token_at_offset(offset)
+            .left_biased()
+            .and_then(|token| token.parent())
+            .and_then(|parent| parent.ancestors().find_map(CssGenericProperty::cast))
+        else {
+            return Ok(None);
+        };
+        let Ok(property) = declaration.name() else {
+            return Ok(None);
+        };
+
+        let value = declaration.value();
+        let mut chain = Vec::new();
+        let resolved = resolve_value(
+            value.syntax(),
+            &collect_custom_properties(&root),
+            &mut chain,
+        );
+
+        Ok(range(
+            store_entry.line_index(),
+            declaration.syntax().text_trimmed_range(),
+            self.encoding_for(&store_entry),
+        )
+        .ok()
+        .map(|range| ResolvedValueResult {
+            property: property.syntax().text_trimmed().to_string(),
+            value: value.syntax().text_trimmed().to_string(),
+            resolved,
+            chain: chain
+                .into_iter()
+                .map(|step| ResolutionStep {
+                    name: step.name,
+                    value: step.value,
+                })
+                .collect(),
+            range,
+        }))
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod wasm_bindings {
+    use crate::{error::Error, wasm_text_document};
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Get the syntax tree of the given source code, as returned by the `cssls/syntaxTree` request.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @returns The syntax tree, formatted for debugging.
+ */
+export async function get_syntax_tree(source: TextDocumentSource): Promise<string>;
+
+/**
+ * Get the value of the declaration at a position of the given source code, with every `var()` substituted, as returned
+ * by the `cssls/resolvedValue` request.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @param position A position in the declaration.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns The resolved value, or `null` if the position isn't in a declaration.
+ */
+export async function get_resolved_value(source: TextDocumentSource, position: import("vscode-languageserver-types").Position, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<ResolvedValueResult | null>;
+"#;
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn get_syntax_tree(document: JsValue) -> Result<String, Error> {
+        let parsed_text_document = wasm_text_document::create_text_document(document)?;
+
+        wasm_text_document::with_document(
+            parsed_text_document,
+            Default::default(),
+            |language_service, uri| language_service.debug_syntax_tree_for_uri(uri),
+        )
+    }
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn get_resolved_value(
+        document: JsValue,
+        position: JsValue,
+        encoding: Option<String>,
+    ) -> Result<JsValue, Error> {
+        let parsed_text_document = wasm_text_document::create_text_document(document)?;
+        let position = wasm_text_document::from_value(position, "position")?;
+
+        let resolved_value = wasm_text_document::with_document(
+            parsed_text_document,
+            wasm_text_document::position_encoding(encoding)?,
+            |language_service, uri| language_service.get_resolved_value(uri, position),
+        )?;
+
+        wasm_text_document::to_value(&resolved_value)
+    }
+}
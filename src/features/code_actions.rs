@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+
+use biome_css_syntax::{CssLanguage, CssSyntaxKind};
+use biome_rowan::{SyntaxNode, TextRange};
+use lsp_types::{
+    CodeAction, CodeActionKind, Range, TextDocumentItem, TextEdit, Uri, WorkspaceEdit,
+};
+
+#[cfg(feature = "diagnostics")]
+use crate::{cancellation::CancellationToken, features::diagnostics::fix_edit};
+use crate::{
+    converters::{from_proto, line_index::LineIndex, to_proto, PositionEncoding},
+    error::Error,
+    instrumentation::request,
+    service::LanguageService,
+    settings::CodeActionSettings,
+    store::StoreEntry,
+};
+
+/// The font size of the root element when the `codeActions.rootFontSize` setting isn't set, the default of browsers.
+const DEFAULT_ROOT_FONT_SIZE: f64 = 16.0;
+
+/// The units lengths are converted between, and the units each one is converted to.
+const CONVERTIBLE_UNITS: &[(&str, &[&str])] =
+    &[("px", &["rem", "em"]), ("rem", &["px"]), ("em", &["px"])];
+
+/// A length in one of the CONVERTIBLE_UNITS, e.g. `24px`.
+struct Length {
+    range: TextRange,
+    text: String,
+    value: f64,
+    /// The lowercase unit of the length.
+    unit: String,
+}
+
+impl Length {
+    fn from_node(node: &SyntaxNode<CssLanguage>) -> Option<Self> {
+        if node.kind() != CssSyntaxKind::CSS_REGULAR_DIMENSION {
+            return None;
+        }
+        let unit = node.last_token()?.text_trimmed().to_ascii_lowercase();
+        if !CONVERTIBLE_UNITS.iter().any(|(known, _)| *known == unit) {
+            return None;
+        }
+
+        Some(Self {
+            range: node.text_trimmed_range(),
+            text: node.text_trimmed().to_string(),
+            value: node.first_token()?.text_trimmed().parse().ok()?,
+            unit,
+        })
+    }
+
+    /// The text of the length converted to another unit, e.g. `1.5rem` for `24px` with a root font size of `16`. `em`
+    /// lengths are converted as if the font size of their element were the root font size.
+    fn convert(&self, unit: &str, root_font_size: f64) -> String {
+        let value = match unit {
+            "px" => self.value * root_font_size,
+            _ => self.value / root_font_size,
+        };
+        format!("{}{unit}", format_value(value))
+    }
+}
+
+/// Format a converted value with at most four decimals, e.g. `0.0625` or `24`.
+fn format_value(value: f64) -> String {
+    let rounded = (value * 10_000.0).round() / 10_000.0;
+    // Avoid `-0` for tiny negative values
+    format!("{}", if rounded == 0.0 { 0.0 } else { rounded })
+}
+
+/// Compute the code actions converting the lengths in a range between `px` and `rem` or `em`: for each length, one
+/// action per unit it converts to, then one action per unit converting every length of the document in that unit.
+fn compute_code_actions(
+    uri: &Uri,
+    root: &SyntaxNode<CssLanguage>,
+    line_index: &LineIndex,
+    range: Range,
+    encoding: PositionEncoding,
+    settings: &CodeActionSettings,
+) -> Result<Vec<CodeAction>, Error> {
+    let range = from_proto::text_range(line_index, range, encoding)?;
+    let root_font_size = settings
+        .root_font_size
+        .filter(|size| *size > 0)
+        .map_or(DEFAULT_ROOT_FONT_SIZE, f64::from);
+
+    let lengths = root
+        .descendants()
+        .filter_map(|node| Length::from_node(&node))
+        .collect::<Vec<_>>();
+    let in_range = lengths
+        .iter()
+        .filter(|length| length.range.intersect(range).is_some())
+        .collect::<Vec<_>>();
+
+    let action = |title: String, edits: Vec<TextEdit>| {
+        let mut changes = HashMap::new();
+        changes.insert(uri.clone(), edits);
+        CodeAction {
+            title,
+            kind: Some(CodeActionKind::REFACTOR_REWRITE),
+            edit: Some(WorkspaceEdit::new(changes)),
+            ..Default::default()
+        }
+    };
+    let edit = |length: &Length, unit: &str| -> Result<TextEdit, Error> {
+        Ok(TextEdit::new(
+            to_proto::range(line_index, length.range, encoding)?,
+            length.convert(unit, root_font_size),
+        ))
+    };
+
+    let mut actions = Vec::new();
+    for length in &in_range {
+        for unit in targets(&length.unit) {
+            let converted = edit(length, unit)?;
+            actions.push(action(
+                format!("Convert `{}` to `{}`", length.text, converted.new_text),
+                vec![converted],
+            ));
+        }
+    }
+
+    let mut units = in_range
+        .iter()
+        .map(|length| length.unit.as_str())
+        .collect::<Vec<_>>();
+    units.sort_unstable();
+    units.dedup();
+    for from in units {
+        for unit in targets(from) {
+            let edits = lengths
+                .iter()
+                .filter(|length| length.unit == from)
+                .map(|length| edit(length, unit))
+                .collect::<Result<Vec<_>, _>>()?;
+            actions.push(action(
+                format!("Convert every `{from}` length of the document to `{unit}`"),
+                edits,
+            ));
+        }
+    }
+
+    Ok(actions)
+}
+
+/// The units a length in a unit converts to.
+fn targets(unit: &str) -> &'static [&'static str] {
+    CONVERTIBLE_UNITS
+        .iter()
+        .find(|(known, _)| *known == unit)
+        .map_or(&[], |(_, targets)| targets)
+}
+
+impl LanguageService {
+    /// Get the code actions for a range of the given document: the quick fixes of the diagnostics in the range that can
+    /// be fixed, then the conversions of the lengths in the range between `px` and `rem` or `em`, with the root font
+    /// size of the `codeActions.rootFontSize` setting, `16` by default. Each length can be converted on its own, or along
+    /// with every length of the document in the same unit, e.g. when migrating a stylesheet to relative units.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The original source code as a `TextDocumentItem`.
+    /// * `range` - The range of the selection, or an empty range at the cursor.
+    ///
+    /// # Returns
+    ///
+    /// * The code actions, or `Error::InvalidRange` if the range isn't inside the document.
+    pub fn get_code_actions(
+        &self,
+        document: TextDocumentItem,
+        range: Range,
+    ) -> Result<Vec<CodeAction>, Error> {
+        let store_entry = self.store.get_or_update_document(document);
+        self.code_actions(&store_entry, range)
+    }
+
+    /// Same as `get_code_actions`, for a document already in the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    /// * `range` - The range of the selection, or an empty range at the cursor.
+    pub fn get_code_actions_for_uri(
+        &self,
+        uri: &Uri,
+        range: Range,
+    ) -> Result<Vec<CodeAction>, Error> {
+        self.code_actions(&*self.open_entry(uri)?, range)
+    }
+
+    fn code_actions(
+        &self,
+        store_entry: &StoreEntry,
+        range: Range,
+    ) -> Result<Vec<CodeAction>, Error> {
+        request!(self, "code_actions", store_entry);
+        let actions = compute_code_actions(
+            &store_entry.document.uri,
+            &store_entry.css_tree().syntax(),
+            store_entry.line_index(),
+            range,
+            self.encoding_for(store_entry),
+            &self.settings_for(&store_entry.document.uri).code_actions,
+        )?;
+
+        #[cfg(feature = "diagnostics")]
+        let actions = self
+            .quick_fixes(store_entry, range)?
+            .into_iter()
+            .chain(actions)
+            .collect();
+        Ok(actions)
+    }
+
+    /// Get the quick fixes of the diagnostics intersecting a range that can be fixed, e.g. removing a redundant
+    /// `@import`.
+    #[cfg(feature = "diagnostics")]
+    fn quick_fixes(
+        &self,
+        store_entry: &StoreEntry,
+        range: Range,
+    ) -> Result<Vec<CodeAction>, Error> {
+        let diagnostics = self
+            .diagnostics(store_entry, &CancellationToken::default())
+            .unwrap_or_default();
+        let line_index = store_entry.line_index();
+        let encoding = self.encoding_for(store_entry);
+
+        let mut actions = Vec::new();
+        for diagnostic in diagnostics {
+            if diagnostic.range.end < range.start || range.end < diagnostic.range.start {
+                continue;
+            }
+            let Some(edit) = fix_edit(&diagnostic) else {
+                continue;
+            };
+
+            let replaced = &store_entry.document.text
+                [from_proto::text_range(line_index, edit.range, encoding)?];
+            let replaced = replaced.trim_matches(|c: char| c == ',' || c.is_whitespace());
+            let title = if edit.new_text.is_empty() {
+                format!("Remove `{replaced}`")
+            } else {
+                format!("Replace `{replaced}` with `{}`", edit.new_text)
+            };
+            let mut changes = HashMap::new();
+            changes.insert(store_entry.document.uri.clone(), vec![edit]);
+            actions.push(CodeAction {
+                title,
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic]),
+                edit: Some(WorkspaceEdit::new(changes)),
+                ..Default::default()
+            });
+        }
+
+        Ok(actions)
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod wasm_bindings {
+    use crate::service::wasm_bindings::WasmLanguageService;
+    use crate::{error::Error, wasm_text_document};
+    use lsp_types::Uri;
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Get the code actions for a range of the given source code, converting its lengths between `px` and `rem` or `em`.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @param range The range of the selection, or an empty range at the cursor.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns A list of `CodeAction` objects.
+ */
+export async function get_code_actions(source: TextDocumentSource, range: import("vscode-languageserver-types").Range, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<import("vscode-languageserver-types").CodeAction[]>;
+
+export interface LanguageService {
+	/**
+	 * Get the code actions for a range of an open document, see `get_code_actions`.
+	 */
+	getCodeActions(uri: string, range: import("vscode-languageserver-types").Range): import("vscode-languageserver-types").CodeAction[];
+}
+"#;
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn get_code_actions(
+        document: JsValue,
+        range: JsValue,
+        encoding: Option<String>,
+    ) -> Result<JsValue, Error> {
+        let parsed_text_document = wasm_text_document::create_text_document(document)?;
+        let range = wasm_text_document::from_value(range, "range")?;
+
+        let code_actions = wasm_text_document::with_document(
+            parsed_text_document,
+            wasm_text_document::position_encoding(encoding)?,
+            |language_service, uri| language_service.get_code_actions_for_uri(uri, range),
+        )?;
+
+        wasm_text_document::to_value(&code_actions)
+    }
+
+    #[wasm_bindgen(js_class = LanguageService)]
+    impl WasmLanguageService {
+        #[wasm_bindgen(js_name = getCodeActions, skip_typescript)]
+        pub fn get_code_actions(&self, uri: JsValue, range: JsValue) -> Result<JsValue, Error> {
+            let uri: Uri = wasm_text_document::from_value(uri, "uri")?;
+            let range = wasm_text_document::from_value(range, "range")?;
+            let code_actions = self
+                .language_service
+                .get_code_actions_for_uri(&uri, range)?;
+
+            wasm_text_document::to_value(&code_actions)
+        }
+    }
+}
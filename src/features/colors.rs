@@ -1,12 +1,22 @@
 use biome_css_parser::CssParse;
-use biome_css_syntax::{CssLanguage, CssSyntaxKind};
-use biome_rowan::{AstNode, SyntaxNode};
+use biome_css_syntax::{CssFunction, CssLanguage, CssSyntaxKind};
+use biome_rowan::{AstNode, AstNodeList, AstSeparatedList, SyntaxNode, TextRange, TextSize};
 use csscolorparser::{parse as parse_color, NAMED_COLORS};
-use lsp_types::{Color, ColorInformation, ColorPresentation, Range, TextDocumentItem};
+use lsp_types::{
+    Color, ColorInformation, ColorPresentation, Range, TextDocumentItem, TextEdit, Uri,
+};
 
 use crate::{
-    converters::{line_index::LineIndex, to_proto::range, PositionEncoding},
+    cancellation::{CancellationToken, Cancelled},
+    converters::{from_proto, line_index::LineIndex, to_proto::range, PositionEncoding},
+    design_tokens::DesignTokens,
+    error::Error,
+    instrumentation::request,
+    progress::{Batcher, ProgressReporter},
+    scss::{ScssConstruct, ScssConstructKind},
     service::LanguageService,
+    store::StoreEntry,
+    visitor::{walk, Visitor},
 };
 
 fn convert_parsed_color(color: csscolorparser::Color) -> Color {
@@ -18,143 +28,737 @@ fn convert_parsed_color(color: csscolorparser::Color) -> Color {
     }
 }
 
-fn extract_colors_information(
-    node: &SyntaxNode<CssLanguage>,
-    line_index: &LineIndex,
-    encoding: PositionEncoding,
-) -> Vec<ColorInformation> {
-    let mut colors = Vec::new();
+/// Finds the colors of a tree: named colors, hex colors and color functions. The custom properties used with `var()`
+/// are also collected, for the design tokens whose value is a color.
+#[derive(Default)]
+pub(crate) struct ColorVisitor {
+    pub(crate) colors: Vec<(TextRange, Color)>,
+    pub(crate) variables: Vec<(TextRange, String)>,
+}
 
-    // PERF: This implementation will traverse the entire tree of the CSS file, matching many unnecessary nodes.
-    // A more efficient implementation would instead only look for colors in relevant contexts (e.g. CSS values, function parameters etc.)
+impl ColorVisitor {
+    fn push(&mut self, node: &SyntaxNode<CssLanguage>, color: csscolorparser::Color) {
+        self.colors
+            .push((node.text_trimmed_range(), convert_parsed_color(color)));
+    }
+}
 
-    node.children().for_each(|child| {
-        match child.kind() {
+impl Visitor for ColorVisitor {
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[
+            CssSyntaxKind::CSS_FUNCTION,
+            CssSyntaxKind::CSS_IDENTIFIER,
+            CssSyntaxKind::CSS_COLOR,
+        ])
+    }
+
+    fn visit(&mut self, node: &SyntaxNode<CssLanguage>) {
+        match node.kind() {
             CssSyntaxKind::CSS_FUNCTION => {
                 // CSS functions come in many forms and shapes, and are sometimes colors themselves or container of other colors
-                // In our case, we only care about functions that are colors (rgb, hsl, etc.) as the other branches will cover
-                // functions that contain colors (e.g. linear-gradient, light-dark, etc.)
-                if let Some(function_name) = child.first_child().map(|n| n.text().to_string()) {
-                    if matches!(
-                        function_name.as_str(),
-                        "rgb"
-                            | "rgba"
-                            | "hsl"
-                            | "hsla"
-                            | "hwb"
-                            | "lab"
-                            | "lch"
-                            | "hwba"
-                            | "hsv"
-                            | "hsva"
-                    ) {
-                        if let Ok(function_color) = parse_color(&node.text().to_string()) {
-                            colors.push(ColorInformation {
-                                color: convert_parsed_color(function_color),
-                                range: range(line_index, node.text_range(), encoding).unwrap(),
-                            });
-                        }
+                // In our case, we only care about functions that are colors (rgb, hsl, etc.) as the colors inside of other
+                // functions (e.g. linear-gradient, light-dark, etc.) are visited on their own
+                let Some(function_name) = node.first_child().map(|n| n.text_trimmed().to_string())
+                else {
+                    return;
+                };
+
+                if function_name == "var" {
+                    let variable = CssFunction::cast_ref(node)
+                        .and_then(|function| function.items().iter().next()?.ok())
+                        .map(|variable| variable.syntax().text_trimmed().to_string());
+                    if let Some(variable) = variable {
+                        self.variables.push((node.text_trimmed_range(), variable));
+                    }
+                } else if matches!(
+                    function_name.as_str(),
+                    "rgb"
+                        | "rgba"
+                        | "hsl"
+                        | "hsla"
+                        | "hwb"
+                        | "lab"
+                        | "lch"
+                        | "hwba"
+                        | "hsv"
+                        | "hsva"
+                ) {
+                    if let Ok(color) = parse_color(&node.text_trimmed().to_string()) {
+                        self.push(node, color);
                     }
                 }
             }
             // Any CSS identifier, such as a property name or basic value (ex: `color: red;` contains two identifiers)
             CssSyntaxKind::CSS_IDENTIFIER => {
-                if let Some(color) = NAMED_COLORS.get(&node.text().to_string()).map(|color| {
-                    csscolorparser::Color::from_rgba8(color[0], color[1], color[2], 255)
-                }) {
-                    colors.push(ColorInformation {
-                        color: convert_parsed_color(color),
-                        range: range(line_index, node.text_range(), encoding).unwrap(),
-                    });
+                if let Some(color) = NAMED_COLORS.get(&node.text_trimmed().to_string()) {
+                    self.push(
+                        node,
+                        csscolorparser::Color::from_rgba8(color[0], color[1], color[2], 255),
+                    );
                 }
             }
             // HEX colors
             CssSyntaxKind::CSS_COLOR => {
-                if let Ok(color) = parse_color(&node.text().to_string()) {
-                    colors.push(ColorInformation {
-                        color: convert_parsed_color(color),
-                        range: range(line_index, node.text_range(), encoding).unwrap(),
-                    });
+                if let Ok(color) = parse_color(&node.text_trimmed().to_string()) {
+                    self.push(node, color);
                 }
             }
             _ => {}
         }
-    });
+    }
+}
 
-    // TODO: Handle CSS variables
+/// Get the colors of the SCSS or LESS variables whose value is a color, e.g. `#ff0000` in `$primary: #ff0000;`. Variable
+/// declarations are masked before parsing, so their values aren't part of the syntax tree.
+pub(crate) fn scss_variable_colors(constructs: &[ScssConstruct]) -> Vec<(TextRange, Color)> {
+    constructs
+        .iter()
+        .filter(|construct| construct.kind == ScssConstructKind::Variable)
+        .filter_map(|construct| {
+            let (range, value) = construct.value.as_ref()?;
+            Some((*range, convert_parsed_color(parse_color(value).ok()?)))
+        })
+        .collect()
+}
 
-    for child in node.children() {
-        colors.extend(extract_colors_information(&child, line_index, encoding));
+/// Add the colors of the `var()` functions using design tokens whose value is a color, keeping the colors in source
+/// order.
+fn add_token_colors(
+    colors: &mut Vec<(TextRange, Color)>,
+    variables: &[(TextRange, String)],
+    tokens: &DesignTokens,
+) {
+    if tokens.is_empty() {
+        return;
     }
 
+    colors.extend(
+        variables
+            .iter()
+            .filter_map(|(range, variable)| Some((*range, tokens.get(variable)?.color()?))),
+    );
+    colors.sort_by_key(|(range, _)| range.start());
+}
+
+fn color_information(
+    colors: &[(TextRange, Color)],
+    line_index: &LineIndex,
+    encoding: PositionEncoding,
+) -> Vec<ColorInformation> {
     colors
+        .iter()
+        .filter_map(|(text_range, color)| {
+            Some(ColorInformation {
+                color: *color,
+                range: range(line_index, *text_range, encoding).ok()?,
+            })
+        })
+        .collect()
 }
 
+/// Find the colors of a document, one top-level rule at a time. `on_rule` is called with the colors of each rule and
+/// the offset of its end, for callers streaming the colors as they are found.
+///
+/// The colors of `scss_colors`, found outside of the syntax tree, are passed along with the rule whose range (with its
+/// leading trivia, where masked SCSS ends up) contains them.
 fn find_document_colors(
     css: &CssParse,
+    scss_colors: &[(TextRange, Color)],
     line_index: &LineIndex,
     encoding: PositionEncoding,
-) -> Vec<ColorInformation> {
-    let binding = css.tree().rules();
-    extract_colors_information(binding.syntax(), line_index, encoding)
+    tokens: &DesignTokens,
+    cancellation: &CancellationToken,
+    on_rule: &mut dyn FnMut(&[ColorInformation], TextSize),
+) -> Result<Vec<ColorInformation>, Cancelled> {
+    let mut colors = Vec::new();
+    let mut scss_colors = scss_colors.iter().peekable();
+
+    for rule in css.tree().rules().iter() {
+        let mut visitor = ColorVisitor::default();
+        walk(
+            rule.syntax(),
+            &mut [&mut visitor as &mut dyn Visitor],
+            cancellation,
+        )?;
+
+        let rule_end = rule.syntax().text_range().end();
+        while let Some(color) = scss_colors.next_if(|(range, _)| range.start() < rule_end) {
+            visitor.colors.push(*color);
+        }
+        add_token_colors(&mut visitor.colors, &visitor.variables, tokens);
+        visitor.colors.sort_by_key(|(range, _)| range.start());
+
+        let rule_colors = color_information(&visitor.colors, line_index, encoding);
+        on_rule(&rule_colors, rule_end);
+        colors.extend(rule_colors);
+    }
+
+    // The SCSS colors after the last rule
+    let remaining = scss_colors.copied().collect::<Vec<_>>();
+    if !remaining.is_empty() {
+        let remaining = color_information(&remaining, line_index, encoding);
+        on_rule(&remaining, css.syntax().text_range().end());
+        colors.extend(remaining);
+    }
+
+    Ok(colors)
+}
+
+/// Find the colors intersecting `range`, only going through the top-level rules intersecting it. The colors of
+/// `scss_colors`, found outside of the syntax tree, are included as well.
+fn find_colors_in_range(
+    css: &CssParse,
+    scss_colors: &[(TextRange, Color)],
+    range: TextRange,
+    tokens: &DesignTokens,
+    cancellation: &CancellationToken,
+) -> Result<Vec<(TextRange, Color)>, Cancelled> {
+    let mut visitor = ColorVisitor {
+        colors: scss_colors.to_vec(),
+        ..Default::default()
+    };
+
+    for rule in css.tree().rules().iter() {
+        let rule_range = rule.syntax().text_range();
+        if rule_range.end() < range.start() {
+            continue;
+        }
+        if rule_range.start() > range.end() {
+            break;
+        }
+
+        walk(
+            rule.syntax(),
+            &mut [&mut visitor as &mut dyn Visitor],
+            cancellation,
+        )?;
+    }
+
+    add_token_colors(&mut visitor.colors, &visitor.variables, tokens);
+    visitor.colors.sort_by_key(|(range, _)| range.start());
+    visitor
+        .colors
+        .retain(|(color_range, _)| color_range.intersect(range).is_some());
+    Ok(visitor.colors)
+}
+
+/// Format a component of a color with at most two decimals, e.g. `0.5` or `120`.
+fn format_component(component: f32) -> String {
+    let rounded = (component * 100.0).round() / 100.0;
+    format!("{rounded}")
+}
+
+fn format_percentage(fraction: f32) -> String {
+    format!("{}%", (fraction * 100.0).round())
+}
+
+/// The syntax of a color: `#` for hexadecimal colors, the name of the function without its `a` suffix for color
+/// functions, e.g. `hsl` for `hsla()`, and `name` for named colors.
+fn color_syntax(text: &str) -> &str {
+    if text.starts_with('#') {
+        "#"
+    } else if let Some((function, _)) = text.split_once('(') {
+        function.trim_end_matches('a')
+    } else {
+        "name"
+    }
+}
+
+/// Compute the presentations of a color replacing the text `current` at `range`: its name if it has one, then its
+/// `rgb()`, hexadecimal, `hsl()` and `hwb()` syntaxes. The syntax of `current` is moved first.
+fn compute_color_presentations(
+    color: Color,
+    range: Range,
+    current: &str,
+) -> Vec<ColorPresentation> {
+    let parsed = csscolorparser::Color::new(color.red, color.green, color.blue, color.alpha);
+    let [red, green, blue, _] = parsed.to_rgba8();
+    let opaque = color.alpha >= 1.0;
+    let alpha = format_component(color.alpha);
+
+    let mut labels = Vec::new();
+    if opaque {
+        // Several names can have the same color, e.g. `gray` and `grey`
+        let name = NAMED_COLORS
+            .entries()
+            .filter(|(_, rgb)| **rgb == [red, green, blue])
+            .map(|(name, _)| *name)
+            .min();
+        labels.extend(name.map(str::to_string));
+    }
+
+    labels.push(if opaque {
+        format!("rgb({red}, {green}, {blue})")
+    } else {
+        format!("rgba({red}, {green}, {blue}, {alpha})")
+    });
+    labels.push(parsed.to_hex_string());
+
+    let [hue, saturation, lightness, _] = parsed.to_hsla();
+    let (hue, saturation, lightness) = (
+        format_component(hue),
+        format_percentage(saturation),
+        format_percentage(lightness),
+    );
+    labels.push(if opaque {
+        format!("hsl({hue}, {saturation}, {lightness})")
+    } else {
+        format!("hsla({hue}, {saturation}, {lightness}, {alpha})")
+    });
+
+    let [hue, whiteness, blackness, _] = parsed.to_hwba();
+    let (hue, whiteness, blackness) = (
+        format_component(hue),
+        format_percentage(whiteness),
+        format_percentage(blackness),
+    );
+    labels.push(if opaque {
+        format!("hwb({hue} {whiteness} {blackness})")
+    } else {
+        format!("hwb({hue} {whiteness} {blackness} / {alpha})")
+    });
+
+    let current = current.trim().to_ascii_lowercase();
+    if let Some(index) = labels
+        .iter()
+        .position(|label| color_syntax(label) == color_syntax(&current))
+    {
+        let label = labels.remove(index);
+        labels.insert(0, label);
+    }
+
+    labels
+        .into_iter()
+        .map(|label| ColorPresentation {
+            text_edit: Some(TextEdit {
+                range,
+                new_text: label.clone(),
+            }),
+            label,
+            additional_text_edits: None,
+        })
+        .collect()
+}
+
+/// Pass at most `remaining` colors to the batcher, in order.
+fn extend_limited(
+    batcher: &mut Batcher<ColorInformation>,
+    colors: &[ColorInformation],
+    remaining: &mut usize,
+) {
+    let count = colors.len().min(*remaining);
+    batcher.extend(colors[..count].iter().cloned());
+    *remaining -= count;
 }
 
 impl LanguageService {
-    pub fn get_document_colors(&mut self, document: TextDocumentItem) -> Vec<ColorInformation> {
+    /// Get the colors of the given document. If a maximum number of colors was set in the ColorSettings of the document,
+    /// only the first colors of the document are returned.
+    pub fn get_document_colors(&self, document: TextDocumentItem) -> Vec<ColorInformation> {
+        self.get_document_colors_with_cancellation(document, &CancellationToken::default())
+            .unwrap_or_default()
+    }
+
+    /// Same as `get_document_colors`, stopping early with `Err(Cancelled)` once `cancellation` is cancelled.
+    pub fn get_document_colors_with_cancellation(
+        &self,
+        document: TextDocumentItem,
+        cancellation: &CancellationToken,
+    ) -> Result<Vec<ColorInformation>, Cancelled> {
         let store_entry = self.store.get_or_update_document(document);
+        self.document_colors(&store_entry, cancellation)
+    }
+
+    /// Same as `get_document_colors`, for a document already in the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    pub fn get_document_colors_for_uri(&self, uri: &Uri) -> Result<Vec<ColorInformation>, Error> {
+        let store_entry = self.open_entry(uri)?;
+        Ok(self
+            .document_colors(&store_entry, &CancellationToken::default())
+            .unwrap_or_default())
+    }
 
-        find_document_colors(
-            &store_entry.css_tree,
-            &store_entry.line_index,
-            self.encoding,
+    /// Same as `get_document_colors`, only returning the colors intersecting the given range, e.g. the visible range of
+    /// the document. Only the rules intersecting the range are analyzed, which avoids going through the whole document.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The original source code as a `TextDocumentItem`.
+    /// * `range` - The range of the document to find colors in.
+    ///
+    /// # Returns
+    ///
+    /// * The colors intersecting the range, or `Error::InvalidRange` if the range isn't inside the document.
+    pub fn get_document_colors_in_range(
+        &self,
+        document: TextDocumentItem,
+        range: Range,
+    ) -> Result<Vec<ColorInformation>, Error> {
+        let store_entry = self.store.get_or_update_document(document);
+        request!(self, "colors", store_entry);
+        let line_index = store_entry.line_index();
+        let encoding = self.encoding_for(&store_entry);
+        let text_range = from_proto::text_range(line_index, range, encoding)?;
+
+        let colors = find_colors_in_range(
+            &store_entry.css_tree(),
+            &scss_variable_colors(&store_entry.scss_constructs()),
+            text_range,
+            &self.design_tokens(),
+            &CancellationToken::default(),
         )
+        .unwrap_or_default();
+        let mut colors = color_information(&colors, line_index, encoding);
+        if let Some(max_colors) = self
+            .settings_for(&store_entry.document.uri)
+            .colors
+            .max_document_colors
+        {
+            colors.truncate(max_colors);
+        }
+
+        Ok(colors)
+    }
+
+    fn document_colors(
+        &self,
+        store_entry: &StoreEntry,
+        cancellation: &CancellationToken,
+    ) -> Result<Vec<ColorInformation>, Cancelled> {
+        request!(self, "colors", store_entry);
+        let mut colors = store_entry.try_cached("document_colors", |entry| {
+            let analysis = entry.analysis(cancellation)?;
+            let mut colors = analysis.colors.clone();
+            add_token_colors(&mut colors, &analysis.variables, &self.design_tokens());
+
+            Ok(color_information(
+                &colors,
+                entry.line_index(),
+                self.encoding_for(entry),
+            ))
+        })?;
+        if let Some(max_colors) = self
+            .settings_for(&store_entry.document.uri)
+            .colors
+            .max_document_colors
+        {
+            colors.truncate(max_colors);
+        }
+
+        Ok(colors)
     }
 
+    /// Same as `get_document_colors_with_cancellation`, passing the colors to `on_colors` in batches as they are found
+    /// instead of returning them all at once, e.g. to send them as partial results of the request. Progress through the
+    /// document is reported to `progress`.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The original source code as a `TextDocumentItem`.
+    /// * `cancellation` - A token to stop early, in which case some batches may already have been passed.
+    /// * `batch_size` - The maximum number of colors per batch, `progress::DEFAULT_BATCH_SIZE` is a good default.
+    /// * `on_colors` - Called with each batch of colors.
+    /// * `progress` - Receives the progress of the request, `&mut ()` to ignore it.
+    pub fn stream_document_colors(
+        &self,
+        document: TextDocumentItem,
+        cancellation: &CancellationToken,
+        batch_size: usize,
+        on_colors: &mut dyn FnMut(Vec<ColorInformation>),
+        progress: &mut dyn ProgressReporter,
+    ) -> Result<(), Cancelled> {
+        let store_entry = self.store.get_or_update_document(document);
+        request!(self, "colors", store_entry);
+        let text_len = store_entry.document.text.len().max(1) as u64;
+
+        progress.begin("Finding colors");
+        let mut batcher = Batcher::new(batch_size, on_colors);
+        let mut remaining = self
+            .settings_for(&store_entry.document.uri)
+            .colors
+            .max_document_colors
+            .unwrap_or(usize::MAX);
+        let mut last_percentage = 0;
+        let mut computed = false;
+
+        let tokens = self.design_tokens();
+        let colors = store_entry.try_cached("document_colors", |entry| {
+            computed = true;
+            find_document_colors(
+                &entry.css_tree(),
+                &scss_variable_colors(&entry.scss_constructs()),
+                entry.line_index(),
+                self.encoding_for(entry),
+                &tokens,
+                cancellation,
+                &mut |colors, end| {
+                    extend_limited(&mut batcher, colors, &mut remaining);
+
+                    let percentage = (u64::from(u32::from(end)) * 100 / text_len) as u32;
+                    if percentage > last_percentage {
+                        last_percentage = percentage;
+                        progress.report(percentage);
+                    }
+                },
+            )
+        });
+
+        // Cached colors were found without going through the document, they can be passed right away
+        if let (Ok(colors), false) = (&colors, computed) {
+            extend_limited(&mut batcher, colors, &mut remaining);
+        }
+        batcher.flush();
+        progress.end();
+
+        colors.map(|_| ())
+    }
+
+    /// Get the ways a color can be written, as requested by `textDocument/colorPresentation` when a color is picked,
+    /// e.g. `rgb(255, 0, 0)`, `#ff0000` or `hsl(0, 100%, 50%)`. The syntax of the color being replaced comes first, so
+    /// that picking a color keeps its format.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The original source code as a `TextDocumentItem`.
+    /// * `color` - The picked color.
+    /// * `range` - The range of the color being replaced, edited by the presentations.
+    ///
+    /// # Returns
+    ///
+    /// * The presentations of the color, or `Error::InvalidRange` if the range isn't inside the document.
     pub fn get_color_presentations(
-        self,
-        _document: TextDocumentItem,
-        _color: ColorInformation,
-        _range: Range,
-    ) -> Vec<ColorPresentation> {
-        todo!("Implement get_color_presentations in colors.rs");
+        &self,
+        document: TextDocumentItem,
+        color: Color,
+        range: Range,
+    ) -> Result<Vec<ColorPresentation>, Error> {
+        let store_entry = self.store.get_or_update_document(document);
+        self.color_presentations(&store_entry, color, range)
+    }
+
+    /// Same as `get_color_presentations`, for a document already in the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    /// * `color` - The picked color.
+    /// * `range` - The range of the color being replaced, edited by the presentations.
+    pub fn get_color_presentations_for_uri(
+        &self,
+        uri: &Uri,
+        color: Color,
+        range: Range,
+    ) -> Result<Vec<ColorPresentation>, Error> {
+        self.color_presentations(&*self.open_entry(uri)?, color, range)
+    }
+
+    fn color_presentations(
+        &self,
+        store_entry: &StoreEntry,
+        color: Color,
+        range: Range,
+    ) -> Result<Vec<ColorPresentation>, Error> {
+        request!(self, "color_presentations", store_entry);
+        let text_range = from_proto::text_range(
+            store_entry.line_index(),
+            range,
+            self.encoding_for(store_entry),
+        )?;
+        let current = &store_entry.document.text
+            [usize::from(text_range.start())..usize::from(text_range.end())];
+
+        Ok(compute_color_presentations(color, range, current))
     }
 }
 
 #[cfg(feature = "wasm")]
 mod wasm_bindings {
     use crate::{
-        converters::{line_index::LineIndex, PositionEncoding},
-        parser::parse_css,
+        cancellation::CancellationToken,
+        converters::{from_proto, line_index::LineIndex},
+        design_tokens::DesignTokens,
+        error::Error,
+        less::scan_less,
+        parser::{parse_css_with_dialect, CssDialect},
+        scss::scan_scss,
     };
+    use biome_css_parser::CssParse;
+    use biome_rowan::TextRange;
+    use lsp_types::{Color, TextDocumentItem, Uri};
 
-    use super::find_document_colors;
-    use serde_wasm_bindgen;
+    use super::{
+        color_information, find_colors_in_range, find_document_colors, scss_variable_colors,
+    };
+    use crate::service::wasm_bindings::WasmLanguageService;
     use wasm_bindgen::prelude::*;
 
+    /// Parse a document in its dialect, along with the colors of its SCSS or LESS variables.
+    fn parse_document(document: &TextDocumentItem) -> (CssParse, Vec<(TextRange, Color)>) {
+        let dialect = CssDialect::from_document(document);
+        let scss_colors = match dialect {
+            CssDialect::Scss => scss_variable_colors(&scan_scss(&document.text).constructs),
+            CssDialect::Less => scss_variable_colors(&scan_less(&document.text).constructs),
+            CssDialect::Css => Vec::new(),
+        };
+        (parse_css_with_dialect(&document.text, dialect), scss_colors)
+    }
+
     #[wasm_bindgen(typescript_custom_section)]
-    const TS_APPEND_CONTENT: &'static str = r#"export async function get_document_colors(source: import("vscode-languageserver-textdocument").TextDocument): Promise<import("vscode-languageserver-types").ColorInformation[]>;"#;
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Get the colors of the given source code.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @param maxColors The maximum number of colors to return, only the first colors of the document are returned if it has more.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns A list of `ColorInformation` objects, in source order.
+ */
+export async function get_document_colors(source: TextDocumentSource, maxColors?: number, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<import("vscode-languageserver-types").ColorInformation[]>;
+
+export interface LanguageService {
+	/**
+	 * Get the colors of an open document, see `get_document_colors`.
+	 */
+	getDocumentColors(uri: string): import("vscode-languageserver-types").ColorInformation[];
+}
+"#;
 
     #[wasm_bindgen(skip_typescript)]
-    pub fn get_document_colors(document: JsValue) -> JsValue {
-        let parsed_text_document = crate::wasm_text_document::create_text_document(document);
-        let document_colors = find_document_colors(
-            &parse_css(&parsed_text_document.text),
+    pub fn get_document_colors(
+        document: JsValue,
+        max_colors: Option<u32>,
+        encoding: Option<String>,
+    ) -> Result<JsValue, Error> {
+        let parsed_text_document = crate::wasm_text_document::create_text_document(document)?;
+        let (css, scss_colors) = parse_document(&parsed_text_document);
+        let mut document_colors = find_document_colors(
+            &css,
+            &scss_colors,
             &LineIndex::new(&parsed_text_document.text),
-            PositionEncoding::Wide(crate::converters::WideEncoding::Utf16),
-        );
+            crate::wasm_text_document::position_encoding(encoding)?,
+            &DesignTokens::default(),
+            &CancellationToken::default(),
+            &mut |_, _| {},
+        )
+        .unwrap_or_default();
+        // Truncated before serializing, which is the costly part for documents with many colors
+        if let Some(max_colors) = max_colors {
+            document_colors.truncate(max_colors as usize);
+        }
 
-        serde_wasm_bindgen::to_value(&document_colors).unwrap()
+        crate::wasm_text_document::to_value(&document_colors)
     }
 
     #[wasm_bindgen(typescript_custom_section)]
-    const TS_APPEND_CONTENT: &'static str = r#"export async function get_color_presentations(source: import("vscode-languageserver-textdocument").TextDocument, color: import("vscode-languageserver-types").ColorInformation, range: import("vscode-languageserver-types").Range): Promise<import("vscode-languageserver-types").ColorPresentation[]>;"#;
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Get the colors of the given source code intersecting a range, only analyzing the rules intersecting it.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @param range The range to find colors in, e.g. the visible range of the document.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns A list of `ColorInformation` objects, in source order.
+ */
+export async function get_document_colors_in_range(source: TextDocumentSource, range: import("vscode-languageserver-types").Range, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<import("vscode-languageserver-types").ColorInformation[]>;
+"#;
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn get_document_colors_in_range(
+        document: JsValue,
+        range: JsValue,
+        encoding: Option<String>,
+    ) -> Result<JsValue, Error> {
+        let parsed_text_document = crate::wasm_text_document::create_text_document(document)?;
+        let range = crate::wasm_text_document::from_value(range, "range")?;
+        let line_index = LineIndex::new(&parsed_text_document.text);
+        let encoding = crate::wasm_text_document::position_encoding(encoding)?;
+
+        let text_range = from_proto::text_range(&line_index, range, encoding)?;
+        let (css, scss_colors) = parse_document(&parsed_text_document);
+        let colors = find_colors_in_range(
+            &css,
+            &scss_colors,
+            text_range,
+            &DesignTokens::default(),
+            &CancellationToken::default(),
+        )
+        .unwrap_or_default();
+
+        crate::wasm_text_document::to_value(&color_information(&colors, &line_index, encoding))
+    }
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Get the ways a color can be written when it is picked, e.g. `rgb(255, 0, 0)`, `#ff0000` or
+ * `hsl(0, 100%, 50%)`. The syntax of the color being replaced comes first.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @param color The picked color.
+ * @param range The range of the color being replaced, edited by the presentations.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns A list of `ColorPresentation` objects.
+ */
+export async function get_color_presentations(source: TextDocumentSource, color: import("vscode-languageserver-types").Color, range: import("vscode-languageserver-types").Range, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<import("vscode-languageserver-types").ColorPresentation[]>;
+
+export interface LanguageService {
+	/**
+	 * Get the presentations of a color picked in an open document, see `get_color_presentations`.
+	 */
+	getColorPresentations(uri: string, color: import("vscode-languageserver-types").Color, range: import("vscode-languageserver-types").Range): import("vscode-languageserver-types").ColorPresentation[];
+}
+"#;
 
     #[wasm_bindgen(skip_typescript)]
     pub fn get_color_presentations(
-        _document: JsValue,
-        _color: JsValue,
-        _range: JsValue,
-    ) -> JsValue {
-        todo!("Implement get_color_presentations in colors/wasm_bindings.rs");
+        document: JsValue,
+        color: JsValue,
+        range: JsValue,
+        encoding: Option<String>,
+    ) -> Result<JsValue, Error> {
+        let parsed_text_document = crate::wasm_text_document::create_text_document(document)?;
+        let color = crate::wasm_text_document::from_value(color, "color")?;
+        let range = crate::wasm_text_document::from_value(range, "range")?;
+
+        let presentations = crate::wasm_text_document::with_document(
+            parsed_text_document,
+            crate::wasm_text_document::position_encoding(encoding)?,
+            |language_service, uri| {
+                language_service.get_color_presentations_for_uri(uri, color, range)
+            },
+        )?;
+
+        crate::wasm_text_document::to_value(&presentations)
+    }
+
+    #[wasm_bindgen(js_class = LanguageService)]
+    impl WasmLanguageService {
+        #[wasm_bindgen(js_name = getDocumentColors, skip_typescript)]
+        pub fn get_document_colors(&self, uri: JsValue) -> Result<JsValue, Error> {
+            let uri: Uri = crate::wasm_text_document::from_value(uri, "uri")?;
+            let colors = self.language_service.get_document_colors_for_uri(&uri)?;
+
+            crate::wasm_text_document::to_value(&colors)
+        }
+
+        #[wasm_bindgen(js_name = getColorPresentations, skip_typescript)]
+        pub fn get_color_presentations(
+            &self,
+            uri: JsValue,
+            color: JsValue,
+            range: JsValue,
+        ) -> Result<JsValue, Error> {
+            let uri: Uri = crate::wasm_text_document::from_value(uri, "uri")?;
+            let presentations = self.language_service.get_color_presentations_for_uri(
+                &uri,
+                crate::wasm_text_document::from_value(color, "color")?,
+                crate::wasm_text_document::from_value(range, "range")?,
+            )?;
+
+            crate::wasm_text_document::to_value(&presentations)
+        }
     }
 }
@@ -0,0 +1,1162 @@
+use biome_css_syntax::{CssFunction, CssGenericProperty, CssLanguage, CssSyntaxKind};
+use biome_rowan::{AstNode, SyntaxNode, SyntaxToken, TextSize};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionItemTag, Documentation, InsertTextFormat,
+    MarkupContent, MarkupKind, Position, Range, TextDocumentItem, TextEdit, Uri,
+};
+use rustc_hash::FxHashSet;
+
+use crate::{
+    container_queries::SIZE_FEATURES,
+    converters::from_proto,
+    data::{CssDataSet, DataEntry, EntryStatus},
+    descriptors::{descriptor_at_rule, enclosing_block, enclosing_prelude},
+    design_tokens::{DesignToken, DesignTokens},
+    directives::{has_unparsed_at_rules, CustomDirective},
+    emmet::expand_abbreviation,
+    error::Error,
+    instrumentation::request,
+    keyframes::{keyframe_offset, keyframe_selectors},
+    layers::enclosing_layer,
+    pages::{is_page_context_property, page_context, MARGIN_RULES, PAGE_PSEUDO_CLASSES},
+    parser::CssDialect,
+    scss::{scss_modules, ScssConstructKind, ScssMember, ScssModule},
+    service::LanguageService,
+    settings::CompletionSettings,
+    store::StoreEntry,
+    workspace_index::SymbolKind,
+};
+
+/// A source of completion items contributed by an embedder, e.g. the design tokens of a project inside `var()` or
+/// the asset paths of a bundler manifest inside `url()`.
+///
+/// Providers are added to a LanguageService with `add_completion_provider`, and asked for items on every completion
+/// request. They decide from the CompletionContext whether they have anything to offer.
+pub trait CompletionProvider: Send + Sync {
+    /// Get the completion items for a position, usually none outside of the locations the provider knows about.
+    fn provide(&self, context: &CompletionContext) -> Vec<CompletionItem>;
+}
+
+/// Where in a document completion was requested.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CompletionLocation {
+    /// A selector, or where a new rule can start.
+    Selector,
+    /// The name of a declaration, or an empty spot of a declaration block.
+    PropertyName,
+    /// The value of a declaration, after its colon.
+    PropertyValue { property: String },
+    /// The arguments of a function, e.g. `var(|)`, and the property of the declaration the function is in, if any.
+    FunctionArgument {
+        function: String,
+        property: Option<String>,
+    },
+    /// Any other location, e.g. the prelude of an at-rule or an SCSS directive.
+    Other,
+}
+
+/// The position completion was requested at, and what surrounds it.
+pub struct CompletionContext<'a> {
+    document: &'a TextDocumentItem,
+    position: Position,
+    offset: TextSize,
+    token: Option<SyntaxToken<CssLanguage>>,
+    location: CompletionLocation,
+    data: &'a CssDataSet,
+}
+
+impl<'a> CompletionContext<'a> {
+    fn new(
+        store_entry: &'a StoreEntry,
+        position: Position,
+        offset: TextSize,
+        data: &'a CssDataSet,
+    ) -> Self {
+        let token = store_entry
+            .css_tree()
+            .syntax()
+            .token_at_offset(offset)
+            .left_biased();
+        // SCSS constructs are masked before parsing, the tree doesn't tell where in them the position is
+        let in_scss_construct = store_entry.scss_constructs().iter().any(|construct| {
+            construct.kind != ScssConstructKind::VariableReference
+                && construct.range.contains_inclusive(offset)
+        });
+        let location = match &token {
+            Some(token) if !in_scss_construct => find_location(token, offset),
+            _ => CompletionLocation::Other,
+        };
+
+        Self {
+            document: &store_entry.document,
+            position,
+            offset,
+            token,
+            location,
+            data,
+        }
+    }
+
+    pub fn uri(&self) -> &Uri {
+        &self.document.uri
+    }
+
+    /// The text of the whole document.
+    pub fn text(&self) -> &str {
+        &self.document.text
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// The offset of the position in the text of the document.
+    pub fn offset(&self) -> TextSize {
+        self.offset
+    }
+
+    /// The token ending at or containing the position, if any.
+    pub fn token(&self) -> Option<&SyntaxToken<CssLanguage>> {
+        self.token.as_ref()
+    }
+
+    pub fn location(&self) -> &CompletionLocation {
+        &self.location
+    }
+
+    /// The part of the word before the position, e.g. `--brand` in `var(--brand|)`, to filter items with.
+    pub fn prefix(&self) -> &str {
+        let text = &self.document.text[..usize::from(self.offset)];
+        let start = text
+            .char_indices()
+            .rev()
+            .find(|(_, char)| !(char.is_alphanumeric() || *char == '-' || *char == '_'))
+            .map_or(0, |(index, char)| index + char.len_utf8());
+        &text[start..]
+    }
+
+    /// The CSS data known to the LanguageService, including its custom data.
+    pub fn css_data(&self) -> &CssDataSet {
+        self.data
+    }
+}
+
+fn find_location(token: &SyntaxToken<CssLanguage>, offset: TextSize) -> CompletionLocation {
+    let Some(parent) = token.parent() else {
+        return CompletionLocation::Other;
+    };
+
+    let property = parent
+        .ancestors()
+        .find_map(CssGenericProperty::cast)
+        .and_then(|property| {
+            let after_colon = property
+                .colon_token()
+                .is_ok_and(|colon| colon.text_trimmed_range().end() <= offset);
+            let name = property.name().ok()?.syntax().text_trimmed().to_string();
+            Some((name, after_colon))
+        });
+
+    let function = parent
+        .ancestors()
+        .take_while(|node| node.kind() != CssSyntaxKind::CSS_GENERIC_PROPERTY)
+        .find_map(CssFunction::cast)
+        .filter(|function| {
+            function
+                .l_paren_token()
+                .is_ok_and(|l_paren| l_paren.text_trimmed_range().end() <= offset)
+        })
+        .and_then(|function| {
+            Some(
+                function
+                    .name()
+                    .ok()?
+                    .value_token()
+                    .ok()?
+                    .text_trimmed()
+                    .to_ascii_lowercase(),
+            )
+        });
+
+    match (function, property) {
+        (Some(function), property) => CompletionLocation::FunctionArgument {
+            function,
+            property: property.map(|(name, _)| name),
+        },
+        (None, Some((property, true))) => CompletionLocation::PropertyValue { property },
+        (None, Some((_, false))) => CompletionLocation::PropertyName,
+        (None, None) => {
+            let in_block = parent.ancestors().any(|node| {
+                matches!(
+                    node.kind(),
+                    CssSyntaxKind::CSS_DECLARATION_LIST_BLOCK
+                        | CssSyntaxKind::CSS_DECLARATION_OR_RULE_BLOCK
+                        | CssSyntaxKind::CSS_DECLARATION_OR_AT_RULE_BLOCK
+                )
+            });
+            let in_selector = parent
+                .ancestors()
+                .any(|node| node.kind() == CssSyntaxKind::CSS_SELECTOR_LIST);
+
+            if in_selector {
+                CompletionLocation::Selector
+            } else if in_block {
+                CompletionLocation::PropertyName
+            } else if matches!(
+                parent.kind(),
+                CssSyntaxKind::CSS_ROOT | CssSyntaxKind::CSS_RULE_LIST
+            ) {
+                CompletionLocation::Selector
+            } else {
+                CompletionLocation::Other
+            }
+        }
+    }
+}
+
+/// The design tokens of the project, offered as the custom properties they are used through inside `var()`.
+fn design_token_items(context: &CompletionContext, tokens: &DesignTokens) -> Vec<CompletionItem> {
+    match context.location() {
+        CompletionLocation::FunctionArgument { function, .. } if function == "var" => {
+            tokens.tokens().iter().map(design_token_item).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// The members of SCSS documents and of the stylesheets they load: variables after a `$` (e.g. `$primary`), mixins
+/// after `@include` and functions in values along with the namespaces of the modules they use, or the members of a
+/// module after its namespace (e.g. `colors.$primary` or `@include buttons.`). The `$` of variables isn't part of the
+/// word the client replaces, so it isn't inserted again.
+fn scss_member_items(
+    context: &CompletionContext,
+    members: impl FnOnce() -> Vec<ScssMember>,
+    modules: impl FnOnce() -> Vec<ScssModule>,
+) -> Vec<CompletionItem> {
+    let mut before = &context.text()[..usize::from(context.offset()) - context.prefix().len()];
+    let is_variable = match before.strip_suffix('$') {
+        Some(before_variable) => {
+            before = before_variable;
+            true
+        }
+        None => false,
+    };
+    let namespace = match before.strip_suffix('.') {
+        Some(before_namespace) => {
+            let start = before_namespace
+                .char_indices()
+                .rev()
+                .find(|(_, char)| !(char.is_alphanumeric() || *char == '-' || *char == '_'))
+                .map_or(0, |(index, char)| index + char.len_utf8());
+            before = &before_namespace[..start];
+            match &before_namespace[start..] {
+                // A dot after anything but a namespace, e.g. a class selector
+                "" => return Vec::new(),
+                namespace => Some(namespace),
+            }
+        }
+        None => None,
+    };
+
+    let is_value = match context.location() {
+        CompletionLocation::PropertyValue { .. } => true,
+        CompletionLocation::FunctionArgument { function, .. } => function != "var",
+        _ => false,
+    };
+    let kind = if is_variable {
+        ScssConstructKind::Variable
+    } else if before.trim_end().ends_with("@include")
+        && (namespace.is_some() || before.ends_with(char::is_whitespace))
+    {
+        ScssConstructKind::Mixin
+    } else if namespace.is_some() || is_value {
+        ScssConstructKind::Function
+    } else {
+        return Vec::new();
+    };
+
+    let namespaces = match (kind, namespace) {
+        (ScssConstructKind::Mixin | ScssConstructKind::Function, None) => modules(),
+        _ => Vec::new(),
+    };
+    let namespace_items = namespaces.into_iter().filter_map(|module| {
+        Some(CompletionItem {
+            label: module.namespace?,
+            kind: Some(CompletionItemKind::MODULE),
+            detail: Some(format!("@use \"{}\"", module.url)),
+            ..Default::default()
+        })
+    });
+
+    members()
+        .into_iter()
+        .filter(|member| member.kind == kind && member.namespace.as_deref() == namespace)
+        .map(|member| match member.kind {
+            ScssConstructKind::Variable => CompletionItem {
+                insert_text: Some(member.name.trim_start_matches('$').to_string()),
+                filter_text: Some(member.name.trim_start_matches('$').to_string()),
+                label: member.name,
+                kind: Some(CompletionItemKind::VARIABLE),
+                detail: member.value,
+                ..Default::default()
+            },
+            _ => CompletionItem {
+                detail: Some(member.signature()),
+                label: member.name,
+                kind: Some(CompletionItemKind::FUNCTION),
+                ..Default::default()
+            },
+        })
+        .chain(namespace_items)
+        .collect()
+}
+
+/// The values of CSS Modules declared or imported by a CSS document with `@value`, in values.
+fn icss_value_items(
+    context: &CompletionContext,
+    members: impl FnOnce() -> Vec<ScssMember>,
+) -> Vec<CompletionItem> {
+    match context.location() {
+        CompletionLocation::PropertyValue { .. } => {}
+        CompletionLocation::FunctionArgument { function, .. } if function != "var" => {}
+        _ => return Vec::new(),
+    }
+
+    members()
+        .into_iter()
+        .filter(|member| member.kind == ScssConstructKind::Variable)
+        .map(|member| CompletionItem {
+            label: member.name,
+            kind: Some(CompletionItemKind::CONSTANT),
+            detail: member.value,
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// The custom at-rules of the `css.customDirectives` setting after a `@` (e.g. `@apply`), and its custom functions in
+/// values (e.g. `theme()`). As with SCSS variables, the `@` isn't part of the word the client replaces.
+fn custom_directive_items(
+    context: &CompletionContext,
+    directives: &[String],
+) -> Vec<CompletionItem> {
+    let before = &context.text()[..usize::from(context.offset()) - context.prefix().len()];
+    let is_at_rule = before.ends_with('@');
+    let is_value = match context.location() {
+        CompletionLocation::PropertyValue { .. } => true,
+        CompletionLocation::FunctionArgument { function, .. } => function != "var",
+        _ => false,
+    };
+
+    directives
+        .iter()
+        .filter_map(|directive| match CustomDirective::parse(directive) {
+            CustomDirective::AtRule(name) if is_at_rule => Some(CompletionItem {
+                label: name.to_string(),
+                kind: Some(CompletionItemKind::KEYWORD),
+                detail: Some(format!("@{name} (custom at-rule)")),
+                ..Default::default()
+            }),
+            CustomDirective::Function(name) if is_value && !is_at_rule => Some(CompletionItem {
+                label: name.to_string(),
+                kind: Some(CompletionItemKind::FUNCTION),
+                detail: Some(format!("{name}() (custom function)")),
+                ..Default::default()
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The at-rules of the CSS data after a `@` outside of values, e.g. `@scope`, documented by their data. As with custom
+/// directives, the `@` isn't part of the word the client replaces.
+fn at_rule_items(context: &CompletionContext) -> Vec<CompletionItem> {
+    let before = &context.text()[..usize::from(context.offset()) - context.prefix().len()];
+    if !before.ends_with('@')
+        || matches!(
+            context.location(),
+            CompletionLocation::PropertyValue { .. } | CompletionLocation::FunctionArgument { .. }
+        )
+    {
+        return Vec::new();
+    }
+
+    context
+        .css_data()
+        .at_directives()
+        .map(|at_rule| CompletionItem {
+            label: at_rule.name.trim_start_matches('@').to_string(),
+            kind: Some(CompletionItemKind::KEYWORD),
+            documentation: Some(Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: at_rule.documentation(),
+            })),
+            tags: (at_rule.status == EntryStatus::Obsolete)
+                .then(|| vec![CompletionItemTag::DEPRECATED]),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// The layers of the workspace in the prelude of `@layer` and in the `layer()` of `@import`. In layer blocks, the
+/// layers nested in the block are offered by their name relative to it, e.g. `buttons` in `@layer components { … }`.
+fn layer_items(
+    context: &CompletionContext,
+    layers: impl FnOnce() -> Vec<String>,
+) -> Vec<CompletionItem> {
+    let before = &context.text()[..usize::from(context.offset()) - context.prefix().len()];
+    let statement = before
+        .rfind(['{', '}', ';'])
+        .map_or(before, |end| &before[end + 1..])
+        .trim_start();
+    let in_prelude = statement.strip_prefix("@layer").is_some_and(|names| {
+        names.starts_with(char::is_whitespace)
+            && names.chars().all(|char| {
+                char.is_alphanumeric()
+                    || matches!(char, '-' | '_' | '.' | ',')
+                    || char.is_whitespace()
+            })
+    });
+    let in_import = statement.starts_with("@import") && statement.ends_with("layer(");
+    if !in_prelude && !in_import {
+        return Vec::new();
+    }
+
+    // Nested layers are completed after the name of their parent, e.g. `buttons` after `@layer components.`
+    let qualifier = statement
+        .rsplit(|char: char| char.is_whitespace() || matches!(char, ',' | '('))
+        .next()
+        .unwrap_or_default();
+    let parent = context
+        .token()
+        .and_then(|token| token.parent())
+        .and_then(|node| enclosing_layer(&node))
+        .filter(|parent| !parent.is_empty() && in_prelude);
+    layers()
+        .into_iter()
+        .filter_map(|layer| {
+            let layer = match &parent {
+                Some(parent) => layer.strip_prefix(parent.as_str())?.strip_prefix('.')?,
+                None => &layer,
+            };
+            Some(layer.strip_prefix(qualifier)?.to_string())
+        })
+        .filter(|layer| !layer.is_empty())
+        .map(|layer| CompletionItem {
+            label: layer,
+            kind: Some(CompletionItemKind::MODULE),
+            detail: Some("Cascade layer".to_string()),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// The items of the prelude of `@container`: the containers declared in the workspace before the query, the size
+/// features in parentheses, e.g. `inline-size` in `@container (inline-size > 400px)`, the `style()` queries where a
+/// query can start, and the custom properties of the workspace in `style()`, e.g. `--theme` in `style(--theme: dark)`.
+fn container_query_items(
+    context: &CompletionContext,
+    containers: impl FnOnce() -> Vec<String>,
+    custom_properties: impl FnOnce() -> Vec<String>,
+) -> Vec<CompletionItem> {
+    let before = &context.text()[..usize::from(context.offset()) - context.prefix().len()];
+    let statement = before
+        .rfind(['{', '}', ';'])
+        .map_or(before, |end| &before[end + 1..])
+        .trim_start();
+    let Some(prelude) = statement
+        .strip_prefix("@container")
+        .filter(|prelude| prelude.starts_with(char::is_whitespace))
+    else {
+        return Vec::new();
+    };
+
+    // The innermost parenthesis the position is in, if any
+    let mut parentheses = Vec::new();
+    for (index, char) in prelude.char_indices() {
+        match char {
+            '(' => parentheses.push(index),
+            ')' => {
+                parentheses.pop();
+            }
+            _ => {}
+        }
+    }
+
+    match parentheses.last() {
+        Some(&open) if prelude[..open].ends_with("style") => {
+            if prelude[open..].contains(':') {
+                return Vec::new();
+            }
+            custom_properties()
+                .into_iter()
+                .map(|property| CompletionItem {
+                    label: property,
+                    kind: Some(CompletionItemKind::VARIABLE),
+                    ..Default::default()
+                })
+                .collect()
+        }
+        Some(&open) => {
+            if !prelude[open + 1..].trim().is_empty() {
+                return Vec::new();
+            }
+            SIZE_FEATURES
+                .iter()
+                .flat_map(|feature| {
+                    let prefixes: &[&str] = if feature.is_range {
+                        &["", "min-", "max-"]
+                    } else {
+                        &[""]
+                    };
+                    prefixes.iter().map(|prefix| CompletionItem {
+                        label: format!("{prefix}{}", feature.name),
+                        kind: Some(CompletionItemKind::PROPERTY),
+                        documentation: Some(Documentation::String(feature.description.to_string())),
+                        ..Default::default()
+                    })
+                })
+                .collect()
+        }
+        None => {
+            let words = prelude.split_whitespace().collect::<Vec<_>>();
+            let can_start_query = match words.as_slice() {
+                [] | [_] => true,
+                [.., last] => matches!(*last, "and" | "or" | "not"),
+            };
+            if !can_start_query || !prelude.ends_with(char::is_whitespace) {
+                return Vec::new();
+            }
+
+            let style = CompletionItem {
+                label: "style".to_string(),
+                kind: Some(CompletionItemKind::FUNCTION),
+                detail: Some("style() (container style query)".to_string()),
+                ..Default::default()
+            };
+            let containers = if words.is_empty() {
+                containers()
+            } else {
+                Vec::new()
+            };
+            containers
+                .into_iter()
+                .map(|container| CompletionItem {
+                    label: container,
+                    kind: Some(CompletionItemKind::MODULE),
+                    detail: Some("Container".to_string()),
+                    ..Default::default()
+                })
+                .chain([style])
+                .collect()
+        }
+    }
+}
+
+/// The descriptors of the at-rule whose block the position is in, e.g. `system` in `@counter-style`, and the keywords
+/// of their values, documented by the CSS data.
+fn descriptor_items(context: &CompletionContext) -> Vec<CompletionItem> {
+    // The at-rules masked before parsing, e.g. `@view-transition`, are only found in the source
+    let masked_at_rule = || {
+        let before = &context.text()[..usize::from(context.offset())];
+        let prelude = enclosing_prelude(before)?;
+        let name = prelude.split_whitespace().next()?;
+        has_unparsed_at_rules(name).then(|| name.to_ascii_lowercase())
+    };
+    let Some(at_directive) = context
+        .token()
+        .and_then(|token| token.parent())
+        .and_then(|node| descriptor_at_rule(&node))
+        .or_else(masked_at_rule)
+        .and_then(|at_rule| context.css_data().at_directive(&at_rule))
+    else {
+        return Vec::new();
+    };
+
+    match context.location() {
+        CompletionLocation::PropertyName => at_directive
+            .descriptors
+            .iter()
+            .map(|descriptor| CompletionItem {
+                label: descriptor.name.clone(),
+                kind: Some(CompletionItemKind::PROPERTY),
+                documentation: Some(Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: descriptor.documentation(),
+                })),
+                tags: (descriptor.status == EntryStatus::Obsolete)
+                    .then(|| vec![CompletionItemTag::DEPRECATED]),
+                ..Default::default()
+            })
+            .collect(),
+        CompletionLocation::PropertyValue { property } => at_directive
+            .descriptors
+            .iter()
+            .filter(|descriptor| descriptor.name.eq_ignore_ascii_case(property))
+            .flat_map(|descriptor| &descriptor.values)
+            .map(|value| CompletionItem {
+                label: value.name.clone(),
+                kind: Some(CompletionItemKind::VALUE),
+                documentation: Some(Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: value.documentation(),
+                })),
+                ..Default::default()
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The keywords every property accepts, offered along with the values of the CSS data.
+const CSS_WIDE_KEYWORDS: &[(&str, &str)] = &[
+    ("inherit", "The computed value of the property on the parent element."),
+    ("initial", "The initial value of the property, as defined by its specification."),
+    (
+        "unset",
+        "`inherit` for inherited properties, `initial` for the others.",
+    ),
+    (
+        "revert",
+        "The value the property would have without the styles of the current origin, e.g. the styles of the browser \
+         for the styles of the page.",
+    ),
+    (
+        "revert-layer",
+        "The value the property would have without the styles of the current cascade layer.",
+    ),
+];
+
+/// The properties of the CSS data in declaration blocks, and the keywords of their values after their `:`, documented
+/// by their data. New declarations are inserted as a snippet with their `:` and `;`, e.g. `color: |;`. The blocks of
+/// at-rules with descriptors and of the page context are left to `descriptor_items` and `page_items`.
+fn property_items(context: &CompletionContext) -> Vec<CompletionItem> {
+    let node = context.token().and_then(|token| token.parent());
+    let before = &context.text()[..usize::from(context.offset())];
+    // Variables and at-rules are being typed after a `$` or a `@`, and keyframes between the keyframes of `@keyframes`
+    let after_sigil = before[..before.len() - context.prefix().len()].ends_with(['$', '@']);
+    let in_at_rule = keyframes_block(context).is_some()
+        || node.as_ref().is_some_and(|node| {
+            page_context(node).is_some()
+                || descriptor_at_rule(node)
+                    .and_then(|at_rule| context.css_data().at_directive(&at_rule))
+                    .is_some_and(|at_directive| !at_directive.descriptors.is_empty())
+        })
+        || enclosing_prelude(before)
+            .and_then(|prelude| prelude.split_whitespace().next())
+            .is_some_and(has_unparsed_at_rules);
+    if after_sigil || in_at_rule {
+        return Vec::new();
+    }
+
+    match context.location() {
+        CompletionLocation::PropertyName => {
+            // The name of an existing declaration is replaced on its own, without inserting another `:`
+            let after = context.text()[usize::from(context.offset())..]
+                .trim_start_matches(|char: char| char.is_alphanumeric() || char == '-')
+                .trim_start();
+            let is_new = !after.starts_with(':');
+
+            context
+                .css_data()
+                .properties()
+                .map(|property| CompletionItem {
+                    label: property.name.clone(),
+                    kind: Some(CompletionItemKind::PROPERTY),
+                    documentation: Some(Documentation::MarkupContent(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value: property.documentation(),
+                    })),
+                    insert_text: is_new.then(|| format!("{}: $0;", property.name)),
+                    insert_text_format: is_new.then_some(InsertTextFormat::SNIPPET),
+                    tags: (property.status == EntryStatus::Obsolete)
+                        .then(|| vec![CompletionItemTag::DEPRECATED]),
+                    ..Default::default()
+                })
+                .collect()
+        }
+        CompletionLocation::PropertyValue { property } if !property.starts_with("--") => context
+            .css_data()
+            .property(property)
+            .map_or(&[][..], |property| &property.values)
+            .iter()
+            .map(|value| CompletionItem {
+                label: value.name.clone(),
+                kind: Some(CompletionItemKind::VALUE),
+                documentation: Some(Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: value.documentation(),
+                })),
+                tags: (value.status == EntryStatus::Obsolete)
+                    .then(|| vec![CompletionItemTag::DEPRECATED]),
+                ..Default::default()
+            })
+            .chain(
+                CSS_WIDE_KEYWORDS
+                    .iter()
+                    .map(|(keyword, description)| CompletionItem {
+                        label: keyword.to_string(),
+                        kind: Some(CompletionItemKind::KEYWORD),
+                        detail: Some("CSS-wide keyword".to_string()),
+                        documentation: Some(Documentation::String(description.to_string())),
+                        ..Default::default()
+                    }),
+            )
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The counter styles declared in the workspace by `@counter-style` rules, in the values of `list-style-type` and
+/// `list-style`.
+fn counter_style_items(
+    context: &CompletionContext,
+    counter_styles: impl FnOnce() -> Vec<String>,
+) -> Vec<CompletionItem> {
+    let CompletionLocation::PropertyValue { property } = context.location() else {
+        return Vec::new();
+    };
+    if !matches!(
+        property.to_ascii_lowercase().as_str(),
+        "list-style-type" | "list-style"
+    ) {
+        return Vec::new();
+    }
+
+    counter_styles()
+        .into_iter()
+        .map(|counter_style| CompletionItem {
+            label: counter_style,
+            kind: Some(CompletionItemKind::ENUM_MEMBER),
+            detail: Some("Counter style".to_string()),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Get the block of the `@keyframes` rule the position is directly in, between its keyframes.
+fn keyframes_block(context: &CompletionContext) -> Option<SyntaxNode<CssLanguage>> {
+    // After the closing brace of a keyframe, the position is in the block of the rule, not in the keyframe's
+    let node = context.token().and_then(|token| {
+        if token.kind() == CssSyntaxKind::R_CURLY
+            && token.text_trimmed_range().end() <= context.offset()
+        {
+            token.parent()?.parent()
+        } else {
+            token.parent()
+        }
+    })?;
+    enclosing_block(&node).filter(|block| block.kind() == CssSyntaxKind::CSS_KEYFRAMES_BLOCK)
+}
+
+/// The keyframe selectors of `@keyframes` rules where a keyframe can start, `from`, `to` and the usual percentages,
+/// without the offsets that already have a keyframe in the rule, e.g. `100%` after `to { … }`.
+fn keyframe_selector_items(context: &CompletionContext) -> Vec<CompletionItem> {
+    let Some(block) = keyframes_block(context) else {
+        return Vec::new();
+    };
+    let offsets = keyframe_selectors(&block)
+        .iter()
+        .filter(|selector| {
+            !selector
+                .text_trimmed_range()
+                .contains_inclusive(context.offset())
+        })
+        .filter_map(keyframe_offset)
+        .collect::<Vec<_>>();
+
+    [
+        ("from", 0.0, "The start of the animation, the same as `0%`."),
+        ("to", 100.0, "The end of the animation, the same as `100%`."),
+        ("0%", 0.0, ""),
+        ("25%", 25.0, ""),
+        ("50%", 50.0, ""),
+        ("75%", 75.0, ""),
+        ("100%", 100.0, ""),
+    ]
+    .into_iter()
+    .filter(|(_, offset, _)| !offsets.contains(offset))
+    .map(|(label, _, description)| CompletionItem {
+        label: label.to_string(),
+        kind: Some(match description {
+            "" => CompletionItemKind::UNIT,
+            _ => CompletionItemKind::KEYWORD,
+        }),
+        detail: Some("Keyframe selector".to_string()),
+        documentation: (!description.is_empty())
+            .then(|| Documentation::String(description.to_string())),
+        ..Default::default()
+    })
+    .collect()
+}
+
+/// The items of `@page` rules: the pseudo-classes of page selectors, e.g. `:first`, the margin boxes after a `@` in
+/// their block, e.g. `@top-center`, and the descriptors of pages and the properties of the page context in the blocks of
+/// pages and margin boxes. The source is read as is, as the rules being typed are often bogus.
+fn page_items(context: &CompletionContext) -> Vec<CompletionItem> {
+    let before = &context.text()[..usize::from(context.offset()) - context.prefix().len()];
+    let statement = before
+        .rfind(['{', '}', ';'])
+        .map_or(before, |end| &before[end + 1..])
+        .trim_start();
+    let is_page = |prelude: &str| {
+        prelude.strip_prefix("@page").is_some_and(|rest| {
+            !rest.starts_with(|char: char| char.is_alphanumeric() || char == '-')
+        })
+    };
+    if is_page(statement) && before.ends_with(':') {
+        return PAGE_PSEUDO_CLASSES
+            .iter()
+            .map(|(name, description)| CompletionItem {
+                label: name.to_string(),
+                kind: Some(CompletionItemKind::KEYWORD),
+                detail: Some("Page pseudo-class".to_string()),
+                documentation: Some(Documentation::String(description.to_string())),
+                ..Default::default()
+            })
+            .collect();
+    }
+
+    let Some(prelude) = enclosing_prelude(before) else {
+        return Vec::new();
+    };
+    let in_page = is_page(prelude);
+    let in_margin_box = prelude
+        .strip_prefix('@')
+        .is_some_and(|name| MARGIN_RULES.contains(&name.to_ascii_lowercase().as_str()));
+    if !in_page && !in_margin_box {
+        return Vec::new();
+    }
+    let page_descriptors = context
+        .css_data()
+        .at_directive("@page")
+        .filter(|_| in_page)
+        .map_or(&[][..], |at_directive| &at_directive.descriptors);
+
+    if before.ends_with('@') {
+        if !in_page {
+            return Vec::new();
+        }
+        return MARGIN_RULES
+            .iter()
+            .map(|name| CompletionItem {
+                label: name.to_string(),
+                kind: Some(CompletionItemKind::KEYWORD),
+                detail: Some("Page margin box".to_string()),
+                ..Default::default()
+            })
+            .collect();
+    }
+    if statement.starts_with('@') {
+        return Vec::new();
+    }
+
+    match statement.split_once(':') {
+        Some((name, _)) => page_descriptors
+            .iter()
+            .filter(|descriptor| descriptor.name.eq_ignore_ascii_case(name.trim()))
+            .flat_map(|descriptor| &descriptor.values)
+            .map(|value| CompletionItem {
+                label: value.name.clone(),
+                kind: Some(CompletionItemKind::VALUE),
+                documentation: Some(Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: value.documentation(),
+                })),
+                ..Default::default()
+            })
+            .collect(),
+        None => page_descriptors
+            .iter()
+            .chain(context.css_data().properties().filter(|property| {
+                !property.name.starts_with('-')
+                    && is_page_context_property(&property.name, in_margin_box)
+            }))
+            .map(|property| CompletionItem {
+                label: property.name.clone(),
+                kind: Some(CompletionItemKind::PROPERTY),
+                documentation: Some(Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: property.documentation(),
+                })),
+                tags: (property.status == EntryStatus::Obsolete)
+                    .then(|| vec![CompletionItemTag::DEPRECATED]),
+                ..Default::default()
+            })
+            .collect(),
+    }
+}
+
+/// The declaration an Emmet abbreviation typed in a declaration block stands for, e.g. `margin: 10px;` for `m10`, as a
+/// snippet replacing the abbreviation, when the `completion.emmetAbbreviations` setting is enabled. The source is read
+/// as is, as abbreviations are bogus declarations.
+fn emmet_items(context: &CompletionContext, settings: &CompletionSettings) -> Vec<CompletionItem> {
+    if !settings.emmet_abbreviations {
+        return Vec::new();
+    }
+    let text = &context.text()[..usize::from(context.offset())];
+    let start = text
+        .char_indices()
+        .rev()
+        .find(|(_, char)| !(char.is_ascii_alphanumeric() || matches!(char, '.' | '#' | '%' | '-')))
+        .map_or(0, |(end, char)| end + char.len_utf8());
+    let abbreviation = &text[start..];
+    let before = text[..start].trim_end();
+    if !abbreviation.starts_with(|char: char| char.is_ascii_alphabetic())
+        || !before.ends_with(['{', '}', ';'])
+    {
+        return Vec::new();
+    }
+    // Only in the blocks of style rules, keyframes and at-rules with descriptors, not those of `@media` or `@keyframes`
+    let Some(prelude) = enclosing_prelude(text) else {
+        return Vec::new();
+    };
+    if let Some(name) = prelude.strip_prefix('@') {
+        let name = name
+            .split(|char: char| !(char.is_alphanumeric() || char == '-'))
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if context
+            .css_data()
+            .at_directive(&format!("@{name}"))
+            .is_none_or(|at_directive| at_directive.descriptors.is_empty())
+            && !MARGIN_RULES.contains(&name.as_str())
+        {
+            return Vec::new();
+        }
+    }
+    let Some(expansion) = expand_abbreviation(abbreviation) else {
+        return Vec::new();
+    };
+
+    // Abbreviations are ASCII, their length is the same in every encoding
+    let position = context.position();
+    let range = Range::new(
+        Position::new(
+            position.line,
+            position.character - abbreviation.len() as u32,
+        ),
+        position,
+    );
+    vec![CompletionItem {
+        label: abbreviation.to_string(),
+        kind: Some(CompletionItemKind::SNIPPET),
+        detail: Some(expansion.replace("$1", "")),
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        text_edit: Some(TextEdit::new(range, expansion).into()),
+        ..Default::default()
+    }]
+}
+
+fn design_token_item(token: &DesignToken) -> CompletionItem {
+    let kind = match token.color() {
+        Some(_) => CompletionItemKind::COLOR,
+        None => CompletionItemKind::VARIABLE,
+    };
+
+    CompletionItem {
+        label: token.property.clone(),
+        kind: Some(kind),
+        detail: Some(token.value.clone()),
+        documentation: token
+            .description
+            .as_ref()
+            .map(|description| Documentation::String(description.clone())),
+        ..Default::default()
+    }
+}
+
+/// Merge the items of every source, ranked by their `sort_text`, or their label for those without one. When several
+/// items have the same label, only the first one is kept.
+fn merge_items(items: impl Iterator<Item = CompletionItem>) -> Vec<CompletionItem> {
+    let mut labels = FxHashSet::default();
+    let mut items = items
+        .filter(|item| labels.insert(item.label.clone()))
+        .collect::<Vec<_>>();
+
+    items.sort_by(|a, b| {
+        let a = a.sort_text.as_deref().unwrap_or(&a.label);
+        let b = b.sort_text.as_deref().unwrap_or(&b.label);
+        a.cmp(b)
+    });
+    items
+}
+
+impl LanguageService {
+    /// Get the completion items for a position of the given document: the design tokens of the project inside `var()`,
+    /// the variables, mixins and functions visible from SCSS documents, the CSS Modules values of CSS documents, the
+    /// at-rules of the CSS data, the custom directives of the `css.customDirectives` setting, the cascade layers of the
+    /// workspace after `@layer`, the containers and query features of `@container` preludes, the descriptors of
+    /// at-rules such as `@counter-style` and their values, the properties of the CSS data in declaration blocks and the
+    /// keywords of their values, the counter styles of the workspace in `list-style-type`,
+    /// the keyframe selectors of `@keyframes` rules, the page pseudo-classes, margin boxes and page-context properties
+    /// of `@page` rules, the Emmet abbreviations of declarations when the `completion.emmetAbbreviations` setting is
+    /// enabled, and the items contributed by the CompletionProviders added with `add_completion_provider`.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The original source code as a `TextDocumentItem`.
+    /// * `position` - The position of the cursor.
+    ///
+    /// # Returns
+    ///
+    /// * The items, ranked by their `sort_text`, or `Error::InvalidPosition` if the position isn't in the document.
+    pub fn get_completions(
+        &self,
+        document: TextDocumentItem,
+        position: Position,
+    ) -> Result<Vec<CompletionItem>, Error> {
+        let store_entry = self.store.get_or_update_document(document);
+        self.completions(&store_entry, position)
+    }
+
+    /// Same as `get_completions`, for a document already in the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    /// * `position` - The position of the cursor.
+    pub fn get_completions_for_uri(
+        &self,
+        uri: &Uri,
+        position: Position,
+    ) -> Result<Vec<CompletionItem>, Error> {
+        self.completions(&*self.open_entry(uri)?, position)
+    }
+
+    /// The names of the symbols of a kind declared in the workspace, e.g. the containers named by `container-name`.
+    fn workspace_symbol_names(&self, kind: SymbolKind) -> Vec<String> {
+        self.workspace_index()
+            .documents()
+            .iter()
+            .flat_map(|document| &document.symbols)
+            .filter(|symbol| symbol.kind == kind && symbol.is_declaration)
+            .map(|symbol| symbol.name.clone())
+            .collect()
+    }
+
+    fn completions(
+        &self,
+        store_entry: &StoreEntry,
+        position: Position,
+    ) -> Result<Vec<CompletionItem>, Error> {
+        request!(self, "completion", store_entry);
+        let offset = from_proto::offset(
+            store_entry.line_index(),
+            position,
+            self.encoding_for(store_entry),
+        )?;
+        let context = CompletionContext::new(store_entry, position, offset, self.css_data());
+        let member_items = match store_entry.dialect {
+            CssDialect::Scss => scss_member_items(
+                &context,
+                || self.scss_members(store_entry),
+                || scss_modules(&store_entry.document.text, &store_entry.scss_constructs()),
+            ),
+            CssDialect::Css => icss_value_items(&context, || self.scss_members(store_entry)),
+            CssDialect::Less => Vec::new(),
+        };
+
+        Ok(merge_items(
+            design_token_items(&context, &self.design_tokens())
+                .into_iter()
+                .chain(member_items)
+                .chain(at_rule_items(&context))
+                .chain(custom_directive_items(
+                    &context,
+                    &self.settings().css.custom_directives,
+                ))
+                .chain(layer_items(&context, || {
+                    self.workspace_index()
+                        .documents()
+                        .iter()
+                        .flat_map(|document| &document.symbols)
+                        .filter(|symbol| symbol.kind == SymbolKind::Layer)
+                        .map(|symbol| symbol.name.clone())
+                        .collect()
+                }))
+                .chain(container_query_items(
+                    &context,
+                    || self.workspace_symbol_names(SymbolKind::Container),
+                    || self.workspace_symbol_names(SymbolKind::CustomProperty),
+                ))
+                .chain(descriptor_items(&context))
+                .chain(property_items(&context))
+                .chain(counter_style_items(&context, || {
+                    self.workspace_symbol_names(SymbolKind::CounterStyle)
+                }))
+                .chain(keyframe_selector_items(&context))
+                .chain(page_items(&context))
+                .chain(emmet_items(
+                    &context,
+                    &self.settings_for(&store_entry.document.uri).completion,
+                ))
+                .chain(
+                    self.completion_providers
+                        .iter()
+                        .flat_map(|provider| provider.provide(&context)),
+                ),
+        ))
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod wasm_bindings {
+    use crate::service::wasm_bindings::WasmLanguageService;
+    use crate::{error::Error, wasm_text_document};
+    use lsp_types::Uri;
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Get the completion items for a position of the given source code.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @param position The position of the cursor.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns A list of `CompletionItem` objects, ranked by their `sortText`.
+ */
+export async function get_completions(source: TextDocumentSource, position: import("vscode-languageserver-types").Position, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<import("vscode-languageserver-types").CompletionItem[]>;
+
+export interface LanguageService {
+	/**
+	 * Get the completion items for a position of an open document, see `get_completions`.
+	 */
+	getCompletions(uri: string, position: import("vscode-languageserver-types").Position): import("vscode-languageserver-types").CompletionItem[];
+}
+"#;
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn get_completions(
+        document: JsValue,
+        position: JsValue,
+        encoding: Option<String>,
+    ) -> Result<JsValue, Error> {
+        let parsed_text_document = wasm_text_document::create_text_document(document)?;
+        let position = wasm_text_document::from_value(position, "position")?;
+
+        let completions = wasm_text_document::with_document(
+            parsed_text_document,
+            wasm_text_document::position_encoding(encoding)?,
+            |language_service, uri| language_service.get_completions_for_uri(uri, position),
+        )?;
+
+        wasm_text_document::to_value(&completions)
+    }
+
+    #[wasm_bindgen(js_class = LanguageService)]
+    impl WasmLanguageService {
+        #[wasm_bindgen(js_name = getCompletions, skip_typescript)]
+        pub fn get_completions(&self, uri: JsValue, position: JsValue) -> Result<JsValue, Error> {
+            let uri: Uri = wasm_text_document::from_value(uri, "uri")?;
+            let position = wasm_text_document::from_value(position, "position")?;
+            let completions = self
+                .language_service
+                .get_completions_for_uri(&uri, position)?;
+
+            wasm_text_document::to_value(&completions)
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use lsp_types::{Location, Position, TextDocumentItem, Uri};
+
+use crate::{
+    converters::from_proto, error::Error, instrumentation::request, scss::ScssConstructKind,
+    service::LanguageService, store::StoreEntry,
+};
+
+impl LanguageService {
+    /// Get the declarations of the symbol at a position of the given document, e.g. of an SCSS variable, of the mixin of
+    /// an `@include`, of the placeholder selector of an `@extend`, of a custom property or of a class. SCSS variables,
+    /// mixins, functions and placeholder selectors are looked up in the document and the stylesheets it loads with
+    /// `@use` and `@import` first, and other symbols in the documents of the workspace index.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The original source code as a `TextDocumentItem`.
+    /// * `position` - A position in the name of the symbol.
+    ///
+    /// # Returns
+    ///
+    /// * The locations of the declarations, empty if there is no symbol at the position or it isn't declared, or
+    ///   `Error::InvalidPosition` if the position isn't in the document.
+    pub fn get_definition(
+        &self,
+        document: TextDocumentItem,
+        position: Position,
+    ) -> Result<Vec<Location>, Error> {
+        let store_entry = self.store.get_or_update_document(document);
+        self.definition(&store_entry, position)
+    }
+
+    /// Same as `get_definition`, for a document already in the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    /// * `position` - A position in the name of the symbol.
+    pub fn get_definition_for_uri(
+        &self,
+        uri: &Uri,
+        position: Position,
+    ) -> Result<Vec<Location>, Error> {
+        self.definition(&*self.open_entry(uri)?, position)
+    }
+
+    fn definition(
+        &self,
+        store_entry: &StoreEntry,
+        position: Position,
+    ) -> Result<Vec<Location>, Error> {
+        request!(self, "definition", store_entry);
+        let offset = from_proto::offset(
+            store_entry.line_index(),
+            position,
+            self.encoding_for(store_entry),
+        )?;
+
+        if let Some((name, _)) = store_entry.scss_placeholder_at(offset) {
+            let declarations = self
+                .scss_placeholders(store_entry)
+                .into_iter()
+                .filter(|(placeholder, _)| *placeholder == name)
+                .map(|(_, location)| location)
+                .collect::<Vec<_>>();
+            if !declarations.is_empty() {
+                return Ok(declarations);
+            }
+        }
+
+        let member = match store_entry.scss_variable_at(offset) {
+            Some((namespace, variable)) => {
+                Some((ScssConstructKind::Variable, namespace, variable.name))
+            }
+            None => store_entry
+                .scss_callable_at(offset)
+                .map(|callable| (callable.kind, callable.namespace, callable.name)),
+        };
+        if let Some((kind, namespace, name)) = member {
+            let declarations = self
+                .scss_members(store_entry)
+                .into_iter()
+                .filter(|declaration| {
+                    declaration.kind == kind
+                        && declaration.namespace == namespace
+                        && declaration.name == name
+                })
+                .map(|declaration| declaration.location)
+                .collect::<Vec<_>>();
+            if !declarations.is_empty() {
+                return Ok(declarations);
+            }
+        }
+
+        // Symbols declared anywhere in the workspace, e.g. variables of stylesheets loaded by the ones the document loads
+        self.symbol_occurrences(&store_entry.document.uri, position, |symbol| {
+            symbol.is_declaration
+        })
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod wasm_bindings {
+    use crate::service::wasm_bindings::WasmLanguageService;
+    use crate::{error::Error, wasm_text_document};
+    use lsp_types::Uri;
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Get the declarations of the symbol at a position of the given source code, e.g. of an SCSS variable.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @param position A position in the name of the symbol.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns A list of `Location` objects, empty if there is no symbol at the position.
+ */
+export async function get_definition(source: TextDocumentSource, position: import("vscode-languageserver-types").Position, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<import("vscode-languageserver-types").Location[]>;
+
+export interface LanguageService {
+	/**
+	 * Get the declarations of the symbol at a position of an open document, see `get_definition`.
+	 */
+	getDefinition(uri: string, position: import("vscode-languageserver-types").Position): import("vscode-languageserver-types").Location[];
+}
+"#;
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn get_definition(
+        document: JsValue,
+        position: JsValue,
+        encoding: Option<String>,
+    ) -> Result<JsValue, Error> {
+        let parsed_text_document = wasm_text_document::create_text_document(document)?;
+        let position = wasm_text_document::from_value(position, "position")?;
+
+        let locations = wasm_text_document::with_document(
+            parsed_text_document,
+            wasm_text_document::position_encoding(encoding)?,
+            |language_service, uri| language_service.get_definition_for_uri(uri, position),
+        )?;
+
+        wasm_text_document::to_value(&locations)
+    }
+
+    #[wasm_bindgen(js_class = LanguageService)]
+    impl WasmLanguageService {
+        #[wasm_bindgen(js_name = getDefinition, skip_typescript)]
+        pub fn get_definition(&self, uri: JsValue, position: JsValue) -> Result<JsValue, Error> {
+            let uri: Uri = wasm_text_document::from_value(uri, "uri")?;
+            let position = wasm_text_document::from_value(position, "position")?;
+            let locations = self
+                .language_service
+                .get_definition_for_uri(&uri, position)?;
+
+            wasm_text_document::to_value(&locations)
+        }
+    }
+}
@@ -0,0 +1,2577 @@
+#[cfg(feature = "parallel")]
+use std::iter;
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+use biome_css_syntax::{
+    AnyCssQueryFeature, CssCompoundSelector, CssContainerAtRule, CssContainerSizeFeatureInParens,
+    CssCounterStyleAtRule, CssGenericProperty, CssImportAtRule, CssLanguage, CssPropertyAtRule,
+    CssSyntaxKind,
+};
+use biome_diagnostics::{Diagnostic as _, PrintDescription};
+#[cfg(feature = "parallel")]
+use biome_rowan::SendNode;
+use biome_rowan::{AstNode, AstNodeList, Direction, SyntaxNode, TextRange, TextSize};
+use csscolorparser::Color;
+use lsp_types::{
+    Diagnostic, DiagnosticSeverity, DocumentDiagnosticReport, FullDocumentDiagnosticReport,
+    NumberOrString, PreviousResultId, Range, RelatedFullDocumentDiagnosticReport,
+    RelatedUnchangedDocumentDiagnosticReport, TextDocumentItem, TextEdit,
+    UnchangedDocumentDiagnosticReport, Uri, WorkspaceDiagnosticReport,
+    WorkspaceDocumentDiagnosticReport, WorkspaceEdit, WorkspaceFullDocumentDiagnosticReport,
+    WorkspaceUnchangedDocumentDiagnosticReport,
+};
+use rustc_hash::{FxHashMap, FxHashSet, FxHasher};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "parallel")]
+use crate::visitor::{par_rule_chunks, walk_nodes};
+use crate::{
+    cancellation::{CancellationToken, Cancelled},
+    container_queries::{queried_container, size_feature},
+    contrast::{contrast_ratio, is_large_text, minimum_ratio},
+    converters::to_proto::range,
+    counter_styles::{is_reserved_name, RESERVED_COUNTER_STYLES, SYSTEMS},
+    data::CssDataSet,
+    descriptors::{descriptor_at_rule, enclosing_prelude},
+    design_tokens::DesignTokens,
+    directives::CustomDirective,
+    error::Error,
+    font_faces::{
+        font_sources, parse_unicode_range, resolve_url, UnicodeRangeError, FONT_FORMATS,
+        FONT_TECHNOLOGIES,
+    },
+    instrumentation::request,
+    keyframes::{keyframe_offset, keyframe_selectors},
+    layers::layer_names,
+    pages::{is_page_context_property, page_context},
+    parser::CssDialect,
+    progress::{Batcher, ItemProgress, ProgressReporter},
+    properties::is_standard_property,
+    recovery::invalid_hex_colors,
+    registered_properties::{
+        computationally_dependent_value, is_color, matches_syntax, parse_syntax, PropertySyntax,
+    },
+    scss::{is_ident_char, is_nested_property, ScssConstructKind},
+    selectors::resolved_selectors,
+    service::LanguageService,
+    settings::{ColorContrastSettings, ContrastLevel},
+    store::StoreEntry,
+    visitor::{walk, Visitor},
+    workspace_index::SymbolKind,
+};
+
+/// A check reporting diagnostics on documents, e.g. a project-specific rule like "no raw colors outside tokens.css".
+///
+/// Rules are added to a LanguageService with `add_diagnostic_rule`, and run together on the nodes of the kinds they
+/// ask for, in a single traversal of each document.
+pub trait DiagnosticRule: Send + Sync {
+    /// The name of the rule, used as the code of its diagnostics, e.g. `no-raw-colors`.
+    fn name(&self) -> &str;
+
+    /// The severity of the diagnostics of the rule, unless they have their own.
+    fn default_severity(&self) -> DiagnosticSeverity {
+        DiagnosticSeverity::WARNING
+    }
+
+    /// The kinds of nodes to check, or `None` to check every node.
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]>;
+
+    /// Check a node of one of the kinds returned by `kinds`.
+    fn check(&self, context: &RuleContext) -> Vec<RuleDiagnostic>;
+}
+
+/// The node checked by a DiagnosticRule, and the document it is in.
+pub struct RuleContext<'a> {
+    node: &'a SyntaxNode<CssLanguage>,
+    document: &'a TextDocumentItem,
+}
+
+impl RuleContext<'_> {
+    pub fn node(&self) -> &SyntaxNode<CssLanguage> {
+        self.node
+    }
+
+    pub fn uri(&self) -> &Uri {
+        &self.document.uri
+    }
+
+    /// The text of the whole document.
+    pub fn text(&self) -> &str {
+        &self.document.text
+    }
+}
+
+/// A problem found by a DiagnosticRule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuleDiagnostic {
+    /// The range of the problem in the document, e.g. the `text_trimmed_range` of the checked node.
+    pub range: TextRange,
+    pub message: String,
+    /// The severity of the diagnostic, the default severity of its rule if `None`.
+    pub severity: Option<DiagnosticSeverity>,
+    /// The text replacing the range of the diagnostic to fix the problem, applied by `get_fix_all_edit` and by the
+    /// quick fixes of `get_code_actions`.
+    pub fix: Option<String>,
+    /// The range replaced by `fix` when it isn't the range of the diagnostic, e.g. the whole line of a statement to
+    /// remove.
+    pub fix_range: Option<TextRange>,
+}
+
+impl RuleDiagnostic {
+    pub fn new(range: TextRange, message: impl Into<String>) -> Self {
+        Self {
+            range,
+            message: message.into(),
+            severity: None,
+            fix: None,
+            fix_range: None,
+        }
+    }
+
+    pub fn with_severity(mut self, severity: DiagnosticSeverity) -> Self {
+        self.severity = Some(severity);
+        self
+    }
+
+    pub fn with_fix(mut self, replacement: impl Into<String>) -> Self {
+        self.fix = Some(replacement.into());
+        self
+    }
+
+    /// Same as `with_fix`, replacing another range than the range of the diagnostic.
+    pub fn with_fix_range(mut self, range: TextRange, replacement: impl Into<String>) -> Self {
+        self.fix_range = Some(range);
+        self.with_fix(replacement)
+    }
+}
+
+/// Data attached to the diagnostics that can be fixed, so that the fix can be applied without running the rule again.
+#[derive(Debug, Deserialize, Serialize)]
+struct DiagnosticData {
+    fix: String,
+    /// The range replaced by the fix, the range of the diagnostic if `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    range: Option<Range>,
+}
+
+/// Reports the literal values duplicating the value of a design token, e.g. `#0055ff` when `--color-blue` is
+/// `#0055FF`, as they won't follow the token when it changes. Token definitions, in custom properties, are ignored.
+struct DesignTokenLiterals(Arc<DesignTokens>);
+
+impl DiagnosticRule for DesignTokenLiterals {
+    fn name(&self) -> &str {
+        "design-token-literal"
+    }
+
+    fn default_severity(&self) -> DiagnosticSeverity {
+        DiagnosticSeverity::INFORMATION
+    }
+
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[
+            CssSyntaxKind::CSS_COLOR,
+            CssSyntaxKind::CSS_FUNCTION,
+            CssSyntaxKind::CSS_IDENTIFIER,
+            CssSyntaxKind::CSS_REGULAR_DIMENSION,
+            CssSyntaxKind::CSS_PERCENTAGE,
+        ])
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<RuleDiagnostic> {
+        let node = context.node();
+        let in_value = node
+            .ancestors()
+            .find_map(CssGenericProperty::cast)
+            .is_some_and(|property| {
+                let after_colon = property.colon_token().is_ok_and(|colon| {
+                    colon.text_trimmed_range().end() <= node.text_trimmed_range().start()
+                });
+                let custom = property
+                    .name()
+                    .is_ok_and(|name| name.syntax().text_trimmed().to_string().starts_with("--"));
+                after_colon && !custom
+            });
+        if !in_value {
+            return Vec::new();
+        }
+
+        let value = node.text_trimmed().to_string();
+        let Some(token) = self.0.find_by_value(&value) else {
+            return Vec::new();
+        };
+        // Identifiers and functions only duplicate color tokens, e.g. `red` or `rgb(255, 0, 0)`, other tokens whose value
+        // is a keyword or a function are too likely to match by chance
+        if matches!(
+            node.kind(),
+            CssSyntaxKind::CSS_FUNCTION | CssSyntaxKind::CSS_IDENTIFIER
+        ) && token.color().is_none()
+        {
+            return Vec::new();
+        }
+
+        vec![RuleDiagnostic::new(
+            node.text_trimmed_range(),
+            format!(
+                "`{value}` is the value of the design token `{}`, use `var({})` instead",
+                token.property, token.property
+            ),
+        )
+        .with_fix(format!("var({})", token.property))]
+    }
+}
+
+/// Reports the nesting selectors `&` in invalid positions of CSS documents: followed by a type selector, e.g. `&div`, as
+/// type selectors must start compound selectors, or outside of nested rules and `@scope`, where `&` matches `:scope`
+/// which is rarely intended. SCSS and Less allow both, e.g. `&__element`, so only CSS is checked.
+struct NestingSelectorPositions;
+
+impl DiagnosticRule for NestingSelectorPositions {
+    fn name(&self) -> &str {
+        "nesting-selector-position"
+    }
+
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[CssSyntaxKind::CSS_COMPOUND_SELECTOR])
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<RuleDiagnostic> {
+        let Some(compound) = CssCompoundSelector::cast_ref(context.node()) else {
+            return Vec::new();
+        };
+        let Some(nesting) = compound.nesting_selector_token() else {
+            return Vec::new();
+        };
+        // At-rules with a block listed as custom directives are masked as `&`, e.g. `@screen md {`
+        if &context.text()[nesting.text_trimmed_range()] != "&" {
+            return Vec::new();
+        }
+
+        if let Some(simple) = compound.simple_selector() {
+            let range = TextRange::new(
+                nesting.text_trimmed_range().start(),
+                simple.syntax().text_trimmed_range().end(),
+            );
+            let simple = simple.syntax().text_trimmed().to_string();
+            // `{simple}&` is valid too, but `&:is({simple})` can also be parsed by tools predating CSS Nesting
+            return vec![RuleDiagnostic::new(
+                range,
+                format!(
+                    "`&{simple}` is invalid, a type selector must start the compound selector: write `{simple}&`"
+                ),
+            )
+            .with_severity(DiagnosticSeverity::ERROR)
+            .with_fix(format!("&:is({simple})"))];
+        }
+
+        let rules = compound
+            .syntax()
+            .ancestors()
+            .filter(|node| {
+                matches!(
+                    node.kind(),
+                    CssSyntaxKind::CSS_QUALIFIED_RULE
+                        | CssSyntaxKind::CSS_NESTED_QUALIFIED_RULE
+                        | CssSyntaxKind::CSS_SCOPE_AT_RULE
+                )
+            })
+            .count();
+        if rules > 1 {
+            return Vec::new();
+        }
+        vec![RuleDiagnostic::new(
+            nesting.text_trimmed_range(),
+            "`&` isn't in a nested rule or in `@scope`, so it matches `:scope`, the root element",
+        )]
+    }
+}
+
+/// Reports the pseudo-elements in the selectors of the scoping root and limit of `@scope`, e.g. `@scope (.card::before)`,
+/// as pseudo-elements can't be either. The legacy single-colon pseudo-elements, e.g. `:before`, are reported too.
+struct ScopeBoundaries;
+
+impl DiagnosticRule for ScopeBoundaries {
+    fn name(&self) -> &str {
+        "scope-boundary"
+    }
+
+    fn default_severity(&self) -> DiagnosticSeverity {
+        DiagnosticSeverity::ERROR
+    }
+
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[CssSyntaxKind::CSS_SCOPE_EDGE])
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<RuleDiagnostic> {
+        context
+            .node()
+            .descendants()
+            .filter(|node| match node.kind() {
+                CssSyntaxKind::CSS_PSEUDO_ELEMENT_SELECTOR => true,
+                CssSyntaxKind::CSS_PSEUDO_CLASS_SELECTOR => matches!(
+                    node.text_trimmed()
+                        .to_string()
+                        .to_ascii_lowercase()
+                        .as_str(),
+                    ":before" | ":after" | ":first-line" | ":first-letter"
+                ),
+                _ => false,
+            })
+            .map(|pseudo_element| {
+                let limit = context
+                    .node()
+                    .prev_sibling_or_token()
+                    .is_some_and(|previous| previous.kind() == CssSyntaxKind::TO_KW);
+                RuleDiagnostic::new(
+                    pseudo_element.text_trimmed_range(),
+                    format!(
+                        "The pseudo-element `{}` can't be the {} of a scope",
+                        pseudo_element.text_trimmed(),
+                        if limit { "limit" } else { "root" }
+                    ),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Reports the unknown features of the size queries of `@container`, e.g. `(min-widht: 400px)`, and the features
+/// prefixed with `min-` or `max-` without a value, e.g. `(min-width)` or `(min-width > 400px)`, as prefixed features can
+/// only be compared with a colon.
+struct ContainerQueryFeatures;
+
+impl DiagnosticRule for ContainerQueryFeatures {
+    fn name(&self) -> &str {
+        "container-query-feature"
+    }
+
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[CssSyntaxKind::CSS_CONTAINER_SIZE_FEATURE_IN_PARENS])
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<RuleDiagnostic> {
+        // A query starting with `style(` is parsed as the name `style` followed by a size feature
+        if context
+            .node()
+            .parent()
+            .and_then(CssContainerAtRule::cast)
+            .is_some_and(|rule| rule.name().is_some() && queried_container(&rule).is_none())
+        {
+            return Vec::new();
+        }
+        let Some(feature) = CssContainerSizeFeatureInParens::cast_ref(context.node())
+            .and_then(|parens| parens.feature().ok())
+        else {
+            return Vec::new();
+        };
+        let (name, is_plain) = match &feature {
+            AnyCssQueryFeature::CssQueryFeaturePlain(feature) => (feature.name(), true),
+            AnyCssQueryFeature::CssQueryFeatureBoolean(feature) => (feature.name(), false),
+            AnyCssQueryFeature::CssQueryFeatureRange(feature) => (feature.left(), false),
+            AnyCssQueryFeature::CssQueryFeatureReverseRange(feature) => (feature.right(), false),
+            AnyCssQueryFeature::CssQueryFeatureRangeInterval(feature) => (feature.name(), false),
+        };
+        let Ok(name) = name else {
+            return Vec::new();
+        };
+        let text = name.syntax().text_trimmed().to_string();
+        // Custom properties can only be queried by style queries, which are reported by the parser
+        if text.starts_with("--") {
+            return Vec::new();
+        }
+
+        match size_feature(&text) {
+            None => vec![RuleDiagnostic::new(
+                name.syntax().text_trimmed_range(),
+                format!("Unknown container size feature `{text}`"),
+            )],
+            Some((_, true)) if !is_plain => vec![RuleDiagnostic::new(
+                name.syntax().text_trimmed_range(),
+                format!("The prefixed feature `{text}` must be followed by a colon and a value"),
+            )
+            .with_severity(DiagnosticSeverity::ERROR)],
+            Some(_) => Vec::new(),
+        }
+    }
+}
+
+/// Reports the keyframe selectors of `@keyframes` rules that are ignored, as they aren't `from`, `to` or a percentage
+/// from `0%` to `100%`, e.g. `120%`, and the selectors repeating an earlier one of the same rule, e.g. `to` after
+/// `100%`, whose declarations are merged with the earlier keyframe's.
+struct KeyframeSelectors;
+
+impl DiagnosticRule for KeyframeSelectors {
+    fn name(&self) -> &str {
+        "keyframe-selector"
+    }
+
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[CssSyntaxKind::CSS_KEYFRAMES_BLOCK])
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<RuleDiagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut offsets: Vec<(f64, String)> = Vec::new();
+        for selector in keyframe_selectors(context.node()) {
+            let text = selector.text_trimmed().to_string();
+            let range = selector.text_trimmed_range();
+            match keyframe_offset(&selector) {
+                None => diagnostics.push(
+                    RuleDiagnostic::new(
+                        range,
+                        format!("`{text}` isn't a keyframe selector, use `from`, `to` or a percentage"),
+                    )
+                    .with_severity(DiagnosticSeverity::ERROR),
+                ),
+                Some(offset) if !(0.0..=100.0).contains(&offset) => diagnostics.push(
+                    RuleDiagnostic::new(
+                        range,
+                        format!("`{text}` is outside of `0%` to `100%`, the keyframe is ignored"),
+                    )
+                    .with_severity(DiagnosticSeverity::ERROR),
+                ),
+                Some(offset) => match offsets.iter().find(|(earlier, _)| *earlier == offset) {
+                    Some((_, earlier)) => diagnostics.push(RuleDiagnostic::new(
+                        range,
+                        format!(
+                            "`{text}` repeats the keyframe selector `{earlier}`, their declarations are merged"
+                        ),
+                    )),
+                    None => offsets.push((offset, text)),
+                },
+            }
+        }
+        diagnostics
+    }
+}
+
+/// Get the descriptors of an at-rule, e.g. `syntax` in `@property --angle { syntax: "<angle>"; }`, mapped by their
+/// lowercase name to their declaration and the nodes of their value. The last declaration of a descriptor wins, as for
+/// properties.
+fn descriptors(
+    rule: &SyntaxNode<CssLanguage>,
+) -> FxHashMap<String, (CssGenericProperty, Vec<SyntaxNode<CssLanguage>>)> {
+    let mut descriptors = FxHashMap::default();
+    for property in rule.descendants().filter_map(CssGenericProperty::cast) {
+        let Ok(name) = property.name() else {
+            continue;
+        };
+        let values = property
+            .value()
+            .syntax_list()
+            .node()
+            .children()
+            .collect::<Vec<_>>();
+        descriptors.insert(
+            name.syntax()
+                .text_trimmed()
+                .to_string()
+                .to_ascii_lowercase(),
+            (property, values),
+        );
+    }
+    descriptors
+}
+
+/// Get the value of an integer, e.g. `3` in `pad: 3 "0"`.
+fn integer(node: &SyntaxNode<CssLanguage>) -> Option<i64> {
+    (node.kind() == CssSyntaxKind::CSS_NUMBER)
+        .then(|| node.text_trimmed().to_string().parse().ok())
+        .flatten()
+}
+
+/// Whether a value is a symbol of a counter style: a string, an identifier or an image.
+fn is_symbol(node: &SyntaxNode<CssLanguage>) -> bool {
+    matches!(
+        node.kind(),
+        CssSyntaxKind::CSS_STRING
+            | CssSyntaxKind::CSS_IDENTIFIER
+            | CssSyntaxKind::CSS_URL_FUNCTION
+            | CssSyntaxKind::CSS_FUNCTION
+    )
+}
+
+/// Split the nodes of a value at its commas, e.g. `1 infinite, 2 5` into `1 infinite` and `2 5`.
+fn comma_separated(values: &[SyntaxNode<CssLanguage>]) -> Vec<&[SyntaxNode<CssLanguage>]> {
+    values
+        .split(|value| {
+            value.kind() == CssSyntaxKind::CSS_GENERIC_DELIMITER && value.text_trimmed() == ","
+        })
+        .collect()
+}
+
+/// Reports the `@property` rules that are ignored because of their descriptors: a missing `syntax`, `inherits` or
+/// `initial-value`, a `syntax` string that isn't a valid syntax, an `inherits` that isn't `true` or `false`, or an
+/// `initial-value` that doesn't match the syntax or isn't computationally independent.
+struct PropertyRegistrations;
+
+impl DiagnosticRule for PropertyRegistrations {
+    fn name(&self) -> &str {
+        "property-registration"
+    }
+
+    fn default_severity(&self) -> DiagnosticSeverity {
+        DiagnosticSeverity::ERROR
+    }
+
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[CssSyntaxKind::CSS_PROPERTY_AT_RULE])
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<RuleDiagnostic> {
+        let Some(rule) = CssPropertyAtRule::cast_ref(context.node()) else {
+            return Vec::new();
+        };
+        let name_range = rule
+            .name()
+            .map(|name| name.syntax().text_trimmed_range())
+            .unwrap_or_else(|_| context.node().text_trimmed_range());
+
+        let descriptors = descriptors(context.node());
+
+        let mut diagnostics = Vec::new();
+        let missing = |descriptor: &str, reason: &str| {
+            RuleDiagnostic::new(
+                name_range,
+                format!("The `{descriptor}` descriptor is required{reason}, the rule is ignored without it"),
+            )
+        };
+
+        let syntax = match descriptors.get("syntax") {
+            None => {
+                diagnostics.push(missing("syntax", ""));
+                None
+            }
+            Some((property, values)) => match values.as_slice() {
+                [string] if string.kind() == CssSyntaxKind::CSS_STRING => {
+                    let text = string.text_trimmed().to_string();
+                    match parse_syntax(&text[1..text.len().max(2) - 1]) {
+                        Ok(syntax) => Some((syntax, text)),
+                        Err(message) => {
+                            diagnostics
+                                .push(RuleDiagnostic::new(string.text_trimmed_range(), message));
+                            None
+                        }
+                    }
+                }
+                _ => {
+                    diagnostics.push(RuleDiagnostic::new(
+                        property.syntax().text_trimmed_range(),
+                        "The `syntax` descriptor must be a string, e.g. `\"<length>\"`",
+                    ));
+                    None
+                }
+            },
+        };
+
+        match descriptors.get("inherits") {
+            None => diagnostics.push(missing("inherits", "")),
+            Some((property, values)) => {
+                let is_boolean = matches!(values.as_slice(), [value] if value.kind() == CssSyntaxKind::CSS_IDENTIFIER
+                    && matches!(value.text_trimmed().to_string().to_ascii_lowercase().as_str(), "true" | "false"));
+                if !is_boolean {
+                    diagnostics.push(RuleDiagnostic::new(
+                        property.syntax().text_trimmed_range(),
+                        "The `inherits` descriptor must be `true` or `false`",
+                    ));
+                }
+            }
+        }
+
+        match (descriptors.get("initial-value"), syntax) {
+            (None, Some((PropertySyntax::Universal, _))) => {}
+            (None, _) => {
+                diagnostics.push(missing("initial-value", " unless the syntax is `\"*\"`"))
+            }
+            (Some((property, values)), Some((syntax, text))) => {
+                if let Some(value) = computationally_dependent_value(values) {
+                    diagnostics.push(RuleDiagnostic::new(
+                        value.text_trimmed_range(),
+                        format!(
+                            "The initial value must be computationally independent, `{}` isn't",
+                            value.text_trimmed()
+                        ),
+                    ));
+                } else if !matches_syntax(&syntax, values) {
+                    diagnostics.push(RuleDiagnostic::new(
+                        property.value().syntax_list().node().text_trimmed_range(),
+                        format!(
+                            "The initial value `{}` doesn't match the syntax {text}",
+                            property.value().syntax_list().node().text_trimmed()
+                        ),
+                    ));
+                }
+            }
+            (Some(_), None) => {}
+        }
+
+        diagnostics
+    }
+}
+
+/// Reports the `@counter-style` rules that are ignored, because of their name, e.g. `decimal` which can't be
+/// redefined, or because their `system` lacks the symbols it needs, and the descriptors of the rules with an invalid
+/// value, e.g. `range: 5 2` whose lower bound is greater than its upper bound.
+struct CounterStyleDescriptors;
+
+impl DiagnosticRule for CounterStyleDescriptors {
+    fn name(&self) -> &str {
+        "counter-style-descriptor"
+    }
+
+    fn default_severity(&self) -> DiagnosticSeverity {
+        DiagnosticSeverity::ERROR
+    }
+
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[CssSyntaxKind::CSS_COUNTER_STYLE_AT_RULE])
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<RuleDiagnostic> {
+        let Some(name) =
+            CssCounterStyleAtRule::cast_ref(context.node()).and_then(|rule| rule.name().ok())
+        else {
+            return Vec::new();
+        };
+        let name_range = name.syntax().text_trimmed_range();
+        let name = name.syntax().text_trimmed().to_string();
+        let descriptors = descriptors(context.node());
+
+        let mut diagnostics = Vec::new();
+        if is_reserved_name(&name) {
+            diagnostics.push(RuleDiagnostic::new(
+                name_range,
+                format!("`{name}` can't name a counter style, the rule is ignored"),
+            ));
+        } else if RESERVED_COUNTER_STYLES.contains(&name.to_ascii_lowercase().as_str()) {
+            diagnostics.push(RuleDiagnostic::new(
+                name_range,
+                format!(
+                    "The predefined counter style `{name}` can't be redefined, the rule is ignored"
+                ),
+            ));
+        }
+
+        let system = match descriptors.get("system") {
+            None => Some("symbolic".to_string()),
+            Some((property, values)) => {
+                let system = values
+                    .first()
+                    .filter(|value| value.kind() == CssSyntaxKind::CSS_IDENTIFIER)
+                    .map(|value| value.text_trimmed().to_string().to_ascii_lowercase())
+                    .filter(|system| SYSTEMS.contains(&system.as_str()));
+                let is_valid = match (system.as_deref(), &values[values.len().min(1)..]) {
+                    (Some("fixed"), [first]) => integer(first).is_some(),
+                    (Some("extends"), [extended]) => {
+                        extended.kind() == CssSyntaxKind::CSS_IDENTIFIER
+                            && !is_reserved_name(&extended.text_trimmed().to_string())
+                    }
+                    (Some("extends"), _) => false,
+                    (Some(_), []) => true,
+                    _ => false,
+                };
+                if !is_valid {
+                    diagnostics.push(RuleDiagnostic::new(
+                        property.syntax().text_trimmed_range(),
+                        "`system` must be `cyclic`, `numeric`, `alphabetic`, `symbolic`, `additive`, `fixed` with an \
+                         optional first value, or `extends` with a counter style",
+                    ));
+                    None
+                } else {
+                    system
+                }
+            }
+        };
+
+        let symbols = descriptors
+            .get("symbols")
+            .map_or(0, |(_, values)| values.len());
+        let requirement = match system.as_deref() {
+            Some("extends") => {
+                for descriptor in ["symbols", "additive-symbols"] {
+                    if let Some((property, _)) = descriptors.get(descriptor) {
+                        diagnostics.push(RuleDiagnostic::new(
+                            property.syntax().text_trimmed_range(),
+                            format!("`{descriptor}` can't be used with `system: extends`, the rule is ignored"),
+                        ));
+                    }
+                }
+                None
+            }
+            Some("additive") => (!descriptors.contains_key("additive-symbols"))
+                .then_some("The `additive` system requires `additive-symbols`".to_string()),
+            Some(system @ ("alphabetic" | "numeric")) => (symbols < 2)
+                .then(|| format!("The `{system}` system requires at least two `symbols`")),
+            Some(system) => {
+                (symbols == 0).then(|| format!("The `{system}` system requires `symbols`"))
+            }
+            None => None,
+        };
+        if let Some(requirement) = requirement {
+            diagnostics.push(RuleDiagnostic::new(
+                name_range,
+                format!("{requirement}, the rule is ignored"),
+            ));
+        }
+
+        if let Some((property, values)) = descriptors.get("additive-symbols") {
+            let weights = comma_separated(values)
+                .into_iter()
+                .map(|symbol| match symbol {
+                    [weight, symbol] | [symbol, weight] if is_symbol(symbol) => {
+                        integer(weight).filter(|weight| *weight >= 0)
+                    }
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>();
+            match weights {
+                None => diagnostics.push(RuleDiagnostic::new(
+                    property.syntax().text_trimmed_range(),
+                    "Each of the `additive-symbols` must be a non-negative weight and a symbol, e.g. `10 X`",
+                )),
+                Some(weights) if weights.windows(2).any(|pair| pair[0] <= pair[1]) => {
+                    diagnostics.push(RuleDiagnostic::new(
+                        property.syntax().text_trimmed_range(),
+                        "The `additive-symbols` must be sorted by decreasing weight",
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+
+        if let Some((property, values)) = descriptors.get("range") {
+            let is_auto = matches!(values.as_slice(), [auto]
+                if auto.text_trimmed().to_string().eq_ignore_ascii_case("auto"));
+            if !is_auto {
+                let bound = |value: &SyntaxNode<CssLanguage>, infinite: i64| {
+                    integer(value).or_else(|| {
+                        value
+                            .text_trimmed()
+                            .to_string()
+                            .eq_ignore_ascii_case("infinite")
+                            .then_some(infinite)
+                    })
+                };
+                for range in comma_separated(values) {
+                    let bounds = match range {
+                        [lower, upper] => bound(lower, i64::MIN).zip(bound(upper, i64::MAX)),
+                        _ => None,
+                    };
+                    match (bounds, range) {
+                        (Some((lower, upper)), [first, .., last]) if lower > upper => diagnostics
+                            .push(RuleDiagnostic::new(
+                                TextRange::new(
+                                    first.text_trimmed_range().start(),
+                                    last.text_trimmed_range().end(),
+                                ),
+                                "The lower bound of the range is greater than its upper bound",
+                            )),
+                        (Some(_), _) => {}
+                        (None, _) => {
+                            diagnostics.push(RuleDiagnostic::new(
+                                property.syntax().text_trimmed_range(),
+                                "`range` must be `auto` or ranges of two integers or `infinite`, separated by commas",
+                            ));
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some((property, values)) = descriptors.get("pad") {
+            let is_valid = matches!(values.as_slice(), [length, symbol] | [symbol, length]
+                if is_symbol(symbol) && integer(length).is_some_and(|length| length >= 0));
+            if !is_valid {
+                diagnostics.push(RuleDiagnostic::new(
+                    property.syntax().text_trimmed_range(),
+                    "`pad` must be a non-negative length and a symbol, e.g. `3 \"0\"`",
+                ));
+            }
+        }
+
+        diagnostics
+    }
+}
+
+/// Reports the `@font-face` rules that are ignored because they lack a `font-family` or a `src`, and the descriptors of
+/// `@font-face` and `@font-palette-values` rules with an invalid value, e.g. a source of `src` that isn't a `url()` or a
+/// `local()`, an unknown `format()`, a malformed or reversed range of `unicode-range`, or an `override-colors` entry
+/// without an index.
+struct FontDescriptors;
+
+impl DiagnosticRule for FontDescriptors {
+    fn name(&self) -> &str {
+        "font-descriptor"
+    }
+
+    fn default_severity(&self) -> DiagnosticSeverity {
+        DiagnosticSeverity::ERROR
+    }
+
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[
+            CssSyntaxKind::CSS_FONT_FACE_AT_RULE,
+            CssSyntaxKind::CSS_FONT_PALETTE_VALUES_AT_RULE,
+        ])
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<RuleDiagnostic> {
+        let descriptors = descriptors(context.node());
+        let mut diagnostics = Vec::new();
+
+        if context.node().kind() == CssSyntaxKind::CSS_FONT_FACE_AT_RULE {
+            let keyword_range = context
+                .node()
+                .first_token()
+                .map_or(context.node().text_trimmed_range(), |keyword| {
+                    keyword.text_trimmed_range()
+                });
+            for descriptor in ["font-family", "src"] {
+                if !descriptors.contains_key(descriptor) {
+                    diagnostics.push(RuleDiagnostic::new(
+                        keyword_range,
+                        format!(
+                            "The `{descriptor}` descriptor is required, the rule is ignored without it"
+                        ),
+                    ));
+                }
+            }
+
+            if let Some((property, _)) = descriptors.get("src") {
+                for source in font_sources(property) {
+                    if !source.is_valid {
+                        diagnostics.push(RuleDiagnostic::new(
+                            source.range,
+                            "A source must be a `url()`, optionally followed by `format()` and `tech()`, or a `local()`",
+                        ));
+                    }
+                    let unknown_formats = source
+                        .formats
+                        .iter()
+                        .filter(|(format, _)| {
+                            !FONT_FORMATS.contains(&format.to_ascii_lowercase().as_str())
+                        })
+                        .map(|(format, range)| (format!("Unknown font format `{format}`"), range));
+                    let unknown_technologies = source
+                        .technologies
+                        .iter()
+                        .filter(|(technology, _)| {
+                            !FONT_TECHNOLOGIES.contains(&technology.to_ascii_lowercase().as_str())
+                        })
+                        .map(|(technology, range)| {
+                            (format!("Unknown font technology `{technology}`"), range)
+                        });
+                    diagnostics.extend(unknown_formats.chain(unknown_technologies).map(
+                        |(message, range)| {
+                            RuleDiagnostic::new(*range, message)
+                                .with_severity(DiagnosticSeverity::WARNING)
+                        },
+                    ));
+                }
+            }
+
+            diagnostics.extend(unicode_range_diagnostics(context));
+
+            if let Some((property, values)) = descriptors.get("font-display") {
+                let is_valid = matches!(values.as_slice(), [value] if matches!(
+                    value.text_trimmed().to_string().to_ascii_lowercase().as_str(),
+                    "auto" | "block" | "swap" | "fallback" | "optional"
+                ));
+                if !is_valid {
+                    diagnostics.push(RuleDiagnostic::new(
+                        property.syntax().text_trimmed_range(),
+                        "`font-display` must be `auto`, `block`, `swap`, `fallback` or `optional`",
+                    ));
+                }
+            }
+        } else {
+            if let Some((property, values)) = descriptors.get("base-palette") {
+                let is_valid = matches!(values.as_slice(), [value] if integer(value).is_some_and(|index| index >= 0)
+                    || matches!(value.text_trimmed().to_string().to_ascii_lowercase().as_str(), "light" | "dark"));
+                if !is_valid {
+                    diagnostics.push(RuleDiagnostic::new(
+                        property.syntax().text_trimmed_range(),
+                        "`base-palette` must be `light`, `dark` or the index of a palette of the font",
+                    ));
+                }
+            }
+
+            if let Some((_, values)) = descriptors.get("override-colors") {
+                for entry in comma_separated(values) {
+                    let is_valid = matches!(entry, [index, color] if integer(index).is_some_and(|index| index >= 0)
+                        && is_color(color)
+                        && !color.text_trimmed().to_string().eq_ignore_ascii_case("currentcolor"));
+                    let (Some(first), Some(last)) = (entry.first(), entry.last()) else {
+                        continue;
+                    };
+                    if !is_valid {
+                        diagnostics.push(RuleDiagnostic::new(
+                            TextRange::new(
+                                first.text_trimmed_range().start(),
+                                last.text_trimmed_range().end(),
+                            ),
+                            "Each color override must be an index of the palette and an absolute color, e.g. `0 red`",
+                        ));
+                    }
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+/// Check the ranges of the `unicode-range` descriptor of a `@font-face` rule, e.g. `U+0025-00FF, U+4??`. The parser
+/// doesn't know the syntax of ranges, and fails on wildcards, so the source of the value is checked instead.
+fn unicode_range_diagnostics(context: &RuleContext) -> Vec<RuleDiagnostic> {
+    // The last declaration wins, as for properties
+    let Some(declaration) = context
+        .node()
+        .descendants()
+        .filter(|node| {
+            matches!(
+                node.kind(),
+                CssSyntaxKind::CSS_GENERIC_PROPERTY | CssSyntaxKind::CSS_BOGUS_PROPERTY
+            ) && node.first_child().is_some_and(|name| {
+                name.text_trimmed()
+                    .to_string()
+                    .eq_ignore_ascii_case("unicode-range")
+            })
+        })
+        .last()
+    else {
+        return Vec::new();
+    };
+    let Some(colon) = declaration
+        .children_with_tokens()
+        .filter_map(|child| child.into_token())
+        .find(|token| token.kind() == CssSyntaxKind::COLON)
+    else {
+        return Vec::new();
+    };
+
+    let value_start = colon.text_trimmed_range().end();
+    let value =
+        &context.text()[TextRange::new(value_start, declaration.text_trimmed_range().end())];
+    let mut diagnostics = Vec::new();
+    let mut offset = value_start;
+    for range in value.split(',') {
+        let trimmed = range.trim();
+        let leading = TextSize::of(&range[..range.len() - range.trim_start().len()]);
+        // An empty range, e.g. after a trailing comma, is reported on the comma before it, or on the declaration
+        let text_range = match trimmed {
+            "" if offset > value_start => {
+                TextRange::at(offset - TextSize::of(','), TextSize::of(','))
+            }
+            "" => declaration.text_trimmed_range(),
+            _ => TextRange::at(offset + leading, TextSize::of(trimmed)),
+        };
+        offset += TextSize::of(range) + TextSize::of(',');
+
+        let message = match parse_unicode_range(trimmed) {
+            Ok(_) => continue,
+            Err(UnicodeRangeError::Malformed) if trimmed.is_empty() => {
+                "Expected a unicode range, e.g. `U+26`, `U+0025-00FF` or `U+4??`".to_string()
+            }
+            Err(UnicodeRangeError::Malformed) => {
+                format!("`{trimmed}` isn't a unicode range, e.g. `U+26`, `U+0025-00FF` or `U+4??`")
+            }
+            Err(UnicodeRangeError::Reversed) => {
+                format!("The range `{trimmed}` is reversed, its start is greater than its end")
+            }
+            Err(UnicodeRangeError::OutOfRange) => {
+                format!("The range `{trimmed}` ends after `U+10FFFF`, the last code point")
+            }
+        };
+        diagnostics.push(RuleDiagnostic::new(text_range, message));
+    }
+    diagnostics
+}
+
+/// Reports the declarations of the at-rules whose descriptors are known from the CSS data, e.g. `@counter-style`,
+/// that aren't one of their descriptors, e.g. `color` which is a property.
+struct UnknownDescriptors<'a>(&'a CssDataSet);
+
+impl DiagnosticRule for UnknownDescriptors<'_> {
+    fn name(&self) -> &str {
+        "unknown-descriptor"
+    }
+
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[CssSyntaxKind::CSS_GENERIC_PROPERTY])
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<RuleDiagnostic> {
+        let Some(at_rule) = descriptor_at_rule(context.node()) else {
+            return Vec::new();
+        };
+        let Some(name) =
+            CssGenericProperty::cast_ref(context.node()).and_then(|property| property.name().ok())
+        else {
+            return Vec::new();
+        };
+        let is_described = self
+            .0
+            .at_directive(&at_rule)
+            .is_some_and(|at_directive| !at_directive.descriptors.is_empty());
+        let text = name.syntax().text_trimmed().to_string();
+        if !is_described || self.0.descriptor(&at_rule, &text).is_some() {
+            return Vec::new();
+        }
+
+        let message = if self.0.property(&text).is_some() {
+            format!("`{text}` is a property, not a descriptor of `{at_rule}`")
+        } else {
+            format!("Unknown descriptor `{text}` in `{at_rule}`")
+        };
+        vec![RuleDiagnostic::new(
+            name.syntax().text_trimmed_range(),
+            message,
+        )]
+    }
+}
+
+/// Reports the declarations of `@page` rules and of their margin boxes, e.g. `@top-center`, that are ignored as they
+/// are neither a descriptor of pages, e.g. `size`, nor a property applying to the page context, e.g. `display`.
+struct PageProperties<'a>(&'a CssDataSet);
+
+impl DiagnosticRule for PageProperties<'_> {
+    fn name(&self) -> &str {
+        "page-property"
+    }
+
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[CssSyntaxKind::CSS_GENERIC_PROPERTY])
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<RuleDiagnostic> {
+        let Some(at_rule) = page_context(context.node()) else {
+            return Vec::new();
+        };
+        let Some(name) =
+            CssGenericProperty::cast_ref(context.node()).and_then(|property| property.name().ok())
+        else {
+            return Vec::new();
+        };
+        let text = name.syntax().text_trimmed().to_string();
+        let in_margin_box = at_rule != "@page";
+        if is_page_context_property(&text, in_margin_box)
+            || (!in_margin_box && self.0.descriptor(&at_rule, &text).is_some())
+        {
+            return Vec::new();
+        }
+
+        vec![RuleDiagnostic::new(
+            name.syntax().text_trimmed_range(),
+            format!("`{text}` doesn't apply to the page context, it is ignored in `{at_rule}`"),
+        )]
+    }
+}
+
+/// The at-rules whose blocks contain the declarations of the elements of their parent rule or of their own rules, e.g.
+/// `@media` nested in a style rule, and the ones of SCSS whose blocks contain declarations, e.g. `@mixin`. The blocks of
+/// the other at-rules contain descriptors, or the declarations of frameworks and of at-rules the parser doesn't know.
+const STYLE_AT_RULES: &[&str] = &[
+    "@at-root",
+    "@container",
+    "@each",
+    "@else",
+    "@for",
+    "@if",
+    "@include",
+    "@layer",
+    "@media",
+    "@mixin",
+    "@scope",
+    "@starting-style",
+    "@supports",
+    "@while",
+];
+
+/// Reports the properties that are neither standard properties of CSS nor documented by the CSS data, e.g. the
+/// misspelled `colr`. Custom properties and vendor-prefixed ones are never reported, nor the descriptors of at-rules,
+/// which `UnknownDescriptors` checks, and the names of the `:export` blocks of CSS Modules.
+struct UnknownProperties<'a> {
+    data: &'a CssDataSet,
+    dialect: CssDialect,
+}
+
+impl DiagnosticRule for UnknownProperties<'_> {
+    fn name(&self) -> &str {
+        "unknown-property"
+    }
+
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[CssSyntaxKind::CSS_GENERIC_PROPERTY])
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<RuleDiagnostic> {
+        let Some(name) =
+            CssGenericProperty::cast_ref(context.node()).and_then(|property| property.name().ok())
+        else {
+            return Vec::new();
+        };
+        // The names masked before parsing are checked as written, e.g. the interpolations of SCSS aren't identifiers
+        let range = name.syntax().text_trimmed_range();
+        let text = &context.text()[range];
+        if text.starts_with('-')
+            || !text.bytes().all(is_ident_char)
+            || is_standard_property(text)
+            || self.data.property(&text.to_ascii_lowercase()).is_some()
+            // The property of CSS Modules composing the classes of other rules
+            || text == "composes"
+            || descriptor_at_rule(context.node()).is_some()
+            || page_context(context.node()).is_some()
+        {
+            return Vec::new();
+        }
+
+        // The blocks of the other at-rules, of the `:export` rules of CSS Modules and of the nested properties of SCSS
+        // have names of their own
+        let is_ignored = enclosing_prelude(&context.text()[..usize::from(range.start())])
+            .is_some_and(|prelude| {
+                let at_rule = prelude
+                    .split(|char: char| char.is_whitespace() || char == '(')
+                    .next()
+                    .unwrap_or_default()
+                    .to_ascii_lowercase();
+                (prelude.starts_with('@') && !STYLE_AT_RULES.contains(&at_rule.as_str()))
+                    || prelude.starts_with(":export")
+                    || prelude.starts_with(":import")
+                    || (self.dialect == CssDialect::Scss && is_nested_property(prelude))
+            });
+        if is_ignored {
+            return Vec::new();
+        }
+
+        vec![RuleDiagnostic::new(
+            range,
+            format!("Unknown property `{text}`"),
+        )]
+    }
+}
+
+/// Reports the dimensions whose unit isn't a unit of CSS, e.g. `10pxx`, which make their declaration invalid. The values
+/// of custom properties, which can be any token, aren't checked, nor the ranges of `unicode-range` that the parser
+/// mistakes for dimensions.
+struct UnknownUnits;
+
+impl DiagnosticRule for UnknownUnits {
+    fn name(&self) -> &str {
+        "unknown-unit"
+    }
+
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[CssSyntaxKind::CSS_UNKNOWN_DIMENSION])
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<RuleDiagnostic> {
+        let node = context.node();
+        let Some(unit) = node.last_token() else {
+            return Vec::new();
+        };
+        let in_custom_property = node.ancestors().any(|ancestor| {
+            ancestor.kind() == CssSyntaxKind::CSS_GENERIC_PROPERTY
+                && ancestor
+                    .first_child()
+                    .is_some_and(|name| name.text_trimmed().to_string().starts_with("--"))
+        });
+        if in_custom_property || in_unicode_range(node) {
+            return Vec::new();
+        }
+
+        vec![RuleDiagnostic::new(
+            node.text_trimmed_range(),
+            format!(
+                "`{}` isn't a unit of CSS, e.g. `px`, `rem` or `%`",
+                unit.text_trimmed()
+            ),
+        )]
+    }
+}
+
+/// Whether a node is in the value of a `unicode-range` declaration, whose ranges the parser doesn't know.
+fn in_unicode_range(node: &SyntaxNode<CssLanguage>) -> bool {
+    node.ancestors().any(|node| {
+        matches!(
+            node.kind(),
+            CssSyntaxKind::CSS_GENERIC_PROPERTY | CssSyntaxKind::CSS_BOGUS_PROPERTY
+        ) && node.first_child().is_some_and(|name| {
+            name.text_trimmed()
+                .to_string()
+                .eq_ignore_ascii_case("unicode-range")
+        })
+    })
+}
+
+/// Reports the `@import` rules loading the same stylesheet as a previous one, with the same layer, `supports()` and
+/// media queries, e.g. a second `@import "base.css";` in a document, as they only load it again. The fix removes them.
+struct RedundantImports;
+
+impl DiagnosticRule for RedundantImports {
+    fn name(&self) -> &str {
+        "redundant-import"
+    }
+
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[CssSyntaxKind::CSS_IMPORT_AT_RULE])
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<RuleDiagnostic> {
+        let Some((statement, (url, target))) = context.node().parent().zip(
+            CssImportAtRule::cast_ref(context.node())
+                .and_then(|import| import_target(context.uri(), &import)),
+        ) else {
+            return Vec::new();
+        };
+        let is_imported = statement.siblings(Direction::Prev).skip(1).any(|sibling| {
+            sibling
+                .first_child()
+                .and_then(CssImportAtRule::cast)
+                .and_then(|import| import_target(context.uri(), &import))
+                .is_some_and(|(_, previous)| previous == target)
+        });
+        if !is_imported {
+            return Vec::new();
+        }
+
+        let range = statement.text_trimmed_range();
+        vec![RuleDiagnostic::new(
+            range,
+            format!("`{url}` is already imported by a previous `@import` with the same conditions"),
+        )
+        .with_fix_range(line_range(context.text(), range), "")]
+    }
+}
+
+/// Reports the style rules whose selectors are the same as those of a previous rule in the same conditions, e.g. a
+/// second `.a:hover` rule, or `.a { &:hover { … } }` after `.a:hover { … }`, as their declarations could be merged.
+/// Selectors are compared once resolved against the rules they are nested in, and in any order. The rules of SCSS
+/// control directives and the selectors with interpolations are skipped, see `has_dynamic_selectors`. Less isn't
+/// checked, as its mixins are declared as style rules.
+struct DuplicateSelectors;
+
+impl DiagnosticRule for DuplicateSelectors {
+    fn name(&self) -> &str {
+        "duplicate-selector"
+    }
+
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[CssSyntaxKind::CSS_ROOT])
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<RuleDiagnostic> {
+        let mut rules = FxHashSet::default();
+        context
+            .node()
+            .descendants()
+            .filter(|node| {
+                matches!(
+                    node.kind(),
+                    CssSyntaxKind::CSS_QUALIFIED_RULE | CssSyntaxKind::CSS_NESTED_QUALIFIED_RULE
+                )
+            })
+            .filter(|rule| !has_dynamic_selectors(context.text(), rule))
+            .filter_map(|rule| {
+                let mut selectors = resolved_selectors(&rule)
+                    .iter()
+                    .map(|selector| selector.split_whitespace().collect::<Vec<_>>().join(" "))
+                    .collect::<Vec<_>>();
+                if selectors.is_empty() {
+                    return None;
+                }
+                let message = format!(
+                    "`{}` is already selected by a previous rule in the same conditions, whose declarations could be merged with these",
+                    selectors.join(", ")
+                );
+                selectors.sort_unstable();
+                selectors.dedup();
+                if rules.insert((rule_conditions(context.text(), &rule), selectors)) {
+                    return None;
+                }
+                Some(RuleDiagnostic::new(
+                    rule.first_child()?.text_trimmed_range(),
+                    message,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// The SCSS control directives, whose rules may apply in some cases only, e.g. the same rule in `@if` and `@else`.
+const CONTROL_DIRECTIVES: [&str; 5] = ["each", "else", "for", "if", "while"];
+
+/// Whether the selectors of a style rule aren't known before compiling the SCSS code. Such rules are masked before
+/// parsing: the rule itself stands for a directive, e.g. the block of `@include`, or it is nested in a control
+/// directive, or its selectors or those of the rules it is nested in have an interpolation, e.g. `.icon-#{$name}`.
+fn has_dynamic_selectors(text: &str, rule: &SyntaxNode<CssLanguage>) -> bool {
+    if masked_prelude(text, rule).is_some() {
+        return true;
+    }
+
+    rule.ancestors()
+        .skip(1)
+        .filter(|ancestor| {
+            matches!(
+                ancestor.kind(),
+                CssSyntaxKind::CSS_QUALIFIED_RULE | CssSyntaxKind::CSS_NESTED_QUALIFIED_RULE
+            )
+        })
+        .filter_map(|ancestor| masked_prelude(text, &ancestor))
+        .any(|source| {
+            let directive = source.strip_prefix('@').map(|directive| {
+                directive
+                    .split(|char: char| !(char.is_alphanumeric() || char == '-'))
+                    .next()
+                    .unwrap_or_default()
+            });
+            source.contains("#{")
+                || directive.is_some_and(|name| CONTROL_DIRECTIVES.contains(&name))
+        })
+}
+
+/// Get the source of the prelude of a style rule if it was masked before parsing, up to its block, e.g. `@if $dark` for
+/// the rule parsed as `& { … }`.
+fn masked_prelude<'a>(text: &'a str, rule: &SyntaxNode<CssLanguage>) -> Option<&'a str> {
+    let prelude = rule.first_child()?;
+    let range = prelude.text_trimmed_range();
+    if prelude.text_trimmed() == text[range] {
+        return None;
+    }
+    let end = rule
+        .last_child()
+        .map_or(range.end(), |block| block.text_trimmed_range().start());
+    Some(text[TextRange::new(range.start(), end)].trim_end())
+}
+
+/// The conditions a style rule applies in: the preludes of the at-rules it is nested in, e.g. `@media print`, with
+/// their whitespace collapsed. The directives masked before parsing are found in the source, e.g. `@screen md` or
+/// `@include dark`.
+fn rule_conditions(text: &str, rule: &SyntaxNode<CssLanguage>) -> String {
+    rule.ancestors()
+        .skip(1)
+        .filter_map(|ancestor| {
+            let condition = match ancestor.kind() {
+                CssSyntaxKind::CSS_AT_RULE => {
+                    let at_rule = ancestor.first_child()?;
+                    let end = at_rule
+                        .last_child()
+                        .map_or(at_rule.text_trimmed_range().end(), |block| {
+                            block.text_trimmed_range().start()
+                        });
+                    &text[TextRange::new(at_rule.text_trimmed_range().start(), end)]
+                }
+                CssSyntaxKind::CSS_QUALIFIED_RULE | CssSyntaxKind::CSS_NESTED_QUALIFIED_RULE => {
+                    masked_prelude(text, &ancestor)?
+                }
+                _ => return None,
+            };
+            Some(condition.split_whitespace().collect::<Vec<_>>().join(" "))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Get the URL of an `@import`, and what it imports: the resolved URL of the stylesheet, along with its layer,
+/// `supports()` and media queries with their whitespace collapsed.
+fn import_target(from: &Uri, import: &CssImportAtRule) -> Option<(String, String)> {
+    let value = import.url().ok()?.syntax().descendants().find(|node| {
+        matches!(
+            node.kind(),
+            CssSyntaxKind::CSS_STRING | CssSyntaxKind::CSS_URL_VALUE_RAW
+        )
+    })?;
+    let text = value.text_trimmed().to_string();
+    let url = match value.kind() {
+        CssSyntaxKind::CSS_STRING => text.get(1..text.len() - 1)?.to_string(),
+        _ => text,
+    };
+
+    let conditions = [
+        import
+            .layer()
+            .map(|layer| layer.syntax().text_trimmed().to_string()),
+        import
+            .supports()
+            .map(|supports| supports.syntax().text_trimmed().to_string()),
+        Some(import.media().syntax().text_trimmed().to_string()),
+    ];
+    let target = conditions
+        .iter()
+        .flatten()
+        .flat_map(|condition| condition.split_whitespace())
+        .fold(
+            resolve_url(from, &url).map_or_else(|| url.clone(), |uri| uri.to_string()),
+            |target, word| format!("{target} {word}"),
+        );
+    Some((url, target))
+}
+
+/// Extend the range of a statement to its whole line, including the line break, when nothing else is written on the
+/// line, so that removing it doesn't leave an empty line.
+fn line_range(text: &str, range: TextRange) -> TextRange {
+    let (start, end) = (usize::from(range.start()), usize::from(range.end()));
+    let line_start = text[..start].rfind('\n').map_or(0, |newline| newline + 1);
+    let line_end = text[end..]
+        .find('\n')
+        .map_or(text.len(), |newline| end + newline + 1);
+    if !text[line_start..start].trim().is_empty() || !text[end..line_end].trim().is_empty() {
+        return range;
+    }
+
+    TextRange::new(
+        TextSize::from(line_start as u32),
+        TextSize::from(line_end as u32),
+    )
+}
+
+/// Reports the `color` of style rules whose contrast with their `background-color`, or the `background` setting a
+/// single color, is below the minimum ratio of WCAG 2 at the level of the `diagnostics.colorContrast` setting. Rules
+/// without a background are checked against the background of the rules they are nested in, or the `defaultBackground`
+/// of the setting, if any. Only colors known statically are checked,
+/// not `var()` or `inherit`, and text is large when the rule sets a `font-size` in `px` or `pt` that is large enough.
+struct ColorContrast {
+    level: ContrastLevel,
+    default_background: Option<(String, Color)>,
+    severity: DiagnosticSeverity,
+}
+
+impl ColorContrast {
+    fn new(settings: &ColorContrastSettings) -> Option<Self> {
+        settings.enabled.then(|| Self {
+            level: settings.level,
+            default_background: settings.default_background.as_ref().and_then(|background| {
+                Some((background.clone(), csscolorparser::parse(background).ok()?))
+            }),
+            severity: settings
+                .severity
+                .map_or(DiagnosticSeverity::WARNING, Into::into),
+        })
+    }
+}
+
+impl DiagnosticRule for ColorContrast {
+    fn name(&self) -> &str {
+        "color-contrast"
+    }
+
+    fn default_severity(&self) -> DiagnosticSeverity {
+        self.severity
+    }
+
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[CssSyntaxKind::CSS_DECLARATION_OR_RULE_BLOCK])
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<RuleDiagnostic> {
+        let declarations = rule_declarations(context.node());
+        let value_of = |name: &str| single_value(&declarations, name);
+
+        let Some((foreground_node, foreground)) =
+            value_of("color").and_then(|value| Some((value, static_color(value)?)))
+        else {
+            return Vec::new();
+        };
+        // Nested rules are drawn on the background of the rules they are nested in, if they don't have their own. A
+        // background that isn't a known color, e.g. an image, hides the default background.
+        let background = context
+            .node()
+            .ancestors()
+            .filter(|node| node.kind() == CssSyntaxKind::CSS_DECLARATION_OR_RULE_BLOCK)
+            .find_map(|block| {
+                let declarations = rule_declarations(&block);
+                let name = ["background-color", "background"]
+                    .into_iter()
+                    .find(|name| declarations.contains_key(*name))?;
+                Some(single_value(&declarations, name).and_then(|value| {
+                    Some((value.text_trimmed().to_string(), static_color(value)?))
+                }))
+            });
+        let (background_text, background) = match background {
+            Some(Some(background)) => background,
+            Some(None) => return Vec::new(),
+            None => match &self.default_background {
+                Some(background) => background.clone(),
+                None => return Vec::new(),
+            },
+        };
+        // The color below a translucent background isn't known
+        if background.a < 1.0 {
+            return Vec::new();
+        }
+
+        let font_size = value_of("font-size").and_then(|value| {
+            if value.kind() != CssSyntaxKind::CSS_REGULAR_DIMENSION {
+                return None;
+            }
+            let size = value.first_token()?.text_trimmed().parse::<f32>().ok()?;
+            match value
+                .last_token()?
+                .text_trimmed()
+                .to_ascii_lowercase()
+                .as_str()
+            {
+                "px" => Some(size),
+                "pt" => Some(size * 4.0 / 3.0),
+                _ => None,
+            }
+        });
+        let bold = value_of("font-weight").is_some_and(|value| {
+            let weight = value.text_trimmed().to_string().to_ascii_lowercase();
+            weight == "bold"
+                || weight == "bolder"
+                || weight.parse::<f32>().is_ok_and(|weight| weight >= 700.0)
+        });
+        let large_text = font_size.is_some_and(|size| is_large_text(size, bold));
+
+        let ratio = contrast_ratio(&foreground, &background);
+        let minimum = minimum_ratio(self.level, large_text);
+        if ratio >= minimum {
+            return Vec::new();
+        }
+
+        let level = match self.level {
+            ContrastLevel::Aa => "AA",
+            ContrastLevel::Aaa => "AAA",
+        };
+        vec![RuleDiagnostic::new(
+            foreground_node.text_trimmed_range(),
+            format!(
+                "The contrast of `{}` on `{background_text}` is {}:1, below the {minimum}:1 required by WCAG {level} for {} text",
+                foreground_node.text_trimmed(),
+                // Rounded down, so that a ratio just below the minimum isn't shown as the minimum
+                (ratio * 100.0).floor() / 100.0,
+                if large_text { "large" } else { "normal" },
+            ),
+        )]
+    }
+}
+
+/// Get the declarations of a style rule from its block, without those of the rules nested in it, mapped by their
+/// lowercase property to the nodes of their value. The last declaration of a property wins.
+fn rule_declarations(
+    block: &SyntaxNode<CssLanguage>,
+) -> FxHashMap<String, Vec<SyntaxNode<CssLanguage>>> {
+    let mut declarations = FxHashMap::default();
+    for property in block
+        .children()
+        .flat_map(|list| list.children())
+        .filter(|child| child.kind() == CssSyntaxKind::CSS_DECLARATION_WITH_SEMICOLON)
+        .filter_map(|declaration| declaration.descendants().find_map(CssGenericProperty::cast))
+    {
+        let Ok(name) = property.name() else {
+            continue;
+        };
+        let values = property
+            .value()
+            .syntax_list()
+            .node()
+            .children()
+            .collect::<Vec<_>>();
+        declarations.insert(
+            name.syntax()
+                .text_trimmed()
+                .to_string()
+                .to_ascii_lowercase(),
+            values,
+        );
+    }
+    declarations
+}
+
+/// Get the value of a declaration made of a single node, e.g. `red` in `color: red`.
+fn single_value<'a>(
+    declarations: &'a FxHashMap<String, Vec<SyntaxNode<CssLanguage>>>,
+    name: &str,
+) -> Option<&'a SyntaxNode<CssLanguage>> {
+    match declarations.get(name).map(Vec::as_slice) {
+        Some([value]) => Some(value),
+        _ => None,
+    }
+}
+
+/// Get the color of a value known statically: a hexadecimal color, a named color or a color function without
+/// `var()`, e.g. `rgb(0 0 0 / 50%)`.
+fn static_color(value: &SyntaxNode<CssLanguage>) -> Option<Color> {
+    if !matches!(
+        value.kind(),
+        CssSyntaxKind::CSS_COLOR | CssSyntaxKind::CSS_IDENTIFIER | CssSyntaxKind::CSS_FUNCTION
+    ) {
+        return None;
+    }
+    csscolorparser::parse(&value.text_trimmed().to_string()).ok()
+}
+
+/// Run the given rules on the syntax tree of a document, returning the diagnostics of each rule in source order. With
+/// the `parallel` feature, large documents are checked on multiple threads, without reporting their progress.
+fn check_rules(
+    rules: &[&dyn DiagnosticRule],
+    entry: &StoreEntry,
+    cancellation: &CancellationToken,
+    progress: &mut dyn ProgressReporter,
+) -> Result<Vec<Vec<RuleDiagnostic>>, Cancelled> {
+    #[cfg(feature = "parallel")]
+    if entry.document.text.len() > crate::PARALLEL_THRESHOLD {
+        return check_rules_parallel(rules, &entry.document, &entry.send_root(), cancellation);
+    }
+
+    let mut visitors = rule_visitors(rules, &entry.document);
+    let mut progress_visitor = ProgressVisitor {
+        progress,
+        text_len: entry.document.text.len().max(1) as u64,
+        last_percentage: 0,
+    };
+    walk(
+        &entry.css_tree().syntax(),
+        &mut visitors
+            .iter_mut()
+            .map(|visitor| visitor as &mut dyn Visitor)
+            .chain([&mut progress_visitor as &mut dyn Visitor])
+            .collect::<Vec<_>>(),
+        cancellation,
+    )?;
+
+    Ok(visitors
+        .into_iter()
+        .map(|visitor| visitor.diagnostics)
+        .collect())
+}
+
+/// Check the top-level rules of a document on multiple threads, merging the diagnostics of each rule in source order.
+#[cfg(feature = "parallel")]
+fn check_rules_parallel(
+    rules: &[&dyn DiagnosticRule],
+    document: &TextDocumentItem,
+    root: &SendNode,
+    cancellation: &CancellationToken,
+) -> Result<Vec<Vec<RuleDiagnostic>>, Cancelled> {
+    let walk_rules = |nodes: &mut dyn Iterator<Item = SyntaxNode<CssLanguage>>| {
+        let mut visitors = rule_visitors(rules, document);
+        walk_nodes(
+            nodes,
+            &mut visitors
+                .iter_mut()
+                .map(|visitor| visitor as &mut dyn Visitor)
+                .collect::<Vec<_>>(),
+            cancellation,
+        )?;
+        Ok(visitors
+            .into_iter()
+            .map(|visitor| visitor.diagnostics)
+            .collect::<Vec<_>>())
+    };
+
+    // The nodes containing the top-level rules are checked on this thread, e.g. the root by `DuplicateSelectors`
+    let root_node = root.clone().into_node::<CssLanguage>();
+    let mut diagnostics = walk_rules(
+        &mut root_node
+            .iter()
+            .flat_map(|root| iter::once(root.clone()).chain(root.children())),
+    )?;
+    for chunk in par_rule_chunks(root, |rules| {
+        walk_rules(&mut rules.iter().flat_map(|rule| rule.descendants()))
+    })? {
+        for (diagnostics, chunk) in diagnostics.iter_mut().zip(chunk) {
+            diagnostics.extend(chunk);
+        }
+    }
+
+    Ok(diagnostics)
+}
+
+fn rule_visitors<'a>(
+    rules: &[&'a dyn DiagnosticRule],
+    document: &'a TextDocumentItem,
+) -> Vec<RuleVisitor<'a>> {
+    rules
+        .iter()
+        .map(|&rule| RuleVisitor {
+            rule,
+            document,
+            diagnostics: Vec::new(),
+        })
+        .collect()
+}
+
+/// Reports the progress of the traversal of a document by the start of the rules, whenever its percentage changes.
+struct ProgressVisitor<'a> {
+    progress: &'a mut dyn ProgressReporter,
+    text_len: u64,
+    last_percentage: u32,
+}
+
+impl Visitor for ProgressVisitor<'_> {
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[
+            CssSyntaxKind::CSS_QUALIFIED_RULE,
+            CssSyntaxKind::CSS_NESTED_QUALIFIED_RULE,
+            CssSyntaxKind::CSS_AT_RULE,
+        ])
+    }
+
+    fn visit(&mut self, node: &SyntaxNode<CssLanguage>) {
+        let start = u64::from(u32::from(node.text_trimmed_range().start()));
+        let percentage = (start * 100 / self.text_len) as u32;
+        if percentage > self.last_percentage {
+            self.last_percentage = percentage;
+            self.progress.report(percentage);
+        }
+    }
+}
+
+/// Runs a DiagnosticRule during the traversal of a document.
+struct RuleVisitor<'a> {
+    rule: &'a dyn DiagnosticRule,
+    document: &'a TextDocumentItem,
+    diagnostics: Vec<RuleDiagnostic>,
+}
+
+impl Visitor for RuleVisitor<'_> {
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        self.rule.kinds()
+    }
+
+    fn visit(&mut self, node: &SyntaxNode<CssLanguage>) {
+        self.diagnostics.extend(self.rule.check(&RuleContext {
+            node,
+            document: self.document,
+        }));
+    }
+}
+
+impl LanguageService {
+    /// Get the syntax errors of the given document, the problems found by the validation of its declarations, e.g. an
+    /// unknown property or unit, the diagnostics reported by the rules added with `add_diagnostic_rule`, the literals
+    /// duplicating the value of a design token, and the colors without enough contrast when the
+    /// `diagnostics.colorContrast` setting enables it, sorted by position. The `diagnostics.rules` setting changes the
+    /// severity of the diagnostics of each rule by their code, or ignores them.
+    pub fn get_diagnostics(&self, document: TextDocumentItem) -> Vec<Diagnostic> {
+        self.get_diagnostics_with_cancellation(document, &CancellationToken::default())
+            .unwrap_or_default()
+    }
+
+    /// Same as `get_diagnostics`, stopping early with `Err(Cancelled)` once `cancellation` is cancelled.
+    pub fn get_diagnostics_with_cancellation(
+        &self,
+        document: TextDocumentItem,
+        cancellation: &CancellationToken,
+    ) -> Result<Vec<Diagnostic>, Cancelled> {
+        let store_entry = self.store.get_or_update_document(document);
+        self.diagnostics(&store_entry, cancellation)
+    }
+
+    /// Same as `get_diagnostics_with_cancellation`, passing the diagnostics to `on_diagnostics` in batches instead of
+    /// returning them all at once, e.g. to send them as partial results of the request. The batches are only passed
+    /// once the whole document is checked, as the diagnostics are sorted by position, while the progress through the
+    /// document is reported to `progress`.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The original source code as a `TextDocumentItem`.
+    /// * `cancellation` - A token to stop early, in which case no batch is passed.
+    /// * `batch_size` - The maximum number of diagnostics per batch, `progress::DEFAULT_BATCH_SIZE` is a good default.
+    /// * `on_diagnostics` - Called with each batch of diagnostics.
+    /// * `progress` - Receives the progress of the request, `&mut ()` to ignore it.
+    pub fn stream_diagnostics(
+        &self,
+        document: TextDocumentItem,
+        cancellation: &CancellationToken,
+        batch_size: usize,
+        on_diagnostics: &mut dyn FnMut(Vec<Diagnostic>),
+        progress: &mut dyn ProgressReporter,
+    ) -> Result<(), Cancelled> {
+        let store_entry = self.store.get_or_update_document(document);
+
+        progress.begin("Checking the document");
+        let result = self
+            .diagnostics_with_progress(&store_entry, cancellation, progress)
+            .map(|diagnostics| {
+                let mut batcher = Batcher::new(batch_size, on_diagnostics);
+                batcher.extend(diagnostics);
+                batcher.flush();
+            });
+        progress.end();
+
+        result
+    }
+
+    /// Same as `get_diagnostics`, for a document already in the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    pub fn get_diagnostics_for_uri(&self, uri: &Uri) -> Result<Vec<Diagnostic>, Error> {
+        let store_entry = self.open_entry(uri)?;
+        Ok(self
+            .diagnostics(&store_entry, &CancellationToken::default())
+            .unwrap_or_default())
+    }
+
+    /// Same as `get_diagnostics_for_uri`, as a report of `textDocument/diagnostic`. Reports have a result id changing
+    /// with their diagnostics, so that clients pulling them again get an unchanged report instead of the same
+    /// diagnostics.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    /// * `previous_result_id` - The result id of the last report of the document received by the client, if any.
+    pub fn get_diagnostic_report_for_uri(
+        &self,
+        uri: &Uri,
+        previous_result_id: Option<&str>,
+    ) -> Result<DocumentDiagnosticReport, Error> {
+        let diagnostics = self.get_diagnostics_for_uri(uri)?;
+        let result_id = result_id(&diagnostics);
+
+        Ok(if previous_result_id == Some(result_id.as_str()) {
+            DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                related_documents: None,
+                unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                    result_id,
+                },
+            })
+        } else {
+            DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                related_documents: None,
+                full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                    result_id: Some(result_id),
+                    items: diagnostics,
+                },
+            })
+        })
+    }
+
+    /// Get the diagnostics of every document of the workspace index, as a report of `workspace/diagnostic`: the open
+    /// documents, and the stylesheets discovered with `index_workspace_folder` even if they aren't open. Documents
+    /// loaded from a persisted index are only reported once their folder is indexed again, as their text isn't kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `previous_result_ids` - The result ids of the reports received by the client. Documents whose diagnostics
+    ///   didn't change get an unchanged report, and documents that aren't indexed anymore an empty report, so that
+    ///   clients drop their diagnostics.
+    /// * `cancellation` - A token to stop early, e.g. when the client cancels the request.
+    /// * `progress` - Receives the progress through the documents, `&mut ()` to ignore it.
+    ///
+    /// # Returns
+    ///
+    /// * The reports of the documents, sorted by URI, or `Err(Cancelled)`.
+    pub fn get_workspace_diagnostics(
+        &self,
+        previous_result_ids: &[PreviousResultId],
+        cancellation: &CancellationToken,
+        progress: &mut dyn ProgressReporter,
+    ) -> Result<WorkspaceDiagnosticReport, Cancelled> {
+        progress.begin("Checking stylesheets");
+        let report = self.workspace_diagnostics(previous_result_ids, cancellation, progress);
+        progress.end();
+
+        report
+    }
+
+    fn workspace_diagnostics(
+        &self,
+        previous_result_ids: &[PreviousResultId],
+        cancellation: &CancellationToken,
+        progress: &mut dyn ProgressReporter,
+    ) -> Result<WorkspaceDiagnosticReport, Cancelled> {
+        let mut previous_result_ids = previous_result_ids
+            .iter()
+            .map(|previous| (&previous.uri, previous.value.as_str()))
+            .collect::<FxHashMap<_, _>>();
+
+        let mut documents = self.workspace_index().documents();
+        documents.sort_by(|a, b| a.uri.as_str().cmp(b.uri.as_str()));
+
+        let mut progress = ItemProgress::new(progress, "files", documents.len());
+        let mut items = Vec::new();
+        for document in &documents {
+            cancellation.check()?;
+            progress.advance();
+            let Some(entry) = document.entry() else {
+                continue;
+            };
+
+            let uri = document.uri.clone();
+            // Only open documents have a version, the version of files read from disk is meaningless to the client
+            let version = self
+                .store
+                .get(&uri)
+                .map(|open| i64::from(open.document.version));
+            let diagnostics = self.diagnostics(entry, cancellation)?;
+            let result_id = result_id(&diagnostics);
+
+            items.push(
+                if previous_result_ids.remove(&uri) == Some(result_id.as_str()) {
+                    WorkspaceDocumentDiagnosticReport::Unchanged(
+                        WorkspaceUnchangedDocumentDiagnosticReport {
+                            uri,
+                            version,
+                            unchanged_document_diagnostic_report:
+                                UnchangedDocumentDiagnosticReport { result_id },
+                        },
+                    )
+                } else {
+                    WorkspaceDocumentDiagnosticReport::Full(WorkspaceFullDocumentDiagnosticReport {
+                        uri,
+                        version,
+                        full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                            result_id: Some(result_id),
+                            items: diagnostics,
+                        },
+                    })
+                },
+            );
+        }
+
+        let mut removed = previous_result_ids
+            .into_keys()
+            .filter(|uri| !documents.iter().any(|document| document.uri == **uri))
+            .collect::<Vec<_>>();
+        removed.sort_by_key(|uri| uri.as_str());
+        items.extend(removed.into_iter().map(|uri| {
+            WorkspaceDocumentDiagnosticReport::Full(WorkspaceFullDocumentDiagnosticReport {
+                uri: uri.clone(),
+                version: None,
+                full_document_diagnostic_report: FullDocumentDiagnosticReport::default(),
+            })
+        }));
+
+        Ok(WorkspaceDiagnosticReport { items })
+    }
+
+    /// Get an edit applying the fixes of every diagnostic of a document at once, as done by the `csslsrs.applyFixAll`
+    /// command. When the ranges of several fixes overlap, only the first one is applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    ///
+    /// # Returns
+    ///
+    /// * A WorkspaceEdit changing the document, without changes if no diagnostic can be fixed.
+    pub fn get_fix_all_edit(&self, uri: &Uri) -> Result<WorkspaceEdit, Error> {
+        let mut changes = HashMap::new();
+        let edits = fix_all_edits(self.get_diagnostics_for_uri(uri)?);
+        if !edits.is_empty() {
+            changes.insert(uri.clone(), edits);
+        }
+        Ok(WorkspaceEdit::new(changes))
+    }
+
+    /// Same as `get_fix_all_edit`, for every document of the workspace index, e.g. to fix the stylesheets of a whole
+    /// project at once. As with `get_workspace_diagnostics`, documents loaded from a persisted index are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `cancellation` - A token to stop early, in which case no edit is returned.
+    /// * `progress` - Receives the progress through the documents, `&mut ()` to ignore it.
+    ///
+    /// # Returns
+    ///
+    /// * A WorkspaceEdit changing the documents that can be fixed, or `Err(Cancelled)`.
+    pub fn get_workspace_fix_all_edit(
+        &self,
+        cancellation: &CancellationToken,
+        progress: &mut dyn ProgressReporter,
+    ) -> Result<WorkspaceEdit, Cancelled> {
+        progress.begin("Fixing problems");
+        let documents = self.workspace_index().documents();
+        let mut progress_items = ItemProgress::new(progress, "files", documents.len());
+
+        let mut changes = HashMap::new();
+        let result = documents.iter().try_for_each(|document| {
+            cancellation.check()?;
+            progress_items.advance();
+
+            if let Some(entry) = document.entry() {
+                let edits = fix_all_edits(self.diagnostics(entry, cancellation)?);
+                if !edits.is_empty() {
+                    changes.insert(document.uri.clone(), edits);
+                }
+            }
+            Ok(())
+        });
+        progress.end();
+
+        result.map(|()| WorkspaceEdit::new(changes))
+    }
+
+    pub(crate) fn diagnostics(
+        &self,
+        store_entry: &StoreEntry,
+        cancellation: &CancellationToken,
+    ) -> Result<Vec<Diagnostic>, Cancelled> {
+        self.diagnostics_with_progress(store_entry, cancellation, &mut ())
+    }
+
+    /// Same as `diagnostics`, reporting the progress through the document to `progress` when it isn't cached yet and
+    /// is checked on a single thread.
+    fn diagnostics_with_progress(
+        &self,
+        store_entry: &StoreEntry,
+        cancellation: &CancellationToken,
+        progress: &mut dyn ProgressReporter,
+    ) -> Result<Vec<Diagnostic>, Cancelled> {
+        request!(self, "diagnostics", store_entry);
+        let mut diagnostics = store_entry.try_cached("diagnostics", |entry| {
+            let design_tokens = self.design_tokens();
+            let design_token_literals =
+                (!design_tokens.is_empty()).then_some(DesignTokenLiterals(design_tokens));
+
+            let unknown_descriptors = UnknownDescriptors(self.css_data());
+            let unknown_properties = UnknownProperties {
+                data: self.css_data(),
+                dialect: entry.dialect,
+            };
+            let page_properties = PageProperties(self.css_data());
+            let color_contrast = ColorContrast::new(
+                &self
+                    .settings_for(&entry.document.uri)
+                    .diagnostics
+                    .color_contrast,
+            );
+            let rules = self
+                .diagnostic_rules
+                .iter()
+                .map(|rule| rule.as_ref())
+                .chain(
+                    design_token_literals
+                        .as_ref()
+                        .map(|rule| rule as &dyn DiagnosticRule),
+                )
+                .chain(
+                    (entry.dialect == CssDialect::Css)
+                        .then_some(&NestingSelectorPositions as &dyn DiagnosticRule),
+                )
+                .chain(
+                    (entry.dialect != CssDialect::Less)
+                        .then_some(&DuplicateSelectors as &dyn DiagnosticRule),
+                )
+                .chain(
+                    color_contrast
+                        .as_ref()
+                        .map(|rule| rule as &dyn DiagnosticRule),
+                )
+                .chain([
+                    &ScopeBoundaries as &dyn DiagnosticRule,
+                    &ContainerQueryFeatures,
+                    &KeyframeSelectors,
+                    &PropertyRegistrations,
+                    &CounterStyleDescriptors,
+                    &FontDescriptors,
+                    &unknown_descriptors,
+                    &page_properties,
+                    &RedundantImports,
+                    &unknown_properties,
+                    &UnknownUnits,
+                ])
+                .collect::<Vec<_>>();
+
+            let mut diagnostics = rules
+                .iter()
+                .zip(check_rules(&rules, entry, cancellation, progress)?)
+                .flat_map(|(&rule, diagnostics)| {
+                    diagnostics
+                        .into_iter()
+                        .map(move |diagnostic| (rule, diagnostic))
+                })
+                .filter_map(|(rule, diagnostic)| {
+                    Some(Diagnostic {
+                        range: range(
+                            entry.line_index(),
+                            diagnostic.range,
+                            self.encoding_for(entry),
+                        )
+                        .ok()?,
+                        severity: Some(
+                            diagnostic
+                                .severity
+                                .unwrap_or_else(|| rule.default_severity()),
+                        ),
+                        code: Some(NumberOrString::String(rule.name().to_string())),
+                        source: Some("csslsrs".to_string()),
+                        message: diagnostic.message,
+                        data: diagnostic.fix.and_then(|fix| {
+                            serde_json::to_value(DiagnosticData {
+                                fix,
+                                range: diagnostic.fix_range.and_then(|fix_range| {
+                                    range(entry.line_index(), fix_range, self.encoding_for(entry))
+                                        .ok()
+                                }),
+                            })
+                            .ok()
+                        }),
+                        ..Default::default()
+                    })
+                })
+                .collect::<Vec<_>>();
+            // The keyframe selectors the parser fails on, e.g. `foo`, are reported by `KeyframeSelectors` already
+            let keyframe_selectors = diagnostics
+                .iter()
+                .filter(|diagnostic| {
+                    diagnostic.code
+                        == Some(NumberOrString::String(KeyframeSelectors.name().to_string()))
+                })
+                .map(|diagnostic| diagnostic.range)
+                .collect::<Vec<_>>();
+            diagnostics.extend(self.syntax_errors(entry).into_iter().filter(|error| {
+                !keyframe_selectors
+                    .iter()
+                    .any(|range| range.start <= error.range.start && error.range.end <= range.end)
+            }));
+            diagnostics.extend(self.malformed_hex_colors(entry));
+            diagnostics.sort_by_key(|diagnostic| diagnostic.range.start);
+
+            Ok(diagnostics)
+        })?;
+
+        // Placeholder selectors, layers and containers can be declared or used in other documents, so their diagnostics
+        // aren't cached with the others
+        let undeclared = self
+            .undefined_placeholders(store_entry)
+            .into_iter()
+            .chain(self.undeclared_layers(store_entry, cancellation)?)
+            .chain(self.unused_layers(store_entry, cancellation)?)
+            .chain(self.undeclared_containers(store_entry, cancellation)?)
+            .collect::<Vec<_>>();
+        if !undeclared.is_empty() {
+            diagnostics.extend(undeclared);
+            diagnostics.sort_by_key(|diagnostic| diagnostic.range.start);
+        }
+
+        // The levels of the rules apply to the cached diagnostics too, as they depend on the scope of the document
+        let settings = self.settings_for(&store_entry.document.uri);
+        let rules = &settings.diagnostics.rules;
+        if !rules.is_empty() {
+            diagnostics.retain_mut(|diagnostic| {
+                let Some(NumberOrString::String(code)) = &diagnostic.code else {
+                    return true;
+                };
+                let Some(level) = rules.get(code) else {
+                    return true;
+                };
+                diagnostic.severity = level.severity();
+                diagnostic.severity.is_some()
+            });
+        }
+        Ok(diagnostics)
+    }
+
+    /// Report the syntax errors of a document, in the code as written even when its tree was parsed again after
+    /// repairing them. The errors in the value of `unicode-range` are left to `FontDescriptors`, the parser failing
+    /// on the wildcards of valid ranges, e.g. `U+4??`.
+    fn syntax_errors(&self, store_entry: &StoreEntry) -> Vec<Diagnostic> {
+        let parse = store_entry.css_tree();
+        let root = parse.syntax();
+        parse
+            .diagnostics()
+            .iter()
+            .filter_map(|error| {
+                let span = error.location().span?;
+                let in_unicode_range = root
+                    .token_at_offset(span.start())
+                    .right_biased()
+                    .and_then(|token| token.parent())
+                    .is_some_and(|node| in_unicode_range(&node));
+                if in_unicode_range {
+                    return None;
+                }
+
+                Some(Diagnostic {
+                    range: range(
+                        store_entry.line_index(),
+                        span,
+                        self.encoding_for(store_entry),
+                    )
+                    .ok()?,
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String("syntax-error".to_string())),
+                    source: Some("csslsrs".to_string()),
+                    message: PrintDescription(error).to_string(),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    /// Report the hashes of values that aren't hex colors, e.g. `#ggg` or `#12345`, from the code as written as they are
+    /// blanked before parsing, see `recovery::mask_invalid_hex_colors`.
+    fn malformed_hex_colors(&self, store_entry: &StoreEntry) -> Vec<Diagnostic> {
+        invalid_hex_colors(&store_entry.document.text, store_entry.dialect)
+            .into_iter()
+            .filter(|color| color.is_color)
+            .filter_map(|color| {
+                let text = &store_entry.document.text[color.range.clone()];
+                Some(Diagnostic {
+                    range: range(
+                        store_entry.line_index(),
+                        TextRange::new(
+                            TextSize::from(color.range.start as u32),
+                            TextSize::from(color.range.end as u32),
+                        ),
+                        self.encoding_for(store_entry),
+                    )
+                    .ok()?,
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String("invalid-hex-color".to_string())),
+                    source: Some("csslsrs".to_string()),
+                    message: format!(
+                        "`{text}` isn't a hex color, which has 3, 4, 6 or 8 hex digits, e.g. `#f00` or `#ff0000`"
+                    ),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    /// Report the layers a document assigns rules to, with `@layer` blocks or `@import … layer()`, without them being
+    /// declared by a `@layer` statement in the document or anywhere else in the workspace, e.g. `@layer components {`
+    /// without any `@layer base, components;`. The order of such layers depends on where they first appear.
+    ///
+    /// Frameworks listing `layer` in the `css.customDirectives` setting declare their layers themselves, e.g. Tailwind
+    /// with `@tailwind`, so nothing is reported then.
+    fn undeclared_layers(
+        &self,
+        store_entry: &StoreEntry,
+        cancellation: &CancellationToken,
+    ) -> Result<Vec<Diagnostic>, Cancelled> {
+        if self.has_layer_directive() {
+            return Ok(Vec::new());
+        }
+        let analysis = store_entry.analysis(cancellation)?;
+        let assignments = analysis
+            .symbols
+            .iter()
+            .filter(|symbol| symbol.kind == SymbolKind::Layer && !symbol.is_declaration)
+            .collect::<Vec<_>>();
+        if assignments.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let declared = |name: &str| {
+            analysis.symbols.iter().any(|symbol| {
+                symbol.kind == SymbolKind::Layer && symbol.is_declaration && symbol.name == name
+            })
+        };
+        let index = self.workspace_index();
+        Ok(assignments
+            .into_iter()
+            .filter(|symbol| {
+                !declared(&symbol.name)
+                    && !index
+                        .find(SymbolKind::Layer, &symbol.name)
+                        .iter()
+                        .any(|(_, symbol)| symbol.is_declaration)
+            })
+            .filter_map(|symbol| {
+                Some(Diagnostic {
+                    range: range(
+                        store_entry.line_index(),
+                        symbol.range,
+                        self.encoding_for(store_entry),
+                    )
+                    .ok()?,
+                    severity: Some(DiagnosticSeverity::INFORMATION),
+                    code: Some(NumberOrString::String("undeclared-layer".to_string())),
+                    source: Some("csslsrs".to_string()),
+                    message: format!(
+                        "The layer `{}` isn't declared by a `@layer` statement, so its order depends on where it first appears",
+                        symbol.name
+                    ),
+                    ..Default::default()
+                })
+            })
+            .collect())
+    }
+
+    /// Report the layers declared by a `@layer` statement of a document without ever being assigned rules, by a `@layer`
+    /// block, an `@import … layer()` or one of their sublayers, in the document or anywhere else in the workspace, e.g.
+    /// `utilities` in `@layer base, utilities;` without any `@layer utilities { … }`. The fix removes the layer from the
+    /// statement, or the whole statement when none of its layers are assigned rules.
+    ///
+    /// As with `undeclared_layers`, nothing is reported when `layer` is one of the `css.customDirectives`.
+    fn unused_layers(
+        &self,
+        store_entry: &StoreEntry,
+        cancellation: &CancellationToken,
+    ) -> Result<Vec<Diagnostic>, Cancelled> {
+        let analysis = store_entry.analysis(cancellation)?;
+        let has_statements = analysis
+            .symbols
+            .iter()
+            .any(|symbol| symbol.kind == SymbolKind::Layer && symbol.is_declaration);
+        if !has_statements || self.has_layer_directive() {
+            return Ok(Vec::new());
+        }
+
+        let documents = self.workspace_index().documents();
+        let assigned = analysis
+            .symbols
+            .iter()
+            .chain(documents.iter().flat_map(|document| &document.symbols))
+            .filter(|symbol| symbol.kind == SymbolKind::Layer && !symbol.is_declaration)
+            .map(|symbol| symbol.name.as_str())
+            .collect::<Vec<_>>();
+        let is_used = |name: &str| {
+            assigned.iter().any(|assigned| {
+                assigned
+                    .strip_prefix(name)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+        };
+
+        let text = &store_entry.document.text;
+        let mut diagnostics = Vec::new();
+        for statement in store_entry
+            .css_tree()
+            .syntax()
+            .descendants()
+            .filter(|node| node.kind() == CssSyntaxKind::CSS_LAYER_AT_RULE)
+        {
+            let names = layer_names(&statement);
+            let unused = (0..names.len())
+                .filter(|index| names[*index].is_statement && !is_used(&names[*index].name))
+                .collect::<Vec<_>>();
+            for &index in &unused {
+                let name = &names[index];
+                // The separators around the layer are removed along with it, e.g. `, utilities`
+                let fix_range = if unused.len() == names.len() {
+                    let statement = statement.parent().unwrap_or_else(|| statement.clone());
+                    line_range(text, statement.text_trimmed_range())
+                } else if let Some(next) = names.get(index + 1) {
+                    TextRange::new(name.range.start(), next.range.start())
+                } else {
+                    TextRange::new(names[index - 1].range.end(), name.range.end())
+                };
+                let encoding = self.encoding_for(store_entry);
+                let (Ok(name_range), Ok(fix_range)) = (
+                    range(store_entry.line_index(), name.range, encoding),
+                    range(store_entry.line_index(), fix_range, encoding),
+                ) else {
+                    continue;
+                };
+
+                diagnostics.push(Diagnostic {
+                    range: name_range,
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    code: Some(NumberOrString::String("unused-layer".to_string())),
+                    source: Some("csslsrs".to_string()),
+                    message: format!(
+                        "The layer `{}` is declared but never assigned rules by a `@layer` block or an `@import`",
+                        name.name
+                    ),
+                    data: serde_json::to_value(DiagnosticData {
+                        fix: String::new(),
+                        range: Some(fix_range),
+                    })
+                    .ok(),
+                    ..Default::default()
+                });
+            }
+        }
+        Ok(diagnostics)
+    }
+
+    /// Whether `layer` is one of the `css.customDirectives`, for frameworks declaring their layers themselves, e.g.
+    /// Tailwind with `@tailwind`.
+    fn has_layer_directive(&self) -> bool {
+        self.settings()
+            .css
+            .custom_directives
+            .iter()
+            .any(|directive| CustomDirective::parse(directive) == CustomDirective::AtRule("layer"))
+    }
+
+    /// Report the containers a document queries with `@container` without any `container-name` or `container`
+    /// declaration naming them, in the document or anywhere else in the workspace, e.g. `@container sidebar (…)`
+    /// without any `container-name: sidebar`. Such queries never match.
+    fn undeclared_containers(
+        &self,
+        store_entry: &StoreEntry,
+        cancellation: &CancellationToken,
+    ) -> Result<Vec<Diagnostic>, Cancelled> {
+        let analysis = store_entry.analysis(cancellation)?;
+        let queried = analysis
+            .symbols
+            .iter()
+            .filter(|symbol| symbol.kind == SymbolKind::Container && !symbol.is_declaration)
+            .collect::<Vec<_>>();
+        if queried.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let index = self.workspace_index();
+        Ok(queried
+            .into_iter()
+            .filter(|symbol| {
+                !index
+                    .find(SymbolKind::Container, &symbol.name)
+                    .iter()
+                    .any(|(_, symbol)| symbol.is_declaration)
+            })
+            .filter_map(|symbol| {
+                Some(Diagnostic {
+                    range: range(
+                        store_entry.line_index(),
+                        symbol.range,
+                        self.encoding_for(store_entry),
+                    )
+                    .ok()?,
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    code: Some(NumberOrString::String("undeclared-container".to_string())),
+                    source: Some("csslsrs".to_string()),
+                    message: format!(
+                        "No `container-name` or `container` declaration names the container `{}`, so the query never matches",
+                        symbol.name
+                    ),
+                    ..Default::default()
+                })
+            })
+            .collect())
+    }
+
+    /// Report the placeholder selectors an SCSS document extends without them being declared, in the document, in the
+    /// stylesheets it loads or anywhere else in the workspace, e.g. `@extend %card;` without any `%card { … }`. Extends
+    /// with the `!optional` flag aren't reported, as Sass ignores them.
+    fn undefined_placeholders(&self, store_entry: &StoreEntry) -> Vec<Diagnostic> {
+        let constructs = store_entry.scss_constructs();
+        let extends = constructs
+            .iter()
+            .filter(|construct| {
+                store_entry.dialect == CssDialect::Scss
+                    && construct.kind == ScssConstructKind::Extend
+                    && !store_entry.document.text[construct.range].ends_with("!optional")
+            })
+            .filter_map(|construct| {
+                let name = construct.name.strip_prefix('%')?;
+                name.bytes()
+                    .all(is_ident_char)
+                    .then_some((name, construct.name_range))
+            })
+            .collect::<Vec<_>>();
+        if extends.is_empty() {
+            return Vec::new();
+        }
+
+        let placeholders = self.scss_placeholders(store_entry);
+        let index = self.workspace_index();
+        extends
+            .into_iter()
+            .filter(|(name, _)| {
+                !placeholders
+                    .iter()
+                    .any(|(placeholder, _)| placeholder == name)
+                    && !index
+                        .find(SymbolKind::Placeholder, name)
+                        .iter()
+                        .any(|(_, symbol)| symbol.is_declaration)
+            })
+            .filter_map(|(name, name_range)| {
+                Some(Diagnostic {
+                    range: range(
+                        store_entry.line_index(),
+                        name_range,
+                        self.encoding_for(store_entry),
+                    )
+                    .ok()?,
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    code: Some(NumberOrString::String("undefined-placeholder".to_string())),
+                    source: Some("csslsrs".to_string()),
+                    message: format!("The placeholder selector `%{name}` is not declared"),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+}
+
+/// Get the edits fixing diagnostics, skipping the fixes overlapping a previous one.
+fn fix_all_edits(diagnostics: Vec<Diagnostic>) -> Vec<TextEdit> {
+    let mut edits: Vec<TextEdit> = Vec::new();
+    for edit in diagnostics.iter().filter_map(fix_edit) {
+        if edits.iter().any(|previous| {
+            edit.range.start < previous.range.end && previous.range.start < edit.range.end
+        }) {
+            continue;
+        }
+
+        edits.push(edit);
+    }
+
+    edits
+}
+
+/// Get the edit fixing a diagnostic, from the data attached to the diagnostics that can be fixed.
+pub(crate) fn fix_edit(diagnostic: &Diagnostic) -> Option<TextEdit> {
+    let data = serde_json::from_value::<DiagnosticData>(diagnostic.data.clone()?).ok()?;
+    Some(TextEdit::new(
+        data.range.unwrap_or(diagnostic.range),
+        data.fix,
+    ))
+}
+
+/// The result id of a diagnostic report, a hash of its diagnostics.
+fn result_id(diagnostics: &[Diagnostic]) -> String {
+    let mut hasher = FxHasher::default();
+    serde_json::to_string(diagnostics)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(feature = "wasm")]
+mod wasm_bindings {
+    use crate::service::wasm_bindings::WasmLanguageService;
+    use crate::{error::Error, wasm_text_document};
+    use lsp_types::Uri;
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Get the diagnostics of the given source code, sorted by position.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns A list of `Diagnostic` objects.
+ */
+export async function get_diagnostics(source: TextDocumentSource, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<import("vscode-languageserver-types").Diagnostic[]>;
+
+export interface LanguageService {
+	/**
+	 * Get the diagnostics of an open document, see `get_diagnostics`.
+	 */
+	getDiagnostics(uri: string): import("vscode-languageserver-types").Diagnostic[];
+}
+"#;
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn get_diagnostics(document: JsValue, encoding: Option<String>) -> Result<JsValue, Error> {
+        let parsed_text_document = wasm_text_document::create_text_document(document)?;
+
+        let diagnostics = wasm_text_document::with_document(
+            parsed_text_document,
+            wasm_text_document::position_encoding(encoding)?,
+            |language_service, uri| language_service.get_diagnostics_for_uri(uri),
+        )?;
+
+        wasm_text_document::to_value(&diagnostics)
+    }
+
+    #[wasm_bindgen(js_class = LanguageService)]
+    impl WasmLanguageService {
+        #[wasm_bindgen(js_name = getDiagnostics, skip_typescript)]
+        pub fn get_diagnostics(&self, uri: JsValue) -> Result<JsValue, Error> {
+            let uri: Uri = wasm_text_document::from_value(uri, "uri")?;
+            let diagnostics = self.language_service.get_diagnostics_for_uri(&uri)?;
+
+            wasm_text_document::to_value(&diagnostics)
+        }
+    }
+}
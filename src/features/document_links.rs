@@ -0,0 +1,143 @@
+use biome_css_syntax::CssGenericProperty;
+use biome_rowan::AstNode;
+use lsp_types::{DocumentLink, TextDocumentItem, Uri};
+
+use crate::{
+    converters::to_proto,
+    error::Error,
+    font_faces::{font_sources, resolve_url},
+    icss::icss_imports,
+    instrumentation::request,
+    parser::CssDialect,
+    scss::scss_modules,
+    service::LanguageService,
+    store::StoreEntry,
+};
+
+impl LanguageService {
+    /// Get the links of the given document: the stylesheets an SCSS document loads with `@use`, `@forward` and
+    /// `@import`, or those a CSS document imports values from with `@value … from "…";`, resolved with
+    /// `resolve_scss_module`, and the font files loaded by the `src` descriptor of `@font-face` rules, relative to the
+    /// document. The URLs of stylesheets that aren't found aren't links.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The original source code as a `TextDocumentItem`.
+    ///
+    /// # Returns
+    ///
+    /// * The links, in source order.
+    pub fn get_document_links(&self, document: TextDocumentItem) -> Vec<DocumentLink> {
+        let store_entry = self.store.get_or_update_document(document);
+        self.document_links(&store_entry)
+    }
+
+    /// Same as `get_document_links`, for a document already in the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    pub fn get_document_links_for_uri(&self, uri: &Uri) -> Result<Vec<DocumentLink>, Error> {
+        Ok(self.document_links(&*self.open_entry(uri)?))
+    }
+
+    fn document_links(&self, store_entry: &StoreEntry) -> Vec<DocumentLink> {
+        request!(self, "document_links", store_entry);
+        let constructs = store_entry.scss_constructs();
+        let urls = match store_entry.dialect {
+            CssDialect::Scss => scss_modules(&store_entry.document.text, &constructs)
+                .into_iter()
+                .map(|module| (module.url, module.range))
+                .collect(),
+            CssDialect::Css => icss_imports(&store_entry.document.text, &constructs)
+                .into_iter()
+                .map(|import| (import.url, import.range))
+                .collect(),
+            CssDialect::Less => Vec::new(),
+        };
+
+        let uri = &store_entry.document.uri;
+        let fonts = store_entry
+            .css_tree()
+            .syntax()
+            .descendants()
+            .filter_map(CssGenericProperty::cast)
+            .flat_map(|property| font_sources(&property))
+            .filter_map(|source| {
+                let (url, range) = source.url?;
+                Some((range, resolve_url(uri, &url)?))
+            });
+
+        let encoding = self.encoding_for(store_entry);
+        let mut links = urls
+            .into_iter()
+            .filter_map(|(url, range)| Some((range, self.resolve_scss_module(uri, &url)?)))
+            .chain(fonts)
+            .filter_map(|(range, target)| {
+                Some(DocumentLink {
+                    range: to_proto::range(store_entry.line_index(), range, encoding).ok()?,
+                    target: Some(target),
+                    tooltip: None,
+                    data: None,
+                })
+            })
+            .collect::<Vec<_>>();
+        links.sort_by_key(|link| link.range.start);
+        links
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod wasm_bindings {
+    use crate::service::wasm_bindings::WasmLanguageService;
+    use crate::{error::Error, wasm_text_document};
+    use lsp_types::Uri;
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Get the links of the given source code, e.g. the stylesheets loaded by an SCSS document with `@use`
+ * or the font files of `@font-face` rules.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns A list of `DocumentLink` objects.
+ */
+export async function get_document_links(source: TextDocumentSource, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<import("vscode-languageserver-types").DocumentLink[]>;
+
+export interface LanguageService {
+	/**
+	 * Get the links of an open document, see `get_document_links`.
+	 */
+	getDocumentLinks(uri: string): import("vscode-languageserver-types").DocumentLink[];
+}
+"#;
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn get_document_links(
+        document: JsValue,
+        encoding: Option<String>,
+    ) -> Result<JsValue, Error> {
+        let parsed_text_document = wasm_text_document::create_text_document(document)?;
+
+        let links = wasm_text_document::with_document(
+            parsed_text_document,
+            wasm_text_document::position_encoding(encoding)?,
+            |language_service, uri| language_service.get_document_links_for_uri(uri),
+        )?;
+
+        wasm_text_document::to_value(&links)
+    }
+
+    #[wasm_bindgen(js_class = LanguageService)]
+    impl WasmLanguageService {
+        #[wasm_bindgen(js_name = getDocumentLinks, skip_typescript)]
+        pub fn get_document_links(&self, uri: JsValue) -> Result<JsValue, Error> {
+            let uri: Uri = wasm_text_document::from_value(uri, "uri")?;
+            let links = self.language_service.get_document_links_for_uri(&uri)?;
+
+            wasm_text_document::to_value(&links)
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use biome_css_syntax::{CssLanguage, CssSyntaxKind};
+use biome_rowan::{SyntaxNode, TextRange, TextSize, WalkEvent};
+use lsp_types::{DocumentSymbol, SymbolKind, TextDocumentItem, Uri};
+
+use crate::{
+    converters::{to_proto, PositionEncoding},
+    error::Error,
+    instrumentation::request,
+    layers::layer_names,
+    selectors::resolved_selectors,
+    service::LanguageService,
+    store::StoreEntry,
+};
+
+impl LanguageService {
+    /// Get the outline of the given document: its rules and the at-rules with a block, e.g. `@media`, with the rules
+    /// nested in them as children. Nested rules have their selectors resolved against their parent rules as detail,
+    /// e.g. `.card .title:hover` for `&:hover` in `.title` in `.card`, and nested layers their full name. Rules are thus
+    /// grouped by the `@layer` blocks they are in, and `@layer` statements declaring the order of layers are listed too.
+    /// The margin boxes of `@page` rules, e.g. `@top-center`, are their children. The directives of SCSS and Less with a
+    /// block, e.g. `@if` or `@include`, are named by their source.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The original source code as a `TextDocumentItem`.
+    ///
+    /// # Returns
+    ///
+    /// * The symbols of the top-level rules, in source order.
+    pub fn get_document_symbols(&self, document: TextDocumentItem) -> Vec<DocumentSymbol> {
+        let store_entry = self.store.get_or_update_document(document);
+        self.document_symbols(&store_entry)
+    }
+
+    /// Same as `get_document_symbols`, for a document already in the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    pub fn get_document_symbols_for_uri(&self, uri: &Uri) -> Result<Vec<DocumentSymbol>, Error> {
+        Ok(self.document_symbols(&*self.open_entry(uri)?))
+    }
+
+    fn document_symbols(&self, store_entry: &StoreEntry) -> Vec<DocumentSymbol> {
+        request!(self, "document_symbols", store_entry);
+        let encoding = self.encoding_for(store_entry);
+        children_symbols(store_entry, &store_entry.css_tree().syntax(), encoding)
+    }
+}
+
+/// The symbols of the rules and at-rules in a node, without descending into the ones found.
+fn children_symbols(
+    store_entry: &StoreEntry,
+    node: &SyntaxNode<CssLanguage>,
+    encoding: PositionEncoding,
+) -> Vec<DocumentSymbol> {
+    let mut symbols = Vec::new();
+    let mut descendants = node.preorder();
+    while let Some(event) = descendants.next() {
+        let WalkEvent::Enter(descendant) = event else {
+            continue;
+        };
+        let symbol = match descendant.kind() {
+            CssSyntaxKind::CSS_QUALIFIED_RULE | CssSyntaxKind::CSS_NESTED_QUALIFIED_RULE => {
+                rule_symbol(store_entry, &descendant, encoding)
+            }
+            CssSyntaxKind::CSS_AT_RULE | CssSyntaxKind::CSS_MARGIN_AT_RULE => {
+                at_rule_symbol(store_entry, &descendant, encoding)
+            }
+            _ => continue,
+        };
+        descendants.skip_subtree();
+        symbols.extend(symbol);
+    }
+    symbols
+}
+
+fn rule_symbol(
+    store_entry: &StoreEntry,
+    rule: &SyntaxNode<CssLanguage>,
+    encoding: PositionEncoding,
+) -> Option<DocumentSymbol> {
+    let block = block(rule)?;
+    let prelude = TextRange::new(
+        rule.text_trimmed_range().start(),
+        block.text_trimmed_range().start(),
+    );
+    let name = source_name(store_entry, prelude)?;
+
+    // The directives of SCSS and Less with a block are masked as nested rules, e.g. `@if $condition {` as `&`, and
+    // the interpolations of selectors are blanked, so only the selectors parsed as is are resolved
+    let source = &store_entry.document.text[prelude];
+    let parsed = rule.text_trimmed().to_string();
+    let parsed = &parsed[..source.len()];
+    let (kind, detail) = if parsed.starts_with('&') && !source.starts_with('&') {
+        (directive_kind(&name), None)
+    } else if parsed != source {
+        (SymbolKind::CLASS, None)
+    } else {
+        let resolved = resolved_selectors(rule).join(", ");
+        (SymbolKind::CLASS, (resolved != name).then_some(resolved))
+    };
+
+    symbol(
+        store_entry,
+        rule,
+        Some(&block),
+        name,
+        kind,
+        detail,
+        prelude,
+        encoding,
+    )
+}
+
+fn at_rule_symbol(
+    store_entry: &StoreEntry,
+    at_rule: &SyntaxNode<CssLanguage>,
+    encoding: PositionEncoding,
+) -> Option<DocumentSymbol> {
+    // The margin boxes of pages, e.g. `@top-center` in `@page { … }`, aren't wrapped in a `CssAtRule`
+    let specific = if at_rule.kind() == CssSyntaxKind::CSS_MARGIN_AT_RULE {
+        at_rule.clone()
+    } else {
+        at_rule.first_child()?
+    };
+    // Layer statements have no block, but declare the order of the layers rules are grouped by
+    let is_layer = specific.kind() == CssSyntaxKind::CSS_LAYER_AT_RULE;
+    // The block of a layer is in its declaration, e.g. `base { … }` in `@layer base { … }`
+    let block = if is_layer {
+        specific.children().find_map(|child| block(&child))
+    } else {
+        block(&specific)
+    };
+    if block.is_none() && !is_layer {
+        return None;
+    }
+    let prelude = TextRange::new(
+        at_rule.text_trimmed_range().start(),
+        block
+            .as_ref()
+            .map_or(at_rule.text_trimmed_range().end(), |block| {
+                block.text_trimmed_range().start()
+            }),
+    );
+    let name = source_name(store_entry, prelude)?;
+
+    // Nested layers have their full name as detail, e.g. `components.buttons` for `@layer buttons` in `components`
+    let detail = is_layer
+        .then(|| {
+            layer_names(&specific)
+                .into_iter()
+                .map(|layer| layer.name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .filter(|layers| !layers.is_empty() && name.strip_prefix("@layer ") != Some(layers));
+
+    symbol(
+        store_entry,
+        at_rule,
+        block.as_ref(),
+        name,
+        SymbolKind::NAMESPACE,
+        detail,
+        prelude,
+        encoding,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn symbol(
+    store_entry: &StoreEntry,
+    node: &SyntaxNode<CssLanguage>,
+    block: Option<&SyntaxNode<CssLanguage>>,
+    name: String,
+    kind: SymbolKind,
+    detail: Option<String>,
+    prelude: TextRange,
+    encoding: PositionEncoding,
+) -> Option<DocumentSymbol> {
+    let line_index = store_entry.line_index();
+    let selection_range = TextRange::new(
+        prelude.start(),
+        prelude.start() + TextSize::of(name_source(store_entry, prelude)),
+    );
+
+    #[allow(deprecated)]
+    Some(DocumentSymbol {
+        name,
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range: to_proto::range(line_index, node.text_trimmed_range(), encoding).ok()?,
+        selection_range: to_proto::range(line_index, selection_range, encoding).ok()?,
+        children: block.map(|block| children_symbols(store_entry, block, encoding)),
+    })
+}
+
+/// The block of a rule or of a specific at-rule, e.g. the `CssMediaAtRule` of a `CssAtRule`.
+fn block(node: &SyntaxNode<CssLanguage>) -> Option<SyntaxNode<CssLanguage>> {
+    node.children().find(|child| {
+        child
+            .first_token()
+            .is_some_and(|token| token.kind() == CssSyntaxKind::L_CURLY)
+    })
+}
+
+/// The source of a prelude, without its trailing whitespace and the semicolon ending statements.
+fn name_source(store_entry: &StoreEntry, prelude: TextRange) -> &str {
+    store_entry.document.text[prelude]
+        .trim_end()
+        .trim_end_matches(';')
+        .trim_end()
+}
+
+/// The name of a symbol, the source of its prelude on a single line, e.g. `@media (min-width: 100px)`.
+fn source_name(store_entry: &StoreEntry, prelude: TextRange) -> Option<String> {
+    let name = name_source(store_entry, prelude)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!name.is_empty()).then_some(name)
+}
+
+/// The kind of the symbol of a directive of SCSS or Less masked as a nested rule.
+fn directive_kind(name: &str) -> SymbolKind {
+    match name.split(|c: char| c.is_whitespace() || c == '(').next() {
+        Some("@mixin" | "@function") => SymbolKind::FUNCTION,
+        Some(name) if name.starts_with('@') => SymbolKind::NAMESPACE,
+        // Nested properties of SCSS, e.g. `font: {`
+        _ => SymbolKind::PROPERTY,
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod wasm_bindings {
+    use crate::service::wasm_bindings::WasmLanguageService;
+    use crate::{error::Error, wasm_text_document};
+    use lsp_types::Uri;
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Get the outline of the given source code: its rules and at-rules, with the rules nested in them as children.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns A list of `DocumentSymbol` objects.
+ */
+export async function get_document_symbols(source: TextDocumentSource, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<import("vscode-languageserver-types").DocumentSymbol[]>;
+
+export interface LanguageService {
+	/**
+	 * Get the outline of an open document, see `get_document_symbols`.
+	 */
+	getDocumentSymbols(uri: string): import("vscode-languageserver-types").DocumentSymbol[];
+}
+"#;
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn get_document_symbols(
+        document: JsValue,
+        encoding: Option<String>,
+    ) -> Result<JsValue, Error> {
+        let parsed_text_document = wasm_text_document::create_text_document(document)?;
+
+        let symbols = wasm_text_document::with_document(
+            parsed_text_document,
+            wasm_text_document::position_encoding(encoding)?,
+            |language_service, uri| language_service.get_document_symbols_for_uri(uri),
+        )?;
+
+        wasm_text_document::to_value(&symbols)
+    }
+
+    #[wasm_bindgen(js_class = LanguageService)]
+    impl WasmLanguageService {
+        #[wasm_bindgen(js_name = getDocumentSymbols, skip_typescript)]
+        pub fn get_document_symbols(&self, uri: JsValue) -> Result<JsValue, Error> {
+            let uri: Uri = wasm_text_document::from_value(uri, "uri")?;
+            let symbols = self.language_service.get_document_symbols_for_uri(&uri)?;
+
+            wasm_text_document::to_value(&symbols)
+        }
+    }
+}
@@ -1,18 +1,23 @@
-use crate::{converters::line_index::LineIndex, service::LanguageService};
+use crate::{
+    converters::line_index::LineIndex, error::Error, instrumentation::request, parser::CssDialect,
+    service::LanguageService, store::StoreEntry,
+};
 use biome_rowan::TextSize;
-use lsp_types::{FoldingRange, FoldingRangeKind, TextDocumentItem};
+use lsp_types::{FoldingRange, FoldingRangeKind, TextDocumentItem, Uri};
 
 /// Compute the folding ranges for the given CSS source code. It supports CSS blocks enclosed in
 /// braces, multi-line comments, and regions marked with `#region` and `#endregion` comments.
 ///
 /// # Arguments
 /// `document` - The original CSS source code as a `TextDocumentItem`.
+/// `dialect` - The dialect of the document, `//` line comments are only skipped in SCSS and Less.
 /// `line_index` - The precomputed line index from StoreEntry.
 ///
 /// # Returns
 /// A vector of `FoldingRange` indicating the foldable regions in the CSS code.
 fn compute_folding_ranges(
     document: &TextDocumentItem,
+    dialect: CssDialect,
     line_index: &LineIndex,
 ) -> Vec<FoldingRange> {
     let mut folding_ranges = Vec::new();
@@ -65,6 +70,38 @@ fn compute_folding_ranges(
                             .unwrap_or_else(|x| x.saturating_sub(1))
                             as u32;
                         comment_stack.push(line_number);
+                    } else if next_char == '/'
+                        && dialect != CssDialect::Css
+                        && !source[..offset].ends_with(':')
+                    {
+                        // Line comments of SCSS and Less can't contain blocks, but can mark regions. `//` right after
+                        // a colon is part of a URL, e.g. `url(http://…)`.
+                        let end = source[offset..]
+                            .find('\n')
+                            .map_or(source.len(), |end| offset + end);
+                        let comment = &source[offset..end];
+                        let text_size_offset = TextSize::try_from(offset).unwrap();
+                        let line_number = line_starts
+                            .binary_search(&text_size_offset)
+                            .unwrap_or_else(|x| x.saturating_sub(1))
+                            as u32;
+
+                        if comment.contains("#region") {
+                            region_stack.push(line_number);
+                        } else if comment.contains("#endregion") {
+                            if let Some(region_start) = region_stack.pop() {
+                                folding_ranges.push(FoldingRange {
+                                    start_line: region_start,
+                                    start_character: None,
+                                    end_line: line_number,
+                                    end_character: None,
+                                    kind: Some(FoldingRangeKind::Region),
+                                    collapsed_text: None,
+                                });
+                            }
+                        }
+
+                        while chars.next_if(|&(index, _)| index < end).is_some() {}
                     }
                 }
             }
@@ -122,7 +159,7 @@ fn compute_folding_ranges(
     // Determine the last line with content, so the final newline is always
     // clickable in the editor, even if the last folding range is collapsed
     let mut total_lines = line_starts.len() as u32 - 1;
-    if source.ends_with('\n') && total_lines > 0 {
+    if source.ends_with(['\n', '\r']) && total_lines > 0 {
         total_lines -= 1;
     }
 
@@ -179,17 +216,33 @@ impl LanguageService {
     ///
     /// # Returns
     /// A vector of `FoldingRange` indicating the foldable regions in the CSS code.
-    pub fn get_folding_ranges(&mut self, document: TextDocumentItem) -> Vec<FoldingRange> {
+    pub fn get_folding_ranges(&self, document: TextDocumentItem) -> Vec<FoldingRange> {
         let store_document = self.store.get_or_update_document(document);
-        compute_folding_ranges(&store_document.document, &store_document.line_index)
+        self.folding_ranges(&store_document)
+    }
+
+    /// Same as `get_folding_ranges`, for a document already in the store.
+    ///
+    /// # Arguments
+    /// `uri` - The URI of an open document.
+    pub fn get_folding_ranges_for_uri(&self, uri: &Uri) -> Result<Vec<FoldingRange>, Error> {
+        Ok(self.folding_ranges(&*self.open_entry(uri)?))
+    }
+
+    fn folding_ranges(&self, store_entry: &StoreEntry) -> Vec<FoldingRange> {
+        request!(self, "folding", store_entry);
+        store_entry.cached("folding_ranges", |entry| {
+            compute_folding_ranges(&entry.document, entry.dialect, entry.line_index())
+        })
     }
 }
 
 #[cfg(feature = "wasm")]
 mod wasm_bindings {
     use super::compute_folding_ranges;
-    use crate::converters::line_index::LineIndex;
-    use serde_wasm_bindgen;
+    use crate::service::wasm_bindings::WasmLanguageService;
+    use crate::{converters::line_index::LineIndex, error::Error, parser::CssDialect};
+    use lsp_types::Uri;
     use wasm_bindgen::prelude::*;
 
     #[wasm_bindgen(typescript_custom_section)]
@@ -198,20 +251,39 @@ mod wasm_bindings {
  * Get the folding ranges for the given CSS source code. It supports CSS blocks enclosed in
  * braces, multi-line comments, and regions marked with `#region` and `#endregion` comments.
  * 
- * @param source The CSS source code as a `TextDocument`.
+ * @param source The CSS source code as a `TextDocumentSource`.
  * @returns A list of `FoldingRange` objects indicating the foldable regions in the CSS code.
  */
-export async function get_folding_ranges(source: import("vscode-languageserver-textdocument").TextDocument): Promise<import("vscode-languageserver-types").FoldingRange[]>;
+export async function get_folding_ranges(source: TextDocumentSource): Promise<import("vscode-languageserver-types").FoldingRange[]>;
+
+export interface LanguageService {
+	/**
+	 * Get the folding ranges of an open document, see `get_folding_ranges`.
+	 */
+	getFoldingRanges(uri: string): import("vscode-languageserver-types").FoldingRange[];
+}
 "#;
 
     #[wasm_bindgen(skip_typescript)]
-    pub fn get_folding_ranges(document: JsValue) -> JsValue {
-        let parsed_text_document = crate::wasm_text_document::create_text_document(document);
+    pub fn get_folding_ranges(document: JsValue) -> Result<JsValue, Error> {
+        let parsed_text_document = crate::wasm_text_document::create_text_document(document)?;
         let folding_ranges = compute_folding_ranges(
             &parsed_text_document,
+            CssDialect::from_document(&parsed_text_document),
             &LineIndex::new(&parsed_text_document.text),
         );
 
-        serde_wasm_bindgen::to_value(&folding_ranges).unwrap()
+        crate::wasm_text_document::to_value(&folding_ranges)
+    }
+
+    #[wasm_bindgen(js_class = LanguageService)]
+    impl WasmLanguageService {
+        #[wasm_bindgen(js_name = getFoldingRanges, skip_typescript)]
+        pub fn get_folding_ranges(&self, uri: JsValue) -> Result<JsValue, Error> {
+            let uri: Uri = crate::wasm_text_document::from_value(uri, "uri")?;
+            let folding_ranges = self.language_service.get_folding_ranges_for_uri(&uri)?;
+
+            crate::wasm_text_document::to_value(&folding_ranges)
+        }
     }
 }
@@ -0,0 +1,345 @@
+use biome_css_syntax::CssSyntaxKind;
+use biome_rowan::{TextRange, TextSize};
+use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position, TextDocumentItem, Uri};
+
+use crate::{
+    cancellation::CancellationToken,
+    converters::{from_proto, to_proto},
+    data::DataEntry,
+    descriptors::{descriptor_at_rule, enclosing_prelude},
+    directives::has_unparsed_at_rules,
+    error::Error,
+    instrumentation::request,
+    layers::enclosing_layer,
+    scss::{is_ident_char, ScssConstructKind},
+    selectors::{resolved_selectors, specificity},
+    service::LanguageService,
+    store::StoreEntry,
+    workspace_index::SymbolKind,
+};
+
+impl LanguageService {
+    /// Get the hover for a position of the given document: the value of the SCSS or LESS variable at the position, e.g.
+    /// `$primary: #ff0000` over `color: $primary;`, or the parameters of the SCSS mixin or function, e.g.
+    /// `@mixin button($size)` over `@include button(4px);`. Members declared in another stylesheet are found in the
+    /// stylesheets the document loads with `@use` and `@import`. At-rules show their documentation from the CSS data,
+    /// e.g. over `@scope`, and the names of declarations the documentation of their property or descriptor, e.g. over
+    /// `display` or over the `src` of `@font-face`. Layer names show the order of the layers of the
+    /// document, and the selectors of rules their specificity once resolved against the rules they are nested in, e.g.
+    /// `.card .title:hover` for `&:hover` in `.title` in `.card`, along with the name of their layer if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The original source code as a `TextDocumentItem`.
+    /// * `position` - The position of the cursor.
+    ///
+    /// # Returns
+    ///
+    /// * The hover, `None` if there is nothing to show at the position, or `Error::InvalidPosition` if the position
+    ///   isn't in the document.
+    pub fn get_hover(
+        &self,
+        document: TextDocumentItem,
+        position: Position,
+    ) -> Result<Option<Hover>, Error> {
+        let store_entry = self.store.get_or_update_document(document);
+        self.hover(&store_entry, position)
+    }
+
+    /// Same as `get_hover`, for a document already in the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    /// * `position` - The position of the cursor.
+    pub fn get_hover_for_uri(&self, uri: &Uri, position: Position) -> Result<Option<Hover>, Error> {
+        self.hover(&*self.open_entry(uri)?, position)
+    }
+
+    fn hover(&self, store_entry: &StoreEntry, position: Position) -> Result<Option<Hover>, Error> {
+        request!(self, "hover", store_entry);
+        let encoding = self.encoding_for(store_entry);
+        let offset = from_proto::offset(store_entry.line_index(), position, encoding)?;
+
+        let code = |code: String| format!("```{}\n{code}\n```", store_entry.dialect.language_id());
+        let (value, range) =
+            if let Some((namespace, variable)) = store_entry.scss_variable_at(offset) {
+                // Declarations show their own value, references the value of the declaration they refer to
+                let value = match variable.value {
+                    Some((_, value)) => Some(value),
+                    None => self
+                        .scss_members(store_entry)
+                        .into_iter()
+                        .find(|declaration| {
+                            declaration.kind == ScssConstructKind::Variable
+                                && declaration.namespace == namespace
+                                && declaration.name == variable.name
+                        })
+                        .and_then(|declaration| declaration.value),
+                };
+                let Some(value) = value else {
+                    return Ok(None);
+                };
+                let name = match namespace {
+                    Some(namespace) => format!("{namespace}.{}", variable.name),
+                    None => variable.name,
+                };
+                (code(format!("{name}: {value}")), variable.name_range)
+            } else if let Some(callable) = store_entry.scss_callable_at(offset) {
+                // Mixins and functions show their parameters, the document's own declarations coming first
+                let Some(declaration) =
+                    self.scss_members(store_entry)
+                        .into_iter()
+                        .find(|declaration| {
+                            declaration.kind == callable.kind
+                                && declaration.namespace == callable.namespace
+                                && declaration.name == callable.name
+                        })
+                else {
+                    return Ok(None);
+                };
+                (code(declaration.signature()), callable.name_range)
+            } else if let Some((value, range)) = self.at_rule_hover(store_entry, offset) {
+                (value, range)
+            } else if let Some((value, range)) = self.property_hover(store_entry, offset) {
+                (value, range)
+            } else if let Some((value, range)) = self.layer_hover(store_entry, offset) {
+                (value, range)
+            } else if let Some((value, range)) = selector_hover(store_entry, offset) {
+                (value, range)
+            } else {
+                return Ok(None);
+            };
+
+        Ok(Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: to_proto::range(store_entry.line_index(), range, encoding).ok(),
+        }))
+    }
+
+    /// The documentation of the at-rule whose name is at the position, e.g. `@scope`, from the CSS data.
+    fn at_rule_hover(
+        &self,
+        store_entry: &StoreEntry,
+        offset: TextSize,
+    ) -> Option<(String, TextRange)> {
+        let bytes = store_entry.document.text.as_bytes();
+        let offset = usize::from(offset);
+        let word_start = bytes[..offset]
+            .iter()
+            .rposition(|byte| !is_ident_char(*byte))
+            .map_or(0, |index| index + 1);
+        let at = match bytes.get(offset) {
+            Some(b'@') => offset,
+            _ => word_start.checked_sub(1).filter(|at| bytes[*at] == b'@')?,
+        };
+        let end = bytes[at + 1..]
+            .iter()
+            .position(|byte| !is_ident_char(*byte))
+            .map_or(bytes.len(), |length| at + 1 + length);
+        let range = TextRange::new(TextSize::try_from(at).ok()?, TextSize::try_from(end).ok()?);
+
+        // Skip the `@` of strings and comments, which aren't tokens
+        let token = store_entry
+            .css_tree()
+            .syntax()
+            .token_at_offset(range.start())
+            .right_biased()?;
+        if !token.text_trimmed_range().contains(range.start())
+            || token.kind() == CssSyntaxKind::CSS_STRING_LITERAL
+        {
+            return None;
+        }
+
+        let at_rule = self
+            .css_data()
+            .at_directive(&store_entry.document.text[range])?;
+        Some((at_rule.documentation(), range))
+    }
+
+    /// The documentation of the property whose name is at the position, e.g. `display`, from the CSS data. In the blocks
+    /// of at-rules, the documentation of their descriptor with that name comes first, e.g. `src` in `@font-face`.
+    fn property_hover(
+        &self,
+        store_entry: &StoreEntry,
+        offset: TextSize,
+    ) -> Option<(String, TextRange)> {
+        // The cursor can be at the end of the name, right before its colon
+        let name = store_entry
+            .css_tree()
+            .syntax()
+            .token_at_offset(offset)
+            .filter_map(|token| token.parent())
+            .find(|node| {
+                node.kind() == CssSyntaxKind::CSS_IDENTIFIER
+                    && node.parent().is_some_and(|property| {
+                        property.kind() == CssSyntaxKind::CSS_GENERIC_PROPERTY
+                            && property.first_child().as_ref() == Some(node)
+                    })
+            })?;
+        let text = name.text_trimmed().to_string();
+        if text.starts_with("--") {
+            return None;
+        }
+
+        // The at-rules masked before parsing, e.g. `@view-transition`, are only found in the source
+        let masked_at_rule = || {
+            let before =
+                &store_entry.document.text[..usize::from(name.text_trimmed_range().start())];
+            let at_rule = enclosing_prelude(before)?.split_whitespace().next()?;
+            has_unparsed_at_rules(at_rule).then(|| at_rule.to_ascii_lowercase())
+        };
+        let data = self.css_data();
+        let documentation = descriptor_at_rule(&name)
+            .or_else(masked_at_rule)
+            .and_then(|at_rule| data.descriptor(&at_rule, &text))
+            .or_else(|| data.property(&text))?
+            .documentation();
+        Some((documentation, name.text_trimmed_range()))
+    }
+
+    /// The hover of a layer name, with the order of the layers declared alongside it in the document.
+    fn layer_hover(
+        &self,
+        store_entry: &StoreEntry,
+        offset: TextSize,
+    ) -> Option<(String, TextRange)> {
+        let analysis = store_entry.analysis(&CancellationToken::default()).ok()?;
+        let layers = analysis
+            .symbols
+            .iter()
+            .filter(|symbol| symbol.kind == SymbolKind::Layer)
+            .collect::<Vec<_>>();
+        let layer = layers
+            .iter()
+            .find(|symbol| symbol.range.contains_inclusive(offset))?;
+
+        // Layers are ordered by their first appearance, among the layers nested in the same parent
+        let parent = |name: &str| name.rsplit_once('.').map(|(parent, _)| parent.to_string());
+        let mut order = Vec::new();
+        for symbol in &layers {
+            if parent(&symbol.name) == parent(&layer.name) && !order.contains(&&symbol.name) {
+                order.push(&symbol.name);
+            }
+        }
+        let order = order
+            .iter()
+            .map(|name| format!("`{name}`"))
+            .collect::<Vec<_>>()
+            .join(" < ");
+        Some((
+            format!(
+                "```{}\n@layer {}\n```\nLayer order in this stylesheet: {order}",
+                store_entry.dialect.language_id(),
+                layer.name
+            ),
+            layer.range,
+        ))
+    }
+}
+
+/// The hover of a selector of a style rule, with its resolved selector and its specificity, and the name of the layer
+/// of the rule if any.
+fn selector_hover(store_entry: &StoreEntry, offset: TextSize) -> Option<(String, TextRange)> {
+    let token = store_entry
+        .css_tree()
+        .syntax()
+        .token_at_offset(offset)
+        .right_biased()?;
+    // The outermost selector list is the prelude of the rule, not the arguments of e.g. `:is()`
+    let prelude = token
+        .parent()?
+        .ancestors()
+        .filter(|node| {
+            matches!(
+                node.kind(),
+                CssSyntaxKind::CSS_SELECTOR_LIST | CssSyntaxKind::CSS_RELATIVE_SELECTOR_LIST
+            )
+        })
+        .last()?;
+    let selectors = prelude.children().collect::<Vec<_>>();
+    let index = selectors
+        .iter()
+        .position(|selector| selector.text_trimmed_range().contains_inclusive(offset))?;
+    // Bogus selectors aren't resolved, and the preludes of e.g. `@scope` aren't the selectors of a rule
+    let resolved = resolved_selectors(&prelude.parent()?);
+    if resolved.len() != selectors.len() {
+        return None;
+    }
+
+    let selector = &resolved[index];
+    let mut value = format!(
+        "```{}\n{selector}\n```\n[Selector Specificity](https://developer.mozilla.org/docs/Web/CSS/Specificity): {}",
+        store_entry.dialect.language_id(),
+        specificity(selector)
+    );
+    if let Some(layer) = enclosing_layer(&prelude) {
+        match layer.as_str() {
+            "" => value.push_str("\n\nIn an anonymous layer"),
+            layer => value.push_str(&format!("\n\nIn the layer `{layer}`")),
+        }
+    }
+    Some((value, selectors[index].text_trimmed_range()))
+}
+
+#[cfg(feature = "wasm")]
+mod wasm_bindings {
+    use crate::service::wasm_bindings::WasmLanguageService;
+    use crate::{error::Error, wasm_text_document};
+    use lsp_types::Uri;
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Get the hover for a position of the given source code, e.g. the documentation of a property, the value of an SCSS
+ * variable or the parameters of a mixin.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @param position The position of the cursor.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns A `Hover` object, `null` if there is nothing to show at the position.
+ */
+export async function get_hover(source: TextDocumentSource, position: import("vscode-languageserver-types").Position, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<import("vscode-languageserver-types").Hover | null>;
+
+export interface LanguageService {
+	/**
+	 * Get the hover for a position of an open document, see `get_hover`.
+	 */
+	getHover(uri: string, position: import("vscode-languageserver-types").Position): import("vscode-languageserver-types").Hover | null;
+}
+"#;
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn get_hover(
+        document: JsValue,
+        position: JsValue,
+        encoding: Option<String>,
+    ) -> Result<JsValue, Error> {
+        let parsed_text_document = wasm_text_document::create_text_document(document)?;
+        let position = wasm_text_document::from_value(position, "position")?;
+
+        let hover = wasm_text_document::with_document(
+            parsed_text_document,
+            wasm_text_document::position_encoding(encoding)?,
+            |language_service, uri| language_service.get_hover_for_uri(uri, position),
+        )?;
+
+        wasm_text_document::to_value(&hover)
+    }
+
+    #[wasm_bindgen(js_class = LanguageService)]
+    impl WasmLanguageService {
+        #[wasm_bindgen(js_name = getHover, skip_typescript)]
+        pub fn get_hover(&self, uri: JsValue, position: JsValue) -> Result<JsValue, Error> {
+            let uri: Uri = wasm_text_document::from_value(uri, "uri")?;
+            let position = wasm_text_document::from_value(position, "position")?;
+            let hover = self.language_service.get_hover_for_uri(&uri, position)?;
+
+            wasm_text_document::to_value(&hover)
+        }
+    }
+}
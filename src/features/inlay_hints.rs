@@ -0,0 +1,268 @@
+use biome_css_parser::CssParse;
+use biome_rowan::AstNode;
+use lsp_types::{
+    InlayHint, InlayHintLabel, InlayHintTooltip, MarkupContent, MarkupKind, Range,
+    TextDocumentItem, Uri,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    converters::{from_proto, line_index::LineIndex, to_proto::position, PositionEncoding},
+    custom_properties::{collect_custom_properties, resolve_custom_property, var_reference},
+    error::Error,
+    instrumentation::request,
+    service::LanguageService,
+    store::{DerivedState, StoreEntry},
+};
+
+/// Data attached to each inlay hint, so that `inlayHint/resolve` can find what the hint is about.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InlayHintData {
+    uri: Uri,
+    name: String,
+}
+
+/// Compute lightweight inlay hints showing the resolved value after each `var()` referencing a
+/// custom property declared in the document. The tooltip is left for `resolve_hint` to fill in.
+///
+/// # Arguments
+/// `uri` - The URI of the document, attached to each hint for the resolve step.
+/// `css` - The parsed CSS document.
+/// `line_index` - The precomputed line index from StoreEntry.
+/// `range` - The range of the document to compute hints for.
+/// `encoding` - The position encoding to use for the hints.
+fn compute_inlay_hints(
+    uri: &Uri,
+    css: &CssParse,
+    line_index: &LineIndex,
+    range: Range,
+    encoding: PositionEncoding,
+) -> Result<Vec<InlayHint>, Error> {
+    let range = from_proto::text_range(line_index, range, encoding)?;
+
+    let root = css.tree();
+    let declarations = collect_custom_properties(root.syntax());
+
+    Ok(root
+        .syntax()
+        .descendants()
+        .filter_map(|node| var_reference(&node))
+        .filter(|reference| range.contains_range(reference.range))
+        .filter_map(|reference| {
+            let value = resolve_custom_property(&reference.name, &declarations, &mut Vec::new())
+                .or_else(|| reference.fallback.clone())?;
+
+            Some(InlayHint {
+                position: position(line_index, reference.range.end(), encoding).ok()?,
+                label: InlayHintLabel::String(format!("= {value}")),
+                kind: None,
+                text_edits: None,
+                tooltip: None,
+                padding_left: Some(true),
+                padding_right: None,
+                data: serde_json::to_value(InlayHintData {
+                    uri: uri.clone(),
+                    name: reference.name,
+                })
+                .ok(),
+            })
+        })
+        .collect())
+}
+
+/// Fill in the tooltip of an inlay hint with the full resolution chain of its custom property.
+/// Hints that weren't produced by `compute_inlay_hints` are returned unchanged.
+fn resolve_hint(css: &CssParse, mut hint: InlayHint) -> InlayHint {
+    let Some(data) = hint
+        .data
+        .clone()
+        .and_then(|data| serde_json::from_value::<InlayHintData>(data).ok())
+    else {
+        return hint;
+    };
+
+    let declarations = collect_custom_properties(css.tree().syntax());
+
+    let mut chain = Vec::new();
+    if resolve_custom_property(&data.name, &declarations, &mut chain).is_none() {
+        return hint;
+    }
+
+    let steps = chain
+        .iter()
+        .map(|step| format!("{}: {};", step.name, step.value))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    hint.tooltip = Some(InlayHintTooltip::MarkupContent(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: format!("```css\n{steps}\n```"),
+    }));
+
+    hint
+}
+
+impl LanguageService {
+    /// Get the inlay hints for the given CSS document. A hint is shown after every `var()` whose
+    /// custom property is declared in the document, displaying the value it resolves to.
+    ///
+    /// # Arguments
+    /// `document` - The original CSS source code as a `TextDocumentItem`.
+    /// `range` - The visible range of the document, only hints inside it are returned.
+    ///
+    /// # Returns
+    /// A vector of `InlayHint`, without tooltips. Use `resolve_inlay_hint` to compute them.
+    /// `Error::InvalidRange` if the range isn't inside the document.
+    pub fn get_inlay_hints(
+        &self,
+        document: TextDocumentItem,
+        range: Range,
+    ) -> Result<Vec<InlayHint>, Error> {
+        let store_entry = self
+            .store
+            .get_or_update_document_with(document, DerivedState::ALL);
+        self.inlay_hints(&store_entry, range)
+    }
+
+    /// Same as `get_inlay_hints`, for a document already in the store.
+    ///
+    /// # Arguments
+    /// `uri` - The URI of an open document.
+    /// `range` - The visible range of the document, only hints inside it are returned.
+    pub fn get_inlay_hints_for_uri(
+        &self,
+        uri: &Uri,
+        range: Range,
+    ) -> Result<Vec<InlayHint>, Error> {
+        self.inlay_hints(&*self.open_entry(uri)?, range)
+    }
+
+    fn inlay_hints(&self, store_entry: &StoreEntry, range: Range) -> Result<Vec<InlayHint>, Error> {
+        request!(self, "inlay_hints", store_entry);
+        compute_inlay_hints(
+            &store_entry.document.uri,
+            &store_entry.css_tree(),
+            store_entry.line_index(),
+            range,
+            self.encoding_for(store_entry),
+        )
+    }
+
+    /// Resolve an inlay hint previously returned by `get_inlay_hints`, attaching a tooltip with
+    /// the full chain of custom properties its value was resolved through.
+    ///
+    /// # Arguments
+    /// `hint` - The `InlayHint` to resolve. Its document must still be in the store.
+    ///
+    /// # Returns
+    /// The resolved `InlayHint`, or the hint unchanged if it could not be resolved.
+    pub fn resolve_inlay_hint(&self, hint: InlayHint) -> InlayHint {
+        let Some(uri) = hint
+            .data
+            .clone()
+            .and_then(|data| serde_json::from_value::<InlayHintData>(data).ok())
+            .map(|data| data.uri)
+        else {
+            return hint;
+        };
+
+        match self.store.get(&uri) {
+            Some(store_entry) => resolve_hint(&store_entry.css_tree(), hint),
+            None => hint,
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod wasm_bindings {
+    use super::{compute_inlay_hints, resolve_hint};
+    use crate::service::wasm_bindings::WasmLanguageService;
+    use crate::{
+        converters::line_index::LineIndex,
+        error::Error,
+        parser::{parse_css_with_dialect, CssDialect},
+    };
+    use lsp_types::Uri;
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Get the inlay hints for the given CSS source code. A hint is shown after every `var()` whose
+ * custom property is declared in the document, displaying the value it resolves to.
+ *
+ * @param source The CSS source code as a `TextDocumentSource`.
+ * @param range The visible range of the document, only hints inside it are returned.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns A list of `InlayHint` objects, without tooltips.
+ */
+export async function get_inlay_hints(source: TextDocumentSource, range: import("vscode-languageserver-types").Range, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<import("vscode-languageserver-types").InlayHint[]>;
+
+/**
+ * Resolve an inlay hint previously returned by `get_inlay_hints`, attaching a tooltip with the
+ * full chain of custom properties its value was resolved through.
+ *
+ * @param source The CSS source code as a `TextDocumentSource`.
+ * @param hint The `InlayHint` to resolve.
+ * @returns The resolved `InlayHint`.
+ */
+export async function resolve_inlay_hint(source: TextDocumentSource, hint: import("vscode-languageserver-types").InlayHint): Promise<import("vscode-languageserver-types").InlayHint>;
+
+export interface LanguageService {
+	/**
+	 * Get the inlay hints in a range of an open document, see `get_inlay_hints`.
+	 */
+	getInlayHints(uri: string, range: import("vscode-languageserver-types").Range): import("vscode-languageserver-types").InlayHint[];
+}
+"#;
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn get_inlay_hints(
+        document: JsValue,
+        range: JsValue,
+        encoding: Option<String>,
+    ) -> Result<JsValue, Error> {
+        let parsed_text_document = crate::wasm_text_document::create_text_document(document)?;
+        let range = crate::wasm_text_document::from_value(range, "range")?;
+
+        let inlay_hints = compute_inlay_hints(
+            &parsed_text_document.uri,
+            &parse_css_with_dialect(
+                &parsed_text_document.text,
+                CssDialect::from_document(&parsed_text_document),
+            ),
+            &LineIndex::new(&parsed_text_document.text),
+            range,
+            crate::wasm_text_document::position_encoding(encoding)?,
+        )?;
+
+        crate::wasm_text_document::to_value(&inlay_hints)
+    }
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn resolve_inlay_hint(document: JsValue, hint: JsValue) -> Result<JsValue, Error> {
+        let parsed_text_document = crate::wasm_text_document::create_text_document(document)?;
+        let hint = crate::wasm_text_document::from_value(hint, "hint")?;
+
+        let css = parse_css_with_dialect(
+            &parsed_text_document.text,
+            CssDialect::from_document(&parsed_text_document),
+        );
+        let resolved_hint = resolve_hint(&css, hint);
+
+        crate::wasm_text_document::to_value(&resolved_hint)
+    }
+
+    #[wasm_bindgen(js_class = LanguageService)]
+    impl WasmLanguageService {
+        #[wasm_bindgen(js_name = getInlayHints, skip_typescript)]
+        pub fn get_inlay_hints(&self, uri: JsValue, range: JsValue) -> Result<JsValue, Error> {
+            let uri: Uri = crate::wasm_text_document::from_value(uri, "uri")?;
+            let range = crate::wasm_text_document::from_value(range, "range")?;
+            let inlay_hints = self.language_service.get_inlay_hints_for_uri(&uri, range)?;
+
+            crate::wasm_text_document::to_value(&inlay_hints)
+        }
+    }
+}
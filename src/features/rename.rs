@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+
+use biome_rowan::{TextRange, TextSize};
+use lsp_types::{Position, PrepareRenameResponse, TextDocumentItem, TextEdit, Uri, WorkspaceEdit};
+
+use crate::{
+    cancellation::CancellationToken,
+    converters::{from_proto, to_proto},
+    error::Error,
+    instrumentation::request,
+    keyframes::is_animation_keyword,
+    scss::{is_ident_char, is_ident_start},
+    service::LanguageService,
+    store::StoreEntry,
+    workspace_index::{IndexedSymbol, SymbolKind},
+};
+
+impl LanguageService {
+    /// Check that the symbol at a position of the given document can be renamed, before its new name is typed: a
+    /// custom property, the name of a `@keyframes` rule or a class, e.g. `--primary` in `color: var(--primary)`, `spin`
+    /// in `animation: spin 1s` or `button` in `.button`.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The original source code as a `TextDocumentItem`.
+    /// * `position` - A position in the name of the symbol.
+    ///
+    /// # Returns
+    ///
+    /// * The range of the name of the symbol, with the name as the placeholder of the new name, `None` if there is
+    ///   nothing to rename at the position, or `Error::InvalidPosition` if the position isn't in the document.
+    pub fn prepare_rename(
+        &self,
+        document: TextDocumentItem,
+        position: Position,
+    ) -> Result<Option<PrepareRenameResponse>, Error> {
+        let store_entry = self.store.get_or_update_document(document);
+        self.prepare_rename_range(&store_entry, position)
+    }
+
+    /// Same as `prepare_rename`, for a document already in the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    /// * `position` - A position in the name of the symbol.
+    pub fn prepare_rename_for_uri(
+        &self,
+        uri: &Uri,
+        position: Position,
+    ) -> Result<Option<PrepareRenameResponse>, Error> {
+        self.prepare_rename_range(&*self.open_entry(uri)?, position)
+    }
+
+    /// Rename the symbol at a position of the given document, at each of its occurrences in the document: custom
+    /// properties at their declarations and at the `var()` functions referencing them, e.g. `--primary` in
+    /// `--primary: red` and in `color: var(--primary)`, `@keyframes` rules at their name and at the `animation-name` and
+    /// `animation` properties referencing them, and classes at each of their selectors.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The original source code as a `TextDocumentItem`.
+    /// * `position` - A position in the name of the symbol.
+    /// * `new_name` - The new name of the symbol, as written in place of its name, e.g. `--accent` for a custom property
+    ///   or `card` for the class `.button`.
+    ///
+    /// # Returns
+    ///
+    /// * A WorkspaceEdit replacing every occurrence of the symbol in the document, `Error::InvalidRename` if there is
+    ///   nothing to rename at the position, see `prepare_rename`, or `new_name` isn't a valid name for the symbol, or
+    ///   `Error::InvalidPosition` if the position isn't in the document.
+    pub fn rename(
+        &self,
+        document: TextDocumentItem,
+        position: Position,
+        new_name: &str,
+    ) -> Result<WorkspaceEdit, Error> {
+        let store_entry = self.store.get_or_update_document(document);
+        self.rename_edit(&store_entry, position, new_name)
+    }
+
+    /// Same as `rename`, for a document already in the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    /// * `position` - A position in the name of the symbol.
+    /// * `new_name` - The new name of the symbol.
+    pub fn rename_for_uri(
+        &self,
+        uri: &Uri,
+        position: Position,
+        new_name: &str,
+    ) -> Result<WorkspaceEdit, Error> {
+        self.rename_edit(&*self.open_entry(uri)?, position, new_name)
+    }
+
+    fn prepare_rename_range(
+        &self,
+        store_entry: &StoreEntry,
+        position: Position,
+    ) -> Result<Option<PrepareRenameResponse>, Error> {
+        request!(self, "prepare_rename", store_entry);
+        let encoding = self.encoding_for(store_entry);
+        let offset = from_proto::offset(store_entry.line_index(), position, encoding)?;
+
+        let analysis = store_entry.analysis(&CancellationToken::default())?;
+        let Some(symbol) = renameable_symbol(&analysis.symbols, offset) else {
+            return Ok(None);
+        };
+        let range = name_range(&store_entry.document.text, symbol);
+
+        Ok(Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: to_proto::range(store_entry.line_index(), range, encoding)?,
+            placeholder: store_entry.document.text[range].to_string(),
+        }))
+    }
+
+    fn rename_edit(
+        &self,
+        store_entry: &StoreEntry,
+        position: Position,
+        new_name: &str,
+    ) -> Result<WorkspaceEdit, Error> {
+        request!(self, "rename", store_entry);
+        let encoding = self.encoding_for(store_entry);
+        let offset = from_proto::offset(store_entry.line_index(), position, encoding)?;
+
+        let analysis = store_entry.analysis(&CancellationToken::default())?;
+        let Some(symbol) = renameable_symbol(&analysis.symbols, offset) else {
+            return Err(Error::InvalidRename(
+                "there is nothing to rename at the position".to_string(),
+            ));
+        };
+        if !is_valid_name(symbol.kind, new_name) {
+            return Err(Error::InvalidRename(format!(
+                "`{new_name}` isn't a valid name for `{}`",
+                symbol.kind.display_name(&symbol.name)
+            )));
+        }
+
+        let edits = analysis
+            .symbols
+            .iter()
+            .filter(|occurrence| occurrence.kind == symbol.kind && occurrence.name == symbol.name)
+            .map(|occurrence| {
+                let range = name_range(&store_entry.document.text, occurrence);
+                let range = to_proto::range(store_entry.line_index(), range, encoding)?;
+                Ok(TextEdit::new(range, new_name.to_string()))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let mut changes = HashMap::new();
+        changes.insert(store_entry.document.uri.clone(), edits);
+        Ok(WorkspaceEdit::new(changes))
+    }
+}
+
+/// Find the symbol at an offset that can be renamed, among the symbols of a document.
+fn renameable_symbol(symbols: &[IndexedSymbol], offset: TextSize) -> Option<&IndexedSymbol> {
+    symbols.iter().find(|symbol| {
+        matches!(
+            symbol.kind,
+            SymbolKind::CustomProperty | SymbolKind::Keyframes | SymbolKind::Class
+        ) && symbol.range.contains_inclusive(offset)
+    })
+}
+
+/// The range of the name of a symbol, without the quotes of `@keyframes` rules named by a string, e.g. `"spin"`.
+fn name_range(text: &str, symbol: &IndexedSymbol) -> TextRange {
+    let range = symbol.range;
+    match text[range].as_bytes() {
+        [quote @ (b'"' | b'\''), .., end] if end == quote => TextRange::new(
+            range.start() + TextSize::from(1),
+            range.end() - TextSize::from(1),
+        ),
+        _ => range,
+    }
+}
+
+/// Whether `name` can replace the name of a symbol of this kind, as an identifier without escapes, e.g. `--primary` for
+/// a custom property, where `--` alone is reserved, and not a keyword of `animation` for a `@keyframes` rule.
+fn is_valid_name(kind: SymbolKind, name: &str) -> bool {
+    let bytes = name.as_bytes();
+    let is_identifier = match bytes {
+        [b'-', b'-', ..] => true,
+        [b'-', second, ..] => is_ident_start(*second),
+        [first, ..] => is_ident_start(*first),
+        [] => false,
+    } && name != "-"
+        && bytes.iter().copied().all(is_ident_char);
+
+    match kind {
+        SymbolKind::CustomProperty => is_identifier && name.len() > 2 && name.starts_with("--"),
+        SymbolKind::Keyframes => is_identifier && !is_animation_keyword(name),
+        _ => is_identifier,
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod wasm_bindings {
+    use crate::service::wasm_bindings::WasmLanguageService;
+    use crate::{error::Error, wasm_text_document};
+    use lsp_types::Uri;
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Check that the symbol at a position of the given source code can be renamed, e.g. a custom property, the name of a
+ * `@keyframes` rule or a class.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @param position A position in the name of the symbol.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns The range of the name and its text as the placeholder of the new name, `null` if there is nothing to rename.
+ */
+export async function prepare_rename(source: TextDocumentSource, position: import("vscode-languageserver-types").Position, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<{ range: import("vscode-languageserver-types").Range; placeholder: string } | null>;
+
+/**
+ * Rename the symbol at a position of the given source code, at each of its occurrences, e.g. a custom property at its
+ * declarations and `var()` references.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @param position A position in the name of the symbol.
+ * @param newName The new name of the symbol, e.g. `--accent` for a custom property.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns A `WorkspaceEdit` object. Throws if there is nothing to rename at the position or the new name is invalid.
+ */
+export async function rename(source: TextDocumentSource, position: import("vscode-languageserver-types").Position, newName: string, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<import("vscode-languageserver-types").WorkspaceEdit>;
+
+export interface LanguageService {
+	/**
+	 * Check that the symbol at a position of an open document can be renamed, see `prepare_rename`.
+	 */
+	prepareRename(uri: string, position: import("vscode-languageserver-types").Position): { range: import("vscode-languageserver-types").Range; placeholder: string } | null;
+	/**
+	 * Rename the symbol at a position of an open document, see `rename`.
+	 */
+	rename(uri: string, position: import("vscode-languageserver-types").Position, newName: string): import("vscode-languageserver-types").WorkspaceEdit;
+}
+"#;
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn prepare_rename(
+        document: JsValue,
+        position: JsValue,
+        encoding: Option<String>,
+    ) -> Result<JsValue, Error> {
+        let parsed_text_document = wasm_text_document::create_text_document(document)?;
+        let position = wasm_text_document::from_value(position, "position")?;
+
+        let response = wasm_text_document::with_document(
+            parsed_text_document,
+            wasm_text_document::position_encoding(encoding)?,
+            |language_service, uri| language_service.prepare_rename_for_uri(uri, position),
+        )?;
+
+        wasm_text_document::to_value(&response)
+    }
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn rename(
+        document: JsValue,
+        position: JsValue,
+        new_name: String,
+        encoding: Option<String>,
+    ) -> Result<JsValue, Error> {
+        let parsed_text_document = wasm_text_document::create_text_document(document)?;
+        let position = wasm_text_document::from_value(position, "position")?;
+
+        let edit = wasm_text_document::with_document(
+            parsed_text_document,
+            wasm_text_document::position_encoding(encoding)?,
+            |language_service, uri| language_service.rename_for_uri(uri, position, &new_name),
+        )?;
+
+        wasm_text_document::to_value(&edit)
+    }
+
+    #[wasm_bindgen(js_class = LanguageService)]
+    impl WasmLanguageService {
+        #[wasm_bindgen(js_name = prepareRename, skip_typescript)]
+        pub fn prepare_rename(&self, uri: JsValue, position: JsValue) -> Result<JsValue, Error> {
+            let uri: Uri = wasm_text_document::from_value(uri, "uri")?;
+            let position = wasm_text_document::from_value(position, "position")?;
+            let response = self
+                .language_service
+                .prepare_rename_for_uri(&uri, position)?;
+
+            wasm_text_document::to_value(&response)
+        }
+
+        #[wasm_bindgen(js_name = rename, skip_typescript)]
+        pub fn rename(
+            &self,
+            uri: JsValue,
+            position: JsValue,
+            new_name: String,
+        ) -> Result<JsValue, Error> {
+            let uri: Uri = wasm_text_document::from_value(uri, "uri")?;
+            let position = wasm_text_document::from_value(position, "position")?;
+            let edit = self
+                .language_service
+                .rename_for_uri(&uri, position, &new_name)?;
+
+            wasm_text_document::to_value(&edit)
+        }
+    }
+}
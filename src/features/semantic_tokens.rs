@@ -0,0 +1,382 @@
+use biome_rowan::TextSize;
+use lsp_types::{
+    SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens, SemanticTokensLegend,
+    TextDocumentItem, Uri,
+};
+
+use crate::{
+    converters::{line_index::LineIndex, to_proto::position, PositionEncoding},
+    error::Error,
+    instrumentation::request,
+    parser::CssDialect,
+    service::LanguageService,
+    store::StoreEntry,
+};
+
+const VARIABLE: u32 = 0;
+const FUNCTION: u32 = 1;
+const CLASS: u32 = 2;
+const OPERATOR: u32 = 3;
+
+const DECLARATION: u32 = 1 << 0;
+
+/// The legend of the semantic tokens returned by `get_semantic_tokens`, to be sent to the client
+/// as part of the server capabilities.
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: vec![
+            SemanticTokenType::VARIABLE,
+            SemanticTokenType::FUNCTION,
+            SemanticTokenType::CLASS,
+            SemanticTokenType::OPERATOR,
+        ],
+        token_modifiers: vec![SemanticTokenModifier::DECLARATION],
+    }
+}
+
+struct RawToken {
+    start: usize,
+    end: usize,
+    token_type: u32,
+    modifiers: u32,
+}
+
+fn is_ident_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' || !byte.is_ascii()
+}
+
+fn is_ident_start(byte: Option<&u8>) -> bool {
+    byte.is_some_and(|&byte| byte.is_ascii_alphabetic() || byte == b'_' || !byte.is_ascii())
+}
+
+fn scan_ident(bytes: &[u8], start: usize) -> usize {
+    let mut end = start;
+    while end < bytes.len() && is_ident_char(bytes[end]) {
+        end += 1;
+    }
+    end
+}
+
+fn skip_whitespace(bytes: &[u8], start: usize) -> usize {
+    let mut end = start;
+    while end < bytes.len() && bytes[end].is_ascii_whitespace() {
+        end += 1;
+    }
+    end
+}
+
+fn declaration_modifier(bytes: &[u8], end: usize) -> u32 {
+    if bytes.get(skip_whitespace(bytes, end)) == Some(&b':') {
+        DECLARATION
+    } else {
+        0
+    }
+}
+
+/// Scan the source for the constructs we classify: custom properties for every document and, for
+/// SCSS documents, `$variables`, mixin and function names, placeholder selectors and `#{…}`
+/// interpolation delimiters. This is done lexically, as the parser does not support SCSS.
+fn scan_tokens(source: &str, scss: bool) -> Vec<RawToken> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    // For each open brace, whether it opened an interpolation or a regular block
+    let mut braces = Vec::new();
+    let mut in_extend = false;
+
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = source[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| i + 2 + end + 2);
+                continue;
+            }
+            b'/' if scss && bytes.get(i + 1) == Some(&b'/') => {
+                i = source[i..].find('\n').map_or(bytes.len(), |end| i + end);
+                continue;
+            }
+            quote @ (b'"' | b'\'') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote && bytes[i] != b'\n' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            b'#' if scss && bytes.get(i + 1) == Some(&b'{') => {
+                tokens.push(RawToken {
+                    start: i,
+                    end: i + 2,
+                    token_type: OPERATOR,
+                    modifiers: 0,
+                });
+                braces.push(true);
+                i += 2;
+                continue;
+            }
+            b'{' => {
+                braces.push(false);
+                in_extend = false;
+            }
+            b'}' => {
+                if braces.pop() == Some(true) {
+                    tokens.push(RawToken {
+                        start: i,
+                        end: i + 1,
+                        token_type: OPERATOR,
+                        modifiers: 0,
+                    });
+                } else {
+                    in_extend = false;
+                }
+            }
+            b';' => in_extend = false,
+            b'$' if scss && is_ident_start(bytes.get(i + 1)) => {
+                let end = scan_ident(bytes, i + 1);
+                tokens.push(RawToken {
+                    start: i,
+                    end,
+                    token_type: VARIABLE,
+                    modifiers: declaration_modifier(bytes, end),
+                });
+                i = end;
+                continue;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                let end = scan_ident(bytes, i);
+                if end > i + 2 {
+                    tokens.push(RawToken {
+                        start: i,
+                        end,
+                        token_type: VARIABLE,
+                        modifiers: declaration_modifier(bytes, end),
+                    });
+                }
+                i = end;
+                continue;
+            }
+            // A `%` directly after a number is a percentage, not a placeholder selector
+            b'%' if scss
+                && is_ident_start(bytes.get(i + 1))
+                && !(i > 0 && is_ident_char(bytes[i - 1])) =>
+            {
+                let end = scan_ident(bytes, i + 1);
+                tokens.push(RawToken {
+                    start: i,
+                    end,
+                    token_type: CLASS,
+                    modifiers: if in_extend { 0 } else { DECLARATION },
+                });
+                i = end;
+                continue;
+            }
+            b'@' => {
+                let keyword_end = scan_ident(bytes, i + 1);
+                let keyword = &source[i + 1..keyword_end];
+                i = keyword_end;
+
+                in_extend = scss && keyword == "extend";
+
+                if scss && matches!(keyword, "mixin" | "function" | "include") {
+                    let name_start = skip_whitespace(bytes, keyword_end);
+                    let name_end = scan_ident(bytes, name_start);
+                    if name_end > name_start {
+                        tokens.push(RawToken {
+                            start: name_start,
+                            end: name_end,
+                            token_type: FUNCTION,
+                            modifiers: if keyword == "include" { 0 } else { DECLARATION },
+                        });
+                    }
+                    i = name_end;
+                }
+                continue;
+            }
+            byte if is_ident_char(byte) => {
+                // Skip whole identifiers, so that `--` or `%` in the middle of one is ignored
+                i = scan_ident(bytes, i);
+                continue;
+            }
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    tokens
+}
+
+/// Compute the semantic tokens for the given document, relative-encoded as required by the LSP.
+///
+/// # Arguments
+/// `document` - The original source code as a `TextDocumentItem`.
+/// `dialect` - The dialect of the document, SCSS constructs are only classified in SCSS documents.
+/// `line_index` - The precomputed line index from StoreEntry.
+/// `encoding` - The position encoding to use for the tokens.
+fn compute_semantic_tokens(
+    document: &TextDocumentItem,
+    dialect: CssDialect,
+    line_index: &LineIndex,
+    encoding: PositionEncoding,
+) -> SemanticTokens {
+    let scss = dialect == CssDialect::Scss;
+
+    let tokens = scan_tokens(&document.text, scss);
+    let to_positions = |token: &RawToken| {
+        Some((
+            position(line_index, TextSize::from(token.start as u32), encoding).ok()?,
+            position(line_index, TextSize::from(token.end as u32), encoding).ok()?,
+        ))
+    };
+
+    // Converting offsets to positions is the costly part, the scan itself can't be split
+    #[cfg(feature = "parallel")]
+    let positions = if document.text.len() > crate::PARALLEL_THRESHOLD {
+        use rayon::prelude::*;
+        tokens.par_iter().map(to_positions).collect::<Vec<_>>()
+    } else {
+        tokens.iter().map(to_positions).collect::<Vec<_>>()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let positions = tokens.iter().map(to_positions).collect::<Vec<_>>();
+
+    let mut data = Vec::new();
+    let mut previous_line = 0;
+    let mut previous_start = 0;
+
+    for (token, positions) in tokens.iter().zip(positions) {
+        let Some((start, end)) = positions else {
+            continue;
+        };
+
+        // Multi-line tokens are not supported by every client, and none of ours can span lines
+        if start.line != end.line {
+            continue;
+        }
+
+        let delta_line = start.line - previous_line;
+        let delta_start = if delta_line == 0 {
+            start.character - previous_start
+        } else {
+            start.character
+        };
+
+        data.push(SemanticToken {
+            delta_line,
+            delta_start,
+            length: end.character - start.character,
+            token_type: token.token_type,
+            token_modifiers_bitset: token.modifiers,
+        });
+
+        previous_line = start.line;
+        previous_start = start.character;
+    }
+
+    SemanticTokens {
+        result_id: None,
+        data,
+    }
+}
+
+impl LanguageService {
+    /// Get the semantic tokens for the given document. Custom properties are classified in every
+    /// document, while SCSS documents additionally get `$variables`, `@mixin`/`@function`/`@include`
+    /// names, placeholder selectors and interpolation delimiters classified.
+    ///
+    /// # Arguments
+    /// `document` - The original source code as a `TextDocumentItem`.
+    ///
+    /// # Returns
+    /// The `SemanticTokens` of the document, using the legend from `semantic_tokens_legend`.
+    pub fn get_semantic_tokens(&self, document: TextDocumentItem) -> SemanticTokens {
+        let store_entry = self.store.get_or_update_document(document);
+        self.semantic_tokens(&store_entry)
+    }
+
+    /// Same as `get_semantic_tokens`, for a document already in the store.
+    ///
+    /// # Arguments
+    /// `uri` - The URI of an open document.
+    pub fn get_semantic_tokens_for_uri(&self, uri: &Uri) -> Result<SemanticTokens, Error> {
+        Ok(self.semantic_tokens(&*self.open_entry(uri)?))
+    }
+
+    fn semantic_tokens(&self, store_entry: &StoreEntry) -> SemanticTokens {
+        request!(self, "semantic_tokens", store_entry);
+        store_entry.cached("semantic_tokens", |entry| {
+            compute_semantic_tokens(
+                &entry.document,
+                entry.dialect,
+                entry.line_index(),
+                self.encoding_for(entry),
+            )
+        })
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod wasm_bindings {
+    use super::compute_semantic_tokens;
+    use crate::service::wasm_bindings::WasmLanguageService;
+    use crate::{converters::line_index::LineIndex, error::Error, parser::CssDialect};
+    use lsp_types::Uri;
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Get the semantic tokens for the given source code. Custom properties are classified in every
+ * document, while SCSS documents additionally get `$variables`, `@mixin`/`@function`/`@include`
+ * names, placeholder selectors and interpolation delimiters classified.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns The `SemanticTokens` of the document, using the legend from `get_semantic_tokens_legend`.
+ */
+export async function get_semantic_tokens(source: TextDocumentSource, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<import("vscode-languageserver-types").SemanticTokens>;
+
+/**
+ * Get the legend of the semantic tokens returned by `get_semantic_tokens`.
+ */
+export async function get_semantic_tokens_legend(): Promise<import("vscode-languageserver-types").SemanticTokensLegend>;
+
+export interface LanguageService {
+	/**
+	 * Get the semantic tokens of an open document, see `get_semantic_tokens`.
+	 */
+	getSemanticTokens(uri: string): import("vscode-languageserver-types").SemanticTokens;
+}
+"#;
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn get_semantic_tokens(
+        document: JsValue,
+        encoding: Option<String>,
+    ) -> Result<JsValue, Error> {
+        let parsed_text_document = crate::wasm_text_document::create_text_document(document)?;
+        let semantic_tokens = compute_semantic_tokens(
+            &parsed_text_document,
+            CssDialect::from_document(&parsed_text_document),
+            &LineIndex::new(&parsed_text_document.text),
+            crate::wasm_text_document::position_encoding(encoding)?,
+        );
+
+        crate::wasm_text_document::to_value(&semantic_tokens)
+    }
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn get_semantic_tokens_legend() -> Result<JsValue, Error> {
+        crate::wasm_text_document::to_value(&super::semantic_tokens_legend())
+    }
+
+    #[wasm_bindgen(js_class = LanguageService)]
+    impl WasmLanguageService {
+        #[wasm_bindgen(js_name = getSemanticTokens, skip_typescript)]
+        pub fn get_semantic_tokens(&self, uri: JsValue) -> Result<JsValue, Error> {
+            let uri: Uri = crate::wasm_text_document::from_value(uri, "uri")?;
+            let semantic_tokens = self.language_service.get_semantic_tokens_for_uri(&uri)?;
+
+            crate::wasm_text_document::to_value(&semantic_tokens)
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use biome_rowan::TextSize;
+use lsp_types::{
+    ParameterInformation, ParameterLabel, Position, SignatureHelp, SignatureInformation,
+    TextDocumentItem, Uri,
+};
+
+use crate::{
+    converters::from_proto, error::Error, instrumentation::request, service::LanguageService,
+    store::StoreEntry,
+};
+
+impl LanguageService {
+    /// Get the signature help for a position of the given document: the parameters of the SCSS mixin included or the
+    /// function called around the position, e.g. `@mixin button($size, $color: red)` in `@include button(4px, |)`,
+    /// with the parameter of the argument at the position as the active one.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The original source code as a `TextDocumentItem`.
+    /// * `position` - The position of the cursor.
+    ///
+    /// # Returns
+    ///
+    /// * The signature help, `None` if the position isn't in the arguments of a known mixin or function, or
+    ///   `Error::InvalidPosition` if the position isn't in the document.
+    pub fn get_signature_help(
+        &self,
+        document: TextDocumentItem,
+        position: Position,
+    ) -> Result<Option<SignatureHelp>, Error> {
+        let store_entry = self.store.get_or_update_document(document);
+        self.signature_help(&store_entry, position)
+    }
+
+    /// Same as `get_signature_help`, for a document already in the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    /// * `position` - The position of the cursor.
+    pub fn get_signature_help_for_uri(
+        &self,
+        uri: &Uri,
+        position: Position,
+    ) -> Result<Option<SignatureHelp>, Error> {
+        self.signature_help(&*self.open_entry(uri)?, position)
+    }
+
+    fn signature_help(
+        &self,
+        store_entry: &StoreEntry,
+        position: Position,
+    ) -> Result<Option<SignatureHelp>, Error> {
+        request!(self, "signature_help", store_entry);
+        let offset = from_proto::offset(
+            store_entry.line_index(),
+            position,
+            self.encoding_for(store_entry),
+        )?;
+
+        let Some((open_parenthesis, active_parameter)) =
+            arguments_at(&store_entry.document.text, usize::from(offset))
+        else {
+            return Ok(None);
+        };
+        let Some(callable) = store_entry.scss_callable_at(TextSize::from(open_parenthesis as u32))
+        else {
+            return Ok(None);
+        };
+        let Some(declaration) = self
+            .scss_members(store_entry)
+            .into_iter()
+            .find(|declaration| {
+                declaration.kind == callable.kind
+                    && declaration.namespace == callable.namespace
+                    && declaration.name == callable.name
+            })
+        else {
+            return Ok(None);
+        };
+
+        let label = declaration.signature();
+        let parameters = declaration.parameters.as_deref().map(|parameters| {
+            let parameters_start = label.len() - parameters.len();
+            split_parameters(parameters)
+                .into_iter()
+                .map(|(start, end)| ParameterInformation {
+                    label: ParameterLabel::LabelOffsets([
+                        utf16_len(&label[..parameters_start + start]),
+                        utf16_len(&label[..parameters_start + end]),
+                    ]),
+                    documentation: None,
+                })
+                .collect()
+        });
+
+        Ok(Some(SignatureHelp {
+            signatures: vec![SignatureInformation {
+                label,
+                documentation: None,
+                parameters,
+                active_parameter: None,
+            }],
+            active_signature: Some(0),
+            active_parameter: Some(active_parameter),
+        }))
+    }
+}
+
+/// Find the arguments an offset is in: the offset of their opening parenthesis, and the index of the argument at the
+/// offset. Arguments don't span several statements, so the search stops at the start of the statement.
+fn arguments_at(text: &str, offset: usize) -> Option<(usize, u32)> {
+    let mut depth = 0;
+    let mut argument = 0;
+    for (index, byte) in text.as_bytes()[..offset.min(text.len())]
+        .iter()
+        .enumerate()
+        .rev()
+    {
+        match byte {
+            b')' => depth += 1,
+            b'(' if depth == 0 => return Some((index, argument)),
+            b'(' => depth -= 1,
+            b',' if depth == 0 => argument += 1,
+            b';' | b'{' | b'}' if depth == 0 => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Get the ranges of each parameter in parameters with their parentheses, e.g. `$size` and `$color: red` in
+/// `($size, $color: red)`.
+fn split_parameters(parameters: &str) -> Vec<(usize, usize)> {
+    let inner_end = parameters
+        .strip_suffix(')')
+        .map_or(parameters.len(), str::len);
+    let mut ranges = Vec::new();
+    let mut depth = 0;
+    let mut start = 1;
+    for (index, byte) in parameters.bytes().enumerate().take(inner_end).skip(1) {
+        match byte {
+            b'(' => depth += 1,
+            b')' => depth -= 1,
+            b',' if depth == 0 => {
+                ranges.push((start, index));
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    ranges.push((start, inner_end));
+
+    ranges
+        .into_iter()
+        .filter_map(|(start, end)| {
+            let parameter = &parameters[start..end];
+            let trimmed = parameter.trim();
+            let start = start + (parameter.len() - parameter.trim_start().len());
+            (!trimmed.is_empty()).then_some((start, start + trimmed.len()))
+        })
+        .collect()
+}
+
+fn utf16_len(text: &str) -> u32 {
+    text.encode_utf16().count() as u32
+}
+
+#[cfg(feature = "wasm")]
+mod wasm_bindings {
+    use crate::service::wasm_bindings::WasmLanguageService;
+    use crate::{error::Error, wasm_text_document};
+    use lsp_types::Uri;
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Get the signature help for a position of the given source code, e.g. the parameters of an SCSS mixin in the
+ * arguments of its `@include`.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @param position The position of the cursor.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns A `SignatureHelp` object, `null` if the position isn't in the arguments of a known mixin or function.
+ */
+export async function get_signature_help(source: TextDocumentSource, position: import("vscode-languageserver-types").Position, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<import("vscode-languageserver-types").SignatureHelp | null>;
+
+export interface LanguageService {
+	/**
+	 * Get the signature help for a position of an open document, see `get_signature_help`.
+	 */
+	getSignatureHelp(uri: string, position: import("vscode-languageserver-types").Position): import("vscode-languageserver-types").SignatureHelp | null;
+}
+"#;
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn get_signature_help(
+        document: JsValue,
+        position: JsValue,
+        encoding: Option<String>,
+    ) -> Result<JsValue, Error> {
+        let parsed_text_document = wasm_text_document::create_text_document(document)?;
+        let position = wasm_text_document::from_value(position, "position")?;
+
+        let signature_help = wasm_text_document::with_document(
+            parsed_text_document,
+            wasm_text_document::position_encoding(encoding)?,
+            |language_service, uri| language_service.get_signature_help_for_uri(uri, position),
+        )?;
+
+        wasm_text_document::to_value(&signature_help)
+    }
+
+    #[wasm_bindgen(js_class = LanguageService)]
+    impl WasmLanguageService {
+        #[wasm_bindgen(js_name = getSignatureHelp, skip_typescript)]
+        pub fn get_signature_help(
+            &self,
+            uri: JsValue,
+            position: JsValue,
+        ) -> Result<JsValue, Error> {
+            let uri: Uri = wasm_text_document::from_value(uri, "uri")?;
+            let position = wasm_text_document::from_value(position, "position")?;
+            let signature_help = self
+                .language_service
+                .get_signature_help_for_uri(&uri, position)?;
+
+            wasm_text_document::to_value(&signature_help)
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use std::sync::Arc;
+
+use lsp_types::{Location, OneOf, Uri, WorkspaceSymbol};
+
+use crate::{
+    converters::{to_proto::range, PositionEncoding},
+    error::Error,
+    instrumentation::span,
+    service::LanguageService,
+    workspace_index::{IndexedDocument, SymbolKind},
+};
+
+fn lsp_symbol_kind(kind: SymbolKind) -> lsp_types::SymbolKind {
+    match kind {
+        SymbolKind::CustomProperty | SymbolKind::Variable => lsp_types::SymbolKind::VARIABLE,
+        SymbolKind::Class
+        | SymbolKind::Id
+        | SymbolKind::Keyframes
+        | SymbolKind::CounterStyle
+        | SymbolKind::Placeholder => lsp_types::SymbolKind::CLASS,
+        SymbolKind::Layer => lsp_types::SymbolKind::NAMESPACE,
+        SymbolKind::Container => lsp_types::SymbolKind::MODULE,
+        SymbolKind::Mixin | SymbolKind::Function => lsp_types::SymbolKind::FUNCTION,
+    }
+}
+
+/// Compute the symbols declared in the given indexed documents whose name matches the query.
+///
+/// # Arguments
+/// `documents` - The documents of the workspace, as indexed by the WorkspaceIndex.
+/// `query` - A case-insensitive query, an empty query matches every symbol.
+/// `encoding` - The position encoding to use for the locations of the symbols, unless their document was opened with
+/// its own.
+fn compute_workspace_symbols(
+    documents: &[Arc<IndexedDocument>],
+    query: &str,
+    encoding: PositionEncoding,
+) -> Vec<WorkspaceSymbol> {
+    let query = &query.to_lowercase();
+
+    let mut symbols = documents
+        .iter()
+        .flat_map(|document| {
+            let encoding = document
+                .entry()
+                .and_then(|entry| entry.encoding)
+                .unwrap_or(encoding);
+
+            document
+                .symbols
+                .iter()
+                .filter(|symbol| symbol.is_declaration)
+                .filter_map(move |symbol| {
+                    let name = symbol.kind.display_name(&symbol.name);
+                    if !name.to_lowercase().contains(query) {
+                        return None;
+                    }
+
+                    Some(WorkspaceSymbol {
+                        name,
+                        kind: lsp_symbol_kind(symbol.kind),
+                        tags: None,
+                        container_name: None,
+                        location: OneOf::Left(Location {
+                            uri: document.uri.clone(),
+                            range: range(document.line_index(), symbol.range, encoding).ok()?,
+                        }),
+                        data: None,
+                    })
+                })
+        })
+        .collect::<Vec<_>>();
+
+    // Documents are indexed in no particular order, sort the symbols to get stable results
+    symbols.sort_by(|a, b| match (&a.location, &b.location) {
+        (OneOf::Left(a), OneOf::Left(b)) => a
+            .uri
+            .as_str()
+            .cmp(b.uri.as_str())
+            .then(a.range.start.cmp(&b.range.start)),
+        _ => std::cmp::Ordering::Equal,
+    });
+
+    symbols
+}
+
+impl LanguageService {
+    /// Get the symbols declared across every document in the store: custom properties, classes, ids, keyframes,
+    /// layers, container names and counter styles, plus the variables, mixins, functions and placeholder selectors of
+    /// SCSS documents.
+    ///
+    /// # Arguments
+    /// `query` - A case-insensitive query, only symbols whose name contains it are returned.
+    ///
+    /// # Returns
+    /// A vector of `WorkspaceSymbol`, sorted by document and position.
+    pub fn get_workspace_symbols(&self, query: &str) -> Vec<WorkspaceSymbol> {
+        span!("workspace_symbols", query = query);
+        compute_workspace_symbols(&self.workspace_index().documents(), query, self.encoding)
+    }
+
+    /// Same as `get_workspace_symbols`, only returning the symbols declared in one document.
+    ///
+    /// # Arguments
+    /// `uri` - The URI of an open document.
+    ///
+    /// # Returns
+    /// A vector of `WorkspaceSymbol`, sorted by position, or `Error::DocumentNotOpen`.
+    pub fn get_workspace_symbols_for_uri(&self, uri: &Uri) -> Result<Vec<WorkspaceSymbol>, Error> {
+        span!("workspace_symbols", uri = uri.as_str());
+        self.open_entry(uri)?;
+
+        Ok(compute_workspace_symbols(
+            &self
+                .workspace_index()
+                .document(uri)
+                .into_iter()
+                .collect::<Vec<_>>(),
+            "",
+            self.encoding,
+        ))
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod wasm_bindings {
+    use super::compute_workspace_symbols;
+    use crate::{
+        cancellation::CancellationToken, error::Error,
+        file_system::wasm_bindings::JsFileSystemProvider, store::DocumentStore,
+        workspace_index::WorkspaceIndex,
+    };
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Get the symbols declared across the given CSS documents: custom properties, classes, ids,
+ * keyframes, layers, container names and counter styles, plus the variables, mixins, functions
+ * and placeholder selectors of SCSS documents.
+ *
+ * @param sources The CSS source code of every document of the workspace, as `TextDocumentSource`s.
+ * @param query A case-insensitive query, only symbols whose name contains it are returned.
+ * @param fileSystem Optional access to the file system, to include the stylesheets of `folder`.
+ * @param folder The URI of the workspace folder whose stylesheets should be included.
+ * @param persistedIndex An index of `folder` returned by `index_workspace_folder`, only the files modified since are read again.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns A list of `WorkspaceSymbol` objects, sorted by document and position.
+ */
+export async function get_workspace_symbols(sources: TextDocumentSource[], query: string, fileSystem?: FileSystemProvider, folder?: string, persistedIndex?: Uint8Array, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<import("vscode-languageserver-types").WorkspaceSymbol[]>;
+
+/**
+ * Index the stylesheets of a workspace folder, to be persisted by the caller (e.g. in the extension's storage) and
+ * passed to `get_workspace_symbols` or back to this function on the next launch, instead of indexing the whole folder again.
+ *
+ * @param fileSystem Access to the file system.
+ * @param folder The URI of the workspace folder.
+ * @param persistedIndex A previous index of the folder, only the files modified since are read again.
+ * @returns The updated index of the folder.
+ */
+export async function index_workspace_folder(fileSystem: FileSystemProvider, folder: string, persistedIndex?: Uint8Array): Promise<Uint8Array>;
+"#;
+
+    fn load_folder(
+        file_system: Option<JsFileSystemProvider>,
+        folder: Option<String>,
+        persisted_index: Option<Vec<u8>>,
+    ) -> WorkspaceIndex {
+        let index = WorkspaceIndex::new();
+        if let Some(persisted_index) = persisted_index {
+            // An invalid index is ignored, the folder is then indexed from scratch
+            let _ = index.load(&persisted_index);
+        }
+
+        if let (Some(file_system), Some(folder)) =
+            (file_system, folder.and_then(|folder| folder.parse().ok()))
+        {
+            // Nothing can cancel the indexing here
+            let _ = index.index_folder(&file_system, &folder, &CancellationToken::default());
+        }
+
+        index
+    }
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn get_workspace_symbols(
+        documents: JsValue,
+        query: String,
+        file_system: Option<JsFileSystemProvider>,
+        folder: Option<String>,
+        persisted_index: Option<Vec<u8>>,
+        encoding: Option<String>,
+    ) -> Result<JsValue, Error> {
+        let store = DocumentStore::new();
+        for document in crate::wasm_text_document::create_text_documents(documents)? {
+            store.insert(document);
+        }
+
+        let index = load_folder(file_system, folder, persisted_index);
+        index.sync(&store);
+
+        let symbols = compute_workspace_symbols(
+            &index.documents(),
+            &query,
+            crate::wasm_text_document::position_encoding(encoding)?,
+        );
+
+        crate::wasm_text_document::to_value(&symbols)
+    }
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn index_workspace_folder(
+        file_system: JsFileSystemProvider,
+        folder: String,
+        persisted_index: Option<Vec<u8>>,
+    ) -> Vec<u8> {
+        load_folder(Some(file_system), Some(folder), persisted_index).save()
+    }
+}
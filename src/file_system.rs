@@ -0,0 +1,183 @@
+//! Access to files that aren't open in the editor, for the features that need to look at the rest of the workspace
+//! (`@import` resolution, path completion, document links, the workspace index, etc.)
+//!
+//! csslsrs never accesses the file system directly, as it may run in environments without one (e.g. the browser).
+//! Instead, the host provides an implementation of `FileSystemProvider`, or of `AsyncFileSystemProvider` when files
+//! can only be read asynchronously.
+
+use std::{future::Future, io, pin::Pin};
+
+use lsp_types::Uri;
+
+/// The type of an entry of the file system, using the same values as VS Code's `FileType`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FileType {
+    Unknown,
+    File,
+    Directory,
+    SymbolicLink,
+}
+
+impl FileType {
+    /// Convert the numeric value used by VS Code's `FileType` to a FileType.
+    pub fn from_vscode(value: u32) -> Self {
+        match value {
+            1 => FileType::File,
+            2 => FileType::Directory,
+            64 => FileType::SymbolicLink,
+            _ => FileType::Unknown,
+        }
+    }
+}
+
+/// Metadata about an entry of the file system.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileStat {
+    pub file_type: FileType,
+    /// The size of the entry, in bytes.
+    pub size: u64,
+    /// The last modification time of the entry, in milliseconds since the UNIX epoch.
+    pub mtime: u64,
+}
+
+/// A synchronous provider of access to the file system of the host.
+pub trait FileSystemProvider: Send + Sync {
+    /// Read the content of the file at the given URI.
+    fn read_file(&self, uri: &Uri) -> io::Result<String>;
+
+    /// List the entries of the directory at the given URI, as pairs of their name and type.
+    fn read_directory(&self, uri: &Uri) -> io::Result<Vec<(String, FileType)>>;
+
+    /// Get metadata about the entry at the given URI.
+    fn stat(&self, uri: &Uri) -> io::Result<FileStat>;
+}
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// An asynchronous provider of access to the file system of the host, e.g. backed by JavaScript promises.
+///
+/// Every `FileSystemProvider` is also an `AsyncFileSystemProvider` whose futures are immediately ready.
+pub trait AsyncFileSystemProvider {
+    /// Read the content of the file at the given URI.
+    fn read_file<'a>(&'a self, uri: &'a Uri) -> BoxFuture<'a, io::Result<String>>;
+
+    /// List the entries of the directory at the given URI, as pairs of their name and type.
+    fn read_directory<'a>(
+        &'a self,
+        uri: &'a Uri,
+    ) -> BoxFuture<'a, io::Result<Vec<(String, FileType)>>>;
+
+    /// Get metadata about the entry at the given URI.
+    fn stat<'a>(&'a self, uri: &'a Uri) -> BoxFuture<'a, io::Result<FileStat>>;
+}
+
+impl<T: FileSystemProvider + ?Sized> AsyncFileSystemProvider for T {
+    fn read_file<'a>(&'a self, uri: &'a Uri) -> BoxFuture<'a, io::Result<String>> {
+        Box::pin(std::future::ready(FileSystemProvider::read_file(self, uri)))
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        uri: &'a Uri,
+    ) -> BoxFuture<'a, io::Result<Vec<(String, FileType)>>> {
+        Box::pin(std::future::ready(FileSystemProvider::read_directory(
+            self, uri,
+        )))
+    }
+
+    fn stat<'a>(&'a self, uri: &'a Uri) -> BoxFuture<'a, io::Result<FileStat>> {
+        Box::pin(std::future::ready(FileSystemProvider::stat(self, uri)))
+    }
+}
+
+/// Get the URI of the entry named `name` in the directory at `directory`.
+pub(crate) fn join(directory: &Uri, name: &str) -> Option<Uri> {
+    format!("{}/{name}", directory.as_str().trim_end_matches('/'))
+        .parse()
+        .ok()
+}
+
+#[cfg(feature = "wasm")]
+pub(crate) mod wasm_bindings {
+    use std::io;
+
+    use lsp_types::Uri;
+    use serde::Deserialize;
+    use wasm_bindgen::prelude::*;
+
+    use super::{FileStat, FileSystemProvider, FileType};
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * Synchronous access to the file system of the host, for the features that need to read files
+ * that aren't open in the editor. File types use the values of VS Code's `FileType`.
+ */
+export interface FileSystemProvider {
+	readFile(uri: string): string;
+	readDirectory(uri: string): [string, number][];
+	stat(uri: string): { type: number; size: number; mtime: number };
+}
+"#;
+
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(typescript_type = "FileSystemProvider")]
+        pub type JsFileSystemProvider;
+
+        #[wasm_bindgen(method, catch, js_name = readFile)]
+        fn read_file(this: &JsFileSystemProvider, uri: &str) -> Result<String, JsValue>;
+
+        #[wasm_bindgen(method, catch, js_name = readDirectory)]
+        fn read_directory(this: &JsFileSystemProvider, uri: &str) -> Result<JsValue, JsValue>;
+
+        #[wasm_bindgen(method, catch)]
+        fn stat(this: &JsFileSystemProvider, uri: &str) -> Result<JsValue, JsValue>;
+    }
+
+    #[derive(Deserialize)]
+    struct JsFileStat {
+        #[serde(rename = "type")]
+        file_type: u32,
+        size: u64,
+        mtime: u64,
+    }
+
+    // WASM is single-threaded, so the JS object can never actually be accessed from another thread.
+    unsafe impl Send for JsFileSystemProvider {}
+    unsafe impl Sync for JsFileSystemProvider {}
+
+    fn js_error(error: JsValue) -> io::Error {
+        io::Error::other(format!("{error:?}"))
+    }
+
+    impl FileSystemProvider for JsFileSystemProvider {
+        fn read_file(&self, uri: &Uri) -> io::Result<String> {
+            JsFileSystemProvider::read_file(self, uri.as_str()).map_err(js_error)
+        }
+
+        fn read_directory(&self, uri: &Uri) -> io::Result<Vec<(String, FileType)>> {
+            let entries =
+                JsFileSystemProvider::read_directory(self, uri.as_str()).map_err(js_error)?;
+            let entries: Vec<(String, u32)> =
+                serde_wasm_bindgen::from_value(entries).map_err(|error| js_error(error.into()))?;
+
+            Ok(entries
+                .into_iter()
+                .map(|(name, file_type)| (name, FileType::from_vscode(file_type)))
+                .collect())
+        }
+
+        fn stat(&self, uri: &Uri) -> io::Result<FileStat> {
+            let stat = JsFileSystemProvider::stat(self, uri.as_str()).map_err(js_error)?;
+            let stat: JsFileStat =
+                serde_wasm_bindgen::from_value(stat).map_err(|error| js_error(error.into()))?;
+
+            Ok(FileStat {
+                file_type: FileType::from_vscode(stat.file_type),
+                size: stat.size,
+                mtime: stat.mtime,
+            })
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Helpers for the fonts defined by `@font-face` rules, shared by the features checking the sources of their `src`
+//! descriptor and the ranges of their `unicode-range` descriptor, and linking the font files they load.
+
+use biome_css_syntax::{CssGenericProperty, CssLanguage, CssSyntaxKind};
+use biome_rowan::{AstNode, AstNodeList, SyntaxNode, TextRange, TextSize};
+use lsp_types::Uri;
+
+use crate::{descriptors::descriptor_at_rule, scss::normalize_path};
+
+/// The formats of `format()` in the sources of `src`, e.g. `format("woff2")`.
+#[cfg(feature = "diagnostics")]
+pub(crate) const FONT_FORMATS: &[&str] = &[
+    "collection",
+    "embedded-opentype",
+    "opentype",
+    "svg",
+    "truetype",
+    "woff",
+    "woff2",
+];
+
+/// The font technologies of `tech()` in the sources of `src`, e.g. `tech(variations)`.
+#[cfg(feature = "diagnostics")]
+pub(crate) const FONT_TECHNOLOGIES: &[&str] = &[
+    "features-opentype",
+    "features-aat",
+    "features-graphite",
+    "color-colrv0",
+    "color-colrv1",
+    "color-svg",
+    "color-sbix",
+    "color-cbdt",
+    "variations",
+    "palettes",
+    "incremental",
+];
+
+/// A source of the `src` descriptor of a `@font-face` rule, e.g. `url("font.woff2") format("woff2")`.
+pub(crate) struct FontSource {
+    /// The range of the whole source.
+    #[cfg_attr(not(feature = "diagnostics"), allow(dead_code))]
+    pub(crate) range: TextRange,
+    /// The URL of the font file and its range, without quotes, or `None` for `local()` and invalid sources.
+    pub(crate) url: Option<(String, TextRange)>,
+    /// The arguments of `format()` and their ranges, without quotes.
+    pub(crate) formats: Vec<(String, TextRange)>,
+    /// The arguments of `tech()` and their ranges.
+    pub(crate) technologies: Vec<(String, TextRange)>,
+    /// Whether the source is a `url()`, optionally followed by `format()` and `tech()`, or a `local()`.
+    pub(crate) is_valid: bool,
+}
+
+fn function_name(node: &SyntaxNode<CssLanguage>) -> Option<String> {
+    (node.kind() == CssSyntaxKind::CSS_FUNCTION)
+        .then(|| node.first_token())
+        .flatten()
+        .map(|name| name.text_trimmed().to_ascii_lowercase())
+}
+
+/// Get the text of a string or identifier and its range, without the quotes of strings.
+fn unquoted(node: &SyntaxNode<CssLanguage>) -> Option<(String, TextRange)> {
+    let text = node.text_trimmed().to_string();
+    let range = node.text_trimmed_range();
+    match node.kind() {
+        CssSyntaxKind::CSS_STRING if text.len() >= 2 => Some((
+            text[1..text.len() - 1].to_string(),
+            TextRange::new(
+                range.start() + TextSize::from(1),
+                range.end() - TextSize::from(1),
+            ),
+        )),
+        CssSyntaxKind::CSS_IDENTIFIER | CssSyntaxKind::CSS_URL_VALUE_RAW => Some((text, range)),
+        _ => None,
+    }
+}
+
+/// Get the strings and identifiers passed to a function, e.g. `"woff2"` in `format("woff2")`.
+fn arguments(function: &SyntaxNode<CssLanguage>) -> Vec<(String, TextRange)> {
+    function
+        .children()
+        .filter(|child| child.kind() == CssSyntaxKind::CSS_PARAMETER_LIST)
+        .flat_map(|parameters| parameters.descendants())
+        .filter_map(|node| unquoted(&node))
+        .collect()
+}
+
+/// Get the sources of a `src` declaration of a `@font-face` rule, or nothing for other declarations.
+pub(crate) fn font_sources(property: &CssGenericProperty) -> Vec<FontSource> {
+    let is_src = property.name().is_ok_and(|name| {
+        name.syntax()
+            .text_trimmed()
+            .to_string()
+            .eq_ignore_ascii_case("src")
+    });
+    if !is_src || descriptor_at_rule(property.syntax()).as_deref() != Some("@font-face") {
+        return Vec::new();
+    }
+
+    let values = property
+        .value()
+        .syntax_list()
+        .node()
+        .children()
+        .collect::<Vec<_>>();
+    values
+        .split(|value| {
+            value.kind() == CssSyntaxKind::CSS_GENERIC_DELIMITER && value.text_trimmed() == ","
+        })
+        .filter_map(|source| {
+            let (first, last) = (source.first()?, source.last()?);
+            let mut source_data = FontSource {
+                range: TextRange::new(
+                    first.text_trimmed_range().start(),
+                    last.text_trimmed_range().end(),
+                ),
+                url: None,
+                formats: Vec::new(),
+                technologies: Vec::new(),
+                is_valid: false,
+            };
+
+            if first.kind() == CssSyntaxKind::CSS_URL_FUNCTION {
+                source_data.url = first.children().find_map(|value| unquoted(&value));
+                // `format()` comes before `tech()`, and each one at most once
+                let functions = source[1..].iter().map(function_name).collect::<Vec<_>>();
+                source_data.is_valid = matches!(
+                    functions
+                        .iter()
+                        .map(|name| name.as_deref())
+                        .collect::<Vec<_>>()
+                        .as_slice(),
+                    [] | [Some("format")] | [Some("tech")] | [Some("format"), Some("tech")]
+                );
+                for (function, name) in source[1..].iter().zip(functions) {
+                    match name.as_deref() {
+                        Some("format") => source_data.formats.extend(arguments(function)),
+                        Some("tech") => source_data.technologies.extend(arguments(function)),
+                        _ => {}
+                    }
+                }
+            } else {
+                source_data.is_valid =
+                    source.len() == 1 && function_name(first).as_deref() == Some("local");
+            }
+            Some(source_data)
+        })
+        .collect()
+}
+
+/// Resolve a URL relative to the document referencing it, e.g. the font file of `url(fonts/inter.woff2)` or the
+/// stylesheet of `@import "base.css"`.
+///
+/// # Returns
+///
+/// * The URI of the file, or `None` for data URLs and URLs relative to the root of a site, whose root is unknown.
+pub(crate) fn resolve_url(from: &Uri, url: &str) -> Option<Uri> {
+    // The query and fragment of a URL don't change the file, e.g. `font.eot?#iefix`
+    let url = url.split(['?', '#']).next()?;
+    if url.is_empty() || url.starts_with("data:") || url.starts_with('/') {
+        return None;
+    }
+    if url.contains("://") {
+        return url.parse().ok();
+    }
+
+    let (directory, _) = from.as_str().rsplit_once('/')?;
+    normalize_path(&format!("{directory}/{url}")).parse().ok()
+}
+
+/// The last code point of Unicode, the upper bound of the ranges of `unicode-range`.
+#[cfg(feature = "diagnostics")]
+const MAX_CODE_POINT: u32 = 0x10FFFF;
+
+/// Why a range of the `unicode-range` descriptor is invalid.
+#[cfg(feature = "diagnostics")]
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum UnicodeRangeError {
+    /// The range isn't `U+` followed by a code point, a wildcard range or two code points separated by `-`, each of
+    /// up to 6 hexadecimal digits.
+    Malformed,
+    /// The start of the range is greater than its end, e.g. `U+00FF-0025`.
+    Reversed,
+    /// The range ends after the last code point, `U+10FFFF`.
+    OutOfRange,
+}
+
+/// Parse a range of the `unicode-range` descriptor of a `@font-face` rule: a code point, e.g. `U+26`, an interval, e.g.
+/// `U+0025-00FF`, or a wildcard range whose trailing `?` stand for any hexadecimal digit, e.g. `U+4??`.
+///
+/// # Returns
+///
+/// * The first and last code points of the range, both included, or why the range is invalid.
+#[cfg(feature = "diagnostics")]
+pub(crate) fn parse_unicode_range(range: &str) -> Result<(u32, u32), UnicodeRangeError> {
+    let hexadecimal = |digits: &str| {
+        (1..=6).contains(&digits.len()) && digits.bytes().all(|byte| byte.is_ascii_hexdigit())
+    };
+    let range = range
+        .strip_prefix(['U', 'u'])
+        .and_then(|range| range.strip_prefix('+'))
+        .ok_or(UnicodeRangeError::Malformed)?;
+
+    let (start, end) = match range.split_once('-') {
+        Some((start, end)) if hexadecimal(start) && hexadecimal(end) => (
+            u32::from_str_radix(start, 16).map_err(|_| UnicodeRangeError::Malformed)?,
+            u32::from_str_radix(end, 16).map_err(|_| UnicodeRangeError::Malformed)?,
+        ),
+        Some(_) => return Err(UnicodeRangeError::Malformed),
+        None => {
+            let digits = range.trim_end_matches('?');
+            let wildcards = (range.len() - digits.len()) as u32;
+            if !(1..=6).contains(&range.len()) || !(digits.is_empty() || hexadecimal(digits)) {
+                return Err(UnicodeRangeError::Malformed);
+            }
+            let prefix = match digits {
+                "" => 0,
+                digits => {
+                    u32::from_str_radix(digits, 16).map_err(|_| UnicodeRangeError::Malformed)?
+                }
+            };
+            let start = prefix << (4 * wildcards);
+            (start, start | ((1 << (4 * wildcards)) - 1))
+        }
+    };
+
+    if end > MAX_CODE_POINT {
+        Err(UnicodeRangeError::OutOfRange)
+    } else if start > end {
+        Err(UnicodeRangeError::Reversed)
+    } else {
+        Ok((start, end))
+    }
+}
+
+#[cfg(all(test, feature = "diagnostics"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_unicode_range() {
+        assert_eq!(parse_unicode_range("U+26"), Ok((0x26, 0x26)));
+        assert_eq!(parse_unicode_range("u+0025-00ff"), Ok((0x25, 0xFF)));
+        assert_eq!(parse_unicode_range("U+4??"), Ok((0x400, 0x4FF)));
+        assert_eq!(
+            parse_unicode_range("U+??????"),
+            Err(UnicodeRangeError::OutOfRange)
+        );
+        assert_eq!(parse_unicode_range("U+0-10FFFF"), Ok((0, MAX_CODE_POINT)));
+        assert_eq!(
+            parse_unicode_range("U+00FF-0025"),
+            Err(UnicodeRangeError::Reversed)
+        );
+        assert_eq!(
+            parse_unicode_range("U+110000"),
+            Err(UnicodeRangeError::OutOfRange)
+        );
+        assert_eq!(
+            parse_unicode_range("U+1234567"),
+            Err(UnicodeRangeError::Malformed)
+        );
+        assert_eq!(
+            parse_unicode_range("U+1?3"),
+            Err(UnicodeRangeError::Malformed)
+        );
+        assert_eq!(
+            parse_unicode_range("U+4??-4FF"),
+            Err(UnicodeRangeError::Malformed)
+        );
+        assert_eq!(parse_unicode_range("U+"), Err(UnicodeRangeError::Malformed));
+        assert_eq!(
+            parse_unicode_range("0025"),
+            Err(UnicodeRangeError::Malformed)
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Support for the `@value` statements of CSS Modules (ICSS), e.g. `@value primary: #bf4040;` or
+//! `@value primary, secondary as accent from "./colors.css";`.
+//!
+//! CSS documents with `@value` statements are masked before parsing like SCSS ones, see `scss.rs`, with the same
+//! scanner. Values are recorded as SCSS variables, their uses in declarations and in the preludes of at-rules (e.g.
+//! `@media small`) as variable references, and imports as `value` directives.
+
+use biome_rowan::TextRange;
+
+use crate::{
+    parser::CssDialect,
+    scss::{
+        is_ident_char, is_ident_start, quoted_strings, Scanner, ScssConstruct, ScssConstructKind,
+        ScssScan,
+    },
+};
+
+/// Scans a CSS document for its `@value` statements, and masks them so that the document can be parsed.
+///
+/// # Arguments
+///
+/// * `code` - A string slice that holds the CSS code to scan.
+///
+/// # Returns
+///
+/// * The masked code, and the values, references and imports found in it.
+pub fn scan_icss(code: &str) -> ScssScan {
+    let mut scanner = Scanner::new(code, CssDialect::Css);
+    // Most CSS documents don't use CSS Modules, they are left as is
+    if code.contains("@value") {
+        let (names, ranges) = scanner.scan_icss_statements();
+        scanner.scan_icss_references(&names, &ranges);
+    }
+    scanner.finish()
+}
+
+/// The values imported by a CSS document with `@value … from "…";`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IcssImport {
+    /// The URL of the stylesheet, e.g. `./colors.css`.
+    pub url: String,
+    /// The range of the URL, without its quotes.
+    pub range: TextRange,
+    /// The name of each value in the stylesheet, with the name it is used through in the document, e.g. `secondary`
+    /// and `accent` for `secondary as accent`.
+    pub names: Vec<(String, String)>,
+}
+
+/// Get the values imported by a CSS document, from its `@value … from "…";` statements.
+///
+/// # Arguments
+///
+/// * `code` - The CSS code of the document.
+/// * `constructs` - The constructs found in the code by `scan_icss`.
+pub fn icss_imports(code: &str, constructs: &[ScssConstruct]) -> Vec<IcssImport> {
+    constructs
+        .iter()
+        .filter(|construct| {
+            construct.kind == ScssConstructKind::Directive && construct.name == "value"
+        })
+        .filter_map(|construct| {
+            let arguments_start = usize::from(construct.name_range.end());
+            let arguments = &code[arguments_start..usize::from(construct.range.end())];
+            let (url, url_start, _) = quoted_strings(arguments).pop()?;
+            let names = arguments[..url_start - 1].trim_end().strip_suffix("from")?;
+
+            Some(IcssImport {
+                url: url.to_string(),
+                range: TextRange::new(
+                    ((arguments_start + url_start) as u32).into(),
+                    ((arguments_start + url_start + url.len()) as u32).into(),
+                ),
+                names: names
+                    .split(',')
+                    .filter_map(|name| {
+                        let mut words = name.split_whitespace();
+                        let imported = words.next()?;
+                        let local = match (words.next(), words.next()) {
+                            (Some("as"), Some(local)) => local,
+                            _ => imported,
+                        };
+                        Some((imported.to_string(), local.to_string()))
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+impl Scanner<'_> {
+    /// Scan the statements of a CSS document, masking its `@value` statements. Returns the names of the values
+    /// declared or imported, and the ranges of the values of declarations and of the preludes of at-rules, where
+    /// values can be used.
+    fn scan_icss_statements(&mut self) -> (Vec<String>, Vec<(usize, usize)>) {
+        let mut names = Vec::new();
+        let mut ranges = Vec::new();
+        let mut i = 0;
+        loop {
+            i = self.skip_trivia(i);
+            let Some(&byte) = self.bytes.get(i) else {
+                break;
+            };
+
+            let end = self.statement_end(i);
+            if byte == b'{' || byte == b'}' || byte == b';' {
+                i += 1;
+                continue;
+            }
+            if byte == b'@' {
+                let name_end = self.ident_end(i + 1);
+                if self.code[i + 1..name_end] == *"value" {
+                    names.extend(self.icss_value(i, name_end, end));
+                } else {
+                    ranges.push((name_end, end));
+                }
+            } else if self.bytes.get(end) != Some(&b'{') {
+                // Declarations, whose value follows their colon. Selectors are left out.
+                if let Some(colon) = self.code[i..end].find(':') {
+                    ranges.push((i + colon + 1, end));
+                }
+            }
+            i = end;
+        }
+        (names, ranges)
+    }
+
+    /// Record and mask a `@value` statement, returning the names of the values it declares or imports.
+    fn icss_value(&mut self, start: usize, name_end: usize, end: usize) -> Vec<String> {
+        let code = self.code;
+        let range_end = self.trim_end(start, end);
+        let arguments = &code[name_end..range_end];
+        self.blank(start, self.terminated_end(end));
+
+        if arguments.contains(['"', '\'']) && arguments.contains(" from") {
+            self.push(
+                ScssConstructKind::Directive,
+                (start + 1, name_end),
+                (start, range_end),
+                None,
+            );
+            return icss_imports(code, &self.constructs[self.constructs.len() - 1..])
+                .into_iter()
+                .flat_map(|import| import.names)
+                .map(|(_, local)| local)
+                .collect();
+        }
+
+        // Values are declared with or without a colon, e.g. `@value small (max-width: 599px);`
+        let value_name_start = self.skip_trivia(name_end).min(range_end);
+        let value_name_end = self.ident_end(value_name_start);
+        if value_name_end == value_name_start {
+            return Vec::new();
+        }
+        let mut value_start = self.skip_trivia(value_name_end).min(range_end);
+        if self.bytes.get(value_start) == Some(&b':') {
+            value_start = self.skip_trivia(value_start + 1).min(range_end);
+        }
+        self.push(
+            ScssConstructKind::Variable,
+            (value_name_start, value_name_end),
+            (start, range_end),
+            Some((value_start, range_end)),
+        );
+        vec![code[value_name_start..value_name_end].to_string()]
+    }
+
+    /// Record the uses of the values in the given ranges, as variable references. Identifiers which are part of a
+    /// selector (e.g. `.primary`) or a function name aren't uses.
+    fn scan_icss_references(&mut self, names: &[String], ranges: &[(usize, usize)]) {
+        for &(start, end) in ranges {
+            let mut i = start;
+            while i < end {
+                match self.bytes[i] {
+                    b'"' | b'\'' => {
+                        i = self.string_end(i);
+                        continue;
+                    }
+                    b'/' if self.bytes[i..].starts_with(b"/*") => {
+                        i = self.comment_end(i);
+                        continue;
+                    }
+                    byte if is_ident_start(byte) => {
+                        let ident_end = self.ident_end(i);
+                        let previous = i.checked_sub(1).map(|previous| self.bytes[previous]);
+                        let is_name = !previous.is_some_and(|byte| {
+                            is_ident_char(byte) || matches!(byte, b'.' | b'#' | b'$' | b'@' | b'%')
+                        }) && self.bytes.get(ident_end) != Some(&b'(')
+                            && names.iter().any(|name| *name == self.code[i..ident_end]);
+                        if is_name {
+                            self.push(
+                                ScssConstructKind::VariableReference,
+                                (i, ident_end),
+                                (i, ident_end),
+                                None,
+                            );
+                        }
+                        i = ident_end;
+                        continue;
+                    }
+                    _ => {}
+                }
+                i += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_icss_masks_to_css() {
+        let code = "@value primary: #bf4040;\n@value small as mobile from \"./breakpoints.css\";\n.a { color: primary; }";
+        let scan = scan_icss(code);
+        assert_eq!(scan.masked.len(), code.len());
+        assert_eq!(
+            scan.masked.trim_start(),
+            ".a { color: primary; }",
+            "@value statements are blanked"
+        );
+    }
+
+    #[test]
+    fn test_scan_icss_constructs() {
+        let code = "@value primary: #bf4040;\n@value small, large as wide from './breakpoints.css';\n.primary { color: primary; }\n@media wide { .a { border: 1px solid primary; } }";
+        let scan = scan_icss(code);
+
+        assert_eq!(
+            scan.constructs
+                .iter()
+                .map(|construct| (construct.kind, construct.name.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (ScssConstructKind::Variable, "primary"),
+                (ScssConstructKind::Directive, "value"),
+                (ScssConstructKind::VariableReference, "primary"),
+                (ScssConstructKind::VariableReference, "wide"),
+                (ScssConstructKind::VariableReference, "primary"),
+            ]
+        );
+        assert_eq!(
+            scan.constructs[0]
+                .value
+                .as_ref()
+                .map(|(_, value)| value.as_str()),
+            Some("#bf4040")
+        );
+
+        let imports = icss_imports(code, &scan.constructs);
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].url, "./breakpoints.css");
+        assert_eq!(&code[imports[0].range], "./breakpoints.css");
+        assert_eq!(
+            imports[0].names,
+            vec![
+                ("small".to_string(), "small".to_string()),
+                ("large".to_string(), "wide".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_icss_keeps_css() {
+        let code = ".a { color: red; }\n@media (min-width: 100px) { .b { margin: 0; } }";
+        let scan = scan_icss(code);
+        assert_eq!(scan.masked, code);
+        assert!(scan.constructs.is_empty());
+    }
+}
@@ -0,0 +1,306 @@
+//! Instrumentation of the LanguageService with `tracing` spans for parsing, store updates and features, and an
+//! optional report of the time taken by recent requests, to diagnose latency, e.g. from a `csslsrs/perf` command of a
+//! server.
+//!
+//! Spans and timings are only compiled with the `tracing` feature, keeping WASM builds small. Without it, `span!`,
+//! `request!` and `warning!` expand to nothing.
+
+// Requests are only made by the features of the service, without which `request!` and the timers it starts are unused
+#![cfg_attr(
+    not(any(
+        feature = "code-actions",
+        feature = "colors",
+        feature = "completion",
+        feature = "definition",
+        feature = "diagnostics",
+        feature = "document-links",
+        feature = "document-symbols",
+        feature = "folding",
+        feature = "hover",
+        feature = "inlay-hints",
+        feature = "rename",
+        feature = "semantic-tokens",
+        feature = "signature-help"
+    )),
+    allow(unused_macros, unused_imports, dead_code)
+)]
+
+#[cfg(feature = "tracing")]
+use std::{
+    cmp::Reverse,
+    collections::VecDeque,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+#[cfg(feature = "tracing")]
+use lsp_types::Uri;
+
+/// Enter a `tracing` span for the rest of the current scope, e.g. `span!("parse", size = code.len());`.
+macro_rules! span {
+    ($name:literal $(, $field:ident = $value:expr)* $(,)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = ::tracing::debug_span!($name $(, $field = $value)*).entered();
+        #[cfg(not(feature = "tracing"))]
+        $(let _ = &$value;)*
+    };
+}
+
+/// Enter the span of a request of a feature on a document for the rest of the current scope, and record how long it
+/// took in the TimingReport of the service, if any, e.g. `request!(self, "colors", store_entry);`.
+macro_rules! request {
+    ($service:expr, $feature:literal, $store_entry:expr) => {
+        #[cfg(feature = "tracing")]
+        let _request = $crate::instrumentation::RequestTimer::start(
+            $service.timing_report(),
+            $feature,
+            &$store_entry.document,
+            ::tracing::info_span!(
+                "request",
+                feature = $feature,
+                uri = $store_entry.document.uri.as_str(),
+                size = $store_entry.document.text.len(),
+            )
+            .entered(),
+        );
+        #[cfg(not(feature = "tracing"))]
+        let _ = (&$service, &$store_entry);
+    };
+}
+
+/// Log a warning about invalid input that was recovered from, e.g.
+/// `warning!("position clamped to the end of the document", line = position.line);`.
+macro_rules! warning {
+    ($message:literal $(, $field:ident = $value:expr)* $(,)?) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::warn!($($field = $value,)* $message);
+        #[cfg(not(feature = "tracing"))]
+        {
+            $(let _ = &$value;)*
+        }
+    };
+}
+
+pub(crate) use {request, span, warning};
+
+/// How long a request took.
+#[cfg(feature = "tracing")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestTiming {
+    /// The feature of the request, e.g. `colors` or `completion`.
+    pub feature: &'static str,
+    pub uri: Uri,
+    pub duration: Duration,
+    /// The size of the document, in bytes.
+    pub document_size: usize,
+}
+
+/// The timings of a feature in a TimingReport.
+#[cfg(feature = "tracing")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeatureTimings {
+    pub feature: &'static str,
+    pub count: usize,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+/// The timings of the most recent requests of a LanguageService, enabled with `LanguageServiceBuilder::timing_report`.
+///
+/// Durations can't be measured in WASM, where the report stays empty.
+#[cfg(feature = "tracing")]
+#[derive(Debug)]
+pub struct TimingReport {
+    capacity: usize,
+    timings: Mutex<VecDeque<RequestTiming>>,
+}
+
+#[cfg(feature = "tracing")]
+impl TimingReport {
+    /// Create a report keeping the timings of the last `capacity` requests.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            timings: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<RequestTiming>> {
+        self.timings.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn record(&self, timing: RequestTiming) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut timings = self.lock();
+        if timings.len() == self.capacity {
+            timings.pop_front();
+        }
+        timings.push_back(timing);
+    }
+
+    /// Get the recorded timings, from the oldest to the most recent.
+    pub fn timings(&self) -> Vec<RequestTiming> {
+        self.lock().iter().cloned().collect()
+    }
+
+    /// Get the recorded timings grouped by feature, the slowest features in total first.
+    pub fn summary(&self) -> Vec<FeatureTimings> {
+        let mut summary: Vec<FeatureTimings> = Vec::new();
+        for timing in self.lock().iter() {
+            match summary
+                .iter_mut()
+                .find(|feature| feature.feature == timing.feature)
+            {
+                Some(feature) => {
+                    feature.count += 1;
+                    feature.total += timing.duration;
+                    feature.max = feature.max.max(timing.duration);
+                }
+                None => summary.push(FeatureTimings {
+                    feature: timing.feature,
+                    count: 1,
+                    total: timing.duration,
+                    max: timing.duration,
+                }),
+            }
+        }
+
+        summary.sort_by_key(|feature| Reverse(feature.total));
+        summary
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+}
+
+/// Records the timing of a request when dropped, see `request!`.
+#[cfg(feature = "tracing")]
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+pub(crate) struct RequestTimer<'a> {
+    report: Option<&'a TimingReport>,
+    feature: &'static str,
+    document: &'a lsp_types::TextDocumentItem,
+    #[cfg(not(target_arch = "wasm32"))]
+    start: std::time::Instant,
+    _span: tracing::span::EnteredSpan,
+}
+
+#[cfg(feature = "tracing")]
+impl<'a> RequestTimer<'a> {
+    pub(crate) fn start(
+        report: Option<&'a TimingReport>,
+        feature: &'static str,
+        document: &'a lsp_types::TextDocumentItem,
+        span: tracing::span::EnteredSpan,
+    ) -> Self {
+        Self {
+            report,
+            feature,
+            document,
+            #[cfg(not(target_arch = "wasm32"))]
+            start: std::time::Instant::now(),
+            _span: span,
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for RequestTimer<'_> {
+    fn drop(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(report) = self.report {
+            report.record(RequestTiming {
+                feature: self.feature,
+                uri: self.document.uri.clone(),
+                duration: self.start.elapsed(),
+                document_size: self.document.text.len(),
+            });
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn timing(feature: &'static str, millis: u64) -> RequestTiming {
+        RequestTiming {
+            feature,
+            uri: Uri::from_str("file:///test.css").unwrap(),
+            duration: Duration::from_millis(millis),
+            document_size: 0,
+        }
+    }
+
+    #[test]
+    fn test_timing_report() {
+        let report = TimingReport::new(3);
+        report.record(timing("colors", 10));
+        report.record(timing("folding", 1));
+        report.record(timing("colors", 2));
+        report.record(timing("folding", 4));
+
+        assert_eq!(
+            report.timings(),
+            vec![
+                timing("folding", 1),
+                timing("colors", 2),
+                timing("folding", 4)
+            ]
+        );
+        assert_eq!(
+            report.summary(),
+            vec![
+                FeatureTimings {
+                    feature: "folding",
+                    count: 2,
+                    total: Duration::from_millis(5),
+                    max: Duration::from_millis(4),
+                },
+                FeatureTimings {
+                    feature: "colors",
+                    count: 1,
+                    total: Duration::from_millis(2),
+                    max: Duration::from_millis(2),
+                },
+            ]
+        );
+
+        report.clear();
+        assert_eq!(report.timings(), vec![]);
+    }
+
+    #[cfg(all(feature = "colors", feature = "folding"))]
+    #[test]
+    fn test_timing_report_records_requests() {
+        use crate::service::LanguageService;
+        use lsp_types::TextDocumentItem;
+
+        let language_service = LanguageService::builder().timing_report(8).build();
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///test.css").unwrap(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: "a { color: red; }".to_string(),
+        };
+
+        language_service.get_folding_ranges(document.clone());
+        language_service.get_document_colors(document);
+
+        let timings = language_service.timing_report().unwrap().timings();
+        assert_eq!(
+            timings
+                .iter()
+                .map(|timing| (timing.feature, timing.document_size))
+                .collect::<Vec<_>>(),
+            vec![("folding", 17), ("colors", 17)]
+        );
+        assert!(LanguageService::default().timing_report().is_none());
+    }
+}
@@ -0,0 +1,415 @@
+//! An in-process JSON-RPC adapter, mapping the raw messages of the LSP to LanguageService calls and back.
+//!
+//! Hosts that already own a transport, e.g. a server for several languages, can forward the messages about CSS
+//! documents to a `JsonRpcAdapter` instead of routing each request to the LanguageService themselves. Messages are
+//! passed as `serde_json::Value`s or as the bytes of their JSON, without the `Content-Length` header of the transport.
+//!
+//! Requests always get a response, with a `MethodNotFound` error for the methods the adapter doesn't handle, e.g. those
+//! of features left out of the build, so that hosts can try another handler. Notifications and responses never get one.
+
+use lsp_types::{
+    notification::{self, Notification},
+    request::{self, Request},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    error::Error,
+    extensions::{ResolvedValue, SyntaxTree},
+    service::LanguageService,
+    settings::Settings,
+};
+
+/// The error codes of JSON-RPC and of the LSP used in the responses of the adapter.
+pub mod error_codes {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const INTERNAL_ERROR: i64 = -32603;
+    pub const SERVER_NOT_INITIALIZED: i64 = -32002;
+    pub const REQUEST_CANCELLED: i64 = -32800;
+}
+
+/// The error of a response to a request, or of a notification that couldn't be handled.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ResponseError {
+    pub code: i64,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl ResponseError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    fn invalid_params(error: serde_json::Error) -> Self {
+        Self::new(error_codes::INVALID_PARAMS, error.to_string())
+    }
+}
+
+impl From<Error> for ResponseError {
+    fn from(error: Error) -> Self {
+        let code = match error {
+            Error::Cancelled => error_codes::REQUEST_CANCELLED,
+            _ => error_codes::INVALID_PARAMS,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+/// Handles the LSP messages of a host with its LanguageService.
+///
+/// # Example
+///
+/// ```rust
+/// use csslsrs::json_rpc::JsonRpcAdapter;
+/// use csslsrs::service::LanguageService;
+/// use serde_json::json;
+///
+/// let adapter = JsonRpcAdapter::new(LanguageService::default());
+/// adapter.handle_message(json!({
+///     "jsonrpc": "2.0",
+///     "method": "textDocument/didOpen",
+///     "params": {
+///         "textDocument": { "uri": "file:///a.css", "languageId": "css", "version": 1, "text": "a {}" }
+///     }
+/// }));
+///
+/// let response = adapter.handle_message(json!({
+///     "jsonrpc": "2.0",
+///     "id": 1,
+///     "method": "cssls/syntaxTree",
+///     "params": { "textDocument": { "uri": "file:///a.css" } }
+/// }));
+/// assert!(response.unwrap()["result"].is_string());
+/// ```
+pub struct JsonRpcAdapter {
+    language_service: LanguageService,
+}
+
+impl JsonRpcAdapter {
+    pub fn new(language_service: LanguageService) -> Self {
+        Self { language_service }
+    }
+
+    pub fn language_service(&self) -> &LanguageService {
+        &self.language_service
+    }
+
+    /// Handle a message received by the host.
+    ///
+    /// # Returns
+    ///
+    /// * The response to send back if the message is a request, or if it isn't a valid message.
+    /// * `None` for notifications, whether they could be handled or not, and for responses.
+    pub fn handle_message(&self, message: Value) -> Option<Value> {
+        let Value::Object(mut message) = message else {
+            return Some(error_response(
+                Value::Null,
+                ResponseError::new(error_codes::INVALID_REQUEST, "a message must be an object"),
+            ));
+        };
+        let params = message.remove("params").unwrap_or(Value::Null);
+        let id = message.remove("id");
+
+        let method = match message.remove("method") {
+            Some(Value::String(method)) => method,
+            // Responses to the requests of the host aren't for the adapter
+            None if id.is_some() => return None,
+            _ => {
+                return Some(error_response(
+                    id.unwrap_or(Value::Null),
+                    ResponseError::new(
+                        error_codes::INVALID_REQUEST,
+                        "the method of a message must be a string",
+                    ),
+                ))
+            }
+        };
+
+        match id {
+            Some(id) => Some(match self.handle_request(&method, params) {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                Err(error) => error_response(id, error),
+            }),
+            None => {
+                // Notifications have no response to report their errors in
+                let _ = self.handle_notification(&method, params);
+                None
+            }
+        }
+    }
+
+    /// Same as `handle_message`, with the UTF-8 encoded JSON of the message and of its response.
+    pub fn handle_bytes(&self, message: &[u8]) -> Option<Vec<u8>> {
+        let response = match serde_json::from_slice(message) {
+            Ok(message) => self.handle_message(message)?,
+            Err(error) => error_response(
+                Value::Null,
+                ResponseError::new(error_codes::PARSE_ERROR, error.to_string()),
+            ),
+        };
+
+        Some(response.to_string().into_bytes())
+    }
+
+    /// Handle a request, given its method and params.
+    ///
+    /// # Returns
+    ///
+    /// * The result of the request.
+    /// * A `MethodNotFound` error if the adapter doesn't handle the method, or the error of the LanguageService.
+    pub fn handle_request(&self, method: &str, params: Value) -> Result<Value, ResponseError> {
+        let language_service = &self.language_service;
+
+        match method {
+            #[cfg(feature = "completion")]
+            request::Completion::METHOD => handle::<request::Completion>(params, |params| {
+                let position = params.text_document_position;
+                let items = language_service
+                    .get_completions_for_uri(&position.text_document.uri, position.position)?;
+                Ok(Some(items.into()))
+            }),
+            #[cfg(feature = "definition")]
+            request::GotoDefinition::METHOD => {
+                handle::<request::GotoDefinition>(params, |params| {
+                    let position = params.text_document_position_params;
+                    let locations = language_service
+                        .get_definition_for_uri(&position.text_document.uri, position.position)?;
+                    Ok(Some(lsp_types::GotoDefinitionResponse::Array(locations)))
+                })
+            }
+            #[cfg(feature = "definition")]
+            request::References::METHOD => handle::<request::References>(params, |params| {
+                let position = params.text_document_position;
+                let include_declaration = params.context.include_declaration;
+                let locations = language_service.symbol_occurrences(
+                    &position.text_document.uri,
+                    position.position,
+                    |symbol| include_declaration || !symbol.is_declaration,
+                )?;
+                Ok(Some(locations))
+            }),
+            #[cfg(feature = "diagnostics")]
+            request::DocumentDiagnosticRequest::METHOD => {
+                handle::<request::DocumentDiagnosticRequest>(params, |params| {
+                    language_service
+                        .get_diagnostic_report_for_uri(
+                            &params.text_document.uri,
+                            params.previous_result_id.as_deref(),
+                        )
+                        .map(Into::into)
+                })
+            }
+            #[cfg(feature = "diagnostics")]
+            request::WorkspaceDiagnosticRequest::METHOD => {
+                handle::<request::WorkspaceDiagnosticRequest>(params, |params| {
+                    let report = language_service.get_workspace_diagnostics(
+                        &params.previous_result_ids,
+                        &crate::cancellation::CancellationToken::default(),
+                        &mut (),
+                    )?;
+                    Ok(report.into())
+                })
+            }
+            #[cfg(feature = "code-actions")]
+            request::CodeActionRequest::METHOD => {
+                handle::<request::CodeActionRequest>(params, |params| {
+                    let actions = language_service
+                        .get_code_actions_for_uri(&params.text_document.uri, params.range)?;
+                    Ok(Some(
+                        actions
+                            .into_iter()
+                            .map(lsp_types::CodeActionOrCommand::CodeAction)
+                            .collect(),
+                    ))
+                })
+            }
+            #[cfg(feature = "colors")]
+            request::DocumentColor::METHOD => handle::<request::DocumentColor>(params, |params| {
+                language_service.get_document_colors_for_uri(&params.text_document.uri)
+            }),
+            #[cfg(feature = "colors")]
+            request::ColorPresentationRequest::METHOD => {
+                handle::<request::ColorPresentationRequest>(params, |params| {
+                    language_service.get_color_presentations_for_uri(
+                        &params.text_document.uri,
+                        params.color,
+                        params.range,
+                    )
+                })
+            }
+            #[cfg(feature = "document-links")]
+            request::DocumentLinkRequest::METHOD => {
+                handle::<request::DocumentLinkRequest>(params, |params| {
+                    language_service
+                        .get_document_links_for_uri(&params.text_document.uri)
+                        .map(Some)
+                })
+            }
+            #[cfg(feature = "document-symbols")]
+            request::DocumentSymbolRequest::METHOD => {
+                handle::<request::DocumentSymbolRequest>(params, |params| {
+                    language_service
+                        .get_document_symbols_for_uri(&params.text_document.uri)
+                        .map(|symbols| Some(lsp_types::DocumentSymbolResponse::Nested(symbols)))
+                })
+            }
+            #[cfg(feature = "folding")]
+            request::FoldingRangeRequest::METHOD => {
+                handle::<request::FoldingRangeRequest>(params, |params| {
+                    language_service
+                        .get_folding_ranges_for_uri(&params.text_document.uri)
+                        .map(Some)
+                })
+            }
+            #[cfg(feature = "hover")]
+            request::HoverRequest::METHOD => handle::<request::HoverRequest>(params, |params| {
+                let position = params.text_document_position_params;
+                language_service.get_hover_for_uri(&position.text_document.uri, position.position)
+            }),
+            #[cfg(feature = "signature-help")]
+            request::SignatureHelpRequest::METHOD => {
+                handle::<request::SignatureHelpRequest>(params, |params| {
+                    let position = params.text_document_position_params;
+                    language_service
+                        .get_signature_help_for_uri(&position.text_document.uri, position.position)
+                })
+            }
+            #[cfg(feature = "inlay-hints")]
+            request::InlayHintRequest::METHOD => {
+                handle::<request::InlayHintRequest>(params, |params| {
+                    language_service
+                        .get_inlay_hints_for_uri(&params.text_document.uri, params.range)
+                        .map(Some)
+                })
+            }
+            #[cfg(feature = "inlay-hints")]
+            request::InlayHintResolveRequest::METHOD => {
+                handle::<request::InlayHintResolveRequest>(params, |hint| {
+                    Ok(language_service.resolve_inlay_hint(hint))
+                })
+            }
+            #[cfg(feature = "rename")]
+            request::PrepareRenameRequest::METHOD => {
+                handle::<request::PrepareRenameRequest>(params, |params| {
+                    language_service
+                        .prepare_rename_for_uri(&params.text_document.uri, params.position)
+                })
+            }
+            #[cfg(feature = "rename")]
+            request::Rename::METHOD => handle::<request::Rename>(params, |params| {
+                let position = params.text_document_position;
+                language_service
+                    .rename_for_uri(
+                        &position.text_document.uri,
+                        position.position,
+                        &params.new_name,
+                    )
+                    .map(Some)
+            }),
+            #[cfg(feature = "semantic-tokens")]
+            request::SemanticTokensFullRequest::METHOD => {
+                handle::<request::SemanticTokensFullRequest>(params, |params| {
+                    let tokens =
+                        language_service.get_semantic_tokens_for_uri(&params.text_document.uri)?;
+                    Ok(Some(tokens.into()))
+                })
+            }
+            #[cfg(feature = "workspace-symbols")]
+            request::WorkspaceSymbolRequest::METHOD => {
+                handle::<request::WorkspaceSymbolRequest>(params, |params| {
+                    let symbols = language_service.get_workspace_symbols(&params.query);
+                    Ok(Some(lsp_types::WorkspaceSymbolResponse::Nested(symbols)))
+                })
+            }
+            request::ExecuteCommand::METHOD => {
+                handle::<request::ExecuteCommand>(params, |params| {
+                    language_service.execute_command(&params.command, params.arguments)
+                })
+            }
+            SyntaxTree::METHOD => {
+                handle::<SyntaxTree>(params, |params| language_service.handle_syntax_tree(params))
+            }
+            ResolvedValue::METHOD => handle::<ResolvedValue>(params, |params| {
+                language_service.handle_resolved_value(params)
+            }),
+            _ => Err(ResponseError::new(
+                error_codes::METHOD_NOT_FOUND,
+                format!("unhandled method `{method}`"),
+            )),
+        }
+    }
+
+    /// Handle a notification, given its method and params. Notifications the adapter doesn't handle are ignored, as
+    /// required by the LSP.
+    ///
+    /// `workspace/didChangeConfiguration` takes `Settings` as its `settings`, replacing every setting at once.
+    ///
+    /// # Returns
+    ///
+    /// * An error if the params are invalid, or the error of the LanguageService, e.g. for a change of a document that
+    ///   isn't open.
+    pub fn handle_notification(&self, method: &str, params: Value) -> Result<(), ResponseError> {
+        let language_service = &self.language_service;
+
+        match method {
+            notification::DidOpenTextDocument::METHOD => {
+                let params = notification_params::<notification::DidOpenTextDocument>(params)?;
+                language_service.open_document(params.text_document);
+            }
+            notification::DidChangeTextDocument::METHOD => {
+                let params = notification_params::<notification::DidChangeTextDocument>(params)?;
+                language_service.change_document(
+                    &params.text_document.uri,
+                    params.text_document.version,
+                    params.content_changes,
+                )?;
+            }
+            notification::DidSaveTextDocument::METHOD => {
+                let params = notification_params::<notification::DidSaveTextDocument>(params)?;
+                language_service.save_document(&params.text_document.uri, params.text)?;
+            }
+            notification::DidCloseTextDocument::METHOD => {
+                let params = notification_params::<notification::DidCloseTextDocument>(params)?;
+                language_service.close_document(&params.text_document.uri);
+            }
+            notification::DidChangeConfiguration::METHOD => {
+                let params = notification_params::<notification::DidChangeConfiguration>(params)?;
+                let settings: Settings = serde_json::from_value(params.settings)
+                    .map_err(ResponseError::invalid_params)?;
+                language_service.update_settings(settings);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+fn handle<R: Request>(
+    params: Value,
+    handler: impl FnOnce(R::Params) -> Result<R::Result, Error>,
+) -> Result<Value, ResponseError> {
+    let params = serde_json::from_value(params).map_err(ResponseError::invalid_params)?;
+    let result = handler(params)?;
+
+    serde_json::to_value(result)
+        .map_err(|error| ResponseError::new(error_codes::INTERNAL_ERROR, error.to_string()))
+}
+
+fn notification_params<N: Notification>(params: Value) -> Result<N::Params, ResponseError> {
+    serde_json::from_value(params).map_err(ResponseError::invalid_params)
+}
+
+fn error_response(id: Value, error: ResponseError) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": error })
+}
@@ -0,0 +1,94 @@
+//! Helpers for the keyframe selectors of `@keyframes` rules, e.g. `from` or `50%`, shared by the features completing
+//! and checking them, and for the animations referencing `@keyframes` rules, shared with the workspace index.
+
+use biome_css_syntax::{CssGenericProperty, CssSyntaxKind};
+use biome_rowan::{AstNode, AstNodeList, TextRange};
+#[cfg(any(feature = "completion", feature = "diagnostics"))]
+use {biome_css_syntax::CssLanguage, biome_rowan::SyntaxNode};
+
+/// Get the keyframe selectors of a `@keyframes` rule, or of its block, in source order, e.g. `50%` and `to` in
+/// `@keyframes fade { 50%, to { opacity: 1; } }`. Invalid selectors, e.g. `middle`, are included as bogus selectors.
+#[cfg(any(feature = "completion", feature = "diagnostics"))]
+pub(crate) fn keyframe_selectors(rule: &SyntaxNode<CssLanguage>) -> Vec<SyntaxNode<CssLanguage>> {
+    rule.descendants()
+        .filter(|node| node.kind() == CssSyntaxKind::CSS_KEYFRAMES_SELECTOR_LIST)
+        .flat_map(|list| list.children())
+        .collect()
+}
+
+/// Get the offset of a keyframe selector in the animation, as a percentage, e.g. `0` for `from` or `50` for `50%`.
+///
+/// # Returns
+///
+/// * The offset, or `None` for invalid selectors. Offsets outside of `0%` to `100%` are returned as is.
+#[cfg(any(feature = "completion", feature = "diagnostics"))]
+pub(crate) fn keyframe_offset(selector: &SyntaxNode<CssLanguage>) -> Option<f64> {
+    let text = selector.text_trimmed().to_string().to_ascii_lowercase();
+    match selector.kind() {
+        CssSyntaxKind::CSS_KEYFRAMES_IDENT_SELECTOR if text == "from" => Some(0.0),
+        CssSyntaxKind::CSS_KEYFRAMES_IDENT_SELECTOR if text == "to" => Some(100.0),
+        CssSyntaxKind::CSS_KEYFRAMES_PERCENTAGE_SELECTOR => text.strip_suffix('%')?.parse().ok(),
+        _ => None,
+    }
+}
+
+/// The keywords of the `animation` shorthand, which can't name the `@keyframes` rule of an animation.
+const ANIMATION_KEYWORDS: &[&str] = &[
+    "none",
+    "auto",
+    "normal",
+    "reverse",
+    "alternate",
+    "alternate-reverse",
+    "forwards",
+    "backwards",
+    "both",
+    "running",
+    "paused",
+    "infinite",
+    "ease",
+    "ease-in",
+    "ease-out",
+    "ease-in-out",
+    "linear",
+    "step-start",
+    "step-end",
+    "initial",
+    "inherit",
+    "unset",
+    "revert",
+    "revert-layer",
+];
+
+/// Get the `@keyframes` rules referenced by a declaration, e.g. `spin` in `animation: spin 1s infinite` or in
+/// `animation-name: fade, spin`.
+pub(crate) fn referenced_keyframes(property: &CssGenericProperty) -> Vec<(String, TextRange)> {
+    let Ok(name) = property.name() else {
+        return Vec::new();
+    };
+    let name = name
+        .syntax()
+        .text_trimmed()
+        .to_string()
+        .to_ascii_lowercase();
+    if name != "animation" && name != "animation-name" {
+        return Vec::new();
+    }
+
+    property
+        .value()
+        .syntax_list()
+        .node()
+        .children()
+        .filter(|value| value.kind() == CssSyntaxKind::CSS_IDENTIFIER)
+        .map(|value| (value.text_trimmed().to_string(), value.text_trimmed_range()))
+        .filter(|(name, _)| !is_animation_keyword(name))
+        .collect()
+}
+
+/// Whether an identifier can't name a `@keyframes` rule, as it is a keyword of the `animation` shorthand, e.g. `ease`.
+pub(crate) fn is_animation_keyword(name: &str) -> bool {
+    ANIMATION_KEYWORDS
+        .iter()
+        .any(|keyword| name.eq_ignore_ascii_case(keyword))
+}
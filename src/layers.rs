@@ -0,0 +1,97 @@
+//! Helpers to name the cascade layers of `@layer` rules and of `@import … layer()`, shared by the workspace index and
+//! the features that show which layer a rule belongs to.
+
+use biome_css_syntax::{
+    AnyCssImportLayer, AnyCssLayer, CssImportAtRule, CssLanguage, CssLayerAtRule,
+};
+use biome_rowan::{AstNode, AstSeparatedList, SyntaxNode, TextRange};
+
+/// A layer name written in a `@layer` rule or an `@import`.
+pub(crate) struct LayerName {
+    /// The full name of the layer, including the layers it is nested in, e.g. `components.buttons` for `buttons` in
+    /// `@layer components { @layer buttons { … } }`.
+    pub(crate) name: String,
+    /// The range of the name as written.
+    pub(crate) range: TextRange,
+    /// Whether the name is declared by a `@layer` statement, e.g. `@layer base, components;`, rather than assigned
+    /// rules by a `@layer` block or an `@import`.
+    pub(crate) is_statement: bool,
+}
+
+/// Get the layer names of a `CssLayerAtRule` or `CssImportAtRule` node, in source order.
+pub(crate) fn layer_names(node: &SyntaxNode<CssLanguage>) -> Vec<LayerName> {
+    let (names, is_statement) = if let Some(rule) = CssLayerAtRule::cast_ref(node) {
+        match rule.layer() {
+            Ok(AnyCssLayer::CssLayerDeclaration(declaration)) => (
+                declaration
+                    .references()
+                    .iter()
+                    .flatten()
+                    .map(|name| name.syntax_list().node().clone())
+                    .collect::<Vec<_>>(),
+                false,
+            ),
+            Ok(AnyCssLayer::CssLayerReference(reference)) => (
+                reference
+                    .references()
+                    .iter()
+                    .flatten()
+                    .map(|name| name.syntax_list().node().clone())
+                    .collect(),
+                true,
+            ),
+            _ => return Vec::new(),
+        }
+    } else if let Some(import) = CssImportAtRule::cast_ref(node) {
+        match import.layer() {
+            Some(AnyCssImportLayer::CssImportNamedLayer(layer)) => {
+                (vec![layer.name().syntax_list().node().clone()], false)
+            }
+            _ => return Vec::new(),
+        }
+    } else {
+        return Vec::new();
+    };
+
+    let parent = enclosing_layer(node);
+    names
+        .into_iter()
+        .filter(|name| !name.text_trimmed_range().is_empty())
+        .map(|name| {
+            let text = name.text_trimmed().to_string();
+            LayerName {
+                name: match parent.as_deref() {
+                    Some(parent) if !parent.is_empty() => format!("{parent}.{text}"),
+                    _ => text,
+                },
+                range: name.text_trimmed_range(),
+                is_statement,
+            }
+        })
+        .collect()
+}
+
+/// Get the full name of the layer blocks a node is nested in, e.g. `components.buttons` for a rule in
+/// `@layer components { @layer buttons { … } }`. The blocks of the node itself, if it is a layer rule, don't count.
+///
+/// # Returns
+///
+/// * The name, empty in anonymous layers, e.g. `@layer { … }`, or `None` outside of layer blocks.
+pub(crate) fn enclosing_layer(node: &SyntaxNode<CssLanguage>) -> Option<String> {
+    let mut names = Vec::new();
+    for rule in node.ancestors().skip(1).filter_map(CssLayerAtRule::cast) {
+        let Ok(AnyCssLayer::CssLayerDeclaration(declaration)) = rule.layer() else {
+            continue;
+        };
+        match declaration.references().iter().flatten().next() {
+            Some(name) => names.push(name.syntax_list().node().text_trimmed().to_string()),
+            // Layers nested in an anonymous layer can't be referenced from outside of it
+            None => return Some(String::new()),
+        }
+    }
+
+    (!names.is_empty()).then(|| {
+        names.reverse();
+        names.join(".")
+    })
+}
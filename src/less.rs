@@ -0,0 +1,427 @@
+//! Support for LESS documents.
+//!
+//! LESS documents are masked before parsing like SCSS ones, see `scss.rs`, with the same scanner. Their constructs are
+//! recorded as their SCSS equivalent: `@variables` as variables, mixin definitions and calls as mixins and includes,
+//! `:extend()` as extends, guards and `@plugin` as directives and `@{…}` as interpolations.
+
+use crate::{
+    parser::CssDialect,
+    scss::{is_ident_start, Scanner, ScssConstructKind, ScssScan},
+};
+
+/// Scans a LESS document for its LESS-only constructs, and masks them so that the document can be parsed as CSS.
+///
+/// # Arguments
+///
+/// * `code` - A string slice that holds the LESS code to scan.
+///
+/// # Returns
+///
+/// * The masked code, and the constructs found in it.
+pub fn scan_less(code: &str) -> ScssScan {
+    let mut scanner = Scanner::new(code, CssDialect::Less);
+    let at_keywords = scanner.scan_less_statements();
+    scanner.scan_less_variable_references(&at_keywords);
+    scanner.finish()
+}
+
+impl Scanner<'_> {
+    /// Scan the statements of a LESS document, returning the start of the at-keywords and variable declarations, which
+    /// aren't variable references.
+    fn scan_less_statements(&mut self) -> Vec<usize> {
+        let mut at_keywords = Vec::new();
+        let mut i = 0;
+        loop {
+            i = self.skip_trivia(i);
+            let Some(&byte) = self.bytes.get(i) else {
+                break;
+            };
+
+            i = match byte {
+                b'{' | b'}' | b';' => i + 1,
+                b'@' if !self.is_interpolation(i) => {
+                    at_keywords.push(i);
+                    self.less_at_rule(i)
+                }
+                _ => self.less_rule_or_declaration(i),
+            };
+        }
+        at_keywords
+    }
+
+    fn less_at_rule(&mut self, start: usize) -> usize {
+        let name_end = self.ident_end(start + 1);
+        let name = &self.code[start + 1..name_end];
+        let end = self.statement_end(start);
+        let range_end = self.trim_end(start, end);
+        let next = self.skip_trivia(name_end);
+
+        // `@page :first` is the only at-rule whose prelude can start with a colon
+        if self.bytes.get(next) == Some(&b':') && name != "page" {
+            if self.bytes.get(end) != Some(&b'{') {
+                return self.variable_declaration(start);
+            }
+            // Detached rulesets (e.g. `@detached: { color: red; }`) become a nested rule, so that their declarations are
+            // still parsed
+            self.push(
+                ScssConstructKind::Variable,
+                (start, name_end),
+                (start, range_end),
+                None,
+            );
+            self.masked[start] = b'&';
+            self.blank(start + 1, end);
+            // The semicolon which can follow their block isn't valid after a rule
+            let after_block = self.skip_trivia(self.block_end(end));
+            if self.bytes.get(after_block) == Some(&b';') {
+                self.blank(after_block, after_block + 1);
+            }
+            return end;
+        }
+
+        let kind = if self.code[name_end..].starts_with("()") {
+            // Calls of detached rulesets, e.g. `@detached();`
+            ScssConstructKind::Include
+        } else if name == "plugin" || (name == "import" && self.bytes.get(next) == Some(&b'(')) {
+            // `@plugin` and the options of `@import`, e.g. `@import (reference) "mixins";`
+            ScssConstructKind::Directive
+        } else {
+            self.mask_prelude(name_end, end);
+            return end;
+        };
+        let name = match kind {
+            ScssConstructKind::Include => (start, name_end),
+            _ => (start + 1, name_end),
+        };
+        self.push(kind, name, (start, range_end), None);
+        self.blank(start, self.terminated_end(end));
+        end
+    }
+
+    fn less_rule_or_declaration(&mut self, start: usize) -> usize {
+        let end = self.statement_end(start);
+        let range_end = self.trim_end(start, end);
+
+        if self.bytes.get(end) == Some(&b'{') {
+            self.mask_less_prelude(start, end);
+        } else if matches!(self.bytes[start], b'.' | b'#') {
+            // Mixin calls, e.g. `.button();`, `.button;` or `#namespace > .button(@size) !important;`
+            let name_end = self.code[start..range_end]
+                .find(['(', '!', ';'])
+                .map_or(range_end, |name_end| self.trim_end(start, start + name_end));
+            self.push(
+                ScssConstructKind::Include,
+                (start, name_end),
+                (start, range_end),
+                None,
+            );
+            self.blank(start, self.terminated_end(end));
+        } else if self.code[start..range_end].starts_with("&:extend(") {
+            // Extends of the parent selector, e.g. `&:extend(.button all);`
+            self.mask_extends(start, range_end);
+            self.blank(start, self.terminated_end(end));
+        } else {
+            // Merged properties, e.g. `box-shadow+: inset 0 0 10px #555;` or `transform+_: scale(2);`
+            let name_end = self.ident_end(start);
+            for merge in ["+_:", "+:"] {
+                if self.code[name_end..range_end].starts_with(merge) {
+                    self.blank(name_end, name_end + merge.len() - 1);
+                    break;
+                }
+            }
+            self.mask_values(start, end);
+        }
+        end
+    }
+
+    /// Mask the LESS constructs of the prelude of a rule in the given range: the parameters of mixin definitions,
+    /// guards, extends, interpolations and variables.
+    fn mask_less_prelude(&mut self, start: usize, end: usize) {
+        let range_end = self.trim_end(start, end);
+
+        // The selector ends with the parameters of a mixin definition, e.g. `.button(@size; @color: red)`, or a guard
+        let mut selector_end = self.guard_start(start, range_end).unwrap_or(range_end);
+        if matches!(self.bytes[start], b'.' | b'#') {
+            let name_end = self.ident_end(start + 1);
+            if name_end > start + 1 && name_end < selector_end && self.bytes[name_end] == b'(' {
+                self.push(
+                    ScssConstructKind::Mixin,
+                    (start, name_end),
+                    (start, range_end),
+                    None,
+                );
+                selector_end = name_end;
+            }
+        }
+        if let Some(guard_start) = self.guard_start(start, range_end) {
+            self.push(
+                ScssConstructKind::Directive,
+                (guard_start, guard_start + "when".len()),
+                (guard_start, range_end),
+                None,
+            );
+        }
+
+        self.mask_prelude(start, selector_end);
+        self.mask_extends(start, selector_end);
+        self.blank(selector_end, end);
+    }
+
+    /// Find the end of the block opening at `start`, after its closing brace.
+    fn block_end(&self, start: usize) -> usize {
+        let mut depth = 0usize;
+        let mut i = start;
+        while i < self.bytes.len() {
+            match self.bytes[i] {
+                b'"' | b'\'' => {
+                    i = self.string_end(i);
+                    continue;
+                }
+                b'/' if self.bytes[i..].starts_with(b"/*") || self.is_line_comment(i, 0) => {
+                    i = self.comment_end(i);
+                    continue;
+                }
+                b'{' => depth += 1,
+                b'}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return i + 1;
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        self.bytes.len()
+    }
+
+    /// Find the `when` keyword starting the guard of a mixin definition or rule in the given range, e.g.
+    /// `.button when (@mode = dark)`.
+    fn guard_start(&self, start: usize, end: usize) -> Option<usize> {
+        let mut depth = 0usize;
+        let mut i = start;
+        while i < end {
+            match self.bytes[i] {
+                b'"' | b'\'' => {
+                    i = self.string_end(i);
+                    continue;
+                }
+                b'(' => depth += 1,
+                b')' => depth = depth.saturating_sub(1),
+                byte if depth == 0 && is_ident_start(byte) => {
+                    let ident_end = self.ident_end(i);
+                    if &self.code[i..ident_end] == "when"
+                        && i > start
+                        && self.bytes[i - 1].is_ascii_whitespace()
+                        && (ident_end == end
+                            || matches!(self.bytes[ident_end], b'(' | b' ' | b'\t' | b'\n' | b'\r'))
+                    {
+                        return Some(i);
+                    }
+                    i = ident_end;
+                    continue;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        None
+    }
+
+    /// Record and blank the extends in the given range, e.g. `:extend(.button all)`.
+    fn mask_extends(&mut self, start: usize, end: usize) {
+        let mut from = start;
+        while let Some(offset) = self.code[from..end].find(":extend(") {
+            let extend_start = from + offset;
+            let argument_start = extend_start + ":extend(".len();
+            let close = self.code[argument_start..end]
+                .find(')')
+                .map_or(end, |close| argument_start + close);
+            let extend_end = (close + 1).min(end);
+
+            let mut argument_end = self.trim_end(argument_start, close);
+            if self.code[argument_start..argument_end].ends_with(" all") {
+                argument_end = self.trim_end(argument_start, argument_end - "all".len());
+            }
+            self.push(
+                ScssConstructKind::Extend,
+                (argument_start, argument_end),
+                (extend_start, extend_end),
+                None,
+            );
+            self.blank(extend_start, extend_end);
+            from = extend_end;
+        }
+    }
+
+    /// Mask the variable at `start`, e.g. `@size` or `@@name`, as an identifier. Returns the end of the variable.
+    pub(crate) fn mask_less_variable(&mut self, start: usize) -> usize {
+        let mut name_start = start + 1;
+        if self.bytes.get(name_start) == Some(&b'@') {
+            name_start += 1;
+        }
+        if !self
+            .bytes
+            .get(name_start)
+            .is_some_and(|&byte| is_ident_start(byte))
+        {
+            // A lone `@` is being typed, and makes the parser panic
+            self.blank(start, name_start);
+            return name_start;
+        }
+        self.masked[start..name_start].fill(b'_');
+        self.ident_end(name_start)
+    }
+
+    /// Find the variables used outside of their declaration, anywhere in the document but in comments and strings.
+    fn scan_less_variable_references(&mut self, at_keywords: &[usize]) {
+        let mut i = 0;
+        while i < self.bytes.len() {
+            match self.bytes[i] {
+                b'"' | b'\'' => {
+                    i = self.string_end(i);
+                    continue;
+                }
+                b'/' if self.bytes[i..].starts_with(b"/*") || self.is_line_comment(i, 0) => {
+                    i = self.comment_end(i);
+                    continue;
+                }
+                // The name of variable variables, e.g. `@name` in `@@name`, is the variable used
+                b'@' if self.bytes.get(i + 1) == Some(&b'@') => i += 1,
+                b'@' if self
+                    .bytes
+                    .get(i + 1)
+                    .is_some_and(|&byte| is_ident_start(byte)) =>
+                {
+                    let end = self.ident_end(i + 1);
+                    let after_ident = i
+                        .checked_sub(1)
+                        .is_some_and(|previous| self.bytes[previous].is_ascii_alphanumeric());
+                    if !at_keywords.contains(&i) && !after_ident {
+                        self.push(
+                            ScssConstructKind::VariableReference,
+                            (i, end),
+                            (i, end),
+                            None,
+                        );
+                    }
+                    i = end;
+                    continue;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_css_with_dialect;
+    use biome_rowan::TextRange;
+
+    const LESS: &str = r#"@import (reference) "mixins";
+// Colors
+@primary: #ff0000;
+@spacing: 4px;
+@selector: ~".card";
+@detached: { background: red; };
+
+.button(@size; @color: @primary) when (iscolor(@color)) {
+  padding: (@spacing / 2) @size * 2;
+  width: ~"calc(100% - @{spacing})";
+  color: @@name;
+}
+
+.@{prefix}-title:extend(.title all) {
+  .button(2px);
+  #namespace > .mixin() !important;
+  &:extend(.card);
+  box-shadow+: inset 0 0 10px #555;
+  @detached();
+  @media @phone { .icon { margin: -@spacing; } }
+}
+"#;
+
+    #[test]
+    fn test_scan_less_masks_to_css() {
+        let scan = scan_less(LESS);
+
+        assert_eq!(scan.masked.len(), LESS.len());
+        assert!(
+            !parse_css_with_dialect(LESS, CssDialect::Less).has_errors(),
+            "Masked LESS should parse without errors, got:\n{}",
+            scan.masked
+        );
+    }
+
+    #[test]
+    fn test_scan_less_constructs() {
+        let constructs = scan_less(LESS)
+            .constructs
+            .into_iter()
+            .filter(|construct| construct.kind != ScssConstructKind::VariableReference)
+            .map(|construct| (construct.kind, construct.name))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            constructs,
+            vec![
+                (ScssConstructKind::Directive, "import".to_string()),
+                (ScssConstructKind::Variable, "@primary".to_string()),
+                (ScssConstructKind::Variable, "@spacing".to_string()),
+                (ScssConstructKind::Variable, "@selector".to_string()),
+                (ScssConstructKind::Variable, "@detached".to_string()),
+                (ScssConstructKind::Mixin, ".button".to_string()),
+                (ScssConstructKind::Directive, "when".to_string()),
+                (ScssConstructKind::Interpolation, "@{prefix}".to_string()),
+                (ScssConstructKind::Extend, ".title".to_string()),
+                (ScssConstructKind::Include, ".button".to_string()),
+                (
+                    ScssConstructKind::Include,
+                    "#namespace > .mixin".to_string()
+                ),
+                (ScssConstructKind::Extend, ".card".to_string()),
+                (ScssConstructKind::Include, "@detached".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_less_variables() {
+        let scan = scan_less("@primary: #ff0000;\na { color: @primary; }\n@media screen {}");
+
+        let declaration = &scan.constructs[0];
+        assert_eq!(declaration.kind, ScssConstructKind::Variable);
+        assert_eq!(
+            declaration.value,
+            Some((TextRange::new(10.into(), 17.into()), "#ff0000".to_string()))
+        );
+
+        let references = scan.constructs[1..]
+            .iter()
+            .map(|construct| {
+                (
+                    construct.kind,
+                    construct.name.as_str(),
+                    construct.name_range,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            references,
+            vec![(
+                ScssConstructKind::VariableReference,
+                "@primary",
+                TextRange::new(30.into(), 38.into())
+            )]
+        );
+    }
+
+    #[test]
+    fn test_scan_less_keeps_css() {
+        let code = "@media (min-width: 10px) { a { margin: +1px 50%; background: url(a@2x.png); } }\n@page :first {}";
+        assert_eq!(scan_less(code).masked, code);
+    }
+}
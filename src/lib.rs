@@ -1,15 +1,98 @@
 pub mod analyzer;
 pub mod parser;
 
+mod container_queries;
+#[cfg(feature = "diagnostics")]
+mod contrast;
+mod counter_styles;
+mod custom_properties;
+mod descriptors;
+#[cfg(feature = "completion")]
+mod emmet;
+#[cfg(any(feature = "diagnostics", feature = "document-links"))]
+mod font_faces;
+mod keyframes;
+mod layers;
+#[cfg(any(feature = "completion", feature = "diagnostics"))]
+mod pages;
+#[cfg(feature = "diagnostics")]
+mod properties;
+mod recovery;
+#[cfg(feature = "diagnostics")]
+mod registered_properties;
+
+pub mod cancellation;
+pub mod capabilities;
+#[cfg(feature = "cli")]
+pub mod cli;
+pub mod commands;
+pub mod data;
+pub mod design_tokens;
+pub mod directives;
+pub mod error;
+pub mod extensions;
+
+pub use error::Error;
+
+// The syntax trees returned by csslsrs are built with these crates, re-exported so that embedders analyzing them use
+// the same versions.
+pub use biome_css_syntax;
+pub use biome_rowan;
+
+pub mod file_system;
+pub mod icss;
+pub mod instrumentation;
+pub mod json_rpc;
+pub mod less;
+pub mod progress;
+pub mod scss;
+pub mod selectors;
+
+#[cfg(feature = "server")]
+pub mod server;
 pub mod service;
+pub mod settings;
 pub mod store;
+pub mod virtual_document;
+pub mod visitor;
+pub mod workspace_index;
 
 pub mod features {
+    #[cfg(feature = "code-actions")]
+    pub mod code_actions;
+    #[cfg(feature = "colors")]
     pub mod colors;
+    #[cfg(feature = "completion")]
+    pub mod completion;
+    #[cfg(feature = "definition")]
+    pub mod definition;
+    #[cfg(feature = "diagnostics")]
+    pub mod diagnostics;
+    #[cfg(feature = "document-links")]
+    pub mod document_links;
+    #[cfg(feature = "document-symbols")]
+    pub mod document_symbols;
+    #[cfg(feature = "folding")]
     pub mod folding;
+    #[cfg(feature = "hover")]
+    pub mod hover;
+    #[cfg(feature = "inlay-hints")]
+    pub mod inlay_hints;
+    #[cfg(feature = "rename")]
+    pub mod rename;
+    #[cfg(feature = "semantic-tokens")]
+    pub mod semantic_tokens;
+    #[cfg(feature = "signature-help")]
+    pub mod signature_help;
+    #[cfg(feature = "workspace-symbols")]
+    pub mod workspace_symbols;
 }
 
 #[cfg(feature = "wasm")]
 pub mod wasm_text_document;
 
 pub mod converters;
+
+/// Documents larger than this, in bytes, are analyzed on multiple threads when the `parallel` feature is enabled.
+#[cfg(feature = "parallel")]
+pub(crate) const PARALLEL_THRESHOLD: usize = 64 * 1024;
@@ -0,0 +1,127 @@
+//! Helpers for the `@page` rules of print stylesheets, with their page selectors, e.g. `@page :first`, and the margin
+//! boxes nested in them, e.g. `@top-center`, shared by the features completing, outlining and checking them.
+
+use biome_css_syntax::{CssLanguage, CssSyntaxKind};
+use biome_rowan::SyntaxNode;
+
+use crate::descriptors::enclosing_block;
+
+/// The pseudo-classes of page selectors, with their description.
+#[cfg(feature = "completion")]
+pub(crate) const PAGE_PSEUDO_CLASSES: &[(&str, &str)] = &[
+    ("first", "The first page of the document."),
+    ("left", "The left pages of a double-sided document, e.g. the even pages of a left-to-right document."),
+    ("right", "The right pages of a double-sided document, e.g. the odd pages of a left-to-right document."),
+    ("blank", "The pages left blank by a forced page break, e.g. before a chapter starting on a right page."),
+];
+
+/// The margin boxes of a page, from its top left corner clockwise.
+#[cfg(feature = "completion")]
+pub(crate) const MARGIN_RULES: &[&str] = &[
+    "top-left-corner",
+    "top-left",
+    "top-center",
+    "top-right",
+    "top-right-corner",
+    "right-top",
+    "right-middle",
+    "right-bottom",
+    "bottom-right-corner",
+    "bottom-right",
+    "bottom-center",
+    "bottom-left",
+    "bottom-left-corner",
+    "left-bottom",
+    "left-middle",
+    "left-top",
+];
+
+/// The properties, or their prefixes ending with `-`, that apply to the page context, per CSS Paged Media.
+const PAGE_PROPERTIES: &[&str] = &[
+    "background",
+    "background-",
+    "border",
+    "border-",
+    "box-decoration-break",
+    "box-shadow",
+    "color",
+    "counter-",
+    "direction",
+    "font",
+    "font-",
+    "letter-spacing",
+    "line-height",
+    "margin",
+    "margin-",
+    "outline",
+    "outline-",
+    "padding",
+    "padding-",
+    "quotes",
+    "text-",
+    "visibility",
+    "white-space",
+    "word-spacing",
+    "z-index",
+];
+
+/// The properties that only apply to margin boxes, which are laid out and generate content.
+const MARGIN_BOX_PROPERTIES: &[&str] = &[
+    "content",
+    "height",
+    "max-height",
+    "max-width",
+    "min-height",
+    "min-width",
+    "vertical-align",
+    "width",
+];
+
+/// Whether a property applies to the page context, either to pages or to their margin boxes. Custom properties and
+/// vendor-prefixed ones are always accepted.
+pub(crate) fn is_page_context_property(name: &str, in_margin_box: bool) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.starts_with('-')
+        || PAGE_PROPERTIES.iter().any(|property| {
+            name == *property || (property.ends_with('-') && name.starts_with(property))
+        })
+        || (in_margin_box && MARGIN_BOX_PROPERTIES.contains(&name.as_str()))
+}
+
+/// Get the name of the `@page` rule or margin box whose block directly contains a node, including its `@`, e.g.
+/// `@top-center` for the declarations of `@top-center { … }` in `@page { … }`.
+///
+/// # Returns
+///
+/// * The name of the rule, or `None` if the node isn't in the page context.
+pub(crate) fn page_context(node: &SyntaxNode<CssLanguage>) -> Option<String> {
+    let block = enclosing_block(node)?;
+    let rule = block.parent()?;
+    match (block.kind(), rule.kind()) {
+        (CssSyntaxKind::CSS_PAGE_AT_RULE_BLOCK, CssSyntaxKind::CSS_PAGE_AT_RULE) => {
+            Some("@page".to_string())
+        }
+        (CssSyntaxKind::CSS_DECLARATION_OR_AT_RULE_BLOCK, CssSyntaxKind::CSS_MARGIN_AT_RULE) => {
+            // The keyword of the margin box follows its `@`
+            let keyword = rule.first_token()?.next_token()?;
+            Some(format!("@{}", keyword.text_trimmed().to_ascii_lowercase()))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_page_context_property() {
+        assert!(is_page_context_property("margin-top", false));
+        assert!(is_page_context_property("Font", false));
+        assert!(is_page_context_property("--gutter", false));
+        assert!(!is_page_context_property("display", false));
+        assert!(!is_page_context_property("fontsize", false));
+        assert!(!is_page_context_property("content", false));
+        assert!(is_page_context_property("content", true));
+    }
+}
@@ -1,4 +1,93 @@
+use std::borrow::Cow;
+
 use biome_css_parser::{parse_css as biome_parse_css, CssParse, CssParserOptions};
+use biome_rowan::{AstNode, NodeOrToken, TextRange};
+use lsp_types::{TextDocumentItem, Uri};
+
+use crate::{
+    directives::{has_unparsed_at_rules, scan_custom_directives},
+    icss::scan_icss,
+    instrumentation::span,
+    less::scan_less,
+    recovery::{mask_invalid_hex_colors, recover},
+    scss::scan_scss,
+};
+
+/// The dialect of a stylesheet. The parser only understands standard CSS, but is configured to be
+/// more tolerant of the syntax of the other dialects (e.g. `//` comments), and the constructs
+/// only found in SCSS and LESS documents are masked before parsing, see `scss::scan_scss` and
+/// `less::scan_less`, as are the `@value` statements of CSS Modules, see `icss::scan_icss`. The
+/// custom directives of PostCSS plugins and frameworks, and the standard at-rules the parser
+/// doesn't know yet, e.g. `@view-transition`, are masked in every dialect, see
+/// `directives::scan_custom_directives`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CssDialect {
+    #[default]
+    Css,
+    Scss,
+    Less,
+}
+
+impl CssDialect {
+    /// Detects the dialect of a document from its language identifier, falling back to the
+    /// extension of its URI when the language identifier is not a known stylesheet language.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document to detect the dialect of.
+    ///
+    /// # Returns
+    ///
+    /// * The dialect of the document, `CssDialect::Css` if it could not be detected.
+    pub fn from_document(document: &TextDocumentItem) -> Self {
+        Self::from_language_id(&document.language_id)
+            .unwrap_or_else(|| Self::from_uri(&document.uri))
+    }
+
+    /// Detects the dialect of a file from the extension of its URI.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the file.
+    ///
+    /// # Returns
+    ///
+    /// * The dialect of the file, `CssDialect::Css` if the extension is not a known stylesheet extension.
+    pub fn from_uri(uri: &Uri) -> Self {
+        uri.path()
+            .as_str()
+            .rsplit_once('.')
+            .and_then(|(_, extension)| Self::from_language_id(extension))
+            .unwrap_or_default()
+    }
+
+    /// The language identifier of the dialect, as used by LSP clients.
+    pub fn language_id(self) -> &'static str {
+        match self {
+            CssDialect::Css => "css",
+            CssDialect::Scss => "scss",
+            CssDialect::Less => "less",
+        }
+    }
+
+    fn from_language_id(language_id: &str) -> Option<Self> {
+        match language_id.to_ascii_lowercase().as_str() {
+            "css" => Some(CssDialect::Css),
+            "scss" => Some(CssDialect::Scss),
+            "less" => Some(CssDialect::Less),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn parser_options(self) -> CssParserOptions {
+        match self {
+            CssDialect::Css => CssParserOptions::default(),
+            CssDialect::Scss | CssDialect::Less => {
+                CssParserOptions::default().allow_wrong_line_comments()
+            }
+        }
+    }
+}
 
 /// Parses CSS code and returns the parse result.
 ///
@@ -10,11 +99,157 @@ use biome_css_parser::{parse_css as biome_parse_css, CssParse, CssParserOptions}
 ///
 /// * A `CssParse` struct containing the syntax tree and diagnostics.
 pub fn parse_css(code: &str) -> CssParse {
-    // Create parser options
-    let options = CssParserOptions::default();
+    parse_css_with_dialect(code, CssDialect::Css)
+}
 
-    // Parse the CSS code
-    biome_parse_css(code, options)
+/// Parses code written in the given dialect and returns the parse result.
+///
+/// # Arguments
+///
+/// * `code` - A string slice that holds the code to parse.
+/// * `dialect` - The dialect the code is written in.
+///
+/// # Returns
+///
+/// * A `CssParse` struct containing the syntax tree and diagnostics.
+pub fn parse_css_with_dialect(code: &str, dialect: CssDialect) -> CssParse {
+    parse_css_with_directives(code, dialect, &[])
+}
+
+/// Parses code written in the given dialect, using the custom at-rules and functions of PostCSS plugins and frameworks
+/// (e.g. Tailwind's `@apply`), and returns the parse result. The custom directives are masked before parsing, see
+/// `directives::scan_custom_directives`. The syntax errors of unfinished edits, e.g. an unclosed function, are repaired
+/// so that they don't swallow the rest of the document, see `recovery::recover`, but are still reported. The blocks of
+/// the conditional rules nested in style rules, whose declarations the parser doesn't expect, are parsed again as the
+/// blocks of style rules. The hashes of values that aren't hex colors are blanked, see
+/// `recovery::mask_invalid_hex_colors`.
+///
+/// # Arguments
+///
+/// * `code` - A string slice that holds the code to parse.
+/// * `dialect` - The dialect the code is written in.
+/// * `custom_directives` - The entries of the `css.customDirectives` setting, e.g. `apply` or `theme()`.
+///
+/// # Returns
+///
+/// * A `CssParse` struct containing the syntax tree and diagnostics.
+pub fn parse_css_with_directives(
+    code: &str,
+    dialect: CssDialect,
+    custom_directives: &[String],
+) -> CssParse {
+    span!("parse", size = code.len());
+    let masked = match dialect {
+        CssDialect::Css if code.contains("@value") => Cow::Owned(scan_icss(code).masked),
+        CssDialect::Css => Cow::Borrowed(code),
+        CssDialect::Scss => Cow::Owned(scan_scss(code).masked),
+        CssDialect::Less => Cow::Owned(scan_less(code).masked),
+    };
+    let mut masked = if custom_directives.is_empty() && !has_unparsed_at_rules(&masked) {
+        masked
+    } else {
+        Cow::Owned(scan_custom_directives(&masked, dialect, custom_directives).masked)
+    };
+    if let Some(hex_colors) = mask_invalid_hex_colors(&masked, dialect) {
+        masked = Cow::Owned(hex_colors);
+    }
+
+    let parse = biome_parse_css(&masked, dialect.parser_options());
+    if parse.has_errors() {
+        recover(&masked, parse, dialect)
+    } else {
+        parse
+    }
+}
+
+/// Dumps a syntax tree in a human-readable form, with the kind and range of every node and token. Meant for debugging
+/// and for exploring the tree before writing analyses on it, the format isn't stable.
+///
+/// # Arguments
+///
+/// * `parse` - The parse result to dump.
+///
+/// # Returns
+///
+/// * A string with one line per node and token, indented by depth.
+pub fn debug_syntax_tree(parse: &CssParse) -> String {
+    format!("{:#?}", parse.syntax())
+}
+
+/// Reparses CSS code after an edit, reusing the previous syntax tree when possible.
+///
+/// When the edit is contained in a single top-level rule, only that rule is reparsed and spliced
+/// into the previous tree. Otherwise, or if the edit changes the structure of the document (e.g.
+/// by adding a new rule or introducing a syntax error), the whole code is parsed again.
+///
+/// # Arguments
+///
+/// * `previous` - The `CssParse` of the code before the edit.
+/// * `edit` - The range of the code before the edit that was replaced.
+/// * `code` - A string slice that holds the CSS code after the edit.
+/// * `dialect` - The dialect the code is written in.
+/// * `custom_directives` - The custom directives the code was parsed with, see `parse_css_with_directives`.
+///
+/// # Returns
+///
+/// * A `CssParse` struct equivalent to the result of `parse_css_with_directives(code, dialect, custom_directives)`.
+pub fn reparse_css(
+    previous: &CssParse,
+    edit: TextRange,
+    code: &str,
+    dialect: CssDialect,
+    custom_directives: &[String],
+) -> CssParse {
+    span!("reparse", size = code.len());
+    reparse_rule(previous, edit, code, dialect, custom_directives)
+        .unwrap_or_else(|| parse_css_with_directives(code, dialect, custom_directives))
+}
+
+fn reparse_rule(
+    previous: &CssParse,
+    edit: TextRange,
+    code: &str,
+    dialect: CssDialect,
+    custom_directives: &[String],
+) -> Option<CssParse> {
+    // Diagnostics can't be moved along with the nodes they refer to, so only trees without any
+    // are reused. The reparsed rule must be free of diagnostics as well, for the same reason.
+    if !previous.diagnostics().is_empty() {
+        return None;
+    }
+
+    let rules = previous.tree().rules().syntax().clone();
+    let rule = rules.children().find(|rule| {
+        rule.text_range().start() < edit.start() && edit.end() < rule.text_range().end()
+    })?;
+
+    let previous_length = usize::from(previous.syntax().text_range().len());
+    let start = usize::from(rule.text_range().start());
+    let end = (usize::from(rule.text_range().end()) + code.len()).checked_sub(previous_length)?;
+    let rule_code = code.get(start..end)?;
+
+    let reparsed = parse_css_with_directives(rule_code, dialect, custom_directives);
+    if !reparsed.diagnostics().is_empty() {
+        return None;
+    }
+
+    let mut reparsed_rules = reparsed.tree().rules().syntax().children();
+    let reparsed_rule = reparsed_rules.next()?;
+
+    // The edit must not have split the rule in several, or merged it with the trivia around it
+    if reparsed_rules.next().is_some()
+        || reparsed_rule.kind() != rule.kind()
+        || usize::from(reparsed_rule.text_range().len()) != rule_code.len()
+    {
+        return None;
+    }
+
+    // Replacing the rule from the root gives a new root, replacing it from its parent would give a detached list
+    let root = previous
+        .syntax()
+        .replace_child(NodeOrToken::Node(rule), NodeOrToken::Node(reparsed_rule))?;
+
+    Some(CssParse::new(root, Vec::new()))
 }
 
 #[cfg(test)]
@@ -31,10 +266,127 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_debug_syntax_tree() {
+        let dump = debug_syntax_tree(&parse_css("a { color: red; }"));
+        assert!(dump.starts_with("0: CSS_ROOT@0..17"));
+        assert!(dump.contains("CSS_QUALIFIED_RULE@0..17"));
+        assert!(dump.contains("IDENT@4..9 \"color\""));
+    }
+
     #[test]
     fn test_parse_invalid_css() {
         let code = "body { margin }";
         let parse = parse_css(code);
         assert!(parse.has_errors(), "Parsing invalid CSS should have errors");
     }
+
+    #[test]
+    fn test_reparse_css_single_rule() {
+        let code = "a { color: red; }\nb { color: blue; }\n";
+        let parse = parse_css(code);
+
+        let edit = TextRange::new(11.into(), 14.into());
+        let new_code = "a { color: green; }\nb { color: blue; }\n";
+
+        assert!(
+            reparse_rule(&parse, edit, new_code, CssDialect::Css, &[]).is_some(),
+            "Edits inside a single rule should be reparsed incrementally"
+        );
+        assert_eq!(
+            format!(
+                "{:#?}",
+                reparse_css(&parse, edit, new_code, CssDialect::Css, &[]).syntax()
+            ),
+            format!("{:#?}", parse_css(new_code).syntax()),
+            "Incremental reparse should match a full parse"
+        );
+    }
+
+    #[test]
+    fn test_reparse_css_structural_change() {
+        let code = "a { color: red; }\nb { color: blue; }\n";
+        let parse = parse_css(code);
+
+        // Closing the first rule early, moving its declaration outside of it
+        let edit = TextRange::new(3.into(), 3.into());
+        let new_code = "a {} color: red; }\nb { color: blue; }\n";
+
+        assert!(
+            reparse_rule(&parse, edit, new_code, CssDialect::Css, &[]).is_none(),
+            "Edits changing the structure of the document should fall back to a full parse"
+        );
+        assert_eq!(
+            format!(
+                "{:#?}",
+                reparse_css(&parse, edit, new_code, CssDialect::Css, &[]).syntax()
+            ),
+            format!("{:#?}", parse_css(new_code).syntax()),
+            "Fallback reparse should match a full parse"
+        );
+    }
+
+    #[test]
+    fn test_dialect_from_document() {
+        use std::str::FromStr;
+
+        let document = |uri: &str, language_id: &str| TextDocumentItem {
+            uri: lsp_types::Uri::from_str(uri).unwrap(),
+            language_id: language_id.to_string(),
+            version: 1,
+            text: String::new(),
+        };
+
+        assert_eq!(
+            CssDialect::from_document(&document("file:///a.css", "scss")),
+            CssDialect::Scss
+        );
+        assert_eq!(
+            CssDialect::from_document(&document("file:///a.less", "unknown")),
+            CssDialect::Less
+        );
+        assert_eq!(
+            CssDialect::from_document(&document("file:///a.txt", "plaintext")),
+            CssDialect::Css
+        );
+    }
+
+    #[test]
+    fn test_parse_scss_constructs() {
+        let code = "$gap: 4px;\n.a { margin: $gap * 2; @include b; &:hover { color: red; } }";
+        assert!(parse_css(code).has_errors());
+        assert!(
+            !parse_css_with_dialect(code, CssDialect::Scss).has_errors(),
+            "SCSS constructs are masked before parsing"
+        );
+    }
+
+    #[test]
+    fn test_parse_less_constructs() {
+        let code = "@gap: 4px;\n.m(@a) { margin: @a; }\n.a { .m(@gap * 2); &:extend(.b); }";
+        assert!(parse_css(code).has_errors());
+        assert!(
+            !parse_css_with_dialect(code, CssDialect::Less).has_errors(),
+            "LESS constructs are masked before parsing"
+        );
+    }
+
+    #[test]
+    fn test_parse_icss_values() {
+        let code = "@value primary: #bf4040;\n@value small from \"./breakpoints.css\";\n.a { color: primary; }";
+        assert!(
+            !parse_css(code).has_errors(),
+            "@value statements are masked before parsing"
+        );
+    }
+
+    #[test]
+    fn test_parse_scss_line_comments() {
+        let code = "// comment\nbody { margin: 0; }";
+        assert!(parse_css(code).has_errors());
+        assert!(
+            !parse_css_with_dialect(code, CssDialect::Scss).has_errors(),
+            "Line comments are allowed in SCSS"
+        );
+    }
 }
@@ -0,0 +1,176 @@
+//! Progress reporting and partial results for requests that can take a while on very large or minified files.
+//!
+//! `ProgressReporter` maps to LSP work done progress (`$/progress` with `WorkDoneProgressBegin`, `Report` and `End`),
+//! while features streaming partial results take a callback receiving batches of results, to be sent with the
+//! request's `partialResultToken`.
+
+/// Receives the progress of a request. Every method does nothing by default.
+pub trait ProgressReporter {
+    /// Called once, before the request starts.
+    fn begin(&mut self, _title: &str) {}
+
+    /// Called as the request advances, with a percentage between 0 and 100.
+    fn report(&mut self, _percentage: u32) {}
+
+    /// Same as `report`, with a message describing how far the request is, e.g. `120/3400 files`. Calls `report` by
+    /// default.
+    fn report_message(&mut self, percentage: u32, _message: &str) {
+        self.report(percentage);
+    }
+
+    /// Called once, after the request completed or was cancelled.
+    fn end(&mut self) {}
+}
+
+/// A ProgressReporter ignoring all progress.
+impl ProgressReporter for () {}
+
+/// Forwards the reports of a step of a longer operation, without its `begin` and `end`, so that the operation is
+/// reported as a whole, e.g. indexing several folders.
+pub(crate) struct StepProgress<'a>(pub(crate) &'a mut dyn ProgressReporter);
+
+impl ProgressReporter for StepProgress<'_> {
+    fn report(&mut self, percentage: u32) {
+        self.0.report(percentage);
+    }
+
+    fn report_message(&mut self, percentage: u32, message: &str) {
+        self.0.report_message(percentage, message);
+    }
+}
+
+/// Reports the progress through a known number of items, e.g. the files of a folder, whenever its percentage changes.
+pub(crate) struct ItemProgress<'a> {
+    progress: &'a mut dyn ProgressReporter,
+    /// What the items are, e.g. `files`.
+    unit: &'static str,
+    total: usize,
+    done: usize,
+    last_percentage: Option<u32>,
+}
+
+impl<'a> ItemProgress<'a> {
+    pub(crate) fn new(
+        progress: &'a mut dyn ProgressReporter,
+        unit: &'static str,
+        total: usize,
+    ) -> Self {
+        Self {
+            progress,
+            unit,
+            total,
+            done: 0,
+            last_percentage: None,
+        }
+    }
+
+    /// Count one more item as done.
+    pub(crate) fn advance(&mut self) {
+        self.done += 1;
+
+        let percentage = (self.done * 100 / self.total.max(1)).min(100) as u32;
+        if self.last_percentage != Some(percentage) {
+            self.last_percentage = Some(percentage);
+            self.progress.report_message(
+                percentage,
+                &format!("{}/{} {}", self.done, self.total, self.unit),
+            );
+        }
+    }
+}
+
+/// The number of results sent per batch by streaming features, unless specified otherwise.
+pub const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// Collects results into batches of a given size, passing each full batch to a callback.
+#[cfg_attr(
+    not(any(feature = "colors", feature = "diagnostics")),
+    allow(dead_code)
+)]
+pub(crate) struct Batcher<'a, T> {
+    batch: Vec<T>,
+    batch_size: usize,
+    on_batch: &'a mut dyn FnMut(Vec<T>),
+}
+
+#[cfg_attr(
+    not(any(feature = "colors", feature = "diagnostics")),
+    allow(dead_code)
+)]
+impl<'a, T> Batcher<'a, T> {
+    pub(crate) fn new(batch_size: usize, on_batch: &'a mut dyn FnMut(Vec<T>)) -> Self {
+        let batch_size = batch_size.max(1);
+
+        Self {
+            batch: Vec::with_capacity(batch_size),
+            batch_size,
+            on_batch,
+        }
+    }
+
+    pub(crate) fn extend(&mut self, items: impl IntoIterator<Item = T>) {
+        for item in items {
+            self.batch.push(item);
+            if self.batch.len() == self.batch_size {
+                (self.on_batch)(std::mem::replace(
+                    &mut self.batch,
+                    Vec::with_capacity(self.batch_size),
+                ));
+            }
+        }
+    }
+
+    /// Pass the last, possibly incomplete, batch to the callback.
+    pub(crate) fn flush(self) {
+        if !self.batch.is_empty() {
+            (self.on_batch)(self.batch);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batcher() {
+        let mut batches = Vec::new();
+        let mut on_batch = |batch| batches.push(batch);
+
+        let mut batcher = Batcher::new(2, &mut on_batch);
+        batcher.extend([1, 2, 3]);
+        batcher.extend([4, 5]);
+        batcher.flush();
+
+        assert_eq!(batches, vec![vec![1, 2], vec![3, 4], vec![5]]);
+    }
+
+    #[derive(Default)]
+    struct Reports(Vec<(u32, String)>);
+
+    impl ProgressReporter for Reports {
+        fn report_message(&mut self, percentage: u32, message: &str) {
+            self.0.push((percentage, message.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_item_progress() {
+        let mut reports = Reports::default();
+
+        let mut progress = ItemProgress::new(&mut reports, "files", 300);
+        for _ in 0..6 {
+            progress.advance();
+        }
+
+        // Items that don't change the percentage aren't reported
+        assert_eq!(
+            reports.0,
+            vec![
+                (0, "1/300 files".to_string()),
+                (1, "3/300 files".to_string()),
+                (2, "6/300 files".to_string())
+            ]
+        );
+    }
+}
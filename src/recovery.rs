@@ -0,0 +1,857 @@
+//! The recovery of the syntax errors of documents being edited, so that the features keep working on the rest of the
+//! document while the user is mid-edit.
+//!
+//! The parser recovers from errors by wrapping the tokens it doesn't expect in bogus nodes, which often swallow the end
+//! of the rule or even of the document, e.g. the `}` ending the rule of `.a { color: rgb(255 0 0 } .b { … }` along with
+//! the rules after it, whose colors and symbols are then lost. Documents with syntax errors are thus parsed a second
+//! time after repairing the errors typical of unfinished edits, replacing characters without changing the length of
+//! the code like the masks of `scss.rs`:
+//!
+//! - The parentheses and brackets still open at the end of a statement are closed in the whitespace before it, e.g.
+//!   `rgb(255 0 0)}`, along with the unquoted `url(` not closed before whitespace.
+//! - The quote of a string not closed before the end of its line is blanked.
+//! - A declaration followed by another one on the next line without a `;` is ended in the indentation of that line.
+//!
+//! The repaired tree is only kept if it has fewer errors and the parser doesn't panic on it, along with the diagnostics
+//! of the code as written.
+//!
+//! The parser only expects rules in the blocks of conditional rules, so the declarations of the ones nested in style
+//! rules are parsed as selectors, e.g. `color: red` in `.a { @media (…) { color: red; } }`, swallowing the end of the
+//! style rule. In documents with syntax errors, the blocks of these conditional rules are blanked before parsing the
+//! code again, then parsed on their own as the blocks of style rules and spliced into the tree along with their
+//! errors, see `reparse_nested_conditionals`.
+//!
+//! The hashes of values that aren't hex colors are blanked before the first parse instead, as the parser panics on the
+//! ones not starting with a hex digit, e.g. the `#` being typed in `color: #`.
+
+use std::{ops::Range, panic};
+
+use biome_css_parser::{parse_css as biome_parse_css, CssParse, ParseDiagnostic};
+use biome_css_syntax::{CssLanguage, CssSyntaxKind};
+use biome_diagnostics::{Diagnostic as _, PrintDescription};
+use biome_rowan::{NodeOrToken, SyntaxNode, SyntaxSlot, TextSize};
+use rustc_hash::FxHashMap;
+
+use crate::{
+    parser::CssDialect,
+    scss::{is_ident_char, is_ident_start, Scanner},
+};
+
+/// The at-rules whose blocks the parser only expects rules in, even when they are nested in style rules.
+const CONDITIONAL_AT_RULES: [&str; 5] = ["container", "layer", "media", "scope", "supports"];
+
+/// Parse code with syntax errors again after repairing them, see the module documentation.
+///
+/// # Arguments
+///
+/// * `code` - The code that was parsed, after masking the constructs of its dialect.
+/// * `parse` - The parse of the code, with syntax errors.
+/// * `dialect` - The dialect the code is written in.
+///
+/// # Returns
+///
+/// * The parse of the repaired code with the diagnostics of `parse`, or `parse` itself if nothing could be repaired.
+pub(crate) fn recover(code: &str, parse: CssParse, dialect: CssDialect) -> CssParse {
+    let parse = reparse_nested_conditionals(code, dialect).unwrap_or(parse);
+    if !parse.has_errors() {
+        return parse;
+    }
+    let Some(repaired) = repair(code, dialect) else {
+        return parse;
+    };
+    // The parser can panic on repaired code it parses as written, e.g. `@media (x)--x: 1` after a closed string, in
+    // which case only the code as written is parsed
+    let Ok(recovered) = panic::catch_unwind(|| {
+        reparse_nested_conditionals(&repaired, dialect)
+            .unwrap_or_else(|| biome_parse_css(&repaired, dialect.parser_options()))
+    }) else {
+        return parse;
+    };
+    if recovered.diagnostics().len() >= parse.diagnostics().len() {
+        return parse;
+    }
+
+    CssParse::new(recovered.syntax(), parse.into_diagnostics())
+}
+
+/// Repair the errors of unfinished edits in code, keeping its length.
+///
+/// # Returns
+///
+/// * The repaired code, or `None` if there was nothing to repair.
+fn repair(code: &str, dialect: CssDialect) -> Option<String> {
+    let mut scanner = Scanner::new(code, dialect);
+    let bytes = scanner.bytes;
+    // The closing characters of the parentheses and brackets open in the current statement
+    let mut open = Vec::new();
+    let mut block_depth = 0usize;
+    // Whether the current statement of a block has a colon, i.e. is a declaration
+    let mut in_declaration = false;
+
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes[i..].starts_with(b"/*") || scanner.is_line_comment(i, open.len()) => {
+                i = scanner.comment_end(i);
+                continue;
+            }
+            quote @ (b'"' | b'\'') => {
+                let end = scanner.string_end(i);
+                if end - 1 > i && bytes[end - 1] == quote {
+                    i = end;
+                } else {
+                    // The rest of the line is scanned as is, instead of as the content of the string
+                    scanner.masked[i] = b' ';
+                    i += 1;
+                }
+                continue;
+            }
+            b'\\' => {
+                i += 2;
+                continue;
+            }
+            b'(' if is_url(bytes, i) => {
+                if let Some(end) = url_end(bytes, i + 1) {
+                    i = end;
+                    continue;
+                }
+                open.push(b')');
+            }
+            b'(' => open.push(b')'),
+            b'[' => open.push(b']'),
+            closing @ (b')' | b']') if open.last() == Some(&closing) => {
+                open.pop();
+            }
+            byte @ (b'{' | b'}' | b';') => {
+                close(&mut scanner.masked, &open, i);
+                open.clear();
+                in_declaration = false;
+                match byte {
+                    b'{' => block_depth += 1,
+                    b'}' => block_depth = block_depth.saturating_sub(1),
+                    _ => {}
+                }
+            }
+            b':' if open.is_empty() && block_depth > 0 => in_declaration = true,
+            b'\n' if in_declaration && open.is_empty() => {
+                if let Some(semicolon) = missing_semicolon(bytes, i) {
+                    scanner.masked[semicolon] = b';';
+                    in_declaration = false;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    close(&mut scanner.masked, &open, bytes.len());
+
+    (scanner.masked != bytes).then(|| {
+        String::from_utf8(scanner.masked)
+            .expect("only ASCII characters are replaced, with ASCII characters")
+    })
+}
+
+/// A conditional rule nested in a style rule, e.g. `@media (…) { … }` in `.a { @media (…) { … } }`.
+struct NestedConditional {
+    /// The start of the at-rule.
+    start: usize,
+    /// The range of its block, from its `{` to its `}`.
+    block: Range<usize>,
+    /// The conditional rules nested in its block.
+    children: Vec<NestedConditional>,
+}
+
+/// Find the conditional rules nested in style rules, see `CONDITIONAL_AT_RULES`, outside of strings, comments and
+/// interpolations. The rules whose block isn't closed are left out.
+fn nested_conditionals(code: &str, dialect: CssDialect) -> Vec<NestedConditional> {
+    let scanner = Scanner::new(code, dialect);
+    let bytes = scanner.bytes;
+    // The blocks open at the current position: whether declarations are expected in each, i.e. it is the block of a
+    // style rule or of a nested conditional rule, and the nested conditional rule it is the block of
+    let mut blocks: Vec<(bool, Option<NestedConditional>)> = Vec::new();
+    let mut nested = Vec::new();
+    let mut statement_start = 0;
+    let mut parentheses = 0usize;
+
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes[i..].starts_with(b"/*") || scanner.is_line_comment(i, parentheses) => {
+                i = scanner.comment_end(i);
+                continue;
+            }
+            b'"' | b'\'' => {
+                i = scanner.string_end(i);
+                continue;
+            }
+            b'\\' => {
+                i += 2;
+                continue;
+            }
+            _ if scanner.is_interpolation(i) => {
+                i = scanner.interpolation_end(i);
+                continue;
+            }
+            b'(' => parentheses += 1,
+            b')' => parentheses = parentheses.saturating_sub(1),
+            b'{' => {
+                let start = scanner.skip_trivia(statement_start);
+                let is_conditional = bytes[start] == b'@'
+                    && CONDITIONAL_AT_RULES.iter().any(|name| {
+                        code[start + 1..scanner.ident_end(start + 1)].eq_ignore_ascii_case(name)
+                    });
+                let in_style_rule = blocks
+                    .last()
+                    .is_some_and(|(in_style_rule, _)| *in_style_rule);
+                let conditional = (is_conditional && in_style_rule).then(|| NestedConditional {
+                    start,
+                    block: i..i,
+                    children: Vec::new(),
+                });
+                blocks.push((bytes[start] != b'@' || conditional.is_some(), conditional));
+                statement_start = i + 1;
+                parentheses = 0;
+            }
+            b'}' => {
+                if let Some((_, Some(mut conditional))) = blocks.pop() {
+                    conditional.block.end = i + 1;
+                    match blocks
+                        .iter_mut()
+                        .rev()
+                        .find_map(|(_, parent)| parent.as_mut())
+                    {
+                        Some(parent) => parent.children.push(conditional),
+                        None => nested.push(conditional),
+                    }
+                }
+                statement_start = i + 1;
+                parentheses = 0;
+            }
+            b';' => statement_start = i + 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    nested
+}
+
+/// Parse code again with the blocks of its conditional rules nested in style rules parsed as the blocks of style
+/// rules, see the module documentation.
+///
+/// # Returns
+///
+/// * The parse of the code with the blocks spliced into its tree, along with their syntax errors, or `None` if there
+///   are no conditional rules nested in style rules.
+fn reparse_nested_conditionals(code: &str, dialect: CssDialect) -> Option<CssParse> {
+    let nested = nested_conditionals(code, dialect);
+    if nested.is_empty() {
+        return None;
+    }
+
+    let (root, diagnostics) = parse_blocks(code, Scanner::new(code, dialect), 0, &nested);
+    Some(CssParse::new(root, diagnostics))
+}
+
+/// Parse the code of `scanner` with the blocks of `nested` blanked, then each of these blocks on its own as the block
+/// of a style rule, and splice them into the tree in place of the blanked ones. Each block is parsed from the start of
+/// its at-rule only, so that documents with many of them are still parsed in linear time. The blocks that can't be
+/// found in the tree are left blank.
+///
+/// # Arguments
+///
+/// * `code` - The code of the document.
+/// * `scanner` - A scanner of the code to parse, `code` itself or the code of a nested conditional rule.
+/// * `offset` - The offset of the code of `scanner` in `code`.
+/// * `nested` - The conditional rules nested in style rules of the code to parse, with their offsets in `code`.
+///
+/// # Returns
+///
+/// * The root of the tree and its syntax errors, with offsets relative to the code of `scanner`.
+fn parse_blocks(
+    code: &str,
+    mut scanner: Scanner,
+    offset: usize,
+    nested: &[NestedConditional],
+) -> (SyntaxNode<CssLanguage>, Vec<ParseDiagnostic>) {
+    let dialect = scanner.dialect;
+    for conditional in nested {
+        scanner.blank(
+            conditional.block.start + 1 - offset,
+            conditional.block.end - 1 - offset,
+        );
+    }
+    let masked = String::from_utf8(scanner.masked)
+        .expect("whole characters are replaced, with ASCII characters");
+    let parse = biome_parse_css(&masked, dialect.parser_options());
+    let root = parse.syntax();
+    let mut diagnostics = parse.into_diagnostics();
+
+    // The blanked blocks are found in a single traversal of the tree, then all replaced at once
+    let mut conditionals = nested
+        .iter()
+        .map(|conditional| {
+            (
+                TextSize::from((conditional.block.start - offset) as u32),
+                conditional,
+            )
+        })
+        .collect::<FxHashMap<_, _>>();
+    let mut blocks = Vec::new();
+    for blanked in root.descendants().filter(|node| {
+        matches!(
+            node.kind(),
+            CssSyntaxKind::CSS_RULE_LIST_BLOCK | CssSyntaxKind::CSS_BOGUS_BLOCK
+        )
+    }) {
+        let Some(conditional) = conditionals.remove(&blanked.text_trimmed_range().start()) else {
+            continue;
+        };
+
+        // The prelude of the at-rule is blanked, with a selector in place of its name
+        let block_end = offset + usize::from(blanked.text_range().end());
+        let mut block_scanner = Scanner::new(&code[conditional.start..block_end], dialect);
+        block_scanner.blank(0, conditional.block.start - conditional.start);
+        block_scanner.masked[0] = b'a';
+        let (block_root, block_diagnostics) = parse_blocks(
+            code,
+            block_scanner,
+            conditional.start,
+            &conditional.children,
+        );
+        let shift = TextSize::from((conditional.start - offset) as u32);
+        let Some(block) = block_root.descendants().find(|node| {
+            node.kind() == CssSyntaxKind::CSS_DECLARATION_OR_RULE_BLOCK
+                && node.text_range() + shift == blanked.text_range()
+        }) else {
+            continue;
+        };
+
+        blocks.push((blanked, block));
+        diagnostics.extend(
+            block_diagnostics
+                .into_iter()
+                .map(|diagnostic| shift_diagnostic(diagnostic, shift)),
+        );
+    }
+    (splice(&root, &blocks), diagnostics)
+}
+
+/// Replace the blanked blocks of a tree with their parsed blocks, only rebuilding the nodes containing them.
+///
+/// # Arguments
+///
+/// * `node` - The node to replace the blocks in.
+/// * `blocks` - The blanked blocks in the tree of `node` and the blocks replacing them, in source order.
+fn splice(
+    node: &SyntaxNode<CssLanguage>,
+    blocks: &[(SyntaxNode<CssLanguage>, SyntaxNode<CssLanguage>)],
+) -> SyntaxNode<CssLanguage> {
+    let range = node.text_range();
+    let first = blocks.partition_point(|(blanked, _)| blanked.text_range().start() < range.start());
+    let count = blocks[first..]
+        .iter()
+        .take_while(|(blanked, _)| blanked.text_range().end() <= range.end())
+        .count();
+    let blocks = &blocks[first..first + count];
+    match blocks {
+        [] => node.clone(),
+        [(blanked, block)] if blanked == node => block.clone(),
+        _ => SyntaxNode::new_detached(
+            node.kind(),
+            node.slots()
+                .map(|slot| match slot {
+                    SyntaxSlot::Node(child) => Some(NodeOrToken::Node(splice(&child, blocks))),
+                    SyntaxSlot::Token(token) => Some(NodeOrToken::Token(token)),
+                    SyntaxSlot::Empty => None,
+                })
+                .collect::<Vec<_>>(),
+        ),
+    }
+}
+
+/// Move a syntax error by `shift`. Only its message and span are kept, the rest isn't used by the features.
+fn shift_diagnostic(diagnostic: ParseDiagnostic, shift: TextSize) -> ParseDiagnostic {
+    ParseDiagnostic::new(
+        PrintDescription(&diagnostic).to_string(),
+        diagnostic.location().span.map(|span| span + shift),
+    )
+}
+
+/// A hash in a value that isn't a hex color, e.g. the `#` being typed in `color: #`, `#ggg` or `#12345`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct InvalidHexColor {
+    /// The range of the hash and of the name following it.
+    pub(crate) range: Range<usize>,
+    /// Whether the hash stands for a color, as opposed to a hash in the value of a custom property, which can be any
+    /// token, or an id in the selector of a nested rule the parser mistakes for a declaration, e.g. `#main` in
+    /// `div:hover #main { … }`.
+    pub(crate) is_color: bool,
+}
+
+/// Find the hashes that aren't hex colors in the values of declarations and in the features of at-rule preludes, e.g.
+/// `#zz` in `@media (color: #zz)`.
+pub(crate) fn invalid_hex_colors(code: &str, dialect: CssDialect) -> Vec<InvalidHexColor> {
+    let scanner = Scanner::new(code, dialect);
+    let bytes = scanner.bytes;
+    let mut colors = Vec::new();
+    let mut block_depth = 0usize;
+
+    let mut i = 0;
+    loop {
+        i = scanner.skip_trivia(i);
+        let Some(&byte) = bytes.get(i) else {
+            break;
+        };
+        match byte {
+            b'{' => block_depth += 1,
+            b'}' => block_depth = block_depth.saturating_sub(1),
+            b';' => {}
+            _ => {
+                let end = scanner.statement_end(i);
+                if let Some((value_start, is_color)) = value_start(&scanner, i, end, block_depth) {
+                    colors.extend(
+                        hashes(&scanner, value_start, end)
+                            .into_iter()
+                            .filter(|range| !is_hex_color(&code[range.start + 1..range.end]))
+                            .map(|range| InvalidHexColor { range, is_color }),
+                    );
+                }
+                i = end;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    colors
+}
+
+/// Blank the hashes of values that aren't hex colors, see `invalid_hex_colors`, as the parser panics on the ones not
+/// starting with a hex digit.
+///
+/// # Returns
+///
+/// * The masked code, or `None` if there was nothing to mask.
+pub(crate) fn mask_invalid_hex_colors(code: &str, dialect: CssDialect) -> Option<String> {
+    if !code.contains('#') {
+        return None;
+    }
+    let colors = invalid_hex_colors(code, dialect);
+    if colors.is_empty() {
+        return None;
+    }
+
+    let mut masked = code.as_bytes().to_vec();
+    for color in colors {
+        masked[color.range].fill(b' ');
+    }
+    Some(String::from_utf8(masked).expect("whole characters are replaced, with ASCII characters"))
+}
+
+/// Find where the values of the statement from `start` to `end` start, for the statements the parser parses values in:
+/// the declarations of blocks, and the at-rules, whose features have values after their colon.
+///
+/// # Returns
+///
+/// * The start of the values, and whether their hashes stand for colors, see `InvalidHexColor`. `None` for the other
+///   statements, e.g. the selectors of rules.
+fn value_start(
+    scanner: &Scanner,
+    start: usize,
+    end: usize,
+    block_depth: usize,
+) -> Option<(usize, bool)> {
+    let bytes = scanner.bytes;
+    if bytes[start] == b'@' {
+        let colon = bytes[start..end].iter().position(|byte| *byte == b':')?;
+        return Some((start + colon + 1, true));
+    }
+
+    // The parser parses the statements of blocks starting with an identifier and a colon as declarations, even when
+    // they are followed by a block
+    let name_end = scanner.ident_end(start);
+    if block_depth == 0 || name_end == start || !is_ident_start(bytes[start]) {
+        return None;
+    }
+    let colon = scanner.skip_trivia(name_end);
+    if colon >= end || bytes[colon] != b':' {
+        return None;
+    }
+    let is_color = !bytes[start..].starts_with(b"--") && bytes.get(end) != Some(&b'{');
+    Some((colon + 1, is_color))
+}
+
+/// Find the hashes from `start` to `end`, outside of strings, comments, unquoted URLs and interpolations.
+fn hashes(scanner: &Scanner, start: usize, end: usize) -> Vec<Range<usize>> {
+    let bytes = scanner.bytes;
+    let mut hashes = Vec::new();
+    let mut i = start;
+    while i < end {
+        match bytes[i] {
+            b'/' if bytes[i..].starts_with(b"/*") => {
+                i = scanner.comment_end(i);
+                continue;
+            }
+            b'"' | b'\'' => {
+                i = scanner.string_end(i);
+                continue;
+            }
+            b'\\' => i += 1,
+            b'(' if is_url(bytes, i) => {
+                if let Some(url_end) = url_end(bytes, i + 1) {
+                    i = url_end;
+                    continue;
+                }
+            }
+            b'#' if bytes.get(i + 1) != Some(&b'{') => {
+                let name_end = scanner.ident_end(i + 1).min(end);
+                hashes.push(i..name_end);
+                i = name_end;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    hashes
+}
+
+/// Whether the digits of a hash make a hex color: 3, 4, 6 or 8 hex digits, e.g. `f00` or `ff000080`.
+fn is_hex_color(digits: &str) -> bool {
+    matches!(digits.len(), 3 | 4 | 6 | 8) && digits.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+/// Whether the parenthesis at `index` opens a `url(` function.
+fn is_url(bytes: &[u8], index: usize) -> bool {
+    index >= 3
+        && bytes[index - 3..index].eq_ignore_ascii_case(b"url")
+        && (index == 3 || !is_ident_char(bytes[index - 4]))
+}
+
+/// Find the end of an unquoted URL starting at `start`, after its closing parenthesis.
+///
+/// # Returns
+///
+/// * The end of the URL, or `None` if it isn't closed or is quoted, in which case it is scanned like the other
+///   functions.
+fn url_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let skip_whitespace = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        i
+    };
+
+    let mut end = skip_whitespace(start);
+    if matches!(bytes.get(end), Some(b'"' | b'\'')) {
+        return None;
+    }
+    // Unquoted URLs can contain `;`, e.g. `url(data:image/png;base64,…)`, but not whitespace
+    while end < bytes.len()
+        && !bytes[end].is_ascii_whitespace()
+        && !matches!(bytes[end], b')' | b'{' | b'}' | b'"' | b'\'')
+    {
+        end += 1;
+    }
+    let end = skip_whitespace(end);
+    (bytes.get(end) == Some(&b')')).then_some(end + 1)
+}
+
+/// Close the parentheses and brackets still open at `end` in the whitespace before it, the innermost first. They are
+/// left open if there isn't enough whitespace.
+fn close(masked: &mut [u8], open: &[u8], end: usize) {
+    let mut start = end;
+    while start > 0 && masked[start - 1].is_ascii_whitespace() {
+        start -= 1;
+    }
+    if open.is_empty() || end - start < open.len() {
+        return;
+    }
+
+    for (offset, closing) in open.iter().rev().enumerate() {
+        masked[start + offset] = *closing;
+    }
+}
+
+/// Find where to end the declaration before the line break at `newline` when the next line starts another declaration
+/// or a nested rule, e.g. `background: blue;` or `&:hover {` after `color: red`.
+///
+/// # Returns
+///
+/// * The offset of the character to replace with a `;`: the indentation of the next line, or the line break if the
+///   line isn't indented. `None` if the value of the declaration continues on the next line, e.g. after the commas of
+///   `box-shadow`.
+fn missing_semicolon(bytes: &[u8], newline: usize) -> Option<usize> {
+    let last = bytes[..newline]
+        .iter()
+        .rev()
+        .find(|byte| !byte.is_ascii_whitespace())?;
+    if matches!(last, b',' | b':' | b'(') {
+        return None;
+    }
+
+    let mut start = newline + 1;
+    while matches!(bytes.get(start), Some(b' ' | b'\t')) {
+        start += 1;
+    }
+    let line_end = bytes[start..]
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .map_or(bytes.len(), |position| start + position);
+    let line = &bytes[start..line_end];
+
+    // The block of the rule declared by the current line can start on the next one
+    let is_rule = line.first() != Some(&b'{') && line.contains(&b'{');
+    let is_declaration = || {
+        let name_end = line
+            .iter()
+            .position(|byte| !is_ident_char(*byte))
+            .unwrap_or(line.len());
+        line.first().is_some_and(|byte| is_ident_start(*byte))
+            && line[name_end..].trim_ascii_start().first() == Some(&b':')
+    };
+    if !is_rule && !is_declaration() {
+        return None;
+    }
+
+    Some(if start > newline + 1 {
+        newline + 1
+    } else {
+        newline
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use biome_rowan::AstNodeList;
+    use std::time::Instant;
+
+    #[test]
+    fn test_repair() {
+        let repair = |code: &str| repair(code, CssDialect::Css);
+
+        assert_eq!(
+            repair(".a { color: rgb(255 0 0 } .b { color: blue }").as_deref(),
+            Some(".a { color: rgb(255 0 0)} .b { color: blue }")
+        );
+        assert_eq!(
+            repair("@supports (display: grid { .a {} }").as_deref(),
+            Some("@supports (display: grid){ .a {} }")
+        );
+        assert_eq!(
+            repair(".a { background: url( } .b {}").as_deref(),
+            Some(".a { background: url()} .b {}")
+        );
+        assert_eq!(
+            repair(".a { content: \"b }\n.c {}").as_deref(),
+            Some(".a { content:  b }\n.c {}")
+        );
+        assert_eq!(
+            repair(".a {\n  color: red\n  background: blue;\n}").as_deref(),
+            Some(".a {\n  color: red\n; background: blue;\n}")
+        );
+        assert_eq!(
+            repair(".a {\n  color: red\n  .b { color: blue }\n}").as_deref(),
+            Some(".a {\n  color: red\n; .b { color: blue }\n}")
+        );
+
+        // Without enough whitespace to close them, parentheses are left open
+        assert_eq!(repair(".a { color: rgb(255 0 0}"), None);
+        assert_eq!(repair(".a { color: calc((1px + 2px }"), None);
+        // Valid constructs are left as is
+        for code in [
+            ".a { background: url(data:image/png;base64,iVBOR) }",
+            ".a { background: url( image.png ) }",
+            ".a { content: \"{ ( [\"; }",
+            "/* ( */ .a[href] { color: red }",
+            ".a {\n  box-shadow: 0 0 red,\n    inset 0 0 blue;\n}",
+            ".a {\n  color: red;\n  a:hover { color: blue }\n}",
+            ".a {\n  &:hover\n  {\n    color: blue;\n  }\n}",
+            ".a {\n  color: red;\n  &:hover,\n  a:focus { color: blue }\n}",
+        ] {
+            assert_eq!(repair(code), None, "{code}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_hex_colors() {
+        fn invalid(code: &str) -> Vec<(&str, bool)> {
+            invalid_hex_colors(code, CssDialect::Css)
+                .into_iter()
+                .map(|color| (&code[color.range], color.is_color))
+                .collect()
+        }
+
+        assert_eq!(
+            invalid(".a { color: #ggg; border: 1px solid #12345 }"),
+            vec![("#ggg", true), ("#12345", true)]
+        );
+        assert_eq!(invalid(".a { color: # }"), vec![("#", true)]);
+        assert_eq!(
+            invalid("@media (color: #zz) { .a { background: rgb(#1) } }"),
+            vec![("#zz", true), ("#1", true)]
+        );
+        assert_eq!(
+            invalid(".a { --selector: #main; div:hover #main { color: red } }"),
+            vec![("#main", false), ("#main", false)]
+        );
+        // Selectors, strings, URLs and valid colors are left as is
+        for code in [
+            "#main, .a:not(#nav) { color: #f00 #ff000080 }",
+            ".a { &:hover #main { color: #FFF } }",
+            ".a { content: \"#ggg\"; background: url(#ggg) }",
+            ".a { /* #ggg */ color: red }",
+        ] {
+            assert_eq!(invalid(code), vec![], "{code}");
+        }
+        assert_eq!(
+            invalid_hex_colors(".a { width: #{$w}; }", CssDialect::Scss),
+            vec![]
+        );
+
+        assert_eq!(
+            mask_invalid_hex_colors(".a { color: #; b: #ggg #fff }", CssDialect::Css).as_deref(),
+            Some(".a { color:  ; b:      #fff }")
+        );
+        assert_eq!(
+            mask_invalid_hex_colors(".a { color: #fff }", CssDialect::Css),
+            None
+        );
+    }
+
+    #[test]
+    fn test_nested_conditionals() {
+        fn nested(code: &str) -> Vec<(&str, Vec<&str>)> {
+            nested_conditionals(code, CssDialect::Scss)
+                .into_iter()
+                .map(|conditional| {
+                    (
+                        &code[conditional.start..conditional.block.end],
+                        conditional
+                            .children
+                            .iter()
+                            .map(|child| &code[child.start..child.block.end])
+                            .collect(),
+                    )
+                })
+                .collect()
+        }
+
+        assert_eq!(
+            nested(".a { @media (x) { color: red; @supports (y) { color: blue } } }"),
+            vec![(
+                "@media (x) { color: red; @supports (y) { color: blue } }",
+                vec!["@supports (y) { color: blue }"]
+            )]
+        );
+        assert_eq!(
+            nested("@media (x) { .a { @LAYER l { color: red } } }"),
+            vec![("@LAYER l { color: red }", vec![])]
+        );
+        assert_eq!(
+            nested(".a-#{$b} { @media (x) { c: #{$d} } }"),
+            vec![("@media (x) { c: #{$d} }", vec![])]
+        );
+        // Top-level conditional rules, strings, comments and unclosed blocks are left out
+        for code in [
+            "@media (x) { @supports (y) { .a { color: red } } }",
+            ".a { content: \"@media (x) {\"; }",
+            ".a { /* @media (x) { */ } // @media (x) {\n",
+            ".a { @media (x) { color: red",
+        ] {
+            assert!(nested(code).is_empty(), "{code}");
+        }
+    }
+
+    #[test]
+    fn test_reparse_nested_conditionals() {
+        let code =
+            ".a { @media (x) { color: red; @supports (y) { color: blue } } }\n.b { color: red }";
+        let parse = reparse_nested_conditionals(code, CssDialect::Css).unwrap();
+        assert!(!parse.has_errors());
+        assert_eq!(parse.syntax().text().to_string(), code);
+        let declarations = parse
+            .syntax()
+            .descendants()
+            .filter(|node| node.kind() == CssSyntaxKind::CSS_DECLARATION)
+            .map(|node| node.text_trimmed().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(declarations, ["color: red", "color: blue", "color: red"]);
+
+        // The errors in the blocks are kept
+        let parse =
+            reparse_nested_conditionals(".a { @media (x) { color: red blue: } }", CssDialect::Css)
+                .unwrap();
+        assert_eq!(parse.diagnostics().len(), 1);
+        assert!(reparse_nested_conditionals(".a { color: red }", CssDialect::Css).is_none());
+
+        // The errors of the blocks keep their offsets in the document
+        let code = ".b {}\n.a { @media (x) { @supports (y) { color: red blue: } } }";
+        let parse = reparse_nested_conditionals(code, CssDialect::Css).unwrap();
+        let starts = parse
+            .diagnostics()
+            .iter()
+            .filter_map(|diagnostic| diagnostic.location().span)
+            .map(|span| usize::from(span.start()))
+            .collect::<Vec<_>>();
+        assert_eq!(starts, [code.rfind(':').unwrap()]);
+    }
+
+    #[test]
+    fn test_reparse_nested_conditionals_linear() {
+        // Each block is parsed on its own, so that 4 times as many rules take about 4 times as long to parse, instead of
+        // 16 times when parsing the document up to each block
+        let parse_time = |rules: usize| {
+            let code = ".a { @media (width > 600px) { color: red; } }\n".repeat(rules);
+            (0..3)
+                .map(|_| {
+                    let start = Instant::now();
+                    reparse_nested_conditionals(&code, CssDialect::Css).unwrap();
+                    start.elapsed()
+                })
+                .min()
+                .unwrap()
+        };
+
+        let small = parse_time(500);
+        let large = parse_time(2000);
+        assert!(
+            large < small * 10,
+            "{large:?} to parse 4 times as many rules as in {small:?}"
+        );
+    }
+
+    #[test]
+    fn test_recover() {
+        let code = ".a { color: rgb(255 0 0 } .b { color: blue }";
+        let parse = biome_parse_css(code, CssDialect::Css.parser_options());
+        let errors = parse.diagnostics().len();
+
+        let recovered = recover(code, parse, CssDialect::Css);
+        assert_eq!(recovered.tree().rules().len(), 2);
+        assert_eq!(
+            recovered.syntax().text_range(),
+            biome_parse_css(code, CssDialect::Css.parser_options())
+                .syntax()
+                .text_range()
+        );
+        // The errors of the code as written are kept, e.g. to report them
+        assert_eq!(recovered.diagnostics().len(), errors);
+    }
+
+    #[test]
+    fn test_recover_parser_panic() {
+        // The parser panics on the repaired code of these, with the string closed
+        for code in [
+            "a { content: \"x }\n@media (x)--x: 1",
+            "\";@media (x)--x: 1",
+        ] {
+            let parse = biome_parse_css(code, CssDialect::Css.parser_options());
+            let errors = parse.diagnostics().len();
+
+            let recovered = recover(code, parse, CssDialect::Css);
+            assert_eq!(recovered.syntax().text().to_string(), code);
+            assert_eq!(recovered.diagnostics().len(), errors);
+        }
+    }
+}
@@ -0,0 +1,397 @@
+//! Helpers for the custom properties registered by `@property` rules, e.g. `@property --angle { syntax: "<angle>"; … }`,
+//! parsing their `syntax` descriptor and matching their `initial-value` descriptor against it.
+
+use biome_css_syntax::{CssLanguage, CssSyntaxKind};
+use biome_rowan::{SyntaxKind, SyntaxNode};
+
+/// The data types a registered custom property can have, e.g. `<length>` in `syntax: "<length>+"`.
+pub(crate) const DATA_TYPES: &[&str] = &[
+    "angle",
+    "color",
+    "custom-ident",
+    "image",
+    "integer",
+    "length",
+    "length-percentage",
+    "number",
+    "percentage",
+    "resolution",
+    "string",
+    "time",
+    "transform-function",
+    "transform-list",
+    "url",
+];
+
+const CSS_WIDE_KEYWORDS: &[&str] = &["initial", "inherit", "unset", "revert", "revert-layer"];
+
+const ABSOLUTE_LENGTH_UNITS: &[&str] = &["px", "cm", "mm", "q", "in", "pt", "pc"];
+
+const RELATIVE_LENGTH_UNITS: &[&str] = &[
+    "em", "rem", "ex", "rex", "cap", "rcap", "ch", "rch", "ic", "ric", "lh", "rlh", "cqw", "cqh",
+    "cqi", "cqb", "cqmin", "cqmax",
+];
+
+const VIEWPORT_LENGTH_UNITS: &[&str] = &["vw", "vh", "vi", "vb", "vmin", "vmax"];
+
+const MATH_FUNCTIONS: &[&str] = &[
+    "calc", "min", "max", "clamp", "round", "mod", "rem", "abs", "sign", "sin", "cos", "tan",
+    "asin", "acos", "atan", "atan2", "pow", "sqrt", "hypot", "log", "exp",
+];
+
+const COLOR_FUNCTIONS: &[&str] = &[
+    "rgb",
+    "rgba",
+    "hsl",
+    "hsla",
+    "hwb",
+    "lab",
+    "lch",
+    "oklab",
+    "oklch",
+    "color",
+    "color-mix",
+    "light-dark",
+];
+
+const IMAGE_FUNCTIONS: &[&str] = &[
+    "image",
+    "image-set",
+    "-webkit-image-set",
+    "cross-fade",
+    "element",
+    "paint",
+];
+
+const TRANSFORM_FUNCTIONS: &[&str] = &[
+    "matrix",
+    "matrix3d",
+    "perspective",
+    "rotate",
+    "rotate3d",
+    "rotatex",
+    "rotatey",
+    "rotatez",
+    "scale",
+    "scale3d",
+    "scalex",
+    "scaley",
+    "scalez",
+    "skew",
+    "skewx",
+    "skewy",
+    "translate",
+    "translate3d",
+    "translatex",
+    "translatey",
+    "translatez",
+];
+
+/// The parsed `syntax` descriptor of a `@property` rule.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum PropertySyntax {
+    /// `"*"`, any value.
+    Universal,
+    /// Components separated by `|`, e.g. `"<length> | auto"`, a value matching any of them.
+    Components(Vec<SyntaxComponent>),
+}
+
+/// A component of a `syntax` descriptor, e.g. `<length>+`.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct SyntaxComponent {
+    pub(crate) name: ComponentName,
+    pub(crate) multiplier: Option<Multiplier>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ComponentName {
+    /// One of `DATA_TYPES`, without the angle brackets.
+    DataType(&'static str),
+    /// A keyword matching itself, e.g. `auto`.
+    Keyword(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Multiplier {
+    /// `+`, a list of values separated by spaces.
+    Space,
+    /// `#`, a list of values separated by commas.
+    Comma,
+}
+
+/// Parse the content of the string of a `syntax` descriptor, e.g. `<length> | auto`.
+///
+/// # Returns
+///
+/// * The parsed syntax, or a message explaining why it isn't a valid syntax.
+pub(crate) fn parse_syntax(syntax: &str) -> Result<PropertySyntax, String> {
+    let syntax = syntax.trim();
+    if syntax == "*" {
+        return Ok(PropertySyntax::Universal);
+    }
+    if syntax.is_empty() {
+        return Err("The syntax is empty, use `\"*\"` to accept any value".to_string());
+    }
+
+    syntax
+        .split('|')
+        .map(|component| parse_component(component.trim()))
+        .collect::<Result<_, _>>()
+        .map(PropertySyntax::Components)
+}
+
+fn parse_component(component: &str) -> Result<SyntaxComponent, String> {
+    if component.is_empty() {
+        return Err("A component of the syntax is empty".to_string());
+    }
+    if component.contains(char::is_whitespace) {
+        return Err(format!(
+            "`{component}` isn't a single component, components must be separated by `|`"
+        ));
+    }
+
+    let (name, multiplier) = match component.as_bytes()[component.len() - 1] {
+        b'+' => (&component[..component.len() - 1], Some(Multiplier::Space)),
+        b'#' => (&component[..component.len() - 1], Some(Multiplier::Comma)),
+        _ => (component, None),
+    };
+
+    let name = if let Some(data_type) = name
+        .strip_prefix('<')
+        .and_then(|name| name.strip_suffix('>'))
+    {
+        let Some(data_type) = DATA_TYPES.iter().find(|name| **name == data_type) else {
+            return Err(format!("Unknown data type `<{data_type}>`"));
+        };
+        if *data_type == "transform-list" && multiplier.is_some() {
+            return Err(
+                "`<transform-list>` is already a list and can't have a multiplier".to_string(),
+            );
+        }
+        ComponentName::DataType(data_type)
+    } else {
+        let is_ident = name.bytes().next().is_some_and(|byte| {
+            byte.is_ascii_alphabetic() || byte == b'_' || byte == b'-' || !byte.is_ascii()
+        }) && !name.starts_with("--")
+            && name.bytes().all(crate::scss::is_ident_char);
+        if !is_ident {
+            return Err(format!(
+                "`{name}` is neither a data type, e.g. `<length>`, nor a keyword"
+            ));
+        }
+        let lowercase = name.to_ascii_lowercase();
+        if CSS_WIDE_KEYWORDS.contains(&lowercase.as_str()) || lowercase == "default" {
+            return Err(format!(
+                "The keyword `{name}` is reserved and can't be in a syntax"
+            ));
+        }
+        ComponentName::Keyword(name.to_string())
+    };
+
+    Ok(SyntaxComponent { name, multiplier })
+}
+
+/// Get the unit of a dimension node, e.g. `px` for `10px`.
+fn dimension_unit(node: &SyntaxNode<CssLanguage>) -> Option<String> {
+    (node.kind() == CssSyntaxKind::CSS_REGULAR_DIMENSION)
+        .then(|| node.last_token())
+        .flatten()
+        .map(|unit| unit.text_trimmed().to_ascii_lowercase())
+}
+
+fn function_name(node: &SyntaxNode<CssLanguage>) -> Option<String> {
+    (node.kind() == CssSyntaxKind::CSS_FUNCTION)
+        .then(|| node.first_token())
+        .flatten()
+        .map(|name| name.text_trimmed().to_ascii_lowercase())
+}
+
+/// Whether a unit is a length unit, and if so whether it is absolute.
+fn length_unit(unit: &str) -> Option<bool> {
+    if ABSOLUTE_LENGTH_UNITS.contains(&unit) {
+        return Some(true);
+    }
+    let viewport_unit = unit
+        .strip_prefix(['s', 'l', 'd'])
+        .filter(|unit| VIEWPORT_LENGTH_UNITS.contains(unit));
+    (RELATIVE_LENGTH_UNITS.contains(&unit)
+        || VIEWPORT_LENGTH_UNITS.contains(&unit)
+        || viewport_unit.is_some())
+    .then_some(false)
+}
+
+/// Find the first part of a value that isn't computationally independent, as required by the `initial-value` of a
+/// registered property, e.g. `2em` which depends on the font size, or a `var()`.
+pub(crate) fn computationally_dependent_value(
+    values: &[SyntaxNode<CssLanguage>],
+) -> Option<SyntaxNode<CssLanguage>> {
+    values
+        .iter()
+        .flat_map(|value| value.descendants())
+        .find(|node| {
+            node.kind() == CssSyntaxKind::CSS_VAR_FUNCTION
+                || function_name(node).is_some_and(|name| name == "var" || name == "env")
+                || dimension_unit(node)
+                    .and_then(|unit| length_unit(&unit))
+                    .is_some_and(|is_absolute| !is_absolute)
+        })
+}
+
+fn is_number(node: &SyntaxNode<CssLanguage>) -> bool {
+    node.kind() == CssSyntaxKind::CSS_NUMBER
+}
+
+fn is_zero(node: &SyntaxNode<CssLanguage>) -> bool {
+    is_number(node)
+        && node
+            .text_trimmed()
+            .to_string()
+            .parse::<f64>()
+            .is_ok_and(|number| number == 0.0)
+}
+
+fn has_unit(node: &SyntaxNode<CssLanguage>, units: &[&str]) -> bool {
+    dimension_unit(node).is_some_and(|unit| units.contains(&unit.as_str()))
+}
+
+/// Whether a value is a `<color>`: a hex color, a named color, `currentcolor` or a color function.
+pub(crate) fn is_color(node: &SyntaxNode<CssLanguage>) -> bool {
+    match node.kind() {
+        CssSyntaxKind::CSS_COLOR => true,
+        CssSyntaxKind::CSS_IDENTIFIER => {
+            let name = node.text_trimmed().to_string().to_ascii_lowercase();
+            name == "currentcolor" || csscolorparser::parse(&name).is_ok()
+        }
+        _ => function_name(node).is_some_and(|name| COLOR_FUNCTIONS.contains(&name.as_str())),
+    }
+}
+
+fn matches_data_type(data_type: &str, node: &SyntaxNode<CssLanguage>) -> bool {
+    let function = function_name(node);
+    // The type of a math function is unknown without resolving it, so it is accepted by every numeric type
+    let is_math = function
+        .as_ref()
+        .is_some_and(|name| MATH_FUNCTIONS.contains(&name.as_str()));
+    let is_length =
+        || is_zero(node) || dimension_unit(node).is_some_and(|unit| length_unit(&unit).is_some());
+
+    match data_type {
+        "length" => is_math || is_length(),
+        "percentage" => is_math || node.kind() == CssSyntaxKind::CSS_PERCENTAGE,
+        "length-percentage" => {
+            is_math || is_length() || node.kind() == CssSyntaxKind::CSS_PERCENTAGE
+        }
+        "number" => is_math || is_number(node),
+        "integer" => {
+            is_math || is_number(node) && !node.text_trimmed().to_string().contains(['.', 'e', 'E'])
+        }
+        "angle" => is_math || is_zero(node) || has_unit(node, &["deg", "grad", "rad", "turn"]),
+        "time" => is_math || has_unit(node, &["s", "ms"]),
+        "resolution" => is_math || has_unit(node, &["dpi", "dpcm", "dppx", "x"]),
+        "color" => is_color(node),
+        "url" => node.kind() == CssSyntaxKind::CSS_URL_FUNCTION,
+        "image" => {
+            node.kind() == CssSyntaxKind::CSS_URL_FUNCTION
+                || function.is_some_and(|name| {
+                    name.ends_with("-gradient") || IMAGE_FUNCTIONS.contains(&name.as_str())
+                })
+        }
+        "string" => node.kind() == CssSyntaxKind::CSS_STRING,
+        "custom-ident" => {
+            node.kind() == CssSyntaxKind::CSS_IDENTIFIER && {
+                let name = node.text_trimmed().to_string().to_ascii_lowercase();
+                !CSS_WIDE_KEYWORDS.contains(&name.as_str()) && name != "default"
+            }
+        }
+        "transform-function" => {
+            function.is_some_and(|name| TRANSFORM_FUNCTIONS.contains(&name.as_str()))
+        }
+        _ => false,
+    }
+}
+
+fn matches_component(component: &SyntaxComponent, values: &[SyntaxNode<CssLanguage>]) -> bool {
+    let matches_single = |node: &SyntaxNode<CssLanguage>| match &component.name {
+        ComponentName::DataType(data_type) => matches_data_type(data_type, node),
+        ComponentName::Keyword(keyword) => {
+            node.kind() == CssSyntaxKind::CSS_IDENTIFIER && node.text_trimmed() == keyword.as_str()
+        }
+    };
+    let is_comma = |node: &SyntaxNode<CssLanguage>| {
+        node.kind() == CssSyntaxKind::CSS_GENERIC_DELIMITER && node.text_trimmed() == ","
+    };
+
+    match (&component.name, component.multiplier) {
+        (ComponentName::DataType("transform-list"), _) => {
+            !values.is_empty()
+                && values
+                    .iter()
+                    .all(|value| matches_data_type("transform-function", value))
+        }
+        (_, None) => values.len() == 1 && matches_single(&values[0]),
+        (_, Some(Multiplier::Space)) => !values.is_empty() && values.iter().all(matches_single),
+        (_, Some(Multiplier::Comma)) => values
+            .split(is_comma)
+            .all(|item| item.len() == 1 && matches_single(&item[0])),
+    }
+}
+
+/// Whether a value, as the nodes of its component value list, matches a syntax.
+pub(crate) fn matches_syntax(syntax: &PropertySyntax, values: &[SyntaxNode<CssLanguage>]) -> bool {
+    match syntax {
+        PropertySyntax::Universal => true,
+        PropertySyntax::Components(components) => {
+            // Values that couldn't be parsed, e.g. with SCSS interpolations, can't be checked
+            values
+                .iter()
+                .flat_map(|value| value.descendants())
+                .any(|node| node.kind().is_bogus())
+                || components
+                    .iter()
+                    .any(|component| matches_component(component, values))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_syntax() {
+        assert_eq!(parse_syntax(" * "), Ok(PropertySyntax::Universal));
+        assert_eq!(
+            parse_syntax("<length>+ | auto | <color>#"),
+            Ok(PropertySyntax::Components(vec![
+                SyntaxComponent {
+                    name: ComponentName::DataType("length"),
+                    multiplier: Some(Multiplier::Space),
+                },
+                SyntaxComponent {
+                    name: ComponentName::Keyword("auto".to_string()),
+                    multiplier: None,
+                },
+                SyntaxComponent {
+                    name: ComponentName::DataType("color"),
+                    multiplier: Some(Multiplier::Comma),
+                },
+            ]))
+        );
+
+        assert!(parse_syntax("").is_err());
+        assert!(parse_syntax("<length> |").is_err());
+        assert!(parse_syntax("<length> <color>").is_err());
+        assert!(parse_syntax("<lenght>").is_err());
+        assert!(parse_syntax("<transform-list>+").is_err());
+        assert!(parse_syntax("inherit").is_err());
+        assert!(parse_syntax("<length> | *").is_err());
+    }
+
+    #[test]
+    fn test_length_unit() {
+        assert_eq!(length_unit("px"), Some(true));
+        assert_eq!(length_unit("rem"), Some(false));
+        assert_eq!(length_unit("svh"), Some(false));
+        assert_eq!(length_unit("deg"), None);
+    }
+}
@@ -0,0 +1,1536 @@
+//! Support for SCSS documents.
+//!
+//! The parser only understands standard CSS, so the SCSS-only constructs of a document (`$variables`, `@mixin`,
+//! `@include`, control directives, `#{…}` interpolation, placeholder selectors, operators in values) are found with a
+//! lexical scan and masked before parsing: replaced with whitespace, or with CSS of the same length that the parser
+//! accepts. The offsets of the syntax tree are thus still those of the document, and the features understand the
+//! masked constructs through the ones recorded by the scan.
+
+use std::sync::Arc;
+
+use biome_rowan::{TextRange, TextSize};
+#[cfg(any(
+    feature = "completion",
+    feature = "definition",
+    feature = "diagnostics",
+    feature = "hover",
+    feature = "signature-help"
+))]
+use lsp_types::Location;
+use lsp_types::Uri;
+
+#[cfg(any(
+    feature = "completion",
+    feature = "definition",
+    feature = "diagnostics",
+    feature = "hover",
+    feature = "signature-help"
+))]
+use rustc_hash::FxHashSet;
+
+#[cfg(any(
+    feature = "completion",
+    feature = "definition",
+    feature = "hover",
+    feature = "signature-help"
+))]
+use crate::icss::icss_imports;
+#[cfg(any(
+    feature = "completion",
+    feature = "definition",
+    feature = "diagnostics",
+    feature = "hover",
+    feature = "signature-help"
+))]
+use crate::{converters::to_proto, workspace_index::SymbolKind};
+use crate::{
+    file_system::{FileSystemProvider, FileType},
+    icss::scan_icss,
+    less::scan_less,
+    parser::CssDialect,
+    service::LanguageService,
+    store::StoreEntry,
+};
+
+/// The kind of an SCSS construct found by `scan_scss`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScssConstructKind {
+    /// A variable declaration, e.g. `$primary: red;`.
+    Variable,
+    /// A variable used outside of its declaration, e.g. `$primary` in `color: $primary`.
+    VariableReference,
+    /// A `@mixin` declaration.
+    Mixin,
+    /// A mixin included with `@include`.
+    Include,
+    /// A `@function` declaration.
+    Function,
+    /// A placeholder selector, e.g. `%button`.
+    Placeholder,
+    /// A selector extended with `@extend`, e.g. `%button` in `@extend %button;`.
+    Extend,
+    /// Any other SCSS directive, e.g. `@use`, `@if` or `@return`.
+    Directive,
+    /// An interpolation, e.g. `#{$name}`.
+    Interpolation,
+}
+
+/// An SCSS construct found in a document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScssConstruct {
+    pub kind: ScssConstructKind,
+    /// The name of the construct: `$primary` for variables, `button` for `@mixin button` or `%button`, `%button` for
+    /// `@extend %button`, and the name of the directive or the text of the interpolation for the others.
+    pub name: String,
+    /// The range of the name.
+    pub name_range: TextRange,
+    /// The range of the whole construct, e.g. `$primary: red` or the prelude of `@mixin button($size) {`, without
+    /// the semicolon ending it.
+    pub range: TextRange,
+    /// The value of a variable declaration without its flags, e.g. `red` for `$primary: red !default;`, and its
+    /// range.
+    pub value: Option<(TextRange, String)>,
+}
+
+/// The result of `scan_scss`.
+#[derive(Clone, Debug)]
+pub struct ScssScan {
+    /// The text of the document with its SCSS-only constructs masked, of the same length as the document.
+    pub masked: String,
+    /// The constructs found in the document, in source order.
+    pub constructs: Vec<ScssConstruct>,
+}
+
+/// The directives that only exist in SCSS, masked along with their prelude.
+const DIRECTIVES: &[&str] = &[
+    "use", "forward", "mixin", "include", "function", "return", "if", "else", "each", "for",
+    "while", "extend", "content", "debug", "warn", "error", "at-root",
+];
+
+/// Scans an SCSS document for its SCSS-only constructs, and masks them so that the document can be parsed as CSS.
+///
+/// # Arguments
+///
+/// * `code` - A string slice that holds the SCSS code to scan.
+///
+/// # Returns
+///
+/// * The masked code, and the constructs found in it.
+pub fn scan_scss(code: &str) -> ScssScan {
+    let mut scanner = Scanner::new(code, CssDialect::Scss);
+    scanner.scan_statements();
+    scanner.scan_variable_references();
+    scanner.finish()
+}
+
+impl StoreEntry {
+    /// Get the SCSS constructs of the document, scanning it if they aren't cached for this version yet. LESS documents
+    /// have the equivalent LESS constructs, see `less::scan_less`, and CSS documents the values of CSS Modules, see
+    /// `icss::scan_icss`.
+    pub fn scss_constructs(&self) -> Arc<Vec<ScssConstruct>> {
+        self.cached("scss_constructs", |entry| {
+            Arc::new(match entry.dialect {
+                CssDialect::Scss => scan_scss(&entry.document.text).constructs,
+                CssDialect::Less => scan_less(&entry.document.text).constructs,
+                CssDialect::Css => scan_icss(&entry.document.text).constructs,
+            })
+        })
+    }
+
+    /// Get the SCSS variable declared or used at an offset, e.g. `$primary` in `color: $primary;`, along with the
+    /// namespace it is used through, e.g. `colors` for `colors.$primary`.
+    pub fn scss_variable_at(&self, offset: TextSize) -> Option<(Option<String>, ScssConstruct)> {
+        let construct = self
+            .scss_constructs()
+            .iter()
+            .find(|construct| {
+                matches!(
+                    construct.kind,
+                    ScssConstructKind::Variable | ScssConstructKind::VariableReference
+                ) && construct.name_range.contains_inclusive(offset)
+            })?
+            .clone();
+
+        let namespace = namespace_before(&self.document.text, construct.name_range.start());
+        Some((namespace, construct))
+    }
+
+    /// Get the mixin or function declared, included or called at an offset, e.g. `button` in `@include button;` or
+    /// `double` in `width: double(4px);`.
+    pub fn scss_callable_at(&self, offset: TextSize) -> Option<ScssCallable> {
+        if self.dialect != CssDialect::Scss {
+            return None;
+        }
+
+        let constructs = self.scss_constructs();
+        let construct = constructs.iter().find(|construct| {
+            matches!(
+                construct.kind,
+                ScssConstructKind::Mixin | ScssConstructKind::Include | ScssConstructKind::Function
+            ) && construct.name_range.contains_inclusive(offset)
+        });
+        if let Some(construct) = construct {
+            // Included mixins can come from a module, e.g. `@include buttons.primary;`
+            let (namespace, name) = match construct.name.rsplit_once('.') {
+                Some((namespace, name)) => (Some(namespace.to_string()), name),
+                None => (None, construct.name.as_str()),
+            };
+            let name_start = construct.name_range.end() - TextSize::of(name);
+            return Some(ScssCallable {
+                kind: match construct.kind {
+                    ScssConstructKind::Function => ScssConstructKind::Function,
+                    _ => ScssConstructKind::Mixin,
+                },
+                namespace,
+                name: name.to_string(),
+                name_range: TextRange::new(name_start, construct.name_range.end()),
+                is_declaration: construct.kind != ScssConstructKind::Include,
+            });
+        }
+
+        // Functions are called in values and directives (e.g. `double(4px)` or `math.div(4px, 2)`), which the scan
+        // doesn't record
+        let bytes = self.document.text.as_bytes();
+        let mut start = usize::from(offset).min(bytes.len());
+        while start > 0 && is_ident_char(bytes[start - 1]) {
+            start -= 1;
+        }
+        let mut end = usize::from(offset).min(bytes.len());
+        while end < bytes.len() && is_ident_char(bytes[end]) {
+            end += 1;
+        }
+        let is_variable_or_at_rule = start > 0 && matches!(bytes[start - 1], b'$' | b'@' | b'%');
+        if start == end || is_variable_or_at_rule || bytes.get(end) != Some(&b'(') {
+            return None;
+        }
+        Some(ScssCallable {
+            kind: ScssConstructKind::Function,
+            namespace: namespace_before(&self.document.text, TextSize::from(start as u32)),
+            name: self.document.text[start..end].to_string(),
+            name_range: text_range(start, end),
+            is_declaration: false,
+        })
+    }
+
+    /// Get the placeholder selector declared or extended at an offset, e.g. `card` in `%card {` or in
+    /// `@extend %card;`, along with the range of its name.
+    pub fn scss_placeholder_at(&self, offset: TextSize) -> Option<(String, TextRange)> {
+        if self.dialect != CssDialect::Scss {
+            return None;
+        }
+
+        self.scss_constructs()
+            .iter()
+            .find_map(|construct| match construct.kind {
+                ScssConstructKind::Placeholder if construct.range.contains_inclusive(offset) => {
+                    Some((construct.name.clone(), construct.name_range))
+                }
+                ScssConstructKind::Extend if construct.name_range.contains_inclusive(offset) => {
+                    let name = construct.name.strip_prefix('%')?;
+                    name.bytes().all(is_ident_char).then(|| {
+                        (
+                            name.to_string(),
+                            TextRange::new(
+                                construct.name_range.start() + TextSize::from(1),
+                                construct.name_range.end(),
+                            ),
+                        )
+                    })
+                }
+                _ => None,
+            })
+    }
+}
+
+/// A mixin or function declared, included or called in an SCSS document, see `StoreEntry::scss_callable_at`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScssCallable {
+    /// `Mixin` or `Function`.
+    pub kind: ScssConstructKind,
+    /// The namespace the mixin or function is used through, e.g. `buttons` for `@include buttons.primary;`.
+    pub namespace: Option<String>,
+    /// The name of the mixin or function, without its namespace.
+    pub name: String,
+    /// The range of the name.
+    pub name_range: TextRange,
+    /// Whether this is the declaration of the mixin or function, rather than an include or a call.
+    pub is_declaration: bool,
+}
+
+/// Get the namespace a member whose name starts at an offset is used through, e.g. `colors` for `colors.$primary`.
+fn namespace_before(text: &str, offset: TextSize) -> Option<String> {
+    let before = text[..usize::from(offset)]
+        .strip_suffix('.')
+        .unwrap_or_default();
+    let namespace_start = before
+        .char_indices()
+        .rev()
+        .find(|(_, char)| !(char.is_alphanumeric() || *char == '-' || *char == '_'))
+        .map_or(0, |(index, char)| index + char.len_utf8());
+    Some(&before[namespace_start..])
+        .filter(|namespace| !namespace.is_empty())
+        .map(str::to_string)
+}
+
+/// How an SCSS document loads a stylesheet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScssModuleKind {
+    /// `@use`, making the members of the stylesheet available through its namespace.
+    Use,
+    /// `@forward`, making the members of the stylesheet available to the stylesheets using the document.
+    Forward,
+    /// `@import`, making the members of the stylesheet available globally.
+    Import,
+}
+
+/// A stylesheet loaded by an SCSS document with `@use`, `@forward` or `@import`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScssModule {
+    pub kind: ScssModuleKind,
+    /// The URL of the stylesheet as written, e.g. `src/colors` in `@use "src/colors";`.
+    pub url: String,
+    /// The range of the URL, without its quotes.
+    pub range: TextRange,
+    /// The namespace of the members of the stylesheet: `colors` for `@use "src/colors";` unless renamed with `as`, and
+    /// none for `@use "src/colors" as *;`, `@forward` and `@import`.
+    pub namespace: Option<String>,
+    /// The prefix added to the names of the members of a `@forward`, e.g. `list-` for `@forward "list" as list-*;`.
+    pub prefix: Option<String>,
+    /// The only members forwarded by a `@forward` with `show`, e.g. `$gap` and `button` for
+    /// `@forward "src/list" show $gap, button;`.
+    pub show: Option<Vec<String>>,
+    /// The members left out of a `@forward` with `hide`.
+    pub hide: Vec<String>,
+}
+
+impl ScssModule {
+    /// Whether a member of the stylesheet, with its name prefixed for a `@forward`, is available through this module.
+    pub fn exposes(&self, name: &str) -> bool {
+        self.show
+            .as_ref()
+            .is_none_or(|show| show.iter().any(|shown| shown == name))
+            && !self.hide.iter().any(|hidden| hidden == name)
+    }
+}
+
+/// Get the stylesheets loaded by an SCSS document, from its `@use`, `@forward` and `@import` directives. The built-in
+/// modules of Sass (e.g. `sass:math`) and CSS imports (e.g. `@import url(theme.css);`) are left out.
+///
+/// # Arguments
+///
+/// * `code` - The SCSS code of the document.
+/// * `constructs` - The constructs found in the code by `scan_scss`.
+pub fn scss_modules(code: &str, constructs: &[ScssConstruct]) -> Vec<ScssModule> {
+    constructs
+        .iter()
+        .filter_map(|construct| {
+            let kind = match construct.name.as_str() {
+                _ if construct.kind != ScssConstructKind::Directive => return None,
+                "use" => ScssModuleKind::Use,
+                "forward" => ScssModuleKind::Forward,
+                "import" => ScssModuleKind::Import,
+                _ => return None,
+            };
+            Some((kind, construct))
+        })
+        .flat_map(|(kind, construct)| {
+            let arguments_start = usize::from(construct.name_range.end());
+            let arguments = &code[arguments_start..usize::from(construct.range.end())];
+            let urls = quoted_strings(arguments);
+            let module = |url: &str, url_start: usize| ScssModule {
+                kind,
+                url: url.to_string(),
+                range: text_range(
+                    arguments_start + url_start,
+                    arguments_start + url_start + url.len(),
+                ),
+                namespace: None,
+                prefix: None,
+                show: None,
+                hide: Vec::new(),
+            };
+
+            if kind == ScssModuleKind::Import {
+                return urls
+                    .into_iter()
+                    .map(|(url, url_start, _)| module(url, url_start))
+                    .collect::<Vec<_>>();
+            }
+
+            let Some((url, url_start, url_end)) = urls.into_iter().next() else {
+                return Vec::new();
+            };
+            let mut module = module(url, url_start);
+            if kind == ScssModuleKind::Use {
+                let name = url.rsplit('/').next().unwrap_or(url);
+                let name = name.strip_prefix('_').unwrap_or(name);
+                module.namespace = Some(name.split('.').next().unwrap_or(name).to_string());
+            }
+
+            // The clauses following the URL, e.g. `as c`, `as list-* hide $gap` or `show button, $gap`. The
+            // configuration of `with (…)` comes last.
+            let clauses = arguments[url_end..]
+                .split(" with")
+                .next()
+                .unwrap_or_default();
+            let mut words = clauses
+                .split(|char: char| char.is_whitespace() || char == ',')
+                .filter(|word| !word.is_empty())
+                .peekable();
+            while let Some(word) = words.next() {
+                match word {
+                    "as" => match (kind, words.next()) {
+                        (ScssModuleKind::Use, Some("*")) => module.namespace = None,
+                        (ScssModuleKind::Use, Some(namespace)) => {
+                            module.namespace = Some(namespace.to_string())
+                        }
+                        (_, prefix) => {
+                            module.prefix = prefix
+                                .and_then(|prefix| prefix.strip_suffix('*'))
+                                .map(str::to_string)
+                        }
+                    },
+                    "show" | "hide" => {
+                        let mut names = Vec::new();
+                        while let Some(name) =
+                            words.next_if(|name| !matches!(*name, "as" | "show" | "hide"))
+                        {
+                            names.push(name.to_string());
+                        }
+                        if word == "show" {
+                            module.show = Some(names);
+                        } else {
+                            module.hide = names;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            vec![module]
+        })
+        .filter(|module| !module.url.starts_with("sass:"))
+        .collect()
+}
+
+/// Get the content of the quoted strings of a text, with the offset of their content and of their end.
+pub(crate) fn quoted_strings(text: &str) -> Vec<(&str, usize, usize)> {
+    let mut strings = Vec::new();
+    let mut rest = 0;
+    while let Some(start) = text[rest..].find(['"', '\'']).map(|start| rest + start) {
+        let quote = &text[start..start + 1];
+        let Some(end) = text[start + 1..].find(quote).map(|end| start + 1 + end) else {
+            break;
+        };
+        strings.push((&text[start + 1..end], start + 1, end + 1));
+        rest = end + 1;
+    }
+    strings
+}
+
+/// Get the URIs a stylesheet loaded by an SCSS document can have, e.g. `_colors.scss` or `colors/_index.scss` next to
+/// the document for `@use "colors";`.
+///
+/// # Arguments
+///
+/// * `from` - The URI of the document loading the stylesheet.
+/// * `url` - The URL of the stylesheet, relative to the document.
+pub fn module_uris(from: &Uri, url: &str) -> Vec<Uri> {
+    let Some((directory, _)) = from.as_str().rsplit_once('/') else {
+        return Vec::new();
+    };
+    let (directory, name) = match url.rsplit_once('/') {
+        Some((parent, name)) => (format!("{directory}/{parent}"), name),
+        None => (directory.to_string(), url),
+    };
+
+    let names = if name.ends_with(".scss") || name.ends_with(".css") {
+        vec![name.to_string(), format!("_{name}")]
+    } else {
+        vec![
+            format!("{name}.scss"),
+            format!("_{name}.scss"),
+            format!("{name}.css"),
+            format!("{name}/_index.scss"),
+            format!("{name}/index.scss"),
+        ]
+    };
+    names
+        .into_iter()
+        .filter_map(|name| normalize_path(&format!("{directory}/{name}")).parse().ok())
+        .collect()
+}
+
+/// Remove the `.` and `..` segments of the path of a URI.
+pub(crate) fn normalize_path(uri: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in uri.split('/') {
+        match segment {
+            "." => {}
+            // The scheme and authority, e.g. `file:` and the empty segments of `file:///`, are never removed
+            ".." if segments
+                .last()
+                .is_some_and(|last| !last.is_empty() && !last.ends_with(':')) =>
+            {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+/// A variable, mixin or function visible from an SCSS document: one of its own, or one of a stylesheet it loads.
+#[cfg(any(
+    feature = "completion",
+    feature = "definition",
+    feature = "hover",
+    feature = "signature-help"
+))]
+#[derive(Clone, Debug)]
+pub(crate) struct ScssMember {
+    /// `Variable`, `Mixin` or `Function`.
+    pub kind: ScssConstructKind,
+    /// The namespace the member is used through, for the members of a stylesheet loaded with `@use`.
+    pub namespace: Option<String>,
+    /// The name of the member, e.g. `$primary` or `button`.
+    pub name: String,
+    /// The value of a variable, unknown for the variables of persisted documents.
+    #[cfg_attr(
+        not(any(feature = "completion", feature = "hover", feature = "signature-help")),
+        allow(dead_code)
+    )]
+    pub value: Option<String>,
+    /// The parameters of a mixin or function, e.g. `($size, $color: red)`, unknown for those of persisted documents
+    /// and none for mixins without parameters.
+    #[cfg_attr(
+        not(any(feature = "completion", feature = "hover", feature = "signature-help")),
+        allow(dead_code)
+    )]
+    pub parameters: Option<String>,
+    /// The location of the name of the member in its declaration.
+    #[cfg_attr(not(feature = "definition"), allow(dead_code))]
+    pub location: Location,
+}
+
+#[cfg(any(feature = "completion", feature = "hover", feature = "signature-help"))]
+impl ScssMember {
+    /// The declaration of the member as shown in hovers and completion details, e.g. `@mixin c.button($size)`.
+    pub fn signature(&self) -> String {
+        let keyword = match self.kind {
+            ScssConstructKind::Mixin => "@mixin ",
+            ScssConstructKind::Function => "@function ",
+            _ => "",
+        };
+        let namespace = self
+            .namespace
+            .as_ref()
+            .map(|namespace| format!("{namespace}."))
+            .unwrap_or_default();
+        match &self.value {
+            Some(value) => format!("{namespace}{}: {value}", self.name),
+            None => format!(
+                "{keyword}{namespace}{}{}",
+                self.name,
+                self.parameters.as_deref().unwrap_or_default()
+            ),
+        }
+    }
+}
+
+impl LanguageService {
+    /// Resolve the URL of a stylesheet loaded by an SCSS document, or whose values a CSS document imports with
+    /// `@value`, to its URI: a partial (e.g. `_colors.scss`), a stylesheet (`colors.scss` or `colors.css`) or the index
+    /// of a folder (`colors/_index.scss`), looked up next to the document, then in the load paths of its settings. Stylesheets are looked up in the workspace index, then with
+    /// the FileSystemProvider of the service, in which case they are added to the workspace index.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The URI of the document loading the stylesheet.
+    /// * `url` - The URL of the stylesheet, e.g. `src/colors` in `@use "src/colors";`.
+    ///
+    /// # Returns
+    ///
+    /// * The URI of the stylesheet, `None` if it isn't found or is a built-in module of Sass.
+    pub fn resolve_scss_module(&self, from: &Uri, url: &str) -> Option<Uri> {
+        if url.starts_with("sass:") || url.contains("://") {
+            return None;
+        }
+
+        let mut candidates = module_uris(from, url);
+        for load_path in &self.settings_for(from).scss.load_paths {
+            // The URIs of stylesheets are relative to the folder of the document, so load paths become a folder
+            let Ok(folder) = format!("{}/", load_path.trim_end_matches('/')).parse::<Uri>() else {
+                continue;
+            };
+            candidates.extend(module_uris(&folder, url));
+        }
+
+        let index = self.workspace_index();
+        let file_system = self.file_system_provider();
+        let uri = candidates.into_iter().find(|uri| {
+            index.document(uri).is_some()
+                || file_system.is_some_and(|file_system| {
+                    FileSystemProvider::stat(file_system, uri)
+                        .is_ok_and(|stat| stat.file_type == FileType::File)
+                })
+        })?;
+        // Stylesheets outside of the indexed folders, e.g. in a load path, are indexed once resolved
+        if index.document(&uri).is_none() {
+            self.reload_from_disk(&uri).ok()?;
+        }
+        Some(uri)
+    }
+}
+
+#[cfg(any(
+    feature = "completion",
+    feature = "definition",
+    feature = "hover",
+    feature = "signature-help"
+))]
+impl LanguageService {
+    /// Get the members visible from a document: its own variables, mixins and functions, and those of the stylesheets
+    /// it loads with `@use` and `@import`, including the members these stylesheets forward. The members of LESS
+    /// documents are their own, and those of CSS documents their CSS Modules values, including the imported ones.
+    pub(crate) fn scss_members(&self, store_entry: &StoreEntry) -> Vec<ScssMember> {
+        let mut members = self.own_scss_members(store_entry);
+        match store_entry.dialect {
+            CssDialect::Scss => {}
+            CssDialect::Css => {
+                members.extend(self.icss_imported_values(store_entry));
+                return members;
+            }
+            CssDialect::Less => return members,
+        }
+
+        let mut visited = FxHashSet::from_iter([store_entry.document.uri.clone()]);
+        let constructs = store_entry.scss_constructs();
+        for module in scss_modules(&store_entry.document.text, &constructs) {
+            // Forwarded members are available to the stylesheets using the document, not to the document itself
+            if module.kind == ScssModuleKind::Forward {
+                continue;
+            }
+            let Some(uri) = self.resolve_scss_module(&store_entry.document.uri, &module.url) else {
+                continue;
+            };
+            members.extend(
+                self.exposed_scss_members(&uri, &mut visited)
+                    .into_iter()
+                    .map(|member| ScssMember {
+                        namespace: module.namespace.clone(),
+                        ..member
+                    }),
+            );
+        }
+        members
+    }
+
+    /// Get the members a stylesheet exposes to the documents loading it: its own members, those it forwards and those
+    /// of the stylesheets it imports. Stylesheets are only visited once, e.g. when they load each other.
+    fn exposed_scss_members(&self, uri: &Uri, visited: &mut FxHashSet<Uri>) -> Vec<ScssMember> {
+        if !visited.insert(uri.clone()) {
+            return Vec::new();
+        }
+        let Some(document) = self.workspace_index().document(uri) else {
+            return Vec::new();
+        };
+        let Some(entry) = document.entry() else {
+            // Persisted documents only have the symbols of their members
+            return document
+                .symbols
+                .iter()
+                .filter(|symbol| symbol.is_declaration)
+                .filter_map(|symbol| {
+                    let kind = match symbol.kind {
+                        SymbolKind::Variable => ScssConstructKind::Variable,
+                        SymbolKind::Mixin => ScssConstructKind::Mixin,
+                        SymbolKind::Function => ScssConstructKind::Function,
+                        _ => return None,
+                    };
+                    let range =
+                        to_proto::range(document.line_index(), symbol.range, self.encoding).ok()?;
+                    Some(ScssMember {
+                        kind,
+                        namespace: None,
+                        name: symbol.name.clone(),
+                        value: None,
+                        parameters: None,
+                        location: Location::new(document.uri.clone(), range),
+                    })
+                })
+                .collect();
+        };
+
+        let mut members = self.own_scss_members(entry);
+        let constructs = entry.scss_constructs();
+        for module in scss_modules(&entry.document.text, &constructs) {
+            if module.kind == ScssModuleKind::Use {
+                continue;
+            }
+            let Some(uri) = self.resolve_scss_module(&entry.document.uri, &module.url) else {
+                continue;
+            };
+            members.extend(
+                self.exposed_scss_members(&uri, visited)
+                    .into_iter()
+                    .filter_map(|mut member| {
+                        if let Some(prefix) = &module.prefix {
+                            member.name = match member.name.strip_prefix('$') {
+                                Some(name) => format!("${prefix}{name}"),
+                                None => format!("{prefix}{}", member.name),
+                            };
+                        }
+                        module.exposes(&member.name).then_some(member)
+                    }),
+            );
+        }
+        members
+    }
+
+    /// Get the values a CSS document imports with `@value … from "…";`, under the name they are used through.
+    fn icss_imported_values(&self, store_entry: &StoreEntry) -> Vec<ScssMember> {
+        let constructs = store_entry.scss_constructs();
+        icss_imports(&store_entry.document.text, &constructs)
+            .into_iter()
+            .flat_map(|import| {
+                let values = self
+                    .resolve_scss_module(&store_entry.document.uri, &import.url)
+                    .and_then(|uri| self.workspace_index().document(&uri))
+                    .and_then(|document| Some(self.own_scss_members(document.entry()?)))
+                    .unwrap_or_default();
+                import
+                    .names
+                    .into_iter()
+                    .filter_map(move |(imported, local)| {
+                        let value = values.iter().find(|value| {
+                            value.kind == ScssConstructKind::Variable && value.name == imported
+                        })?;
+                        Some(ScssMember {
+                            name: local,
+                            ..value.clone()
+                        })
+                    })
+            })
+            .collect()
+    }
+
+    /// Get the variables, mixins and functions declared in a document.
+    fn own_scss_members(&self, entry: &StoreEntry) -> Vec<ScssMember> {
+        let encoding = self.encoding_for(entry);
+        entry
+            .scss_constructs()
+            .iter()
+            .filter(|construct| {
+                matches!(
+                    construct.kind,
+                    ScssConstructKind::Variable
+                        | ScssConstructKind::Mixin
+                        | ScssConstructKind::Function
+                )
+            })
+            .filter_map(|construct| {
+                let range =
+                    to_proto::range(entry.line_index(), construct.name_range, encoding).ok()?;
+                let parameters = &entry.document.text
+                    [usize::from(construct.name_range.end())..usize::from(construct.range.end())];
+                Some(ScssMember {
+                    kind: construct.kind,
+                    namespace: None,
+                    name: construct.name.clone(),
+                    value: construct.value.as_ref().map(|(_, value)| value.clone()),
+                    parameters: parameters_of(parameters),
+                    location: Location::new(entry.document.uri.clone(), range),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(any(feature = "definition", feature = "diagnostics"))]
+impl LanguageService {
+    /// Get the placeholder selectors an SCSS document can extend, with the location of their name: its own, and those
+    /// of the stylesheets it loads with `@use`, `@forward` and `@import`, directly or not. Other dialects have none.
+    pub(crate) fn scss_placeholders(&self, store_entry: &StoreEntry) -> Vec<(String, Location)> {
+        if store_entry.dialect != CssDialect::Scss {
+            return Vec::new();
+        }
+
+        let encoding = self.encoding_for(store_entry);
+        let mut placeholders = store_entry
+            .scss_constructs()
+            .iter()
+            .filter(|construct| construct.kind == ScssConstructKind::Placeholder)
+            .filter_map(|construct| {
+                let range =
+                    to_proto::range(store_entry.line_index(), construct.name_range, encoding)
+                        .ok()?;
+                Some((
+                    construct.name.clone(),
+                    Location::new(store_entry.document.uri.clone(), range),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let mut visited = FxHashSet::from_iter([store_entry.document.uri.clone()]);
+        placeholders.extend(self.loaded_scss_placeholders(store_entry, &mut visited));
+        placeholders
+    }
+
+    /// Get the placeholder selectors of the stylesheets loaded by a document, and of those they load. Stylesheets are
+    /// only visited once, and the ones loaded by persisted documents aren't known.
+    fn loaded_scss_placeholders(
+        &self,
+        entry: &StoreEntry,
+        visited: &mut FxHashSet<Uri>,
+    ) -> Vec<(String, Location)> {
+        let mut placeholders = Vec::new();
+        let constructs = entry.scss_constructs();
+        for module in scss_modules(&entry.document.text, &constructs) {
+            let Some(uri) = self.resolve_scss_module(&entry.document.uri, &module.url) else {
+                continue;
+            };
+            if !visited.insert(uri.clone()) {
+                continue;
+            }
+            let Some(document) = self.workspace_index().document(&uri) else {
+                continue;
+            };
+
+            let encoding = document
+                .entry()
+                .map_or(self.encoding, |entry| self.encoding_for(entry));
+            placeholders.extend(
+                document
+                    .symbols
+                    .iter()
+                    .filter(|symbol| {
+                        symbol.kind == SymbolKind::Placeholder && symbol.is_declaration
+                    })
+                    .filter_map(|symbol| {
+                        let range =
+                            to_proto::range(document.line_index(), symbol.range, encoding).ok()?;
+                        Some((symbol.name.clone(), Location::new(uri.clone(), range)))
+                    }),
+            );
+            if let Some(entry) = document.entry() {
+                placeholders.extend(self.loaded_scss_placeholders(entry, visited));
+            }
+        }
+        placeholders
+    }
+}
+
+/// Get the parameters of a mixin or function from the text following its name in its declaration, e.g.
+/// `($size, $color: red)` in `@mixin button($size, $color: red)`.
+#[cfg(any(
+    feature = "completion",
+    feature = "definition",
+    feature = "hover",
+    feature = "signature-help"
+))]
+fn parameters_of(text: &str) -> Option<String> {
+    let text = text.trim_start();
+    if !text.starts_with('(') {
+        return None;
+    }
+    let mut depth = 0;
+    for (index, char) in text.char_indices() {
+        match char {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(text[..=index].to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(text.trim_end().to_string())
+}
+
+pub(crate) fn is_ident_start(byte: u8) -> bool {
+    byte.is_ascii_alphabetic() || byte == b'_' || byte == b'-' || !byte.is_ascii()
+}
+
+pub(crate) fn is_ident_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-' || !byte.is_ascii()
+}
+
+fn text_range(start: usize, end: usize) -> TextRange {
+    TextRange::new(TextSize::from(start as u32), TextSize::from(end as u32))
+}
+
+/// Whether the prelude of a block is a property of SCSS whose block contains its nested properties, e.g. `font:` in
+/// `font: { family: serif; }` or `margin: 0` in `margin: 0 { top: 1px; }`, as opposed to a selector, whose
+/// pseudo-classes aren't followed by whitespace, e.g. `a:hover`.
+pub(crate) fn is_nested_property(prelude: &str) -> bool {
+    prelude.split_once(':').is_some_and(|(name, value)| {
+        !name.is_empty()
+            && name.bytes().all(is_ident_char)
+            && (value.is_empty() || value.starts_with(char::is_whitespace))
+    })
+}
+
+/// The lexical scanner masking the constructs of SCSS documents, also used for LESS documents with the statements of
+/// `less.rs`.
+pub(crate) struct Scanner<'a> {
+    pub(crate) code: &'a str,
+    pub(crate) bytes: &'a [u8],
+    pub(crate) dialect: CssDialect,
+    pub(crate) masked: Vec<u8>,
+    pub(crate) constructs: Vec<ScssConstruct>,
+}
+
+impl<'a> Scanner<'a> {
+    pub(crate) fn new(code: &'a str, dialect: CssDialect) -> Self {
+        Self {
+            code,
+            bytes: code.as_bytes(),
+            dialect,
+            masked: code.as_bytes().to_vec(),
+            constructs: Vec::new(),
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> ScssScan {
+        self.constructs
+            .sort_by_key(|construct| construct.range.start());
+
+        ScssScan {
+            masked: String::from_utf8(self.masked).expect(
+                "only whole ranges delimited by ASCII characters are masked, with ASCII characters",
+            ),
+            constructs: self.constructs,
+        }
+    }
+
+    pub(crate) fn push(
+        &mut self,
+        kind: ScssConstructKind,
+        name: (usize, usize),
+        range: (usize, usize),
+        value: Option<(usize, usize)>,
+    ) {
+        self.constructs.push(ScssConstruct {
+            kind,
+            name: self.code[name.0..name.1].to_string(),
+            name_range: text_range(name.0, name.1),
+            range: text_range(range.0, range.1),
+            value: value
+                .map(|(start, end)| (text_range(start, end), self.code[start..end].to_string())),
+        });
+    }
+
+    /// Replace the given range with whitespace, keeping its line breaks.
+    pub(crate) fn blank(&mut self, start: usize, end: usize) {
+        for byte in &mut self.masked[start..end] {
+            if !matches!(byte, b'\n' | b'\r') {
+                *byte = b' ';
+            }
+        }
+    }
+
+    pub(crate) fn ident_end(&self, start: usize) -> usize {
+        let mut end = start;
+        while end < self.bytes.len() && is_ident_char(self.bytes[end]) {
+            end += 1;
+        }
+        end
+    }
+
+    /// Whether a `//` at `index` starts a line comment. `//` in values (e.g. `url(http://…)`) is only a comment
+    /// outside of parentheses, and never right after a colon.
+    pub(crate) fn is_line_comment(&self, index: usize, depth: usize) -> bool {
+        // CSS has no line comments, `//` only starts one in SCSS and LESS
+        self.dialect != CssDialect::Css
+            && self.bytes[index..].starts_with(b"//")
+            && depth == 0
+            && (index == 0 || self.bytes[index - 1] != b':')
+    }
+
+    pub(crate) fn comment_end(&self, start: usize) -> usize {
+        if self.bytes[start..].starts_with(b"/*") {
+            self.code[start + 2..]
+                .find("*/")
+                .map_or(self.bytes.len(), |end| start + 2 + end + 2)
+        } else {
+            self.code[start..]
+                .find('\n')
+                .map_or(self.bytes.len(), |end| start + end)
+        }
+    }
+
+    pub(crate) fn string_end(&self, start: usize) -> usize {
+        let quote = self.bytes[start];
+        let mut end = start + 1;
+        while end < self.bytes.len() && self.bytes[end] != quote && self.bytes[end] != b'\n' {
+            end += if self.bytes[end] == b'\\' { 2 } else { 1 };
+        }
+        (end + 1).min(self.bytes.len())
+    }
+
+    /// Find the end of the interpolation starting at `start`, after its closing brace. An interpolation that isn't
+    /// closed ends with its line.
+    pub(crate) fn interpolation_end(&self, start: usize) -> usize {
+        let mut depth = 0;
+        let mut i = start + 1;
+        while i < self.bytes.len() {
+            match self.bytes[i] {
+                b'"' | b'\'' => {
+                    i = self.string_end(i);
+                    continue;
+                }
+                b'{' => depth += 1,
+                b'}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return i + 1;
+                    }
+                }
+                b'\n' => return i,
+                _ => {}
+            }
+            i += 1;
+        }
+        self.bytes.len()
+    }
+
+    /// Whether an interpolation starts at `index`: `#{` in SCSS and `@{` in LESS.
+    pub(crate) fn is_interpolation(&self, index: usize) -> bool {
+        let opening = match self.dialect {
+            CssDialect::Less => b'@',
+            CssDialect::Css | CssDialect::Scss => b'#',
+        };
+        self.bytes.get(index) == Some(&opening) && self.bytes.get(index + 1) == Some(&b'{')
+    }
+
+    pub(crate) fn skip_trivia(&self, mut i: usize) -> usize {
+        loop {
+            while i < self.bytes.len() && self.bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            if self.bytes[i..].starts_with(b"/*") || self.is_line_comment(i, 0) {
+                i = self.comment_end(i);
+            } else {
+                return i;
+            }
+        }
+    }
+
+    /// Find the end of the statement starting at `start`: the first `;` outside of parentheses, or `{` or `}`
+    /// outside of interpolations. Returns the length of the code if the statement isn't terminated.
+    pub(crate) fn statement_end(&self, start: usize) -> usize {
+        let mut depth = 0usize;
+        let mut i = start;
+        while i < self.bytes.len() {
+            match self.bytes[i] {
+                b'"' | b'\'' => {
+                    i = self.string_end(i);
+                    continue;
+                }
+                b'/' if self.bytes[i..].starts_with(b"/*") || self.is_line_comment(i, depth) => {
+                    i = self.comment_end(i);
+                    continue;
+                }
+                _ if self.is_interpolation(i) => {
+                    i = self.interpolation_end(i);
+                    continue;
+                }
+                b'(' | b'[' => depth += 1,
+                b')' | b']' => depth = depth.saturating_sub(1),
+                b';' if depth == 0 => return i,
+                b'{' | b'}' => return i,
+                _ => {}
+            }
+            i += 1;
+        }
+        self.bytes.len()
+    }
+
+    /// Trim the whitespace at the end of a range.
+    pub(crate) fn trim_end(&self, start: usize, mut end: usize) -> usize {
+        while end > start && self.bytes[end - 1].is_ascii_whitespace() {
+            end -= 1;
+        }
+        end
+    }
+
+    fn scan_statements(&mut self) {
+        let mut i = 0;
+        loop {
+            i = self.skip_trivia(i);
+            let Some(&byte) = self.bytes.get(i) else {
+                break;
+            };
+
+            i = match byte {
+                b'{' | b'}' | b';' => i + 1,
+                b'$' if self.is_variable_declaration(i) => self.variable_declaration(i),
+                b'@' => self.at_rule(i),
+                _ => self.rule_or_declaration(i),
+            };
+        }
+    }
+
+    fn is_variable_declaration(&self, start: usize) -> bool {
+        let name_end = self.ident_end(start + 1);
+        name_end > start + 1 && self.bytes.get(self.skip_trivia(name_end)) == Some(&b':')
+    }
+
+    pub(crate) fn variable_declaration(&mut self, start: usize) -> usize {
+        let name_end = self.ident_end(start + 1);
+        let end = self.statement_end(start);
+        let range_end = self.trim_end(start, end);
+
+        // The value is what follows the colon, without the `!default` and `!global` flags
+        let value_start = self
+            .skip_trivia(self.skip_trivia(name_end) + 1)
+            .min(range_end);
+        let mut value_end = range_end;
+        while let Some(flag) = self.code[value_start..value_end].rfind('!') {
+            let flag_start = value_start + flag;
+            if self.ident_end(flag_start + 1) != value_end {
+                break;
+            }
+            value_end = self.trim_end(value_start, flag_start);
+        }
+
+        self.push(
+            ScssConstructKind::Variable,
+            (start, name_end),
+            (start, range_end),
+            Some((value_start, value_end)),
+        );
+        self.blank(start, self.terminated_end(end));
+        end
+    }
+
+    /// The end of a statement ending at `end`, including its semicolon.
+    pub(crate) fn terminated_end(&self, end: usize) -> usize {
+        if self.bytes.get(end) == Some(&b';') {
+            end + 1
+        } else {
+            end
+        }
+    }
+
+    fn at_rule(&mut self, start: usize) -> usize {
+        let name_end = self.ident_end(start + 1);
+        let name = &self.code[start + 1..name_end];
+        let end = self.statement_end(start);
+
+        // `@import` is CSS, but also loads SCSS stylesheets, so it is recorded along with the directives
+        let is_import = name == "import";
+        if !DIRECTIVES.contains(&name) && !is_import {
+            self.mask_prelude(name_end, end);
+            return end;
+        }
+
+        let range_end = self.trim_end(start, end);
+        let argument_start = self.skip_trivia(name_end).min(range_end);
+        let kind = match name {
+            "mixin" => ScssConstructKind::Mixin,
+            "include" => ScssConstructKind::Include,
+            "function" => ScssConstructKind::Function,
+            "extend" => ScssConstructKind::Extend,
+            _ => ScssConstructKind::Directive,
+        };
+        let name = match kind {
+            ScssConstructKind::Mixin | ScssConstructKind::Include | ScssConstructKind::Function => {
+                // Included mixins can come from a module, e.g. `@include buttons.primary;`
+                let mut argument_end = self.ident_end(argument_start);
+                while self.bytes.get(argument_end) == Some(&b'.') {
+                    argument_end = self.ident_end(argument_end + 1);
+                }
+                (argument_start, argument_end)
+            }
+            ScssConstructKind::Extend => {
+                let mut argument_end = range_end;
+                if self.code[argument_start..argument_end].ends_with("!optional") {
+                    argument_end = self.trim_end(argument_start, argument_end - "!optional".len());
+                }
+                (argument_start, argument_end)
+            }
+            _ => (start + 1, name_end),
+        };
+        self.push(kind, name, (start, range_end), None);
+
+        // SCSS imports several stylesheets at once, which the parser chokes on, imports of one stylesheet are left to it
+        if is_import && !self.code[name_end..end].contains(',') {
+            self.mask_prelude(name_end, end);
+            return end;
+        }
+
+        // Directives with a block (e.g. `@mixin`, `@if` or `@include` with content) become a nested rule, so that the
+        // declarations and rules of their block are still parsed
+        if self.bytes.get(end) == Some(&b'{') {
+            self.masked[start] = b'&';
+            self.blank(start + 1, end);
+        } else {
+            self.blank(start, self.terminated_end(end));
+        }
+        end
+    }
+
+    fn rule_or_declaration(&mut self, start: usize) -> usize {
+        let end = self.statement_end(start);
+        if self.bytes.get(end) == Some(&b'{') {
+            // Nested properties (e.g. `margin: { top: 0; }`) become a nested rule, like directives with a block, along
+            // with the value of their property if it has one, e.g. `margin: 0 { top: 1px; }`
+            let prelude = &self.code[start..self.trim_end(start, end)];
+            if prelude.ends_with(':') || is_nested_property(prelude) {
+                self.masked[start] = b'&';
+                self.blank(start + 1, end);
+            } else {
+                self.mask_prelude(start, end);
+            }
+        } else {
+            self.mask_values(start, end);
+        }
+        end
+    }
+
+    fn mask_interpolation(&mut self, start: usize) -> usize {
+        let end = self.interpolation_end(start);
+        self.push(
+            ScssConstructKind::Interpolation,
+            (start, end),
+            (start, end),
+            None,
+        );
+        // Interpolations are replaced with an identifier, which is valid wherever they are used (selectors, property
+        // names and values)
+        self.masked[start..end].fill(b'_');
+        end
+    }
+
+    /// Mask the SCSS constructs of the prelude of a rule or at-rule in the given range: interpolations and placeholder
+    /// selectors, or interpolations and variables in LESS.
+    pub(crate) fn mask_prelude(&mut self, start: usize, end: usize) {
+        let mut i = start;
+        while i < end {
+            match self.bytes[i] {
+                b'"' | b'\'' => {
+                    i = self.string_end(i);
+                    continue;
+                }
+                b'/' if self.bytes[i..].starts_with(b"/*") || self.is_line_comment(i, 0) => {
+                    i = self.comment_end(i);
+                    continue;
+                }
+                _ if self.is_interpolation(i) => {
+                    i = self.mask_interpolation(i);
+                    continue;
+                }
+                // Placeholder selectors become type selectors
+                b'%' if self.dialect == CssDialect::Scss
+                    && self
+                        .bytes
+                        .get(i + 1)
+                        .is_some_and(|&byte| is_ident_start(byte)) =>
+                {
+                    let name_end = self.ident_end(i + 1);
+                    self.push(
+                        ScssConstructKind::Placeholder,
+                        (i + 1, name_end),
+                        (i, name_end),
+                        None,
+                    );
+                    self.masked[i] = b'_';
+                    i = name_end;
+                    continue;
+                }
+                // LESS variables can be used in the prelude of at-rules, e.g. `@media @phone {`
+                b'@' if self.dialect == CssDialect::Less => {
+                    i = self.mask_less_variable(i);
+                    continue;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    /// Mask the SCSS expressions of the declarations or at-rule prelude in the given range: interpolations, module
+    /// namespaces (e.g. `math.div()`), parentheses used for grouping and arithmetic operators, and the variables and
+    /// escapes of LESS.
+    pub(crate) fn mask_values(&mut self, start: usize, end: usize) {
+        // Whether each open parenthesis was masked, those of functions are kept
+        let mut parentheses = Vec::<bool>::new();
+        let mut i = start;
+        while i < end {
+            let byte = self.bytes[i];
+            let depth = parentheses.iter().filter(|masked| !**masked).count();
+            let previous = i.checked_sub(1).map(|previous| self.bytes[previous]);
+            let next = self.bytes.get(i + 1).copied();
+
+            match byte {
+                b'"' | b'\'' => {
+                    i = self.string_end(i);
+                    continue;
+                }
+                b'/' if self.bytes[i..].starts_with(b"/*") || self.is_line_comment(i, depth) => {
+                    i = self.comment_end(i);
+                    continue;
+                }
+                _ if self.is_interpolation(i) => {
+                    i = self.mask_interpolation(i);
+                    continue;
+                }
+                b'$' => {
+                    i = self.ident_end(i + 1);
+                    continue;
+                }
+                b'@' if self.dialect == CssDialect::Less => {
+                    i = self.mask_less_variable(i);
+                    continue;
+                }
+                // LESS escapes, e.g. `~"calc(100% - 10px)"`, become the string they escape
+                b'~' if self.dialect == CssDialect::Less && matches!(next, Some(b'"' | b'\'')) => {
+                    self.masked[i] = b' '
+                }
+                b'-' if depth == 0
+                    && previous.is_some_and(|byte| byte.is_ascii_whitespace())
+                    && next.is_some_and(|byte| byte.is_ascii_whitespace()) =>
+                {
+                    self.masked[i] = b' '
+                }
+                _ if is_ident_char(byte) => {
+                    let ident_end = self.ident_end(i);
+                    // The content of `url()` is kept as is, e.g. the dots of `url(image.png)`
+                    i = if self.code[i..ident_end].eq_ignore_ascii_case("url")
+                        && self.bytes.get(ident_end) == Some(&b'(')
+                    {
+                        self.code[ident_end..end]
+                            .find(')')
+                            .map_or(end, |close| ident_end + close + 1)
+                    } else {
+                        ident_end
+                    };
+                    continue;
+                }
+                b'(' => {
+                    let grouping = !previous.is_some_and(is_ident_char);
+                    if grouping {
+                        self.masked[i] = b' ';
+                    }
+                    parentheses.push(grouping);
+                }
+                b')' if parentheses.pop() == Some(true) => {
+                    self.masked[i] = b' ';
+                }
+                b'.' if previous.is_some_and(|byte| byte.is_ascii_alphabetic())
+                    && next.is_some_and(|byte| is_ident_start(byte) || byte == b'$') =>
+                {
+                    self.masked[i] = b'_';
+                }
+                // `calc()` and the other math functions have operators of their own, only those outside of functions
+                // are masked. Signs (e.g. `+1`) and percentages (e.g. `50%`) are kept.
+                b'*' if depth == 0 => self.masked[i] = b' ',
+                b'+' if depth == 0
+                    && !(previous.is_none_or(|byte| byte.is_ascii_whitespace())
+                        && next.is_some_and(|byte| byte.is_ascii_digit() || byte == b'.')) =>
+                {
+                    self.masked[i] = b' '
+                }
+                b'%' if depth == 0 && previous.is_some_and(|byte| byte.is_ascii_whitespace()) => {
+                    self.masked[i] = b' '
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    /// Find the variables used outside of their declaration, anywhere in the document but in comments.
+    fn scan_variable_references(&mut self) {
+        let declarations = self
+            .constructs
+            .iter()
+            .filter(|construct| construct.kind == ScssConstructKind::Variable)
+            .map(|construct| construct.name_range.start())
+            .collect::<Vec<_>>();
+
+        let mut i = 0;
+        while i < self.bytes.len() {
+            match self.bytes[i] {
+                b'/' if self.bytes[i..].starts_with(b"/*") || self.is_line_comment(i, 0) => {
+                    i = self.comment_end(i);
+                    continue;
+                }
+                b'$' if self
+                    .bytes
+                    .get(i + 1)
+                    .is_some_and(|&byte| is_ident_start(byte)) =>
+                {
+                    let end = self.ident_end(i + 1);
+                    if !declarations.contains(&TextSize::from(i as u32)) {
+                        self.push(
+                            ScssConstructKind::VariableReference,
+                            (i, end),
+                            (i, end),
+                            None,
+                        );
+                    }
+                    i = end;
+                    continue;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_css_with_dialect, CssDialect};
+
+    const SCSS: &str = r#"@use "sass:math";
+// Colors
+$primary: #ff0000 !default;
+$spacing: 4px;
+
+@mixin button($size) {
+  padding: math.div($spacing, 2) $size * 2;
+  width: calc(100% - #{$spacing});
+}
+
+%card { border: 1px solid $primary; }
+
+.button-#{$size} {
+  @include button(2px);
+  @extend %card;
+  color: $primary;
+  &:hover { margin: ($spacing + 1px) -$spacing; }
+  @if $size > 1 { opacity: 0.5; } @else { opacity: 1; }
+}
+"#;
+
+    #[test]
+    fn test_scan_scss_masks_to_css() {
+        let scan = scan_scss(SCSS);
+
+        assert_eq!(scan.masked.len(), SCSS.len());
+        assert!(
+            !parse_css_with_dialect(SCSS, CssDialect::Scss).has_errors(),
+            "Masked SCSS should parse without errors, got:\n{}",
+            scan.masked
+        );
+    }
+
+    #[test]
+    fn test_scan_scss_constructs() {
+        let constructs = scan_scss(SCSS)
+            .constructs
+            .into_iter()
+            .filter(|construct| construct.kind != ScssConstructKind::VariableReference)
+            .map(|construct| (construct.kind, construct.name))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            constructs,
+            vec![
+                (ScssConstructKind::Directive, "use".to_string()),
+                (ScssConstructKind::Variable, "$primary".to_string()),
+                (ScssConstructKind::Variable, "$spacing".to_string()),
+                (ScssConstructKind::Mixin, "button".to_string()),
+                (ScssConstructKind::Interpolation, "#{$spacing}".to_string()),
+                (ScssConstructKind::Placeholder, "card".to_string()),
+                (ScssConstructKind::Interpolation, "#{$size}".to_string()),
+                (ScssConstructKind::Include, "button".to_string()),
+                (ScssConstructKind::Extend, "%card".to_string()),
+                (ScssConstructKind::Directive, "if".to_string()),
+                (ScssConstructKind::Directive, "else".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_scss_variables() {
+        let scan = scan_scss("$primary: #ff0000 !default;\na { color: $primary; }");
+
+        let declaration = &scan.constructs[0];
+        assert_eq!(declaration.range, TextRange::new(0.into(), 26.into()));
+        assert_eq!(
+            declaration.value,
+            Some((TextRange::new(10.into(), 17.into()), "#ff0000".to_string()))
+        );
+
+        let reference = &scan.constructs[1];
+        assert_eq!(reference.kind, ScssConstructKind::VariableReference);
+        assert_eq!(reference.name, "$primary");
+        assert_eq!(reference.name_range, TextRange::new(39.into(), 47.into()));
+    }
+
+    #[test]
+    fn test_scan_scss_nested_properties() {
+        assert_eq!(
+            scan_scss(".a { font: { family: serif; } margin: 0 { top: 1px; } }").masked,
+            ".a { &     { family: serif; } &         { top: 1px; } }"
+        );
+        for code in [
+            ".a { margin: 0 { top: 1px; } }",
+            ".a { font: bold { family: serif; size: 1rem; } }",
+            ".a {\n  margin: 0 auto {\n    top: 1px;\n  }\n}",
+        ] {
+            assert!(
+                !parse_css_with_dialect(code, CssDialect::Scss).has_errors(),
+                "{code}"
+            );
+        }
+        // The pseudo-classes of selectors aren't followed by whitespace
+        assert_eq!(
+            scan_scss(".a { a:hover { color: red; } }").masked,
+            ".a { a:hover { color: red; } }"
+        );
+    }
+
+    #[test]
+    fn test_scan_scss_keeps_css() {
+        let code = "a { width: calc(1px + 2px * 3); margin: +1px 50%; background: url(http://a.b/c.png); }";
+        assert_eq!(scan_scss(code).masked, code);
+    }
+
+    #[test]
+    fn test_scss_modules() {
+        let code = "@use \"sass:math\";\n@use \"src/_colors.scss\";\n@use 'theme' as t;\n@forward \"list\" as list-* hide list-reset, $list-gap;\n@import \"a\", \"b\";";
+        let modules = scss_modules(code, &scan_scss(code).constructs);
+
+        assert_eq!(
+            modules
+                .iter()
+                .map(|module| (
+                    module.kind,
+                    module.url.as_str(),
+                    module.namespace.as_deref()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (ScssModuleKind::Use, "src/_colors.scss", Some("colors")),
+                (ScssModuleKind::Use, "theme", Some("t")),
+                (ScssModuleKind::Forward, "list", None),
+                (ScssModuleKind::Import, "a", None),
+                (ScssModuleKind::Import, "b", None),
+            ]
+        );
+        assert_eq!(&code[modules[1].range], "theme");
+        assert_eq!(modules[2].prefix.as_deref(), Some("list-"));
+        assert!(modules[2].exposes("list-append"));
+        assert!(!modules[2].exposes("$list-gap"));
+    }
+
+    #[test]
+    fn test_namespace_before() {
+        let namespace = |code: &str| namespace_before(code, TextSize::of(code));
+
+        assert_eq!(namespace(".a { width: colors."), Some("colors".to_string()));
+        assert_eq!(
+            namespace(".a { width: →colors."),
+            Some("colors".to_string())
+        );
+        assert_eq!(namespace(".a { width: →."), None);
+        assert_eq!(namespace(".a { width: "), None);
+    }
+}
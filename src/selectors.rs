@@ -0,0 +1,547 @@
+//! Specificity of selectors, as defined by Selectors Level 4.
+//!
+//! This doesn't depend on the LSP features, so that tools built on csslsrs (e.g. linters comparing the specificity of
+//! rules) can use it on their own selectors.
+
+use std::{
+    fmt,
+    ops::{Add, AddAssign},
+};
+
+use biome_css_syntax::{
+    AnyCssCompoundSelector, AnyCssPseudoClass, AnyCssPseudoClassNthSelector, AnyCssPseudoElement,
+    AnyCssRelativeSelector, AnyCssRule, AnyCssSelector, AnyCssSimpleSelector, AnyCssSubSelector,
+    CssCompoundSelector, CssLanguage, CssNestedQualifiedRule, CssQualifiedRule, CssSyntaxKind,
+};
+use biome_rowan::{
+    AstNode, AstNodeList, AstSeparatedList, Direction, SyntaxNode, SyntaxResult, SyntaxToken,
+};
+
+use crate::parser::parse_css;
+
+/// The specificity of a selector. Specificities compare by their ids first, then their classes and then their types.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Specificity {
+    /// The number of id selectors, e.g. `#header`.
+    pub ids: u32,
+    /// The number of class selectors, attribute selectors and pseudo-classes, e.g. `.button`, `[href]` or `:hover`.
+    pub classes: u32,
+    /// The number of type selectors and pseudo-elements, e.g. `a` or `::before`.
+    pub types: u32,
+}
+
+impl Specificity {
+    pub const fn new(ids: u32, classes: u32, types: u32) -> Self {
+        Self {
+            ids,
+            classes,
+            types,
+        }
+    }
+}
+
+const ID: Specificity = Specificity::new(1, 0, 0);
+const CLASS: Specificity = Specificity::new(0, 1, 0);
+const TYPE: Specificity = Specificity::new(0, 0, 1);
+
+impl Add for Specificity {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            ids: self.ids + other.ids,
+            classes: self.classes + other.classes,
+            types: self.types + other.types,
+        }
+    }
+}
+
+impl AddAssign for Specificity {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl fmt::Display for Specificity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.ids, self.classes, self.types)
+    }
+}
+
+/// Compute the specificity of a selector, e.g. `#header .button:hover`.
+///
+/// `&` isn't nested in another rule, so it counts as `:scope`. Use `selector_specificity` to resolve it against the
+/// parent rules of a parsed document.
+///
+/// # Arguments
+///
+/// * `selector` - The text of the selector. For a selector list, the highest specificity of its selectors is returned.
+///
+/// # Returns
+///
+/// * `Specificity` - The specificity of the selector. Invalid parts of the selector don't count.
+pub fn specificity(selector: &str) -> Specificity {
+    let parse = parse_css(&format!("{selector} {{}}"));
+
+    parse
+        .tree()
+        .rules()
+        .iter()
+        .next()
+        .and_then(|rule| match rule {
+            AnyCssRule::CssQualifiedRule(rule) => Some(rule_specificity(rule.syntax())),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Compute the specificity of a selector of a parsed document.
+///
+/// `&` has the specificity of the selectors of the parent rule, and the selectors of nested rules not containing `&`
+/// are relative to it, as if they started with `& `.
+///
+/// # Arguments
+///
+/// * `selector` - The selector, e.g. an item of the prelude of a rule.
+///
+/// # Returns
+///
+/// * `Specificity` - The specificity of the selector. Invalid parts of the selector don't count.
+pub fn selector_specificity(selector: &AnyCssSelector) -> Specificity {
+    let nesting = nesting_specificity(selector.syntax());
+    let specificity = compute_selector(selector, nesting);
+
+    let in_nested_prelude = selector
+        .syntax()
+        .ancestors()
+        .find(is_style_rule)
+        .is_some_and(|rule| parent_rule(&rule).is_some());
+    if in_nested_prelude && !contains_nesting_selector(selector) {
+        specificity + nesting
+    } else {
+        specificity
+    }
+}
+
+/// Resolve the selectors of a rule against the rules it is nested in, e.g. `.card .title:hover` for `&:hover` in
+/// `.title` in `.card`, through the at-rules between them such as `@media`.
+///
+/// `&` becomes the selector of the parent rule, or `:is()` of its selectors when it has several or when it is
+/// complex and `&` doesn't start the selector. Selectors without `&` are relative to the parent rule, as if they
+/// started with `& `. `&` outside of nested rules is kept.
+///
+/// # Arguments
+///
+/// * `rule` - A qualified rule or a nested qualified rule of a parsed document.
+///
+/// # Returns
+///
+/// * `Vec<String>` - The resolved selectors, one for each selector of the rule. Bogus selectors are skipped.
+pub fn resolved_selectors(rule: &SyntaxNode<CssLanguage>) -> Vec<String> {
+    let parents = parent_rule(rule)
+        .map(|parent| resolved_selectors(&parent))
+        .filter(|parents| !parents.is_empty());
+
+    rule_selectors(rule)
+        .into_iter()
+        .map(|(selector, combinator)| match &parents {
+            Some(parents) => resolve_selector(&selector, combinator.as_deref(), parents),
+            None => selector.syntax().text_trimmed().to_string(),
+        })
+        .collect()
+}
+
+/// The selectors of a style rule, with the combinator starting the relative selectors of nested rules, e.g. `>`.
+fn rule_selectors(rule: &SyntaxNode<CssLanguage>) -> Vec<(AnyCssSelector, Option<String>)> {
+    if let Some(rule) = CssQualifiedRule::cast_ref(rule) {
+        rule.prelude()
+            .iter()
+            .flatten()
+            .map(|selector| (selector, None))
+            .collect()
+    } else if let Some(rule) = CssNestedQualifiedRule::cast_ref(rule) {
+        rule.prelude()
+            .iter()
+            .flatten()
+            .filter_map(|selector| match selector {
+                AnyCssRelativeSelector::CssRelativeSelector(selector) => Some((
+                    selector.selector().ok()?,
+                    selector
+                        .combinator()
+                        .map(|combinator| combinator.text_trimmed().to_string()),
+                )),
+                AnyCssRelativeSelector::CssBogusSelector(_) => None,
+            })
+            .collect()
+    } else {
+        Vec::new()
+    }
+}
+
+fn resolve_selector(
+    selector: &AnyCssSelector,
+    combinator: Option<&str>,
+    parents: &[String],
+) -> String {
+    let text = selector.syntax().text_trimmed().to_string();
+    let start = selector.syntax().text_trimmed_range().start();
+    let is_compound = |parent: &String| !parent.contains([' ', '>', '+', '~']);
+    let nesting = |at_start: bool| match parents {
+        [parent] if at_start || is_compound(parent) => parent.clone(),
+        _ => format!(":is({})", parents.join(", ")),
+    };
+
+    let ampersands = selector
+        .syntax()
+        .descendants_tokens(Direction::Next)
+        .filter(|token| token.kind() == CssSyntaxKind::AMP)
+        .map(|token| usize::from(token.text_trimmed_range().start() - start))
+        .collect::<Vec<_>>();
+    if ampersands.is_empty() {
+        let combinator =
+            combinator.map_or(String::from(" "), |combinator| format!(" {combinator} "));
+        return format!("{}{combinator}{text}", nesting(true));
+    }
+
+    let mut resolved = String::with_capacity(text.len());
+    let mut last = 0;
+    for offset in ampersands {
+        resolved.push_str(&text[last..offset]);
+        resolved.push_str(&nesting(offset == 0));
+        last = offset + 1;
+    }
+    resolved.push_str(&text[last..]);
+    match combinator {
+        Some(combinator) => format!("{combinator} {resolved}"),
+        None => resolved,
+    }
+}
+
+fn is_style_rule(node: &SyntaxNode<CssLanguage>) -> bool {
+    matches!(
+        node.kind(),
+        CssSyntaxKind::CSS_QUALIFIED_RULE | CssSyntaxKind::CSS_NESTED_QUALIFIED_RULE
+    )
+}
+
+/// The closest style rule a rule is nested in, through at-rules such as `@media`.
+fn parent_rule(rule: &SyntaxNode<CssLanguage>) -> Option<SyntaxNode<CssLanguage>> {
+    rule.ancestors().skip(1).find(is_style_rule)
+}
+
+/// The specificity of `&` in the selectors of a node, `:scope`'s outside of nested rules.
+fn nesting_specificity(node: &SyntaxNode<CssLanguage>) -> Specificity {
+    let mut rules = node.ancestors().filter(is_style_rule);
+
+    match (rules.next(), rules.next()) {
+        (Some(_), Some(parent)) => rule_specificity(&parent),
+        _ => CLASS,
+    }
+}
+
+/// The highest specificity of the selectors of a rule, which is the specificity of `&` in its nested rules.
+fn rule_specificity(rule: &SyntaxNode<CssLanguage>) -> Specificity {
+    let nesting = nesting_specificity(rule);
+    let is_nested = parent_rule(rule).is_some();
+
+    rule_selectors(rule)
+        .into_iter()
+        .map(|(selector, _)| {
+            let specificity = compute_selector(&selector, nesting);
+            if is_nested && !contains_nesting_selector(&selector) {
+                specificity + nesting
+            } else {
+                specificity
+            }
+        })
+        .max()
+        .unwrap_or_default()
+}
+
+fn contains_nesting_selector(selector: &AnyCssSelector) -> bool {
+    selector
+        .syntax()
+        .descendants()
+        .filter_map(CssCompoundSelector::cast)
+        .any(|compound| compound.nesting_selector_token().is_some())
+}
+
+fn max_specificity(
+    selectors: impl Iterator<Item = AnyCssSelector>,
+    nesting: Specificity,
+) -> Specificity {
+    selectors
+        .map(|selector| compute_selector(&selector, nesting))
+        .max()
+        .unwrap_or_default()
+}
+
+fn compute_selector(selector: &AnyCssSelector, nesting: Specificity) -> Specificity {
+    match selector {
+        AnyCssSelector::CssComplexSelector(complex) => {
+            let left = complex.left().map_or(Specificity::default(), |left| {
+                compute_selector(&left, nesting)
+            });
+            let right = complex.right().map_or(Specificity::default(), |right| {
+                compute_selector(&right, nesting)
+            });
+            left + right
+        }
+        AnyCssSelector::CssCompoundSelector(compound) => compute_compound(compound, nesting),
+        AnyCssSelector::CssBogusSelector(_) => Specificity::default(),
+    }
+}
+
+fn compute_compound(compound: &CssCompoundSelector, nesting: Specificity) -> Specificity {
+    let mut specificity = Specificity::default();
+
+    if compound.nesting_selector_token().is_some() {
+        specificity += nesting;
+    }
+    // The universal selector doesn't count
+    if let Some(AnyCssSimpleSelector::CssTypeSelector(_)) = compound.simple_selector() {
+        specificity += TYPE;
+    }
+
+    compound
+        .sub_selectors()
+        .iter()
+        .fold(specificity, |specificity, sub_selector| {
+            specificity + compute_sub_selector(&sub_selector, nesting)
+        })
+}
+
+fn compute_sub_selector(sub_selector: &AnyCssSubSelector, nesting: Specificity) -> Specificity {
+    match sub_selector {
+        AnyCssSubSelector::CssIdSelector(_) => ID,
+        AnyCssSubSelector::CssClassSelector(_) | AnyCssSubSelector::CssAttributeSelector(_) => {
+            CLASS
+        }
+        AnyCssSubSelector::CssPseudoClassSelector(pseudo_class) => pseudo_class
+            .class()
+            .map_or(Specificity::default(), |class| {
+                compute_pseudo_class(&class, nesting)
+            }),
+        AnyCssSubSelector::CssPseudoElementSelector(pseudo_element) => {
+            match pseudo_element.element() {
+                // e.g. `::slotted(.button)`, which counts its argument
+                Ok(AnyCssPseudoElement::CssPseudoElementFunctionSelector(function)) => {
+                    TYPE + function
+                        .selector()
+                        .map_or(Specificity::default(), |selector| {
+                            compute_selector(&selector, nesting)
+                        })
+                }
+                Ok(AnyCssPseudoElement::CssBogusPseudoElement(_)) | Err(_) => {
+                    Specificity::default()
+                }
+                Ok(_) => TYPE,
+            }
+        }
+        AnyCssSubSelector::CssBogusSubSelector(_) => Specificity::default(),
+    }
+}
+
+fn compute_pseudo_class(class: &AnyCssPseudoClass, nesting: Specificity) -> Specificity {
+    match class {
+        AnyCssPseudoClass::CssPseudoClassIdentifier(identifier) => {
+            let name = identifier
+                .name()
+                .and_then(|name| name.value_token())
+                .map(|token| token.text_trimmed().to_ascii_lowercase())
+                .unwrap_or_default();
+
+            // The pseudo-elements of CSS 2 can be written with a single colon
+            match name.as_str() {
+                "before" | "after" | "first-line" | "first-letter" => TYPE,
+                _ => CLASS,
+            }
+        }
+        AnyCssPseudoClass::CssPseudoClassFunctionSelectorList(function) => {
+            let arguments = max_specificity(function.selectors().iter().flatten(), nesting);
+            compute_selector_function(function.name(), arguments)
+        }
+        AnyCssPseudoClass::CssPseudoClassFunctionCompoundSelectorList(function) => {
+            let arguments = function
+                .compound_selectors()
+                .iter()
+                .flatten()
+                .filter_map(|selector| match selector {
+                    AnyCssCompoundSelector::CssCompoundSelector(compound) => {
+                        Some(compute_compound(&compound, nesting))
+                    }
+                    AnyCssCompoundSelector::CssBogusSelector(_) => None,
+                })
+                .max()
+                .unwrap_or_default();
+            compute_selector_function(function.name(), arguments)
+        }
+        // `:has()`
+        AnyCssPseudoClass::CssPseudoClassFunctionRelativeSelectorList(function) => function
+            .relative_selectors()
+            .iter()
+            .flatten()
+            .filter_map(|selector| match selector {
+                AnyCssRelativeSelector::CssRelativeSelector(selector) => selector.selector().ok(),
+                AnyCssRelativeSelector::CssBogusSelector(_) => None,
+            })
+            .map(|selector| compute_selector(&selector, nesting))
+            .max()
+            .unwrap_or_default(),
+        // The `:global()` and `:local()` of CSS Modules only wrap their argument
+        AnyCssPseudoClass::CssPseudoClassFunctionSelector(function) => function
+            .selector()
+            .map_or(Specificity::default(), |selector| {
+                compute_selector(&selector, nesting)
+            }),
+        // `:host()` and `:host-context()`, which count their argument
+        AnyCssPseudoClass::CssPseudoClassFunctionCompoundSelector(function) => {
+            match function.selector() {
+                Ok(AnyCssCompoundSelector::CssCompoundSelector(compound)) => {
+                    CLASS + compute_compound(&compound, nesting)
+                }
+                _ => CLASS,
+            }
+        }
+        // `:nth-child(2n of .item)` counts the selectors after `of`
+        AnyCssPseudoClass::CssPseudoClassFunctionNth(function) => match function.selector() {
+            Ok(AnyCssPseudoClassNthSelector::CssPseudoClassNthSelector(selector)) => {
+                CLASS
+                    + selector
+                        .of_selector()
+                        .map_or(Specificity::default(), |of_selector| {
+                            max_specificity(of_selector.selectors().iter().flatten(), nesting)
+                        })
+            }
+            _ => CLASS,
+        },
+        AnyCssPseudoClass::CssPseudoClassFunctionIdentifier(_)
+        | AnyCssPseudoClass::CssPseudoClassFunctionValueList(_) => CLASS,
+        AnyCssPseudoClass::CssBogusPseudoClass(_) => Specificity::default(),
+    }
+}
+
+/// The specificity of a pseudo-class taking a list of selectors, e.g. `:is()`, `:not()` or `:where()`.
+fn compute_selector_function(
+    name: SyntaxResult<SyntaxToken<CssLanguage>>,
+    arguments: Specificity,
+) -> Specificity {
+    let name = name
+        .map(|name| name.text_trimmed().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match name.as_str() {
+        "where" => Specificity::default(),
+        "is" | "not" | "matches" | "any" => arguments,
+        _ => CLASS + arguments,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_specificity() {
+        assert_eq!(specificity("*"), Specificity::new(0, 0, 0));
+        assert_eq!(specificity("a"), Specificity::new(0, 0, 1));
+        assert_eq!(specificity("ul li.active"), Specificity::new(0, 1, 2));
+        assert_eq!(
+            specificity("#header .button[disabled]:hover::before"),
+            Specificity::new(1, 3, 1)
+        );
+        assert_eq!(specificity("a:before"), Specificity::new(0, 0, 2));
+        assert_eq!(specificity("a, #b, .c"), Specificity::new(1, 0, 0));
+    }
+
+    #[test]
+    fn test_specificity_of_functional_pseudo_classes() {
+        assert_eq!(specificity(":is(#a, .b) c"), Specificity::new(1, 0, 1));
+        assert_eq!(specificity(":not(.a, .b.c)"), Specificity::new(0, 2, 0));
+        assert_eq!(specificity(":where(#a) .b"), Specificity::new(0, 1, 0));
+        assert_eq!(specificity("a:has(> img, #b)"), Specificity::new(1, 0, 1));
+        assert_eq!(
+            specificity("li:nth-child(2n + 1 of .item)"),
+            Specificity::new(0, 2, 1)
+        );
+    }
+
+    #[test]
+    fn test_specificity_of_nested_selectors() {
+        assert_eq!(specificity("&"), Specificity::new(0, 1, 0));
+
+        let parse = parse_css("#app, .card { & .title { } .body { &:hover, div > & { } } }");
+        let specificities = parse
+            .syntax()
+            .descendants()
+            .filter_map(AnyCssSelector::cast)
+            .filter(|selector| {
+                selector
+                    .syntax()
+                    .parent()
+                    .is_some_and(|parent| parent.kind() == CssSyntaxKind::CSS_RELATIVE_SELECTOR)
+            })
+            .map(|selector| selector_specificity(&selector))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            specificities,
+            vec![
+                // `#app .title`
+                Specificity::new(1, 1, 0),
+                // `#app .body`
+                Specificity::new(1, 1, 0),
+                // `#app .body:hover`
+                Specificity::new(1, 2, 0),
+                // `div > #app .body`
+                Specificity::new(1, 1, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_specificity_in_nested_at_rules() {
+        let parse = parse_css("#app { @media (min-width: 100px) { .title { } } }");
+        let selector = parse
+            .syntax()
+            .descendants()
+            .filter_map(AnyCssSelector::cast)
+            .last()
+            .unwrap();
+
+        // `#app .title`
+        assert_eq!(selector_specificity(&selector), Specificity::new(1, 1, 0));
+    }
+
+    #[test]
+    fn test_resolved_selectors() {
+        let parse = parse_css(
+            "& .top { }\n.card { &:hover { } > .title { .icon, & + & { } } @media print { .body { } } }\n#a, .b { .c & { } }\nul li { .x & { } }",
+        );
+        let resolved = parse
+            .syntax()
+            .descendants()
+            .filter(is_style_rule)
+            .map(|rule| resolved_selectors(&rule))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            resolved,
+            vec![
+                vec!["& .top".to_string()],
+                vec![".card".to_string()],
+                vec![".card:hover".to_string()],
+                vec![".card > .title".to_string()],
+                vec![
+                    ".card > .title .icon".to_string(),
+                    ".card > .title + :is(.card > .title)".to_string(),
+                ],
+                vec![".card .body".to_string()],
+                vec!["#a".to_string(), ".b".to_string()],
+                vec![".c :is(#a, .b)".to_string()],
+                vec!["ul li".to_string()],
+                vec![".x :is(ul li)".to_string()],
+            ]
+        );
+    }
+}
@@ -0,0 +1,83 @@
+//! The FileSystemProvider of the server. Unlike embedders of the library, e.g. in the browser, the server runs next to
+//! the files of the workspace, and reads them directly.
+
+use std::{fs, io, path::PathBuf, time::UNIX_EPOCH};
+
+use lsp_types::Uri;
+
+use crate::file_system::{FileStat, FileSystemProvider, FileType};
+
+/// A FileSystemProvider reading `file://` URIs from the local file system.
+pub struct LocalFileSystem;
+
+/// Get the path of a `file://` URI.
+fn path(uri: &Uri) -> io::Result<PathBuf> {
+    let is_file = uri
+        .scheme()
+        .is_some_and(|scheme| scheme.as_str().eq_ignore_ascii_case("file"));
+    if !is_file {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("`{}` is not a file URI", uri.as_str()),
+        ));
+    }
+
+    let path = uri
+        .path()
+        .as_estr()
+        .decode()
+        .into_string()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "the path is not UTF-8"))?;
+    // The paths of Windows URIs start with a slash before their drive, e.g. `/C:/Users`
+    let path = match path.strip_prefix('/') {
+        Some(windows_path) if cfg!(windows) && windows_path.get(1..2) == Some(":") => windows_path,
+        _ => &*path,
+    };
+
+    Ok(PathBuf::from(path))
+}
+
+fn file_type(file_type: fs::FileType) -> FileType {
+    if file_type.is_symlink() {
+        FileType::SymbolicLink
+    } else if file_type.is_dir() {
+        FileType::Directory
+    } else if file_type.is_file() {
+        FileType::File
+    } else {
+        FileType::Unknown
+    }
+}
+
+impl FileSystemProvider for LocalFileSystem {
+    fn read_file(&self, uri: &Uri) -> io::Result<String> {
+        fs::read_to_string(path(uri)?)
+    }
+
+    fn read_directory(&self, uri: &Uri) -> io::Result<Vec<(String, FileType)>> {
+        fs::read_dir(path(uri)?)?
+            .map(|entry| {
+                let entry = entry?;
+                Ok((
+                    entry.file_name().to_string_lossy().into_owned(),
+                    file_type(entry.file_type()?),
+                ))
+            })
+            .collect()
+    }
+
+    fn stat(&self, uri: &Uri) -> io::Result<FileStat> {
+        let metadata = fs::metadata(path(uri)?)?;
+        let mtime = metadata
+            .modified()
+            .unwrap_or(UNIX_EPOCH)
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis() as u64);
+
+        Ok(FileStat {
+            file_type: file_type(metadata.file_type()),
+            size: metadata.len(),
+            mtime,
+        })
+    }
+}
@@ -0,0 +1,705 @@
+//! A language server answering the requests of a client with a LanguageService, run by the `csslsrs-server` binary.
+//!
+//! `Server` implements the lifecycle of the LSP (`initialize`, `shutdown` and `exit`) around a `JsonRpcAdapter`, and
+//! pushes diagnostics to clients that can't pull them. It handles messages independently of their `Transport`, e.g.
+//! stdio, a TCP socket or a WebSocket.
+//!
+//! The server is configured with `ServerSettings`, sent as `initializationOptions` and then fetched with
+//! `workspace/configuration` for the `css` section whenever the client notifies `workspace/didChangeConfiguration`.
+//! Features the client can register dynamically are registered and unregistered as the settings enable or disable
+//! them. Other features keep their state negotiated in `initialize`.
+//!
+//! The commands of the LanguageService are executed with `workspace/executeCommand`. The edit of
+//! `csslsrs.applyFixAll` is applied with a `workspace/applyEdit` request to the client, and
+//! `csslsrs.reindexWorkspace` indexes the workspace folders of the client when it has no arguments, reading their
+//! files with a `LocalFileSystem`.
+//!
+//! Clients pulling diagnostics can pull those of every indexed stylesheet with `workspace/diagnostic`, open or not.
+//! The server asks them to pull again with `workspace/diagnostic/refresh` when settings change or the workspace is
+//! reindexed.
+//!
+//! Long requests, i.e. `workspace/diagnostic` and the commands indexing or fixing the workspace, report their progress
+//! with `$/progress`, using the `workDoneToken` of the request or a token created with `window/workDoneProgress/create`.
+//! `serve` sends the progress while the request runs, see `Server::handle_message_with`.
+
+use std::io::{self, BufRead, Write};
+
+use lsp_types::{
+    notification::{self, Notification},
+    request::{self, Request},
+    ApplyWorkspaceEditParams, ConfigurationItem, ConfigurationParams, ExecuteCommandOptions,
+    ExecuteCommandParams, InitializeParams, InitializeResult, ProgressParams, ProgressParamsValue,
+    ProgressToken, RegistrationParams, ServerCapabilities, ServerInfo, Unregistration,
+    UnregistrationParams, Uri, WorkDoneProgress, WorkDoneProgressBegin,
+    WorkDoneProgressCreateParams, WorkDoneProgressEnd, WorkDoneProgressReport, WorkspaceEdit,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+mod file_system;
+mod transport;
+
+pub use file_system::LocalFileSystem;
+pub use transport::{read_message, write_message, StreamTransport, Transport};
+
+use transport::is_parse_error;
+
+use crate::{
+    capabilities::{EnabledFeatures, ServerCapabilitiesBuilder},
+    commands::{APPLY_FIX_ALL, REINDEX_WORKSPACE},
+    json_rpc::{error_codes, JsonRpcAdapter, ResponseError},
+    progress::ProgressReporter,
+    service::LanguageService,
+    settings::Settings,
+};
+
+/// The section of the settings of the server in the configuration of the client.
+const CONFIGURATION_SECTION: &str = "css";
+
+/// The prefix of the ids of the `workspace/configuration` requests sent by the server.
+const CONFIGURATION_REQUEST_PREFIX: &str = "csslsrs/configuration/";
+
+/// The prefix of the tokens created by the server with `window/workDoneProgress/create`.
+const PROGRESS_TOKEN_PREFIX: &str = "csslsrs/progress/";
+
+/// The settings of a server: the settings of its LanguageService, and the features it provides.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ServerSettings {
+    #[serde(flatten)]
+    pub settings: Settings,
+    pub features: EnabledFeatures,
+}
+
+/// The state of a server once it is initialized.
+struct Session {
+    adapter: JsonRpcAdapter,
+    capabilities: ServerCapabilitiesBuilder,
+    features: EnabledFeatures,
+    /// The ids of the features registered dynamically.
+    registered: Vec<String>,
+    pull_configuration: bool,
+    /// Whether the client supports `window/workDoneProgress/create`.
+    work_done_progress: bool,
+    workspace_folders: Vec<Uri>,
+}
+
+/// The state of a language server, from the `initialize` request to the `exit` notification.
+#[derive(Default)]
+pub struct Server {
+    session: Option<Session>,
+    next_request_id: u64,
+    shutdown_requested: bool,
+    exit_code: Option<i32>,
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The exit code of the server once it received the `exit` notification: 0 if it was shut down before, 1 otherwise.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    /// Handle a message from the client.
+    ///
+    /// # Returns
+    ///
+    /// * The messages to send to the client in order, i.e. the response to a request, and notifications and requests
+    ///   of the server such as `textDocument/publishDiagnostics` or `client/registerCapability`.
+    pub fn handle_message(&mut self, message: Value) -> Vec<Value> {
+        let mut messages = Vec::new();
+        self.handle_message_with(message, &mut |message| messages.push(message));
+        messages
+    }
+
+    /// Same as `handle_message`, passing the messages to `send` as soon as they are ready, so that the client is
+    /// notified of the progress of long requests while they run.
+    pub fn handle_message_with(&mut self, message: Value, send: &mut dyn FnMut(Value)) {
+        for message in self.dispatch(message, send) {
+            send(message);
+        }
+    }
+
+    /// Handle a message, returning the messages to send after those already passed to `send`.
+    fn dispatch(&mut self, message: Value, send: &mut dyn FnMut(Value)) -> Vec<Value> {
+        let method = message
+            .get("method")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let id = message.get("id").cloned();
+        let initialized = self.session.is_some();
+
+        match (method.as_deref(), id) {
+            (Some(request::Initialize::METHOD), Some(id)) => {
+                vec![response(id, self.initialize(message["params"].clone()))]
+            }
+            (Some(request::Shutdown::METHOD), Some(id)) if initialized => {
+                self.shutdown_requested = true;
+                vec![response(id, Ok(Value::Null))]
+            }
+            (Some(notification::Exit::METHOD), None) => {
+                self.exit_code = Some(if self.shutdown_requested { 0 } else { 1 });
+                Vec::new()
+            }
+            (Some(_), Some(id)) if self.shutdown_requested => vec![response(
+                id,
+                Err(ResponseError::new(
+                    error_codes::INVALID_REQUEST,
+                    "the server is shutting down",
+                )),
+            )],
+            (Some(_), Some(id)) if !initialized => vec![response(
+                id,
+                Err(ResponseError::new(
+                    error_codes::SERVER_NOT_INITIALIZED,
+                    "the server is not initialized",
+                )),
+            )],
+            // Notifications received before `initialize` are dropped
+            (Some(_), None) if !initialized => Vec::new(),
+            (Some(notification::Initialized::METHOD), None) => {
+                let mut messages = self.update_registrations();
+                messages.extend(self.request_configuration());
+                messages
+            }
+            (Some(notification::DidChangeConfiguration::METHOD), None) => {
+                let pull_configuration = self
+                    .session
+                    .as_ref()
+                    .is_some_and(|session| session.pull_configuration);
+                if pull_configuration {
+                    return self.request_configuration().into_iter().collect();
+                }
+
+                // Clients that can't be asked for their configuration push it, with or without its section
+                let mut settings = message["params"]["settings"].clone();
+                if let Some(section) = settings.get_mut(CONFIGURATION_SECTION) {
+                    settings = section.take();
+                }
+                self.configure(settings)
+            }
+            (None, Some(Value::String(id))) if id.starts_with(CONFIGURATION_REQUEST_PREFIX) => {
+                let settings = message["result"][0].clone();
+                self.configure(settings)
+            }
+            (Some(request::ExecuteCommand::METHOD), Some(id)) => {
+                self.execute_command(id, message["params"].clone(), send)
+            }
+            #[cfg(feature = "diagnostics")]
+            (Some(request::WorkspaceDiagnosticRequest::METHOD), Some(id)) => {
+                vec![self.workspace_diagnostics(id, message["params"].clone(), send)]
+            }
+            // Other responses, e.g. to `client/registerCapability`, need no handling
+            (None, Some(_)) => Vec::new(),
+            _ => {
+                let Some(session) = &self.session else {
+                    return Vec::new();
+                };
+                let push_diagnostics = (session.features.diagnostics
+                    && !session.capabilities.pull_diagnostics())
+                .then(|| message.clone());
+
+                let mut messages = session
+                    .adapter
+                    .handle_message(message)
+                    .into_iter()
+                    .collect::<Vec<_>>();
+                if let Some(message) = push_diagnostics {
+                    messages.extend(publish_diagnostics(
+                        session.adapter.language_service(),
+                        &message,
+                    ));
+                }
+                messages
+            }
+        }
+    }
+
+    fn initialize(&mut self, mut params: Value) -> Result<Value, ResponseError> {
+        if self.session.is_some() {
+            return Err(ResponseError::new(
+                error_codes::INVALID_REQUEST,
+                "the server is already initialized",
+            ));
+        }
+
+        // The LSP names the diagnostic capabilities of the workspace `diagnostics`, read by lsp-types as `diagnostic`
+        if let Some(workspace) = params.pointer_mut("/capabilities/workspace") {
+            if let Some(diagnostics) = workspace.get("diagnostics").cloned() {
+                workspace["diagnostic"] = diagnostics;
+            }
+        }
+        let params: InitializeParams = serde_json::from_value(params)
+            .map_err(|error| ResponseError::new(error_codes::INVALID_PARAMS, error.to_string()))?;
+        // Invalid options are ignored rather than failing the initialization, as they may come from user settings
+        let settings: ServerSettings = params
+            .initialization_options
+            .and_then(|options| serde_json::from_value(options).ok())
+            .unwrap_or_default();
+
+        let capabilities = ServerCapabilitiesBuilder::from_client_capabilities(
+            &params.capabilities,
+            &settings.features,
+        );
+        let mut language_service = LanguageService::new(capabilities.position_encoding());
+        language_service.update_settings(settings.settings);
+        language_service.set_file_system_provider(LocalFileSystem);
+
+        #[allow(deprecated)]
+        let workspace_folders = match (params.workspace_folders, params.root_uri) {
+            (Some(folders), _) => folders.into_iter().map(|folder| folder.uri).collect(),
+            (None, Some(root_uri)) => vec![root_uri],
+            (None, None) => Vec::new(),
+        };
+
+        let result = InitializeResult {
+            capabilities: ServerCapabilities {
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: language_service.command_names(),
+                    ..Default::default()
+                }),
+                ..capabilities.build()
+            },
+            server_info: Some(ServerInfo {
+                name: "csslsrs".to_string(),
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            }),
+        };
+        self.session = Some(Session {
+            adapter: JsonRpcAdapter::new(language_service),
+            capabilities,
+            features: settings.features,
+            registered: Vec::new(),
+            pull_configuration: params
+                .capabilities
+                .workspace
+                .and_then(|workspace| workspace.configuration)
+                .unwrap_or_default(),
+            work_done_progress: params
+                .capabilities
+                .window
+                .and_then(|window| window.work_done_progress)
+                .unwrap_or_default(),
+            workspace_folders,
+        });
+
+        serde_json::to_value(result)
+            .map_err(|error| ResponseError::new(error_codes::INTERNAL_ERROR, error.to_string()))
+    }
+
+    /// Execute a command of the LanguageService. The edit of `csslsrs.applyFixAll` is sent to the client to apply,
+    /// instead of being returned.
+    fn execute_command(
+        &mut self,
+        id: Value,
+        params: Value,
+        send: &mut dyn FnMut(Value),
+    ) -> Vec<Value> {
+        let mut params: ExecuteCommandParams = match serde_json::from_value(params) {
+            Ok(params) => params,
+            Err(error) => {
+                return vec![response(
+                    id,
+                    Err(ResponseError::new(
+                        error_codes::INVALID_PARAMS,
+                        error.to_string(),
+                    )),
+                )]
+            }
+        };
+        let mut progress =
+            self.work_done_progress(params.work_done_progress_params.work_done_token, send);
+        let Some(session) = &self.session else {
+            return Vec::new();
+        };
+        if params.command == REINDEX_WORKSPACE && params.arguments.is_empty() {
+            params.arguments = session
+                .workspace_folders
+                .iter()
+                .map(|folder| json!(folder))
+                .collect();
+        }
+
+        let result = session
+            .adapter
+            .language_service()
+            .execute_command_with_progress(&params.command, params.arguments, &mut progress);
+        match result {
+            Ok(Some(edit)) if params.command == APPLY_FIX_ALL => {
+                let mut messages = vec![response(id, Ok(Value::Null))];
+                // Nothing is sent when there is nothing to fix
+                if let Ok(edit) = serde_json::from_value::<WorkspaceEdit>(edit) {
+                    if edit
+                        .changes
+                        .as_ref()
+                        .is_some_and(|changes| !changes.is_empty())
+                    {
+                        messages.push(self.request::<request::ApplyWorkspaceEdit>(
+                            "applyEdit",
+                            ApplyWorkspaceEditParams {
+                                label: Some("Fix all auto-fixable problems".to_string()),
+                                edit,
+                            },
+                        ));
+                    }
+                }
+                messages
+            }
+            // Unopened files may have been indexed, or removed from the index
+            Ok(result) if params.command == REINDEX_WORKSPACE => {
+                let mut messages = vec![response(id, Ok(result.unwrap_or(Value::Null)))];
+                messages.extend(self.refresh_diagnostics());
+                messages
+            }
+            result => vec![response(
+                id,
+                result
+                    .map(|result| result.unwrap_or(Value::Null))
+                    .map_err(ResponseError::from),
+            )],
+        }
+    }
+
+    /// Pull the diagnostics of the workspace, reporting the progress of the check.
+    #[cfg(feature = "diagnostics")]
+    fn workspace_diagnostics(
+        &mut self,
+        id: Value,
+        params: Value,
+        send: &mut dyn FnMut(Value),
+    ) -> Value {
+        use crate::{cancellation::CancellationToken, error::Error};
+        use lsp_types::{WorkspaceDiagnosticParams, WorkspaceDiagnosticReportResult};
+
+        let params: WorkspaceDiagnosticParams = match serde_json::from_value(params) {
+            Ok(params) => params,
+            Err(error) => {
+                return response(
+                    id,
+                    Err(ResponseError::new(
+                        error_codes::INVALID_PARAMS,
+                        error.to_string(),
+                    )),
+                )
+            }
+        };
+        let mut progress =
+            self.work_done_progress(params.work_done_progress_params.work_done_token, send);
+        let Some(session) = &self.session else {
+            return Value::Null;
+        };
+
+        let result = session
+            .adapter
+            .language_service()
+            .get_workspace_diagnostics(
+                &params.previous_result_ids,
+                &CancellationToken::default(),
+                &mut progress,
+            )
+            .map_err(|cancelled| ResponseError::from(Error::from(cancelled)))
+            .and_then(|report| {
+                serde_json::to_value(WorkspaceDiagnosticReportResult::Report(report)).map_err(
+                    |error| ResponseError::new(error_codes::INTERNAL_ERROR, error.to_string()),
+                )
+            });
+        response(id, result)
+    }
+
+    /// Get the reporter of the progress of a request: with the `workDoneToken` of the request if the client sent one,
+    /// or with a token created with `window/workDoneProgress/create` if the client supports it. The progress is
+    /// reported right after the request creating the token, as clients handle messages in order.
+    fn work_done_progress<'a>(
+        &mut self,
+        token: Option<ProgressToken>,
+        send: &'a mut dyn FnMut(Value),
+    ) -> WorkDoneProgressReporter<'a> {
+        let create_token = self
+            .session
+            .as_ref()
+            .is_some_and(|session| session.work_done_progress);
+        let token = match token {
+            Some(token) => Some(token),
+            None if create_token => {
+                let token = ProgressToken::String(format!(
+                    "{PROGRESS_TOKEN_PREFIX}{}",
+                    self.next_request_id
+                ));
+                send(self.request::<request::WorkDoneProgressCreate>(
+                    "progress",
+                    WorkDoneProgressCreateParams {
+                        token: token.clone(),
+                    },
+                ));
+                Some(token)
+            }
+            None => None,
+        };
+
+        WorkDoneProgressReporter { token, send }
+    }
+
+    /// Apply new settings, e.g. from the response to `workspace/configuration`. Invalid settings are ignored.
+    fn configure(&mut self, settings: Value) -> Vec<Value> {
+        let Some(session) = &mut self.session else {
+            return Vec::new();
+        };
+        let settings: ServerSettings = if settings.is_null() {
+            ServerSettings::default()
+        } else {
+            match serde_json::from_value(settings) {
+                Ok(settings) => settings,
+                Err(_) => return Vec::new(),
+            }
+        };
+
+        let language_service = session.adapter.language_service();
+        let settings_changed = *language_service.settings() != settings.settings;
+        language_service.update_settings(settings.settings);
+        session.features = settings.features;
+
+        let mut messages = self.update_registrations();
+        if settings_changed {
+            messages.extend(self.refresh_diagnostics());
+        }
+        messages
+    }
+
+    /// Ask the client to pull the diagnostics of every document again, e.g. after settings changed them, if it pulls
+    /// diagnostics and supports `workspace/diagnostic/refresh`.
+    fn refresh_diagnostics(&mut self) -> Option<Value> {
+        let session = self.session.as_ref()?;
+        let refresh = session.features.diagnostics
+            && session.capabilities.pull_diagnostics()
+            && session.capabilities.refresh_diagnostics();
+
+        refresh.then(|| self.request::<request::WorkspaceDiagnosticRefresh>("refresh", ()))
+    }
+
+    /// Register the features that were enabled and unregister those that were disabled, for the features the client
+    /// can register dynamically.
+    fn update_registrations(&mut self) -> Vec<Value> {
+        let Some(session) = &mut self.session else {
+            return Vec::new();
+        };
+
+        let registrations = session.capabilities.registrations(&session.features);
+        let unregistrations = session
+            .registered
+            .iter()
+            .filter(|id| {
+                !registrations
+                    .iter()
+                    .any(|registration| registration.id == **id)
+            })
+            .map(|id| Unregistration {
+                id: id.clone(),
+                method: id.clone(),
+            })
+            .collect::<Vec<_>>();
+        let registrations = registrations
+            .into_iter()
+            .filter(|registration| !session.registered.contains(&registration.id))
+            .collect::<Vec<_>>();
+
+        session.registered.retain(|id| {
+            !unregistrations
+                .iter()
+                .any(|unregistration| unregistration.id == *id)
+        });
+        session.registered.extend(
+            registrations
+                .iter()
+                .map(|registration| registration.id.clone()),
+        );
+
+        let mut messages = Vec::new();
+        if !unregistrations.is_empty() {
+            messages.push(self.request::<request::UnregisterCapability>(
+                "unregistration",
+                UnregistrationParams {
+                    unregisterations: unregistrations,
+                },
+            ));
+        }
+        if !registrations.is_empty() {
+            messages.push(self.request::<request::RegisterCapability>(
+                "registration",
+                RegistrationParams { registrations },
+            ));
+        }
+        messages
+    }
+
+    /// Ask the client for the settings of the server, if it supports `workspace/configuration`.
+    fn request_configuration(&mut self) -> Option<Value> {
+        let pull_configuration = self.session.as_ref()?.pull_configuration;
+
+        pull_configuration.then(|| {
+            self.request::<request::WorkspaceConfiguration>(
+                "configuration",
+                ConfigurationParams {
+                    items: vec![ConfigurationItem {
+                        scope_uri: None,
+                        section: Some(CONFIGURATION_SECTION.to_string()),
+                    }],
+                },
+            )
+        })
+    }
+
+    /// Build a request of the server to the client. Its id starts with `csslsrs/<kind>/`, to recognize its response.
+    fn request<R: Request>(&mut self, kind: &str, params: R::Params) -> Value {
+        let id = format!("csslsrs/{kind}/{}", self.next_request_id);
+        self.next_request_id += 1;
+
+        json!({ "jsonrpc": "2.0", "id": id, "method": R::METHOD, "params": params })
+    }
+}
+
+/// Reports the progress of a request to the client with `$/progress`, or nothing if the request has no token.
+struct WorkDoneProgressReporter<'a> {
+    token: Option<ProgressToken>,
+    send: &'a mut dyn FnMut(Value),
+}
+
+impl WorkDoneProgressReporter<'_> {
+    fn send(&mut self, progress: WorkDoneProgress) {
+        let Some(token) = &self.token else {
+            return;
+        };
+
+        (self.send)(json!({
+            "jsonrpc": "2.0",
+            "method": notification::Progress::METHOD,
+            "params": ProgressParams {
+                token: token.clone(),
+                value: ProgressParamsValue::WorkDone(progress),
+            },
+        }));
+    }
+}
+
+impl ProgressReporter for WorkDoneProgressReporter<'_> {
+    fn begin(&mut self, title: &str) {
+        self.send(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+            title: title.to_string(),
+            cancellable: Some(false),
+            percentage: Some(0),
+            ..Default::default()
+        }));
+    }
+
+    fn report(&mut self, percentage: u32) {
+        self.send(WorkDoneProgress::Report(WorkDoneProgressReport {
+            percentage: Some(percentage),
+            ..Default::default()
+        }));
+    }
+
+    fn report_message(&mut self, percentage: u32, message: &str) {
+        self.send(WorkDoneProgress::Report(WorkDoneProgressReport {
+            message: Some(message.to_string()),
+            percentage: Some(percentage),
+            ..Default::default()
+        }));
+    }
+
+    fn end(&mut self) {
+        self.send(WorkDoneProgress::End(WorkDoneProgressEnd::default()));
+    }
+}
+
+fn response(id: Value, result: Result<Value, ResponseError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+    }
+}
+
+/// Publish the diagnostics of a document after a notification changed it, or clear them once it is closed.
+#[cfg(feature = "diagnostics")]
+fn publish_diagnostics(language_service: &LanguageService, message: &Value) -> Option<Value> {
+    use lsp_types::{PublishDiagnosticsParams, TextDocumentIdentifier};
+
+    let method = message.get("method")?.as_str()?;
+    let text_document = message.get("params")?.get("textDocument")?;
+    let uri = serde_json::from_value::<TextDocumentIdentifier>(text_document.clone())
+        .ok()?
+        .uri;
+    let version = text_document
+        .get("version")
+        .and_then(Value::as_i64)
+        .and_then(|version| i32::try_from(version).ok());
+
+    let diagnostics = match method {
+        notification::DidOpenTextDocument::METHOD | notification::DidChangeTextDocument::METHOD => {
+            language_service.get_diagnostics_for_uri(&uri).ok()?
+        }
+        notification::DidCloseTextDocument::METHOD => Vec::new(),
+        _ => return None,
+    };
+
+    Some(json!({
+        "jsonrpc": "2.0",
+        "method": notification::PublishDiagnostics::METHOD,
+        "params": PublishDiagnosticsParams::new(uri, diagnostics, version),
+    }))
+}
+
+#[cfg(not(feature = "diagnostics"))]
+fn publish_diagnostics(_language_service: &LanguageService, _message: &Value) -> Option<Value> {
+    None
+}
+
+/// Serve a client until the server receives the `exit` notification or the connection is closed. The messages that
+/// aren't JSON get a `ParseError` response, only the errors of the transport end the connection, e.g. a malformed
+/// header.
+///
+/// # Returns
+///
+/// * The exit code of the server, 1 if the connection was closed before the server was shut down.
+pub fn serve(transport: &mut impl Transport) -> io::Result<i32> {
+    let mut server = Server::new();
+
+    loop {
+        let message = match transport.receive() {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            // The whole message was read, so the next one can still be received
+            Err(error) if is_parse_error(&error) => {
+                transport.send(&response(
+                    Value::Null,
+                    Err(ResponseError::new(
+                        error_codes::PARSE_ERROR,
+                        error.to_string(),
+                    )),
+                ))?;
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+        // Messages are sent as they are ready, the first error of the transport ending the connection
+        let mut result = Ok(());
+        server.handle_message_with(message, &mut |message| {
+            if result.is_ok() {
+                result = transport.send(&message);
+            }
+        });
+        result?;
+
+        if let Some(exit_code) = server.exit_code() {
+            return Ok(exit_code);
+        }
+    }
+
+    Ok(if server.shutdown_requested { 0 } else { 1 })
+}
+
+/// Serve a client over a pair of streams, e.g. stdin and stdout, see `serve`.
+pub fn run(reader: &mut impl BufRead, writer: &mut impl Write) -> io::Result<i32> {
+    serve(&mut StreamTransport::new(reader, writer))
+}
@@ -0,0 +1,135 @@
+//! The transports of the server, sending and receiving whole JSON-RPC messages.
+//!
+//! Over stdio and TCP sockets, messages are framed with a `Content-Length` header. Over WebSockets, each text message
+//! is a JSON-RPC message, as expected by browser-based clients such as `monaco-languageclient`.
+
+use std::io::{self, BufRead, Write};
+
+use serde_json::Value;
+
+/// A connection to a client.
+pub trait Transport {
+    /// Receive the next message of the client, or `None` once the connection is closed. A message that isn't JSON is
+    /// an error of kind `InvalidData` wrapping its `serde_json::Error`, after which the next message can be received.
+    fn receive(&mut self) -> io::Result<Option<Value>>;
+
+    /// Send a message to the client.
+    fn send(&mut self, message: &Value) -> io::Result<()>;
+}
+
+/// A Transport over a pair of byte streams, with messages framed with a `Content-Length` header, e.g. stdio or a TCP
+/// socket.
+pub struct StreamTransport<R, W> {
+    reader: R,
+    writer: W,
+}
+
+impl<R: BufRead, W: Write> StreamTransport<R, W> {
+    pub fn new(reader: R, writer: W) -> Self {
+        Self { reader, writer }
+    }
+}
+
+impl<R: BufRead, W: Write> Transport for StreamTransport<R, W> {
+    fn receive(&mut self) -> io::Result<Option<Value>> {
+        read_message(&mut self.reader)
+    }
+
+    fn send(&mut self, message: &Value) -> io::Result<()> {
+        write_message(&mut self.writer, message)
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl<S: io::Read + Write> Transport for tungstenite::WebSocket<S> {
+    fn receive(&mut self) -> io::Result<Option<Value>> {
+        use tungstenite::{Error, Message};
+
+        loop {
+            match self.read() {
+                Ok(Message::Text(text)) => {
+                    return serde_json::from_str(&text).map(Some).map_err(invalid_data)
+                }
+                Ok(Message::Binary(data)) => {
+                    return serde_json::from_slice(&data)
+                        .map(Some)
+                        .map_err(invalid_data)
+                }
+                Ok(Message::Close(_)) | Err(Error::ConnectionClosed | Error::AlreadyClosed) => {
+                    return Ok(None)
+                }
+                // Pings are answered by tungstenite
+                Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_)) => {}
+                Err(Error::Io(error)) => return Err(error),
+                Err(error) => return Err(io::Error::other(error)),
+            }
+        }
+    }
+
+    fn send(&mut self, message: &Value) -> io::Result<()> {
+        tungstenite::WebSocket::send(self, tungstenite::Message::Text(message.to_string())).map_err(
+            |error| match error {
+                tungstenite::Error::Io(error) => error,
+                error => io::Error::other(error),
+            },
+        )
+    }
+}
+
+/// Read a message framed with a `Content-Length` header.
+///
+/// # Returns
+///
+/// * The message, or `None` once the input is closed.
+/// * An error of kind `InvalidData` if the header or the message are malformed, wrapping the `serde_json::Error` of a
+///   message that isn't JSON.
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        // Other headers, i.e. `Content-Type`, are ignored
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = Some(value.trim().parse::<usize>().map_err(invalid_data)?);
+            }
+        }
+    }
+
+    let content_length =
+        content_length.ok_or_else(|| invalid_data("a message has no `Content-Length` header"))?;
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content)?;
+
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(invalid_data)
+}
+
+/// Write a message framed with a `Content-Length` header.
+pub fn write_message(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+
+    write!(writer, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+    writer.flush()
+}
+
+fn invalid_data(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// Whether an error of `Transport::receive` is a message that isn't JSON, as opposed to an error of the transport.
+pub(crate) fn is_parse_error(error: &io::Error) -> bool {
+    error
+        .get_ref()
+        .is_some_and(|error| error.is::<serde_json::Error>())
+}
@@ -1,8 +1,68 @@
-use crate::{converters::PositionEncoding, store::DocumentStore};
+use std::{
+    io,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use biome_css_parser::CssParse;
+use lsp_types::{TextDocumentContentChangeEvent, TextDocumentItem, Uri};
+use rustc_hash::FxHashMap;
+
+#[cfg(feature = "completion")]
+use crate::features::completion::CompletionProvider;
+#[cfg(feature = "diagnostics")]
+use crate::features::diagnostics::DiagnosticRule;
+#[cfg(feature = "tracing")]
+use crate::instrumentation::TimingReport;
+use crate::{
+    cancellation::{CancellationToken, Cancelled},
+    commands::{builtin_commands, Command},
+    converters::PositionEncoding,
+    data::{CssData, CssDataSet},
+    design_tokens::DesignTokens,
+    error::Error,
+    file_system::FileSystemProvider,
+    parser::debug_syntax_tree,
+    progress::ProgressReporter,
+    settings::{ScopedSettings, Settings, SettingsScope},
+    store::{DocumentStore, StaleVersionPolicy, StoreEntry},
+    virtual_document::VirtualDocument,
+    workspace_index::WorkspaceIndex,
+};
+
+/// An event in the lifecycle of a document, as notified to the hooks registered with
+/// `LanguageService::on_document_event`.
+pub enum DocumentEvent<'a> {
+    /// The document was opened, its entry contains its initial content.
+    Opened(&'a StoreEntry),
+    /// The content of the document changed.
+    Changed(&'a StoreEntry),
+    /// The document was saved.
+    Saved(&'a StoreEntry),
+    /// The document was closed and removed from the store.
+    Closed(&'a Uri),
+}
+
+type DocumentHook = Box<dyn Fn(&DocumentEvent) + Send + Sync>;
+type ScopedSettingsList = Arc<[(SettingsScope, Arc<Settings>)]>;
 
 pub struct LanguageService {
     pub store: DocumentStore,
     pub encoding: PositionEncoding,
+    index: WorkspaceIndex,
+    file_system: Option<Arc<dyn FileSystemProvider>>,
+    pub(crate) virtual_documents: RwLock<FxHashMap<Uri, Arc<VirtualDocument>>>,
+    document_hooks: Vec<DocumentHook>,
+    settings: RwLock<Arc<Settings>>,
+    scoped_settings: RwLock<ScopedSettingsList>,
+    data: CssDataSet,
+    #[cfg(feature = "diagnostics")]
+    pub(crate) diagnostic_rules: Vec<Box<dyn DiagnosticRule>>,
+    #[cfg(feature = "completion")]
+    pub(crate) completion_providers: Vec<Box<dyn CompletionProvider>>,
+    pub(crate) commands: Vec<Box<dyn Command>>,
+    design_tokens: RwLock<Arc<DesignTokens>>,
+    #[cfg(feature = "tracing")]
+    timing_report: Option<TimingReport>,
 }
 
 impl LanguageService {
@@ -21,10 +81,7 @@ impl LanguageService {
     /// let language_service = LanguageService::new(PositionEncoding::Utf8);
     /// ```
     pub fn new(encoding: PositionEncoding) -> Self {
-        LanguageService {
-            store: DocumentStore::new(),
-            encoding,
-        }
+        LanguageServiceBuilder::new().encoding(encoding).build()
     }
 
     /// Create a new LanguageService with an already existing DocumentStore. This can be useful to share the same DocumentStore between multiple LanguageServices. If you do not need to share the DocumentStore, you can use the LanguageService::new() method instead.
@@ -47,14 +104,1373 @@ impl LanguageService {
     /// ```
     ///
     pub fn new_with_store(store: DocumentStore, encoding: PositionEncoding) -> Self {
-        LanguageService { store, encoding }
+        LanguageServiceBuilder::new()
+            .store(store)
+            .encoding(encoding)
+            .build()
+    }
+
+    /// Start configuring a new LanguageService, see LanguageServiceBuilder.
+    pub fn builder() -> LanguageServiceBuilder {
+        LanguageServiceBuilder::new()
+    }
+
+    /// Register a hook called after every change in the lifecycle of a document (open, change, save and close).
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - A function called with the corresponding DocumentEvent.
+    pub fn on_document_event(&mut self, hook: impl Fn(&DocumentEvent) + Send + Sync + 'static) {
+        self.document_hooks.push(Box::new(hook));
+    }
+
+    /// Set the provider used by cross-file features to access files that aren't open, e.g. to resolve `@import`s or to
+    /// index the stylesheets of the workspace. Without one, these features only know about the documents in the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_system` - The FileSystemProvider of the host.
+    pub fn set_file_system_provider(&mut self, file_system: impl FileSystemProvider + 'static) {
+        self.file_system = Some(Arc::new(file_system));
+    }
+
+    /// Set the maximum number of colors returned by `get_document_colors`, like VS Code's `editor.colorDecoratorsLimit`.
+    /// Documents with more colors only get their first colors, in source order. There is no limit by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_colors` - The maximum number of colors, or `None` to return every color.
+    pub fn set_max_document_colors(&self, max_colors: Option<usize>) {
+        let mut settings = Settings::clone(&self.settings());
+        settings.colors.max_document_colors = max_colors;
+        self.update_settings(settings);
+    }
+
+    /// Get the current global settings. Requests get them once when they start, so that they use the same settings
+    /// throughout, even if they are updated in the meantime.
+    pub fn settings(&self) -> Arc<Settings> {
+        self.settings
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Get the settings of a document: the settings of the last ScopedSettings whose scope matches its URI, or the
+    /// global settings if there are none.
+    pub fn settings_for(&self, uri: &Uri) -> Arc<Settings> {
+        let scoped_settings = self
+            .scoped_settings
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+
+        scoped_settings
+            .iter()
+            .rev()
+            .find(|(scope, _)| scope.matches(uri))
+            .map(|(_, settings)| settings.clone())
+            .unwrap_or_else(|| self.settings())
+    }
+
+    /// Replace every setting at once, e.g. when receiving `workspace/didChangeConfiguration`. If the settings changed,
+    /// the results cached for the documents of the store are dropped, as they may depend on the previous settings. The
+    /// documents are also parsed again if `css.customDirectives` changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The new settings.
+    pub fn update_settings(&self, settings: Settings) {
+        let mut current = self
+            .settings
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        if **current != settings {
+            self.store
+                .set_custom_directives(&settings.css.custom_directives);
+            *current = Arc::new(settings);
+            self.clear_caches();
+        }
+    }
+
+    /// Replace every ScopedSettings at once, e.g. with the settings of each workspace folder. When several scopes match
+    /// a document, the last one wins. As with `update_settings`, cached results are dropped if the settings changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `scoped_settings` - The settings of each scope, from the least to the most specific.
+    pub fn update_scoped_settings(&self, scoped_settings: Vec<ScopedSettings>) {
+        let scoped_settings = scoped_settings
+            .into_iter()
+            .map(|scoped| (scoped.scope, Arc::new(scoped.settings)))
+            .collect::<Arc<[_]>>();
+
+        let mut current = self
+            .scoped_settings
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        if *current != scoped_settings {
+            *current = scoped_settings;
+            self.clear_caches();
+        }
+    }
+
+    /// Teach the service about custom CSS syntax, e.g. the at-rules of a framework, by adding a source of CSS data in the
+    /// format of VS Code's custom data files. Entries override those of the sources added before.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The custom data, e.g. parsed with `CssData::from_json`.
+    pub fn add_custom_data(&mut self, data: CssData) {
+        self.data.add(data);
+    }
+
+    /// Get the design tokens of the project. As with settings, requests get them once when they start.
+    pub fn design_tokens(&self) -> Arc<DesignTokens> {
+        self.design_tokens
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Get the timings of the recent requests, e.g. to report them from a `csslsrs/perf` command, if they are recorded
+    /// (see `LanguageServiceBuilder::timing_report`).
+    #[cfg(feature = "tracing")]
+    pub fn timing_report(&self) -> Option<&TimingReport> {
+        self.timing_report.as_ref()
+    }
+
+    /// Replace the design tokens of the project, suggested in `var()`, shown as colors and reported when duplicated by
+    /// literals. Cached results are dropped, as they may depend on the previous tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - The new design tokens, e.g. parsed with `DesignTokens::from_json`.
+    pub fn set_design_tokens(&self, tokens: DesignTokens) {
+        *self
+            .design_tokens
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Arc::new(tokens);
+        self.clear_caches();
+    }
+
+    /// Read the design tokens of the project from a file using the service's FileSystemProvider, e.g. on startup and
+    /// when the file changes, replacing the previous tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of a design tokens file, in the format read by `DesignTokens::from_json`.
+    ///
+    /// # Returns
+    ///
+    /// * An error of kind `Unsupported` if the service has no FileSystemProvider, `InvalidData` if the file isn't valid
+    ///   JSON, or the error of the FileSystemProvider if the file can't be read.
+    pub fn load_design_tokens(&self, uri: &Uri) -> io::Result<()> {
+        let Some(file_system) = self.file_system_provider() else {
+            return Err(io::ErrorKind::Unsupported.into());
+        };
+
+        let json = FileSystemProvider::read_file(file_system, uri)?;
+        let tokens = DesignTokens::from_json(&json)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        self.set_design_tokens(tokens);
+        Ok(())
+    }
+
+    /// Add a rule reporting diagnostics, run by `get_diagnostics` along with the rules added before.
+    ///
+    /// # Arguments
+    ///
+    /// * `rule` - The rule, e.g. a project-specific check on the values of declarations.
+    #[cfg(feature = "diagnostics")]
+    pub fn add_diagnostic_rule(&mut self, rule: impl DiagnosticRule + 'static) {
+        self.diagnostic_rules.push(Box::new(rule));
+        self.clear_caches();
+    }
+
+    /// Add a source of completion items, merged by `get_completions` with the items of the providers added before.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - The provider, e.g. offering the design tokens of a project inside `var()`.
+    #[cfg(feature = "completion")]
+    pub fn add_completion_provider(&mut self, provider: impl CompletionProvider + 'static) {
+        self.completion_providers.push(Box::new(provider));
+    }
+
+    /// Add a command executed by `execute_command`, replacing the command with the same name if there is one, e.g. a
+    /// built-in command.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command, e.g. opening a preview of the colors of the project.
+    pub fn add_command(&mut self, command: impl Command + 'static) {
+        self.commands.push(Box::new(command));
+    }
+
+    /// Get the CSS data known to the service.
+    pub fn css_data(&self) -> &CssDataSet {
+        &self.data
+    }
+
+    /// Get the FileSystemProvider of the service, if one was set.
+    pub fn file_system_provider(&self) -> Option<&dyn FileSystemProvider> {
+        self.file_system.as_deref()
+    }
+
+    /// Add the stylesheets of a workspace folder to the workspace index, using the service's FileSystemProvider.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder` - The URI of the workspace folder.
+    /// * `cancellation` - A token to stop indexing early, the stylesheets indexed until then are kept.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(false)` if the service has no FileSystemProvider, in which case nothing was indexed.
+    /// * `Err(Cancelled)` if indexing was cancelled.
+    pub fn index_workspace_folder(
+        &self,
+        folder: &Uri,
+        cancellation: &CancellationToken,
+    ) -> Result<bool, Cancelled> {
+        self.index_workspace_folder_with_progress(folder, cancellation, &mut ())
+    }
+
+    /// Same as `index_workspace_folder`, reporting the progress through the stylesheets of the folder to `progress`,
+    /// see `WorkspaceIndex::index_folder_with_progress`.
+    pub fn index_workspace_folder_with_progress(
+        &self,
+        folder: &Uri,
+        cancellation: &CancellationToken,
+        progress: &mut dyn ProgressReporter,
+    ) -> Result<bool, Cancelled> {
+        let Some(file_system) = self.file_system_provider() else {
+            return Ok(false);
+        };
+
+        self.index
+            .index_folder_with_progress(file_system, folder, cancellation, progress)?;
+        Ok(true)
+    }
+
+    /// Get the index of the symbols of every document in the store. Documents that changed since the index was last
+    /// used are reindexed first.
+    pub fn workspace_index(&self) -> &WorkspaceIndex {
+        self.index.sync(&self.store);
+        &self.index
+    }
+
+    /// Get the entry of an open document, for the requests taking a URI instead of a whole document.
+    pub(crate) fn open_entry(&self, uri: &Uri) -> Result<Arc<StoreEntry>, Error> {
+        self.store
+            .get(uri)
+            .ok_or_else(|| Error::DocumentNotOpen(uri.clone()))
+    }
+
+    /// Get the syntax tree of an open document, to build custom analyses on it. The tree is the one already parsed for
+    /// the other requests, sharing its nodes is cheap.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    ///
+    /// # Returns
+    ///
+    /// * The parse result of the document, with the diagnostics of the parser.
+    pub fn get_syntax(&self, uri: &Uri) -> Result<CssParse, Error> {
+        Ok(self.open_entry(uri)?.css_tree())
+    }
+
+    /// Dump the syntax tree of a document in a human-readable form, see `parser::debug_syntax_tree`.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document to dump the syntax tree of.
+    pub fn debug_syntax_tree(&self, document: TextDocumentItem) -> String {
+        let store_entry = self.store.get_or_update_document(document);
+        debug_syntax_tree(&store_entry.css_tree())
+    }
+
+    /// Same as `debug_syntax_tree`, for a document already in the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of an open document.
+    pub fn debug_syntax_tree_for_uri(&self, uri: &Uri) -> Result<String, Error> {
+        Ok(debug_syntax_tree(&self.open_entry(uri)?.css_tree()))
+    }
+
+    /// Drop the results cached for the documents of the store and of the workspace index, e.g. when settings changed.
+    fn clear_caches(&self) {
+        self.store.clear_caches();
+        self.index.clear_caches();
+    }
+
+    fn notify(&self, event: DocumentEvent) {
+        for hook in &self.document_hooks {
+            hook(&event);
+        }
+    }
+
+    /// Add a document to the store, as notified by `textDocument/didOpen`. If the document was already in the store,
+    /// its previous content is replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The opened document.
+    ///
+    /// # Returns
+    ///
+    /// * The StoreEntry of the document.
+    pub fn open_document(&self, document: TextDocumentItem) -> Arc<StoreEntry> {
+        self.open_document_with_encoding(document, None)
+    }
+
+    /// Same as `open_document`, for a document whose positions use another encoding than the service, e.g. UTF-8
+    /// offsets from a build tool next to UTF-16 positions from an editor. Every request on the document, and its
+    /// changes, then use this encoding.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The opened document.
+    /// * `encoding` - The encoding of the positions of the document, `None` for the encoding of the service.
+    ///
+    /// # Returns
+    ///
+    /// * The StoreEntry of the document.
+    pub fn open_document_with_encoding(
+        &self,
+        document: TextDocumentItem,
+        encoding: Option<PositionEncoding>,
+    ) -> Arc<StoreEntry> {
+        let store_entry = self.store.insert_with_encoding(document, encoding);
+        self.notify(DocumentEvent::Opened(&store_entry));
+        store_entry
+    }
+
+    /// Get the PositionEncoding of a document: its own if it was opened with one, the encoding of the service otherwise.
+    pub fn encoding_for(&self, store_entry: &StoreEntry) -> PositionEncoding {
+        store_entry.encoding.unwrap_or(self.encoding)
+    }
+
+    /// Apply changes to a document already in the store, as notified by `textDocument/didChange`. Both full and
+    /// incremental (`TextDocumentSyncKind::Incremental`) changes are supported, the ranges of the changes being
+    /// interpreted using the PositionEncoding of the document, see `encoding_for`.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the document to update.
+    /// * `version` - The version of the document after the changes.
+    /// * `changes` - The changes to apply, in order.
+    ///
+    /// # Returns
+    ///
+    /// * The updated StoreEntry.
+    /// * `Error::DocumentNotOpen` if the document is not in the store, or `Error::InvalidRange` if a change has a
+    ///   position in the middle of a character. Other invalid positions are clamped, see
+    ///   `DocumentStore::update_document`.
+    pub fn change_document(
+        &self,
+        uri: &Uri,
+        version: i32,
+        changes: Vec<TextDocumentContentChangeEvent>,
+    ) -> Result<Arc<StoreEntry>, Error> {
+        let store_entry = self
+            .store
+            .update_document(uri, version, changes, self.encoding)?;
+        self.notify(DocumentEvent::Changed(&store_entry));
+        Ok(store_entry)
+    }
+
+    /// Mark a document as saved, as notified by `textDocument/didSave`. If the client included the saved text and it
+    /// differs from the stored one, the stored document is updated to match it. The document is no longer dirty, and
+    /// if the file was indexed from disk, its indexed content is updated as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the saved document.
+    /// * `text` - The content of the document when it was saved, if the client included it.
+    ///
+    /// # Returns
+    ///
+    /// * The StoreEntry of the document, or `Error::DocumentNotOpen` if the document is not in the store.
+    pub fn save_document(&self, uri: &Uri, text: Option<String>) -> Result<Arc<StoreEntry>, Error> {
+        let mut store_entry = self.open_entry(uri)?;
+
+        if let Some(text) = text.filter(|text| *text != store_entry.document.text) {
+            store_entry = self.store.insert(TextDocumentItem {
+                text,
+                ..store_entry.document.clone()
+            });
+        }
+
+        self.store
+            .set_disk_content(uri, Some(&store_entry.document.text));
+        if self.index.has_disk_document(uri) {
+            self.index.insert_disk_entry(store_entry.clone(), None);
+        }
+
+        self.notify(DocumentEvent::Saved(&store_entry));
+        Ok(store_entry)
+    }
+
+    /// Read a file from disk again using the service's FileSystemProvider, e.g. after `textDocument/didSave` or when
+    /// notified of a change by `workspace/didChangeWatchedFiles`, and update the workspace index with its content.
+    ///
+    /// If the document is open, its content in the store is left as is, as it is owned by the client, but whether it is
+    /// dirty is updated to compare it to the new content on disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the file.
+    ///
+    /// # Returns
+    ///
+    /// * An error of kind `Unsupported` if the service has no FileSystemProvider.
+    /// * The error of the FileSystemProvider if the file can't be read. If it doesn't exist anymore, it is removed from
+    ///   the workspace index.
+    pub fn reload_from_disk(&self, uri: &Uri) -> io::Result<()> {
+        let Some(file_system) = self.file_system_provider() else {
+            return Err(io::ErrorKind::Unsupported.into());
+        };
+
+        let text = match FileSystemProvider::read_file(file_system, uri) {
+            Ok(text) => text,
+            Err(error) => {
+                if error.kind() == io::ErrorKind::NotFound {
+                    self.index.remove_disk_document(uri);
+                    self.store.set_disk_content(uri, None);
+                }
+                return Err(error);
+            }
+        };
+        let mtime = FileSystemProvider::stat(file_system, uri)
+            .ok()
+            .map(|stat| stat.mtime);
+
+        self.store.set_disk_content(uri, Some(&text));
+        self.index.insert_disk_document(uri.clone(), text, mtime);
+        Ok(())
+    }
+
+    /// Whether an open document has changes that weren't saved to disk.
+    pub fn is_dirty(&self, uri: &Uri) -> bool {
+        self.store.is_dirty(uri)
+    }
+
+    /// Get the URIs of the open documents with changes that weren't saved to disk.
+    pub fn dirty_documents(&self) -> Vec<Uri> {
+        self.store.dirty_uris()
+    }
+
+    /// Remove a document from the store, as notified by `textDocument/didClose`. Virtual documents are unregistered as
+    /// well.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the closed document.
+    pub fn close_document(&self, uri: &Uri) {
+        self.store.remove(uri);
+        self.virtual_documents
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(uri);
+        self.notify(DocumentEvent::Closed(uri));
+    }
+}
+
+/// Configures a LanguageService before creating it, for the options that can't be passed to `LanguageService::new`.
+///
+/// # Example
+///
+/// ```rust
+/// use csslsrs::converters::PositionEncoding;
+/// use csslsrs::service::LanguageService;
+/// use csslsrs::store::StaleVersionPolicy;
+///
+/// let language_service = LanguageService::builder()
+///     .encoding(PositionEncoding::Utf8)
+///     .max_document_colors(Some(500))
+///     .stale_version_policy(StaleVersionPolicy::Replace)
+///     .build();
+/// ```
+pub struct LanguageServiceBuilder {
+    store: Option<DocumentStore>,
+    encoding: PositionEncoding,
+    file_system: Option<Arc<dyn FileSystemProvider>>,
+    document_hooks: Vec<DocumentHook>,
+    settings: Settings,
+    scoped_settings: Vec<ScopedSettings>,
+    stale_version_policy: Option<StaleVersionPolicy>,
+    data: CssDataSet,
+    #[cfg(feature = "diagnostics")]
+    diagnostic_rules: Vec<Box<dyn DiagnosticRule>>,
+    #[cfg(feature = "completion")]
+    completion_providers: Vec<Box<dyn CompletionProvider>>,
+    commands: Vec<Box<dyn Command>>,
+    design_tokens: DesignTokens,
+    #[cfg(feature = "tracing")]
+    timing_report: Option<TimingReport>,
+}
+
+impl LanguageServiceBuilder {
+    /// Create a builder with the default configuration: UTF-16 positions, a new DocumentStore, no FileSystemProvider
+    /// and no limits.
+    pub fn new() -> Self {
+        Self {
+            store: None,
+            encoding: PositionEncoding::default(),
+            file_system: None,
+            document_hooks: Vec::new(),
+            settings: Settings::default(),
+            scoped_settings: Vec::new(),
+            stale_version_policy: None,
+            data: crate::data::bundled().clone(),
+            #[cfg(feature = "diagnostics")]
+            diagnostic_rules: Vec::new(),
+            #[cfg(feature = "completion")]
+            completion_providers: Vec::new(),
+            commands: builtin_commands(),
+            design_tokens: DesignTokens::new(),
+            #[cfg(feature = "tracing")]
+            timing_report: None,
+        }
+    }
+
+    /// Use an existing DocumentStore, e.g. to share it between multiple LanguageServices.
+    pub fn store(mut self, store: DocumentStore) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Set the PositionEncoding of the positions of requests and results, in most cases LSP clients will expect Utf-16
+    /// encoding.
+    pub fn encoding(mut self, encoding: PositionEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Set the FileSystemProvider, see `LanguageService::set_file_system_provider`.
+    pub fn file_system_provider(mut self, file_system: impl FileSystemProvider + 'static) -> Self {
+        self.file_system = Some(Arc::new(file_system));
+        self
+    }
+
+    /// Register a hook called after every change in the lifecycle of a document, see
+    /// `LanguageService::on_document_event`.
+    pub fn on_document_event(
+        mut self,
+        hook: impl Fn(&DocumentEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.document_hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Set the maximum number of colors returned by `get_document_colors`, see
+    /// `LanguageService::set_max_document_colors`.
+    pub fn max_document_colors(mut self, max_colors: Option<usize>) -> Self {
+        self.settings.colors.max_document_colors = max_colors;
+        self
+    }
+
+    /// Set every setting at once, see `LanguageService::update_settings`.
+    pub fn settings(mut self, settings: Settings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Set the settings of documents in a scope, see `LanguageService::update_scoped_settings`. Scopes added last win.
+    pub fn scoped_settings(mut self, scope: SettingsScope, settings: Settings) -> Self {
+        self.scoped_settings
+            .push(ScopedSettings { scope, settings });
+        self
+    }
+
+    /// Set what the store does with older versions of its documents, see `DocumentStore::set_stale_version_policy`.
+    /// The policy of the store is kept if this isn't called.
+    pub fn stale_version_policy(mut self, policy: StaleVersionPolicy) -> Self {
+        self.stale_version_policy = Some(policy);
+        self
+    }
+
+    /// Add a source of custom CSS data, see `LanguageService::add_custom_data`.
+    pub fn custom_data(mut self, data: CssData) -> Self {
+        self.data.add(data);
+        self
+    }
+
+    /// Add a rule reporting diagnostics, see `LanguageService::add_diagnostic_rule`.
+    #[cfg(feature = "diagnostics")]
+    pub fn diagnostic_rule(mut self, rule: impl DiagnosticRule + 'static) -> Self {
+        self.diagnostic_rules.push(Box::new(rule));
+        self
+    }
+
+    /// Add a source of completion items, see `LanguageService::add_completion_provider`.
+    #[cfg(feature = "completion")]
+    pub fn completion_provider(mut self, provider: impl CompletionProvider + 'static) -> Self {
+        self.completion_providers.push(Box::new(provider));
+        self
+    }
+
+    /// Add a command, see `LanguageService::add_command`.
+    pub fn command(mut self, command: impl Command + 'static) -> Self {
+        self.commands.push(Box::new(command));
+        self
+    }
+
+    /// Set the design tokens of the project, see `LanguageService::set_design_tokens`.
+    pub fn design_tokens(mut self, tokens: DesignTokens) -> Self {
+        self.design_tokens = tokens;
+        self
+    }
+
+    /// Record the timings of the last `capacity` requests, see `LanguageService::timing_report`.
+    #[cfg(feature = "tracing")]
+    pub fn timing_report(mut self, capacity: usize) -> Self {
+        self.timing_report = Some(TimingReport::new(capacity));
+        self
+    }
+
+    pub fn build(self) -> LanguageService {
+        let mut store = self.store.unwrap_or_default();
+        if let Some(policy) = self.stale_version_policy {
+            store.set_stale_version_policy(policy);
+        }
+        store.set_custom_directives(&self.settings.css.custom_directives);
+
+        LanguageService {
+            store,
+            encoding: self.encoding,
+            index: WorkspaceIndex::new(),
+            file_system: self.file_system,
+            virtual_documents: RwLock::new(FxHashMap::default()),
+            document_hooks: self.document_hooks,
+            settings: RwLock::new(Arc::new(self.settings)),
+            scoped_settings: RwLock::new(
+                self.scoped_settings
+                    .into_iter()
+                    .map(|scoped| (scoped.scope, Arc::new(scoped.settings)))
+                    .collect(),
+            ),
+            data: self.data,
+            #[cfg(feature = "diagnostics")]
+            diagnostic_rules: self.diagnostic_rules,
+            #[cfg(feature = "completion")]
+            completion_providers: self.completion_providers,
+            commands: self.commands,
+            design_tokens: RwLock::new(Arc::new(self.design_tokens)),
+            #[cfg(feature = "tracing")]
+            timing_report: self.timing_report,
+        }
+    }
+}
+
+impl Default for LanguageServiceBuilder {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Default for LanguageService {
     fn default() -> Self {
-        LanguageService::new(PositionEncoding::Wide(
-            crate::converters::WideEncoding::Utf16,
-        ))
+        LanguageServiceBuilder::new().build()
+    }
+}
+
+#[cfg(feature = "wasm")]
+pub(crate) mod wasm_bindings {
+    use lsp_types::{
+        ColorInformation, Diagnostic, FoldingRange, TextDocumentContentChangeEvent, Uri,
+        WorkspaceSymbol,
+    };
+    use serde::{Deserialize, Serialize};
+    use tsify_next::Tsify;
+    use wasm_bindgen::prelude::*;
+
+    use super::LanguageService;
+    use crate::{error::Error, settings::Settings, wasm_text_document};
+
+    #[wasm_bindgen(typescript_custom_section)]
+    const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * A language service keeping the documents opened in it, so that the text of a document is only
+ * transferred once and then updated with the changes made to it. Its other methods, one per
+ * feature, take the URI of an open document.
+ */
+export class LanguageService {
+	free(): void;
+	constructor();
+	/**
+	 * Open a document, replacing it if it was already open.
+	 *
+	 * @param source The source code as a `TextDocumentSource`.
+	 * @param encoding The encoding of the positions of every request on the document and of its
+	 * changes, `"utf-16"` by default as in the LSP.
+	 */
+	openDocument(source: TextDocumentSource, encoding?: "utf-8" | "utf-16" | "utf-32"): void;
+	/**
+	 * Apply changes to an open document, as notified by `textDocument/didChange`. Changes with a
+	 * range are applied incrementally, without reparsing the whole document when possible.
+	 *
+	 * @param uri The URI of the document.
+	 * @param version The version of the document after the changes.
+	 * @param contentChanges The changes to apply, in order.
+	 */
+	updateDocument(uri: string, version: number, contentChanges: import("vscode-languageserver-textdocument").TextDocumentContentChangeEvent[]): void;
+	/**
+	 * Close a document, removing it from the service.
+	 *
+	 * @param uri The URI of the document.
+	 */
+	closeDocument(uri: string): void;
+	/**
+	 * Replace every setting of the service at once, e.g. with the settings forwarded from
+	 * `workspace/didChangeConfiguration`. Missing settings get their default value.
+	 *
+	 * @param settings The new settings, with the same shape as the `Settings` of the native
+	 * language service.
+	 */
+	setConfiguration(settings: Settings): void;
+	/**
+	 * Compute several features of an open document at once, see `analyze_document`.
+	 */
+	analyzeDocument(uri: string, features?: AnalyzedFeatures): DocumentAnalysis;
+	/**
+	 * Same as `analyzeDocument`, returning the analysis as JSON, see `analyze_document_json`.
+	 */
+	analyzeDocumentJson(uri: string, features?: AnalyzedFeatures): Uint8Array;
+}
+
+/**
+ * Compute several features of the given source code at once. The document is parsed and analyzed
+ * once, and the results are converted to JS in a single pass, which is cheaper than requesting
+ * each feature for a large document.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @param features The features to compute, every feature if none are given.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns The results of the features, the symbols being the ones declared in the document.
+ */
+export async function analyze_document(source: TextDocumentSource, features?: AnalyzedFeatures, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<DocumentAnalysis>;
+
+/**
+ * Same as `analyze_document`, returning the analysis as UTF-8 encoded JSON. In a Web Worker, its
+ * buffer can be transferred to the main thread without a copy, e.g. with
+ * `postMessage(json, [json.buffer])`, and parsed there with `JSON.parse`.
+ *
+ * @param source The source code as a `TextDocumentSource`.
+ * @param features The features to compute, every feature if none are given.
+ * @param encoding The encoding of the positions, `"utf-16"` by default as in the LSP.
+ * @returns The JSON of a `DocumentAnalysis`.
+ */
+export async function analyze_document_json(source: TextDocumentSource, features?: AnalyzedFeatures, encoding?: "utf-8" | "utf-16" | "utf-32"): Promise<Uint8Array>;
+"#;
+
+    /// The features computed by `analyze_document`, every feature if none are given.
+    #[derive(Default, Deserialize, Tsify)]
+    #[serde(rename_all = "camelCase", default)]
+    struct AnalyzedFeatures {
+        #[tsify(optional)]
+        colors: bool,
+        #[tsify(optional)]
+        folding: bool,
+        #[tsify(optional)]
+        symbols: bool,
+        #[tsify(optional)]
+        diagnostics: bool,
+    }
+
+    impl AnalyzedFeatures {
+        const ALL: Self = Self {
+            colors: true,
+            folding: true,
+            symbols: true,
+            diagnostics: true,
+        };
+    }
+
+    /// The results of `analyze_document`, only present for the features that were computed.
+    #[derive(Default, Serialize, Tsify)]
+    #[serde(rename_all = "camelCase")]
+    struct DocumentAnalysis {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[tsify(type = "import(\"vscode-languageserver-types\").ColorInformation[]")]
+        colors: Option<Vec<ColorInformation>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[tsify(type = "import(\"vscode-languageserver-types\").FoldingRange[]")]
+        folding: Option<Vec<FoldingRange>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[tsify(type = "import(\"vscode-languageserver-types\").WorkspaceSymbol[]")]
+        symbols: Option<Vec<WorkspaceSymbol>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[tsify(type = "import(\"vscode-languageserver-types\").Diagnostic[]")]
+        diagnostics: Option<Vec<Diagnostic>>,
+    }
+
+    /// Only the features set in `features` are computed, unless none are given.
+    fn analyzed_features(features: JsValue) -> Result<AnalyzedFeatures, Error> {
+        if features.is_undefined() || features.is_null() {
+            return Ok(AnalyzedFeatures::ALL);
+        }
+
+        wasm_text_document::from_value(features, "features")
+    }
+
+    // Without some of the features in the build, their results are never computed
+    #[allow(unused_variables, unused_mut)]
+    fn analyze(
+        language_service: &LanguageService,
+        uri: &Uri,
+        features: &AnalyzedFeatures,
+    ) -> Result<DocumentAnalysis, Error> {
+        let mut analysis = DocumentAnalysis::default();
+
+        #[cfg(feature = "colors")]
+        if features.colors {
+            analysis.colors = Some(language_service.get_document_colors_for_uri(uri)?);
+        }
+        #[cfg(feature = "folding")]
+        if features.folding {
+            analysis.folding = Some(language_service.get_folding_ranges_for_uri(uri)?);
+        }
+        #[cfg(feature = "workspace-symbols")]
+        if features.symbols {
+            analysis.symbols = Some(language_service.get_workspace_symbols_for_uri(uri)?);
+        }
+        #[cfg(feature = "diagnostics")]
+        if features.diagnostics {
+            analysis.diagnostics = Some(language_service.get_diagnostics_for_uri(uri)?);
+        }
+
+        Ok(analysis)
+    }
+
+    fn analyze_source(
+        document: JsValue,
+        features: JsValue,
+        encoding: Option<String>,
+    ) -> Result<DocumentAnalysis, Error> {
+        let parsed_text_document = wasm_text_document::create_text_document(document)?;
+        let features = analyzed_features(features)?;
+
+        wasm_text_document::with_document(
+            parsed_text_document,
+            wasm_text_document::position_encoding(encoding)?,
+            |language_service, uri| analyze(language_service, uri, &features),
+        )
+    }
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn analyze_document(
+        document: JsValue,
+        features: JsValue,
+        encoding: Option<String>,
+    ) -> Result<JsValue, Error> {
+        let analysis = analyze_source(document, features, encoding)?;
+
+        wasm_text_document::to_value(&analysis)
+    }
+
+    #[wasm_bindgen(skip_typescript)]
+    pub fn analyze_document_json(
+        document: JsValue,
+        features: JsValue,
+        encoding: Option<String>,
+    ) -> Result<Vec<u8>, Error> {
+        let analysis = analyze_source(document, features, encoding)?;
+
+        wasm_text_document::to_json(&analysis)
+    }
+
+    #[wasm_bindgen(js_name = LanguageService, skip_typescript)]
+    pub struct WasmLanguageService {
+        pub(crate) language_service: LanguageService,
+    }
+
+    #[wasm_bindgen(js_class = LanguageService)]
+    impl WasmLanguageService {
+        #[wasm_bindgen(constructor, skip_typescript)]
+        pub fn new() -> Self {
+            Self {
+                language_service: LanguageService::default(),
+            }
+        }
+
+        #[wasm_bindgen(js_name = openDocument, skip_typescript)]
+        pub fn open_document(
+            &self,
+            document: JsValue,
+            encoding: Option<String>,
+        ) -> Result<(), Error> {
+            let parsed_text_document = wasm_text_document::create_text_document(document)?;
+
+            self.language_service.open_document_with_encoding(
+                parsed_text_document,
+                Some(wasm_text_document::position_encoding(encoding)?),
+            );
+            Ok(())
+        }
+
+        #[wasm_bindgen(js_name = updateDocument, skip_typescript)]
+        pub fn update_document(
+            &self,
+            uri: JsValue,
+            version: i32,
+            content_changes: JsValue,
+        ) -> Result<(), Error> {
+            let uri: Uri = wasm_text_document::from_value(uri, "uri")?;
+            let content_changes: Vec<TextDocumentContentChangeEvent> =
+                wasm_text_document::from_value(content_changes, "contentChanges")?;
+
+            self.language_service
+                .change_document(&uri, version, content_changes)?;
+            Ok(())
+        }
+
+        #[wasm_bindgen(js_name = closeDocument, skip_typescript)]
+        pub fn close_document(&self, uri: JsValue) -> Result<(), Error> {
+            let uri: Uri = wasm_text_document::from_value(uri, "uri")?;
+
+            self.language_service.close_document(&uri);
+            Ok(())
+        }
+
+        #[wasm_bindgen(js_name = setConfiguration, skip_typescript)]
+        pub fn set_configuration(&self, settings: JsValue) -> Result<(), Error> {
+            let settings: Settings = wasm_text_document::from_value(settings, "settings")?;
+
+            self.language_service.update_settings(settings);
+            Ok(())
+        }
+
+        #[wasm_bindgen(js_name = analyzeDocument, skip_typescript)]
+        pub fn analyze_document(&self, uri: JsValue, features: JsValue) -> Result<JsValue, Error> {
+            let uri: Uri = wasm_text_document::from_value(uri, "uri")?;
+            let analysis = analyze(&self.language_service, &uri, &analyzed_features(features)?)?;
+
+            wasm_text_document::to_value(&analysis)
+        }
+
+        #[wasm_bindgen(js_name = analyzeDocumentJson, skip_typescript)]
+        pub fn analyze_document_json(
+            &self,
+            uri: JsValue,
+            features: JsValue,
+        ) -> Result<Vec<u8>, Error> {
+            let uri: Uri = wasm_text_document::from_value(uri, "uri")?;
+            let analysis = analyze(&self.language_service, &uri, &analyzed_features(features)?)?;
+
+            wasm_text_document::to_json(&analysis)
+        }
+    }
+
+    impl Default for WasmLanguageService {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(all(feature = "colors", feature = "folding"))]
+    use std::thread;
+    use std::{
+        str::FromStr,
+        sync::{Arc, Mutex},
+    };
+
+    use lsp_types::{Position, Range};
+
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_language_service_is_send_and_sync() {
+        assert_send_sync::<LanguageService>();
+    }
+
+    #[cfg(all(feature = "colors", feature = "folding"))]
+    #[test]
+    fn test_language_service_concurrent_requests() {
+        let language_service = LanguageService::default();
+
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///test.css").unwrap(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: "body {\n  color: red;\n}\n".to_string(),
+        };
+
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    assert_eq!(
+                        language_service.get_document_colors(document.clone()).len(),
+                        1
+                    );
+                    assert_eq!(
+                        language_service.get_folding_ranges(document.clone()).len(),
+                        1
+                    );
+                });
+            }
+        });
+    }
+
+    #[cfg(all(
+        feature = "colors",
+        feature = "folding",
+        feature = "semantic-tokens",
+        feature = "inlay-hints"
+    ))]
+    #[test]
+    fn test_requests_by_uri() {
+        let language_service = LanguageService::default();
+
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///test.css").unwrap(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: ":root { --a: red; }\nbody {\n  color: var(--a);\n}\n".to_string(),
+        };
+        let range = Range::new(Position::new(0, 0), Position::new(4, 0));
+
+        assert_eq!(
+            language_service.get_document_colors_for_uri(&document.uri),
+            Err(Error::DocumentNotOpen(document.uri.clone()))
+        );
+
+        language_service.open_document(document.clone());
+
+        assert_eq!(
+            language_service.get_document_colors_for_uri(&document.uri),
+            Ok(language_service.get_document_colors(document.clone()))
+        );
+        assert_eq!(
+            language_service.get_folding_ranges_for_uri(&document.uri),
+            Ok(language_service.get_folding_ranges(document.clone()))
+        );
+        assert_eq!(
+            language_service.get_semantic_tokens_for_uri(&document.uri),
+            Ok(language_service.get_semantic_tokens(document.clone()))
+        );
+        assert_eq!(
+            serde_json::to_value(
+                language_service
+                    .get_inlay_hints_for_uri(&document.uri, range)
+                    .unwrap()
+            )
+            .unwrap(),
+            serde_json::to_value(
+                language_service
+                    .get_inlay_hints(document.clone(), range)
+                    .unwrap()
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            language_service.debug_syntax_tree_for_uri(&document.uri),
+            Ok(language_service.debug_syntax_tree(document.clone()))
+        );
+
+        // The syntax tree is the one parsed for the other requests
+        let syntax = language_service.get_syntax(&document.uri).unwrap();
+        assert_eq!(syntax.syntax().to_string(), document.text);
+        assert_eq!(
+            syntax.syntax(),
+            language_service
+                .open_entry(&document.uri)
+                .unwrap()
+                .css_tree()
+                .syntax()
+        );
+    }
+
+    #[test]
+    fn test_language_service_builder() {
+        let opened = Arc::new(Mutex::new(0));
+        let hook_opened = opened.clone();
+        let language_service = LanguageService::builder()
+            .encoding(PositionEncoding::Utf8)
+            .max_document_colors(Some(1))
+            .stale_version_policy(StaleVersionPolicy::Replace)
+            .on_document_event(move |event| {
+                if let DocumentEvent::Opened(_) = event {
+                    *hook_opened.lock().unwrap() += 1;
+                }
+            })
+            .build();
+
+        assert!(matches!(language_service.encoding, PositionEncoding::Utf8));
+        assert_eq!(
+            language_service.settings().colors.max_document_colors,
+            Some(1)
+        );
+        assert!(language_service.file_system_provider().is_none());
+        assert!(language_service.css_data().property("color").is_some());
+
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///test.css").unwrap(),
+            language_id: "css".to_string(),
+            version: 2,
+            text: "a { color: red; }".to_string(),
+        };
+        language_service.open_document(document.clone());
+        assert_eq!(*opened.lock().unwrap(), 1);
+
+        // Older versions replace the stored document with this policy
+        let stale_document = TextDocumentItem {
+            version: 1,
+            ..document
+        };
+        assert_eq!(
+            language_service
+                .store
+                .get_or_update_document(stale_document)
+                .document
+                .version,
+            1
+        );
+    }
+
+    #[cfg(feature = "colors")]
+    #[test]
+    fn test_update_settings() {
+        let language_service = LanguageService::default();
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///test.css").unwrap(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: "a { color: red; }\nb { color: blue; }".to_string(),
+        };
+
+        assert_eq!(
+            language_service.get_document_colors(document.clone()).len(),
+            2
+        );
+        let misses = language_service.store.stats().cache_misses;
+
+        let settings: Settings =
+            serde_json::from_str(r#"{ "colors": { "maxDocumentColors": 1 } }"#).unwrap();
+        language_service.update_settings(settings.clone());
+        assert_eq!(*language_service.settings(), settings);
+
+        // The cached colors were dropped along with the previous settings
+        assert_eq!(
+            language_service.get_document_colors(document.clone()).len(),
+            1
+        );
+        assert!(language_service.store.stats().cache_misses > misses);
+
+        // Updating to the same settings keeps the cache
+        let misses = language_service.store.stats().cache_misses;
+        language_service.update_settings(settings);
+        language_service.get_document_colors(document);
+        assert_eq!(language_service.store.stats().cache_misses, misses);
+    }
+
+    #[cfg(feature = "colors")]
+    #[test]
+    fn test_scoped_settings() {
+        let strict = Settings {
+            colors: crate::settings::ColorSettings {
+                max_document_colors: Some(1),
+            },
+            ..Default::default()
+        };
+        let language_service = LanguageService::builder()
+            .scoped_settings(
+                SettingsScope::Folder(Uri::from_str("file:///project").unwrap()),
+                strict.clone(),
+            )
+            .build();
+
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///project/src/test.css").unwrap(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: "a { color: red; }\nb { color: blue; }".to_string(),
+        };
+        let vendor_document = TextDocumentItem {
+            uri: Uri::from_str("file:///project/vendor/test.css").unwrap(),
+            ..document.clone()
+        };
+
+        assert_eq!(*language_service.settings_for(&document.uri), strict);
+        assert_eq!(
+            language_service.get_document_colors(document.clone()).len(),
+            1
+        );
+
+        // The last matching scope wins
+        language_service.update_scoped_settings(vec![
+            ScopedSettings {
+                scope: SettingsScope::Folder(Uri::from_str("file:///project").unwrap()),
+                settings: strict,
+            },
+            ScopedSettings {
+                scope: SettingsScope::Glob("vendor/**".to_string()),
+                settings: Settings::default(),
+            },
+        ]);
+        assert_eq!(language_service.get_document_colors(document).len(), 1);
+        assert_eq!(
+            language_service.get_document_colors(vendor_document).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_document_lifecycle() {
+        let mut language_service = LanguageService::default();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let hook_events = events.clone();
+        language_service.on_document_event(move |event| {
+            let event = match event {
+                DocumentEvent::Opened(entry) => format!("opened {}", entry.document.version),
+                DocumentEvent::Changed(entry) => format!("changed {}", entry.document.version),
+                DocumentEvent::Saved(entry) => format!("saved {}", entry.document.text),
+                DocumentEvent::Closed(uri) => format!("closed {}", uri.as_str()),
+            };
+            hook_events.lock().unwrap().push(event);
+        });
+
+        let uri = Uri::from_str("file:///test.css").unwrap();
+
+        language_service.open_document(TextDocumentItem {
+            uri: uri.clone(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: "a {}".to_string(),
+        });
+
+        language_service
+            .change_document(
+                &uri,
+                2,
+                vec![TextDocumentContentChangeEvent {
+                    range: Some(Range::new(Position::new(0, 0), Position::new(0, 1))),
+                    range_length: None,
+                    text: "b".to_string(),
+                }],
+            )
+            .unwrap();
+
+        language_service.save_document(&uri, None).unwrap();
+        language_service.close_document(&uri);
+
+        assert!(language_service.store.get(&uri).is_none());
+        assert_eq!(
+            language_service.save_document(&uri, None).err(),
+            Some(Error::DocumentNotOpen(uri.clone()))
+        );
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "opened 1".to_string(),
+                "changed 2".to_string(),
+                "saved b {}".to_string(),
+                "closed file:///test.css".to_string(),
+            ]
+        );
+    }
+
+    // A file system whose files can be changed after it was given to the service
+    #[derive(Clone, Default)]
+    struct SharedFileSystem(Arc<Mutex<FxHashMap<String, String>>>);
+
+    impl FileSystemProvider for SharedFileSystem {
+        fn read_file(&self, uri: &Uri) -> io::Result<String> {
+            self.0
+                .lock()
+                .unwrap()
+                .get(uri.as_str())
+                .cloned()
+                .ok_or_else(|| io::ErrorKind::NotFound.into())
+        }
+
+        fn read_directory(
+            &self,
+            _uri: &Uri,
+        ) -> io::Result<Vec<(String, crate::file_system::FileType)>> {
+            Ok(Vec::new())
+        }
+
+        fn stat(&self, uri: &Uri) -> io::Result<crate::file_system::FileStat> {
+            let text = FileSystemProvider::read_file(self, uri)?;
+            Ok(crate::file_system::FileStat {
+                file_type: crate::file_system::FileType::File,
+                size: text.len() as u64,
+                mtime: text.len() as u64,
+            })
+        }
+    }
+
+    #[test]
+    fn test_dirty_documents_and_reload() {
+        let mut language_service = LanguageService::default();
+        let uri = Uri::from_str("file:///test.css").unwrap();
+        assert_eq!(
+            language_service.reload_from_disk(&uri).unwrap_err().kind(),
+            io::ErrorKind::Unsupported
+        );
+
+        let file_system = SharedFileSystem::default();
+        file_system
+            .0
+            .lock()
+            .unwrap()
+            .insert(uri.to_string(), ":root { --a: red; }".to_string());
+        language_service.set_file_system_provider(file_system.clone());
+
+        language_service.reload_from_disk(&uri).unwrap();
+        assert!(language_service
+            .workspace_index()
+            .document(&uri)
+            .is_some_and(|document| document.entry().is_some()));
+
+        language_service.open_document(TextDocumentItem {
+            uri: uri.clone(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: ":root { --a: red; }".to_string(),
+        });
+        assert!(!language_service.is_dirty(&uri));
+
+        language_service
+            .change_document(
+                &uri,
+                2,
+                vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: ":root { --b: red; }".to_string(),
+                }],
+            )
+            .unwrap();
+        assert_eq!(language_service.dirty_documents(), vec![uri.clone()]);
+
+        // Saving updates the content of the file in the index, used once the document is closed
+        language_service.save_document(&uri, None).unwrap();
+        assert!(!language_service.is_dirty(&uri));
+        language_service.close_document(&uri);
+        assert_eq!(
+            language_service
+                .workspace_index()
+                .find(crate::workspace_index::SymbolKind::CustomProperty, "--b")
+                .len(),
+            1
+        );
+
+        file_system.0.lock().unwrap().remove(uri.as_str());
+        assert_eq!(
+            language_service.reload_from_disk(&uri).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        assert!(language_service.workspace_index().document(&uri).is_none());
     }
 }
@@ -0,0 +1,349 @@
+//! The settings of a LanguageService that can be changed while it is running, e.g. when a server receives
+//! `workspace/didChangeConfiguration`.
+//!
+//! Settings deserialize from the JSON sent by clients, with camelCase keys. Missing settings keep their default value.
+//!
+//! Besides the global settings, documents can get their own settings depending on their URI with `ScopedSettings`,
+//! e.g. for monorepos where each workspace folder has its own conventions.
+
+use std::collections::HashMap;
+
+use lsp_types::{DiagnosticSeverity, Uri};
+use serde::Deserialize;
+
+/// Every setting of a LanguageService, replaced at once with `LanguageService::update_settings`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify_next::Tsify))]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub css: CssSettings,
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub colors: ColorSettings,
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub scss: ScssSettings,
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub completion: CompletionSettings,
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub code_actions: CodeActionSettings,
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub diagnostics: DiagnosticSettings,
+}
+
+/// The settings of the CSS syntax of documents, whatever their dialect.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify_next::Tsify))]
+#[serde(rename_all = "camelCase", default)]
+pub struct CssSettings {
+    /// The custom at-rules and functions of PostCSS plugins and frameworks, which are parsed and completed as known
+    /// ones, e.g. `["tailwind", "apply", "screen", "theme()"]` for Tailwind. Functions are listed with their
+    /// parentheses. As documents are parsed once for every request, only the global settings apply, not the scoped
+    /// ones.
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub custom_directives: Vec<String>,
+}
+
+/// The settings of `get_document_colors` and the other color requests.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify_next::Tsify))]
+#[serde(rename_all = "camelCase", default)]
+pub struct ColorSettings {
+    /// The maximum number of colors returned for a document, like VS Code's `editor.colorDecoratorsLimit`. Documents
+    /// with more colors only get their first colors, in source order. There is no limit by default.
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub max_document_colors: Option<usize>,
+}
+
+/// The settings of the resolution of the stylesheets loaded by SCSS documents, see
+/// `LanguageService::resolve_scss_module`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify_next::Tsify))]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScssSettings {
+    /// The URIs of the folders stylesheets are also looked up in when they aren't found next to the document loading
+    /// them, like the `loadPaths` option of Sass, e.g. `file:///project/node_modules`.
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub load_paths: Vec<String>,
+}
+
+/// The settings of `get_completions`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify_next::Tsify))]
+#[serde(rename_all = "camelCase", default)]
+pub struct CompletionSettings {
+    /// Whether Emmet abbreviations typed in declaration blocks are offered as the declarations they stand for, e.g.
+    /// `m10` as `margin: 10px;` or `posa` as `position: absolute;`. Disabled by default.
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub emmet_abbreviations: bool,
+}
+
+/// The settings of `get_code_actions`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify_next::Tsify))]
+#[serde(rename_all = "camelCase", default)]
+pub struct CodeActionSettings {
+    /// The font size of the root element in pixels, which `rem` lengths are relative to, used to convert lengths
+    /// between `px` and `rem` or `em`. `16` by default, the default of browsers.
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub root_font_size: Option<u32>,
+}
+
+/// The settings of `get_diagnostics`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify_next::Tsify))]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiagnosticSettings {
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub color_contrast: ColorContrastSettings,
+    /// The level of the rules reporting diagnostics, by the code of their diagnostics, e.g.
+    /// `{ "unknown-property": "ignore", "syntax-error": "warning" }`. It overrides the severity of every diagnostic of
+    /// a rule, including the `severity` of `colorContrast`. The other rules keep their default severity.
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub rules: HashMap<String, RuleLevel>,
+}
+
+/// The settings of the `color-contrast` lint, checking the contrast of the `color` of rules against their
+/// `background-color` with the thresholds of WCAG 2.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify_next::Tsify))]
+#[serde(rename_all = "camelCase", default)]
+pub struct ColorContrastSettings {
+    /// Whether the lint runs. Disabled by default.
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub enabled: bool,
+    /// The level of conformance whose minimum ratios are required, `"AA"` by default.
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub level: ContrastLevel,
+    /// The background of the rules setting a `color` without a background, e.g. `"#fff"` for pages with a white
+    /// background. Without it, only the rules setting both are checked.
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub default_background: Option<String>,
+    /// The severity of the diagnostics, `"warning"` by default.
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub severity: Option<Severity>,
+}
+
+/// A level of conformance of WCAG 2 for the contrast of text: AA requires a ratio of 4.5:1, or 3:1 for large text,
+/// and AAA 7:1, or 4.5:1 for large text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify_next::Tsify))]
+pub enum ContrastLevel {
+    #[default]
+    #[serde(rename = "AA")]
+    Aa,
+    #[serde(rename = "AAA")]
+    Aaa,
+}
+
+/// The severity of diagnostics, as named in settings, e.g. `"warning"`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify_next::Tsify))]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl From<Severity> for DiagnosticSeverity {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+            Severity::Information => DiagnosticSeverity::INFORMATION,
+            Severity::Hint => DiagnosticSeverity::HINT,
+        }
+    }
+}
+
+/// The level of a rule in the `diagnostics.rules` setting: the severity of its diagnostics, or `"ignore"` to not report
+/// them at all.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify_next::Tsify))]
+#[serde(rename_all = "camelCase")]
+pub enum RuleLevel {
+    Ignore,
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl RuleLevel {
+    /// The severity of the diagnostics of a rule at this level, `None` if they are ignored.
+    pub fn severity(self) -> Option<DiagnosticSeverity> {
+        match self {
+            RuleLevel::Ignore => None,
+            RuleLevel::Error => Some(DiagnosticSeverity::ERROR),
+            RuleLevel::Warning => Some(DiagnosticSeverity::WARNING),
+            RuleLevel::Information => Some(DiagnosticSeverity::INFORMATION),
+            RuleLevel::Hint => Some(DiagnosticSeverity::HINT),
+        }
+    }
+}
+
+/// The documents a ScopedSettings applies to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SettingsScope {
+    /// The documents in a folder and its subfolders, e.g. a workspace folder.
+    Folder(Uri),
+    /// The documents whose path matches a glob pattern. `*` matches any part of a path segment, `?` a single character
+    /// and `**` any number of segments. Patterns not starting with `/` can match at any depth, e.g. `vendor/**` matches
+    /// `/project/vendor/reset.css`.
+    Glob(String),
+}
+
+impl SettingsScope {
+    pub fn matches(&self, uri: &Uri) -> bool {
+        match self {
+            SettingsScope::Folder(folder) => {
+                let prefix = format!("{}/", folder.as_str().trim_end_matches('/'));
+                uri.as_str().starts_with(&prefix)
+            }
+            SettingsScope::Glob(pattern) => {
+                let path = uri
+                    .path()
+                    .as_str()
+                    .split('/')
+                    .filter(|segment| !segment.is_empty())
+                    .collect::<Vec<_>>();
+
+                let mut segments = pattern
+                    .split('/')
+                    .filter(|segment| !segment.is_empty())
+                    .collect::<Vec<_>>();
+                if !pattern.starts_with('/') {
+                    segments.insert(0, "**");
+                }
+
+                matches_segments(&segments, &path)
+            }
+        }
+    }
+}
+
+fn matches_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| matches_segments(rest, &path[skip..])),
+        Some((segment, rest)) => path.split_first().is_some_and(|(first, path_rest)| {
+            matches_segment(
+                &segment.chars().collect::<Vec<_>>(),
+                &first.chars().collect::<Vec<_>>(),
+            ) && matches_segments(rest, path_rest)
+        }),
+    }
+}
+
+fn matches_segment(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|skip| matches_segment(rest, &text[skip..])),
+        Some(('?', rest)) => !text.is_empty() && matches_segment(rest, &text[1..]),
+        Some((char, rest)) => text.first() == Some(char) && matches_segment(rest, &text[1..]),
+    }
+}
+
+/// Settings replacing the global settings for the documents in a scope.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScopedSettings {
+    pub scope: SettingsScope,
+    pub settings: Settings,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_settings_from_json() {
+        let settings: Settings =
+            serde_json::from_str(r#"{ "colors": { "maxDocumentColors": 500 } }"#).unwrap();
+        assert_eq!(settings.colors.max_document_colors, Some(500));
+
+        let settings: Settings = serde_json::from_str(
+            r#"{ "scss": { "loadPaths": ["file:///project/node_modules"] } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            settings.scss.load_paths,
+            vec!["file:///project/node_modules".to_string()]
+        );
+
+        let settings: Settings =
+            serde_json::from_str(r#"{ "css": { "customDirectives": ["apply", "theme()"] } }"#)
+                .unwrap();
+        assert_eq!(
+            settings.css.custom_directives,
+            vec!["apply".to_string(), "theme()".to_string()]
+        );
+
+        let settings: Settings =
+            serde_json::from_str(r#"{ "completion": { "emmetAbbreviations": true } }"#).unwrap();
+        assert!(settings.completion.emmet_abbreviations);
+
+        let settings: Settings =
+            serde_json::from_str(r#"{ "codeActions": { "rootFontSize": 10 } }"#).unwrap();
+        assert_eq!(settings.code_actions.root_font_size, Some(10));
+
+        let settings: Settings = serde_json::from_str(
+            r#"{ "diagnostics": { "colorContrast": { "enabled": true, "level": "AAA", "severity": "error" } } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            settings.diagnostics.color_contrast,
+            ColorContrastSettings {
+                enabled: true,
+                level: ContrastLevel::Aaa,
+                default_background: None,
+                severity: Some(Severity::Error),
+            }
+        );
+
+        let settings: Settings = serde_json::from_str(
+            r#"{ "diagnostics": { "rules": { "unknown-property": "ignore", "syntax-error": "warning" } } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            settings.diagnostics.rules,
+            HashMap::from([
+                ("unknown-property".to_string(), RuleLevel::Ignore),
+                ("syntax-error".to_string(), RuleLevel::Warning),
+            ])
+        );
+        assert_eq!(RuleLevel::Ignore.severity(), None);
+        assert!(serde_json::from_str::<Settings>(
+            r#"{ "diagnostics": { "rules": { "a": "off" } } }"#
+        )
+        .is_err());
+
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn test_settings_scope() {
+        let uri = Uri::from_str("file:///project/packages/app/src/styles/main.css").unwrap();
+
+        assert!(
+            SettingsScope::Folder(Uri::from_str("file:///project/packages/app").unwrap())
+                .matches(&uri)
+        );
+        assert!(
+            !SettingsScope::Folder(Uri::from_str("file:///project/packages/ap").unwrap())
+                .matches(&uri)
+        );
+
+        assert!(SettingsScope::Glob("src/**".to_string()).matches(&uri));
+        assert!(SettingsScope::Glob("**/*.css".to_string()).matches(&uri));
+        assert!(
+            SettingsScope::Glob("/project/packages/*/src/**/m?in.css".to_string()).matches(&uri)
+        );
+        assert!(!SettingsScope::Glob("/src/**".to_string()).matches(&uri));
+        assert!(!SettingsScope::Glob("vendor/**".to_string()).matches(&uri));
+        assert!(!SettingsScope::Glob("*.scss".to_string()).matches(&uri));
+    }
+}
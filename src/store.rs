@@ -1,73 +1,793 @@
-use std::collections::hash_map::Entry;
+use std::{
+    any::Any,
+    borrow::Cow,
+    convert::Infallible,
+    hash::{Hash, Hasher},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, OnceLock, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
+    time::Duration,
+};
 
-use biome_css_parser::CssParse;
-use lsp_types::{TextDocumentItem, Uri};
-use rustc_hash::FxHashMap;
+use biome_css_parser::{CssParse, ParseDiagnostic};
+use biome_rowan::{Direction, SendNode};
+use lsp_types::{
+    TextDocumentContentChangeEvent, TextDocumentItem, Uri, VersionedTextDocumentIdentifier,
+};
+use ropey::Rope;
+use rustc_hash::{FxHashMap, FxHasher};
 
-use crate::{converters::line_index::LineIndex, parser::parse_css};
+use crate::{
+    converters::{line_index::LineIndex, rope, PositionEncoding},
+    error::Error,
+    instrumentation::span,
+    parser::{parse_css_with_directives, reparse_css, CssDialect},
+};
 
+/// The state derived from the text of a document that a feature needs to compute its result.
+///
+/// Derived state is computed lazily the first time it is accessed, so features that don't need the syntax tree (e.g.
+/// folding) never pay for parsing. Declaring it up front lets the store compute it right away instead.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DerivedState {
+    pub line_index: bool,
+    pub css_tree: bool,
+}
+
+impl DerivedState {
+    pub const NONE: Self = Self {
+        line_index: false,
+        css_tree: false,
+    };
+    pub const LINE_INDEX: Self = Self {
+        line_index: true,
+        css_tree: false,
+    };
+    pub const CSS_TREE: Self = Self {
+        line_index: false,
+        css_tree: true,
+    };
+    pub const ALL: Self = Self {
+        line_index: true,
+        css_tree: true,
+    };
+}
+
+#[derive(Clone)]
 pub struct StoreEntry {
     pub document: TextDocumentItem,
+    /// The dialect of the document, detected from its language identifier or URI.
+    pub dialect: CssDialect,
+    /// The position encoding of the document, if it was opened with its own, see `DocumentStore::insert_with_encoding`.
+    /// Otherwise, the PositionEncoding of the LanguageService is used.
+    pub encoding: Option<PositionEncoding>,
+    // The custom directives of the store when the document was stored, which its syntax tree is parsed with. See
+    // `DocumentStore::set_custom_directives`.
+    custom_directives: Arc<[String]>,
+    // Some clients bump the version of a document without changing its content (e.g. when saving it). Comparing the
+    // hash of the content lets us keep the derived state of the previous version in that case.
+    content_hash: u64,
     // Calculating the offset of every line in a document is quite expensive, but is required for every conversion from
-    // offset to position (and vice versa). For this reason, we cache the line index here, updating it whenever the document is updated.
-    pub(crate) line_index: LineIndex,
-    pub css_tree: CssParse,
+    // offset to position (and vice versa). For this reason, we cache the line index here once it has been computed.
+    line_index: OnceLock<LineIndex>,
+    // Applying a change to a `String` is O(n), which adds up quickly on large documents when a client sends many
+    // changes at once. Changes are applied to a rope instead, built the first time the document is changed and then
+    // kept up to date. The text of the document is only rebuilt from it once per update.
+    rope: OnceLock<Rope>,
+    // Syntax nodes can't be shared between threads, so the parsed CSS is kept in its thread-safe form and turned back
+    // into a `CssParse` on demand, which only allocates a new root node.
+    css_tree: OnceLock<StoredParse>,
+    // Results of features computed for this version of the document, so that repeated requests for an unchanged
+    // document don't recompute them. See `StoreEntry::cached`.
+    cache: DerivedCache,
+}
+
+type CachedValue = Arc<dyn Any + Send + Sync>;
+
+/// Results of features memoized for a given version of a document.
+#[derive(Default)]
+struct DerivedCache {
+    values: Mutex<FxHashMap<&'static str, (i32, CachedValue)>>,
+    // Shared by every entry of a store, for its statistics.
+    counters: Arc<CacheCounters>,
+}
+
+#[derive(Default)]
+struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+// An updated entry starts from a clone of the previous one, which must not carry over the previous results.
+impl Clone for DerivedCache {
+    fn clone(&self) -> Self {
+        Self {
+            values: Mutex::default(),
+            counters: self.counters.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct StoredParse {
+    root: SendNode,
+    diagnostics: Vec<ParseDiagnostic>,
+    // How long parsing took, if it could be measured.
+    duration: Option<Duration>,
+}
+
+impl StoredParse {
+    fn new(parse: impl FnOnce() -> CssParse) -> Self {
+        // `Instant` isn't supported in WASM, where parse durations aren't measured.
+        #[cfg(not(target_arch = "wasm32"))]
+        let (parsed_css, duration) = {
+            let start = std::time::Instant::now();
+            let parsed_css = parse();
+            (parsed_css, Some(start.elapsed()))
+        };
+        #[cfg(target_arch = "wasm32")]
+        let (parsed_css, duration) = (parse(), None);
+
+        Self {
+            root: parsed_css
+                .syntax()
+                .as_send()
+                .expect("the root of a parse is always a root node"),
+            diagnostics: parsed_css.into_diagnostics(),
+            duration,
+        }
+    }
+
+    fn to_parse(&self) -> CssParse {
+        CssParse::new(
+            self.root
+                .clone()
+                .into_node()
+                .expect("the stored root is always a CSS node"),
+            self.diagnostics.clone(),
+        )
+    }
 }
 
 impl StoreEntry {
-    pub(crate) fn new(
-        document: TextDocumentItem,
-        line_index: LineIndex,
-        parsed_css: CssParse,
-    ) -> Self {
+    /// Create an entry for the given document. Its derived state is only computed once needed.
+    pub(crate) fn new(document: TextDocumentItem) -> Self {
         Self {
+            dialect: CssDialect::from_document(&document),
+            encoding: None,
+            custom_directives: Arc::new([]),
+            content_hash: hash_text(&document.text),
             document,
-            line_index,
-            css_tree: parsed_css,
+            line_index: OnceLock::new(),
+            rope: OnceLock::new(),
+            css_tree: OnceLock::new(),
+            cache: DerivedCache::default(),
         }
     }
+
+    /// Get the hash of the text of the document.
+    pub fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+
+    /// Whether the text of the document is `text`.
+    fn has_text(&self, text: &str) -> bool {
+        self.content_hash == hash_text(text) && self.document.text == text
+    }
+
+    /// Get the line index of the document, computing it if needed.
+    pub fn line_index(&self) -> &LineIndex {
+        self.line_index
+            .get_or_init(|| LineIndex::new(&self.document.text))
+    }
+
+    /// Get the text of the document as a rope, building it if needed.
+    pub fn rope(&self) -> &Rope {
+        self.rope
+            .get_or_init(|| Rope::from_str(&self.document.text))
+    }
+
+    /// Get the parsed CSS of the document, parsing it if needed.
+    pub fn css_tree(&self) -> CssParse {
+        self.stored_parse().to_parse()
+    }
+
+    /// Get the root of the parsed CSS of the document in a form that can be sent to other threads.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn send_root(&self) -> SendNode {
+        self.stored_parse().root.clone()
+    }
+
+    fn stored_parse(&self) -> &StoredParse {
+        self.css_tree.get_or_init(|| {
+            StoredParse::new(|| {
+                parse_css_with_directives(
+                    &self.document.text,
+                    self.dialect,
+                    &self.custom_directives,
+                )
+            })
+        })
+    }
+
+    /// Compute the given derived state now, if it hasn't been already.
+    pub fn prepare(&self, needs: DerivedState) {
+        if needs.line_index {
+            self.line_index();
+        }
+        if needs.css_tree {
+            self.stored_parse();
+        }
+    }
+
+    /// Get the result of `compute` for this version of the document, computing it only if it isn't cached yet.
+    ///
+    /// Results are cached under `key`, which must be unique to the feature computing them, and are dropped whenever the
+    /// document is updated. As a store belongs to a single LanguageService, results may depend on its PositionEncoding.
+    pub fn cached<T: Clone + Send + Sync + 'static>(
+        &self,
+        key: &'static str,
+        compute: impl FnOnce(&Self) -> T,
+    ) -> T {
+        self.try_cached(key, |entry| Ok::<_, Infallible>(compute(entry)))
+            .unwrap_or_else(|never| match never {})
+    }
+
+    /// Drop the results cached for this version of the document, see `DocumentStore::clear_caches`.
+    pub(crate) fn clear_cache(&self) {
+        self.cache
+            .values
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    /// Same as `cached`, for computations that can fail (e.g. be cancelled). Errors aren't cached.
+    pub fn try_cached<T: Clone + Send + Sync + 'static, E>(
+        &self,
+        key: &'static str,
+        compute: impl FnOnce(&Self) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let version = self.document.version;
+
+        let cached = self
+            .cache
+            .values
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+            .filter(|(cached_version, _)| *cached_version == version)
+            .and_then(|(_, value)| value.downcast_ref::<T>().cloned());
+        if let Some(value) = cached {
+            self.cache.counters.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(value);
+        }
+        self.cache.counters.misses.fetch_add(1, Ordering::Relaxed);
+
+        // The lock isn't held while computing, so that features can use the cache of other features.
+        let value = compute(self)?;
+
+        self.cache
+            .values
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, (version, Arc::new(value.clone())));
+
+        Ok(value)
+    }
+
+    /// Whether the document has already been parsed.
+    pub fn is_parsed(&self) -> bool {
+        self.css_tree.get().is_some()
+    }
+
+    /// Get statistics about the document.
+    pub fn stats(&self) -> DocumentStats {
+        let parse = self.css_tree.get();
+
+        DocumentStats {
+            uri: self.document.uri.clone(),
+            version: self.document.version,
+            text_bytes: self.document.text.len(),
+            parsed: parse.is_some(),
+            estimated_tree_bytes: parse.map_or(0, |parse| {
+                let root = parse.to_parse().syntax();
+                root.descendants_with_tokens(Direction::Next).count() * ESTIMATED_TREE_ELEMENT_SIZE
+                    + self.document.text.len()
+            }),
+            parse_duration: parse.and_then(|parse| parse.duration),
+        }
+    }
+}
+
+// A rough estimate of the memory used by each node or token of a syntax tree, excluding its text.
+const ESTIMATED_TREE_ELEMENT_SIZE: usize = 64;
+
+/// Statistics about a document of the store, see `StoreEntry::stats`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DocumentStats {
+    pub uri: Uri,
+    pub version: i32,
+    /// The size of the text of the document, in bytes.
+    pub text_bytes: usize,
+    /// Whether the document has been parsed, documents are only parsed once a feature needs it.
+    pub parsed: bool,
+    /// A rough estimate of the memory used by the syntax tree of the document, 0 if it isn't parsed.
+    pub estimated_tree_bytes: usize,
+    /// How long the last (re)parse of the document took, if it could be measured.
+    pub parse_duration: Option<Duration>,
+}
+
+/// Statistics about a store, see `DocumentStore::stats`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StoreStats {
+    pub document_count: usize,
+    /// The total size of the text of the documents, in bytes.
+    pub text_bytes: usize,
+    /// A rough estimate of the total memory used by the syntax trees of the documents.
+    pub estimated_tree_bytes: usize,
+    /// How many results of features were found in the cache since the store was created.
+    pub cache_hits: u64,
+    /// How many results of features had to be computed since the store was created.
+    pub cache_misses: u64,
+    pub documents: Vec<DocumentStats>,
+}
+
+impl StoreStats {
+    /// The proportion of results of features found in the cache, `None` if no results were requested yet.
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        let requests = self.cache_hits + self.cache_misses;
+        (requests > 0).then(|| self.cache_hits as f64 / requests as f64)
+    }
 }
 
+fn hash_text(text: &str) -> u64 {
+    let mut hasher = FxHasher::default();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// What `get_or_update_document` does when given an older version of a stored document, e.g. because requests were
+/// delivered out of order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StaleVersionPolicy {
+    /// Keep the stored document, which is returned instead of the older one.
+    #[default]
+    Ignore,
+    /// Replace the stored document by the older one, as for any other version.
+    Replace,
+}
+
+/// A document received with an older version than the stored one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StaleVersion {
+    pub uri: Uri,
+    /// The version of the received document.
+    pub version: i32,
+    /// The version of the stored document.
+    pub stored_version: i32,
+}
+
+type StaleVersionHook = Box<dyn Fn(&StaleVersion) + Send + Sync>;
+
+/// A thread-safe store of the documents known to a LanguageService.
+///
+/// Entries are immutable once stored: updating a document replaces its entry, so requests can keep using the
+/// `Arc<StoreEntry>` they got without holding any lock while the document is being updated by another thread.
 pub struct DocumentStore {
-    documents: FxHashMap<Uri, StoreEntry>,
+    // Locks nested in one another are taken in the order of the fields, so that threads can't wait on each other.
+    documents: RwLock<FxHashMap<Uri, Arc<StoreEntry>>>,
+    cache_counters: Arc<CacheCounters>,
+    // The hash of the content on disk of each document, `None` if it doesn't exist on disk.
+    disk_hashes: RwLock<FxHashMap<Uri, Option<u64>>>,
+    custom_directives: RwLock<Arc<[String]>>,
+    stale_version_policy: StaleVersionPolicy,
+    stale_version_hooks: Vec<StaleVersionHook>,
 }
 
 impl DocumentStore {
     pub fn new() -> Self {
         Self {
-            documents: FxHashMap::default(),
+            documents: RwLock::new(FxHashMap::default()),
+            cache_counters: Arc::default(),
+            disk_hashes: RwLock::new(FxHashMap::default()),
+            custom_directives: RwLock::new(Arc::new([])),
+            stale_version_policy: StaleVersionPolicy::default(),
+            stale_version_hooks: Vec::new(),
+        }
+    }
+
+    /// Set what `get_or_update_document` does with older versions of stored documents. They are ignored by default.
+    pub fn set_stale_version_policy(&mut self, policy: StaleVersionPolicy) {
+        self.stale_version_policy = policy;
+    }
+
+    /// Register a hook called whenever `get_or_update_document` is given an older version of a stored document,
+    /// whatever the StaleVersionPolicy, e.g. to log a warning.
+    pub fn on_stale_version(&mut self, hook: impl Fn(&StaleVersion) + Send + Sync + 'static) {
+        self.stale_version_hooks.push(Box::new(hook));
+    }
+
+    /// Set the custom at-rules and functions the documents are parsed with, e.g. Tailwind's `@apply`, see
+    /// `parser::parse_css_with_directives`. If they changed, the stored documents are parsed again once needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `directives` - The entries of the `css.customDirectives` setting.
+    pub fn set_custom_directives(&self, directives: &[String]) {
+        let mut documents = self.documents_mut();
+        let mut current = self
+            .custom_directives
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        if **current == *directives {
+            return;
+        }
+        *current = directives.into();
+
+        for entry in documents.values_mut() {
+            let mut updated_entry = StoreEntry::clone(entry);
+            updated_entry.custom_directives = current.clone();
+            updated_entry.css_tree = OnceLock::new();
+            *entry = Arc::new(updated_entry);
         }
     }
 
+    fn documents(&self) -> RwLockReadGuard<'_, FxHashMap<Uri, Arc<StoreEntry>>> {
+        self.documents
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn documents_mut(&self) -> RwLockWriteGuard<'_, FxHashMap<Uri, Arc<StoreEntry>>> {
+        self.documents
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
     /// Get a document from the store, updating it as well if necessary.
-    /// If the document is not yet in the store, it will be added.
-    pub fn get_or_update_document(&mut self, document: TextDocumentItem) -> &StoreEntry {
-        let uri = document.uri.clone();
-        let store_entry = self.documents.entry(uri);
+    /// If the document is not yet in the store, it will be added. If only its version changed, the
+    /// derived state of the stored document (line index, syntax tree) is kept.
+    ///
+    /// If the document is older than the stored one, what happens depends on the StaleVersionPolicy of the store. By
+    /// default, the stored document is returned as is.
+    pub fn get_or_update_document(&self, document: TextDocumentItem) -> Arc<StoreEntry> {
+        self.get_or_update_document_with(document, DerivedState::NONE)
+    }
 
-        match store_entry {
-            Entry::Vacant(entry) => {
-                let line_index = LineIndex::new(&document.text);
-                let css_tree = parse_css(&document.text);
+    /// Same as `get_or_update_document`, but also computes the derived state the caller needs.
+    pub fn get_or_update_document_with(
+        &self,
+        document: TextDocumentItem,
+        needs: DerivedState,
+    ) -> Arc<StoreEntry> {
+        self.get_or_update(Cow::Owned(document), needs)
+    }
+
+    /// Same as `get_or_update_document`, for callers that don't own the document. The document is only cloned if it
+    /// isn't already in the store with the same version, so requests on an up-to-date document never copy its text.
+    pub fn get_or_update_document_ref(&self, document: &TextDocumentItem) -> Arc<StoreEntry> {
+        self.get_or_update(Cow::Borrowed(document), DerivedState::NONE)
+    }
+
+    fn get_or_update(
+        &self,
+        document: Cow<'_, TextDocumentItem>,
+        needs: DerivedState,
+    ) -> Arc<StoreEntry> {
+        span!(
+            "store_update",
+            uri = document.uri.as_str(),
+            version = document.version
+        );
+        // Requests on an up-to-date document only need to read the store
+        if let Some(entry) = self
+            .get(&document.uri)
+            .filter(|entry| entry.document.version == document.version)
+        {
+            entry.prepare(needs);
+            return entry;
+        }
+
+        let mut stale_version = None;
+        let entry = {
+            // The stored entry is checked and replaced under the same lock, so that concurrent updates of a document
+            // don't overwrite each other
+            let mut documents = self.documents_mut();
+            match documents.get(&document.uri).cloned() {
+                Some(entry) if entry.document.version == document.version => entry,
+                Some(entry) if document.version < entry.document.version => {
+                    stale_version = Some(StaleVersion {
+                        uri: document.uri.clone(),
+                        version: document.version,
+                        stored_version: entry.document.version,
+                    });
 
-                entry.insert(StoreEntry::new(document, line_index, css_tree))
+                    match self.stale_version_policy {
+                        StaleVersionPolicy::Ignore => entry,
+                        StaleVersionPolicy::Replace => {
+                            self.insert_into(&mut documents, document.into_owned(), entry.encoding)
+                        }
+                    }
+                }
+                Some(entry) if entry.has_text(&document.text) => {
+                    let mut updated_entry = StoreEntry::clone(&entry);
+                    updated_entry.document.version = document.version;
+
+                    let updated_entry = Arc::new(updated_entry);
+                    documents.insert(updated_entry.document.uri.clone(), updated_entry.clone());
+                    updated_entry
+                }
+                Some(entry) => {
+                    self.insert_into(&mut documents, document.into_owned(), entry.encoding)
+                }
+                None => self.insert_into(&mut documents, document.into_owned(), None),
             }
-            Entry::Occupied(mut entry) => {
-                let mut_entry = entry.get_mut();
+        };
+
+        // Hooks are called once the store is unlocked, so that they can use it
+        if let Some(stale_version) = stale_version {
+            for hook in &self.stale_version_hooks {
+                hook(&stale_version);
+            }
+        }
+
+        entry.prepare(needs);
+
+        entry
+    }
+
+    /// Get a document from the store only if it is at the given version, without copying anything.
+    ///
+    /// # Returns
+    ///
+    /// * The StoreEntry of the document, or `None` if it isn't in the store or has another version.
+    pub fn get_current(
+        &self,
+        document: &VersionedTextDocumentIdentifier,
+    ) -> Option<Arc<StoreEntry>> {
+        self.get(&document.uri)
+            .filter(|entry| entry.document.version == document.version)
+    }
+
+    fn disk_hashes_mut(&self) -> RwLockWriteGuard<'_, FxHashMap<Uri, Option<u64>>> {
+        self.disk_hashes
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Add a document to the store, replacing any previous version of it.
+    ///
+    /// A document that wasn't in the store yet is assumed to have been opened from disk, so it isn't dirty until it
+    /// changes.
+    pub fn insert(&self, document: TextDocumentItem) -> Arc<StoreEntry> {
+        self.insert_with_encoding(document, None)
+    }
 
-                if document.version != mut_entry.document.version {
-                    mut_entry.document = document;
-                    mut_entry.line_index = LineIndex::new(&mut_entry.document.text);
-                    mut_entry.css_tree = parse_css(&mut_entry.document.text);
+    /// Same as `insert`, for a document whose positions use another encoding than the LanguageService, e.g. when
+    /// proxying documents from hosts with different encodings. The encoding is kept by the next versions of the
+    /// document, until it is inserted again.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document to insert.
+    /// * `encoding` - The encoding of the positions of the document, `None` for the encoding of the LanguageService.
+    pub fn insert_with_encoding(
+        &self,
+        document: TextDocumentItem,
+        encoding: Option<PositionEncoding>,
+    ) -> Arc<StoreEntry> {
+        self.insert_into(&mut self.documents_mut(), document, encoding)
+    }
+
+    /// Same as `insert_with_encoding`, with the documents of the store already locked.
+    fn insert_into(
+        &self,
+        documents: &mut FxHashMap<Uri, Arc<StoreEntry>>,
+        document: TextDocumentItem,
+        encoding: Option<PositionEncoding>,
+    ) -> Arc<StoreEntry> {
+        let mut entry = StoreEntry::new(document);
+        entry.encoding = encoding;
+        entry.custom_directives = self
+            .custom_directives
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        entry.cache.counters = self.cache_counters.clone();
+        let entry = Arc::new(entry);
+
+        self.disk_hashes_mut()
+            .entry(entry.document.uri.clone())
+            .or_insert(Some(entry.content_hash()));
+
+        documents.insert(entry.document.uri.clone(), entry.clone());
+
+        entry
+    }
+
+    /// Apply incremental changes to a document already in the store, as sent by clients using
+    /// `TextDocumentSyncKind::Incremental`. Changes are applied in order, the range of each change
+    /// being relative to the document resulting from the previous ones. A change without a range
+    /// replaces the whole document.
+    ///
+    /// If the document had already been parsed and a single change was made, the new version is reparsed
+    /// incrementally. Otherwise, parsing is left for when the tree is first needed.
+    ///
+    /// The ranges of the changes use `encoding`, unless the document was inserted with its own. As done by editors,
+    /// positions past the end of their line or of the document are clamped, and ranges ending before they start are
+    /// reversed. Returns `Error::DocumentNotOpen` if the document is not in the store, or
+    /// `Error::InvalidRange` if one of the changes has a position in the middle of a character, in which case the stored
+    /// document is left untouched.
+    pub fn update_document(
+        &self,
+        uri: &Uri,
+        version: i32,
+        changes: Vec<TextDocumentContentChangeEvent>,
+        encoding: PositionEncoding,
+    ) -> Result<Arc<StoreEntry>, Error> {
+        span!(
+            "store_update",
+            uri = uri.as_str(),
+            version = version,
+            changes = changes.len()
+        );
+        // The stored entry is read and replaced under the same lock, so that concurrent changes to a document are all
+        // applied
+        let mut documents = self.documents_mut();
+        let entry = documents
+            .get(uri)
+            .cloned()
+            .ok_or_else(|| Error::DocumentNotOpen(uri.clone()))?;
+
+        let mut updated_entry = StoreEntry::clone(&entry);
+        updated_entry.document.version = version;
+        let encoding = entry.encoding.unwrap_or(encoding);
+
+        if !changes.is_empty() {
+            // Only a single edit of a parsed document can be reparsed incrementally
+            let reparse = changes.len() == 1 && entry.is_parsed();
+            let mut edit = None;
+
+            // The line index is only kept up to date if it was already computed
+            let mut line_index = entry.line_index.get().cloned();
+
+            let mut text = entry.rope().clone();
+            for change in changes {
+                match change.range {
+                    Some(range) => {
+                        let text_range = rope::text_range(&text, range, encoding)
+                            .map_err(|()| Error::InvalidRange(range))?;
+                        if rope::edits_carriage_return(&text, text_range, &change.text) {
+                            line_index = None;
+                        }
+                        rope::replace(&mut text, text_range, &change.text);
+                        if let Some(line_index) = &mut line_index {
+                            line_index.apply_edit(text_range, &change.text);
+                        }
+                        edit = Some(text_range);
+                    }
+                    None => {
+                        text = Rope::from_str(&change.text);
+                        line_index = None;
+                        edit = None;
+                    }
                 }
+            }
 
-                entry.into_mut()
+            // Changes that don't change the content (e.g. undoing an edit in the same notification) only bump the
+            // version, keeping the derived state of the previous version
+            let new_text = text.to_string();
+            if !entry.has_text(&new_text) {
+                updated_entry.content_hash = hash_text(&new_text);
+                updated_entry.document.text = new_text;
+                updated_entry.rope = OnceLock::from(text);
+                updated_entry.line_index = match line_index {
+                    Some(line_index) => OnceLock::from(line_index),
+                    None => OnceLock::new(),
+                };
+                updated_entry.css_tree = match edit.filter(|_| reparse) {
+                    Some(edit) => OnceLock::from(StoredParse::new(|| {
+                        reparse_css(
+                            &entry.css_tree(),
+                            edit,
+                            &updated_entry.document.text,
+                            entry.dialect,
+                            &entry.custom_directives,
+                        )
+                    })),
+                    None => OnceLock::new(),
+                };
             }
         }
+
+        let updated_entry = Arc::new(updated_entry);
+        documents.insert(uri.clone(), updated_entry.clone());
+
+        Ok(updated_entry)
+    }
+
+    /// Get every document in the store.
+    pub fn entries(&self) -> Vec<Arc<StoreEntry>> {
+        self.documents().values().cloned().collect()
+    }
+
+    /// Drop the results cached for every document, e.g. because they depend on settings that changed. The derived
+    /// state of the documents (line index, syntax tree) is kept.
+    pub fn clear_caches(&self) {
+        for entry in self.documents().values() {
+            entry.clear_cache();
+        }
+    }
+
+    /// Get the URIs of every document in the store.
+    pub fn uris(&self) -> Vec<Uri> {
+        self.documents().keys().cloned().collect()
+    }
+
+    /// Get statistics about the store and its documents, e.g. for a status command or to debug memory usage.
+    pub fn stats(&self) -> StoreStats {
+        let documents = self
+            .entries()
+            .iter()
+            .map(|entry| entry.stats())
+            .collect::<Vec<_>>();
+
+        StoreStats {
+            document_count: documents.len(),
+            text_bytes: documents.iter().map(|document| document.text_bytes).sum(),
+            estimated_tree_bytes: documents
+                .iter()
+                .map(|document| document.estimated_tree_bytes)
+                .sum(),
+            cache_hits: self.cache_counters.hits.load(Ordering::Relaxed),
+            cache_misses: self.cache_counters.misses.load(Ordering::Relaxed),
+            documents,
+        }
+    }
+
+    /// Get a document from the store, without updating it.
+    pub fn get(&self, uri: &Uri) -> Option<Arc<StoreEntry>> {
+        self.documents().get(uri).cloned()
+    }
+
+    pub fn remove(&self, uri: &Uri) {
+        self.documents_mut().remove(uri);
+        self.disk_hashes_mut().remove(uri);
+    }
+
+    /// Record the content on disk of a document of the store, e.g. when it is saved or when the file changed. Documents
+    /// that aren't in the store are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the document.
+    /// * `text` - The content of the file, or `None` if it was deleted.
+    pub fn set_disk_content(&self, uri: &Uri, text: Option<&str>) {
+        if self.get(uri).is_some() {
+            self.disk_hashes_mut()
+                .insert(uri.clone(), text.map(hash_text));
+        }
     }
 
-    pub fn remove(&mut self, uri: &Uri) {
-        self.documents.remove(uri);
+    /// Whether a document of the store has unsaved changes, i.e. its content differs from the content on disk.
+    pub fn is_dirty(&self, uri: &Uri) -> bool {
+        self.get(uri).is_some_and(|entry| {
+            self.disk_hashes
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get(uri)
+                .is_none_or(|disk_hash| *disk_hash != Some(entry.content_hash()))
+        })
+    }
+
+    /// Get the URIs of the documents with unsaved changes.
+    pub fn dirty_uris(&self) -> Vec<Uri> {
+        self.uris()
+            .into_iter()
+            .filter(|uri| self.is_dirty(uri))
+            .collect()
     }
 }
 
@@ -81,21 +801,23 @@ impl Default for DocumentStore {
 mod tests {
     use std::str::FromStr;
 
+    use lsp_types::{Position, Range};
+
     use super::*;
 
     impl DocumentStore {
         pub fn len(&self) -> usize {
-            self.documents.len()
+            self.documents().len()
         }
 
         pub fn is_empty(&self) -> bool {
-            self.documents.is_empty()
+            self.documents().is_empty()
         }
     }
 
     #[test]
     fn test_document_store() {
-        let mut store = DocumentStore::new();
+        let store = DocumentStore::new();
 
         let document = TextDocumentItem {
             uri: Uri::from_str("file:///test").unwrap(),
@@ -125,9 +847,194 @@ mod tests {
         assert_eq!(store.len(), 1);
     }
 
+    #[test]
+    fn test_document_store_update_document() {
+        let store = DocumentStore::new();
+        let encoding = PositionEncoding::Wide(crate::converters::WideEncoding::Utf16);
+
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///test").unwrap(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: "body { color: red; }".to_string(),
+        };
+
+        store.get_or_update_document(document.clone());
+
+        let changes = vec![
+            TextDocumentContentChangeEvent {
+                range: Some(Range::new(Position::new(0, 14), Position::new(0, 17))),
+                range_length: None,
+                text: "blue".to_string(),
+            },
+            TextDocumentContentChangeEvent {
+                range: Some(Range::new(Position::new(0, 19), Position::new(0, 19))),
+                range_length: None,
+                text: "\n  margin: 0;\n".to_string(),
+            },
+        ];
+
+        let store_entry = store
+            .update_document(&document.uri, 2, changes, encoding)
+            .unwrap();
+
+        assert_eq!(store_entry.document.version, 2);
+        assert_eq!(
+            store_entry.document.text,
+            "body { color: blue;\n  margin: 0;\n }"
+        );
+        assert_eq!(store_entry.line_index().len(), 3);
+
+        let full_change = vec![TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "a {}".to_string(),
+        }];
+
+        let store_entry = store
+            .update_document(&document.uri, 3, full_change, encoding)
+            .unwrap();
+
+        assert_eq!(store_entry.document.text, "a {}");
+    }
+
+    #[test]
+    fn test_document_store_update_document_invalid() {
+        let store = DocumentStore::new();
+        let encoding = PositionEncoding::Utf8;
+
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///test").unwrap(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: "body { color: réd; }".to_string(),
+        };
+
+        // In the middle of `é`
+        let inside_character = vec![TextDocumentContentChangeEvent {
+            range: Some(Range::new(Position::new(0, 16), Position::new(0, 17))),
+            range_length: None,
+            text: "e".to_string(),
+        }];
+
+        // The document isn't in the store yet
+        assert_eq!(
+            store
+                .update_document(&document.uri, 2, inside_character.clone(), encoding)
+                .err(),
+            Some(Error::DocumentNotOpen(document.uri.clone()))
+        );
+
+        store.get_or_update_document(document.clone());
+
+        assert_eq!(
+            store
+                .update_document(&document.uri, 2, inside_character, encoding)
+                .err(),
+            Some(Error::InvalidRange(Range::new(
+                Position::new(0, 16),
+                Position::new(0, 17)
+            )))
+        );
+
+        let store_entry = store.get(&document.uri).unwrap();
+        assert_eq!(store_entry.document, document);
+    }
+
+    #[test]
+    fn test_document_store_update_document_clamped() {
+        let store = DocumentStore::new();
+        let encoding = PositionEncoding::Wide(crate::converters::WideEncoding::Utf16);
+
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///test").unwrap(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: "a {}\nb {}".to_string(),
+        };
+        store.get_or_update_document(document.clone());
+        // Keep the line index up to date through the changes
+        store.get(&document.uri).unwrap().line_index();
+
+        let changes = vec![
+            // Past the end of the first line
+            TextDocumentContentChangeEvent {
+                range: Some(Range::new(Position::new(0, 4), Position::new(0, 10))),
+                range_length: None,
+                text: " ".to_string(),
+            },
+            // Past the end of the document
+            TextDocumentContentChangeEvent {
+                range: Some(Range::new(Position::new(4, 0), Position::new(4, 1))),
+                range_length: None,
+                text: "\n".to_string(),
+            },
+            // Ending before it starts
+            TextDocumentContentChangeEvent {
+                range: Some(Range::new(Position::new(0, 2), Position::new(0, 0))),
+                range_length: None,
+                text: "c ".to_string(),
+            },
+        ];
+
+        let store_entry = store
+            .update_document(&document.uri, 2, changes, encoding)
+            .unwrap();
+        assert_eq!(store_entry.document.text, "c {} \nb {}\n");
+        assert_eq!(
+            *store_entry.line_index(),
+            LineIndex::new(&store_entry.document.text)
+        );
+    }
+
+    #[test]
+    fn test_document_store_update_document_line_breaks() {
+        let store = DocumentStore::new();
+        let encoding = PositionEncoding::Wide(crate::converters::WideEncoding::Utf16);
+
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///test").unwrap(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: "a {\r\n  color: red;\r\n}\rb {}".to_string(),
+        };
+        store.get_or_update_document(document.clone());
+        // Keep the line index up to date through the changes
+        store.get(&document.uri).unwrap().line_index();
+
+        let change = |range, text: &str| TextDocumentContentChangeEvent {
+            range: Some(range),
+            range_length: None,
+            text: text.to_string(),
+        };
+        let changes = vec![
+            // Updated incrementally
+            change(
+                Range::new(Position::new(1, 13), Position::new(1, 13)),
+                "\n  margin: 0;",
+            ),
+            // Turns the `\r` ending `}` into a `\r\n`
+            change(Range::new(Position::new(4, 0), Position::new(4, 0)), "\n"),
+            // Removes a `\r\n`
+            change(Range::new(Position::new(0, 3), Position::new(1, 0)), ""),
+        ];
+
+        let store_entry = store
+            .update_document(&document.uri, 2, changes, encoding)
+            .unwrap();
+        assert_eq!(
+            store_entry.document.text,
+            "a {  color: red;\n  margin: 0;\r\n}\r\nb {}"
+        );
+        assert_eq!(
+            *store_entry.line_index(),
+            LineIndex::new(&store_entry.document.text)
+        );
+    }
+
     #[test]
     fn test_document_store_remove() {
-        let mut store = DocumentStore::new();
+        let store = DocumentStore::new();
 
         let document = TextDocumentItem {
             uri: Uri::from_str("file:///test").unwrap(),
@@ -138,12 +1045,446 @@ mod tests {
 
         store.get_or_update_document(document.clone());
 
-        assert!(store.documents.contains_key(&document.uri));
+        assert!(store.documents().contains_key(&document.uri));
         assert!(!store.is_empty());
 
         store.remove(&document.uri);
 
-        assert!(!store.documents.contains_key(&document.uri));
+        assert!(!store.documents().contains_key(&document.uri));
         assert!(store.is_empty());
     }
+
+    #[test]
+    fn test_document_store_concurrent_updates() {
+        let store = DocumentStore::new();
+        let uri = Uri::from_str("file:///test").unwrap();
+        store.get_or_update_document(TextDocumentItem {
+            uri: uri.clone(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: String::new(),
+        });
+
+        // Changes made to the same document at the same time are all applied
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..100 {
+                        let change = TextDocumentContentChangeEvent {
+                            range: Some(Range::new(Position::new(0, 0), Position::new(0, 0))),
+                            range_length: None,
+                            text: "a".to_string(),
+                        };
+                        store
+                            .update_document(&uri, 2, vec![change], PositionEncoding::Utf8)
+                            .unwrap();
+                    }
+                });
+            }
+        });
+
+        assert_eq!(store.get(&uri).unwrap().document.text.len(), 800);
+    }
+
+    #[test]
+    fn test_document_store_stale_version() {
+        let mut store = DocumentStore::new();
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let hook_warnings = warnings.clone();
+        store.on_stale_version(move |stale| hook_warnings.lock().unwrap().push(stale.clone()));
+
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///test").unwrap(),
+            language_id: "css".to_string(),
+            version: 2,
+            text: "b {}".to_string(),
+        };
+        let stale_document = TextDocumentItem {
+            version: 1,
+            text: "a {}".to_string(),
+            ..document.clone()
+        };
+        store.get_or_update_document(document.clone());
+
+        assert_eq!(
+            store
+                .get_or_update_document(stale_document.clone())
+                .document,
+            document
+        );
+        assert_eq!(store.get(&document.uri).unwrap().document, document);
+
+        store.set_stale_version_policy(StaleVersionPolicy::Replace);
+        assert_eq!(
+            store
+                .get_or_update_document(stale_document.clone())
+                .document,
+            stale_document
+        );
+
+        let stale_version = StaleVersion {
+            uri: document.uri.clone(),
+            version: 1,
+            stored_version: 2,
+        };
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            vec![stale_version.clone(), stale_version]
+        );
+    }
+
+    #[test]
+    fn test_document_store_dirty() {
+        let store = DocumentStore::new();
+        let uri = Uri::from_str("file:///test").unwrap();
+
+        store.insert(TextDocumentItem {
+            uri: uri.clone(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: "a {}".to_string(),
+        });
+        assert!(!store.is_dirty(&uri));
+
+        store.insert(TextDocumentItem {
+            uri: uri.clone(),
+            language_id: "css".to_string(),
+            version: 2,
+            text: "b {}".to_string(),
+        });
+        assert_eq!(store.dirty_uris(), vec![uri.clone()]);
+
+        store.set_disk_content(&uri, Some("b {}"));
+        assert!(!store.is_dirty(&uri));
+
+        // A document whose file was deleted is dirty
+        store.set_disk_content(&uri, None);
+        assert!(store.is_dirty(&uri));
+
+        store.remove(&uri);
+        assert!(!store.is_dirty(&uri));
+        assert!(store.dirty_uris().is_empty());
+    }
+
+    #[test]
+    fn test_document_store_lazy_parsing() {
+        let store = DocumentStore::new();
+        let encoding = PositionEncoding::Wide(crate::converters::WideEncoding::Utf16);
+
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///test").unwrap(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: "body { color: red; }".to_string(),
+        };
+
+        let store_entry = store.get_or_update_document(document.clone());
+        assert!(!store_entry.is_parsed());
+
+        let store_entry =
+            store.get_or_update_document_with(document.clone(), DerivedState::CSS_TREE);
+        assert!(store_entry.is_parsed());
+
+        // Documents that were already parsed are reparsed when updated
+        let change = vec![TextDocumentContentChangeEvent {
+            range: Some(Range::new(Position::new(0, 14), Position::new(0, 17))),
+            range_length: None,
+            text: "blue".to_string(),
+        }];
+
+        let store_entry = store
+            .update_document(&document.uri, 2, change, encoding)
+            .unwrap();
+        assert!(store_entry.is_parsed());
+
+        // But replacing the whole document leaves parsing for later
+        let full_change = vec![TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "a {}".to_string(),
+        }];
+
+        let store_entry = store
+            .update_document(&document.uri, 3, full_change, encoding)
+            .unwrap();
+        assert!(!store_entry.is_parsed());
+        assert_eq!(store_entry.css_tree().syntax().to_string(), "a {}");
+    }
+
+    #[test]
+    fn test_document_store_custom_directives() {
+        let store = DocumentStore::new();
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///test.css").unwrap(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: ".btn { @apply font-bold; }".to_string(),
+        };
+
+        let store_entry =
+            store.get_or_update_document_with(document.clone(), DerivedState::CSS_TREE);
+        assert!(store_entry.css_tree().has_errors());
+
+        // Stored documents are parsed again with the new directives, new ones are parsed with them
+        store.set_custom_directives(&["apply".to_string()]);
+        let store_entry = store.get(&document.uri).unwrap();
+        assert!(!store_entry.is_parsed());
+        assert!(!store_entry.css_tree().has_errors());
+
+        let store_entry = store.insert(TextDocumentItem {
+            uri: Uri::from_str("file:///other.css").unwrap(),
+            ..document
+        });
+        assert!(!store_entry.css_tree().has_errors());
+    }
+
+    #[test]
+    fn test_document_store_cached() {
+        let store = DocumentStore::new();
+        let encoding = PositionEncoding::Wide(crate::converters::WideEncoding::Utf16);
+
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///test").unwrap(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: "body { color: red; }".to_string(),
+        };
+
+        let computations = std::cell::Cell::new(0);
+        let compute = |entry: &StoreEntry| {
+            computations.set(computations.get() + 1);
+            entry.document.text.len()
+        };
+
+        let store_entry = store.get_or_update_document(document.clone());
+        assert_eq!(store_entry.cached("length", compute), 20);
+        assert_eq!(store_entry.cached("length", compute), 20);
+        assert_eq!(computations.get(), 1, "The result should be cached");
+
+        let store_entry = store.get_or_update_document(document.clone());
+        assert_eq!(store_entry.cached("length", compute), 20);
+        assert_eq!(
+            computations.get(),
+            1,
+            "The same version should use the cache"
+        );
+
+        let change = vec![TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "a {}".to_string(),
+        }];
+        let store_entry = store
+            .update_document(&document.uri, 2, change, encoding)
+            .unwrap();
+
+        assert_eq!(store_entry.cached("length", compute), 4);
+        assert_eq!(computations.get(), 2, "Updates should invalidate the cache");
+    }
+
+    #[test]
+    fn test_document_store_update_document_wide_chars() {
+        let store = DocumentStore::new();
+        let encoding = PositionEncoding::Wide(crate::converters::WideEncoding::Utf16);
+
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///test").unwrap(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: "a::after { content: \"🎨\"; color: red; }".to_string(),
+        };
+
+        store.get_or_update_document(document.clone());
+
+        // The emoji is two UTF-16 code units long, but four bytes long
+        let changes = vec![TextDocumentContentChangeEvent {
+            range: Some(Range::new(Position::new(0, 33), Position::new(0, 36))),
+            range_length: None,
+            text: "blue".to_string(),
+        }];
+
+        let store_entry = store
+            .update_document(&document.uri, 2, changes, encoding)
+            .unwrap();
+
+        assert_eq!(
+            store_entry.document.text,
+            "a::after { content: \"🎨\"; color: blue; }"
+        );
+        assert_eq!(store_entry.rope().to_string(), store_entry.document.text);
+
+        // Positions in the middle of a character are invalid in UTF-8
+        let changes = vec![TextDocumentContentChangeEvent {
+            range: Some(Range::new(Position::new(0, 22), Position::new(0, 22))),
+            range_length: None,
+            text: "x".to_string(),
+        }];
+
+        assert_eq!(
+            store
+                .update_document(&document.uri, 3, changes.clone(), PositionEncoding::Utf8)
+                .err(),
+            Some(Error::InvalidRange(Range::new(
+                Position::new(0, 22),
+                Position::new(0, 22)
+            )))
+        );
+
+        // As are positions in the middle of a surrogate pair in UTF-16
+        assert_eq!(
+            store
+                .update_document(&document.uri, 3, changes, encoding)
+                .err(),
+            Some(Error::InvalidRange(Range::new(
+                Position::new(0, 22),
+                Position::new(0, 22)
+            )))
+        );
+        assert_eq!(
+            store.get(&document.uri).unwrap().document.text,
+            "a::after { content: \"🎨\"; color: blue; }"
+        );
+    }
+
+    #[test]
+    fn test_document_store_unchanged_content() {
+        let store = DocumentStore::new();
+        let encoding = PositionEncoding::Wide(crate::converters::WideEncoding::Utf16);
+
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///test").unwrap(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: "body { color: red; }".to_string(),
+        };
+
+        let store_entry =
+            store.get_or_update_document_with(document.clone(), DerivedState::CSS_TREE);
+        let content_hash = store_entry.content_hash();
+
+        // A new version with the same content keeps the syntax tree
+        let store_entry = store.get_or_update_document(TextDocumentItem {
+            version: 2,
+            ..document.clone()
+        });
+        assert_eq!(store_entry.document.version, 2);
+        assert_eq!(store_entry.content_hash(), content_hash);
+        assert!(store_entry.is_parsed());
+
+        let full_change = vec![TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: document.text.clone(),
+        }];
+
+        let store_entry = store
+            .update_document(&document.uri, 3, full_change, encoding)
+            .unwrap();
+        assert_eq!(store_entry.document.version, 3);
+        assert!(store_entry.is_parsed());
+
+        // While a new content doesn't
+        let store_entry = store.get_or_update_document(TextDocumentItem {
+            version: 4,
+            text: "a {}".to_string(),
+            ..document.clone()
+        });
+        assert_ne!(store_entry.content_hash(), content_hash);
+        assert!(!store_entry.is_parsed());
+    }
+
+    #[test]
+    fn test_document_store_stats() {
+        let store = DocumentStore::new();
+
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///test").unwrap(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: "body { color: red; }".to_string(),
+        };
+
+        let store_entry = store.get_or_update_document(document.clone());
+        store.get_or_update_document(TextDocumentItem {
+            uri: Uri::from_str("file:///other").unwrap(),
+            ..document.clone()
+        });
+
+        let stats = store.stats();
+        assert_eq!(stats.document_count, 2);
+        assert_eq!(stats.text_bytes, 40);
+        assert_eq!(stats.estimated_tree_bytes, 0, "No document is parsed yet");
+        assert_eq!(stats.cache_hit_rate(), None);
+
+        store_entry.css_tree();
+        store_entry.cached("length", |entry| entry.document.text.len());
+        store_entry.cached("length", |entry| entry.document.text.len());
+
+        let stats = store.stats();
+        assert!(stats.estimated_tree_bytes > 0);
+        assert_eq!((stats.cache_hits, stats.cache_misses), (1, 1));
+        assert_eq!(stats.cache_hit_rate(), Some(0.5));
+
+        let document_stats = stats
+            .documents
+            .iter()
+            .find(|stats| stats.uri == document.uri)
+            .unwrap();
+        assert!(document_stats.parsed);
+        assert!(document_stats.parse_duration.is_some());
+
+        let mut uris = store.uris();
+        uris.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        assert_eq!(
+            uris,
+            vec![
+                Uri::from_str("file:///other").unwrap(),
+                Uri::from_str("file:///test").unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_document_store_borrowed_document() {
+        let store = DocumentStore::new();
+
+        let document = TextDocumentItem {
+            uri: Uri::from_str("file:///test").unwrap(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: "body { color: red; }".to_string(),
+        };
+
+        let store_entry = store.get_or_update_document_ref(&document);
+        assert_eq!(store_entry.document, document);
+
+        // The current version is returned as is
+        assert!(Arc::ptr_eq(
+            &store.get_or_update_document_ref(&document),
+            &store_entry
+        ));
+        assert!(Arc::ptr_eq(
+            &store
+                .get_current(&VersionedTextDocumentIdentifier::new(
+                    document.uri.clone(),
+                    1
+                ))
+                .unwrap(),
+            &store_entry
+        ));
+        assert!(store
+            .get_current(&VersionedTextDocumentIdentifier::new(
+                document.uri.clone(),
+                2
+            ))
+            .is_none());
+
+        let updated_document = TextDocumentItem {
+            version: 2,
+            text: "a {}".to_string(),
+            ..document
+        };
+        let store_entry = store.get_or_update_document_ref(&updated_document);
+        assert_eq!(store_entry.document, updated_document);
+    }
 }
@@ -0,0 +1,378 @@
+//! Virtual documents let hosts embedding CSS in other languages (e.g. styled-components tagged templates, `<style>`
+//! tags) use the LanguageService on the extracted CSS, while getting results in the coordinates of the host document.
+//!
+//! The host extracts the CSS itself and registers it along with a table mapping ranges of the virtual CSS document to
+//! ranges of the host document. Results falling outside of the mapped ranges (e.g. in a placeholder generated for a
+//! template expression) are dropped.
+
+use std::sync::{Arc, PoisonError};
+
+use biome_rowan::{TextRange, TextSize};
+use lsp_types::{
+    ColorInformation, FoldingRange, InlayHint, InlayHintLabel, Position, Range, SemanticToken,
+    SemanticTokens, TextDocumentItem, Uri,
+};
+
+use crate::{
+    converters::{from_proto, line_index::LineIndex, to_proto, PositionEncoding},
+    service::LanguageService,
+    store::StoreEntry,
+};
+
+/// A range of the virtual document that was copied verbatim from the host document.
+///
+/// Offsets are in bytes (UTF-8 code units), from the start of their respective document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OffsetMapping {
+    pub virtual_start: u32,
+    pub host_start: u32,
+    pub length: u32,
+}
+
+/// A virtual CSS document, embedded in a host document.
+pub struct VirtualDocument {
+    pub host_uri: Uri,
+    host_line_index: LineIndex,
+    mappings: Vec<OffsetMapping>,
+}
+
+impl VirtualDocument {
+    fn new(host: &TextDocumentItem, mut mappings: Vec<OffsetMapping>) -> Self {
+        mappings.sort_by_key(|mapping| mapping.virtual_start);
+
+        Self {
+            host_uri: host.uri.clone(),
+            host_line_index: LineIndex::new(&host.text),
+            mappings,
+        }
+    }
+
+    // A range is only mapped if it is entirely inside a single mapping, so that the text in between is the same in
+    // both documents.
+    fn to_host_range(&self, range: TextRange) -> Option<TextRange> {
+        let (start, end) = (u32::from(range.start()), u32::from(range.end()));
+        self.mappings
+            .iter()
+            .find(|mapping| {
+                mapping.virtual_start <= start && end <= mapping.virtual_start + mapping.length
+            })
+            .map(|mapping| {
+                TextRange::new(
+                    TextSize::from(mapping.host_start + start - mapping.virtual_start),
+                    TextSize::from(mapping.host_start + end - mapping.virtual_start),
+                )
+            })
+    }
+
+    fn to_virtual_range(&self, range: TextRange) -> Option<TextRange> {
+        let (start, end) = (u32::from(range.start()), u32::from(range.end()));
+        self.mappings
+            .iter()
+            .find(|mapping| {
+                mapping.host_start <= start && end <= mapping.host_start + mapping.length
+            })
+            .map(|mapping| {
+                TextRange::new(
+                    TextSize::from(mapping.virtual_start + start - mapping.host_start),
+                    TextSize::from(mapping.virtual_start + end - mapping.host_start),
+                )
+            })
+    }
+}
+
+/// Converts positions between a virtual document and its host document.
+pub struct PositionMapper<'a> {
+    document: &'a VirtualDocument,
+    line_index: &'a LineIndex,
+    encoding: PositionEncoding,
+}
+
+impl PositionMapper<'_> {
+    /// Convert a position of the virtual document to the host document.
+    pub fn to_host(&self, position: Position) -> Option<Position> {
+        self.range_to_host(Range::new(position, position))
+            .map(|range| range.start)
+    }
+
+    /// Convert a position of the host document to the virtual document.
+    pub fn to_virtual(&self, position: Position) -> Option<Position> {
+        self.range_to_virtual(Range::new(position, position))
+            .map(|range| range.start)
+    }
+
+    /// Convert a range of the virtual document to the host document. The range must be inside a single mapping.
+    pub fn range_to_host(&self, range: Range) -> Option<Range> {
+        let range = from_proto::text_range(self.line_index, range, self.encoding).ok()?;
+        let host_range = self.document.to_host_range(range)?;
+        to_proto::range(&self.document.host_line_index, host_range, self.encoding).ok()
+    }
+
+    /// Convert a range of the host document to the virtual document. The range must be inside a single mapping.
+    pub fn range_to_virtual(&self, range: Range) -> Option<Range> {
+        let host_range =
+            from_proto::text_range(&self.document.host_line_index, range, self.encoding).ok()?;
+        let range = self.document.to_virtual_range(host_range)?;
+        to_proto::range(self.line_index, range, self.encoding).ok()
+    }
+}
+
+/// A feature result that can be converted from the coordinates of a virtual document to those of its host.
+pub trait IntoHostCoordinates: Sized {
+    /// Convert the result to the host document, or return `None` if it isn't in a mapped range.
+    fn into_host(self, mapper: &PositionMapper) -> Option<Self>;
+}
+
+impl<T: IntoHostCoordinates> IntoHostCoordinates for Vec<T> {
+    fn into_host(self, mapper: &PositionMapper) -> Option<Self> {
+        Some(
+            self.into_iter()
+                .filter_map(|item| item.into_host(mapper))
+                .collect(),
+        )
+    }
+}
+
+/// Errors are returned as is, as they don't have coordinates.
+impl<T: IntoHostCoordinates, E> IntoHostCoordinates for Result<T, E> {
+    fn into_host(self, mapper: &PositionMapper) -> Option<Self> {
+        match self {
+            Ok(result) => result.into_host(mapper).map(Ok),
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
+
+impl IntoHostCoordinates for Position {
+    fn into_host(self, mapper: &PositionMapper) -> Option<Self> {
+        mapper.to_host(self)
+    }
+}
+
+impl IntoHostCoordinates for Range {
+    fn into_host(self, mapper: &PositionMapper) -> Option<Self> {
+        mapper.range_to_host(self)
+    }
+}
+
+impl IntoHostCoordinates for ColorInformation {
+    fn into_host(self, mapper: &PositionMapper) -> Option<Self> {
+        Some(ColorInformation {
+            range: mapper.range_to_host(self.range)?,
+            ..self
+        })
+    }
+}
+
+impl IntoHostCoordinates for FoldingRange {
+    fn into_host(self, mapper: &PositionMapper) -> Option<Self> {
+        let start = mapper.to_host(Position::new(
+            self.start_line,
+            self.start_character.unwrap_or(0),
+        ))?;
+        let end = mapper.to_host(Position::new(
+            self.end_line,
+            self.end_character.unwrap_or(0),
+        ))?;
+
+        Some(FoldingRange {
+            start_line: start.line,
+            start_character: self.start_character.map(|_| start.character),
+            end_line: end.line,
+            end_character: self.end_character.map(|_| end.character),
+            ..self
+        })
+    }
+}
+
+impl IntoHostCoordinates for InlayHint {
+    fn into_host(self, mapper: &PositionMapper) -> Option<Self> {
+        let text_edits = match self.text_edits {
+            Some(text_edits) => Some(
+                text_edits
+                    .into_iter()
+                    .map(|mut text_edit| {
+                        text_edit.range = mapper.range_to_host(text_edit.range)?;
+                        Some(text_edit)
+                    })
+                    .collect::<Option<Vec<_>>>()?,
+            ),
+            None => None,
+        };
+
+        // Locations in label parts point to the virtual document, which the host can't show
+        let label = match self.label {
+            InlayHintLabel::LabelParts(parts) => InlayHintLabel::LabelParts(
+                parts
+                    .into_iter()
+                    .map(|part| lsp_types::InlayHintLabelPart {
+                        location: None,
+                        ..part
+                    })
+                    .collect(),
+            ),
+            label => label,
+        };
+
+        Some(InlayHint {
+            position: mapper.to_host(self.position)?,
+            label,
+            text_edits,
+            ..self
+        })
+    }
+}
+
+impl IntoHostCoordinates for SemanticTokens {
+    fn into_host(self, mapper: &PositionMapper) -> Option<Self> {
+        let mut data = Vec::with_capacity(self.data.len());
+        let (mut line, mut start) = (0, 0);
+        let (mut previous_line, mut previous_start) = (0, 0);
+
+        for token in self.data {
+            // Tokens are relative to the previous one in the virtual document
+            line += token.delta_line;
+            start = if token.delta_line == 0 {
+                start + token.delta_start
+            } else {
+                token.delta_start
+            };
+
+            let Some(Range {
+                start: host_start,
+                end: host_end,
+            }) = mapper.range_to_host(Range::new(
+                Position::new(line, start),
+                Position::new(line, start + token.length),
+            ))
+            else {
+                continue;
+            };
+            if host_start.line != host_end.line {
+                continue;
+            }
+
+            // Tokens must stay sorted, which isn't guaranteed if the mappings reorder the document
+            if (host_start.line, host_start.character) < (previous_line, previous_start) {
+                continue;
+            }
+
+            let delta_line = host_start.line - previous_line;
+            data.push(SemanticToken {
+                delta_line,
+                delta_start: if delta_line == 0 {
+                    host_start.character - previous_start
+                } else {
+                    host_start.character
+                },
+                length: host_end.character - host_start.character,
+                ..token
+            });
+
+            previous_line = host_start.line;
+            previous_start = host_start.character;
+        }
+
+        Some(SemanticTokens { data, ..self })
+    }
+}
+
+impl LanguageService {
+    /// Register a virtual CSS document embedded in a host document, replacing any previous version of it. The virtual
+    /// document is added to the store like any other document, and can then be used with `in_host_coordinates`. It is
+    /// unregistered by `close_document`.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The host document, e.g. a JavaScript file using styled-components.
+    /// * `document` - The virtual CSS document extracted from the host, with a URI unique to it.
+    /// * `mappings` - The ranges of the virtual document copied verbatim from the host, in bytes.
+    ///
+    /// # Returns
+    ///
+    /// * The StoreEntry of the virtual document.
+    pub fn open_virtual_document(
+        &self,
+        host: &TextDocumentItem,
+        document: TextDocumentItem,
+        mappings: Vec<OffsetMapping>,
+    ) -> Arc<StoreEntry> {
+        let virtual_document = VirtualDocument::new(host, mappings);
+        let uri = document.uri.clone();
+
+        let store_entry = self.open_document(document);
+        self.virtual_documents
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(uri, Arc::new(virtual_document));
+
+        store_entry
+    }
+
+    /// Run a request on a virtual document, converting its result to the coordinates of the host document. Results
+    /// that aren't in a mapped range are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the virtual document.
+    /// * `request` - The request to run, given the service, the virtual document, and a PositionMapper to convert
+    ///   parameters from the host document (e.g. the range of `get_inlay_hints`).
+    ///
+    /// # Returns
+    ///
+    /// * The result of the request in host coordinates, or `None` if no virtual document is registered for `uri`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use csslsrs::service::LanguageService;
+    /// use csslsrs::virtual_document::OffsetMapping;
+    /// use lsp_types::{TextDocumentItem, Uri};
+    /// use std::str::FromStr;
+    ///
+    /// let ls = LanguageService::default();
+    ///
+    /// let host = TextDocumentItem {
+    ///     uri: Uri::from_str("file:///button.js").unwrap(),
+    ///     language_id: "javascript".to_string(),
+    ///     version: 1,
+    ///     text: "const Button = styled.a`color: red;`;".to_string(),
+    /// };
+    /// let document = TextDocumentItem {
+    ///     uri: Uri::from_str("embedded:///button.js/0.css").unwrap(),
+    ///     language_id: "css".to_string(),
+    ///     version: 1,
+    ///     text: "a {color: red;}".to_string(),
+    /// };
+    /// let mappings = vec![OffsetMapping { virtual_start: 3, host_start: 24, length: 11 }];
+    /// ls.open_virtual_document(&host, document.clone(), mappings);
+    ///
+    /// # #[cfg(feature = "colors")]
+    /// # {
+    /// let colors = ls
+    ///     .in_host_coordinates(&document.uri, |ls, document, _| ls.get_document_colors(document))
+    ///     .unwrap();
+    /// assert_eq!(colors[0].range.start.character, 31);
+    /// # }
+    /// ```
+    pub fn in_host_coordinates<T: IntoHostCoordinates>(
+        &self,
+        uri: &Uri,
+        request: impl FnOnce(&Self, TextDocumentItem, &PositionMapper) -> T,
+    ) -> Option<T> {
+        let virtual_document = self
+            .virtual_documents
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(uri)
+            .cloned()?;
+        let store_entry = self.store.get(uri)?;
+
+        let mapper = PositionMapper {
+            document: &virtual_document,
+            line_index: store_entry.line_index(),
+            encoding: self.encoding_for(&store_entry),
+        };
+
+        request(self, store_entry.document.clone(), &mapper).into_host(&mapper)
+    }
+}
@@ -0,0 +1,307 @@
+//! A single-pass traversal of syntax trees shared by the analyses of a document.
+//!
+//! Instead of each feature walking the whole tree on its own, analyses implement `Visitor` and declare the kinds of
+//! nodes they are interested in. `walk` then runs any number of them in one traversal, only calling each visitor for
+//! the nodes it asked for. The analyses used by the features of the LanguageService are run together by
+//! `StoreEntry::analysis`, whose results are cached for each version of a document.
+
+use std::sync::Arc;
+
+#[cfg(feature = "parallel")]
+use biome_css_syntax::CssRoot;
+use biome_css_syntax::{CssLanguage, CssSyntaxKind};
+use biome_rowan::SyntaxNode;
+#[cfg(feature = "colors")]
+use biome_rowan::TextRange;
+#[cfg(feature = "parallel")]
+use biome_rowan::{AstNode, SendNode};
+#[cfg(feature = "colors")]
+use lsp_types::Color;
+use rustc_hash::FxHashMap;
+
+#[cfg(feature = "colors")]
+use crate::features::colors::{scss_variable_colors, ColorVisitor};
+use crate::{
+    cancellation::{CancellationToken, Cancelled},
+    scss::ScssConstruct,
+    store::StoreEntry,
+    workspace_index::{scss_symbol, IndexedSymbol, SymbolVisitor},
+};
+
+/// An analysis run on the nodes of a syntax tree, in preorder.
+pub trait Visitor {
+    /// The kinds of nodes to visit, or `None` to visit every node.
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]>;
+
+    /// Visit a node of one of the kinds returned by `kinds`.
+    fn visit(&mut self, node: &SyntaxNode<CssLanguage>);
+}
+
+// Checking for cancellation on every node would be wasteful, as most visits are very cheap.
+const CANCELLATION_CHECK_INTERVAL: usize = 256;
+
+/// Run the given visitors on `root` and its descendants, in a single preorder traversal.
+///
+/// Stops with `Err(Cancelled)` once `cancellation` is cancelled, in which case the visitors only saw part of the tree.
+pub fn walk(
+    root: &SyntaxNode<CssLanguage>,
+    visitors: &mut [&mut dyn Visitor],
+    cancellation: &CancellationToken,
+) -> Result<(), Cancelled> {
+    walk_nodes(root.descendants(), visitors, cancellation)
+}
+
+/// Same as `walk`, on the given nodes only, e.g. the top-level rules analyzed by a thread.
+pub(crate) fn walk_nodes(
+    nodes: impl Iterator<Item = SyntaxNode<CssLanguage>>,
+    visitors: &mut [&mut dyn Visitor],
+    cancellation: &CancellationToken,
+) -> Result<(), Cancelled> {
+    let mut by_kind = FxHashMap::<CssSyntaxKind, Vec<usize>>::default();
+    let mut every_kind = Vec::new();
+    for (index, visitor) in visitors.iter().enumerate() {
+        match visitor.kinds() {
+            Some(kinds) => {
+                for kind in kinds {
+                    by_kind.entry(*kind).or_default().push(index);
+                }
+            }
+            None => every_kind.push(index),
+        }
+    }
+
+    for (count, node) in nodes.enumerate() {
+        if count % CANCELLATION_CHECK_INTERVAL == 0 {
+            cancellation.check()?;
+        }
+
+        for &index in &every_kind {
+            visitors[index].visit(&node);
+        }
+        if let Some(indices) = by_kind.get(&node.kind()) {
+            for &index in indices {
+                visitors[index].visit(&node);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The results of the analyses shared by the features of the LanguageService, for a version of a document.
+///
+/// Ranges are kept as offsets, so that the analysis doesn't depend on the PositionEncoding.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DocumentAnalysis {
+    #[cfg(feature = "colors")]
+    pub(crate) colors: Vec<(TextRange, Color)>,
+    /// The custom properties used with `var()`, and the range of the `var()` function.
+    #[cfg(feature = "colors")]
+    pub(crate) variables: Vec<(TextRange, String)>,
+    pub(crate) symbols: Vec<IndexedSymbol>,
+}
+
+impl DocumentAnalysis {
+    /// Append the analysis of the nodes following the ones analyzed so far.
+    #[cfg(feature = "parallel")]
+    fn extend(&mut self, other: DocumentAnalysis) {
+        #[cfg(feature = "colors")]
+        self.colors.extend(other.colors);
+        #[cfg(feature = "colors")]
+        self.variables.extend(other.variables);
+        self.symbols.extend(other.symbols);
+    }
+
+    /// Add the symbols and colors of the SCSS constructs of a document, keeping them in source order. SCSS constructs
+    /// are masked before parsing, so they aren't part of the syntax tree the visitors walk.
+    fn add_scss(&mut self, constructs: &[ScssConstruct]) {
+        if constructs.is_empty() {
+            return;
+        }
+
+        #[cfg(feature = "colors")]
+        {
+            self.colors.extend(scss_variable_colors(constructs));
+            self.colors.sort_by_key(|(range, _)| range.start());
+        }
+        self.symbols
+            .extend(constructs.iter().filter_map(scss_symbol));
+        self.symbols.sort_by_key(|symbol| symbol.range.start());
+    }
+}
+
+impl StoreEntry {
+    /// Get the shared analysis of the document, running it if it isn't cached for this version yet.
+    pub(crate) fn analysis(
+        &self,
+        cancellation: &CancellationToken,
+    ) -> Result<Arc<DocumentAnalysis>, Cancelled> {
+        self.try_cached("document_analysis", |entry| {
+            let mut analysis = analyze_entry(entry, cancellation)?;
+            analysis.add_scss(&entry.scss_constructs());
+            Ok(Arc::new(analysis))
+        })
+    }
+}
+
+fn analyze_entry(
+    entry: &StoreEntry,
+    cancellation: &CancellationToken,
+) -> Result<DocumentAnalysis, Cancelled> {
+    #[cfg(feature = "parallel")]
+    if entry.document.text.len() > crate::PARALLEL_THRESHOLD {
+        return analyze_parallel(&entry.send_root(), cancellation);
+    }
+
+    analyze(&entry.css_tree().syntax(), cancellation)
+}
+
+fn analyze(
+    node: &SyntaxNode<CssLanguage>,
+    cancellation: &CancellationToken,
+) -> Result<DocumentAnalysis, Cancelled> {
+    #[cfg(feature = "colors")]
+    let mut colors = ColorVisitor::default();
+    let mut symbols = SymbolVisitor::default();
+
+    let mut visitors: Vec<&mut dyn Visitor> = vec![&mut symbols];
+    #[cfg(feature = "colors")]
+    visitors.push(&mut colors);
+    walk(node, &mut visitors, cancellation)?;
+
+    Ok(DocumentAnalysis {
+        #[cfg(feature = "colors")]
+        colors: colors.colors,
+        #[cfg(feature = "colors")]
+        variables: colors.variables,
+        symbols: symbols.symbols,
+    })
+}
+
+/// Analyze the top-level rules of a document on multiple threads, merging their results in source order.
+#[cfg(feature = "parallel")]
+fn analyze_parallel(
+    root: &SendNode,
+    cancellation: &CancellationToken,
+) -> Result<DocumentAnalysis, Cancelled> {
+    let chunks = par_rule_chunks(root, |rules| {
+        let mut analysis = DocumentAnalysis::default();
+        for rule in rules {
+            analysis.extend(analyze(&rule, cancellation)?);
+        }
+        Ok(analysis)
+    })?;
+
+    let mut analysis = DocumentAnalysis::default();
+    for chunk in chunks {
+        analysis.extend(chunk);
+    }
+    Ok(analysis)
+}
+
+/// Run `analyze` on the top-level rules of a document split in chunks, one per thread, returning the results of the
+/// chunks in source order.
+#[cfg(feature = "parallel")]
+pub(crate) fn par_rule_chunks<T: Send>(
+    root: &SendNode,
+    analyze: impl Fn(Vec<SyntaxNode<CssLanguage>>) -> Result<T, Cancelled> + Sync,
+) -> Result<Vec<T>, Cancelled> {
+    use rayon::prelude::*;
+
+    // Syntax nodes can't be sent to other threads, so each thread gets its own copy of the tree to find its rules in
+    fn rules(root: &SendNode) -> impl Iterator<Item = SyntaxNode<CssLanguage>> {
+        root.clone()
+            .into_node::<CssLanguage>()
+            .and_then(CssRoot::cast)
+            .map(|root| root.rules().syntax().children())
+            .into_iter()
+            .flatten()
+    }
+
+    let rule_count = rules(root).count();
+    let chunk_size = rule_count.div_ceil(rayon::current_num_threads()).max(1);
+
+    (0..rule_count)
+        .step_by(chunk_size)
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|start| analyze(rules(root).skip(start).take(chunk_size).collect()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_css;
+
+    #[derive(Default)]
+    struct CountVisitor {
+        kinds: Option<&'static [CssSyntaxKind]>,
+        count: usize,
+    }
+
+    impl Visitor for CountVisitor {
+        fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+            self.kinds
+        }
+
+        fn visit(&mut self, _node: &SyntaxNode<CssLanguage>) {
+            self.count += 1;
+        }
+    }
+
+    #[test]
+    fn test_walk() {
+        let parse = parse_css("a { color: red; } b { color: blue; }");
+
+        let mut every = CountVisitor::default();
+        let mut rules = CountVisitor {
+            kinds: Some(&[CssSyntaxKind::CSS_QUALIFIED_RULE]),
+            ..Default::default()
+        };
+        walk(
+            &parse.syntax(),
+            &mut [&mut every as &mut dyn Visitor, &mut rules],
+            &CancellationToken::default(),
+        )
+        .unwrap();
+
+        assert_eq!(every.count, parse.syntax().descendants().count());
+        assert_eq!(rules.count, 2);
+    }
+
+    #[cfg(all(feature = "parallel", feature = "colors"))]
+    #[test]
+    fn test_analyze_parallel() {
+        let parse = parse_css(
+            &":root { --primary: red; }\n.a { color: var(--primary); background: #fff; }\n"
+                .repeat(100),
+        );
+        let root = parse.syntax().as_send().unwrap();
+
+        let sequential = analyze(&parse.syntax(), &CancellationToken::default()).unwrap();
+        let parallel = analyze_parallel(&root, &CancellationToken::default()).unwrap();
+
+        assert_eq!(parallel.colors, sequential.colors);
+        assert_eq!(parallel.symbols, sequential.symbols);
+        assert_eq!(parallel.colors.len(), 200);
+    }
+
+    #[test]
+    fn test_walk_cancelled() {
+        let parse = parse_css("a { color: red; }");
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+
+        let mut every = CountVisitor::default();
+        assert_eq!(
+            walk(
+                &parse.syntax(),
+                &mut [&mut every as &mut dyn Visitor],
+                &cancellation
+            ),
+            Err(Cancelled)
+        );
+        assert_eq!(every.count, 0);
+    }
+}
@@ -1,36 +1,159 @@
+use std::str::FromStr;
+
 use lsp_types::{TextDocumentItem, Uri};
-use serde::{Deserialize, Serialize};
+
+use crate::{
+    converters::PositionEncoding, error::Error, parser::CssDialect, service::LanguageService,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_wasm_bindgen;
 use wasm_bindgen::prelude::*;
 
-/// Convert a JS object to a TextDocumentItem.
-pub fn create_text_document(js_value: JsValue) -> TextDocumentItem {
-    let js_text_document: JSTextDocument = serde_wasm_bindgen::from_value(js_value).unwrap();
+#[wasm_bindgen(typescript_custom_section)]
+const TS_APPEND_CONTENT: &'static str = r#"
+/**
+ * A document passed to the bindings: a `TextDocument` of `vscode-languageserver-textdocument`, a
+ * plain object with the fields of an LSP `TextDocumentItem`, or a `[uri, text]` pair. The language
+ * is detected from the extension of the URI if it isn't given, and the version defaults to 0.
+ */
+export type TextDocumentSource =
+	| import("vscode-languageserver-textdocument").TextDocument
+	| { uri: string; languageId?: string; version?: number; text: string }
+	| [uri: string, text: string];
+"#;
+
+/// Convert a JS text document to a TextDocumentItem, see `TextDocumentSource` for the accepted shapes.
+pub fn create_text_document(js_value: JsValue) -> Result<TextDocumentItem, Error> {
+    text_document(js_value, "document")
+}
+
+/// Convert a JS array of text documents to TextDocumentItems.
+pub fn create_text_documents(js_value: JsValue) -> Result<Vec<TextDocumentItem>, Error> {
+    let js_text_documents: Vec<PreservedValue> = from_value(js_value, "documents")?;
+
+    js_text_documents
+        .into_iter()
+        .enumerate()
+        .map(|(index, js_text_document)| {
+            text_document(js_text_document.0, &format!("documents[{index}]"))
+        })
+        .collect()
+}
+
+fn text_document(js_value: JsValue, argument: &str) -> Result<TextDocumentItem, Error> {
+    if let Ok((uri, text)) = serde_wasm_bindgen::from_value::<(String, String)>(js_value.clone()) {
+        let uri = Uri::from_str(&uri).map_err(|error| {
+            Error::Serialization(format!("`{argument}`: invalid URI `{uri}`: {error}"))
+        })?;
+        return Ok(TextDocumentItem {
+            language_id: CssDialect::from_uri(&uri).language_id().to_string(),
+            uri,
+            version: 0,
+            text,
+        });
+    }
+
+    let js_text_document: JSTextDocument = from_value(js_value, argument)?;
+    let missing =
+        |field: &str| Error::Serialization(format!("`{argument}`: missing field `{field}`"));
+    let uri = js_text_document.uri.ok_or_else(|| missing("uri"))?;
+    let text = js_text_document
+        .text
+        .or(js_text_document.content)
+        .ok_or_else(|| missing("text"))?;
+
+    Ok(TextDocumentItem {
+        language_id: js_text_document
+            .language_id
+            .unwrap_or_else(|| CssDialect::from_uri(&uri).language_id().to_string()),
+        uri,
+        version: js_text_document.version.unwrap_or_default(),
+        text,
+    })
+}
+
+/// Install a panic hook when the module is instantiated, to log the message of panics to the console. A panic still
+/// leaves the WASM instance unusable, the bindings return errors for malformed arguments and invalid requests instead.
+#[cfg(feature = "panic-hook")]
+#[wasm_bindgen(start)]
+pub fn start() {
+    console_error_panic_hook::set_once();
+}
+
+/// Convert an argument of a binding from JS, naming the argument in the error if it is malformed so that hosts can
+/// tell which one they got wrong.
+pub fn from_value<T: DeserializeOwned>(js_value: JsValue, argument: &str) -> Result<T, Error> {
+    serde_wasm_bindgen::from_value(js_value)
+        .map_err(|error| Error::Serialization(format!("`{argument}`: {error}")))
+}
+
+/// Convert a result of a binding to JS, as the JSON of the LSP: maps become plain objects instead of `Map`s, e.g. the
+/// `data` of inlay hints, so that results can be passed straight through to a client.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, Error> {
+    Ok(value.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+}
+
+/// Convert a result of a binding to UTF-8 encoded JSON, returned to JS as a `Uint8Array` with its own buffer, which
+/// can be transferred between workers without a copy.
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    serde_json::to_vec(value).map_err(|error| Error::Serialization(error.to_string()))
+}
 
-    TextDocumentItem {
-        uri: js_text_document.uri,
-        language_id: js_text_document.language_id,
-        version: js_text_document.version,
-        text: js_text_document.content,
+/// Run a request needing a LanguageService, e.g. for its CSS data or rules, on a document. The document is only in the
+/// store of the service shared by the bindings for the duration of the request.
+pub(crate) fn with_document<T>(
+    document: TextDocumentItem,
+    encoding: PositionEncoding,
+    request: impl FnOnce(&LanguageService, &Uri) -> T,
+) -> T {
+    thread_local! {
+        static LANGUAGE_SERVICE: LanguageService = LanguageService::default();
     }
+
+    LANGUAGE_SERVICE.with(|language_service| {
+        let uri = document.uri.clone();
+        language_service.open_document_with_encoding(document, Some(encoding));
+        let result = request(language_service, &uri);
+        language_service.close_document(&uri);
+        result
+    })
+}
+
+/// Convert the position encoding passed to a binding, a `PositionEncodingKind` of the LSP such as `utf-8`, UTF-16 if
+/// none was passed.
+pub fn position_encoding(encoding: Option<String>) -> Result<PositionEncoding, Error> {
+    let Some(encoding) = encoding else {
+        return Ok(PositionEncoding::default());
+    };
+
+    PositionEncoding::from_kind(&encoding.clone().into())
+        .ok_or_else(|| Error::Serialization(format!("unknown position encoding `{encoding}`")))
 }
 
-/// VS Code's `vscode-languageserver-textdocument` has a slightly different representation of a text document than lsp-types, as such for the serialization
-/// and deserialization of text documents we need to use a custom struct in between. Bit annoying but it works.
-#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+/// The fields of the text documents accepted by the bindings, all optional to report the missing ones by name. The
+/// content of a `TextDocument` of `vscode-languageserver-textdocument` is in its private `_content` field, while plain
+/// objects have a `text` field as in lsp-types.
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct JSTextDocument {
     /// The text document's URI.
-    pub uri: Uri,
+    uri: Option<Uri>,
 
     /// The text document's language identifier.
-    pub language_id: String,
+    language_id: Option<String>,
 
     /// The version number of this document (it will strictly increase after each
     /// change, including undo/redo).
-    pub version: i32,
+    version: Option<i32>,
+
+    /// The content of a plain text document object.
+    text: Option<String>,
 
-    /// The content of the opened text document.
+    /// The content of a `TextDocument` of `vscode-languageserver-textdocument`.
     #[serde(rename = "_content")]
-    pub content: String,
+    content: Option<String>,
 }
+
+/// A JS value kept as is, to convert the elements of an array one by one.
+#[derive(Deserialize)]
+struct PreservedValue(#[serde(with = "serde_wasm_bindgen::preserve")] JsValue);
@@ -0,0 +1,1117 @@
+//! An index of the symbols declared and referenced across every document of the store, used by the features that
+//! need to look beyond the current document (workspace symbols, cross-file references, etc.)
+
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use biome_css_syntax::{
+    CssClassSelector, CssContainerAtRule, CssCounterStyleAtRule, CssGenericProperty, CssIdSelector,
+    CssKeyframesAtRule, CssLanguage, CssSyntaxKind,
+};
+use biome_rowan::{AstNode, SyntaxNode, TextRange, TextSize};
+use lsp_types::{TextDocumentItem, Uri};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cancellation::{CancellationToken, Cancelled},
+    container_queries::{declared_containers, queried_container},
+    converters::{
+        line_index::{LineEnding, LineIndex},
+        WideChar,
+    },
+    counter_styles::referenced_counter_styles,
+    custom_properties::var_reference,
+    file_system::{join, AsyncFileSystemProvider, FileSystemProvider, FileType},
+    keyframes::referenced_keyframes,
+    layers::layer_names,
+    parser::CssDialect,
+    progress::{ItemProgress, ProgressReporter},
+    scss::{ScssConstruct, ScssConstructKind},
+    store::{DocumentStore, StoreEntry},
+    visitor::Visitor,
+};
+
+/// The kind of a symbol tracked by the WorkspaceIndex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SymbolKind {
+    /// A custom property, e.g. `--primary`.
+    CustomProperty,
+    /// A class selector, e.g. `.button`.
+    Class,
+    /// An id selector, e.g. `#header`.
+    Id,
+    /// A `@keyframes` rule, referenced by the `animation-name` and `animation` properties, e.g. `animation: spin 1s`.
+    Keyframes,
+    /// A cascade layer, declared by a `@layer` statement, e.g. `@layer base, components;`, and referenced by the `@layer`
+    /// blocks and the imports assigning rules to it, e.g. `@import "reset.css" layer(base);`.
+    Layer,
+    /// A named container, declared by the `container-name` or `container` property of the elements it matches, e.g.
+    /// `container-name: sidebar`, and referenced by the `@container` rules querying it.
+    Container,
+    /// A counter style, declared by a `@counter-style` rule and referenced by the `list-style-type` and `list-style`
+    /// properties, e.g. `list-style: thumbs inside`, and by the `fallback` and `system: extends` descriptors.
+    CounterStyle,
+    /// An SCSS or LESS variable, e.g. `$primary` or `@primary`.
+    Variable,
+    /// An SCSS mixin, declared with `@mixin` and used with `@include`, or a LESS mixin, e.g. `.button()`.
+    Mixin,
+    /// An SCSS function, declared with `@function`.
+    Function,
+    /// An SCSS placeholder selector, e.g. `%card`, used with `@extend`.
+    Placeholder,
+}
+
+impl SymbolKind {
+    /// Get the name of a symbol of this kind as it is usually displayed, e.g. `.button` for a class named `button`.
+    pub fn display_name(self, name: &str) -> String {
+        match self {
+            SymbolKind::CustomProperty | SymbolKind::Variable => name.to_string(),
+            SymbolKind::Class => format!(".{name}"),
+            SymbolKind::Id => format!("#{name}"),
+            SymbolKind::Keyframes => format!("@keyframes {name}"),
+            SymbolKind::Layer => format!("@layer {name}"),
+            SymbolKind::Container => format!("@container {name}"),
+            SymbolKind::CounterStyle => format!("@counter-style {name}"),
+            // LESS mixins are named by their selector, e.g. `.button`
+            SymbolKind::Mixin if name.starts_with(['.', '#']) => format!("{name}()"),
+            SymbolKind::Mixin => format!("@mixin {name}"),
+            SymbolKind::Function => format!("@function {name}"),
+            SymbolKind::Placeholder => format!("%{name}"),
+        }
+    }
+}
+
+/// An occurrence of a symbol in a document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexedSymbol {
+    /// The name of the symbol, without the syntax around it (e.g. `button` for `.button`, but `--primary` for
+    /// custom properties).
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The range of the name of the symbol.
+    pub range: TextRange,
+    /// Whether this occurrence declares the symbol, or only references it (e.g. `var(--primary)`).
+    pub is_declaration: bool,
+}
+
+/// The symbols of a document, as indexed from a given StoreEntry or loaded from a persisted index.
+pub struct IndexedDocument {
+    pub uri: Uri,
+    pub symbols: Vec<IndexedSymbol>,
+    content_hash: u64,
+    /// The modification time of the file the document was read from, for documents indexed from disk.
+    mtime: Option<u64>,
+    source: IndexedSource,
+}
+
+enum IndexedSource {
+    Entry(Arc<StoreEntry>),
+    // Persisted documents don't keep their text, only what is needed to convert the ranges of their symbols.
+    Persisted(LineIndex),
+}
+
+impl IndexedDocument {
+    fn new(entry: Arc<StoreEntry>) -> Self {
+        // Symbols are collected along with the other analyses of the document
+        let symbols = entry
+            .analysis(&CancellationToken::default())
+            .map(|analysis| analysis.symbols.clone())
+            .unwrap_or_default();
+
+        Self {
+            uri: entry.document.uri.clone(),
+            symbols,
+            content_hash: entry.content_hash(),
+            mtime: None,
+            source: IndexedSource::Entry(entry),
+        }
+    }
+
+    /// Get the StoreEntry the document was indexed from, or `None` if it was loaded from a persisted index.
+    pub fn entry(&self) -> Option<&Arc<StoreEntry>> {
+        match &self.source {
+            IndexedSource::Entry(entry) => Some(entry),
+            IndexedSource::Persisted(_) => None,
+        }
+    }
+
+    pub(crate) fn line_index(&self) -> &LineIndex {
+        match &self.source {
+            IndexedSource::Entry(entry) => entry.line_index(),
+            IndexedSource::Persisted(line_index) => line_index,
+        }
+    }
+}
+
+/// An index of the symbols of every document in a DocumentStore, plus the stylesheets discovered on disk through a
+/// FileSystemProvider.
+///
+/// The index is updated incrementally: syncing it with the store only reindexes the documents whose entry changed
+/// since the last sync, and drops the documents that were removed from the store. Documents open in the store take
+/// precedence over their version on disk.
+pub struct WorkspaceIndex {
+    documents: RwLock<FxHashMap<Uri, Arc<IndexedDocument>>>,
+    disk_documents: RwLock<FxHashMap<Uri, Arc<IndexedDocument>>>,
+}
+
+/// What to do with an entry found while walking a folder.
+enum FolderEntry {
+    Folder(Uri),
+    Stylesheet(Uri),
+}
+
+impl FolderEntry {
+    fn new(folder: &Uri, name: &str, file_type: FileType) -> Option<Self> {
+        // Hidden folders and dependencies are not part of the workspace
+        if name.starts_with('.') || name == "node_modules" {
+            return None;
+        }
+
+        let uri = join(folder, name)?;
+        match file_type {
+            FileType::Directory => Some(FolderEntry::Folder(uri)),
+            FileType::File | FileType::SymbolicLink => {
+                let extension = name.rsplit_once('.')?.1.to_ascii_lowercase();
+                matches!(extension.as_str(), "css" | "scss" | "less")
+                    .then_some(FolderEntry::Stylesheet(uri))
+            }
+            FileType::Unknown => None,
+        }
+    }
+}
+
+impl WorkspaceIndex {
+    pub fn new() -> Self {
+        Self {
+            documents: RwLock::new(FxHashMap::default()),
+            disk_documents: RwLock::new(FxHashMap::default()),
+        }
+    }
+
+    fn documents_read(&self) -> RwLockReadGuard<'_, FxHashMap<Uri, Arc<IndexedDocument>>> {
+        self.documents
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn documents_mut(&self) -> RwLockWriteGuard<'_, FxHashMap<Uri, Arc<IndexedDocument>>> {
+        self.documents
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Bring the index up to date with the given store.
+    pub fn sync(&self, store: &DocumentStore) {
+        let entries = store.entries();
+
+        // Documents are indexed without holding the lock, as it requires parsing them.
+        let stale = {
+            let documents = self.documents_read();
+            entries
+                .iter()
+                .filter(|entry| {
+                    documents
+                        .get(&entry.document.uri)
+                        .and_then(|indexed| indexed.entry())
+                        .is_none_or(|indexed| !Arc::ptr_eq(indexed, entry))
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        let reindexed = stale
+            .into_iter()
+            .map(|entry| Arc::new(IndexedDocument::new(entry)))
+            .collect::<Vec<_>>();
+
+        let uris = entries
+            .iter()
+            .map(|entry| &entry.document.uri)
+            .collect::<FxHashSet<_>>();
+
+        let mut documents = self.documents_mut();
+        documents.retain(|uri, _| uris.contains(uri));
+        for indexed in reindexed {
+            documents.insert(indexed.uri.clone(), indexed);
+        }
+    }
+
+    fn disk_documents_mut(&self) -> RwLockWriteGuard<'_, FxHashMap<Uri, Arc<IndexedDocument>>> {
+        self.disk_documents
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn disk_document(&self, uri: &Uri) -> Option<Arc<IndexedDocument>> {
+        self.disk_documents
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(uri)
+            .cloned()
+    }
+
+    /// Whether the file at `uri` is indexed and wasn't modified since.
+    fn is_up_to_date(&self, uri: &Uri, mtime: Option<u64>) -> bool {
+        mtime.is_some()
+            && self
+                .disk_document(uri)
+                .is_some_and(|indexed| indexed.mtime == mtime)
+    }
+
+    pub(crate) fn insert_disk_document(&self, uri: Uri, text: String, mtime: Option<u64>) {
+        let document = TextDocumentItem {
+            language_id: CssDialect::from_uri(&uri).language_id().to_string(),
+            uri,
+            version: 0,
+            text,
+        };
+        self.insert_disk_entry(Arc::new(StoreEntry::new(document)), mtime);
+    }
+
+    /// Index the content on disk of a document, e.g. the content of an open document that was just saved.
+    pub(crate) fn insert_disk_entry(&self, entry: Arc<StoreEntry>, mtime: Option<u64>) {
+        // A file that was only touched keeps its symbols, without being parsed again
+        let indexed = match self.disk_document(&entry.document.uri) {
+            Some(indexed) if indexed.content_hash == entry.content_hash() => IndexedDocument {
+                uri: entry.document.uri.clone(),
+                symbols: indexed.symbols.clone(),
+                content_hash: indexed.content_hash,
+                mtime,
+                source: IndexedSource::Entry(entry),
+            },
+            _ => IndexedDocument {
+                mtime,
+                ..IndexedDocument::new(entry)
+            },
+        };
+
+        self.disk_documents_mut()
+            .insert(indexed.uri.clone(), Arc::new(indexed));
+    }
+
+    pub(crate) fn has_disk_document(&self, uri: &Uri) -> bool {
+        self.disk_document(uri).is_some()
+    }
+
+    /// Remove a document that was deleted from disk from the index.
+    pub(crate) fn remove_disk_document(&self, uri: &Uri) {
+        self.disk_documents_mut().remove(uri);
+    }
+
+    /// Remove the documents of `folder` that weren't found while indexing it, as their file was deleted.
+    fn retain_found(&self, folder: &Uri, found: &FxHashSet<Uri>) {
+        let prefix = format!("{}/", folder.as_str().trim_end_matches('/'));
+        self.disk_documents_mut()
+            .retain(|uri, _| !uri.as_str().starts_with(&prefix) || found.contains(uri));
+    }
+
+    /// Index every stylesheet (`.css`, `.scss` and `.less` files) in the given folder and its subfolders, skipping
+    /// hidden folders and `node_modules`. Files that can't be read are ignored.
+    ///
+    /// Files that were already indexed, e.g. loaded from a persisted index with `load`, are only read again if their
+    /// modification time changed, and only parsed again if their content changed. Indexed files of the folder that
+    /// don't exist anymore are removed.
+    ///
+    /// Indexing stops with `Err(Cancelled)` once `cancellation` is cancelled, keeping the files indexed until then.
+    pub fn index_folder(
+        &self,
+        file_system: &dyn FileSystemProvider,
+        folder: &Uri,
+        cancellation: &CancellationToken,
+    ) -> Result<(), Cancelled> {
+        self.index_folder_with_progress(file_system, folder, cancellation, &mut ())
+    }
+
+    /// Same as `index_folder`, reporting the progress through the stylesheets of the folder to `progress` once they
+    /// were all found.
+    pub fn index_folder_with_progress(
+        &self,
+        file_system: &dyn FileSystemProvider,
+        folder: &Uri,
+        cancellation: &CancellationToken,
+        progress: &mut dyn ProgressReporter,
+    ) -> Result<(), Cancelled> {
+        progress.begin("Indexing stylesheets");
+        let result = self.index_stylesheets(file_system, folder, cancellation, progress);
+        progress.end();
+
+        result
+    }
+
+    fn index_stylesheets(
+        &self,
+        file_system: &dyn FileSystemProvider,
+        folder: &Uri,
+        cancellation: &CancellationToken,
+        progress: &mut dyn ProgressReporter,
+    ) -> Result<(), Cancelled> {
+        let mut folders = vec![folder.clone()];
+        let mut stylesheets = Vec::new();
+
+        while let Some(folder) = folders.pop() {
+            cancellation.check()?;
+
+            let Ok(entries) = FileSystemProvider::read_directory(file_system, &folder) else {
+                continue;
+            };
+
+            for (name, file_type) in entries {
+                match FolderEntry::new(&folder, &name, file_type) {
+                    Some(FolderEntry::Folder(uri)) => folders.push(uri),
+                    Some(FolderEntry::Stylesheet(uri)) => stylesheets.push(uri),
+                    None => {}
+                }
+            }
+        }
+
+        let mut progress = ItemProgress::new(progress, "files", stylesheets.len());
+        let mut found = FxHashSet::default();
+        for uri in stylesheets {
+            cancellation.check()?;
+
+            let mtime = FileSystemProvider::stat(file_system, &uri)
+                .ok()
+                .map(|stat| stat.mtime);
+            if self.is_up_to_date(&uri, mtime) {
+                found.insert(uri);
+            } else if let Ok(text) = FileSystemProvider::read_file(file_system, &uri) {
+                self.insert_disk_document(uri.clone(), text, mtime);
+                found.insert(uri);
+            }
+            progress.advance();
+        }
+
+        self.retain_found(folder, &found);
+        Ok(())
+    }
+
+    /// Same as `index_folder`, for file systems that can only be accessed asynchronously.
+    pub async fn index_folder_async(
+        &self,
+        file_system: &(impl AsyncFileSystemProvider + ?Sized),
+        folder: &Uri,
+        cancellation: &CancellationToken,
+    ) -> Result<(), Cancelled> {
+        let mut folders = vec![folder.clone()];
+        let mut found = FxHashSet::default();
+
+        while let Some(folder) = folders.pop() {
+            cancellation.check()?;
+
+            let Ok(entries) = file_system.read_directory(&folder).await else {
+                continue;
+            };
+
+            for (name, file_type) in entries {
+                match FolderEntry::new(&folder, &name, file_type) {
+                    Some(FolderEntry::Folder(uri)) => folders.push(uri),
+                    Some(FolderEntry::Stylesheet(uri)) => {
+                        cancellation.check()?;
+
+                        let mtime = file_system.stat(&uri).await.ok().map(|stat| stat.mtime);
+                        if self.is_up_to_date(&uri, mtime) {
+                            found.insert(uri);
+                        } else if let Ok(text) = file_system.read_file(&uri).await {
+                            self.insert_disk_document(uri.clone(), text, mtime);
+                            found.insert(uri);
+                        }
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        self.retain_found(folder, &found);
+        Ok(())
+    }
+
+    /// Serialize the documents discovered on disk, to be restored with `load` the next time the server starts instead of
+    /// indexing the whole workspace again. Documents open in the store aren't included, as their content isn't saved.
+    pub fn save(&self) -> Vec<u8> {
+        let documents = self
+            .disk_documents
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .map(|indexed| PersistedDocument::new(indexed))
+            .collect();
+
+        serde_json::to_vec(&PersistedIndex {
+            format: PERSISTED_INDEX_FORMAT,
+            documents,
+        })
+        .expect("the persisted index is always serializable")
+    }
+
+    /// Restore the documents discovered on disk from data produced by `save`, replacing the indexed documents with the
+    /// same URIs. Restored documents are checked against the file system the next time their folder is indexed.
+    ///
+    /// # Returns
+    ///
+    /// * The number of restored documents, or an error if the data is invalid or was saved by an incompatible version.
+    pub fn load(&self, data: &[u8]) -> Result<usize, serde_json::Error> {
+        let persisted: PersistedIndex = serde_json::from_slice(data)?;
+        if persisted.format != PERSISTED_INDEX_FORMAT {
+            return Err(serde::de::Error::custom(format!(
+                "unsupported index format {}",
+                persisted.format
+            )));
+        }
+
+        let documents = persisted
+            .documents
+            .into_iter()
+            .filter_map(PersistedDocument::into_indexed)
+            .collect::<Vec<_>>();
+        let count = documents.len();
+
+        let mut disk_documents = self.disk_documents_mut();
+        for indexed in documents {
+            disk_documents.insert(indexed.uri.clone(), Arc::new(indexed));
+        }
+
+        Ok(count)
+    }
+
+    /// Drop the results cached for the documents discovered on disk, along with those of the store, e.g. the diagnostics
+    /// reported for the whole workspace.
+    pub(crate) fn clear_caches(&self) {
+        for indexed in self
+            .disk_documents
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+        {
+            if let Some(entry) = indexed.entry() {
+                entry.clear_cache();
+            }
+        }
+    }
+
+    /// Remove the documents discovered on disk from the index, e.g. when a workspace folder is removed.
+    pub fn clear_disk_documents(&self) {
+        self.disk_documents_mut().clear();
+    }
+
+    /// Get every indexed document.
+    pub fn documents(&self) -> Vec<Arc<IndexedDocument>> {
+        let documents = self.documents_read();
+        let disk_documents = self
+            .disk_documents
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        documents
+            .values()
+            .chain(
+                disk_documents
+                    .iter()
+                    .filter(|(uri, _)| !documents.contains_key(*uri))
+                    .map(|(_, document)| document),
+            )
+            .cloned()
+            .collect()
+    }
+
+    /// Get the indexed document with the given URI.
+    pub fn document(&self, uri: &Uri) -> Option<Arc<IndexedDocument>> {
+        self.documents_read().get(uri).cloned().or_else(|| {
+            self.disk_documents
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get(uri)
+                .cloned()
+        })
+    }
+
+    /// Find every occurrence of the symbol with the given kind and name, across all documents.
+    pub fn find(&self, kind: SymbolKind, name: &str) -> Vec<(Arc<IndexedDocument>, IndexedSymbol)> {
+        self.documents()
+            .iter()
+            .flat_map(|document| {
+                document
+                    .symbols
+                    .iter()
+                    .filter(|symbol| symbol.kind == kind && symbol.name == name)
+                    .map(|symbol| (document.clone(), symbol.clone()))
+            })
+            .collect()
+    }
+}
+
+impl Default for WorkspaceIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Bumped whenever the persisted data changes, older data is then rejected by `load`.
+const PERSISTED_INDEX_FORMAT: u32 = 7;
+
+#[derive(Serialize, Deserialize)]
+struct PersistedIndex {
+    format: u32,
+    documents: Vec<PersistedDocument>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PersistedDocument {
+    uri: String,
+    mtime: Option<u64>,
+    content_hash: u64,
+    newlines: Vec<u32>,
+    text_len: u32,
+    /// The start and end of the non-ASCII characters of each line that has some.
+    wide_chars: Vec<(u32, Vec<(u32, u32)>)>,
+    /// The line break of each line not ending with `\n`.
+    line_endings: Vec<(u32, LineEnding)>,
+    symbols: Vec<PersistedSymbol>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PersistedSymbol {
+    name: String,
+    kind: SymbolKind,
+    start: u32,
+    end: u32,
+    is_declaration: bool,
+}
+
+impl PersistedDocument {
+    fn new(indexed: &IndexedDocument) -> Self {
+        let line_index = indexed.line_index();
+
+        Self {
+            uri: indexed.uri.as_str().to_string(),
+            mtime: indexed.mtime,
+            content_hash: indexed.content_hash,
+            newlines: line_index.newlines.iter().copied().map(u32::from).collect(),
+            text_len: line_index.text_len.into(),
+            wide_chars: line_index
+                .line_wide_chars
+                .iter()
+                .map(|(line, chars)| {
+                    let chars = chars
+                        .iter()
+                        .map(|char| (u32::from(char.start), u32::from(char.end)))
+                        .collect();
+                    (*line, chars)
+                })
+                .collect(),
+            line_endings: line_index
+                .line_endings
+                .iter()
+                .map(|(line, line_ending)| (*line, *line_ending))
+                .collect(),
+            symbols: indexed
+                .symbols
+                .iter()
+                .map(|symbol| PersistedSymbol {
+                    name: symbol.name.clone(),
+                    kind: symbol.kind,
+                    start: symbol.range.start().into(),
+                    end: symbol.range.end().into(),
+                    is_declaration: symbol.is_declaration,
+                })
+                .collect(),
+        }
+    }
+
+    fn into_indexed(self) -> Option<IndexedDocument> {
+        let line_index = LineIndex {
+            newlines: self.newlines.into_iter().map(TextSize::from).collect(),
+            text_len: self.text_len.into(),
+            line_wide_chars: self
+                .wide_chars
+                .into_iter()
+                .map(|(line, chars)| {
+                    let chars = chars
+                        .into_iter()
+                        .map(|(start, end)| WideChar {
+                            start: start.into(),
+                            end: end.into(),
+                        })
+                        .collect();
+                    (line, chars)
+                })
+                .collect(),
+            line_endings: self.line_endings.into_iter().collect(),
+        };
+
+        Some(IndexedDocument {
+            uri: self.uri.parse().ok()?,
+            symbols: self
+                .symbols
+                .into_iter()
+                .map(|symbol| IndexedSymbol {
+                    name: symbol.name,
+                    kind: symbol.kind,
+                    range: TextRange::new(symbol.start.into(), symbol.end.into()),
+                    is_declaration: symbol.is_declaration,
+                })
+                .collect(),
+            content_hash: self.content_hash,
+            mtime: self.mtime,
+            source: IndexedSource::Persisted(line_index),
+        })
+    }
+}
+
+/// Get the symbol declared or referenced by an SCSS construct, if any. SCSS constructs are masked before parsing, so
+/// these symbols are found by `scss::scan_scss` (or `less::scan_less`) rather than by the SymbolVisitor.
+pub(crate) fn scss_symbol(construct: &ScssConstruct) -> Option<IndexedSymbol> {
+    let (kind, is_declaration) = match construct.kind {
+        ScssConstructKind::Variable => (SymbolKind::Variable, true),
+        ScssConstructKind::VariableReference => (SymbolKind::Variable, false),
+        ScssConstructKind::Mixin => (SymbolKind::Mixin, true),
+        ScssConstructKind::Include => (SymbolKind::Mixin, false),
+        ScssConstructKind::Function => (SymbolKind::Function, true),
+        ScssConstructKind::Placeholder => (SymbolKind::Placeholder, true),
+        ScssConstructKind::Extend => {
+            let name = construct.name.strip_prefix('%')?;
+            return Some(IndexedSymbol {
+                name: name.to_string(),
+                kind: SymbolKind::Placeholder,
+                range: TextRange::new(
+                    construct.name_range.start() + TextSize::from(1),
+                    construct.name_range.end(),
+                ),
+                is_declaration: false,
+            });
+        }
+        ScssConstructKind::Directive | ScssConstructKind::Interpolation => return None,
+    };
+
+    Some(IndexedSymbol {
+        name: construct.name.clone(),
+        kind,
+        range: construct.name_range,
+        is_declaration,
+    })
+}
+
+/// Collects the symbols declared or referenced in a tree, in source order.
+#[derive(Default)]
+pub(crate) struct SymbolVisitor {
+    pub(crate) symbols: Vec<IndexedSymbol>,
+}
+
+impl SymbolVisitor {
+    fn push(&mut self, name: String, kind: SymbolKind, range: TextRange, is_declaration: bool) {
+        self.symbols.push(IndexedSymbol {
+            name,
+            kind,
+            range,
+            is_declaration,
+        })
+    }
+}
+
+impl Visitor for SymbolVisitor {
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[
+            CssSyntaxKind::CSS_GENERIC_PROPERTY,
+            CssSyntaxKind::CSS_FUNCTION,
+            CssSyntaxKind::CSS_CLASS_SELECTOR,
+            CssSyntaxKind::CSS_ID_SELECTOR,
+            CssSyntaxKind::CSS_KEYFRAMES_AT_RULE,
+            CssSyntaxKind::CSS_LAYER_AT_RULE,
+            CssSyntaxKind::CSS_IMPORT_AT_RULE,
+            CssSyntaxKind::CSS_CONTAINER_AT_RULE,
+            CssSyntaxKind::CSS_COUNTER_STYLE_AT_RULE,
+        ])
+    }
+
+    fn visit(&mut self, node: &SyntaxNode<CssLanguage>) {
+        match node.kind() {
+            CssSyntaxKind::CSS_GENERIC_PROPERTY => {
+                if let Some(name) = node
+                    .first_child()
+                    .filter(|name| name.kind() == CssSyntaxKind::CSS_IDENTIFIER)
+                    .filter(|name| name.text_trimmed().to_string().starts_with("--"))
+                {
+                    // The custom properties of container style queries are queried, e.g. `style(--theme: dark)`
+                    let in_style_query = node.grand_parent().is_some_and(|query| {
+                        query.kind() == CssSyntaxKind::CSS_CONTAINER_STYLE_QUERY_IN_PARENS
+                    });
+                    self.push(
+                        name.text_trimmed().to_string(),
+                        SymbolKind::CustomProperty,
+                        name.text_trimmed_range(),
+                        !in_style_query,
+                    );
+                }
+                if let Some(property) = CssGenericProperty::cast_ref(node) {
+                    for (name, range) in declared_containers(&property) {
+                        self.push(name, SymbolKind::Container, range, true);
+                    }
+                    for (name, range) in referenced_counter_styles(&property) {
+                        self.push(name, SymbolKind::CounterStyle, range, false);
+                    }
+                    for (name, range) in referenced_keyframes(&property) {
+                        self.push(name, SymbolKind::Keyframes, range, false);
+                    }
+                }
+            }
+            CssSyntaxKind::CSS_FUNCTION => {
+                if let Some(reference) = var_reference(node) {
+                    self.push(
+                        reference.name,
+                        SymbolKind::CustomProperty,
+                        reference.name_range,
+                        false,
+                    );
+                }
+            }
+            CssSyntaxKind::CSS_CLASS_SELECTOR => {
+                if let Some(name) =
+                    CssClassSelector::cast(node.clone()).and_then(|class| class.name().ok())
+                {
+                    self.push(
+                        name.syntax().text_trimmed().to_string(),
+                        SymbolKind::Class,
+                        name.syntax().text_trimmed_range(),
+                        true,
+                    );
+                }
+            }
+            CssSyntaxKind::CSS_ID_SELECTOR => {
+                if let Some(name) = CssIdSelector::cast(node.clone()).and_then(|id| id.name().ok())
+                {
+                    self.push(
+                        name.syntax().text_trimmed().to_string(),
+                        SymbolKind::Id,
+                        name.syntax().text_trimmed_range(),
+                        true,
+                    );
+                }
+            }
+            CssSyntaxKind::CSS_KEYFRAMES_AT_RULE => {
+                let Some(name) =
+                    CssKeyframesAtRule::cast(node.clone()).and_then(|rule| rule.name().ok())
+                else {
+                    return;
+                };
+
+                let text = name.syntax().text_trimmed().to_string();
+                self.push(
+                    text.trim_matches(['"', '\'']).to_string(),
+                    SymbolKind::Keyframes,
+                    name.syntax().text_trimmed_range(),
+                    true,
+                );
+            }
+            CssSyntaxKind::CSS_LAYER_AT_RULE | CssSyntaxKind::CSS_IMPORT_AT_RULE => {
+                // Layers are declared by `@layer` statements, blocks and imports only assign rules to them
+                for layer in layer_names(node) {
+                    self.push(
+                        layer.name,
+                        SymbolKind::Layer,
+                        layer.range,
+                        layer.is_statement,
+                    );
+                }
+            }
+            CssSyntaxKind::CSS_CONTAINER_AT_RULE => {
+                if let Some(name) =
+                    CssContainerAtRule::cast_ref(node).and_then(|rule| queried_container(&rule))
+                {
+                    self.push(
+                        name.syntax().text_trimmed().to_string(),
+                        SymbolKind::Container,
+                        name.syntax().text_trimmed_range(),
+                        false,
+                    );
+                }
+            }
+            CssSyntaxKind::CSS_COUNTER_STYLE_AT_RULE => {
+                if let Some(name) =
+                    CssCounterStyleAtRule::cast_ref(node).and_then(|rule| rule.name().ok())
+                {
+                    self.push(
+                        name.syntax().text_trimmed().to_string(),
+                        SymbolKind::CounterStyle,
+                        name.syntax().text_trimmed_range(),
+                        true,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use lsp_types::TextDocumentItem;
+
+    use super::*;
+
+    fn create_document(uri: &str, version: i32, text: &str) -> TextDocumentItem {
+        TextDocumentItem {
+            uri: Uri::from_str(uri).unwrap(),
+            language_id: "css".to_string(),
+            version,
+            text: text.to_string(),
+        }
+    }
+
+    fn names(symbols: &[IndexedSymbol]) -> Vec<(SymbolKind, &str, bool)> {
+        symbols
+            .iter()
+            .map(|symbol| (symbol.kind, symbol.name.as_str(), symbol.is_declaration))
+            .collect()
+    }
+
+    #[test]
+    fn test_index_symbols() {
+        let parse = crate::parser::parse_css(
+            ":root { --primary: red; }\n\
+             .button, #header { color: var(--primary); }\n\
+             @keyframes spin { from { opacity: 0; } }\n\
+             .spinner { animation: 1s ease-in infinite spin, fade 2s; animation-name: none; }\n\
+             @layer base, components.buttons;\n\
+             @layer components { @layer buttons { a { color: red; } } }\n\
+             @container sidebar (min-width: 400px) { a { color: red; } }\n\
+             .layout { container: sidebar main / inline-size; }\n\
+             @container card style(--theme: dark) { a { color: red; } }\n\
+             @counter-style thumbs { system: extends decimal; fallback: stars; }\n\
+             ul { list-style: thumbs inside; list-style-type: none; }",
+        );
+        let mut visitor = SymbolVisitor::default();
+        crate::visitor::walk(
+            &parse.syntax(),
+            &mut [&mut visitor as &mut dyn Visitor],
+            &CancellationToken::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            names(&visitor.symbols),
+            vec![
+                (SymbolKind::CustomProperty, "--primary", true),
+                (SymbolKind::Class, "button", true),
+                (SymbolKind::Id, "header", true),
+                (SymbolKind::CustomProperty, "--primary", false),
+                (SymbolKind::Keyframes, "spin", true),
+                (SymbolKind::Class, "spinner", true),
+                (SymbolKind::Keyframes, "spin", false),
+                (SymbolKind::Keyframes, "fade", false),
+                (SymbolKind::Layer, "base", true),
+                (SymbolKind::Layer, "components.buttons", true),
+                (SymbolKind::Layer, "components", false),
+                (SymbolKind::Layer, "components.buttons", false),
+                (SymbolKind::Container, "sidebar", false),
+                (SymbolKind::Class, "layout", true),
+                (SymbolKind::Container, "sidebar", true),
+                (SymbolKind::Container, "main", true),
+                (SymbolKind::Container, "card", false),
+                (SymbolKind::CustomProperty, "--theme", false),
+                (SymbolKind::CounterStyle, "thumbs", true),
+                (SymbolKind::CounterStyle, "decimal", false),
+                (SymbolKind::CounterStyle, "stars", false),
+                (SymbolKind::CounterStyle, "thumbs", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_workspace_index_sync() {
+        let store = DocumentStore::new();
+        let index = WorkspaceIndex::new();
+
+        store.insert(create_document("file:///a.css", 1, ".a { color: red; }"));
+        store.insert(create_document("file:///b.css", 1, ".b { color: red; }"));
+        index.sync(&store);
+
+        assert_eq!(index.documents().len(), 2);
+        assert_eq!(index.find(SymbolKind::Class, "a").len(), 1);
+
+        store.insert(create_document("file:///a.css", 2, ".c { color: red; }"));
+        store.remove(&Uri::from_str("file:///b.css").unwrap());
+        store.insert(create_document("file:///d.css", 1, ".d { color: red; }"));
+        index.sync(&store);
+
+        assert!(index.find(SymbolKind::Class, "a").is_empty());
+        assert_eq!(index.find(SymbolKind::Class, "c").len(), 1);
+        assert!(index.find(SymbolKind::Class, "b").is_empty());
+        assert_eq!(index.documents().len(), 2);
+
+        // Only documents that changed are reindexed
+        store.insert(create_document("file:///b.css", 1, ".b { color: red; }"));
+        let d = index.document(&Uri::from_str("file:///d.css").unwrap());
+        index.sync(&store);
+        assert!(Arc::ptr_eq(
+            &d.unwrap(),
+            &index
+                .document(&Uri::from_str("file:///d.css").unwrap())
+                .unwrap()
+        ));
+    }
+
+    struct MemoryFileSystem(FxHashMap<&'static str, &'static str>);
+
+    impl FileSystemProvider for MemoryFileSystem {
+        fn read_file(&self, uri: &Uri) -> std::io::Result<String> {
+            self.0
+                .get(uri.as_str())
+                .map(|text| text.to_string())
+                .ok_or_else(|| std::io::ErrorKind::NotFound.into())
+        }
+
+        fn read_directory(&self, uri: &Uri) -> std::io::Result<Vec<(String, FileType)>> {
+            let prefix = format!("{}/", uri.as_str());
+            let mut entries = self
+                .0
+                .keys()
+                .filter_map(|path| path.strip_prefix(&prefix))
+                .map(|rest| match rest.split_once('/') {
+                    Some((folder, _)) => (folder.to_string(), FileType::Directory),
+                    None => (rest.to_string(), FileType::File),
+                })
+                .collect::<Vec<_>>();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            entries.dedup();
+            Ok(entries)
+        }
+
+        // The length of a file stands in for its modification time
+        fn stat(&self, uri: &Uri) -> std::io::Result<crate::file_system::FileStat> {
+            let text = FileSystemProvider::read_file(self, uri)?;
+            Ok(crate::file_system::FileStat {
+                file_type: FileType::File,
+                size: text.len() as u64,
+                mtime: text.len() as u64,
+            })
+        }
+    }
+
+    #[test]
+    fn test_workspace_index_folder() {
+        let store = DocumentStore::new();
+        let index = WorkspaceIndex::new();
+        let file_system = MemoryFileSystem(FxHashMap::from_iter([
+            ("file:///project/a.css", ".a { color: red; }"),
+            ("file:///project/styles/b.scss", ".b { color: red; }"),
+            ("file:///project/styles/readme.md", "# Styles"),
+            (
+                "file:///project/node_modules/lib/c.css",
+                ".c { color: red; }",
+            ),
+        ]));
+
+        index
+            .index_folder(
+                &file_system,
+                &Uri::from_str("file:///project").unwrap(),
+                &CancellationToken::default(),
+            )
+            .unwrap();
+        index.sync(&store);
+
+        assert_eq!(index.documents().len(), 2);
+        assert_eq!(index.find(SymbolKind::Class, "a").len(), 1);
+        assert_eq!(index.find(SymbolKind::Class, "b").len(), 1);
+        assert!(index.find(SymbolKind::Class, "c").is_empty());
+
+        // Open documents take precedence over their version on disk
+        store.insert(create_document(
+            "file:///project/a.css",
+            1,
+            ".z { color: red; }",
+        ));
+        index.sync(&store);
+
+        assert_eq!(index.documents().len(), 2);
+        assert!(index.find(SymbolKind::Class, "a").is_empty());
+        assert_eq!(index.find(SymbolKind::Class, "z").len(), 1);
+    }
+
+    #[test]
+    fn test_workspace_index_folder_async() {
+        use std::{
+            future::Future,
+            task::{Context, Waker},
+        };
+
+        let index = WorkspaceIndex::new();
+        let file_system = MemoryFileSystem(FxHashMap::from_iter([(
+            "file:///project/a.css",
+            ".a { color: red; }",
+        )]));
+
+        let folder = Uri::from_str("file:///project").unwrap();
+        let cancellation = CancellationToken::default();
+        let future = std::pin::pin!(index.index_folder_async(&file_system, &folder, &cancellation));
+
+        // Synchronous providers are always ready, so the future completes on the first poll
+        assert!(future
+            .poll(&mut Context::from_waker(Waker::noop()))
+            .is_ready());
+        assert_eq!(index.find(SymbolKind::Class, "a").len(), 1);
+    }
+
+    #[test]
+    fn test_workspace_index_folder_cancelled() {
+        let index = WorkspaceIndex::new();
+        let file_system = MemoryFileSystem(FxHashMap::from_iter([(
+            "file:///project/a.css",
+            ".a { color: red; }",
+        )]));
+
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+
+        assert_eq!(
+            index.index_folder(
+                &file_system,
+                &Uri::from_str("file:///project").unwrap(),
+                &cancellation
+            ),
+            Err(Cancelled)
+        );
+        assert!(index.documents().is_empty());
+    }
+
+    #[test]
+    fn test_workspace_index_persistence() {
+        let folder = Uri::from_str("file:///project").unwrap();
+        let a = Uri::from_str("file:///project/a.css").unwrap();
+
+        let index = WorkspaceIndex::new();
+        let file_system = MemoryFileSystem(FxHashMap::from_iter([
+            ("file:///project/a.css", ".a { color: red; }\n.café {}"),
+            ("file:///project/b.css", ".b { color: red; }"),
+        ]));
+        index
+            .index_folder(&file_system, &folder, &CancellationToken::default())
+            .unwrap();
+
+        let restored = WorkspaceIndex::new();
+        assert_eq!(restored.load(&index.save()).unwrap(), 2);
+        assert_eq!(restored.find(SymbolKind::Class, "café").len(), 1);
+
+        let restored_a = restored.document(&a).unwrap();
+        assert!(restored_a.entry().is_none());
+        assert_eq!(
+            restored_a.line_index(),
+            index.document(&a).unwrap().line_index()
+        );
+
+        // Unmodified files aren't read again, while deleted files are removed
+        let file_system = MemoryFileSystem(FxHashMap::from_iter([
+            ("file:///project/a.css", ".a { color: red; }\n.café {}"),
+            ("file:///project/c.css", ".c { color: red; }"),
+        ]));
+        restored
+            .index_folder(&file_system, &folder, &CancellationToken::default())
+            .unwrap();
+
+        assert!(restored.document(&a).unwrap().entry().is_none());
+        assert!(restored.find(SymbolKind::Class, "b").is_empty());
+        assert_eq!(restored.find(SymbolKind::Class, "c").len(), 1);
+
+        assert!(restored.load(b"{}").is_err());
+    }
+}
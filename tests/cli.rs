@@ -0,0 +1,215 @@
+#![cfg(all(feature = "cli", unix))]
+
+use std::{fs, path::PathBuf};
+
+use csslsrs::cli::{
+    check, exit_code, file_uri, find_stylesheets, format_diagnostics, json_lines, language_service,
+    Command, Options, OutputFormat,
+};
+use serde_json::Value;
+
+fn project(name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!("csslsrs-cli-{name}-{}", std::process::id()));
+    fs::create_dir_all(folder.join("components")).unwrap();
+    // The paths of stylesheets are resolved, e.g. on macOS where the temporary folder is a symbolic link
+    let folder = folder.canonicalize().unwrap();
+    fs::create_dir_all(folder.join("node_modules/library")).unwrap();
+    fs::write(
+        folder.join("main.css"),
+        ".card {\n  &div { color: red; }\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        folder.join("components/button.css"),
+        ".button { color: red; }\n",
+    )
+    .unwrap();
+    fs::write(
+        folder.join("node_modules/library/index.css"),
+        ".a { &div {} }",
+    )
+    .unwrap();
+    folder
+}
+
+#[test]
+fn test_cli_check() {
+    let folder = project("check");
+    let language_service = language_service(None).unwrap();
+
+    // Without globs, every stylesheet of the folder is checked, except the dependencies
+    let stylesheets = find_stylesheets(&language_service, &folder, &[]).unwrap();
+    assert_eq!(
+        stylesheets,
+        vec![
+            file_uri(&folder.join("components/button.css")).unwrap(),
+            file_uri(&folder.join("main.css")).unwrap(),
+        ]
+    );
+    let globs = |globs: &[&str]| {
+        find_stylesheets(
+            &language_service,
+            &folder,
+            &globs
+                .iter()
+                .map(|glob| glob.to_string())
+                .collect::<Vec<_>>(),
+        )
+        .unwrap()
+    };
+    assert_eq!(globs(&["components/*.css"]), stylesheets[..1]);
+    assert_eq!(globs(&["components"]), stylesheets[..1]);
+    assert_eq!(globs(&["./main.css"]), stylesheets[1..]);
+    assert_eq!(globs(&["*.scss"]), Vec::new());
+
+    let results = check(&language_service, &stylesheets).unwrap();
+    assert!(results[0].diagnostics.is_empty());
+    assert_eq!(results[1].diagnostics.len(), 1);
+    assert_eq!(exit_code(&results), 1);
+    assert_eq!(exit_code(&results[..1]), 0);
+
+    let human = format_diagnostics(&results, OutputFormat::Human, &folder);
+    assert_eq!(
+        human,
+        "main.css:2:3: error [nesting-selector-position] `&div` is invalid, a type selector must start the compound selector: write `div&`\n\
+         1 error and 0 warnings in 2 files\n"
+    );
+
+    let json: Value =
+        serde_json::from_str(&format_diagnostics(&results, OutputFormat::Json, &folder)).unwrap();
+    assert_eq!(json[1]["uri"], stylesheets[1].as_str());
+    assert_eq!(json[1]["diagnostics"][0]["range"]["start"]["line"], 1);
+
+    let sarif: Value =
+        serde_json::from_str(&format_diagnostics(&results, OutputFormat::Sarif, &folder)).unwrap();
+    assert_eq!(sarif["version"], "2.1.0");
+    let run = &sarif["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "csslsrs");
+    assert_eq!(
+        run["tool"]["driver"]["rules"][0]["id"],
+        "nesting-selector-position"
+    );
+    assert_eq!(run["results"][0]["level"], "error");
+    assert_eq!(run["results"][0]["ruleId"], "nesting-selector-position");
+    let location = &run["results"][0]["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], stylesheets[1].as_str());
+    assert_eq!(location["region"]["startLine"], 2);
+    assert_eq!(location["region"]["startColumn"], 3);
+
+    fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn test_cli_check_settings() {
+    let folder = project("settings");
+    fs::write(
+        folder.join("csslsrs.json"),
+        r#"{ "diagnostics": { "colorContrast": { "enabled": true } }, "features": { "hover": false } }"#,
+    )
+    .unwrap();
+    fs::write(
+        folder.join("components/button.css"),
+        ".button { color: #777; background: #fff; }\n",
+    )
+    .unwrap();
+
+    let service = language_service(Some(&folder.join("csslsrs.json"))).unwrap();
+    let stylesheets = find_stylesheets(&service, &folder, &["components".into()]).unwrap();
+    let results = check(&service, &stylesheets).unwrap();
+    assert_eq!(results[0].diagnostics.len(), 1);
+    assert_eq!(
+        results[0].diagnostics[0].code,
+        Some(lsp_types::NumberOrString::String("color-contrast".into()))
+    );
+    // The contrast is only a warning
+    assert_eq!(exit_code(&results), 0);
+
+    fs::write(folder.join("invalid.json"), "{ \"diagnostics\": 1 }").unwrap();
+    assert!(language_service(Some(&folder.join("invalid.json"))).is_err());
+
+    fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn test_cli_json_lines() {
+    let folder = project("json-lines");
+    fs::write(
+        folder.join("main.css"),
+        ".card { color: red; }\n@font-face { font-family: Inter; src: url(fonts/inter.woff2); }\n",
+    )
+    .unwrap();
+    let language_service = language_service(None).unwrap();
+    let stylesheets = find_stylesheets(&language_service, &folder, &["main.css".into()]).unwrap();
+    let uri = stylesheets[0].as_str();
+
+    let lines = |command: Command| {
+        json_lines(&language_service, command, &stylesheets)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    let colors = lines(Command::Colors);
+    assert_eq!(colors.len(), 1);
+    assert_eq!(colors[0]["uri"], uri);
+    assert_eq!(colors[0]["color"]["red"], 1.0);
+    assert_eq!(colors[0]["range"]["start"]["line"], 0);
+
+    let symbols = lines(Command::Symbols);
+    assert_eq!(symbols.len(), 2);
+    assert_eq!(symbols[0]["uri"], uri);
+    assert_eq!(symbols[0]["name"], ".card");
+
+    let links = lines(Command::Links);
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0]["uri"], uri);
+    assert_eq!(
+        links[0]["target"],
+        file_uri(&folder.join("fonts/inter.woff2"))
+            .unwrap()
+            .as_str()
+    );
+
+    assert!(lines(Command::Check).is_empty());
+
+    fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn test_cli_options() {
+    assert_eq!("colors".parse(), Ok(Command::Colors));
+    assert_eq!(
+        "lint".parse::<Command>(),
+        Err("unknown command `lint`".to_string())
+    );
+
+    let parse = |args: &[&str]| Options::parse(args.iter().map(|arg| arg.to_string()));
+
+    assert_eq!(parse(&[]), Ok(Options::default()));
+    assert_eq!(
+        parse(&[
+            "--format=sarif",
+            "--settings",
+            "csslsrs.json",
+            "src/**/*.css"
+        ]),
+        Ok(Options {
+            format: OutputFormat::Sarif,
+            settings: Some(PathBuf::from("csslsrs.json")),
+            globs: vec!["src/**/*.css".to_string()],
+        })
+    );
+    assert_eq!(
+        parse(&["--format=xml"]),
+        Err("unknown format `xml`".to_string())
+    );
+    assert_eq!(
+        parse(&["--settings"]),
+        Err("`--settings` needs a value".to_string())
+    );
+    assert_eq!(
+        parse(&["--fix"]),
+        Err("unknown argument `--fix`".to_string())
+    );
+}
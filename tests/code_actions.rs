@@ -0,0 +1,221 @@
+#![cfg(feature = "code-actions")]
+
+use csslsrs::{
+    service::LanguageService,
+    settings::{CodeActionSettings, Settings},
+    Error,
+};
+use lsp_types::{CodeAction, CodeActionKind, Position, Range, TextDocumentItem, TextEdit, Uri};
+use std::str::FromStr;
+
+fn create_document(text: &str) -> TextDocumentItem {
+    TextDocumentItem {
+        uri: Uri::from_str("file:///test.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: text.to_string(),
+    }
+}
+
+/// The title of each action, with the edits it makes to the document.
+fn actions(code_actions: Vec<CodeAction>) -> Vec<(String, Vec<TextEdit>)> {
+    code_actions
+        .into_iter()
+        .map(|action| {
+            assert_eq!(action.kind, Some(CodeActionKind::REFACTOR_REWRITE));
+            let mut changes = action.edit.unwrap().changes.unwrap();
+            let edits = changes
+                .remove(&Uri::from_str("file:///test.css").unwrap())
+                .unwrap();
+            (action.title, edits)
+        })
+        .collect()
+}
+
+#[test]
+fn test_code_actions_convert_lengths() {
+    let ls = LanguageService::default();
+    let document = create_document(".a { margin: 24px 8px; }\n.b { padding: 1.5rem; width: 50%; }");
+    let cursor = |line, character| {
+        let position = Position::new(line, character);
+        actions(
+            ls.get_code_actions(document.clone(), Range::new(position, position))
+                .unwrap(),
+        )
+    };
+
+    let edit = |line, start, end, text: &str| {
+        TextEdit::new(
+            Range::new(Position::new(line, start), Position::new(line, end)),
+            text.to_string(),
+        )
+    };
+    assert_eq!(
+        cursor(0, 15),
+        vec![
+            (
+                "Convert `24px` to `1.5rem`".to_string(),
+                vec![edit(0, 13, 17, "1.5rem")]
+            ),
+            (
+                "Convert `24px` to `1.5em`".to_string(),
+                vec![edit(0, 13, 17, "1.5em")]
+            ),
+            (
+                "Convert every `px` length of the document to `rem`".to_string(),
+                vec![edit(0, 13, 17, "1.5rem"), edit(0, 18, 21, "0.5rem")]
+            ),
+            (
+                "Convert every `px` length of the document to `em`".to_string(),
+                vec![edit(0, 13, 17, "1.5em"), edit(0, 18, 21, "0.5em")]
+            ),
+        ]
+    );
+    assert_eq!(
+        cursor(1, 16),
+        vec![
+            (
+                "Convert `1.5rem` to `24px`".to_string(),
+                vec![edit(1, 14, 20, "24px")]
+            ),
+            (
+                "Convert every `rem` length of the document to `px`".to_string(),
+                vec![edit(1, 14, 20, "24px")]
+            ),
+        ]
+    );
+    assert_eq!(cursor(1, 31), Vec::new(), "percentages aren't converted");
+    assert_eq!(cursor(0, 2), Vec::new());
+
+    let selection = actions(
+        ls.get_code_actions(
+            document.clone(),
+            Range::new(Position::new(0, 0), Position::new(1, 0)),
+        )
+        .unwrap(),
+    );
+    assert_eq!(
+        selection
+            .iter()
+            .map(|(title, _)| title.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "Convert `24px` to `1.5rem`",
+            "Convert `24px` to `1.5em`",
+            "Convert `8px` to `0.5rem`",
+            "Convert `8px` to `0.5em`",
+            "Convert every `px` length of the document to `rem`",
+            "Convert every `px` length of the document to `em`",
+        ]
+    );
+
+    assert_eq!(
+        ls.get_code_actions(
+            document,
+            Range::new(Position::new(5, 0), Position::new(5, 1))
+        )
+        .err(),
+        Some(Error::InvalidRange(Range::new(
+            Position::new(5, 0),
+            Position::new(5, 1)
+        )))
+    );
+}
+
+#[test]
+fn test_code_actions_root_font_size() {
+    let ls = LanguageService::builder()
+        .settings(Settings {
+            code_actions: CodeActionSettings {
+                root_font_size: Some(10),
+            },
+            ..Default::default()
+        })
+        .build();
+    let document = create_document(".a { font-size: 15px; }");
+    let position = Position::new(0, 17);
+
+    let titles = actions(
+        ls.get_code_actions(document, Range::new(position, position))
+            .unwrap(),
+    )
+    .into_iter()
+    .map(|(title, _)| title)
+    .collect::<Vec<_>>();
+    assert_eq!(titles[0], "Convert `15px` to `1.5rem`");
+}
+
+#[cfg(feature = "diagnostics")]
+#[test]
+fn test_code_actions_quick_fixes() {
+    let ls = LanguageService::default();
+    let document = create_document(
+        "@import \"base.css\";\n@import \"base.css\";\n@layer base, print;\n@layer base { .a { margin: 8px; } }",
+    );
+    let quick_fixes = |range: Range| {
+        ls.get_code_actions(document.clone(), range)
+            .unwrap()
+            .into_iter()
+            .filter(|action| action.kind == Some(CodeActionKind::QUICKFIX))
+            .map(|action| {
+                assert_eq!(
+                    action.diagnostics.map(|diagnostics| diagnostics.len()),
+                    Some(1)
+                );
+                let mut changes = action.edit.unwrap().changes.unwrap();
+                let edits = changes
+                    .remove(&Uri::from_str("file:///test.css").unwrap())
+                    .unwrap();
+                (action.title, edits)
+            })
+            .collect::<Vec<_>>()
+    };
+    let cursor = |line, character| {
+        let position = Position::new(line, character);
+        quick_fixes(Range::new(position, position))
+    };
+
+    assert_eq!(
+        cursor(1, 3),
+        vec![(
+            "Remove `@import \"base.css\";`".to_string(),
+            vec![TextEdit::new(
+                Range::new(Position::new(1, 0), Position::new(2, 0)),
+                String::new()
+            )]
+        )]
+    );
+    assert_eq!(
+        cursor(2, 15),
+        vec![(
+            "Remove `print`".to_string(),
+            vec![TextEdit::new(
+                Range::new(Position::new(2, 11), Position::new(2, 18)),
+                String::new()
+            )]
+        )]
+    );
+    assert_eq!(cursor(0, 3), Vec::new(), "the first import is kept");
+
+    // Quick fixes come before the other actions of the range
+    let titles = ls
+        .get_code_actions(
+            document,
+            Range::new(Position::new(0, 0), Position::new(3, 30)),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|action| action.title)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        titles,
+        vec![
+            "Remove `@import \"base.css\";`",
+            "Remove `print`",
+            "Convert `8px` to `0.5rem`",
+            "Convert `8px` to `0.5em`",
+            "Convert every `px` length of the document to `rem`",
+            "Convert every `px` length of the document to `em`",
+        ]
+    );
+}
@@ -1,13 +1,17 @@
+#![cfg(feature = "colors")]
+
+use csslsrs::cancellation::{CancellationToken, Cancelled};
+use csslsrs::progress::ProgressReporter;
 use csslsrs::service::LanguageService;
-use lsp_types::{Color, ColorInformation, Position, Range, TextDocumentItem, Uri};
+use lsp_types::{Color, ColorInformation, Position, Range, TextDocumentItem, TextEdit, Uri};
 use std::str::FromStr;
 
 #[test]
 fn test_hex_color() {
-    let mut ls = LanguageService::default();
+    let ls = LanguageService::default();
 
     assert_color_symbols(
-        &mut ls,
+        &ls,
         "body { backgroundColor: #ff9977; }",
         vec![ColorInformation {
             color: csscolorparser::parse("#ff9977")
@@ -29,10 +33,10 @@ fn test_hex_color() {
 
 #[test]
 fn test_hsl_color() {
-    let mut ls = LanguageService::default();
+    let ls = LanguageService::default();
 
     assert_color_symbols(
-        &mut ls,
+        &ls,
         "body { backgroundColor: hsl(0, 0%, 100%); }",
         vec![ColorInformation {
             color: csscolorparser::parse("hsl(0, 0%, 100%)")
@@ -54,10 +58,10 @@ fn test_hsl_color() {
 
 #[test]
 fn test_rgb_and_hsl_colors() {
-    let mut ls = LanguageService::default();
+    let ls = LanguageService::default();
 
     assert_color_symbols(
-        &mut ls,
+        &ls,
         ".oo { color: rgb(1,40,1); borderColor: hsl(120, 75%, 85%) }",
         vec![
             ColorInformation {
@@ -86,7 +90,7 @@ fn test_rgb_and_hsl_colors() {
                     },
                     end: Position {
                         line: 0,
-                        character: 58,
+                        character: 57,
                     },
                 },
             },
@@ -96,10 +100,10 @@ fn test_rgb_and_hsl_colors() {
 
 #[test]
 fn test_rgba_color() {
-    let mut ls = LanguageService::default();
+    let ls = LanguageService::default();
 
     assert_color_symbols(
-        &mut ls,
+        &ls,
         "body { backgroundColor: rgba(1, 40, 1, 0.3); }",
         vec![ColorInformation {
             color: csscolorparser::parse("rgba(1, 40, 1, 0.3)")
@@ -121,10 +125,10 @@ fn test_rgba_color() {
 
 #[test]
 fn test_hwb_color() {
-    let mut ls = LanguageService::default();
+    let ls = LanguageService::default();
 
     assert_color_symbols(
-        &mut ls,
+        &ls,
         "body { backgroundColor: hwb(194 0% 0% / .5); }",
         vec![ColorInformation {
             color: csscolorparser::parse("hwb(194 0% 0% / .5)")
@@ -146,10 +150,10 @@ fn test_hwb_color() {
 
 #[test]
 fn test_named_color() {
-    let mut ls = LanguageService::default();
+    let ls = LanguageService::default();
 
     assert_color_symbols(
-        &mut ls,
+        &ls,
         "body { backgroundColor: red; }",
         vec![ColorInformation {
             color: csscolorparser::parse("red")
@@ -171,10 +175,10 @@ fn test_named_color() {
 
 #[test]
 fn test_functions_color() {
-    let mut ls = LanguageService::default();
+    let ls = LanguageService::default();
 
     assert_color_symbols(
-        &mut ls,
+        &ls,
         "body { color: linear-gradient(to right, red, blue); }",
         vec![
             ColorInformation {
@@ -211,6 +215,362 @@ fn test_functions_color() {
     );
 }
 
+#[test]
+fn test_nested_colors() {
+    let ls = LanguageService::default();
+
+    // Each color gets its own range, even when nested in other values
+    assert_color_symbols(
+        &ls,
+        "a { background: linear-gradient(to right, red, #fff); border: 1px solid blue; }",
+        vec![
+            ColorInformation {
+                color: csscolorparser::parse("red")
+                    .map(convert_parsed_color)
+                    .unwrap(),
+                range: Range {
+                    start: Position {
+                        line: 0,
+                        character: 42,
+                    },
+                    end: Position {
+                        line: 0,
+                        character: 45,
+                    },
+                },
+            },
+            ColorInformation {
+                color: csscolorparser::parse("#fff")
+                    .map(convert_parsed_color)
+                    .unwrap(),
+                range: Range {
+                    start: Position {
+                        line: 0,
+                        character: 47,
+                    },
+                    end: Position {
+                        line: 0,
+                        character: 51,
+                    },
+                },
+            },
+            ColorInformation {
+                color: csscolorparser::parse("blue")
+                    .map(convert_parsed_color)
+                    .unwrap(),
+                range: Range {
+                    start: Position {
+                        line: 0,
+                        character: 72,
+                    },
+                    end: Position {
+                        line: 0,
+                        character: 76,
+                    },
+                },
+            },
+        ],
+    );
+}
+
+#[test]
+fn test_max_document_colors() {
+    let ls = LanguageService::default();
+    ls.set_max_document_colors(Some(2));
+
+    let document = TextDocumentItem {
+        uri: Uri::from_str("file:///test.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: "a { color: red; }\nb { color: blue; }\ni { color: #fff; }".to_string(),
+    };
+
+    // The first colors of the document are kept
+    let colors = ls.get_document_colors(document.clone());
+    assert_eq!(
+        colors
+            .iter()
+            .map(|color| color.range.start.line)
+            .collect::<Vec<_>>(),
+        vec![0, 1]
+    );
+
+    let mut streamed = Vec::new();
+    ls.stream_document_colors(
+        document,
+        &CancellationToken::default(),
+        1,
+        &mut |colors| streamed.extend(colors),
+        &mut (),
+    )
+    .unwrap();
+    assert_eq!(streamed, colors);
+}
+
+#[test]
+fn test_document_colors_in_range() {
+    let ls = LanguageService::default();
+
+    let document = TextDocumentItem {
+        uri: Uri::from_str("file:///test.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: "a { color: red; }\nb {\n  color: blue;\n  background: #fff;\n}\ni { color: green; }"
+            .to_string(),
+    };
+
+    // Only the colors intersecting the range are returned, even if their rule starts before it
+    let colors = ls
+        .get_document_colors_in_range(
+            document.clone(),
+            Range::new(Position::new(2, 0), Position::new(3, 2)),
+        )
+        .unwrap();
+    assert_eq!(
+        colors
+            .iter()
+            .map(|color| color.range.start)
+            .collect::<Vec<_>>(),
+        vec![Position::new(2, 9)]
+    );
+
+    let all_colors = ls
+        .get_document_colors_in_range(
+            document.clone(),
+            Range::new(Position::new(0, 0), Position::new(5, 19)),
+        )
+        .unwrap();
+    assert_eq!(all_colors, ls.get_document_colors(document));
+}
+
+#[test]
+fn test_cancelled_colors() {
+    let ls = LanguageService::default();
+    let document = TextDocumentItem {
+        uri: Uri::from_str("file:///test.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: "body { color: red; }".to_string(),
+    };
+
+    let cancellation = CancellationToken::new();
+    cancellation.cancel();
+    assert_eq!(
+        ls.get_document_colors_with_cancellation(document.clone(), &cancellation),
+        Err(Cancelled)
+    );
+
+    // Cancelled results aren't cached
+    assert_eq!(ls.get_document_colors(document).len(), 1);
+}
+
+#[derive(Default)]
+struct RecordedProgress(Vec<String>);
+
+impl ProgressReporter for RecordedProgress {
+    fn begin(&mut self, title: &str) {
+        self.0.push(format!("begin {title}"));
+    }
+
+    fn report(&mut self, percentage: u32) {
+        self.0.push(format!("report {percentage}"));
+    }
+
+    fn end(&mut self) {
+        self.0.push("end".to_string());
+    }
+}
+
+#[test]
+fn test_scss_variable_colors() {
+    let ls = LanguageService::default();
+    let document = TextDocumentItem {
+        uri: Uri::from_str("file:///test.scss").unwrap(),
+        language_id: "scss".to_string(),
+        version: 1,
+        text: "$primary: #ff0000;\n.a { color: $primary; background: blue; }".to_string(),
+    };
+
+    let colors = ls.get_document_colors(document);
+
+    assert_eq!(
+        colors.iter().map(|color| color.range).collect::<Vec<_>>(),
+        vec![
+            Range::new(Position::new(0, 10), Position::new(0, 17)),
+            Range::new(Position::new(1, 34), Position::new(1, 38)),
+        ]
+    );
+    assert_eq!(
+        colors[0].color,
+        csscolorparser::parse("#ff0000")
+            .map(convert_parsed_color)
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_less_variable_colors() {
+    let ls = LanguageService::default();
+    let document = TextDocumentItem {
+        uri: Uri::from_str("file:///test.less").unwrap(),
+        language_id: "less".to_string(),
+        version: 1,
+        text: "@primary: #ff0000;\n.a { color: @primary; background: blue; }".to_string(),
+    };
+
+    let colors = ls.get_document_colors(document);
+
+    assert_eq!(
+        colors.iter().map(|color| color.range).collect::<Vec<_>>(),
+        vec![
+            Range::new(Position::new(0, 10), Position::new(0, 17)),
+            Range::new(Position::new(1, 34), Position::new(1, 38)),
+        ]
+    );
+}
+
+#[test]
+fn test_colors_with_syntax_errors() {
+    let ls = LanguageService::default();
+    let document = TextDocumentItem {
+        uri: Uri::from_str("file:///test.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        // An unclosed function, a declaration without `;` and an unclosed rule, as while typing
+        text: ".a { color: rgb(255 0 0 }\n.b {\n  color: #00ff00\n  background: blue;\n}\n.c { color: #ff0000"
+            .to_string(),
+    };
+
+    let colors = ls.get_document_colors(document);
+
+    assert_eq!(
+        colors.iter().map(|color| color.range).collect::<Vec<_>>(),
+        vec![
+            Range::new(Position::new(0, 12), Position::new(0, 24)),
+            Range::new(Position::new(2, 9), Position::new(2, 16)),
+            Range::new(Position::new(3, 14), Position::new(3, 18)),
+            Range::new(Position::new(5, 12), Position::new(5, 19)),
+        ]
+    );
+}
+
+#[test]
+fn test_stream_colors() {
+    let ls = LanguageService::default();
+    let document = TextDocumentItem {
+        uri: Uri::from_str("file:///test.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: "a { color: red; }\nb { color: blue; }\ni { color: #fff; }".to_string(),
+    };
+
+    let mut batches = Vec::new();
+    let mut progress = RecordedProgress::default();
+    ls.stream_document_colors(
+        document.clone(),
+        &CancellationToken::default(),
+        2,
+        &mut |colors| batches.push(colors),
+        &mut progress,
+    )
+    .unwrap();
+
+    assert_eq!(batches.iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 1]);
+    assert_eq!(
+        progress.0,
+        vec![
+            "begin Finding colors",
+            "report 30",
+            "report 65",
+            "report 100",
+            "end"
+        ]
+    );
+    assert_eq!(
+        batches.concat(),
+        ls.get_document_colors(document.clone()),
+        "Streamed colors are the same as the ones returned at once"
+    );
+
+    // Cached colors are streamed as well
+    let mut batches = Vec::new();
+    ls.stream_document_colors(
+        document,
+        &CancellationToken::default(),
+        2,
+        &mut |colors| batches.push(colors),
+        &mut (),
+    )
+    .unwrap();
+    assert_eq!(batches.concat().len(), 3);
+}
+
+#[test]
+fn test_color_presentations() {
+    let ls = LanguageService::default();
+    let document = TextDocumentItem {
+        uri: Uri::from_str("file:///test.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: "a { color: hsl(0, 0%, 0%); }".to_string(),
+    };
+    let range = Range::new(Position::new(0, 11), Position::new(0, 25));
+    let red = Color {
+        red: 1.0,
+        green: 0.0,
+        blue: 0.0,
+        alpha: 1.0,
+    };
+
+    // The syntax of the replaced color comes first
+    let presentations = ls
+        .get_color_presentations(document.clone(), red, range)
+        .unwrap();
+    assert_eq!(
+        presentations
+            .iter()
+            .map(|presentation| presentation.label.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "hsl(0, 100%, 50%)",
+            "red",
+            "rgb(255, 0, 0)",
+            "#ff0000",
+            "hwb(0 0% 0%)"
+        ]
+    );
+    assert_eq!(
+        presentations[0].text_edit,
+        Some(TextEdit {
+            range,
+            new_text: "hsl(0, 100%, 50%)".to_string(),
+        })
+    );
+
+    let transparent_red = Color { alpha: 0.5, ..red };
+    let presentations = ls
+        .get_color_presentations(document.clone(), transparent_red, range)
+        .unwrap();
+    assert_eq!(
+        presentations
+            .iter()
+            .map(|presentation| presentation.label.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "hsla(0, 100%, 50%, 0.5)",
+            "rgba(255, 0, 0, 0.5)",
+            "#ff000080",
+            "hwb(0 0% 0% / 0.5)"
+        ]
+    );
+
+    let reversed = Range::new(Position::new(0, 25), Position::new(0, 11));
+    assert_eq!(
+        ls.get_color_presentations(document, red, reversed),
+        Err(csslsrs::Error::InvalidRange(reversed))
+    );
+}
+
 fn convert_parsed_color(color: csscolorparser::Color) -> Color {
     Color {
         red: color.r,
@@ -220,11 +580,7 @@ fn convert_parsed_color(color: csscolorparser::Color) -> Color {
     }
 }
 
-fn assert_color_symbols(
-    ls: &mut LanguageService,
-    text: &str,
-    expected_colors: Vec<ColorInformation>,
-) {
+fn assert_color_symbols(ls: &LanguageService, text: &str, expected_colors: Vec<ColorInformation>) {
     let document = TextDocumentItem {
         uri: Uri::from_str("file:///test.css").unwrap(),
         language_id: "css".to_string(),
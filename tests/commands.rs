@@ -0,0 +1,178 @@
+use csslsrs::{
+    commands::{Command, REINDEX_WORKSPACE, SHOW_REFERENCES},
+    error::Error,
+    service::LanguageService,
+};
+use lsp_types::{Location, Position, Range, TextDocumentItem, Uri};
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+fn create_document(uri: &str, text: &str) -> TextDocumentItem {
+    TextDocumentItem {
+        uri: Uri::from_str(uri).unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: text.to_string(),
+    }
+}
+
+/// Counts the open documents, replacing the built-in `csslsrs.reindexWorkspace` in the tests.
+struct CountDocuments;
+
+impl Command for CountDocuments {
+    fn name(&self) -> &str {
+        REINDEX_WORKSPACE
+    }
+
+    fn execute(
+        &self,
+        language_service: &LanguageService,
+        _arguments: Vec<Value>,
+    ) -> Result<Option<Value>, Error> {
+        Ok(Some(json!(language_service.store.entries().len())))
+    }
+}
+
+#[test]
+fn test_execute_command() {
+    let mut language_service = LanguageService::default();
+    assert!(language_service
+        .command_names()
+        .contains(&SHOW_REFERENCES.to_string()));
+
+    // Without a FileSystemProvider, nothing is indexed
+    assert_eq!(
+        language_service.execute_command(REINDEX_WORKSPACE, vec![json!("file:///workspace")]),
+        Ok(Some(json!(false)))
+    );
+    assert_eq!(
+        language_service.execute_command(REINDEX_WORKSPACE, vec![json!(1)]),
+        Err(Error::InvalidArguments(
+            "`folder`: invalid type: integer `1`, expected a string".to_string()
+        ))
+    );
+    assert_eq!(
+        language_service.execute_command("csslsrs.unknown", Vec::new()),
+        Err(Error::UnknownCommand("csslsrs.unknown".to_string()))
+    );
+
+    // Commands added later replace the built-in ones
+    language_service.add_command(CountDocuments);
+    language_service.open_document(create_document("file:///a.css", "a {}"));
+    assert_eq!(
+        language_service.execute_command(REINDEX_WORKSPACE, Vec::new()),
+        Ok(Some(json!(1)))
+    );
+    assert_eq!(
+        language_service
+            .command_names()
+            .iter()
+            .filter(|name| *name == REINDEX_WORKSPACE)
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn test_show_references() {
+    let language_service = LanguageService::default();
+    language_service.open_document(create_document(
+        "file:///theme.css",
+        ":root { --brand: red; }",
+    ));
+    language_service.open_document(create_document(
+        "file:///button.css",
+        ".button { color: var(--brand); }\n.link { color: var(--brand); }",
+    ));
+
+    let result = language_service
+        .execute_command(
+            SHOW_REFERENCES,
+            vec![json!("file:///button.css"), json!(Position::new(0, 24))],
+        )
+        .unwrap()
+        .unwrap();
+    let location = |uri: &str, start: Position, end: Position| {
+        Location::new(Uri::from_str(uri).unwrap(), Range::new(start, end))
+    };
+    assert_eq!(
+        serde_json::from_value::<Vec<Location>>(result).unwrap(),
+        vec![
+            location(
+                "file:///button.css",
+                Position::new(0, 21),
+                Position::new(0, 28)
+            ),
+            location(
+                "file:///button.css",
+                Position::new(1, 19),
+                Position::new(1, 26)
+            ),
+            location(
+                "file:///theme.css",
+                Position::new(0, 8),
+                Position::new(0, 15)
+            ),
+        ]
+    );
+
+    // There is no symbol in a declaration name
+    assert_eq!(
+        language_service.get_references(
+            &Uri::from_str("file:///button.css").unwrap(),
+            Position::new(0, 12)
+        ),
+        Ok(Vec::new())
+    );
+}
+
+#[cfg(feature = "diagnostics")]
+#[test]
+fn test_apply_fix_all() {
+    use csslsrs::{commands::APPLY_FIX_ALL, design_tokens::DesignTokens};
+    use lsp_types::{TextEdit, WorkspaceEdit};
+    use std::collections::HashMap;
+
+    let language_service = LanguageService::builder()
+        .design_tokens(
+            DesignTokens::from_json(
+                r##"{ "color": { "$type": "color", "brand": { "$value": "#0055FF" } } }"##,
+            )
+            .unwrap(),
+        )
+        .build();
+    let uri = Uri::from_str("file:///test.css").unwrap();
+    language_service.open_document(create_document(
+        uri.as_str(),
+        ".a { color: #0055ff; }\n.b { border-color: #0055FF; }",
+    ));
+
+    let result = language_service
+        .execute_command(APPLY_FIX_ALL, vec![json!(uri)])
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        serde_json::from_value::<WorkspaceEdit>(result.clone()).unwrap(),
+        WorkspaceEdit::new(HashMap::from([(
+            uri.clone(),
+            vec![
+                TextEdit::new(
+                    Range::new(Position::new(0, 12), Position::new(0, 19)),
+                    "var(--color-brand)".to_string()
+                ),
+                TextEdit::new(
+                    Range::new(Position::new(1, 19), Position::new(1, 26)),
+                    "var(--color-brand)".to_string()
+                ),
+            ]
+        )]))
+    );
+
+    // Without a URI, every document of the workspace is fixed
+    assert_eq!(
+        language_service
+            .execute_command(APPLY_FIX_ALL, Vec::new())
+            .unwrap(),
+        Some(result)
+    );
+}
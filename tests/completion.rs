@@ -0,0 +1,681 @@
+#![cfg(feature = "completion")]
+
+use csslsrs::{
+    features::completion::{CompletionContext, CompletionLocation, CompletionProvider},
+    service::LanguageService,
+    settings::{CompletionSettings, CssSettings, Settings},
+    Error,
+};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionItemTag, CompletionTextEdit, Documentation,
+    MarkupContent, Position, Range, TextDocumentItem, TextEdit, Uri,
+};
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+/// Offers the design tokens of a project inside `var()`.
+struct DesignTokens(Vec<&'static str>);
+
+impl CompletionProvider for DesignTokens {
+    fn provide(&self, context: &CompletionContext) -> Vec<CompletionItem> {
+        match context.location() {
+            CompletionLocation::FunctionArgument { function, .. } if function == "var" => self
+                .0
+                .iter()
+                .filter(|token| token.starts_with(context.prefix()))
+                .map(|token| {
+                    CompletionItem::new_simple(token.to_string(), "Design token".to_string())
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Records the locations it is asked for items at.
+struct Locations(Arc<Mutex<Vec<CompletionLocation>>>);
+
+impl CompletionProvider for Locations {
+    fn provide(&self, context: &CompletionContext) -> Vec<CompletionItem> {
+        self.0.lock().unwrap().push(context.location().clone());
+        Vec::new()
+    }
+}
+
+/// Offers the same items wherever it is asked.
+struct Items(Vec<CompletionItem>);
+
+impl CompletionProvider for Items {
+    fn provide(&self, _: &CompletionContext) -> Vec<CompletionItem> {
+        self.0.clone()
+    }
+}
+
+fn create_document(text: &str) -> TextDocumentItem {
+    TextDocumentItem {
+        uri: Uri::from_str("file:///test.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: text.to_string(),
+    }
+}
+
+#[test]
+fn test_completion_provider() {
+    let language_service = LanguageService::builder()
+        .completion_provider(DesignTokens(vec!["--brand", "--accent", "--border"]))
+        .build();
+
+    let document = create_document("a { color: var(--b); }");
+    let labels = |position| {
+        language_service
+            .get_completions(document.clone(), position)
+            .unwrap()
+            .into_iter()
+            .map(|item| item.label)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(labels(Position::new(0, 18)), vec!["--border", "--brand"]);
+    assert!(!labels(Position::new(0, 8)).contains(&"--brand".to_string()));
+}
+
+#[test]
+fn test_completion_locations() {
+    let locations = Arc::new(Mutex::new(Vec::new()));
+    let language_service = LanguageService::builder()
+        .completion_provider(Locations(locations.clone()))
+        .build();
+
+    let document = create_document("a { color: red; }");
+    for character in [1, 3, 6, 12] {
+        language_service
+            .get_completions(document.clone(), Position::new(0, character))
+            .unwrap();
+    }
+
+    assert_eq!(
+        *locations.lock().unwrap(),
+        vec![
+            CompletionLocation::Selector,
+            CompletionLocation::PropertyName,
+            CompletionLocation::PropertyName,
+            CompletionLocation::PropertyValue {
+                property: "color".to_string()
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_completion_locations_scss() {
+    let locations = Arc::new(Mutex::new(Vec::new()));
+    let language_service = LanguageService::builder()
+        .completion_provider(Locations(locations.clone()))
+        .build();
+
+    let document = TextDocumentItem {
+        uri: Uri::from_str("file:///test.scss").unwrap(),
+        language_id: "scss".to_string(),
+        version: 1,
+        text: "a { @include button; color: $primary; }".to_string(),
+    };
+    for character in [15, 24] {
+        language_service
+            .get_completions(document.clone(), Position::new(0, character))
+            .unwrap();
+    }
+
+    assert_eq!(
+        *locations.lock().unwrap(),
+        vec![CompletionLocation::Other, CompletionLocation::PropertyName,]
+    );
+}
+
+#[test]
+fn test_completion_scss_variables() {
+    let language_service = LanguageService::default();
+    language_service.open_document(TextDocumentItem {
+        uri: Uri::from_str("file:///project/_colors.scss").unwrap(),
+        language_id: "scss".to_string(),
+        version: 1,
+        text: "$primary: #ff0000;".to_string(),
+    });
+
+    let document = TextDocumentItem {
+        uri: Uri::from_str("file:///project/main.scss").unwrap(),
+        language_id: "scss".to_string(),
+        version: 1,
+        text: "@use \"colors\";\n$gap: 4px;\n.a { margin: $g; color: colors.$ }\n.b { width: →colors.$; height: →. }"
+            .to_string(),
+    };
+    let items = |position| {
+        language_service
+            .get_completions(document.clone(), position)
+            .unwrap()
+            .into_iter()
+            // The keywords of the property are offered too
+            .filter(|item| {
+                !matches!(
+                    item.kind,
+                    Some(CompletionItemKind::VALUE | CompletionItemKind::KEYWORD)
+                )
+            })
+            .map(|item| (item.label, item.insert_text, item.detail))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        items(Position::new(2, 15)),
+        vec![(
+            "$gap".to_string(),
+            Some("gap".to_string()),
+            Some("4px".to_string())
+        )]
+    );
+    assert_eq!(
+        items(Position::new(2, 32)),
+        vec![(
+            "$primary".to_string(),
+            Some("primary".to_string()),
+            Some("#ff0000".to_string())
+        )]
+    );
+    // Namespaces can follow non-ASCII characters
+    assert_eq!(
+        items(Position::new(3, 21)),
+        vec![(
+            "$primary".to_string(),
+            Some("primary".to_string()),
+            Some("#ff0000".to_string())
+        )]
+    );
+    assert!(
+        !items(Position::new(3, 33))
+            .iter()
+            .any(|(label, ..)| label.starts_with('$')),
+        "a dot after a non-ASCII character isn't a namespace"
+    );
+    // Values offer the namespaces of the modules used by the document
+    assert_eq!(
+        items(Position::new(2, 12)),
+        vec![(
+            "colors".to_string(),
+            None,
+            Some("@use \"colors\"".to_string())
+        )]
+    );
+}
+
+#[test]
+fn test_completion_scss_mixins_and_functions() {
+    let language_service = LanguageService::default();
+    let document = TextDocumentItem {
+        uri: Uri::from_str("file:///project/main.scss").unwrap(),
+        language_id: "scss".to_string(),
+        version: 1,
+        text: "@mixin button($size) {}\n@function double($n) { @return $n; }\n.a { @include b; width: d; }"
+            .to_string(),
+    };
+    let items = |position| {
+        language_service
+            .get_completions(document.clone(), position)
+            .unwrap()
+            .into_iter()
+            .filter(|item| item.kind == Some(CompletionItemKind::FUNCTION))
+            .map(|item| (item.label, item.kind, item.detail))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        items(Position::new(2, 15)),
+        vec![(
+            "button".to_string(),
+            Some(CompletionItemKind::FUNCTION),
+            Some("@mixin button($size)".to_string())
+        )]
+    );
+    assert_eq!(
+        items(Position::new(2, 25)),
+        vec![(
+            "double".to_string(),
+            Some(CompletionItemKind::FUNCTION),
+            Some("@function double($n)".to_string())
+        )]
+    );
+}
+
+#[test]
+fn test_completion_custom_directives() {
+    let language_service = LanguageService::builder()
+        .settings(Settings {
+            css: CssSettings {
+                custom_directives: vec![
+                    "tailwind".to_string(),
+                    "apply".to_string(),
+                    "theme()".to_string(),
+                ],
+            },
+            ..Default::default()
+        })
+        .build();
+    let document = create_document("@tai\n.btn { color: t; }\n.b { @ap }");
+    let items = |position| {
+        language_service
+            .get_completions(document.clone(), position)
+            .unwrap()
+            .into_iter()
+            // The at-rules of the CSS data are documented, custom directives aren't
+            .filter(|item| item.documentation.is_none())
+            .map(|item| (item.label, item.kind))
+            .collect::<Vec<_>>()
+    };
+
+    let at_rules = vec![
+        ("apply".to_string(), Some(CompletionItemKind::KEYWORD)),
+        ("tailwind".to_string(), Some(CompletionItemKind::KEYWORD)),
+    ];
+    assert_eq!(items(Position::new(0, 4)), at_rules);
+    assert_eq!(items(Position::new(2, 8)), at_rules);
+    assert_eq!(
+        items(Position::new(1, 15)),
+        vec![("theme".to_string(), Some(CompletionItemKind::FUNCTION))]
+    );
+}
+
+#[test]
+fn test_completion_at_rules() {
+    let language_service = LanguageService::default();
+    let document = create_document("@sc\n.a { color: red; }");
+    let items = language_service
+        .get_completions(document, Position::new(0, 3))
+        .unwrap();
+
+    let scope = items.iter().find(|item| item.label == "scope").unwrap();
+    assert_eq!(scope.kind, Some(CompletionItemKind::KEYWORD));
+    assert!(matches!(
+        &scope.documentation,
+        Some(Documentation::MarkupContent(MarkupContent { value, .. })) if value.contains("DOM subtree")
+    ));
+    assert_eq!(
+        items
+            .iter()
+            .find(|item| item.label == "viewport")
+            .unwrap()
+            .tags,
+        Some(vec![CompletionItemTag::DEPRECATED]),
+        "obsolete at-rules are deprecated"
+    );
+}
+
+#[test]
+fn test_completion_layers() {
+    let language_service = LanguageService::default();
+    let document = create_document(
+        "@import \"theme.css\" layer(base);\n@layer reset, base;\n@layer components { @layer buttons { .a { color: red; } } }",
+    );
+    let items = |position| {
+        language_service
+            .get_completions(document.clone(), position)
+            .unwrap()
+            .into_iter()
+            .map(|item| (item.label, item.kind))
+            .collect::<Vec<_>>()
+    };
+
+    let layers = ["base", "components", "components.buttons", "reset"]
+        .map(|layer| (layer.to_string(), Some(CompletionItemKind::MODULE)))
+        .to_vec();
+    assert_eq!(items(Position::new(0, 26)), layers);
+    assert_eq!(items(Position::new(1, 7)), layers);
+    assert_eq!(
+        items(Position::new(2, 27)),
+        vec![("buttons".to_string(), Some(CompletionItemKind::MODULE))],
+        "layers nested in a block are relative to it"
+    );
+}
+
+#[test]
+fn test_completion_container_queries() {
+    let language_service = LanguageService::default();
+    let document = create_document(
+        ".layout { container: sidebar / inline-size; --theme: dark; }\n@container  { }\n@container sidebar (in) and  { }\n@container style(--) { }",
+    );
+    let items = |position| {
+        language_service
+            .get_completions(document.clone(), position)
+            .unwrap()
+            .into_iter()
+            .map(|item| (item.label, item.kind))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        items(Position::new(1, 11)),
+        vec![
+            ("sidebar".to_string(), Some(CompletionItemKind::MODULE)),
+            ("style".to_string(), Some(CompletionItemKind::FUNCTION)),
+        ]
+    );
+    let features = items(Position::new(2, 22));
+    assert!(features.contains(&(
+        "inline-size".to_string(),
+        Some(CompletionItemKind::PROPERTY)
+    )));
+    assert!(features.contains(&(
+        "min-aspect-ratio".to_string(),
+        Some(CompletionItemKind::PROPERTY)
+    )));
+    assert!(
+        !features.iter().any(|(label, _)| label == "min-orientation"),
+        "only range features have min- and max- prefixes"
+    );
+    assert_eq!(
+        items(Position::new(2, 28)),
+        vec![("style".to_string(), Some(CompletionItemKind::FUNCTION))]
+    );
+    assert_eq!(
+        items(Position::new(3, 19)),
+        vec![("--theme".to_string(), Some(CompletionItemKind::VARIABLE))]
+    );
+}
+
+#[test]
+fn test_completion_items_are_merged() {
+    let language_service = LanguageService::builder()
+        .completion_provider(Items(vec![
+            CompletionItem {
+                label: "zeta".to_string(),
+                sort_text: Some("0".to_string()),
+                ..Default::default()
+            },
+            CompletionItem::new_simple("beta".to_string(), "first".to_string()),
+        ]))
+        .completion_provider(Items(vec![
+            CompletionItem::new_simple("alpha".to_string(), "second".to_string()),
+            CompletionItem::new_simple("beta".to_string(), "second".to_string()),
+        ]))
+        .build();
+
+    let items = language_service
+        .get_completions(create_document(""), Position::new(0, 0))
+        .unwrap();
+    assert_eq!(
+        items
+            .iter()
+            .map(|item| (item.label.as_str(), item.detail.as_deref()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("zeta", None),
+            ("alpha", Some("second")),
+            ("beta", Some("first")),
+        ]
+    );
+
+    assert_eq!(
+        language_service.get_completions(create_document("a {}"), Position::new(1, 0)),
+        Err(Error::InvalidPosition(Position::new(1, 0)))
+    );
+}
+
+#[test]
+fn test_completion_counter_styles() {
+    let language_service = LanguageService::default();
+    let document = create_document(
+        "@counter-style thumbs { system: cyclic; symbols: \"👍\"; }\n@counter-style stars {  }\n@counter-style dots { system: ; }\nul { list-style-type: ; }",
+    );
+    let items = |position| {
+        language_service
+            .get_completions(document.clone(), position)
+            .unwrap()
+            .into_iter()
+            .map(|item| (item.label, item.kind))
+            .collect::<Vec<_>>()
+    };
+
+    let descriptors = items(Position::new(1, 23));
+    assert!(descriptors.contains(&("system".to_string(), Some(CompletionItemKind::PROPERTY))));
+    assert!(descriptors.contains(&("pad".to_string(), Some(CompletionItemKind::PROPERTY))));
+    assert!(
+        !descriptors.iter().any(|(label, _)| label == "color"),
+        "properties aren't descriptors of @counter-style"
+    );
+    let systems = items(Position::new(2, 30));
+    assert!(systems.contains(&("extends".to_string(), Some(CompletionItemKind::VALUE))));
+    assert_eq!(
+        items(Position::new(3, 22))
+            .into_iter()
+            .filter(|(_, kind)| *kind == Some(CompletionItemKind::ENUM_MEMBER))
+            .collect::<Vec<_>>(),
+        vec![
+            ("dots".to_string(), Some(CompletionItemKind::ENUM_MEMBER)),
+            ("stars".to_string(), Some(CompletionItemKind::ENUM_MEMBER)),
+            ("thumbs".to_string(), Some(CompletionItemKind::ENUM_MEMBER)),
+        ]
+    );
+}
+
+#[test]
+fn test_completion_font_descriptors() {
+    let language_service = LanguageService::default();
+    let document = create_document(
+        "@font-face {  }\n@font-face { font-display: ; }\n@font-palette-values --warm {  }",
+    );
+    let labels = |position| {
+        language_service
+            .get_completions(document.clone(), position)
+            .unwrap()
+            .into_iter()
+            .map(|item| item.label)
+            .collect::<Vec<_>>()
+    };
+
+    let descriptors = labels(Position::new(0, 13));
+    assert!(descriptors.contains(&"src".to_string()));
+    assert!(descriptors.contains(&"unicode-range".to_string()));
+    assert_eq!(
+        labels(Position::new(1, 27)),
+        vec!["auto", "block", "fallback", "optional", "swap"]
+    );
+    assert_eq!(
+        labels(Position::new(2, 30)),
+        vec!["base-palette", "font-family", "override-colors"]
+    );
+}
+
+#[test]
+fn test_completion_pages() {
+    let language_service = LanguageService::default();
+    let document = create_document("@page : {  }\n@page { size: ; @ }\n@page { @top-center {  } }");
+    let labels = |position| {
+        language_service
+            .get_completions(document.clone(), position)
+            .unwrap()
+            .into_iter()
+            .map(|item| item.label)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        labels(Position::new(0, 7)),
+        vec!["blank", "first", "left", "right"]
+    );
+    let properties = labels(Position::new(0, 10));
+    assert!(properties.contains(&"size".to_string()));
+    assert!(properties.contains(&"margin".to_string()));
+    assert!(!properties.contains(&"display".to_string()));
+    assert!(!properties.contains(&"content".to_string()));
+    assert!(labels(Position::new(1, 14)).contains(&"landscape".to_string()));
+    let margin_boxes = labels(Position::new(1, 17));
+    assert!(margin_boxes.contains(&"top-center".to_string()));
+    assert!(margin_boxes.contains(&"bottom-left-corner".to_string()));
+    let margin_box_properties = labels(Position::new(2, 22));
+    assert!(margin_box_properties.contains(&"content".to_string()));
+    assert!(!margin_box_properties.contains(&"size".to_string()));
+}
+
+#[test]
+fn test_completion_view_transitions() {
+    let language_service = LanguageService::default();
+    let document = create_document("@view-transition {  }\n@view-transition { navigation: ; }\n@");
+    let labels = |position| {
+        language_service
+            .get_completions(document.clone(), position)
+            .unwrap()
+            .into_iter()
+            .map(|item| item.label)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(labels(Position::new(0, 18)), vec!["navigation", "types"]);
+    assert_eq!(labels(Position::new(1, 31)), vec!["auto", "none"]);
+    let at_rules = labels(Position::new(2, 1));
+    assert!(at_rules.contains(&"view-transition".to_string()));
+    assert!(at_rules.contains(&"starting-style".to_string()));
+}
+
+#[test]
+fn test_completion_keyframe_selectors() {
+    let language_service = LanguageService::default();
+    let document = create_document(
+        "@keyframes fade {  }\n@keyframes spin { from { opacity: 0; } 50% { opacity: 1; }  }",
+    );
+    let labels = |position| {
+        language_service
+            .get_completions(document.clone(), position)
+            .unwrap()
+            .into_iter()
+            .map(|item| item.label)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        labels(Position::new(0, 18)),
+        vec!["0%", "100%", "25%", "50%", "75%", "from", "to"]
+    );
+    assert_eq!(
+        labels(Position::new(1, 59)),
+        vec!["100%", "25%", "75%", "to"],
+        "the offsets with a keyframe aren't offered"
+    );
+    assert!(
+        !labels(Position::new(1, 25)).contains(&"to".to_string()),
+        "keyframe selectors aren't offered in keyframes"
+    );
+}
+
+#[test]
+fn test_completion_emmet_abbreviations() {
+    let document = create_document(
+        ".a { m10-2e }\n.b { color: red; dib }\n@media print { posa }\n.c { color: c#f }\n.d { content: \"café",
+    );
+    let snippets = |language_service: &LanguageService, position| {
+        language_service
+            .get_completions(document.clone(), position)
+            .unwrap()
+            .into_iter()
+            .filter(|item| item.kind == Some(CompletionItemKind::SNIPPET))
+            .collect::<Vec<_>>()
+    };
+
+    let language_service = LanguageService::default();
+    assert_eq!(
+        snippets(&language_service, Position::new(0, 11)),
+        vec![],
+        "abbreviations are disabled by default"
+    );
+
+    let language_service = LanguageService::builder()
+        .settings(Settings {
+            completion: CompletionSettings {
+                emmet_abbreviations: true,
+            },
+            ..Default::default()
+        })
+        .build();
+    let items = snippets(&language_service, Position::new(0, 11));
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].label, "m10-2e");
+    assert_eq!(items[0].detail.as_deref(), Some("margin: 10px 2em;"));
+    assert_eq!(
+        items[0].text_edit,
+        Some(CompletionTextEdit::Edit(TextEdit::new(
+            Range::new(Position::new(0, 5), Position::new(0, 11)),
+            "margin: 10px 2em;".to_string()
+        )))
+    );
+    assert_eq!(
+        snippets(&language_service, Position::new(1, 20))
+            .into_iter()
+            .map(|item| item.detail)
+            .collect::<Vec<_>>(),
+        vec![Some("display: inline-block;".to_string())]
+    );
+    assert_eq!(
+        snippets(&language_service, Position::new(2, 19)),
+        vec![],
+        "`@media` blocks hold rules, not declarations"
+    );
+    assert_eq!(
+        snippets(&language_service, Position::new(3, 15)),
+        vec![],
+        "values aren't abbreviations"
+    );
+    assert_eq!(
+        snippets(&language_service, Position::new(4, 19)),
+        vec![],
+        "abbreviations don't start after a non-ASCII character"
+    );
+}
+
+#[test]
+fn test_completion_properties() {
+    let language_service = LanguageService::default();
+    let document = create_document(
+        ".a {  }\n.b { disp: block; display: fl; --gap: ; }\n@font-face {  }\n@media print { .c {  } }",
+    );
+    let items = |position| {
+        language_service
+            .get_completions(document.clone(), position)
+            .unwrap()
+    };
+    let find = |items: &[CompletionItem], label: &str| {
+        items.iter().find(|item| item.label == label).cloned()
+    };
+
+    // New declarations are inserted with their colon and semicolon
+    let properties = items(Position::new(0, 4));
+    let display = find(&properties, "display").unwrap();
+    assert_eq!(display.kind, Some(CompletionItemKind::PROPERTY));
+    assert_eq!(display.insert_text.as_deref(), Some("display: $0;"));
+    assert!(matches!(
+        display.documentation,
+        Some(Documentation::MarkupContent(MarkupContent { value, .. })) if value.contains("MDN Reference")
+    ));
+    assert!(find(&items(Position::new(3, 19)), "display").is_some());
+    // The name of an existing declaration is replaced on its own
+    let display = find(&items(Position::new(1, 9)), "display").unwrap();
+    assert_eq!(display.insert_text, None);
+
+    let values = items(Position::new(1, 29));
+    assert_eq!(
+        find(&values, "flex").and_then(|item| item.kind),
+        Some(CompletionItemKind::VALUE)
+    );
+    assert_eq!(
+        find(&values, "inherit").and_then(|item| item.detail),
+        Some("CSS-wide keyword".to_string())
+    );
+    assert!(find(&values, "display").is_none());
+    assert!(
+        items(Position::new(1, 38)).is_empty(),
+        "custom properties have no known values"
+    );
+
+    // The blocks of at-rules with descriptors offer their descriptors instead
+    let descriptors = items(Position::new(2, 13));
+    assert!(find(&descriptors, "src").is_some());
+    assert!(find(&descriptors, "display").is_none());
+}
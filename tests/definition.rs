@@ -0,0 +1,140 @@
+#![cfg(feature = "definition")]
+
+use csslsrs::service::LanguageService;
+use lsp_types::{Location, Position, Range, TextDocumentItem, Uri};
+use std::str::FromStr;
+
+fn open_scss_document(ls: &LanguageService, uri: &str, text: &str) {
+    ls.open_document(TextDocumentItem {
+        uri: Uri::from_str(uri).unwrap(),
+        language_id: "scss".to_string(),
+        version: 1,
+        text: text.to_string(),
+    });
+}
+
+#[test]
+fn test_definition_of_scss_variable() {
+    let ls = LanguageService::default();
+    open_scss_document(
+        &ls,
+        "file:///project/main.scss",
+        "$gap: 4px;\n.a { margin: $gap; }",
+    );
+
+    assert_eq!(
+        ls.get_definition_for_uri(
+            &Uri::from_str("file:///project/main.scss").unwrap(),
+            Position::new(1, 15)
+        )
+        .unwrap(),
+        vec![Location::new(
+            Uri::from_str("file:///project/main.scss").unwrap(),
+            Range::new(Position::new(0, 0), Position::new(0, 4)),
+        )]
+    );
+}
+
+#[test]
+fn test_definition_of_scss_variable_in_module() {
+    let ls = LanguageService::default();
+    open_scss_document(&ls, "file:///project/_colors.scss", "$primary: #ff0000;");
+    open_scss_document(&ls, "file:///project/other.scss", "$primary: blue;");
+    open_scss_document(
+        &ls,
+        "file:///project/main.scss",
+        "@use \"colors\";\n.a { color: colors.$primary; }",
+    );
+
+    assert_eq!(
+        ls.get_definition_for_uri(
+            &Uri::from_str("file:///project/main.scss").unwrap(),
+            Position::new(1, 22)
+        )
+        .unwrap(),
+        vec![Location::new(
+            Uri::from_str("file:///project/_colors.scss").unwrap(),
+            Range::new(Position::new(0, 0), Position::new(0, 8)),
+        )]
+    );
+}
+
+#[test]
+fn test_definition_outside_of_symbols() {
+    let ls = LanguageService::default();
+    open_scss_document(&ls, "file:///project/main.scss", ".a { color: red; }");
+
+    assert_eq!(
+        ls.get_definition_for_uri(
+            &Uri::from_str("file:///project/main.scss").unwrap(),
+            Position::new(0, 14)
+        )
+        .unwrap(),
+        Vec::new()
+    );
+}
+
+#[test]
+fn test_definition_of_scss_mixin_and_function() {
+    let ls = LanguageService::default();
+    open_scss_document(
+        &ls,
+        "file:///project/_buttons.scss",
+        "@mixin primary {\n  color: red;\n}",
+    );
+    open_scss_document(
+        &ls,
+        "file:///project/main.scss",
+        "@use \"buttons\";\n@function double($n) {\n  @return $n * 2;\n}\n.a {\n  @include buttons.primary;\n  width: double(2px);\n}",
+    );
+    let definition = |position| {
+        ls.get_definition_for_uri(
+            &Uri::from_str("file:///project/main.scss").unwrap(),
+            position,
+        )
+        .unwrap()
+    };
+
+    assert_eq!(
+        definition(Position::new(5, 21)),
+        vec![Location::new(
+            Uri::from_str("file:///project/_buttons.scss").unwrap(),
+            Range::new(Position::new(0, 7), Position::new(0, 14)),
+        )]
+    );
+    assert_eq!(
+        definition(Position::new(6, 10)),
+        vec![Location::new(
+            Uri::from_str("file:///project/main.scss").unwrap(),
+            Range::new(Position::new(1, 10), Position::new(1, 16)),
+        )]
+    );
+}
+
+#[test]
+fn test_definition_of_scss_placeholder() {
+    let ls = LanguageService::default();
+    open_scss_document(&ls, "file:///project/_base.scss", "%card { padding: 0; }");
+    open_scss_document(&ls, "file:///project/other.scss", "%card { margin: 0; }");
+    open_scss_document(
+        &ls,
+        "file:///project/main.scss",
+        "@use \"base\";\n.a { @extend %card; }",
+    );
+
+    let expected = vec![Location::new(
+        Uri::from_str("file:///project/_base.scss").unwrap(),
+        Range::new(Position::new(0, 1), Position::new(0, 5)),
+    )];
+    // From the name of the placeholder, or its `%`
+    for character in [15, 13] {
+        assert_eq!(
+            ls.get_definition_for_uri(
+                &Uri::from_str("file:///project/main.scss").unwrap(),
+                Position::new(1, character)
+            )
+            .unwrap(),
+            expected
+        );
+    }
+}
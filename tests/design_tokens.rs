@@ -0,0 +1,125 @@
+#![cfg(all(feature = "colors", feature = "completion", feature = "diagnostics"))]
+
+use csslsrs::{design_tokens::DesignTokens, service::LanguageService};
+use lsp_types::{
+    Color, ColorInformation, CompletionItemKind, Diagnostic, DiagnosticSeverity, NumberOrString,
+    Position, Range, TextDocumentItem, Uri,
+};
+use serde_json::json;
+use std::str::FromStr;
+
+const TOKENS: &str = r##"{
+    "color": {
+        "$type": "color",
+        "brand": { "$value": "#0055FF", "$description": "The blue of the logo." }
+    },
+    "space": {
+        "sm": { "$value": "4px", "$type": "dimension" }
+    }
+}"##;
+
+fn create_document(text: &str) -> TextDocumentItem {
+    TextDocumentItem {
+        uri: Uri::from_str("file:///test.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: text.to_string(),
+    }
+}
+
+fn language_service() -> LanguageService {
+    LanguageService::builder()
+        .design_tokens(DesignTokens::from_json(TOKENS).unwrap())
+        .build()
+}
+
+#[test]
+fn test_design_token_completion() {
+    let language_service = language_service();
+    let document = create_document("a { color: var(--b); }");
+
+    let items = language_service
+        .get_completions(document.clone(), Position::new(0, 18))
+        .unwrap();
+    assert_eq!(
+        items
+            .iter()
+            .map(|item| (item.label.as_str(), item.kind, item.detail.as_deref()))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "--color-brand",
+                Some(CompletionItemKind::COLOR),
+                Some("#0055FF")
+            ),
+            (
+                "--space-sm",
+                Some(CompletionItemKind::VARIABLE),
+                Some("4px")
+            ),
+        ]
+    );
+
+    // Design tokens are only offered inside `var()`, not with the properties
+    assert!(!language_service
+        .get_completions(document, Position::new(0, 8))
+        .unwrap()
+        .iter()
+        .any(|item| item.label.starts_with("--")));
+}
+
+#[test]
+fn test_design_token_colors() {
+    let language_service = language_service();
+    let document =
+        create_document(".button { color: var(--color-brand); border-color: var(--space-sm); }");
+
+    assert_eq!(
+        language_service.get_document_colors(document),
+        vec![ColorInformation {
+            range: Range::new(Position::new(0, 17), Position::new(0, 35)),
+            color: Color {
+                red: 0.0,
+                green: 85.0 / 255.0,
+                blue: 1.0,
+                alpha: 1.0,
+            },
+        }]
+    );
+}
+
+#[test]
+fn test_design_token_literals() {
+    let language_service = LanguageService::default();
+    let document = create_document(
+        ":root {\n  --color-brand: #0055ff;\n}\n.button {\n  color: #0055ff;\n  padding: 4px;\n  margin: 8px;\n}\n",
+    );
+    assert_eq!(language_service.get_diagnostics(document.clone()), vec![]);
+
+    // Diagnostics computed before the tokens were set aren't reused
+    language_service.set_design_tokens(DesignTokens::from_json(TOKENS).unwrap());
+    let diagnostic = |range, message: &str, fix: &str| Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::INFORMATION),
+        code: Some(NumberOrString::String("design-token-literal".to_string())),
+        source: Some("csslsrs".to_string()),
+        message: message.to_string(),
+        data: Some(json!({ "fix": fix })),
+        ..Default::default()
+    };
+    assert_eq!(
+        language_service.get_diagnostics(document),
+        vec![
+            diagnostic(
+                Range::new(Position::new(4, 9), Position::new(4, 16)),
+                "`#0055ff` is the value of the design token `--color-brand`, use `var(--color-brand)` instead",
+                "var(--color-brand)",
+            ),
+            diagnostic(
+                Range::new(Position::new(5, 11), Position::new(5, 14)),
+                "`4px` is the value of the design token `--space-sm`, use `var(--space-sm)` instead",
+                "var(--space-sm)",
+            ),
+        ]
+    );
+}
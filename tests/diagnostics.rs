@@ -0,0 +1,1350 @@
+#![cfg(feature = "diagnostics")]
+
+use csslsrs::{
+    biome_css_syntax::CssSyntaxKind,
+    cancellation::CancellationToken,
+    features::diagnostics::{DiagnosticRule, RuleContext, RuleDiagnostic},
+    file_system::{FileStat, FileSystemProvider, FileType},
+    progress::ProgressReporter,
+    service::LanguageService,
+    settings::{
+        ColorContrastSettings, ContrastLevel, DiagnosticSettings, RuleLevel, Settings, Severity,
+    },
+};
+use lsp_types::{
+    Diagnostic, DiagnosticSeverity, DocumentDiagnosticReport, NumberOrString, Position,
+    PreviousResultId, Range, TextDocumentItem, TextEdit, Uri, WorkspaceDocumentDiagnosticReport,
+};
+use std::{io, str::FromStr};
+
+/// Only allows hex colors in the design tokens of the project.
+struct NoRawColors;
+
+impl DiagnosticRule for NoRawColors {
+    fn name(&self) -> &str {
+        "no-raw-colors"
+    }
+
+    fn kinds(&self) -> Option<&'static [CssSyntaxKind]> {
+        Some(&[CssSyntaxKind::CSS_COLOR])
+    }
+
+    fn check(&self, context: &RuleContext) -> Vec<RuleDiagnostic> {
+        if context.uri().as_str().ends_with("/tokens.css") {
+            return Vec::new();
+        }
+
+        let color = context.node().text_trimmed().to_string();
+        let diagnostic = RuleDiagnostic::new(
+            context.node().text_trimmed_range(),
+            format!("Use a design token instead of {color}"),
+        );
+        if color == "#f00" {
+            vec![diagnostic.with_severity(DiagnosticSeverity::ERROR)]
+        } else {
+            vec![diagnostic]
+        }
+    }
+}
+
+fn create_document(uri: &str, text: &str) -> TextDocumentItem {
+    TextDocumentItem {
+        uri: Uri::from_str(uri).unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: text.to_string(),
+    }
+}
+
+fn diagnostic(range: Range, message: &str, severity: DiagnosticSeverity) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        code: Some(NumberOrString::String("no-raw-colors".to_string())),
+        source: Some("csslsrs".to_string()),
+        message: message.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_custom_diagnostic_rule() {
+    let language_service = LanguageService::builder()
+        .diagnostic_rule(NoRawColors)
+        .build();
+
+    let document = create_document(
+        "file:///project/button.css",
+        ".button {\n  color: #f00;\n  background: #00f;\n}\n",
+    );
+    assert_eq!(
+        language_service.get_diagnostics(document),
+        vec![
+            diagnostic(
+                Range::new(Position::new(1, 9), Position::new(1, 13)),
+                "Use a design token instead of #f00",
+                DiagnosticSeverity::ERROR,
+            ),
+            diagnostic(
+                Range::new(Position::new(2, 14), Position::new(2, 18)),
+                "Use a design token instead of #00f",
+                DiagnosticSeverity::WARNING,
+            ),
+        ]
+    );
+
+    let tokens = create_document("file:///project/tokens.css", ":root {\n  --red: #f00;\n}\n");
+    assert_eq!(language_service.get_diagnostics(tokens), vec![]);
+}
+
+#[test]
+fn test_add_diagnostic_rule() {
+    let mut language_service = LanguageService::default();
+
+    let document = create_document("file:///project/button.css", "a { color: #123; }");
+    language_service.open_document(document.clone());
+    assert_eq!(
+        language_service.get_diagnostics_for_uri(&document.uri),
+        Ok(vec![])
+    );
+
+    // Diagnostics computed before the rule was added aren't reused
+    language_service.add_diagnostic_rule(NoRawColors);
+    assert_eq!(
+        language_service.get_diagnostics_for_uri(&document.uri),
+        Ok(vec![diagnostic(
+            Range::new(Position::new(0, 11), Position::new(0, 15)),
+            "Use a design token instead of #123",
+            DiagnosticSeverity::WARNING,
+        )])
+    );
+}
+
+/// The stylesheets of a folder, keyed by URI.
+struct MemoryFileSystem(Vec<(&'static str, &'static str)>);
+
+impl FileSystemProvider for MemoryFileSystem {
+    fn read_file(&self, uri: &Uri) -> io::Result<String> {
+        self.0
+            .iter()
+            .find(|(file, _)| *file == uri.as_str())
+            .map(|(_, text)| text.to_string())
+            .ok_or_else(|| io::ErrorKind::NotFound.into())
+    }
+
+    fn read_directory(&self, uri: &Uri) -> io::Result<Vec<(String, FileType)>> {
+        let prefix = format!("{}/", uri.as_str());
+        Ok(self
+            .0
+            .iter()
+            .filter_map(|(file, _)| file.strip_prefix(&prefix))
+            .map(|name| (name.to_string(), FileType::File))
+            .collect())
+    }
+
+    fn stat(&self, uri: &Uri) -> io::Result<FileStat> {
+        let text = FileSystemProvider::read_file(self, uri)?;
+        Ok(FileStat {
+            file_type: FileType::File,
+            size: text.len() as u64,
+            mtime: 0,
+        })
+    }
+}
+
+#[test]
+fn test_workspace_diagnostics() {
+    let language_service = LanguageService::builder()
+        .diagnostic_rule(NoRawColors)
+        .file_system_provider(MemoryFileSystem(vec![
+            ("file:///project/a.css", "a { color: #123; }"),
+            ("file:///project/b.css", "b {}"),
+        ]))
+        .build();
+    let document = create_document("file:///project/c.css", "c { color: #456; }");
+    language_service.open_document(document.clone());
+    language_service
+        .index_workspace_folder(
+            &Uri::from_str("file:///project").unwrap(),
+            &CancellationToken::default(),
+        )
+        .unwrap();
+
+    // Unopened files are reported along with the open documents, without a version
+    let report = language_service
+        .get_workspace_diagnostics(&[], &CancellationToken::default(), &mut ())
+        .unwrap();
+    let full_reports = report
+        .items
+        .iter()
+        .map(|item| match item {
+            WorkspaceDocumentDiagnosticReport::Full(full) => (
+                full.uri.as_str(),
+                full.version,
+                full.full_document_diagnostic_report.items.len(),
+            ),
+            WorkspaceDocumentDiagnosticReport::Unchanged(_) => {
+                panic!("unexpected unchanged report")
+            }
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        full_reports,
+        vec![
+            ("file:///project/a.css", None, 1),
+            ("file:///project/b.css", None, 0),
+            ("file:///project/c.css", Some(1), 1),
+        ]
+    );
+
+    // Pulling again with the result ids of the reports only reports what changed
+    let mut previous_result_ids = report
+        .items
+        .iter()
+        .map(|item| match item {
+            WorkspaceDocumentDiagnosticReport::Full(full) => PreviousResultId {
+                uri: full.uri.clone(),
+                value: full
+                    .full_document_diagnostic_report
+                    .result_id
+                    .clone()
+                    .unwrap(),
+            },
+            WorkspaceDocumentDiagnosticReport::Unchanged(_) => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+    previous_result_ids.push(PreviousResultId {
+        uri: Uri::from_str("file:///project/removed.css").unwrap(),
+        value: "0".to_string(),
+    });
+    let report = language_service
+        .get_workspace_diagnostics(&previous_result_ids, &CancellationToken::default(), &mut ())
+        .unwrap();
+    assert_eq!(report.items.len(), 4);
+    assert!(report.items[..3]
+        .iter()
+        .all(|item| matches!(item, WorkspaceDocumentDiagnosticReport::Unchanged(_))));
+    // Documents that aren't indexed anymore get an empty report
+    assert!(matches!(
+        &report.items[3],
+        WorkspaceDocumentDiagnosticReport::Full(full)
+            if full.uri.as_str() == "file:///project/removed.css"
+                && full.full_document_diagnostic_report.items.is_empty()
+    ));
+
+    // Document reports use the same result ids
+    let result_id = previous_result_ids[2].value.clone();
+    assert!(matches!(
+        language_service.get_diagnostic_report_for_uri(&document.uri, Some(&result_id)),
+        Ok(DocumentDiagnosticReport::Unchanged(_))
+    ));
+    assert!(matches!(
+        language_service.get_diagnostic_report_for_uri(&document.uri, Some("0")),
+        Ok(DocumentDiagnosticReport::Full(full))
+            if full.full_document_diagnostic_report.result_id == Some(result_id)
+    ));
+}
+
+#[test]
+fn test_undefined_placeholder_diagnostics() {
+    let ls = LanguageService::default();
+    ls.open_document(TextDocumentItem {
+        language_id: "scss".to_string(),
+        ..create_document("file:///project/_base.scss", "%card { padding: 0; }")
+    });
+    let uri = Uri::from_str("file:///project/main.scss").unwrap();
+    ls.open_document(TextDocumentItem {
+        language_id: "scss".to_string(),
+        ..create_document(
+            uri.as_str(),
+            "@use \"base\";\n%local { margin: 0; }\n.a { @extend %card; @extend %local; @extend %missing; @extend %maybe !optional; }",
+        )
+    });
+
+    assert_eq!(
+        ls.get_diagnostics_for_uri(&uri).unwrap(),
+        vec![Diagnostic {
+            range: Range::new(Position::new(2, 44), Position::new(2, 52)),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String("undefined-placeholder".to_string())),
+            source: Some("csslsrs".to_string()),
+            message: "The placeholder selector `%missing` is not declared".to_string(),
+            ..Default::default()
+        }]
+    );
+}
+
+#[test]
+fn test_nesting_selector_position_diagnostics() {
+    let ls = LanguageService::default();
+    let document = create_document(
+        "file:///test.css",
+        ".a { &div { } &.b { } .c :is(&) { } }\n& .d { }\n@scope (.e) { & > .f { } }",
+    );
+
+    let diagnostics = ls
+        .get_diagnostics(document)
+        .into_iter()
+        .map(|diagnostic| (diagnostic.range, diagnostic.severity, diagnostic.message))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        diagnostics,
+        vec![
+            (
+                Range::new(Position::new(0, 5), Position::new(0, 9)),
+                Some(DiagnosticSeverity::ERROR),
+                "`&div` is invalid, a type selector must start the compound selector: write `div&`"
+                    .to_string()
+            ),
+            (
+                Range::new(Position::new(1, 0), Position::new(1, 1)),
+                Some(DiagnosticSeverity::WARNING),
+                "`&` isn't in a nested rule or in `@scope`, so it matches `:scope`, the root element"
+                    .to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_view_transition_diagnostics() {
+    let ls = LanguageService::default();
+    let document = create_document(
+        "file:///test.css",
+        "@view-transition { navigation: auto; types: slide; }\n.a { transition: opacity 1s; @starting-style { opacity: 0; } }\n@starting-style { .b { opacity: 0; } }",
+    );
+
+    assert_eq!(
+        ls.get_diagnostics(document),
+        vec![],
+        "`@view-transition` isn't reported as a nested rule, nor the declarations of `@starting-style`"
+    );
+}
+
+#[test]
+fn test_undeclared_layer_diagnostics() {
+    let ls = LanguageService::default();
+    ls.open_document(create_document(
+        "file:///project/layers.css",
+        "@layer base;",
+    ));
+    let uri = Uri::from_str("file:///project/main.css").unwrap();
+    ls.open_document(create_document(
+        uri.as_str(),
+        "@import url(reset.css) layer(reset);\n@layer base { .a { color: red; } }\n@layer components { .b { color: red; } }",
+    ));
+
+    let diagnostics = ls
+        .get_diagnostics_for_uri(&uri)
+        .unwrap()
+        .into_iter()
+        .map(|diagnostic| (diagnostic.range, diagnostic.severity, diagnostic.code))
+        .collect::<Vec<_>>();
+    let undeclared = Some(NumberOrString::String("undeclared-layer".to_string()));
+    assert_eq!(
+        diagnostics,
+        vec![
+            (
+                Range::new(Position::new(0, 29), Position::new(0, 34)),
+                Some(DiagnosticSeverity::INFORMATION),
+                undeclared.clone()
+            ),
+            (
+                Range::new(Position::new(2, 7), Position::new(2, 17)),
+                Some(DiagnosticSeverity::INFORMATION),
+                undeclared
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_scope_boundary_diagnostics() {
+    let ls = LanguageService::default();
+    let document = create_document(
+        "file:///test.css",
+        "@scope (.card::before) to (.content:after) { .a { color: red; } }\n@scope (.card) to (.content) { }",
+    );
+
+    let diagnostics = ls
+        .get_diagnostics(document)
+        .into_iter()
+        .map(|diagnostic| (diagnostic.range, diagnostic.severity, diagnostic.message))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        diagnostics,
+        vec![
+            (
+                Range::new(Position::new(0, 13), Position::new(0, 21)),
+                Some(DiagnosticSeverity::ERROR),
+                "The pseudo-element `::before` can't be the root of a scope".to_string()
+            ),
+            (
+                Range::new(Position::new(0, 35), Position::new(0, 41)),
+                Some(DiagnosticSeverity::ERROR),
+                "The pseudo-element `:after` can't be the limit of a scope".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_container_query_diagnostics() {
+    let ls = LanguageService::default();
+    ls.open_document(create_document(
+        "file:///project/layout.css",
+        ".layout { container: sidebar / inline-size; }",
+    ));
+    let uri = Uri::from_str("file:///project/main.css").unwrap();
+    ls.open_document(create_document(
+        uri.as_str(),
+        "@container sidebar (min-widht: 400px) { }\n@container main (min-width > 400px) and (orientation) { }\n@container style(--theme: dark) { }",
+    ));
+
+    let diagnostics = ls
+        .get_diagnostics_for_uri(&uri)
+        .unwrap()
+        .into_iter()
+        .map(|diagnostic| (diagnostic.range, diagnostic.severity, diagnostic.code))
+        .collect::<Vec<_>>();
+    let code = |code: &str| Some(NumberOrString::String(code.to_string()));
+    assert_eq!(
+        diagnostics,
+        vec![
+            (
+                Range::new(Position::new(0, 20), Position::new(0, 29)),
+                Some(DiagnosticSeverity::WARNING),
+                code("container-query-feature")
+            ),
+            (
+                Range::new(Position::new(1, 11), Position::new(1, 15)),
+                Some(DiagnosticSeverity::WARNING),
+                code("undeclared-container")
+            ),
+            (
+                Range::new(Position::new(1, 17), Position::new(1, 26)),
+                Some(DiagnosticSeverity::ERROR),
+                code("container-query-feature")
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_keyframe_selector_diagnostics() {
+    let ls = LanguageService::default();
+    let document = create_document(
+        "file:///test.css",
+        "@keyframes fade { from { opacity: 0; } 50%, 120% { opacity: 1; } to, 100% { opacity: 1; } 50% { } }",
+    );
+
+    let diagnostics = ls
+        .get_diagnostics(document)
+        .into_iter()
+        .map(|diagnostic| (diagnostic.range, diagnostic.severity, diagnostic.message))
+        .collect::<Vec<_>>();
+    let range = |start, end| Range::new(Position::new(0, start), Position::new(0, end));
+    assert_eq!(
+        diagnostics,
+        vec![
+            (
+                range(44, 48),
+                Some(DiagnosticSeverity::ERROR),
+                "`120%` is outside of `0%` to `100%`, the keyframe is ignored".to_string()
+            ),
+            (
+                range(69, 73),
+                Some(DiagnosticSeverity::WARNING),
+                "`100%` repeats the keyframe selector `to`, their declarations are merged"
+                    .to_string()
+            ),
+            (
+                range(90, 93),
+                Some(DiagnosticSeverity::WARNING),
+                "`50%` repeats the keyframe selector `50%`, their declarations are merged"
+                    .to_string()
+            ),
+        ]
+    );
+
+    // The selectors the parser fails on aren't reported as syntax errors too
+    let diagnostics = ls.get_diagnostics(create_document(
+        "file:///invalid.css",
+        "@keyframes fade { from { opacity: 0; } foo { opacity: 1; } }",
+    ));
+    assert_eq!(
+        diagnostics
+            .into_iter()
+            .map(|diagnostic| (diagnostic.range, diagnostic.code, diagnostic.message))
+            .collect::<Vec<_>>(),
+        vec![(
+            range(39, 42),
+            Some(NumberOrString::String("keyframe-selector".to_string())),
+            "`foo` isn't a keyframe selector, use `from`, `to` or a percentage".to_string()
+        )]
+    );
+}
+
+#[test]
+fn test_property_registration_diagnostics() {
+    let ls = LanguageService::default();
+    let uri = Uri::from_str("file:///project/properties.css").unwrap();
+    ls.open_document(create_document(
+        uri.as_str(),
+        r#"@property --ok { syntax: "<length>+ | auto"; inherits: false; initial-value: 1px 2px; }
+@property --any { syntax: "*"; inherits: true; }
+@property --missing { syntax: "<color>"; }
+@property --bad { syntax: "<lenght>"; inherits: maybe; initial-value: 1px; }
+@property --em { syntax: "<length>"; inherits: false; initial-value: 2em; }
+@property --angles { syntax: "<angle>#"; inherits: false; initial-value: 10deg, red; }"#,
+    ));
+
+    let diagnostics = ls
+        .get_diagnostics_for_uri(&uri)
+        .unwrap()
+        .into_iter()
+        .map(|diagnostic| {
+            assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+            assert_eq!(
+                diagnostic.code,
+                Some(NumberOrString::String("property-registration".to_string()))
+            );
+            (diagnostic.range, diagnostic.message)
+        })
+        .collect::<Vec<_>>();
+    let range = |line, start, end| Range::new(Position::new(line, start), Position::new(line, end));
+    assert_eq!(
+        diagnostics,
+        vec![
+            (
+                range(2, 10, 19),
+                "The `inherits` descriptor is required, the rule is ignored without it".to_string()
+            ),
+            (
+                range(2, 10, 19),
+                "The `initial-value` descriptor is required unless the syntax is `\"*\"`, the rule is ignored without it".to_string()
+            ),
+            (
+                range(3, 26, 36),
+                "Unknown data type `<lenght>`".to_string()
+            ),
+            (
+                range(3, 38, 53),
+                "The `inherits` descriptor must be `true` or `false`".to_string()
+            ),
+            (
+                range(4, 69, 72),
+                "The initial value must be computationally independent, `2em` isn't".to_string()
+            ),
+            (
+                range(5, 73, 83),
+                "The initial value `10deg, red` doesn't match the syntax \"<angle>#\"".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_counter_style_diagnostics() {
+    let ls = LanguageService::default();
+    let uri = Uri::from_str("file:///project/counters.css").unwrap();
+    ls.open_document(create_document(
+        uri.as_str(),
+        r#"@counter-style thumbs { system: cyclic; symbols: "👍"; pad: 2 "0"; range: 1 infinite, 10 20; }
+@counter-style decimal { system: extends thumbs; symbols: a b; }
+@counter-style letters { system: alphabetic; symbols: a; range: 5 2; color: red; }
+@counter-style roman { system: additive; additive-symbols: 1 I, 5 V; pad: "0"; }
+@counter-style bad { system: fixed x; symbols: a; unknown: 1px; }"#,
+    ));
+
+    let diagnostics = ls
+        .get_diagnostics_for_uri(&uri)
+        .unwrap()
+        .into_iter()
+        .map(|diagnostic| (diagnostic.range, diagnostic.severity, diagnostic.code))
+        .collect::<Vec<_>>();
+    let range = |line, start, end| Range::new(Position::new(line, start), Position::new(line, end));
+    let code = |code: &str| Some(NumberOrString::String(code.to_string()));
+    assert_eq!(
+        diagnostics,
+        vec![
+            (
+                range(1, 15, 22),
+                Some(DiagnosticSeverity::ERROR),
+                code("counter-style-descriptor")
+            ),
+            (
+                range(1, 49, 61),
+                Some(DiagnosticSeverity::ERROR),
+                code("counter-style-descriptor")
+            ),
+            (
+                range(2, 15, 22),
+                Some(DiagnosticSeverity::ERROR),
+                code("counter-style-descriptor")
+            ),
+            (
+                range(2, 64, 67),
+                Some(DiagnosticSeverity::ERROR),
+                code("counter-style-descriptor")
+            ),
+            (
+                range(2, 69, 74),
+                Some(DiagnosticSeverity::WARNING),
+                code("unknown-descriptor")
+            ),
+            (
+                range(3, 41, 67),
+                Some(DiagnosticSeverity::ERROR),
+                code("counter-style-descriptor")
+            ),
+            (
+                range(3, 69, 77),
+                Some(DiagnosticSeverity::ERROR),
+                code("counter-style-descriptor")
+            ),
+            (
+                range(4, 21, 36),
+                Some(DiagnosticSeverity::ERROR),
+                code("counter-style-descriptor")
+            ),
+            (
+                range(4, 50, 57),
+                Some(DiagnosticSeverity::WARNING),
+                code("unknown-descriptor")
+            ),
+        ]
+    );
+    let messages = ls
+        .get_diagnostics_for_uri(&uri)
+        .unwrap()
+        .into_iter()
+        .map(|diagnostic| diagnostic.message)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages[4],
+        "`color` is a property, not a descriptor of `@counter-style`"
+    );
+    assert_eq!(
+        messages[8],
+        "Unknown descriptor `unknown` in `@counter-style`"
+    );
+}
+
+#[test]
+fn test_font_descriptor_diagnostics() {
+    let ls = LanguageService::default();
+    let uri = Uri::from_str("file:///project/fonts.css").unwrap();
+    ls.open_document(create_document(
+        uri.as_str(),
+        r#"@font-face { font-family: Inter; src: url(inter.woff2) format("woff2") tech(variations), local(Inter); font-display: swap; }
+@font-face { src: url(a.woff) format("woof"), "inter.ttf", url(b.ttf) tech(colors); font-display: later; color: red; }
+@font-palette-values --warm { font-family: Inter; base-palette: -1; override-colors: 0 red, blue; }"#,
+    ));
+
+    let diagnostics = ls
+        .get_diagnostics_for_uri(&uri)
+        .unwrap()
+        .into_iter()
+        .map(|diagnostic| (diagnostic.range, diagnostic.severity, diagnostic.message))
+        .collect::<Vec<_>>();
+    let range = |line, start, end| Range::new(Position::new(line, start), Position::new(line, end));
+    let error = Some(DiagnosticSeverity::ERROR);
+    let warning = Some(DiagnosticSeverity::WARNING);
+    assert_eq!(
+        diagnostics,
+        vec![
+            (
+                range(1, 1, 10),
+                error,
+                "The `font-family` descriptor is required, the rule is ignored without it"
+                    .to_string()
+            ),
+            (
+                range(1, 38, 42),
+                warning,
+                "Unknown font format `woof`".to_string()
+            ),
+            (
+                range(1, 46, 57),
+                error,
+                "A source must be a `url()`, optionally followed by `format()` and `tech()`, or a `local()`".to_string()
+            ),
+            (
+                range(1, 75, 81),
+                warning,
+                "Unknown font technology `colors`".to_string()
+            ),
+            (
+                range(1, 84, 103),
+                error,
+                "`font-display` must be `auto`, `block`, `swap`, `fallback` or `optional`".to_string()
+            ),
+            (
+                range(1, 105, 110),
+                warning,
+                "`color` is a property, not a descriptor of `@font-face`".to_string()
+            ),
+            (
+                range(2, 50, 66),
+                error,
+                "`base-palette` must be `light`, `dark` or the index of a palette of the font"
+                    .to_string()
+            ),
+            (
+                range(2, 92, 96),
+                error,
+                "Each color override must be an index of the palette and an absolute color, e.g. `0 red`".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_unicode_range_diagnostics() {
+    let ls = LanguageService::default();
+    let uri = Uri::from_str("file:///project/fonts.css").unwrap();
+    ls.open_document(create_document(
+        uri.as_str(),
+        r#"@font-face { font-family: Inter; src: local(Inter); unicode-range: U+0025-00FF, u+4??, U+26; }
+@font-face { font-family: Inter; src: local(Inter); unicode-range: U+00FF-0025, U+1?3, U+110000, ; }"#,
+    ));
+
+    let diagnostics = ls
+        .get_diagnostics_for_uri(&uri)
+        .unwrap()
+        .into_iter()
+        .map(|diagnostic| (diagnostic.range, diagnostic.severity, diagnostic.message))
+        .collect::<Vec<_>>();
+    let range = |line, start, end| Range::new(Position::new(line, start), Position::new(line, end));
+    let error = Some(DiagnosticSeverity::ERROR);
+    assert_eq!(
+        diagnostics,
+        vec![
+            (
+                range(1, 67, 78),
+                error,
+                "The range `U+00FF-0025` is reversed, its start is greater than its end"
+                    .to_string()
+            ),
+            (
+                range(1, 80, 85),
+                error,
+                "`U+1?3` isn't a unicode range, e.g. `U+26`, `U+0025-00FF` or `U+4??`".to_string()
+            ),
+            (
+                range(1, 87, 95),
+                error,
+                "The range `U+110000` ends after `U+10FFFF`, the last code point".to_string()
+            ),
+            (
+                range(1, 95, 96),
+                error,
+                "Expected a unicode range, e.g. `U+26`, `U+0025-00FF` or `U+4??`".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_page_property_diagnostics() {
+    let ls = LanguageService::default();
+    let uri = Uri::from_str("file:///project/print.css").unwrap();
+    ls.open_document(create_document(
+        uri.as_str(),
+        r#"@page :first { size: A4 landscape; margin: 2cm; display: flex; --gutter: 1cm; }
+@page { @top-center { content: "Title"; size: A5; font-size: 9pt; } }"#,
+    ));
+
+    let diagnostics = ls
+        .get_diagnostics_for_uri(&uri)
+        .unwrap()
+        .into_iter()
+        .map(|diagnostic| (diagnostic.range, diagnostic.severity, diagnostic.message))
+        .collect::<Vec<_>>();
+    let range = |line, start, end| Range::new(Position::new(line, start), Position::new(line, end));
+    let warning = Some(DiagnosticSeverity::WARNING);
+    assert_eq!(
+        diagnostics,
+        vec![
+            (
+                range(0, 48, 55),
+                warning,
+                "`display` doesn't apply to the page context, it is ignored in `@page`".to_string()
+            ),
+            (
+                range(1, 40, 44),
+                warning,
+                "`size` doesn't apply to the page context, it is ignored in `@top-center`"
+                    .to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_color_contrast_diagnostics() {
+    let uri = Uri::from_str("file:///project/theme.css").unwrap();
+    let document = create_document(
+        uri.as_str(),
+        r#".a { color: #777; background-color: #fff; }
+.b { color: #777; background: white; font-size: 24px; }
+.c { color: #767676; }
+.d { color: var(--text); background: white; }
+.e { color: #ccc; background: url(noise.png); .f { color: #eee; } }
+.g { background: black; .h { color: #333; } }"#,
+    );
+    let diagnostics = |settings: ColorContrastSettings| {
+        let ls = LanguageService::builder()
+            .settings(Settings {
+                diagnostics: DiagnosticSettings {
+                    color_contrast: settings,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .build();
+        ls.get_diagnostics(document.clone())
+            .into_iter()
+            .map(|diagnostic| (diagnostic.range, diagnostic.severity, diagnostic.message))
+            .collect::<Vec<_>>()
+    };
+    let range = |line, start, end| Range::new(Position::new(line, start), Position::new(line, end));
+
+    assert_eq!(
+        diagnostics(ColorContrastSettings::default()),
+        Vec::new(),
+        "the lint is disabled by default"
+    );
+    assert_eq!(
+        diagnostics(ColorContrastSettings {
+            enabled: true,
+            ..Default::default()
+        }),
+        vec![
+            (
+                range(0, 12, 16),
+                Some(DiagnosticSeverity::WARNING),
+                "The contrast of `#777` on `#fff` is 4.47:1, below the 4.5:1 required by WCAG AA for normal text"
+                    .to_string()
+            ),
+            (
+                range(5, 36, 40),
+                Some(DiagnosticSeverity::WARNING),
+                "The contrast of `#333` on `black` is 1.66:1, below the 4.5:1 required by WCAG AA for normal text"
+                    .to_string()
+            ),
+        ]
+    );
+    assert_eq!(
+        diagnostics(ColorContrastSettings {
+            enabled: true,
+            level: ContrastLevel::Aaa,
+            default_background: Some("#fff".to_string()),
+            severity: Some(Severity::Error),
+        }),
+        vec![
+            (
+                range(0, 12, 16),
+                Some(DiagnosticSeverity::ERROR),
+                "The contrast of `#777` on `#fff` is 4.47:1, below the 7:1 required by WCAG AAA for normal text"
+                    .to_string()
+            ),
+            (
+                range(1, 12, 16),
+                Some(DiagnosticSeverity::ERROR),
+                "The contrast of `#777` on `white` is 4.47:1, below the 4.5:1 required by WCAG AAA for large text"
+                    .to_string()
+            ),
+            (
+                range(2, 12, 19),
+                Some(DiagnosticSeverity::ERROR),
+                "The contrast of `#767676` on `#fff` is 4.54:1, below the 7:1 required by WCAG AAA for normal text"
+                    .to_string()
+            ),
+            (
+                range(5, 36, 40),
+                Some(DiagnosticSeverity::ERROR),
+                "The contrast of `#333` on `black` is 1.66:1, below the 7:1 required by WCAG AAA for normal text"
+                    .to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_redundant_import_diagnostics() {
+    let ls = LanguageService::default();
+    let uri = Uri::from_str("file:///project/main.css").unwrap();
+    ls.open_document(create_document(
+        uri.as_str(),
+        "@import url(base.css);\n@import \"./base.css\" print;\n@import \"base.css\" supports(display: grid);\n@import \"../project/base.css\";\n.a { color: red; }",
+    ));
+
+    let diagnostics = ls.get_diagnostics_for_uri(&uri).unwrap();
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic {
+            range: Range::new(Position::new(3, 0), Position::new(3, 30)),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String("redundant-import".to_string())),
+            source: Some("csslsrs".to_string()),
+            message: "`../project/base.css` is already imported by a previous `@import` with the same conditions".to_string(),
+            data: Some(serde_json::json!({
+                "fix": "",
+                "range": Range::new(Position::new(3, 0), Position::new(4, 0)),
+            })),
+            ..Default::default()
+        }],
+        "Imports for other media or supports conditions aren't redundant"
+    );
+
+    // The fix removes the whole line of the import
+    let edit = ls.get_fix_all_edit(&uri).unwrap();
+    assert_eq!(
+        edit.changes.unwrap()[&uri],
+        vec![TextEdit::new(
+            Range::new(Position::new(3, 0), Position::new(4, 0)),
+            String::new()
+        )]
+    );
+}
+
+#[test]
+fn test_duplicate_selector_diagnostics() {
+    let ls = LanguageService::default();
+    let document = create_document(
+        "file:///test.css",
+        ".a:hover, .b { color: red; }\n.b, .a:hover { color: blue; }\n.a { &:hover { color: green; } }\n@media print { .a:hover { color: black; } }\n.c { .d { } }\n.c .d { }\n.c { & .d { } }",
+    );
+
+    let diagnostics = ls
+        .get_diagnostics(document)
+        .into_iter()
+        .map(|diagnostic| (diagnostic.range, diagnostic.message))
+        .collect::<Vec<_>>();
+    let message = |selectors: &str| {
+        format!("`{selectors}` is already selected by a previous rule in the same conditions, whose declarations could be merged with these")
+    };
+    assert_eq!(
+        diagnostics,
+        vec![
+            (
+                Range::new(Position::new(1, 0), Position::new(1, 12)),
+                message(".b, .a:hover")
+            ),
+            (
+                Range::new(Position::new(5, 0), Position::new(5, 5)),
+                message(".c .d")
+            ),
+            (
+                Range::new(Position::new(6, 0), Position::new(6, 2)),
+                message(".c")
+            ),
+            (
+                Range::new(Position::new(6, 5), Position::new(6, 9)),
+                message(".c .d")
+            ),
+        ],
+        "selectors are compared once resolved, and rules in other conditions or selecting less aren't duplicates"
+    );
+
+    // The rules of SCSS control directives and the selectors with interpolations aren't known before compiling
+    let document = TextDocumentItem {
+        language_id: "scss".to_string(),
+        ..create_document(
+            "file:///test.scss",
+            "@if $dark { .a { color: white; } } @else { .a { color: black; } }\n.a { color: red; }\n.d { &:hover { color: blue; } }\n.d:hover { color: green; }\n.icon-#{$a} { color: red; }\n.icon-#{$b} { color: red; }\n@each $size in s, m { .b { color: red; } }\n@mixin m { .c { color: red; } }\n@mixin n { .c { color: red; } }",
+        )
+    };
+    let diagnostics = ls
+        .get_diagnostics(document)
+        .into_iter()
+        .filter(|diagnostic| {
+            diagnostic.code == Some(NumberOrString::String("duplicate-selector".to_string()))
+        })
+        .map(|diagnostic| (diagnostic.range, diagnostic.message))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        diagnostics,
+        vec![(
+            Range::new(Position::new(3, 0), Position::new(3, 8)),
+            message(".d:hover")
+        )]
+    );
+}
+
+#[test]
+fn test_unused_layer_diagnostics() {
+    let ls = LanguageService::default();
+    ls.open_document(create_document(
+        "file:///project/components.css",
+        "@layer components.buttons { .button { color: red; } }",
+    ));
+    let uri = Uri::from_str("file:///project/main.css").unwrap();
+    ls.open_document(create_document(
+        uri.as_str(),
+        "@layer reset, base, components, utilities;\n@layer print, legacy;\n@import url(reset.css) layer(reset);\n@layer base { .a { color: red; } }",
+    ));
+
+    let diagnostics = ls.get_diagnostics_for_uri(&uri).unwrap();
+    let unused = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.range, diagnostic.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        unused,
+        vec![
+            (
+                Range::new(Position::new(0, 32), Position::new(0, 41)),
+                "The layer `utilities` is declared but never assigned rules by a `@layer` block or an `@import`"
+            ),
+            (
+                Range::new(Position::new(1, 7), Position::new(1, 12)),
+                "The layer `print` is declared but never assigned rules by a `@layer` block or an `@import`"
+            ),
+            (
+                Range::new(Position::new(1, 14), Position::new(1, 20)),
+                "The layer `legacy` is declared but never assigned rules by a `@layer` block or an `@import`"
+            ),
+        ],
+        "Layers with rules in a sublayer or another document are used"
+    );
+    assert!(diagnostics.iter().all(|diagnostic| diagnostic.code
+        == Some(NumberOrString::String("unused-layer".to_string()))
+        && diagnostic.severity == Some(DiagnosticSeverity::WARNING)));
+
+    // The fix removes the layer from its statement, or the whole statement when none of its layers are used
+    let edit = ls.get_fix_all_edit(&uri).unwrap();
+    assert_eq!(
+        edit.changes.unwrap()[&uri],
+        vec![
+            TextEdit::new(
+                Range::new(Position::new(0, 30), Position::new(0, 41)),
+                String::new()
+            ),
+            TextEdit::new(
+                Range::new(Position::new(1, 0), Position::new(2, 0)),
+                String::new()
+            ),
+        ]
+    );
+
+    // Frameworks declaring their layers with custom directives assign them rules themselves
+    let mut settings = Settings::default();
+    settings.css.custom_directives = vec!["layer".to_string()];
+    ls.update_settings(settings);
+    assert_eq!(ls.get_diagnostics_for_uri(&uri).unwrap(), vec![]);
+}
+
+#[test]
+fn test_syntax_error_diagnostics() {
+    let ls = LanguageService::default();
+    let uri = Uri::from_str("file:///project/main.css").unwrap();
+    ls.open_document(create_document(
+        uri.as_str(),
+        ".a {\n  color: red\n  background: blue;\n}\n.b { color: blue }",
+    ));
+
+    let diagnostics = ls.get_diagnostics_for_uri(&uri).unwrap();
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic {
+            range: Range::new(Position::new(2, 12), Position::new(2, 18)),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String("syntax-error".to_string())),
+            source: Some("csslsrs".to_string()),
+            message: "Unexpected value or character.".to_string(),
+            ..Default::default()
+        }]
+    );
+
+    // The declarations of conditional rules nested in style rules aren't errors, nor hide the errors around them
+    let ranges = |text: &str| {
+        ls.open_document(create_document(uri.as_str(), text));
+        ls.get_diagnostics_for_uri(&uri)
+            .unwrap()
+            .into_iter()
+            .map(|diagnostic| diagnostic.range)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        ranges(".a { color: red\n  background: blue; }\n.b { @media (width > 600px) { color: blue; } }\n.c { color: blue }"),
+        vec![Range::new(Position::new(1, 12), Position::new(1, 18))]
+    );
+    assert_eq!(
+        ranges(".a { @media (x) { color: red; } }\n.b { color: red\n  background: blue; }"),
+        vec![Range::new(Position::new(2, 12), Position::new(2, 18))]
+    );
+    assert_eq!(
+        ranges(".a { @supports (x) { @media (y) { color: red\n  background: blue; } } }\n.b { color: blue }"),
+        vec![Range::new(Position::new(1, 12), Position::new(1, 18))]
+    );
+
+    // The constructs of SCSS aren't syntax errors
+    ls.open_document(TextDocumentItem {
+        uri: Uri::from_str("file:///project/main.scss").unwrap(),
+        language_id: "scss".to_string(),
+        version: 1,
+        text: "$gap: 4px;\n@mixin m($x) { margin: $x; }\n.a { @include m($gap); &:hover { color: red; } }"
+            .to_string(),
+    });
+    assert_eq!(
+        ls.get_diagnostics_for_uri(&Uri::from_str("file:///project/main.scss").unwrap())
+            .unwrap(),
+        vec![]
+    );
+}
+
+#[test]
+fn test_validation_diagnostics() {
+    let ls = LanguageService::default();
+    let uri = Uri::from_str("file:///project/main.css").unwrap();
+    ls.open_document(create_document(
+        uri.as_str(),
+        r#".a { colr: red; width: 10pxx; color: #ggg; border: 1px solid #12345; background: # }
+.b { margin-inline: 1rem; -webkit-line-clamp: 2; --gap: 3foo #main; color: #ff000080; composes: c from "./c.css"; }
+:export { primaryColor: red; }
+@font-face { font-family: Inter; src: local(Inter); unicode-range: U+0025-00FF, U+4??; }
+@media (color: #zz) { .c { zoom: 2 } }
+.d { @media (width > 600px) { colour: red; width: 10pxx; } }"#,
+    ));
+
+    let diagnostics = ls
+        .get_diagnostics_for_uri(&uri)
+        .unwrap()
+        .into_iter()
+        .map(|diagnostic| {
+            let Some(NumberOrString::String(code)) = diagnostic.code else {
+                panic!("diagnostics have a code");
+            };
+            (
+                diagnostic.range,
+                diagnostic.severity,
+                code,
+                diagnostic.message,
+            )
+        })
+        .collect::<Vec<_>>();
+    let range = |line, start, end| Range::new(Position::new(line, start), Position::new(line, end));
+    let hex_color = |text: &str| {
+        format!("`{text}` isn't a hex color, which has 3, 4, 6 or 8 hex digits, e.g. `#f00` or `#ff0000`")
+    };
+    assert_eq!(
+        diagnostics,
+        vec![
+            (
+                range(0, 5, 9),
+                Some(DiagnosticSeverity::WARNING),
+                "unknown-property".to_string(),
+                "Unknown property `colr`".to_string()
+            ),
+            (
+                range(0, 23, 28),
+                Some(DiagnosticSeverity::WARNING),
+                "unknown-unit".to_string(),
+                "`pxx` isn't a unit of CSS, e.g. `px`, `rem` or `%`".to_string()
+            ),
+            (
+                range(0, 37, 41),
+                Some(DiagnosticSeverity::ERROR),
+                "invalid-hex-color".to_string(),
+                hex_color("#ggg")
+            ),
+            (
+                range(0, 61, 67),
+                Some(DiagnosticSeverity::ERROR),
+                "invalid-hex-color".to_string(),
+                hex_color("#12345")
+            ),
+            (
+                range(0, 81, 82),
+                Some(DiagnosticSeverity::ERROR),
+                "invalid-hex-color".to_string(),
+                hex_color("#")
+            ),
+            (
+                range(4, 15, 18),
+                Some(DiagnosticSeverity::ERROR),
+                "invalid-hex-color".to_string(),
+                hex_color("#zz")
+            ),
+            (
+                range(5, 30, 36),
+                Some(DiagnosticSeverity::WARNING),
+                "unknown-property".to_string(),
+                "Unknown property `colour`".to_string()
+            ),
+            (
+                range(5, 50, 55),
+                Some(DiagnosticSeverity::WARNING),
+                "unknown-unit".to_string(),
+                "`pxx` isn't a unit of CSS, e.g. `px`, `rem` or `%`".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_diagnostic_rule_levels() {
+    let document = create_document(
+        "file:///project/main.css",
+        ".a { colr: red; width: 10pxx; color: #ggg; }\n.b { color: rgb(1 2 3; }",
+    );
+    let codes = |rules: &[(&str, RuleLevel)]| {
+        let ls = LanguageService::builder()
+            .settings(Settings {
+                diagnostics: DiagnosticSettings {
+                    rules: rules
+                        .iter()
+                        .map(|(code, level)| (code.to_string(), *level))
+                        .collect(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .build();
+        ls.get_diagnostics(document.clone())
+            .into_iter()
+            .map(|diagnostic| {
+                let Some(NumberOrString::String(code)) = diagnostic.code else {
+                    panic!("diagnostics have a code");
+                };
+                (code, diagnostic.severity.unwrap())
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        codes(&[]),
+        vec![
+            ("unknown-property".to_string(), DiagnosticSeverity::WARNING),
+            ("unknown-unit".to_string(), DiagnosticSeverity::WARNING),
+            ("invalid-hex-color".to_string(), DiagnosticSeverity::ERROR),
+            ("syntax-error".to_string(), DiagnosticSeverity::ERROR),
+        ]
+    );
+    assert_eq!(
+        codes(&[
+            ("unknown-property", RuleLevel::Ignore),
+            ("unknown-unit", RuleLevel::Error),
+            ("syntax-error", RuleLevel::Warning),
+            ("no-such-rule", RuleLevel::Ignore),
+        ]),
+        vec![
+            ("unknown-unit".to_string(), DiagnosticSeverity::ERROR),
+            ("invalid-hex-color".to_string(), DiagnosticSeverity::ERROR),
+            ("syntax-error".to_string(), DiagnosticSeverity::WARNING),
+        ]
+    );
+}
+
+#[derive(Default)]
+struct RecordedProgress(Vec<String>);
+
+impl ProgressReporter for RecordedProgress {
+    fn begin(&mut self, title: &str) {
+        self.0.push(format!("begin {title}"));
+    }
+
+    fn report(&mut self, percentage: u32) {
+        self.0.push(format!("report {percentage}"));
+    }
+
+    fn end(&mut self) {
+        self.0.push("end".to_string());
+    }
+}
+
+#[test]
+fn test_stream_diagnostics() {
+    let ls = LanguageService::default();
+    let document = create_document(
+        "file:///project/main.css",
+        ".a { colr: red; }\n.b { width: 10pxx; }\n.c { colour: #ggg; }",
+    );
+
+    let mut batches = Vec::new();
+    let mut progress = RecordedProgress::default();
+    ls.stream_diagnostics(
+        document.clone(),
+        &CancellationToken::default(),
+        2,
+        &mut |diagnostics| batches.push(diagnostics),
+        &mut progress,
+    )
+    .unwrap();
+
+    assert_eq!(batches.iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 2]);
+    assert_eq!(
+        progress.0,
+        vec![
+            "begin Checking the document",
+            "report 30",
+            "report 66",
+            "end"
+        ]
+    );
+    assert_eq!(
+        batches.concat(),
+        ls.get_diagnostics(document.clone()),
+        "Streamed diagnostics are the same as the ones returned at once"
+    );
+
+    // Cached diagnostics are streamed without going through the document again
+    let mut batches = Vec::new();
+    let mut progress = RecordedProgress::default();
+    ls.stream_diagnostics(
+        document.clone(),
+        &CancellationToken::default(),
+        3,
+        &mut |diagnostics| batches.push(diagnostics),
+        &mut progress,
+    )
+    .unwrap();
+
+    assert_eq!(batches.iter().map(Vec::len).collect::<Vec<_>>(), vec![3, 1]);
+    assert_eq!(progress.0, vec!["begin Checking the document", "end"]);
+
+    // Cancelled requests don't pass any batch
+    let cancellation = CancellationToken::default();
+    cancellation.cancel();
+    let mut batches = Vec::new();
+    let result = ls.stream_diagnostics(
+        create_document("file:///project/other.css", ".a { colr: red; }"),
+        &cancellation,
+        2,
+        &mut |diagnostics| batches.push(diagnostics),
+        &mut (),
+    );
+
+    assert!(result.is_err());
+    assert!(batches.is_empty());
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_parallel_diagnostics() {
+    let ls = LanguageService::default();
+    // Large enough to be checked on multiple threads
+    let text = (0..4000)
+        .map(|index| format!(".a{index} {{ colr: red; }}\n"))
+        .collect::<String>()
+        + ".a0 { color: blue; }";
+    let uri = Uri::from_str("file:///project/main.css").unwrap();
+    ls.open_document(create_document(uri.as_str(), &text));
+
+    let diagnostics = ls.get_diagnostics_for_uri(&uri).unwrap();
+
+    assert_eq!(diagnostics.len(), 4001);
+    // The diagnostics of every thread are merged in source order
+    assert!(diagnostics
+        .iter()
+        .enumerate()
+        .all(|(line, diagnostic)| diagnostic.range.start.line == line as u32));
+    // Rules checking the root still see the whole document
+    assert_eq!(
+        diagnostics.last().unwrap().code,
+        Some(NumberOrString::String("duplicate-selector".to_string()))
+    );
+}
@@ -0,0 +1,134 @@
+#![cfg(feature = "document-links")]
+
+use std::{io, str::FromStr};
+
+use csslsrs::{
+    file_system::{FileStat, FileSystemProvider, FileType},
+    service::LanguageService,
+    settings::{ScssSettings, Settings},
+};
+use lsp_types::{Position, Range, TextDocumentItem, Uri};
+
+/// The stylesheets of the project and of its load paths, keyed by URI.
+struct MemoryFileSystem(Vec<(&'static str, &'static str)>);
+
+impl FileSystemProvider for MemoryFileSystem {
+    fn read_file(&self, uri: &Uri) -> io::Result<String> {
+        self.0
+            .iter()
+            .find(|(file, _)| *file == uri.as_str())
+            .map(|(_, text)| text.to_string())
+            .ok_or_else(|| io::ErrorKind::NotFound.into())
+    }
+
+    fn read_directory(&self, _: &Uri) -> io::Result<Vec<(String, FileType)>> {
+        Ok(Vec::new())
+    }
+
+    fn stat(&self, uri: &Uri) -> io::Result<FileStat> {
+        let text = FileSystemProvider::read_file(self, uri)?;
+        Ok(FileStat {
+            file_type: FileType::File,
+            size: text.len() as u64,
+            mtime: 0,
+        })
+    }
+}
+
+#[test]
+fn test_document_links_scss_modules() {
+    let language_service = LanguageService::builder()
+        .file_system_provider(MemoryFileSystem(vec![
+            ("file:///project/src/colors/_index.scss", "$primary: red;"),
+            ("file:///lib/_theme.scss", "@mixin dark {}"),
+        ]))
+        .settings(Settings {
+            scss: ScssSettings {
+                load_paths: vec!["file:///lib".to_string()],
+            },
+            ..Default::default()
+        })
+        .build();
+    let document = TextDocumentItem {
+        uri: Uri::from_str("file:///project/src/main.scss").unwrap(),
+        language_id: "scss".to_string(),
+        version: 1,
+        text: "@use \"colors\";\n@forward \"theme\";\n@import \"missing\";".to_string(),
+    };
+
+    let links = language_service
+        .get_document_links(document)
+        .into_iter()
+        .map(|link| (link.range, link.target.unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        links,
+        vec![
+            (
+                Range::new(Position::new(0, 6), Position::new(0, 12)),
+                Uri::from_str("file:///project/src/colors/_index.scss").unwrap()
+            ),
+            (
+                Range::new(Position::new(1, 10), Position::new(1, 15)),
+                Uri::from_str("file:///lib/_theme.scss").unwrap()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_document_links_icss_imports() {
+    let language_service = LanguageService::builder()
+        .file_system_provider(MemoryFileSystem(vec![(
+            "file:///project/colors.css",
+            "@value primary: #bf4040;",
+        )]))
+        .build();
+    let document = TextDocumentItem {
+        uri: Uri::from_str("file:///project/button.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: "@value primary from './colors.css';\n.a { color: primary; }".to_string(),
+    };
+
+    let links = language_service.get_document_links(document);
+    assert_eq!(links.len(), 1);
+    assert_eq!(
+        links[0].range,
+        Range::new(Position::new(0, 21), Position::new(0, 33))
+    );
+    assert_eq!(
+        links[0].target,
+        Some(Uri::from_str("file:///project/colors.css").unwrap())
+    );
+}
+
+#[test]
+fn test_document_links_font_sources() {
+    let language_service = LanguageService::default();
+    let document = TextDocumentItem {
+        uri: Uri::from_str("file:///project/styles/fonts.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: "@font-face {\n  font-family: Inter;\n  src: local(Inter), url(\"../fonts/inter.woff2\") format(\"woff2\"),\n    url(inter.eot?#iefix), url(data:font/woff2;base64,AAAA);\n}\na { background: url(bg.png); }".to_string(),
+    };
+
+    let links = language_service
+        .get_document_links(document)
+        .into_iter()
+        .map(|link| (link.range, link.target.unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        links,
+        vec![
+            (
+                Range::new(Position::new(2, 26), Position::new(2, 46)),
+                Uri::from_str("file:///project/fonts/inter.woff2").unwrap()
+            ),
+            (
+                Range::new(Position::new(3, 8), Position::new(3, 24)),
+                Uri::from_str("file:///project/styles/inter.eot").unwrap()
+            ),
+        ]
+    );
+}
@@ -0,0 +1,270 @@
+#![cfg(feature = "document-symbols")]
+
+use std::str::FromStr;
+
+use csslsrs::service::LanguageService;
+use lsp_types::{DocumentSymbol, Position, Range, SymbolKind, TextDocumentItem, Uri};
+
+fn create_document(language_id: &str, text: &str) -> TextDocumentItem {
+    TextDocumentItem {
+        uri: Uri::from_str(&format!("file:///test.{language_id}")).unwrap(),
+        language_id: language_id.to_string(),
+        version: 1,
+        text: text.to_string(),
+    }
+}
+
+/// The name, kind and detail of symbols, with their children.
+fn outline(symbols: &[DocumentSymbol]) -> Vec<(String, SymbolKind, Option<String>, Vec<String>)> {
+    symbols
+        .iter()
+        .map(|symbol| {
+            (
+                symbol.name.clone(),
+                symbol.kind,
+                symbol.detail.clone(),
+                symbol
+                    .children
+                    .iter()
+                    .flatten()
+                    .map(|child| child.name.clone())
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_document_symbols_of_nested_rules() {
+    let ls = LanguageService::default();
+    let symbols = ls.get_document_symbols(create_document(
+        "css",
+        ".card {\n  color: red;\n  &:hover { color: blue; }\n  @media (min-width: 100px) {\n    .title { margin: 0; }\n  }\n}\n#a, #b { }",
+    ));
+
+    assert_eq!(
+        outline(&symbols),
+        vec![
+            (
+                ".card".to_string(),
+                SymbolKind::CLASS,
+                None,
+                vec![
+                    "&:hover".to_string(),
+                    "@media (min-width: 100px)".to_string()
+                ]
+            ),
+            ("#a, #b".to_string(), SymbolKind::CLASS, None, vec![]),
+        ]
+    );
+    assert_eq!(
+        symbols[0].range,
+        Range::new(Position::new(0, 0), Position::new(6, 1))
+    );
+    assert_eq!(
+        symbols[0].selection_range,
+        Range::new(Position::new(0, 0), Position::new(0, 5))
+    );
+
+    let children = symbols[0].children.as_deref().unwrap();
+    assert_eq!(
+        outline(children),
+        vec![
+            (
+                "&:hover".to_string(),
+                SymbolKind::CLASS,
+                Some(".card:hover".to_string()),
+                vec![]
+            ),
+            (
+                "@media (min-width: 100px)".to_string(),
+                SymbolKind::NAMESPACE,
+                None,
+                vec![".title".to_string()]
+            ),
+        ]
+    );
+    assert_eq!(
+        outline(children[1].children.as_deref().unwrap()),
+        vec![(
+            ".title".to_string(),
+            SymbolKind::CLASS,
+            Some(".card .title".to_string()),
+            vec![]
+        )],
+        "rules in nested at-rules are nested in the parent rule"
+    );
+}
+
+#[test]
+fn test_document_symbols_of_scss_directives() {
+    let ls = LanguageService::default();
+    let symbols = ls.get_document_symbols(create_document(
+        "scss",
+        "$theme: dark;\n.a {\n  @if $theme == dark {\n    .b { color: white; }\n  }\n}\n@mixin button($size) {\n  padding: $size;\n}",
+    ));
+
+    assert_eq!(
+        outline(&symbols),
+        vec![
+            (
+                ".a".to_string(),
+                SymbolKind::CLASS,
+                None,
+                vec!["@if $theme == dark".to_string()]
+            ),
+            (
+                "@mixin button($size)".to_string(),
+                SymbolKind::FUNCTION,
+                None,
+                vec![]
+            ),
+        ]
+    );
+
+    let directive = &symbols[0].children.as_deref().unwrap()[0];
+    assert_eq!(directive.kind, SymbolKind::NAMESPACE);
+    assert_eq!(
+        outline(directive.children.as_deref().unwrap()),
+        vec![(
+            ".b".to_string(),
+            SymbolKind::CLASS,
+            Some(".a .b".to_string()),
+            vec![]
+        )]
+    );
+}
+
+#[test]
+fn test_document_symbols_of_layers() {
+    let ls = LanguageService::default();
+    let symbols = ls.get_document_symbols(create_document(
+        "css",
+        "@layer reset, base;\n@layer base {\n  @layer buttons { .a { color: red; } }\n}",
+    ));
+
+    assert_eq!(
+        outline(&symbols),
+        vec![
+            (
+                "@layer reset, base".to_string(),
+                SymbolKind::NAMESPACE,
+                None,
+                vec![]
+            ),
+            (
+                "@layer base".to_string(),
+                SymbolKind::NAMESPACE,
+                None,
+                vec!["@layer buttons".to_string()]
+            ),
+        ]
+    );
+    assert_eq!(symbols[0].children, None);
+    assert_eq!(
+        outline(symbols[1].children.as_deref().unwrap()),
+        vec![(
+            "@layer buttons".to_string(),
+            SymbolKind::NAMESPACE,
+            Some("base.buttons".to_string()),
+            vec![".a".to_string()]
+        )],
+        "rules are grouped by the layers they are in"
+    );
+}
+
+#[test]
+fn test_document_symbols_of_scopes() {
+    let ls = LanguageService::default();
+    let symbols = ls.get_document_symbols(create_document(
+        "css",
+        "@scope (.card) to (.content) {\n  img { border: 0; }\n}",
+    ));
+
+    assert_eq!(
+        outline(&symbols),
+        vec![(
+            "@scope (.card) to (.content)".to_string(),
+            SymbolKind::NAMESPACE,
+            None,
+            vec!["img".to_string()]
+        )]
+    );
+}
+
+#[test]
+fn test_document_symbols_of_pages() {
+    let ls = LanguageService::default();
+    let symbols = ls.get_document_symbols(create_document(
+        "css",
+        "@page :first {\n  margin: 2cm;\n  @top-center { content: \"Title\"; }\n  @bottom-right { content: counter(page); }\n}",
+    ));
+
+    assert_eq!(
+        outline(&symbols),
+        vec![(
+            "@page :first".to_string(),
+            SymbolKind::NAMESPACE,
+            None,
+            vec!["@top-center".to_string(), "@bottom-right".to_string()]
+        )]
+    );
+    assert_eq!(
+        symbols[0].children.as_ref().unwrap()[0].selection_range,
+        Range::new(Position::new(2, 2), Position::new(2, 13))
+    );
+}
+
+#[test]
+fn test_document_symbols_of_view_transitions() {
+    let ls = LanguageService::default();
+    let symbols = ls.get_document_symbols(create_document(
+        "css",
+        "@view-transition {\n  navigation: auto;\n}\n.a { @starting-style { opacity: 0; } }",
+    ));
+
+    assert_eq!(
+        outline(&symbols),
+        vec![
+            (
+                "@view-transition".to_string(),
+                SymbolKind::NAMESPACE,
+                None,
+                vec![]
+            ),
+            (
+                ".a".to_string(),
+                SymbolKind::CLASS,
+                None,
+                vec!["@starting-style".to_string()]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_document_symbols_with_syntax_errors() {
+    let ls = LanguageService::default();
+    let symbols = ls.get_document_symbols(create_document(
+        "css",
+        "@supports (display: grid {\n  .grid { background: url( }\n}\n.card {\n  content: \"Hello\n  .title { color: red; }\n}",
+    ));
+
+    assert_eq!(
+        outline(&symbols),
+        vec![
+            (
+                "@supports (display: grid".to_string(),
+                SymbolKind::NAMESPACE,
+                None,
+                vec![".grid".to_string()]
+            ),
+            (
+                ".card".to_string(),
+                SymbolKind::CLASS,
+                None,
+                vec![".title".to_string()]
+            ),
+        ]
+    );
+}
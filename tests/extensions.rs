@@ -0,0 +1,82 @@
+use csslsrs::{
+    extensions::{ResolutionStep, ResolvedValueResult, SyntaxTreeParams},
+    service::LanguageService,
+    Error,
+};
+use lsp_types::{
+    Position, Range, TextDocumentIdentifier, TextDocumentItem, TextDocumentPositionParams, Uri,
+};
+use std::str::FromStr;
+
+fn open_document(language_service: &LanguageService, text: &str) -> Uri {
+    let uri = Uri::from_str("file:///test.css").unwrap();
+    language_service.open_document(TextDocumentItem {
+        uri: uri.clone(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: text.to_string(),
+    });
+    uri
+}
+
+#[test]
+fn test_syntax_tree_request() {
+    let language_service = LanguageService::default();
+    let uri = open_document(&language_service, "a { color: red; }");
+
+    let syntax_tree = language_service
+        .handle_syntax_tree(SyntaxTreeParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+        })
+        .unwrap();
+    assert!(syntax_tree.contains("CSS_QUALIFIED_RULE"));
+    assert_eq!(
+        Ok(syntax_tree),
+        language_service.debug_syntax_tree_for_uri(&uri)
+    );
+}
+
+#[test]
+fn test_resolved_value_request() {
+    let language_service = LanguageService::default();
+    let uri = open_document(
+        &language_service,
+        ":root {\n  --blue: #00f;\n  --primary: var(--blue);\n}\na {\n  border: 1px solid var(--primary);\n  color: var(--missing);\n}\n",
+    );
+
+    let resolved_value = |line, character| {
+        language_service.handle_resolved_value(TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+            position: Position::new(line, character),
+        })
+    };
+
+    assert_eq!(
+        resolved_value(5, 22),
+        Ok(Some(ResolvedValueResult {
+            property: "border".to_string(),
+            value: "1px solid var(--primary)".to_string(),
+            resolved: Some("1px solid #00f".to_string()),
+            chain: vec![
+                ResolutionStep {
+                    name: "--primary".to_string(),
+                    value: "var(--blue)".to_string(),
+                },
+                ResolutionStep {
+                    name: "--blue".to_string(),
+                    value: "#00f".to_string(),
+                },
+            ],
+            range: Range::new(Position::new(5, 2), Position::new(5, 34)),
+        }))
+    );
+    assert_eq!(
+        resolved_value(6, 4).map(|value| value.map(|value| value.resolved)),
+        Ok(Some(None))
+    );
+    assert_eq!(resolved_value(4, 0), Ok(None));
+    assert_eq!(
+        resolved_value(20, 0),
+        Err(Error::InvalidPosition(Position::new(20, 0)))
+    );
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "folding")]
+
 use csslsrs::service::LanguageService;
 use lsp_types::{FoldingRange, FoldingRangeKind, TextDocumentItem, Uri};
 use std::str::FromStr;
@@ -70,6 +72,27 @@ fn test_folding_ranges_nested_rules() {
     assert_folding_ranges(css_text, expected_sorted);
 }
 
+#[test]
+fn test_folding_ranges_page_margin_boxes() {
+    let css_text = "@page :first {\n    margin: 2cm;\n    @top-center {\n        content: \"Title\";\n    }\n}\n";
+    let expected_ranges = vec![
+        FoldingRange {
+            start_line: 0,
+            end_line: 5,
+            kind: None,
+            ..Default::default()
+        },
+        FoldingRange {
+            start_line: 2,
+            end_line: 4,
+            kind: None,
+            ..Default::default()
+        },
+    ];
+
+    assert_folding_ranges(css_text, expected_ranges);
+}
+
 #[test]
 fn test_folding_ranges_single_line_rule() {
     let css_text = "h1 { color: blue; }\n";
@@ -418,6 +441,39 @@ fn test_folding_ranges_simple_region_without_spaces() {
     assert_folding_ranges(css_text, expected_ranges);
 }
 
+#[test]
+fn test_folding_ranges_scss_line_comments() {
+    let ls = LanguageService::default();
+    let document = TextDocumentItem::new(
+        Uri::from_str("file:///test.scss").unwrap(),
+        "scss".to_string(),
+        1,
+        "// #region Buttons\n.button {\n    // Not a block {\n    color: red;\n}\n// #endregion\n"
+            .to_string(),
+    );
+
+    let mut folding_ranges = ls.get_folding_ranges(document);
+    folding_ranges.sort_by_key(|fr| fr.start_line);
+
+    assert_eq!(
+        folding_ranges,
+        vec![
+            FoldingRange {
+                start_line: 0,
+                end_line: 5,
+                kind: Some(FoldingRangeKind::Region),
+                ..Default::default()
+            },
+            FoldingRange {
+                start_line: 1,
+                end_line: 4,
+                kind: None,
+                ..Default::default()
+            },
+        ]
+    );
+}
+
 /// Utility function to assert folding ranges for a given CSS document.
 ///
 /// # Parameters
@@ -427,7 +483,7 @@ fn test_folding_ranges_simple_region_without_spaces() {
 /// # Panics
 /// Panics if the actual folding ranges do not match the expected ranges.
 fn assert_folding_ranges(text: &str, expected_ranges: Vec<FoldingRange>) {
-    let mut ls = LanguageService::default();
+    let ls = LanguageService::default();
 
     let document = TextDocumentItem::new(
         Uri::from_str("file:///test.css").unwrap(),
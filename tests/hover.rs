@@ -0,0 +1,387 @@
+#![cfg(feature = "hover")]
+
+use csslsrs::{data::DataEntry, service::LanguageService};
+use lsp_types::{HoverContents, MarkupContent, MarkupKind, Position, TextDocumentItem, Uri};
+use std::str::FromStr;
+
+fn open_scss_document(ls: &LanguageService, uri: &str, text: &str) {
+    ls.open_document(TextDocumentItem {
+        uri: Uri::from_str(uri).unwrap(),
+        language_id: "scss".to_string(),
+        version: 1,
+        text: text.to_string(),
+    });
+}
+
+fn hover_markdown(ls: &LanguageService, uri: &str, position: Position) -> Option<String> {
+    let hover = ls
+        .get_hover_for_uri(&Uri::from_str(uri).unwrap(), position)
+        .unwrap()?;
+    match hover.contents {
+        HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }) => Some(value),
+        contents => panic!("Unexpected hover contents: {contents:?}"),
+    }
+}
+
+#[test]
+fn test_hover_scss_variable() {
+    let ls = LanguageService::default();
+    open_scss_document(
+        &ls,
+        "file:///project/main.scss",
+        "$gap: 4px !default;\n.a { margin: $gap; }",
+    );
+
+    assert_eq!(
+        hover_markdown(&ls, "file:///project/main.scss", Position::new(1, 15)),
+        Some("```scss\n$gap: 4px\n```".to_string())
+    );
+    assert_eq!(
+        hover_markdown(&ls, "file:///project/main.scss", Position::new(0, 1)),
+        Some("```scss\n$gap: 4px\n```".to_string())
+    );
+    assert_eq!(
+        hover_markdown(&ls, "file:///project/main.scss", Position::new(1, 3)),
+        None
+    );
+}
+
+#[test]
+fn test_hover_scss_variable_in_module() {
+    let ls = LanguageService::default();
+    open_scss_document(
+        &ls,
+        "file:///project/theme/_colors.scss",
+        "$primary: #ff0000;",
+    );
+    open_scss_document(
+        &ls,
+        "file:///project/main.scss",
+        "@use \"theme/colors\" as c;\n.a { color: c.$primary; }",
+    );
+
+    assert_eq!(
+        hover_markdown(&ls, "file:///project/main.scss", Position::new(1, 16)),
+        Some("```scss\nc.$primary: #ff0000\n```".to_string())
+    );
+}
+
+#[test]
+fn test_hover_scss_mixin_and_function() {
+    let ls = LanguageService::default();
+    open_scss_document(
+        &ls,
+        "file:///project/main.scss",
+        "@mixin button($size, $color: red) {\n  padding: $size;\n}\n@function double($n) {\n  @return $n * 2;\n}\n.a {\n  @include button(4px);\n  width: double(2px);\n}",
+    );
+
+    assert_eq!(
+        hover_markdown(&ls, "file:///project/main.scss", Position::new(7, 12)),
+        Some("```scss\n@mixin button($size, $color: red)\n```".to_string())
+    );
+    assert_eq!(
+        hover_markdown(&ls, "file:///project/main.scss", Position::new(8, 10)),
+        Some("```scss\n@function double($n)\n```".to_string())
+    );
+    assert_eq!(
+        hover_markdown(&ls, "file:///project/main.scss", Position::new(0, 8)),
+        Some("```scss\n@mixin button($size, $color: red)\n```".to_string())
+    );
+}
+
+#[test]
+fn test_hover_scss_mixin_in_module() {
+    let ls = LanguageService::default();
+    open_scss_document(
+        &ls,
+        "file:///project/_buttons.scss",
+        "@mixin primary {\n  color: red;\n}",
+    );
+    open_scss_document(
+        &ls,
+        "file:///project/main.scss",
+        "@use \"buttons\";\n.a { @include buttons.primary; }",
+    );
+
+    assert_eq!(
+        hover_markdown(&ls, "file:///project/main.scss", Position::new(1, 24)),
+        Some("```scss\n@mixin buttons.primary\n```".to_string())
+    );
+}
+
+#[test]
+fn test_hover_scss_forwarded_function() {
+    let ls = LanguageService::default();
+    open_scss_document(
+        &ls,
+        "file:///project/lib/_list.scss",
+        "@function append($list, $value) {\n  @return $list;\n}",
+    );
+    open_scss_document(
+        &ls,
+        "file:///project/lib/_index.scss",
+        "@forward \"list\" as list-*;",
+    );
+    open_scss_document(
+        &ls,
+        "file:///project/main.scss",
+        "@use \"lib\";\n.a { width: lib.list-append(1, 2); }",
+    );
+
+    assert_eq!(
+        hover_markdown(&ls, "file:///project/main.scss", Position::new(1, 20)),
+        Some("```scss\n@function lib.list-append($list, $value)\n```".to_string())
+    );
+}
+
+#[test]
+fn test_hover_icss_value() {
+    let ls = LanguageService::default();
+    for (uri, text) in [
+        ("file:///project/colors.css", "@value primary: #bf4040;"),
+        (
+            "file:///project/button.css",
+            "@value primary as brand from \"./colors.css\";\n.a { color: brand; }",
+        ),
+    ] {
+        ls.open_document(TextDocumentItem {
+            uri: Uri::from_str(uri).unwrap(),
+            language_id: "css".to_string(),
+            version: 1,
+            text: text.to_string(),
+        });
+    }
+
+    assert_eq!(
+        hover_markdown(&ls, "file:///project/button.css", Position::new(1, 13)),
+        Some("```css\nbrand: #bf4040\n```".to_string())
+    );
+}
+
+#[test]
+fn test_hover_layers() {
+    let ls = LanguageService::default();
+    ls.open_document(TextDocumentItem {
+        uri: Uri::from_str("file:///project/main.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: "@layer reset, base;\n@layer base { .a, .b { color: red; } }\n.c { color: red; }"
+            .to_string(),
+    });
+
+    assert_eq!(
+        hover_markdown(&ls, "file:///project/main.css", Position::new(0, 15)),
+        Some(
+            "```css\n@layer base\n```\nLayer order in this stylesheet: `reset` < `base`"
+                .to_string()
+        )
+    );
+    assert_eq!(
+        hover_markdown(&ls, "file:///project/main.css", Position::new(1, 19)),
+        Some("```css\n.b\n```\n[Selector Specificity](https://developer.mozilla.org/docs/Web/CSS/Specificity): (0, 1, 0)\n\nIn the layer `base`".to_string())
+    );
+    assert_eq!(
+        hover_markdown(&ls, "file:///project/main.css", Position::new(2, 1)),
+        Some("```css\n.c\n```\n[Selector Specificity](https://developer.mozilla.org/docs/Web/CSS/Specificity): (0, 1, 0)".to_string()),
+        "rules outside of layers have no layer"
+    );
+}
+
+#[test]
+fn test_hover_selectors() {
+    let ls = LanguageService::default();
+    ls.open_document(TextDocumentItem {
+        uri: Uri::from_str("file:///project/main.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: ".card, #main {\n  .title { &:hover, > span { } }\n  @media (width > 600px) { a:is(.x) { } }\n}"
+            .to_string(),
+    });
+
+    let hover = |line, character| {
+        let markdown = hover_markdown(
+            &ls,
+            "file:///project/main.css",
+            Position::new(line, character),
+        )
+        .unwrap();
+        let (selector, specificity) = markdown.split_once("\n```\n").unwrap();
+        (
+            selector.trim_start_matches("```css\n").to_string(),
+            specificity
+                .trim_start_matches("[Selector Specificity](https://developer.mozilla.org/docs/Web/CSS/Specificity): ")
+                .to_string(),
+        )
+    };
+    let expected =
+        |selector: &str, specificity: &str| (selector.to_string(), specificity.to_string());
+    assert_eq!(hover(0, 9), expected("#main", "(1, 0, 0)"));
+    // Nested selectors are resolved against their parents, with the specificity of the most specific one
+    assert_eq!(
+        hover(1, 12),
+        expected(":is(.card, #main) .title:hover", "(1, 2, 0)")
+    );
+    assert_eq!(
+        hover(1, 22),
+        expected(":is(.card, #main) .title > span", "(1, 1, 1)")
+    );
+    assert_eq!(
+        hover(2, 27),
+        expected(":is(.card, #main) a:is(.x)", "(1, 1, 1)"),
+        "at-rules between the rules are skipped"
+    );
+
+    let range = ls
+        .get_hover_for_uri(
+            &Uri::from_str("file:///project/main.css").unwrap(),
+            Position::new(1, 13),
+        )
+        .unwrap()
+        .unwrap()
+        .range;
+    assert_eq!(
+        range,
+        Some(lsp_types::Range::new(
+            Position::new(1, 11),
+            Position::new(1, 18)
+        )),
+        "only the hovered selector of the list is shown"
+    );
+}
+
+#[test]
+fn test_hover_at_rules() {
+    let ls = LanguageService::default();
+    ls.open_document(TextDocumentItem {
+        uri: Uri::from_str("file:///project/main.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: "@scope (.card) to (.content) {\n  img { content: \"@scope\"; }\n}".to_string(),
+    });
+
+    let hover = ls
+        .get_hover_for_uri(
+            &Uri::from_str("file:///project/main.css").unwrap(),
+            Position::new(0, 3),
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        hover.range,
+        Some(lsp_types::Range::new(
+            Position::new(0, 0),
+            Position::new(0, 6)
+        ))
+    );
+    assert!(
+        matches!(hover.contents, HoverContents::Markup(MarkupContent { value, .. }) if value.starts_with("Applies styles to the elements of a DOM subtree"))
+    );
+    assert_eq!(
+        hover_markdown(&ls, "file:///project/main.css", Position::new(1, 19)),
+        None,
+        "at-rules in strings have no hover"
+    );
+}
+
+#[test]
+fn test_hover_view_transitions() {
+    let ls = LanguageService::default();
+    ls.open_document(TextDocumentItem {
+        uri: Uri::from_str("file:///project/main.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: "@view-transition { navigation: auto; }\n.a { @starting-style { opacity: 0; } }"
+            .to_string(),
+    });
+
+    assert!(
+        hover_markdown(&ls, "file:///project/main.css", Position::new(0, 5)).is_some_and(
+            |markdown| markdown.starts_with("Opts the pages of a site in to view transitions")
+        )
+    );
+    assert!(
+        hover_markdown(&ls, "file:///project/main.css", Position::new(1, 9))
+            .is_some_and(|markdown| markdown.starts_with("Defines the starting values"))
+    );
+}
+
+#[test]
+fn test_hover_properties() {
+    let ls = LanguageService::default();
+    ls.open_document(TextDocumentItem {
+        uri: Uri::from_str("file:///project/main.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: ".a { display: flex; --display: grid; }\n@font-face { font-family: Inter; src: url(inter.woff2); }\n@view-transition { navigation: auto; }"
+            .to_string(),
+    });
+
+    let hover = ls
+        .get_hover_for_uri(
+            &Uri::from_str("file:///project/main.css").unwrap(),
+            Position::new(0, 12),
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        hover.range,
+        Some(lsp_types::Range::new(
+            Position::new(0, 5),
+            Position::new(0, 12)
+        )),
+        "the name is found at its end, before the colon"
+    );
+    let HoverContents::Markup(MarkupContent { value, .. }) = hover.contents else {
+        panic!("the hover should be markdown");
+    };
+    assert!(value.starts_with("Sets whether an element is treated as a block or inline box"));
+    assert!(value.contains("Syntax: `[ <display-outside> || <display-inside> ]"));
+    assert!(value.contains("Initial value: `inline`"));
+    assert!(value.contains("Firefox 1"));
+    assert!(value.ends_with("[MDN Reference](https://developer.mozilla.org/docs/Web/CSS/display)"));
+
+    assert_eq!(
+        hover_markdown(&ls, "file:///project/main.css", Position::new(0, 16)),
+        None,
+        "values have no hover"
+    );
+    assert_eq!(
+        hover_markdown(&ls, "file:///project/main.css", Position::new(0, 23)),
+        None,
+        "custom properties have no documentation"
+    );
+
+    // Descriptors are documented by their at-rule
+    let descriptor = |line, character| {
+        hover_markdown(
+            &ls,
+            "file:///project/main.css",
+            Position::new(line, character),
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        descriptor(1, 35),
+        csslsrs::data::bundled()
+            .descriptor("@font-face", "src")
+            .unwrap()
+            .documentation()
+    );
+    assert_ne!(
+        descriptor(1, 15),
+        csslsrs::data::bundled()
+            .property("font-family")
+            .unwrap()
+            .documentation()
+    );
+    assert_eq!(
+        descriptor(2, 22),
+        csslsrs::data::bundled()
+            .descriptor("@view-transition", "navigation")
+            .unwrap()
+            .documentation()
+    );
+}
@@ -0,0 +1,195 @@
+#![cfg(feature = "inlay-hints")]
+
+use csslsrs::{
+    converters::{PositionEncoding, WideEncoding},
+    service::LanguageService,
+    Error,
+};
+use lsp_types::{
+    InlayHint, InlayHintLabel, InlayHintTooltip, Position, Range, TextDocumentContentChangeEvent,
+    TextDocumentItem, Uri,
+};
+use std::str::FromStr;
+
+#[test]
+fn test_inlay_hints_resolved_chain() {
+    let ls = LanguageService::default();
+
+    let hints = get_inlay_hints(
+        &ls,
+        ":root { --a: 10px; --b: var(--a); }\nbody { width: var(--b); }",
+    );
+
+    assert_eq!(hints.len(), 2, "Unexpected number of inlay hints");
+    assert_hint(&hints[0], Position::new(0, 32), "= 10px");
+    assert_hint(&hints[1], Position::new(1, 22), "= 10px");
+}
+
+#[test]
+fn test_inlay_hints_fallback() {
+    let ls = LanguageService::default();
+
+    let hints = get_inlay_hints(&ls, "body { color: var(--missing, red); }");
+
+    assert_eq!(hints.len(), 1, "Unexpected number of inlay hints");
+    assert_hint(&hints[0], Position::new(0, 33), "= red");
+}
+
+#[test]
+fn test_inlay_hints_unresolvable() {
+    let ls = LanguageService::default();
+
+    let hints = get_inlay_hints(
+        &ls,
+        ":root { --x: var(--y); --y: var(--x); }\nbody { color: var(--x); width: var(--unknown); }",
+    );
+
+    assert!(
+        hints.is_empty(),
+        "Cyclic or unknown references have no hint"
+    );
+}
+
+#[test]
+fn test_inlay_hints_outside_range() {
+    let ls = LanguageService::default();
+
+    let document = create_document(":root { --a: 1px; }\nbody { width: var(--a); }");
+    let hints = ls.get_inlay_hints(
+        document,
+        Range {
+            start: Position::new(0, 0),
+            end: Position::new(0, 19),
+        },
+    );
+
+    assert!(
+        hints.unwrap().is_empty(),
+        "Hints outside the range are not returned"
+    );
+}
+
+#[test]
+fn test_inlay_hints_invalid_range() {
+    let ls = LanguageService::default();
+
+    let document = create_document(":root { --a: 1px; }\nbody { width: var(--a); }");
+    let range = Range {
+        start: Position::new(0, 0),
+        end: Position::new(10, 0),
+    };
+
+    assert_eq!(
+        ls.get_inlay_hints(document, range).err(),
+        Some(Error::InvalidRange(range))
+    );
+}
+
+#[test]
+fn test_inlay_hints_position_encodings() {
+    let text = ":root { --a: 1px; }\n/* 🎨 */ body { width: var(--a); }\n";
+    let range = Range {
+        start: Position::new(0, 0),
+        end: Position::new(2, 0),
+    };
+
+    // The emoji is 4 bytes in UTF-8, 2 code units in UTF-16 and a single code point in UTF-32
+    for (encoding, character) in [
+        (PositionEncoding::Utf8, 33),
+        (PositionEncoding::Wide(WideEncoding::Utf16), 31),
+        (PositionEncoding::Wide(WideEncoding::Utf32), 30),
+    ] {
+        let ls = LanguageService::new(encoding);
+        let hints = ls.get_inlay_hints(create_document(text), range).unwrap();
+
+        assert_eq!(hints.len(), 1, "Unexpected number of inlay hints");
+        assert_hint(&hints[0], Position::new(1, character), "= 1px");
+    }
+}
+
+#[test]
+fn test_inlay_hints_document_encoding() {
+    let ls = LanguageService::default();
+    let range = Range {
+        start: Position::new(0, 0),
+        end: Position::new(2, 0),
+    };
+
+    let document = create_document(":root { --a: 1px; }\n/* 🎨 */ body { width: var(--a); }\n");
+    ls.open_document_with_encoding(document.clone(), Some(PositionEncoding::Utf8));
+
+    let hints = ls.get_inlay_hints_for_uri(&document.uri, range).unwrap();
+    assert_hint(&hints[0], Position::new(1, 33), "= 1px");
+
+    // Changes use the encoding of the document too, and keep it
+    ls.change_document(
+        &document.uri,
+        2,
+        vec![TextDocumentContentChangeEvent {
+            range: Some(Range::new(Position::new(1, 7), Position::new(1, 8))),
+            range_length: None,
+            text: String::new(),
+        }],
+    )
+    .unwrap();
+    let hints = ls.get_inlay_hints_for_uri(&document.uri, range).unwrap();
+    assert_hint(&hints[0], Position::new(1, 32), "= 1px");
+
+    // Other documents use the encoding of the service
+    ls.open_document(document.clone());
+    let hints = ls.get_inlay_hints_for_uri(&document.uri, range).unwrap();
+    assert_hint(&hints[0], Position::new(1, 31), "= 1px");
+}
+
+#[test]
+fn test_resolve_inlay_hint() {
+    let ls = LanguageService::default();
+
+    let hints = get_inlay_hints(
+        &ls,
+        ":root { --a: 10px; --b: var(--a); }\nbody { width: var(--b); }",
+    );
+    assert!(hints[1].tooltip.is_none(), "Tooltips are computed lazily");
+
+    let resolved = ls.resolve_inlay_hint(hints[1].clone());
+
+    match resolved.tooltip {
+        Some(InlayHintTooltip::MarkupContent(content)) => assert_eq!(
+            content.value, "```css\n--b: var(--a);\n--a: 10px;\n```",
+            "Unexpected tooltip"
+        ),
+        _ => panic!("Expected a markdown tooltip"),
+    }
+}
+
+fn create_document(text: &str) -> TextDocumentItem {
+    TextDocumentItem {
+        uri: Uri::from_str("file:///test.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: text.to_string(),
+    }
+}
+
+fn get_inlay_hints(ls: &LanguageService, text: &str) -> Vec<InlayHint> {
+    let last_line = text.lines().count().saturating_sub(1) as u32;
+    let last_line_length = text.lines().next_back().unwrap_or_default().len() as u32;
+
+    ls.get_inlay_hints(
+        create_document(text),
+        Range {
+            start: Position::new(0, 0),
+            end: Position::new(last_line, last_line_length),
+        },
+    )
+    .unwrap()
+}
+
+fn assert_hint(hint: &InlayHint, position: Position, label: &str) {
+    assert_eq!(hint.position, position, "Unexpected hint position");
+
+    match &hint.label {
+        InlayHintLabel::String(hint_label) => assert_eq!(hint_label, label),
+        _ => panic!("Expected a string label"),
+    }
+}
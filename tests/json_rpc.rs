@@ -0,0 +1,117 @@
+#![cfg(all(feature = "colors", feature = "folding"))]
+
+use csslsrs::{
+    json_rpc::{error_codes, JsonRpcAdapter},
+    service::LanguageService,
+};
+use serde_json::{json, Value};
+
+fn open_document(adapter: &JsonRpcAdapter, text: &str) {
+    let response = adapter.handle_message(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": {
+            "textDocument": { "uri": "file:///test.css", "languageId": "css", "version": 1, "text": text }
+        }
+    }));
+    assert_eq!(response, None);
+}
+
+fn request(adapter: &JsonRpcAdapter, id: i64, method: &str, params: Value) -> Value {
+    adapter
+        .handle_message(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+        .unwrap()
+}
+
+#[test]
+fn test_json_rpc_requests() {
+    let adapter = JsonRpcAdapter::new(LanguageService::default());
+    open_document(&adapter, "a {\n  color: red;\n}\n");
+
+    let response = request(
+        &adapter,
+        1,
+        "textDocument/foldingRange",
+        json!({ "textDocument": { "uri": "file:///test.css" } }),
+    );
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["result"][0]["startLine"], 0);
+    assert_eq!(response["result"][0]["endLine"], 2);
+
+    adapter.handle_message(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didChange",
+        "params": {
+            "textDocument": { "uri": "file:///test.css", "version": 2 },
+            "contentChanges": [{
+                "range": { "start": { "line": 1, "character": 9 }, "end": { "line": 1, "character": 12 } },
+                "text": "blue"
+            }]
+        }
+    }));
+
+    let response = request(
+        &adapter,
+        2,
+        "textDocument/documentColor",
+        json!({ "textDocument": { "uri": "file:///test.css" } }),
+    );
+    assert_eq!(response["result"][0]["color"]["blue"], 1.0);
+    assert_eq!(response["result"][0]["range"]["end"]["character"], 13);
+}
+
+#[test]
+fn test_json_rpc_errors() {
+    let adapter = JsonRpcAdapter::new(LanguageService::default());
+
+    let response = request(&adapter, 1, "textDocument/unknown", json!({}));
+    assert_eq!(response["error"]["code"], error_codes::METHOD_NOT_FOUND);
+
+    let response = request(
+        &adapter,
+        2,
+        "textDocument/documentColor",
+        json!({ "textDocument": { "uri": "file:///missing.css" } }),
+    );
+    assert_eq!(response["id"], 2);
+    assert_eq!(response["error"]["code"], error_codes::INVALID_PARAMS);
+
+    let response = request(&adapter, 3, "textDocument/documentColor", json!({}));
+    assert_eq!(response["error"]["code"], error_codes::INVALID_PARAMS);
+
+    let response: Value = serde_json::from_slice(&adapter.handle_bytes(b"{").unwrap()).unwrap();
+    assert_eq!(response["id"], Value::Null);
+    assert_eq!(response["error"]["code"], error_codes::PARSE_ERROR);
+
+    // Notifications and responses never get a response, even when they are invalid
+    assert_eq!(
+        adapter.handle_bytes(br#"{ "jsonrpc": "2.0", "method": "textDocument/didClose" }"#),
+        None
+    );
+    assert_eq!(
+        adapter.handle_message(json!({ "jsonrpc": "2.0", "id": 4, "result": null })),
+        None
+    );
+}
+
+#[test]
+fn test_json_rpc_configuration() {
+    let adapter = JsonRpcAdapter::new(LanguageService::default());
+    open_document(&adapter, "a { color: red; background: blue; }");
+
+    assert_eq!(
+        adapter.handle_notification(
+            "workspace/didChangeConfiguration",
+            json!({ "settings": { "colors": { "maxDocumentColors": 1 } } }),
+        ),
+        Ok(())
+    );
+
+    let response = request(
+        &adapter,
+        1,
+        "textDocument/documentColor",
+        json!({ "textDocument": { "uri": "file:///test.css" } }),
+    );
+    assert_eq!(response["result"].as_array().unwrap().len(), 1);
+}
@@ -0,0 +1,199 @@
+#![cfg(feature = "rename")]
+
+use csslsrs::{error::Error, service::LanguageService};
+use lsp_types::{Position, PrepareRenameResponse, Range, TextDocumentItem, TextEdit, Uri};
+use std::str::FromStr;
+
+fn open_document(ls: &LanguageService, uri: &str, text: &str) {
+    ls.open_document(TextDocumentItem {
+        uri: Uri::from_str(uri).unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: text.to_string(),
+    });
+}
+
+#[test]
+fn test_rename_custom_property() {
+    let ls = LanguageService::default();
+    let uri = Uri::from_str("file:///project/main.css").unwrap();
+    open_document(
+        &ls,
+        uri.as_str(),
+        ":root { --primary: red; --primary-dark: darkred; }\n.a { color: var(--primary); background: var(--x, var(--primary)); }",
+    );
+
+    let edit = |position| ls.rename_for_uri(&uri, position, "--accent");
+    let expected = vec![
+        TextEdit::new(
+            Range::new(Position::new(0, 8), Position::new(0, 17)),
+            "--accent".to_string(),
+        ),
+        TextEdit::new(
+            Range::new(Position::new(1, 16), Position::new(1, 25)),
+            "--accent".to_string(),
+        ),
+        TextEdit::new(
+            Range::new(Position::new(1, 53), Position::new(1, 62)),
+            "--accent".to_string(),
+        ),
+    ];
+
+    // From the declaration and from a reference, at the end of the name
+    for position in [Position::new(0, 10), Position::new(1, 25)] {
+        let changes = edit(position).unwrap().changes.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[&uri], expected);
+    }
+}
+
+#[test]
+fn test_rename_refused() {
+    let ls = LanguageService::default();
+    let uri = Uri::from_str("file:///project/main.css").unwrap();
+    open_document(&ls, uri.as_str(), ".a { --gap: 4px; color: red; }");
+
+    assert!(matches!(
+        ls.rename_for_uri(&uri, Position::new(0, 26), "--accent"),
+        Err(Error::InvalidRename(_))
+    ));
+    assert!(matches!(
+        ls.rename_for_uri(&uri, Position::new(0, 13), "--accent"),
+        Err(Error::InvalidRename(_))
+    ));
+    for new_name in ["gap", "--", "--a b", ""] {
+        assert!(
+            matches!(
+                ls.rename_for_uri(&uri, Position::new(0, 7), new_name),
+                Err(Error::InvalidRename(_))
+            ),
+            "`{new_name}` isn't a custom property"
+        );
+    }
+    assert_eq!(
+        ls.rename_for_uri(&uri, Position::new(5, 0), "--accent"),
+        Err(Error::InvalidPosition(Position::new(5, 0)))
+    );
+}
+
+#[test]
+fn test_rename_in_scss() {
+    let ls = LanguageService::default();
+    let document = TextDocumentItem {
+        uri: Uri::from_str("file:///project/main.scss").unwrap(),
+        language_id: "scss".to_string(),
+        version: 1,
+        text: "$gap: 4px;\n.a {\n  --gap: #{$gap};\n  .b { margin: var(--gap); }\n}".to_string(),
+    };
+
+    let edit = ls
+        .rename(document.clone(), Position::new(3, 22), "--spacing")
+        .unwrap();
+    let ranges = edit.changes.unwrap()[&document.uri]
+        .iter()
+        .map(|edit| edit.range)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ranges,
+        vec![
+            Range::new(Position::new(2, 2), Position::new(2, 7)),
+            Range::new(Position::new(3, 19), Position::new(3, 24)),
+        ]
+    );
+}
+
+#[test]
+fn test_rename_keyframes_and_classes() {
+    let ls = LanguageService::default();
+    let uri = Uri::from_str("file:///project/main.css").unwrap();
+    open_document(
+        &ls,
+        uri.as_str(),
+        "@keyframes spin { to { rotate: 1turn; } }\n.spinner { animation: 1s linear infinite spin; }\n.spinner:hover, .a .spinner { animation-name: spin, fade; }",
+    );
+
+    let ranges = |position, new_name| {
+        ls.rename_for_uri(&uri, position, new_name)
+            .unwrap()
+            .changes
+            .unwrap()[&uri]
+            .iter()
+            .map(|edit| (edit.range, edit.new_text.clone()))
+            .collect::<Vec<_>>()
+    };
+    let edit = |line, start, end, new_text: &str| {
+        (
+            Range::new(Position::new(line, start), Position::new(line, end)),
+            new_text.to_string(),
+        )
+    };
+    assert_eq!(
+        ranges(Position::new(1, 42), "rotate"),
+        vec![
+            edit(0, 11, 15, "rotate"),
+            edit(1, 41, 45, "rotate"),
+            edit(2, 46, 50, "rotate"),
+        ]
+    );
+    assert_eq!(
+        ranges(Position::new(2, 3), "loader"),
+        vec![
+            edit(1, 1, 8, "loader"),
+            edit(2, 1, 8, "loader"),
+            edit(2, 20, 27, "loader"),
+        ]
+    );
+
+    for (position, new_name) in [
+        (Position::new(0, 12), "none"),
+        (Position::new(0, 12), "ease-in"),
+        (Position::new(0, 12), "1turn"),
+        (Position::new(1, 3), "2col"),
+        (Position::new(1, 3), "-2col"),
+        (Position::new(1, 3), "a.b"),
+    ] {
+        assert!(
+            matches!(
+                ls.rename_for_uri(&uri, position, new_name),
+                Err(Error::InvalidRename(_))
+            ),
+            "`{new_name}` is invalid"
+        );
+    }
+}
+
+#[test]
+fn test_prepare_rename() {
+    let ls = LanguageService::default();
+    let uri = Uri::from_str("file:///project/main.css").unwrap();
+    open_document(
+        &ls,
+        uri.as_str(),
+        ":root { --primary: red; }\n@keyframes \"fade in\" { to { opacity: 1; } }\n.button { color: var(--primary); }",
+    );
+
+    let prepare = |line, character| {
+        ls.prepare_rename_for_uri(&uri, Position::new(line, character))
+            .unwrap()
+    };
+    let expected = |line, start, end, placeholder: &str| {
+        Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: Range::new(Position::new(line, start), Position::new(line, end)),
+            placeholder: placeholder.to_string(),
+        })
+    };
+    assert_eq!(prepare(0, 8), expected(0, 8, 17, "--primary"));
+    assert_eq!(prepare(2, 25), expected(2, 21, 30, "--primary"));
+    assert_eq!(
+        prepare(1, 13),
+        expected(1, 12, 19, "fade in"),
+        "the quotes of the name aren't renamed"
+    );
+    assert_eq!(prepare(2, 3), expected(2, 1, 7, "button"));
+    assert_eq!(prepare(2, 12), None, "properties can't be renamed");
+    assert_eq!(prepare(1, 1), None, "at-rules can't be renamed");
+    assert_eq!(
+        ls.prepare_rename_for_uri(&uri, Position::new(9, 0)),
+        Err(Error::InvalidPosition(Position::new(9, 0)))
+    );
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "semantic-tokens")]
+
+use csslsrs::service::LanguageService;
+use lsp_types::{SemanticToken, TextDocumentItem, Uri};
+use std::str::FromStr;
+
+const VARIABLE: u32 = 0;
+const FUNCTION: u32 = 1;
+const CLASS: u32 = 2;
+const OPERATOR: u32 = 3;
+
+const NO_MODIFIERS: u32 = 0;
+const DECLARATION: u32 = 1;
+
+#[test]
+fn test_semantic_tokens_custom_properties() {
+    assert_semantic_tokens(
+        "css",
+        "a { --x: 1; b: var(--x); }",
+        vec![
+            token(0, 4, 3, VARIABLE, DECLARATION),
+            token(0, 15, 3, VARIABLE, NO_MODIFIERS),
+        ],
+    );
+}
+
+#[test]
+fn test_semantic_tokens_scss_ignored_in_css() {
+    assert_semantic_tokens("css", "%p { width: 50%; }\n@mixin m {}", vec![]);
+}
+
+#[test]
+fn test_semantic_tokens_scss_variables() {
+    assert_semantic_tokens(
+        "scss",
+        "$a: 1px;\n.b { width: $a; }",
+        vec![
+            token(0, 0, 2, VARIABLE, DECLARATION),
+            token(1, 12, 2, VARIABLE, NO_MODIFIERS),
+        ],
+    );
+}
+
+#[test]
+fn test_semantic_tokens_scss_mixins() {
+    assert_semantic_tokens(
+        "scss",
+        "@mixin m($x) {}\n@include m(1);",
+        vec![
+            token(0, 7, 1, FUNCTION, DECLARATION),
+            token(0, 2, 2, VARIABLE, NO_MODIFIERS),
+            token(1, 9, 1, FUNCTION, NO_MODIFIERS),
+        ],
+    );
+}
+
+#[test]
+fn test_semantic_tokens_scss_placeholders_and_interpolation() {
+    assert_semantic_tokens(
+        "scss",
+        "%p { #{$v}: d; }\n.a { @extend %p; }",
+        vec![
+            token(0, 0, 2, CLASS, DECLARATION),
+            token(0, 5, 2, OPERATOR, NO_MODIFIERS),
+            token(0, 2, 2, VARIABLE, NO_MODIFIERS),
+            token(0, 2, 1, OPERATOR, NO_MODIFIERS),
+            token(1, 13, 2, CLASS, NO_MODIFIERS),
+        ],
+    );
+}
+
+#[test]
+fn test_semantic_tokens_scss_comments_and_strings() {
+    assert_semantic_tokens("scss", "// $a: 1;\n/* $b */ a { content: \"$c\"; }", vec![]);
+}
+
+fn token(
+    delta_line: u32,
+    delta_start: u32,
+    length: u32,
+    token_type: u32,
+    token_modifiers_bitset: u32,
+) -> SemanticToken {
+    SemanticToken {
+        delta_line,
+        delta_start,
+        length,
+        token_type,
+        token_modifiers_bitset,
+    }
+}
+
+fn assert_semantic_tokens(language_id: &str, text: &str, expected_tokens: Vec<SemanticToken>) {
+    let ls = LanguageService::default();
+
+    let document = TextDocumentItem {
+        uri: Uri::from_str(&format!("file:///test.{language_id}")).unwrap(),
+        language_id: language_id.to_string(),
+        version: 1,
+        text: text.to_string(),
+    };
+
+    let semantic_tokens = ls.get_semantic_tokens(document);
+
+    assert_eq!(
+        semantic_tokens.data, expected_tokens,
+        "Unexpected semantic tokens"
+    );
+}
@@ -0,0 +1,424 @@
+#![cfg(all(feature = "server", feature = "diagnostics", feature = "folding"))]
+
+use std::{
+    io::{BufReader, Cursor},
+    net::{TcpListener, TcpStream},
+    thread,
+};
+
+use csslsrs::{
+    json_rpc::error_codes,
+    server::{read_message, run, serve, write_message, Server, StreamTransport},
+};
+use serde_json::{json, Value};
+
+fn initialize(server: &mut Server) -> Value {
+    let mut messages = server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "initialize",
+        "params": { "capabilities": {} }
+    }));
+    assert_eq!(messages.len(), 1);
+    messages.remove(0)
+}
+
+#[test]
+fn test_server_lifecycle() {
+    let mut server = Server::new();
+
+    let messages = server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "textDocument/foldingRange",
+        "params": { "textDocument": { "uri": "file:///test.css" } }
+    }));
+    assert_eq!(
+        messages[0]["error"]["code"],
+        error_codes::SERVER_NOT_INITIALIZED
+    );
+
+    let response = initialize(&mut server);
+    assert_eq!(response["result"]["serverInfo"]["name"], "csslsrs");
+    assert_eq!(
+        response["result"]["capabilities"]["foldingRangeProvider"],
+        true
+    );
+    assert_eq!(
+        initialize(&mut server)["error"]["code"],
+        error_codes::INVALID_REQUEST
+    );
+
+    server.handle_message(json!({ "jsonrpc": "2.0", "id": 2, "method": "shutdown" }));
+    let messages = server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "id": 3,
+        "method": "textDocument/foldingRange",
+        "params": { "textDocument": { "uri": "file:///test.css" } }
+    }));
+    assert_eq!(messages[0]["error"]["code"], error_codes::INVALID_REQUEST);
+
+    assert_eq!(server.exit_code(), None);
+    server.handle_message(json!({ "jsonrpc": "2.0", "method": "exit" }));
+    assert_eq!(server.exit_code(), Some(0));
+}
+
+#[test]
+fn test_server_publishes_diagnostics() {
+    let mut server = Server::new();
+    initialize(&mut server);
+
+    let messages = server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": {
+            "textDocument": { "uri": "file:///test.css", "languageId": "css", "version": 1, "text": "a { color: red" }
+        }
+    }));
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0]["method"], "textDocument/publishDiagnostics");
+    assert_eq!(messages[0]["params"]["uri"], "file:///test.css");
+    assert_eq!(messages[0]["params"]["version"], 1);
+
+    let messages = server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didClose",
+        "params": { "textDocument": { "uri": "file:///test.css" } }
+    }));
+    assert_eq!(messages[0]["params"]["diagnostics"], json!([]));
+}
+
+#[test]
+fn test_server_over_stdio() {
+    let mut input = Vec::new();
+    for message in [
+        json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize", "params": { "capabilities": {} } }),
+        json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "shutdown" }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ] {
+        write_message(&mut input, &message).unwrap();
+    }
+
+    let mut output = Vec::new();
+    assert_eq!(run(&mut Cursor::new(input), &mut output).unwrap(), 0);
+
+    let mut output = Cursor::new(output);
+    let initialize = read_message(&mut output).unwrap().unwrap();
+    assert_eq!(initialize["id"], 0);
+    let shutdown = read_message(&mut output).unwrap().unwrap();
+    assert_eq!(shutdown["id"], 1);
+    assert_eq!(read_message(&mut output).unwrap(), None);
+}
+
+#[test]
+fn test_server_answers_invalid_json() {
+    let mut input = Vec::new();
+    write_message(
+        &mut input,
+        &json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize", "params": { "capabilities": {} } }),
+    )
+    .unwrap();
+    input.extend_from_slice(b"Content-Length: 10\r\n\r\n{\"jsonrpc\"");
+    for message in [
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "shutdown" }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ] {
+        write_message(&mut input, &message).unwrap();
+    }
+
+    let mut output = Vec::new();
+    assert_eq!(run(&mut Cursor::new(input), &mut output).unwrap(), 0);
+
+    let mut output = Cursor::new(output);
+    assert_eq!(read_message(&mut output).unwrap().unwrap()["id"], 0);
+    let parse_error = read_message(&mut output).unwrap().unwrap();
+    assert_eq!(parse_error["id"], Value::Null);
+    assert_eq!(parse_error["error"]["code"], error_codes::PARSE_ERROR);
+    assert_eq!(read_message(&mut output).unwrap().unwrap()["id"], 1);
+
+    // A malformed header ends the connection
+    let mut output = Vec::new();
+    assert!(run(
+        &mut Cursor::new(b"Content-Length: ten\r\n\r\n{}".to_vec()),
+        &mut output
+    )
+    .is_err());
+    assert!(output.is_empty());
+}
+
+#[test]
+fn test_server_over_tcp() {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        serve(&mut StreamTransport::new(
+            BufReader::new(stream.try_clone().unwrap()),
+            stream,
+        ))
+        .unwrap()
+    });
+
+    let mut client = TcpStream::connect(address).unwrap();
+    let mut reader = BufReader::new(client.try_clone().unwrap());
+    write_message(
+        &mut client,
+        &json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize", "params": { "capabilities": {} } }),
+    )
+    .unwrap();
+    assert_eq!(read_message(&mut reader).unwrap().unwrap()["id"], 0);
+
+    // The connection is closed without shutting the server down
+    drop(reader);
+    drop(client);
+    assert_eq!(server.join().unwrap(), 1);
+}
+
+#[test]
+fn test_server_configuration() {
+    let mut server = Server::new();
+    let response = server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "initialize",
+        "params": {
+            "capabilities": {
+                "textDocument": { "foldingRange": { "dynamicRegistration": true } },
+                "workspace": { "configuration": true }
+            },
+            "initializationOptions": { "colors": { "maxDocumentColors": 10 } }
+        }
+    }));
+    // Folding ranges are registered once the client is initialized
+    assert_eq!(
+        response[0]["result"]["capabilities"]["foldingRangeProvider"],
+        Value::Null
+    );
+
+    let messages =
+        server.handle_message(json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }));
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0]["method"], "client/registerCapability");
+    assert_eq!(
+        messages[0]["params"]["registrations"][0]["method"],
+        "textDocument/foldingRange"
+    );
+    assert_eq!(messages[1]["method"], "workspace/configuration");
+    assert_eq!(messages[1]["params"]["items"][0]["section"], "css");
+
+    let messages = server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "id": messages[1]["id"],
+        "result": [{ "features": { "folding": false } }]
+    }));
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0]["method"], "client/unregisterCapability");
+    assert_eq!(
+        messages[0]["params"]["unregisterations"][0]["id"],
+        "textDocument/foldingRange"
+    );
+
+    // The configuration is pulled again when it changes
+    let messages = server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "method": "workspace/didChangeConfiguration",
+        "params": { "settings": null }
+    }));
+    assert_eq!(messages[0]["method"], "workspace/configuration");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_server_execute_command() {
+    let folder = std::env::temp_dir().join(format!("csslsrs-server-{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    std::fs::write(folder.join("theme.css"), ":root { --brand: red; }").unwrap();
+    let folder_uri = format!("file://{}", folder.display());
+
+    let mut server = Server::new();
+    let response = server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "initialize",
+        "params": {
+            "capabilities": {},
+            "workspaceFolders": [{ "uri": folder_uri, "name": "workspace" }]
+        }
+    }));
+    assert!(
+        response[0]["result"]["capabilities"]["executeCommandProvider"]["commands"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("csslsrs.reindexWorkspace"))
+    );
+
+    // Without arguments, the workspace folders are indexed
+    let messages = server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "workspace/executeCommand",
+        "params": { "command": "csslsrs.reindexWorkspace" }
+    }));
+    assert_eq!(messages[0]["result"], true);
+
+    let uri = format!("{folder_uri}/button.css");
+    server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": {
+            "textDocument": { "uri": uri, "languageId": "css", "version": 1, "text": "a { color: var(--brand); }" }
+        }
+    }));
+    let messages = server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "workspace/executeCommand",
+        "params": {
+            "command": "csslsrs.showReferences",
+            "arguments": [uri, { "line": 0, "character": 17 }]
+        }
+    }));
+    let uris = messages[0]["result"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|location| location["uri"].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        uris,
+        vec![json!(uri), json!(format!("{folder_uri}/theme.css"))]
+    );
+
+    // Nothing to fix, so no edit is sent to the client
+    let messages = server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "id": 3,
+        "method": "workspace/executeCommand",
+        "params": { "command": "csslsrs.applyFixAll", "arguments": [uri] }
+    }));
+    assert_eq!(
+        messages,
+        vec![json!({ "jsonrpc": "2.0", "id": 3, "result": null })]
+    );
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn test_server_workspace_diagnostics() {
+    let mut server = Server::new();
+    let response = server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "initialize",
+        "params": {
+            "capabilities": {
+                "textDocument": { "diagnostic": {} },
+                "workspace": { "diagnostics": { "refreshSupport": true } }
+            }
+        }
+    }));
+    assert_eq!(
+        response[0]["result"]["capabilities"]["diagnosticProvider"]["workspaceDiagnostics"],
+        true
+    );
+
+    server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": {
+            "textDocument": { "uri": "file:///a.css", "languageId": "css", "version": 1, "text": "a {}" }
+        }
+    }));
+    let messages = server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "workspace/diagnostic",
+        "params": { "previousResultIds": [] }
+    }));
+    let item = &messages[0]["result"]["items"][0];
+    assert_eq!(item["uri"], "file:///a.css");
+    assert_eq!(item["kind"], "full");
+    assert_eq!(item["version"], 1);
+
+    // Clients are asked to pull diagnostics again when the settings change
+    let messages = server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "method": "workspace/didChangeConfiguration",
+        "params": { "settings": { "css": { "colors": { "maxDocumentColors": 3 } } } }
+    }));
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0]["method"], "workspace/diagnostic/refresh");
+}
+
+#[test]
+fn test_server_work_done_progress() {
+    let mut server = Server::new();
+    server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "initialize",
+        "params": {
+            "capabilities": {
+                "textDocument": { "diagnostic": {} },
+                "window": { "workDoneProgress": true }
+            }
+        }
+    }));
+    server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": {
+            "textDocument": { "uri": "file:///a.css", "languageId": "css", "version": 1, "text": "a {}" }
+        }
+    }));
+
+    // Without a token from the client, the server creates one
+    let mut sent = Vec::new();
+    server.handle_message_with(
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "workspace/diagnostic",
+            "params": { "previousResultIds": [] }
+        }),
+        &mut |message| sent.push(message),
+    );
+    assert_eq!(sent.len(), 5);
+    assert_eq!(sent[0]["method"], "window/workDoneProgress/create");
+    let token = sent[0]["params"]["token"].clone();
+    assert_eq!(
+        sent[1]["params"],
+        json!({
+            "token": token,
+            "value": { "kind": "begin", "title": "Checking stylesheets", "cancellable": false, "percentage": 0 }
+        })
+    );
+    assert_eq!(
+        sent[2]["params"],
+        json!({
+            "token": token,
+            "value": { "kind": "report", "message": "1/1 files", "percentage": 100 }
+        })
+    );
+    assert_eq!(
+        sent[3]["params"],
+        json!({ "token": token, "value": { "kind": "end" } })
+    );
+    assert_eq!(sent[4]["id"], 1);
+    assert_eq!(sent[4]["result"]["items"][0]["uri"], "file:///a.css");
+
+    // The token of the request is used when it has one
+    let messages = server.handle_message(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "workspace/diagnostic",
+        "params": { "previousResultIds": [], "workDoneToken": "client-token" }
+    }));
+    assert_eq!(messages.len(), 4);
+    assert!(messages[..3]
+        .iter()
+        .all(|message| message["method"] == "$/progress"
+            && message["params"]["token"] == "client-token"));
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "signature-help")]
+
+use csslsrs::service::LanguageService;
+use lsp_types::{ParameterLabel, Position, TextDocumentItem, Uri};
+use std::str::FromStr;
+
+#[test]
+fn test_signature_help_scss_mixin() {
+    let ls = LanguageService::default();
+    ls.open_document(TextDocumentItem {
+        uri: Uri::from_str("file:///project/main.scss").unwrap(),
+        language_id: "scss".to_string(),
+        version: 1,
+        text: "@mixin button($size, $color: red) {}\n.a { @include button(4px, blue); }"
+            .to_string(),
+    });
+    let signature_help = |position| {
+        ls.get_signature_help_for_uri(
+            &Uri::from_str("file:///project/main.scss").unwrap(),
+            position,
+        )
+        .unwrap()
+    };
+
+    let help = signature_help(Position::new(1, 27)).unwrap();
+    assert_eq!(help.active_parameter, Some(1));
+    assert_eq!(
+        help.signatures[0].label,
+        "@mixin button($size, $color: red)"
+    );
+    assert_eq!(
+        help.signatures[0]
+            .parameters
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|parameter| parameter.label.clone())
+            .collect::<Vec<_>>(),
+        vec![
+            ParameterLabel::LabelOffsets([14, 19]),
+            ParameterLabel::LabelOffsets([21, 32]),
+        ]
+    );
+    assert_eq!(
+        signature_help(Position::new(1, 22))
+            .unwrap()
+            .active_parameter,
+        Some(0)
+    );
+    assert_eq!(signature_help(Position::new(1, 3)), None);
+}
@@ -0,0 +1,87 @@
+#![cfg(all(feature = "colors", feature = "semantic-tokens"))]
+
+use csslsrs::{service::LanguageService, virtual_document::OffsetMapping};
+use lsp_types::{Position, Range, TextDocumentItem, Uri};
+use std::str::FromStr;
+
+const HOST: &str = "const Button = styled.a`\n  color: red;\n  ${size}: 10px;\n`;";
+
+/// Register the CSS of `HOST` as a virtual document, with the `${size}` expression replaced by a placeholder.
+fn open_virtual_document(ls: &LanguageService) -> Uri {
+    let host = TextDocumentItem {
+        uri: Uri::from_str("file:///button.js").unwrap(),
+        language_id: "javascript".to_string(),
+        version: 1,
+        text: HOST.to_string(),
+    };
+
+    let document = TextDocumentItem {
+        uri: Uri::from_str("embedded:///button.js/0.css").unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: "a {\n  color: red;\n  --p: 10px;\n}".to_string(),
+    };
+
+    let mappings = vec![
+        // From the start of the template to the expression
+        OffsetMapping {
+            virtual_start: 3,
+            host_start: 24,
+            length: 17,
+        },
+        // From the end of the expression to the end of the template
+        OffsetMapping {
+            virtual_start: 23,
+            host_start: 48,
+            length: 8,
+        },
+    ];
+
+    ls.open_virtual_document(&host, document.clone(), mappings);
+
+    document.uri
+}
+
+#[test]
+fn test_virtual_document_colors() {
+    let ls = LanguageService::default();
+    let uri = open_virtual_document(&ls);
+
+    let colors = ls
+        .in_host_coordinates(&uri, |ls, document, _| ls.get_document_colors(document))
+        .unwrap();
+
+    assert_eq!(colors.len(), 1, "Unexpected number of colors");
+    assert_eq!(
+        colors[0].range,
+        Range::new(Position::new(1, 9), Position::new(1, 12)),
+        "Colors should be in host coordinates"
+    );
+}
+
+#[test]
+fn test_virtual_document_unmapped_results() {
+    let ls = LanguageService::default();
+    let uri = open_virtual_document(&ls);
+
+    let tokens = ls
+        .in_host_coordinates(&uri, |ls, document, _| ls.get_semantic_tokens(document))
+        .unwrap();
+
+    assert!(
+        tokens.data.is_empty(),
+        "The custom property is a placeholder for an expression, which isn't mapped to the host"
+    );
+}
+
+#[test]
+fn test_virtual_document_closed() {
+    let ls = LanguageService::default();
+    let uri = open_virtual_document(&ls);
+
+    ls.close_document(&uri);
+
+    assert!(ls
+        .in_host_coordinates(&uri, |ls, document, _| ls.get_document_colors(document))
+        .is_none());
+}
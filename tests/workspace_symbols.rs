@@ -0,0 +1,194 @@
+#![cfg(feature = "workspace-symbols")]
+
+use csslsrs::service::LanguageService;
+use lsp_types::{OneOf, Position, Range, SymbolKind, TextDocumentItem, Uri, WorkspaceSymbol};
+use std::str::FromStr;
+
+#[test]
+fn test_workspace_symbols() {
+    let ls = LanguageService::default();
+
+    ls.open_document(create_document(
+        "file:///a.css",
+        ":root { --primary: red; }\n.button { color: var(--primary); }",
+    ));
+    ls.open_document(create_document(
+        "file:///b.css",
+        "@keyframes spin { to { opacity: 1; } }\n#header { animation: spin 1s; }",
+    ));
+
+    let symbols = ls.get_workspace_symbols("");
+
+    assert_eq!(symbols.len(), 4, "Unexpected number of symbols");
+    assert_symbol(
+        &symbols[0],
+        "--primary",
+        SymbolKind::VARIABLE,
+        "file:///a.css",
+        Range::new(Position::new(0, 8), Position::new(0, 17)),
+    );
+    assert_symbol(
+        &symbols[1],
+        ".button",
+        SymbolKind::CLASS,
+        "file:///a.css",
+        Range::new(Position::new(1, 1), Position::new(1, 7)),
+    );
+    assert_symbol(
+        &symbols[2],
+        "@keyframes spin",
+        SymbolKind::CLASS,
+        "file:///b.css",
+        Range::new(Position::new(0, 11), Position::new(0, 15)),
+    );
+    assert_symbol(
+        &symbols[3],
+        "#header",
+        SymbolKind::CLASS,
+        "file:///b.css",
+        Range::new(Position::new(1, 1), Position::new(1, 7)),
+    );
+}
+
+#[test]
+fn test_workspace_symbols_query() {
+    let ls = LanguageService::default();
+
+    ls.open_document(create_document(
+        "file:///a.css",
+        ".Button { color: red; }\n.link { color: blue; }",
+    ));
+
+    let symbols = ls.get_workspace_symbols("butt");
+
+    assert_eq!(symbols.len(), 1, "Unexpected number of symbols");
+    assert_eq!(symbols[0].name, ".Button");
+}
+
+#[test]
+fn test_workspace_symbols_updated_documents() {
+    let ls = LanguageService::default();
+
+    ls.open_document(create_document("file:///a.css", ".a { color: red; }"));
+    assert_eq!(ls.get_workspace_symbols("").len(), 1);
+
+    ls.open_document(create_document("file:///a.css", ".b { color: red; }"));
+    let symbols = ls.get_workspace_symbols("");
+    assert_eq!(symbols.len(), 1);
+    assert_eq!(symbols[0].name, ".b");
+
+    ls.close_document(&Uri::from_str("file:///a.css").unwrap());
+    assert!(
+        ls.get_workspace_symbols("").is_empty(),
+        "Closed documents are removed from the index"
+    );
+}
+
+#[test]
+fn test_workspace_symbols_for_uri() {
+    let ls = LanguageService::default();
+
+    ls.open_document(create_document("file:///a.css", ".a { color: red; }"));
+    ls.open_document(create_document("file:///b.css", ".b { color: red; }"));
+
+    let symbols = ls
+        .get_workspace_symbols_for_uri(&Uri::from_str("file:///b.css").unwrap())
+        .unwrap();
+
+    assert_eq!(symbols.len(), 1, "Unexpected number of symbols");
+    assert_symbol(
+        &symbols[0],
+        ".b",
+        SymbolKind::CLASS,
+        "file:///b.css",
+        Range::new(Position::new(0, 1), Position::new(0, 2)),
+    );
+
+    assert!(ls
+        .get_workspace_symbols_for_uri(&Uri::from_str("file:///c.css").unwrap())
+        .is_err());
+}
+
+#[test]
+fn test_workspace_symbols_scss() {
+    let ls = LanguageService::default();
+
+    ls.open_document(TextDocumentItem {
+        uri: Uri::from_str("file:///a.scss").unwrap(),
+        language_id: "scss".to_string(),
+        version: 1,
+        text: "$gap: 4px;\n@mixin card { padding: $gap; }\n%base { margin: 0; }\n.a { @include card; @extend %base; }"
+            .to_string(),
+    });
+
+    let symbols = ls.get_workspace_symbols("");
+
+    assert_eq!(
+        symbols
+            .iter()
+            .map(|symbol| (symbol.name.as_str(), symbol.kind))
+            .collect::<Vec<_>>(),
+        vec![
+            ("$gap", SymbolKind::VARIABLE),
+            ("@mixin card", SymbolKind::FUNCTION),
+            ("%base", SymbolKind::CLASS),
+            (".a", SymbolKind::CLASS),
+        ]
+    );
+    assert_symbol(
+        &symbols[1],
+        "@mixin card",
+        SymbolKind::FUNCTION,
+        "file:///a.scss",
+        Range::new(Position::new(1, 7), Position::new(1, 11)),
+    );
+}
+
+#[test]
+fn test_workspace_symbols_less() {
+    let ls = LanguageService::default();
+
+    ls.open_document(TextDocumentItem {
+        uri: Uri::from_str("file:///a.less").unwrap(),
+        language_id: "less".to_string(),
+        version: 1,
+        text: "@gap: 4px;\n.card(@size) { padding: @gap; }\n.a { .card(2px); }".to_string(),
+    });
+
+    let symbols = ls.get_workspace_symbols("");
+
+    assert_eq!(
+        symbols
+            .iter()
+            .map(|symbol| (symbol.name.as_str(), symbol.kind))
+            .collect::<Vec<_>>(),
+        vec![
+            ("@gap", SymbolKind::VARIABLE),
+            (".card()", SymbolKind::FUNCTION),
+            (".card", SymbolKind::CLASS),
+            (".a", SymbolKind::CLASS),
+        ]
+    );
+}
+
+fn create_document(uri: &str, text: &str) -> TextDocumentItem {
+    TextDocumentItem {
+        uri: Uri::from_str(uri).unwrap(),
+        language_id: "css".to_string(),
+        version: 1,
+        text: text.to_string(),
+    }
+}
+
+fn assert_symbol(symbol: &WorkspaceSymbol, name: &str, kind: SymbolKind, uri: &str, range: Range) {
+    assert_eq!(symbol.name, name, "Unexpected symbol name");
+    assert_eq!(symbol.kind, kind, "Unexpected symbol kind");
+
+    match &symbol.location {
+        OneOf::Left(location) => {
+            assert_eq!(location.uri.as_str(), uri, "Unexpected symbol document");
+            assert_eq!(location.range, range, "Unexpected symbol range");
+        }
+        OneOf::Right(_) => panic!("Expected a location with a range"),
+    }
+}